@@ -14,9 +14,20 @@
 
 //! The top-level application type.
 
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
 use crate::clipboard::Clipboard;
+use crate::error::Error;
 use crate::platform::application as platform;
 
+/// A function called with the files the platform asks us to open.
+type OpenFilesHandler = Box<dyn FnMut(Vec<PathBuf>)>;
+
+thread_local! {
+    static OPEN_FILES_HANDLER: RefCell<Option<OpenFilesHandler>> = RefCell::new(None);
+}
+
 //TODO: we may want to make the user create an instance of this (Application::global()?)
 //but for now I'd like to keep changes minimal.
 /// The top level application object.
@@ -27,6 +38,21 @@ impl Application {
         Application(platform::Application::new())
     }
 
+    /// Create the application for windows that are embedded in another
+    /// application, with [`WindowBuilder::set_parent`].
+    ///
+    /// The other application owns the event loop, and keeps running it as
+    /// it would without us; our windows are driven by it. Don't call [`run`]
+    /// on the result. On macOS the host's application delegate is left in
+    /// place, so requests to open files aren't seen.
+    ///
+    /// [`WindowBuilder::set_parent`]: struct.WindowBuilder.html#method.set_parent
+    /// [`run`]: #method.run
+    #[cfg(feature = "raw-win-handle")]
+    pub fn embedded() -> Application {
+        Application(platform::Application::embedded())
+    }
+
     /// Start the runloop.
     ///
     /// This will block the current thread until the program has finished executing.
@@ -34,6 +60,41 @@ impl Application {
         self.0.run()
     }
 
+    /// Set the function that is called when the platform asks the
+    /// application to open files, such as when the user double-clicks a
+    /// document associated with it.
+    ///
+    /// On macOS the request comes from the system, and may arrive at any
+    /// time. Elsewhere a document is opened by launching the application
+    /// with its path as an argument; see [`open_files_from_args`].
+    ///
+    /// [`open_files_from_args`]: #method.open_files_from_args
+    pub fn set_open_files_handler(&mut self, handler: impl FnMut(Vec<PathBuf>) + 'static) {
+        OPEN_FILES_HANDLER.with(|h| *h.borrow_mut() = Some(Box::new(handler)));
+    }
+
+    /// Pass the command line arguments that name existing files to the
+    /// handler set with [`set_open_files_handler`], as if the platform had
+    /// asked to open them.
+    ///
+    /// This does nothing on macOS, where the system sends these requests
+    /// itself.
+    ///
+    /// [`set_open_files_handler`]: #method.set_open_files_handler
+    pub fn open_files_from_args(&mut self) {
+        #[cfg(any(not(target_os = "macos"), feature = "use_gtk"))]
+        {
+            let paths: Vec<PathBuf> = std::env::args_os()
+                .skip(1)
+                .map(PathBuf::from)
+                .filter(|path| path.is_file())
+                .collect();
+            if !paths.is_empty() {
+                open_files(paths);
+            }
+        }
+    }
+
     /// Terminate the application.
     pub fn quit() {
         platform::Application::quit()
@@ -65,4 +126,84 @@ impl Application {
     pub fn get_locale() -> String {
         platform::Application::get_locale()
     }
+
+    /// Returns the user's preferred text scale factor, where `1.0` is the
+    /// default size.
+    ///
+    /// This reflects accessibility settings such as "Make text bigger" on
+    /// Windows or the GNOME text scaling factor. Changes are reported to
+    /// each window through [`WinHandler::text_scale_changed`].
+    ///
+    /// On macOS this is always `1.0`; AppKit has no system-wide text size
+    /// preference that applies to other apps.
+    ///
+    /// [`WinHandler::text_scale_changed`]: trait.WinHandler.html#method.text_scale_changed
+    pub fn get_text_scale() -> f64 {
+        platform::Application::get_text_scale()
+    }
+
+    /// Returns `true` if the user has asked the system to minimize motion,
+    /// in which case animations should be replaced by instant changes.
+    ///
+    /// Changes are reported to each window through
+    /// [`WinHandler::reduce_motion_changed`], where the platform supports it.
+    ///
+    /// [`WinHandler::reduce_motion_changed`]: trait.WinHandler.html#method.reduce_motion_changed
+    pub fn get_reduce_motion() -> bool {
+        platform::Application::get_reduce_motion()
+    }
+
+    /// Open `url` in the default browser, or in the application registered
+    /// for its scheme (such as `mailto:`).
+    pub fn open_url(url: &str) -> Result<(), Error> {
+        platform::Application::open_url(url)
+    }
+
+    /// Open a file or folder with its default application.
+    pub fn open_path(path: impl AsRef<Path>) -> Result<(), Error> {
+        platform::Application::open_path(path.as_ref())
+    }
+
+    /// Show a file or folder in the system file manager, selected.
+    ///
+    /// Where the file manager can't select items, this opens the containing
+    /// folder instead.
+    pub fn reveal_path(path: impl AsRef<Path>) -> Result<(), Error> {
+        platform::Application::reveal_path(path.as_ref())
+    }
+
+    /// Record that a document was opened, so that the system can offer it
+    /// again later.
+    ///
+    /// The document is added to the Recent Items on macOS, to the jump list
+    /// on Windows, and to the recently used files on GTK.
+    pub fn add_recent_document(path: impl AsRef<Path>) {
+        platform::Application::add_recent_document(path.as_ref())
+    }
+
+    /// The documents recorded with [`add_recent_document`], most recent
+    /// first.
+    ///
+    /// Windows does not share the jump list with the app, so there this only
+    /// returns the documents added since the app was launched.
+    ///
+    /// [`add_recent_document`]: #method.add_recent_document
+    pub fn recent_documents() -> Vec<PathBuf> {
+        platform::Application::recent_documents()
+    }
+
+    /// Forget all of this app's recent documents.
+    pub fn clear_recent_documents() {
+        platform::Application::clear_recent_documents()
+    }
+}
+
+/// Pass `paths` to the handler set with [`set_open_files_handler`].
+///
+/// [`set_open_files_handler`]: struct.Application.html#method.set_open_files_handler
+pub(crate) fn open_files(paths: Vec<PathBuf>) {
+    OPEN_FILES_HANDLER.with(|handler| match handler.borrow_mut().as_mut() {
+        Some(handler) => handler(paths),
+        None => log::info!("no handler for opening {:?}", paths),
+    })
 }
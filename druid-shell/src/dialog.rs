@@ -31,15 +31,25 @@ pub enum FileDialogType {
 }
 
 /// Options for file dialogs.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct FileDialogOptions {
     pub show_hidden: bool,
     pub allowed_types: Option<Vec<FileSpec>>,
     pub default_type: Option<FileSpec>,
+    /// Whether an open dialog picks a directory instead of a file.
+    pub select_directories: bool,
+    /// The file name a save dialog starts with.
+    pub default_name: Option<String>,
+    /// The directory the dialog starts in.
+    pub starting_directory: Option<PathBuf>,
+    /// Whether the user can make new directories from the dialog; `None`
+    /// leaves it to the platform.
+    pub can_create_directories: Option<bool>,
+    /// Whether a save dialog asks before replacing a file that exists.
+    pub confirm_overwrite: bool,
     // we don't want a library user to be able to construct this type directly
     __non_exhaustive: (),
     // multi selection
-    // select directories
 }
 
 /// A description of a filetype, for specifiying allowed types in a file dialog.
@@ -68,12 +78,36 @@ pub struct FileSpec {
 }
 
 impl FileInfo {
+    /// Create a `FileInfo` for the file at `path`.
+    ///
+    /// This is useful when a file is opened by some means other than a file
+    /// dialog, such as from the recent documents.
+    pub fn new(path: impl Into<PathBuf>) -> FileInfo {
+        FileInfo { path: path.into() }
+    }
+
     /// The file's path.
     pub fn path(&self) -> &Path {
         &self.path
     }
 }
 
+impl Default for FileDialogOptions {
+    fn default() -> Self {
+        FileDialogOptions {
+            show_hidden: false,
+            allowed_types: None,
+            default_type: None,
+            select_directories: false,
+            default_name: None,
+            starting_directory: None,
+            can_create_directories: None,
+            confirm_overwrite: true,
+            __non_exhaustive: (),
+        }
+    }
+}
+
 impl FileDialogOptions {
     /// Create a new set of options.
     pub fn new() -> FileDialogOptions {
@@ -99,6 +133,45 @@ impl FileDialogOptions {
         self.default_type = Some(default_type);
         self
     }
+
+    /// Pick a directory instead of a file, in an open dialog.
+    pub fn select_directories(mut self) -> Self {
+        self.select_directories = true;
+        self
+    }
+
+    /// Set the file name a save dialog starts with, such as `Untitled.txt`.
+    pub fn default_name(mut self, name: impl Into<String>) -> Self {
+        self.default_name = Some(name.into());
+        self
+    }
+
+    /// Set the directory the dialog starts in.
+    pub fn starting_directory(mut self, path: impl Into<PathBuf>) -> Self {
+        self.starting_directory = Some(path.into());
+        self
+    }
+
+    /// Set whether the user can make new directories from the dialog.
+    ///
+    /// # Windows
+    ///
+    /// The Windows dialogs always let the user make directories.
+    pub fn can_create_directories(mut self, allowed: bool) -> Self {
+        self.can_create_directories = Some(allowed);
+        self
+    }
+
+    /// Set whether a save dialog asks before replacing a file that exists;
+    /// it does by default.
+    ///
+    /// # macOS
+    ///
+    /// The macOS save panel always asks.
+    pub fn confirm_overwrite(mut self, confirm: bool) -> Self {
+        self.confirm_overwrite = confirm;
+        self
+    }
 }
 
 impl FileSpec {
@@ -0,0 +1,52 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Data carried by drag and drop operations.
+
+use std::path::PathBuf;
+
+use crate::clipboard::ClipboardFormat;
+
+/// The contents of a drag and drop operation.
+#[derive(Debug, Clone, Default)]
+pub struct DragData {
+    /// Paths of dragged files.
+    pub files: Vec<PathBuf>,
+    /// Dragged URLs that do not refer to local files.
+    pub urls: Vec<String>,
+    /// Dragged plain text.
+    pub text: Option<String>,
+    /// Application-specific data, offered alongside the other contents
+    /// when dragging out of the app.
+    pub formats: Vec<ClipboardFormat>,
+}
+
+/// What the drop target did with data dragged out of the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropAction {
+    /// The data was copied; the source should leave it in place.
+    Copy,
+    /// The data was moved; the source should remove it.
+    Move,
+}
+
+impl DragData {
+    /// Returns `true` if nothing is being dragged.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+            && self.urls.is_empty()
+            && self.text.is_none()
+            && self.formats.is_empty()
+    }
+}
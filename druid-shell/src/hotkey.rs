@@ -132,6 +132,131 @@ impl HotKey {
     }
 }
 
+/// The names of the modifier keys, used by [`HotKey::display_text`].
+///
+/// The defaults are in English; apps can replace them with localized names.
+/// They are not used on macOS, where modifiers are shown as glyphs.
+///
+/// [`HotKey::display_text`]: struct.HotKey.html#method.display_text
+#[derive(Debug, Clone)]
+pub struct ModifierNames {
+    pub ctrl: String,
+    pub alt: String,
+    pub shift: String,
+    /// The Windows or Super key.
+    pub meta: String,
+}
+
+impl Default for ModifierNames {
+    fn default() -> Self {
+        ModifierNames {
+            ctrl: "Ctrl".into(),
+            alt: "Alt".into(),
+            shift: "Shift".into(),
+            meta: if cfg!(target_os = "windows") {
+                "Win".into()
+            } else {
+                "Super".into()
+            },
+        }
+    }
+}
+
+impl HotKey {
+    /// A description of this hotkey for display, such as in a menu, following
+    /// the platform's conventions.
+    ///
+    /// On macOS this uses the standard glyphs, as in `⇧⌘S`. Elsewhere the
+    /// modifier names from `names` are joined with `+`, as in `Ctrl+Shift+S`.
+    pub fn display_text(&self, names: &ModifierNames) -> String {
+        let mods: KeyModifiers = self.mods.into();
+        let key = match self.key {
+            KeyCompare::Code(code) => key_code_display_text(code),
+            KeyCompare::Text(text) => text.to_uppercase(),
+        };
+        if cfg!(target_os = "macos") {
+            let mut result = String::new();
+            for (on, glyph) in &[
+                (mods.ctrl, '⌃'),
+                (mods.alt, '⌥'),
+                (mods.shift, '⇧'),
+                (mods.meta, '⌘'),
+            ] {
+                if *on {
+                    result.push(*glyph);
+                }
+            }
+            result.push_str(&key);
+            result
+        } else {
+            let mut parts = Vec::new();
+            for (on, name) in &[
+                (mods.ctrl, &names.ctrl),
+                (mods.meta, &names.meta),
+                (mods.alt, &names.alt),
+                (mods.shift, &names.shift),
+            ] {
+                if *on {
+                    parts.push(name.as_str());
+                }
+            }
+            parts.push(&key);
+            parts.join("+")
+        }
+    }
+}
+
+/// The name or glyph shown for a non-modifier key.
+fn key_code_display_text(code: KeyCode) -> String {
+    let mac = cfg!(target_os = "macos");
+    let text = match code {
+        KeyCode::Escape if mac => "⎋",
+        KeyCode::Escape => "Esc",
+        KeyCode::Backspace if mac => "⌫",
+        KeyCode::Backspace => "Backspace",
+        KeyCode::Delete if mac => "⌦",
+        KeyCode::Delete => "Del",
+        KeyCode::Tab if mac => "⇥",
+        KeyCode::Tab => "Tab",
+        KeyCode::Return | KeyCode::NumpadEnter if mac => "↩",
+        KeyCode::Return | KeyCode::NumpadEnter => "Enter",
+        KeyCode::Home if mac => "↖",
+        KeyCode::End if mac => "↘",
+        KeyCode::PageUp if mac => "⇞",
+        KeyCode::PageUp => "PgUp",
+        KeyCode::PageDown if mac => "⇟",
+        KeyCode::PageDown => "PgDn",
+        KeyCode::ArrowUp if mac => "↑",
+        KeyCode::ArrowUp => "Up",
+        KeyCode::ArrowDown if mac => "↓",
+        KeyCode::ArrowDown => "Down",
+        KeyCode::ArrowLeft if mac => "←",
+        KeyCode::ArrowLeft => "Left",
+        KeyCode::ArrowRight if mac => "→",
+        KeyCode::ArrowRight => "Right",
+        KeyCode::Backtick => "`",
+        KeyCode::Minus | KeyCode::NumpadSubtract => "-",
+        KeyCode::Equals | KeyCode::NumpadEquals => "=",
+        KeyCode::LeftBracket => "[",
+        KeyCode::RightBracket => "]",
+        KeyCode::Semicolon => ";",
+        KeyCode::Quote => "'",
+        KeyCode::Backslash => "\\",
+        KeyCode::Comma => ",",
+        KeyCode::Period | KeyCode::NumpadDecimal => ".",
+        KeyCode::Slash | KeyCode::NumpadDivide => "/",
+        KeyCode::NumpadAdd => "+",
+        KeyCode::NumpadMultiply => "*",
+        _ => {
+            // `Key0`, `KeyA`, `F1`, `Numpad0` and friends
+            let name = format!("{:?}", code);
+            let name = name.trim_start_matches("Numpad");
+            return name.strip_prefix("Key").unwrap_or(name).to_string();
+        }
+    };
+    text.to_string()
+}
+
 /// A platform-agnostic representation of keyboard modifiers, for command handling.
 ///
 /// This does one thing: it allows specifying hotkeys that use the Command key
@@ -268,3 +393,30 @@ impl From<&'static str> for KeyCompare {
         KeyCompare::Text(src)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn display_text() {
+        let names = ModifierNames {
+            ctrl: "Strg".into(),
+            ..ModifierNames::default()
+        };
+        let save_as = HotKey::new(RawMods::CtrlShift, "s");
+        assert_eq!(save_as.display_text(&names), "Strg+Shift+S");
+        let undo = HotKey::new(RawMods::Ctrl, KeyCode::KeyZ);
+        assert_eq!(undo.display_text(&names), "Strg+Z");
+        let close = HotKey::new(None, KeyCode::Escape);
+        assert_eq!(close.display_text(&names), "Esc");
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn display_text() {
+        let save_as = HotKey::new(RawMods::MetaShift, "s");
+        assert_eq!(save_as.display_text(&ModifierNames::default()), "⇧⌘S");
+    }
+}
@@ -84,6 +84,51 @@ impl KeyEvent {
         }
     }
 
+    /// Create a `KeyEvent` from its parts, such as when replaying recorded
+    /// input.
+    pub fn from_parts(
+        key_code: KeyCode,
+        is_repeat: bool,
+        mods: KeyModifiers,
+        text: &str,
+        unmodified_text: &str,
+    ) -> Self {
+        KeyEvent {
+            key_code,
+            is_repeat,
+            mods,
+            text: TinyStr::new(text),
+            unmodified_text: TinyStr::new(unmodified_text),
+        }
+    }
+
+    /// The events for text that the platform's text input made of `key`,
+    /// such as an `é` composed from a dead key and the `e` after it, or the
+    /// end of a Compose sequence.
+    ///
+    /// The text is split over as many events as it takes.
+    #[cfg_attr(target_os = "windows", allow(dead_code))]
+    pub(crate) fn for_committed_text(key: KeyEvent, text: &str) -> Vec<KeyEvent> {
+        let mut events = Vec::new();
+        let mut start = 0;
+        for (offset, c) in text.char_indices() {
+            if offset + c.len_utf8() - start > TINY_STR_CAPACITY {
+                events.push(KeyEvent {
+                    text: TinyStr::new(&text[start..offset]),
+                    ..key
+                });
+                start = offset;
+            }
+        }
+        if start < text.len() {
+            events.push(KeyEvent {
+                text: TinyStr::new(&text[start..]),
+                ..key
+            });
+        }
+        events
+    }
+
     /// For creating `KeyEvent`s during testing.
     #[doc(hidden)]
     pub fn for_test(mods: impl Into<KeyModifiers>, text: &'static str, code: KeyCode) -> Self {
@@ -271,4 +316,21 @@ mod tests {
         assert!(!s_16.is_char_boundary(15));
         let _too_big = TinyStr::new("😍🥰😘😗");
     }
+
+    #[test]
+    fn committed_text_is_split_over_events() {
+        let key = KeyEvent::for_test(KeyModifiers::default(), "e", KeyCode::KeyE);
+        let events = KeyEvent::for_committed_text(key, "é");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].text(), Some("é"));
+        assert_eq!(events[0].unmod_text(), Some("e"));
+        assert_eq!(events[0].key_code, KeyCode::KeyE);
+
+        let texts: Vec<_> = KeyEvent::for_committed_text(key, "😍🥰😘😗")
+            .iter()
+            .map(|event| event.text().unwrap().to_string())
+            .collect();
+        assert_eq!(texts, ["😍🥰😘", "😗"]);
+        assert!(KeyEvent::for_committed_text(key, "").is_empty());
+    }
 }
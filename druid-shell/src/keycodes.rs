@@ -149,7 +149,36 @@ pub enum KeyCode {
     Unknown(platform::RawKeyCode),
 }
 
+/// Every `KeyCode` except `Unknown`, for looking codes up by name.
+#[rustfmt::skip]
+const NAMED_KEY_CODES: &[KeyCode] = {
+    use KeyCode::*;
+    &[
+        Escape, Backtick, Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Minus,
+        Equals, Backspace, Tab, KeyQ, KeyW, KeyE, KeyR, KeyT, KeyY, KeyU, KeyI, KeyO, KeyP,
+        LeftBracket, RightBracket, Return, KeyA, KeyS, KeyD, KeyF, KeyG, KeyH, KeyJ, KeyK, KeyL,
+        Semicolon, Quote, Backslash, KeyZ, KeyX, KeyC, KeyV, KeyB, KeyN, KeyM, Comma, Period,
+        Slash, LeftControl, RightControl, LeftAlt, RightAlt, LeftShift, RightShift, LeftMeta,
+        RightMeta, Space, CapsLock, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+        PrintScreen, ScrollLock, Pause, Insert, Delete, Home, End, PageUp, PageDown, Numpad0,
+        Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+        NumpadEquals, NumpadSubtract, NumpadAdd, NumpadDecimal, NumpadMultiply, NumpadDivide,
+        NumLock, NumpadEnter, ArrowUp, ArrowDown, ArrowLeft, ArrowRight,
+    ]
+};
+
 impl KeyCode {
+    /// Returns the `KeyCode` with this name, as printed by its `Debug`
+    /// implementation, such as `"KeyA"` or `"ArrowUp"`.
+    ///
+    /// Codes that are unknown to druid-shell have no name.
+    pub fn from_name(name: &str) -> Option<KeyCode> {
+        NAMED_KEY_CODES
+            .iter()
+            .copied()
+            .find(|code| format!("{:?}", code) == name)
+    }
+
     pub fn is_printable(self) -> bool {
         use KeyCode::*;
         match self {
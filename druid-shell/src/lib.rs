@@ -23,6 +23,8 @@
 
 pub use piet_common as piet;
 pub use piet_common::kurbo;
+#[cfg(feature = "raw-win-handle")]
+pub use raw_window_handle;
 
 #[cfg(target_os = "windows")]
 #[macro_use]
@@ -40,6 +42,7 @@ mod application;
 mod clipboard;
 mod common_util;
 mod dialog;
+mod drag;
 mod error;
 mod hotkey;
 mod keyboard;
@@ -47,18 +50,24 @@ mod keycodes;
 mod menu;
 mod mouse;
 mod platform;
+mod screen;
+mod touch_bar;
 mod window;
 
 pub use application::Application;
 pub use clipboard::{Clipboard, ClipboardFormat, FormatId};
 pub use common_util::Counter;
 pub use dialog::{FileDialogOptions, FileInfo, FileSpec};
+pub use drag::{DragData, DropAction};
 pub use error::Error;
-pub use hotkey::{HotKey, KeyCompare, RawMods, SysMods};
+pub use hotkey::{HotKey, KeyCompare, ModifierNames, RawMods, SysMods};
 pub use keyboard::{KeyEvent, KeyModifiers};
 pub use keycodes::KeyCode;
 pub use menu::Menu;
-pub use mouse::{Cursor, MouseButton, MouseEvent};
+pub use mouse::{Cursor, CursorDesc, MouseButton, MouseEvent};
+pub use screen::{Monitor, Screen};
+pub use touch_bar::{TouchBar, TouchBarEvent, TouchBarItem};
 pub use window::{
-    IdleHandle, IdleToken, Text, TimerToken, WinHandler, WindowBuilder, WindowHandle,
+    GlobalHotKeyToken, Icon, IdleHandle, IdleToken, Text, TimerToken, WinHandler, WindowBuilder,
+    WindowHandle,
 };
@@ -12,8 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::hotkey::HotKey;
+use crate::hotkey::{HotKey, ModifierNames};
 use crate::platform::menu as platform;
+use crate::window::Icon;
 
 /// A menu object.
 ///
@@ -22,9 +23,12 @@ use crate::platform::menu as platform;
 ///
 /// # Configuring menus
 ///
-/// Currently, a menu and its items cannot be changed once created. If you need
-/// to change anything about a menu (for instance, disabling or selecting items)
-/// you need to create a new menu with the desired properties.
+/// Once a menu has been set on a window, its items can be enabled, relabeled
+/// or hidden through [`WindowHandle::set_menu_item_enabled`] and friends. Any
+/// other change (for instance, selecting items or adding new ones) requires
+/// creating a new menu with the desired properties.
+///
+/// [`WindowHandle::set_menu_item_enabled`]: struct.WindowHandle.html#method.set_menu_item_enabled
 pub struct Menu(platform::Menu);
 
 impl Menu {
@@ -51,6 +55,17 @@ impl Menu {
         self.0.add_dropdown(menu.0, text, enabled)
     }
 
+    /// Set the names of the modifier keys in the hotkeys shown by the items
+    /// added after this, on platforms where the menu doesn't name them
+    /// itself; currently that is Windows.
+    ///
+    /// The default names are those of [`ModifierNames::default`].
+    ///
+    /// [`ModifierNames::default`]: struct.ModifierNames.html#impl-Default
+    pub fn set_modifier_names(&mut self, names: ModifierNames) {
+        self.0.set_modifier_names(names)
+    }
+
     /// Add an item to this menu.
     ///
     /// The `id` should uniquely identify this item. If the user selects this
@@ -77,6 +92,49 @@ impl Menu {
         self.0.add_item(id, text, key, enabled, selected)
     }
 
+    /// Show a named icon next to the item with the given `id`, which must
+    /// already have been added.
+    ///
+    /// Icon names are platform specific: on GTK they are looked up in the
+    /// icon theme (such as `"document-open"`), and on macOS they are
+    /// [`NSImage`] names (such as `"NSFolder"`). Windows menus can only show
+    /// bitmaps, so the icon is ignored there, as it is if the name is not
+    /// found; use [`set_item_image`] for an icon on every platform.
+    ///
+    /// [`NSImage`]: https://developer.apple.com/documentation/appkit/nsimage/1520015-imagenamed
+    /// [`set_item_image`]: #method.set_item_image
+    pub fn set_item_icon(&mut self, id: u32, icon: &str) {
+        self.0.set_item_icon(id, icon)
+    }
+
+    /// Show `image` next to the item with the given `id`, which must already
+    /// have been added.
+    ///
+    /// The image is shown at its own size, so it should be about as tall as
+    /// the menu's text; 16 by 16 pixels suits most menus.
+    pub fn set_item_image(&mut self, id: u32, image: &Icon) {
+        self.0.set_item_image(id, image)
+    }
+
+    /// Mark the item with the given `id`, which must already have been added,
+    /// as one of a group of choices, showing its selection as a radio button
+    /// rather than a checkmark.
+    ///
+    /// macOS uses checkmarks for both, so this has no effect there.
+    pub fn set_item_radio(&mut self, id: u32, radio: bool) {
+        self.0.set_item_radio(id, radio)
+    }
+
+    /// Hide the item with the given `id`, which must already have been added.
+    ///
+    /// A hidden item can be shown again with
+    /// [`WindowHandle::set_menu_item_hidden`].
+    ///
+    /// [`WindowHandle::set_menu_item_hidden`]: struct.WindowHandle.html#method.set_menu_item_hidden
+    pub fn set_item_hidden(&mut self, id: u32, hidden: bool) {
+        self.0.set_item_hidden(id, hidden)
+    }
+
     /// Add a seperator to the menu.
     pub fn add_separator(&mut self) {
         self.0.add_separator()
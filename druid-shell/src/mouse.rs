@@ -17,6 +17,7 @@
 use crate::kurbo::Point;
 
 use crate::keyboard::KeyModifiers;
+use crate::platform::window as platform;
 
 /// The state of the mouse for a click, mouse-up, or move event.
 #[derive(Debug, Clone, PartialEq)]
@@ -64,9 +65,8 @@ impl MouseButton {
     }
 }
 
-//NOTE: this currently only contains cursors that are included by default on
-//both Windows and macOS. We may want to provide polyfills for various additional cursors,
-//and we will also want to add some mechanism for adding custom cursors.
+//NOTE: where a platform has no native version of a cursor, we fall back to
+//the closest one it does have.
 /// Mouse cursors.
 #[derive(Clone)]
 pub enum Cursor {
@@ -76,7 +76,53 @@ pub enum Cursor {
     IBeam,
     Crosshair,
     OpenHand,
+    /// A closed hand, for indicating that something is being dragged.
+    ClosedHand,
     NotAllowed,
     ResizeLeftRight,
     ResizeUpDown,
+    /// Resizing from the top left or bottom right corner.
+    ResizeTopLeftBottomRight,
+    /// Resizing from the top right or bottom left corner.
+    ResizeTopRightBottomLeft,
+    ZoomIn,
+    ZoomOut,
+    /// A cursor created with [`WindowHandle::make_cursor`].
+    ///
+    /// [`WindowHandle::make_cursor`]: struct.WindowHandle.html#method.make_cursor
+    Custom(platform::CustomCursor),
+}
+
+/// The image and hotspot of a custom cursor.
+#[derive(Debug, Clone)]
+pub struct CursorDesc {
+    pub(crate) rgba: Vec<u8>,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) hot: Point,
+}
+
+impl CursorDesc {
+    /// Create a cursor description from unpremultiplied RGBA pixels, four
+    /// bytes per pixel, row by row from the top left.
+    ///
+    /// `hot` is the point in the image, in pixels, that is placed at the
+    /// mouse position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rgba` does not hold exactly `width * height` pixels.
+    pub fn from_rgba(rgba: Vec<u8>, width: usize, height: usize, hot: impl Into<Point>) -> Self {
+        assert_eq!(
+            rgba.len(),
+            width * height * 4,
+            "cursor image does not match its size"
+        );
+        CursorDesc {
+            rgba,
+            width,
+            height,
+            hot: hot.into(),
+        }
+    }
 }
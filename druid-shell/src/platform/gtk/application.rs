@@ -15,13 +15,22 @@
 //! GTK implementation of features at the application scope.
 
 use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
 
 use gio::prelude::ApplicationExtManual;
-use gio::{ApplicationExt, ApplicationFlags, Cancellable};
-use gtk::{Application as GtkApplication, GtkApplicationExt};
+use gio::{
+    AppInfo, AppLaunchContext, ApplicationExt, ApplicationFlags, Cancellable, Settings,
+    SettingsExt, SettingsSchemaSource,
+};
+use gtk::{
+    Application as GtkApplication, GtkApplicationExt, RecentInfo, RecentManager, RecentManagerExt,
+};
 
 use super::clipboard::Clipboard;
 use super::util;
+use crate::Error;
 
 // XXX: The application needs to be global because WindowBuilder::build wants
 // to construct an ApplicationWindow, which needs the application, but
@@ -30,6 +39,11 @@ thread_local!(
     static GTK_APPLICATION: RefCell<Option<GtkApplication>> = RefCell::new(None);
 );
 
+/// The GNOME schema that holds the user's text scaling preference.
+const INTERFACE_SCHEMA: &str = "org.gnome.desktop.interface";
+pub(crate) const TEXT_SCALE_KEY: &str = "text-scaling-factor";
+pub(crate) const ENABLE_ANIMATIONS_KEY: &str = "enable-animations";
+
 pub struct Application;
 
 impl Application {
@@ -61,6 +75,12 @@ impl Application {
         Application
     }
 
+    /// GTK is happy to share its main loop, so this is no different.
+    #[cfg(feature = "raw-win-handle")]
+    pub fn embedded() -> Application {
+        Application::new()
+    }
+
     pub fn run(&mut self) {
         util::assert_main_thread();
 
@@ -96,6 +116,147 @@ impl Application {
         //TODO ahem
         "en-US".into()
     }
+
+    pub fn get_text_scale() -> f64 {
+        interface_settings()
+            .map(|settings| text_scale_from_settings(&settings))
+            .unwrap_or(1.0)
+    }
+
+    pub fn get_reduce_motion() -> bool {
+        interface_settings()
+            .map(|settings| !settings.get_boolean(ENABLE_ANIMATIONS_KEY))
+            .unwrap_or(false)
+    }
+
+    pub fn open_url(url: &str) -> Result<(), Error> {
+        AppInfo::launch_default_for_uri(url, None::<&AppLaunchContext>).map_err(|e| {
+            log::warn!("failed to open {}: {}", url, e);
+            Error::Other("failed to open url")
+        })
+    }
+
+    pub fn open_path(path: &Path) -> Result<(), Error> {
+        Self::open_url(&path_to_uri(path)?)
+    }
+
+    pub fn reveal_path(path: &Path) -> Result<(), Error> {
+        // most file managers implement the freedesktop FileManager1 interface;
+        // there is no GIO API for it, and gio-rs 0.8 has no D-Bus bindings.
+        let child = Command::new("dbus-send")
+            .args([
+                "--session",
+                "--print-reply",
+                "--dest=org.freedesktop.FileManager1",
+                "--type=method_call",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+            ])
+            .arg(format!("array:string:{}", path_to_uri(path)?))
+            .arg("string:")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(_) => return Self::open_containing_folder(path),
+        };
+        // the file manager can take a while to answer, so we wait for it
+        // off the main thread, and come back to it if we need to fall back
+        let path = path.to_owned();
+        thread::spawn(move || {
+            let shown = child.wait().map(|status| status.success()).unwrap_or(false);
+            if !shown {
+                glib::idle_add(move || {
+                    let _ = Self::open_containing_folder(&path);
+                    glib::Continue(false)
+                });
+            }
+        });
+        Ok(())
+    }
+
+    /// Open the folder `path` is in, for when it can't be selected there.
+    fn open_containing_folder(path: &Path) -> Result<(), Error> {
+        match path.parent() {
+            Some(parent) => Self::open_path(parent),
+            None => Self::open_path(path),
+        }
+    }
+
+    pub fn add_recent_document(path: &Path) {
+        match (RecentManager::get_default(), path_to_uri(path)) {
+            (Some(manager), Ok(uri)) => {
+                if !manager.add_item(&uri) {
+                    log::warn!("failed to add {} to recent files", uri);
+                }
+            }
+            (_, Err(e)) => log::warn!("failed to add recent document: {}", e),
+            (None, _) => (),
+        }
+    }
+
+    pub fn recent_documents() -> Vec<PathBuf> {
+        let mut items = own_recent_items();
+        items.sort_by_key(|info| std::cmp::Reverse(info.get_modified()));
+        items
+            .iter()
+            .filter_map(|info| info.get_uri())
+            .filter_map(|uri| glib::filename_from_uri(&uri).ok())
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    pub fn clear_recent_documents() {
+        let manager = match RecentManager::get_default() {
+            Some(manager) => manager,
+            None => return,
+        };
+        // `purge_items` would also remove the items of every other app
+        for uri in own_recent_items().iter().filter_map(RecentInfo::get_uri) {
+            if let Err(e) = manager.remove_item(&uri) {
+                log::warn!("failed to remove {} from recent files: {}", uri, e);
+            }
+        }
+    }
+}
+
+/// The recently used files that were registered by this app.
+fn own_recent_items() -> Vec<RecentInfo> {
+    let name = match glib::get_application_name() {
+        Some(name) => name,
+        None => return Vec::new(),
+    };
+    RecentManager::get_default()
+        .map(|manager| manager.get_items())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|info| info.has_application(&name))
+        .collect()
+}
+
+fn path_to_uri(path: &Path) -> Result<String, Error> {
+    glib::filename_to_uri(path, None)
+        .map(|uri| uri.to_string())
+        .map_err(|_| Error::Other("path can't be converted to a uri"))
+}
+
+/// Returns the GNOME interface settings, if that schema is installed.
+///
+/// `Settings::new` aborts on an unknown schema, so we check first.
+pub(crate) fn interface_settings() -> Option<Settings> {
+    SettingsSchemaSource::get_default()?
+        .lookup(INTERFACE_SCHEMA, true)
+        .map(|_| Settings::new(INTERFACE_SCHEMA))
+}
+
+pub(crate) fn text_scale_from_settings(settings: &Settings) -> f64 {
+    let scale = settings.get_double(TEXT_SCALE_KEY);
+    if scale > 0.0 {
+        scale
+    } else {
+        1.0
+    }
 }
 
 #[inline]
@@ -28,6 +28,9 @@ pub(crate) fn get_file_dialog_path(
 ) -> Result<OsString, Error> {
     // TODO: support message localization
     let (title, action) = match ty {
+        FileDialogType::Open if options.select_directories => {
+            ("Open Folder", FileChooserAction::SelectFolder)
+        }
         FileDialogType::Open => ("Open File", FileChooserAction::Open),
         FileDialogType::Save => ("Save File", FileChooserAction::Save),
     };
@@ -41,6 +44,17 @@ pub(crate) fn get_file_dialog_path(
 
     dialog.set_show_hidden(options.show_hidden);
 
+    if let Some(dir) = &options.starting_directory {
+        dialog.set_current_folder(dir);
+    }
+    if let (FileDialogType::Save, Some(name)) = (&ty, &options.default_name) {
+        dialog.set_current_name(name);
+    }
+    if let Some(allowed) = options.can_create_directories {
+        dialog.set_create_folders(allowed);
+    }
+    dialog.set_do_overwrite_confirmation(options.confirm_overwrite);
+
     let result = dialog.run();
 
     let result = match result {
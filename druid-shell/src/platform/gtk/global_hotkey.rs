@@ -0,0 +1,265 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Global hotkeys, which GDK has no API for, grabbed from the X11 root
+//! window. Wayland has no global hotkeys.
+
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::os::raw::{c_int, c_uint, c_ulong};
+use std::ptr;
+
+use glib::translate::ToGlibPtr;
+use glib::ObjectExt;
+
+use super::window::WindowHandle;
+use crate::hotkey::{HotKey, KeyCompare};
+use crate::keyboard::KeyModifiers;
+use crate::window::GlobalHotKeyToken;
+
+// These are provided by Xlib, and by GDK's X11 backend, which gdk-sys does
+// not bind.
+#[link(name = "X11")]
+extern "C" {
+    fn XKeysymToKeycode(display: *mut c_void, keysym: c_ulong) -> u8;
+    fn XGrabKey(
+        display: *mut c_void,
+        keycode: c_int,
+        modifiers: c_uint,
+        window: c_ulong,
+        owner_events: c_int,
+        pointer_mode: c_int,
+        keyboard_mode: c_int,
+    ) -> c_int;
+    fn XUngrabKey(
+        display: *mut c_void,
+        keycode: c_int,
+        modifiers: c_uint,
+        window: c_ulong,
+    ) -> c_int;
+}
+
+extern "C" {
+    fn gdk_x11_display_get_xdisplay(display: *mut gdk_sys::GdkDisplay) -> *mut c_void;
+    fn gdk_x11_get_default_root_xwindow() -> c_ulong;
+    fn gdk_x11_display_error_trap_push(display: *mut gdk_sys::GdkDisplay);
+    fn gdk_x11_display_error_trap_pop(display: *mut gdk_sys::GdkDisplay) -> c_int;
+    fn gdk_x11_display_error_trap_pop_ignored(display: *mut gdk_sys::GdkDisplay);
+}
+
+/// The start of Xlib's `XKeyEvent`.
+#[repr(C)]
+struct XKeyEvent {
+    type_: c_int,
+    serial: c_ulong,
+    send_event: c_int,
+    display: *mut c_void,
+    window: c_ulong,
+    root: c_ulong,
+    subwindow: c_ulong,
+    time: c_ulong,
+    x: c_int,
+    y: c_int,
+    x_root: c_int,
+    y_root: c_int,
+    state: c_uint,
+    keycode: c_uint,
+}
+
+const KEY_PRESS: c_int = 2;
+const GRAB_MODE_ASYNC: c_int = 1;
+
+const SHIFT_MASK: c_uint = 1;
+const LOCK_MASK: c_uint = 1 << 1;
+const CONTROL_MASK: c_uint = 1 << 2;
+/// Alt.
+const MOD1_MASK: c_uint = 1 << 3;
+/// Num lock.
+const MOD2_MASK: c_uint = 1 << 4;
+/// Super.
+const MOD4_MASK: c_uint = 1 << 6;
+
+/// Caps lock and num lock don't change a hotkey, but X only reports a
+/// grabbed key with exactly the grabbed modifiers, so each is grabbed with
+/// every combination of them.
+const LOCKS: [c_uint; 4] = [0, LOCK_MASK, MOD2_MASK, LOCK_MASK | MOD2_MASK];
+
+struct Grab {
+    token: GlobalHotKeyToken,
+    window: WindowHandle,
+    keycode: c_uint,
+    mods: c_uint,
+}
+
+thread_local! {
+    /// Every hotkey grabbed by the app, which is only ever touched on the
+    /// main thread.
+    static GRABS: RefCell<Vec<Grab>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Grab `hotkey` for `window`, whose handler is told when it is pressed.
+pub(crate) fn register(window: &WindowHandle, hotkey: &HotKey) -> Option<GlobalHotKeyToken> {
+    let display = gdk::Display::get_default()?;
+    if display.get_type().name() != "GdkX11Display" {
+        log::warn!("global hotkeys are only supported on X11.");
+        return None;
+    }
+    let keyval = match hotkey.key {
+        KeyCompare::Code(code) => code.into(),
+        KeyCompare::Text(text) => gdk::unicode_to_keyval(text.chars().next()? as u32),
+    };
+    let mods = x11_modifiers(hotkey.mods.into());
+    let display_ptr: *mut gdk_sys::GdkDisplay = display.to_glib_none().0;
+    let keycode = unsafe {
+        let xdisplay = gdk_x11_display_get_xdisplay(display_ptr);
+        let keycode = c_uint::from(XKeysymToKeycode(xdisplay, c_ulong::from(keyval)));
+        if keycode == 0 {
+            log::warn!("no key on the keyboard for global hotkey {:?}", hotkey);
+            return None;
+        }
+        let root = gdk_x11_get_default_root_xwindow();
+        // another app holding any of the grabs is reported as an X error
+        gdk_x11_display_error_trap_push(display_ptr);
+        for locks in &LOCKS {
+            XGrabKey(
+                xdisplay,
+                keycode as c_int,
+                mods | locks,
+                root,
+                1,
+                GRAB_MODE_ASYNC,
+                GRAB_MODE_ASYNC,
+            );
+        }
+        if gdk_x11_display_error_trap_pop(display_ptr) != 0 {
+            log::warn!("failed to register global hotkey {:?}", hotkey);
+            ungrab(&display, keycode, mods);
+            return None;
+        }
+        keycode
+    };
+
+    let token = GlobalHotKeyToken::next();
+    GRABS.with(|grabs| {
+        let mut grabs = grabs.borrow_mut();
+        if grabs.is_empty() {
+            unsafe {
+                gdk_sys::gdk_window_add_filter(ptr::null_mut(), Some(filter), ptr::null_mut());
+            }
+        }
+        grabs.push(Grab {
+            token,
+            window: window.clone(),
+            keycode,
+            mods,
+        });
+    });
+    Some(token)
+}
+
+/// Release the hotkey registered with `token`.
+pub(crate) fn unregister(token: GlobalHotKeyToken) {
+    remove(|grab| grab.token == token);
+}
+
+/// Release all the hotkeys registered for `window`, which is going away.
+pub(crate) fn unregister_window(window: &WindowHandle) {
+    remove(|grab| grab.window.state.ptr_eq(&window.state));
+}
+
+fn remove(mut matches: impl FnMut(&Grab) -> bool) {
+    let display = match gdk::Display::get_default() {
+        Some(display) => display,
+        None => return,
+    };
+    GRABS.with(|grabs| {
+        let mut grabs = grabs.borrow_mut();
+        if grabs.is_empty() {
+            return;
+        }
+        grabs.retain(|grab| {
+            if matches(grab) {
+                ungrab(&display, grab.keycode, grab.mods);
+                false
+            } else {
+                true
+            }
+        });
+        if grabs.is_empty() {
+            unsafe {
+                gdk_sys::gdk_window_remove_filter(ptr::null_mut(), Some(filter), ptr::null_mut());
+            }
+        }
+    });
+}
+
+fn ungrab(display: &gdk::Display, keycode: c_uint, mods: c_uint) {
+    let display_ptr: *mut gdk_sys::GdkDisplay = display.to_glib_none().0;
+    unsafe {
+        let xdisplay = gdk_x11_display_get_xdisplay(display_ptr);
+        let root = gdk_x11_get_default_root_xwindow();
+        gdk_x11_display_error_trap_push(display_ptr);
+        for locks in &LOCKS {
+            XUngrabKey(xdisplay, keycode as c_int, mods | locks, root);
+        }
+        gdk_x11_display_error_trap_pop_ignored(display_ptr);
+    }
+}
+
+fn x11_modifiers(mods: KeyModifiers) -> c_uint {
+    let mut result = 0;
+    if mods.shift {
+        result |= SHIFT_MASK;
+    }
+    if mods.ctrl {
+        result |= CONTROL_MASK;
+    }
+    if mods.alt {
+        result |= MOD1_MASK;
+    }
+    if mods.meta {
+        result |= MOD4_MASK;
+    }
+    result
+}
+
+/// Looks at every X event before GDK does, and takes the grabbed key
+/// presses.
+unsafe extern "C" fn filter(
+    xevent: *mut gdk_sys::GdkXEvent,
+    _event: *mut gdk_sys::GdkEvent,
+    _data: glib_sys::gpointer,
+) -> gdk_sys::GdkFilterReturn {
+    let event = &*(xevent as *const XKeyEvent);
+    if event.type_ != KEY_PRESS {
+        return gdk_sys::GDK_FILTER_CONTINUE;
+    }
+    let mods = event.state & (SHIFT_MASK | CONTROL_MASK | MOD1_MASK | MOD4_MASK);
+    // the handler may register or unregister hotkeys, so it is called once
+    // the grabs are no longer borrowed
+    let pressed = GRABS.with(|grabs| {
+        grabs
+            .borrow()
+            .iter()
+            .find(|grab| grab.keycode == event.keycode && grab.mods == mods)
+            .map(|grab| (grab.window.clone(), grab.token))
+    });
+    match pressed {
+        Some((window, token)) => {
+            window.global_hotkey_pressed(token);
+            gdk_sys::GDK_FILTER_REMOVE
+        }
+        None => gdk_sys::GDK_FILTER_CONTINUE,
+    }
+}
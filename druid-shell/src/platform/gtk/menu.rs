@@ -14,16 +14,22 @@
 
 //! GTK implementation of menus.
 
+use std::collections::HashMap;
+
 use gdk::ModifierType;
+use glib::object::Cast;
 use gtk::{
-    AccelGroup, GtkMenuExt, GtkMenuItemExt, Menu as GtkMenu, MenuBar as GtkMenuBar,
-    MenuItem as GtkMenuItem, MenuShellExt, SeparatorMenuItemBuilder, WidgetExt,
+    AccelGroup, AccelLabel, AccelLabelExt, BinExt, BoxExt, CheckMenuItem, CheckMenuItemExt,
+    ContainerExt, GtkMenuExt, GtkMenuItemExt, IconSize, Image, LabelExt, Menu as GtkMenu,
+    MenuBar as GtkMenuBar, MenuItem as GtkMenuItem, MenuShellExt, Orientation,
+    SeparatorMenuItemBuilder, WidgetExt,
 };
 
-use super::window::WindowHandle;
+use super::window::{make_pixbuf, WindowHandle};
 use crate::common_util::strip_access_key;
-use crate::hotkey::{HotKey, KeyCompare, RawMods};
+use crate::hotkey::{HotKey, KeyCompare, ModifierNames, RawMods};
 use crate::keyboard::KeyModifiers;
+use crate::window::Icon;
 
 #[derive(Default, Debug)]
 pub struct Menu {
@@ -32,11 +38,26 @@ pub struct Menu {
 
 #[derive(Debug)]
 enum MenuItem {
-    Entry(String, u32, Option<HotKey>),
-    SubMenu(String, Menu),
+    Entry {
+        name: String,
+        id: u32,
+        key: Option<HotKey>,
+        icon: Option<ItemIcon>,
+        enabled: bool,
+        selected: bool,
+        radio: bool,
+        hidden: bool,
+    },
+    SubMenu(String, Menu, bool),
     Separator,
 }
 
+#[derive(Debug)]
+enum ItemIcon {
+    Named(String),
+    Image(Icon),
+}
+
 impl Menu {
     pub fn new() -> Menu {
         Menu { items: Vec::new() }
@@ -46,10 +67,9 @@ impl Menu {
         Menu { items: Vec::new() }
     }
 
-    pub fn add_dropdown(&mut self, menu: Menu, text: &str, _enabled: bool) {
-        // TODO: implement enabled dropdown
+    pub fn add_dropdown(&mut self, menu: Menu, text: &str, enabled: bool) {
         self.items
-            .push(MenuItem::SubMenu(strip_access_key(text), menu));
+            .push(MenuItem::SubMenu(strip_access_key(text), menu, enabled));
     }
 
     pub fn add_item(
@@ -57,12 +77,76 @@ impl Menu {
         id: u32,
         text: &str,
         key: Option<&HotKey>,
-        _enabled: bool,
-        _selected: bool,
+        enabled: bool,
+        selected: bool,
     ) {
-        // TODO: implement enabled, selected item
-        self.items
-            .push(MenuItem::Entry(strip_access_key(text), id, key.cloned()));
+        self.items.push(MenuItem::Entry {
+            name: strip_access_key(text),
+            id,
+            key: key.cloned(),
+            icon: None,
+            enabled,
+            selected,
+            radio: false,
+            hidden: false,
+        });
+    }
+
+    /// GTK names the modifiers of accelerators itself.
+    pub fn set_modifier_names(&mut self, _names: ModifierNames) {}
+
+    pub fn set_item_icon(&mut self, id: u32, icon: &str) {
+        self.set_icon(id, ItemIcon::Named(icon.to_string()));
+    }
+
+    pub fn set_item_image(&mut self, id: u32, image: &Icon) {
+        self.set_icon(id, ItemIcon::Image(image.clone()));
+    }
+
+    fn set_icon(&mut self, id: u32, icon: ItemIcon) {
+        for item in self.items.iter_mut() {
+            if let MenuItem::Entry {
+                id: item_id,
+                icon: item_icon,
+                ..
+            } = item
+            {
+                if *item_id == id {
+                    *item_icon = Some(icon);
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn set_item_radio(&mut self, id: u32, radio: bool) {
+        for item in self.items.iter_mut() {
+            if let MenuItem::Entry {
+                id: item_id,
+                radio: item_radio,
+                ..
+            } = item
+            {
+                if *item_id == id {
+                    *item_radio = radio;
+                }
+            }
+        }
+    }
+
+    pub fn set_item_hidden(&mut self, id: u32, hidden: bool) {
+        for item in self.items.iter_mut() {
+            if let MenuItem::Entry {
+                id: item_id,
+                hidden: item_hidden,
+                ..
+            } = item
+            {
+                if *item_id == id {
+                    *item_hidden = hidden;
+                }
+            }
+        }
     }
 
     pub fn add_separator(&mut self) {
@@ -74,11 +158,45 @@ impl Menu {
         menu: &mut M,
         handle: &WindowHandle,
         accel_group: &AccelGroup,
+        items: &mut HashMap<u32, GtkMenuItem>,
     ) {
         for item in self.items {
             match item {
-                MenuItem::Entry(name, id, key) => {
-                    let item = GtkMenuItem::new_with_label(&name);
+                MenuItem::Entry {
+                    name,
+                    id,
+                    key,
+                    icon,
+                    enabled,
+                    selected,
+                    radio,
+                    hidden,
+                } => {
+                    // GTK only draws a check mark on check menu items; radio
+                    // items are always ones, to keep room for the unset mark
+                    let item: GtkMenuItem = if selected || radio {
+                        let item = CheckMenuItem::new();
+                        item.set_draw_as_radio(radio);
+                        // set before connecting, so this doesn't activate the item
+                        item.set_active(selected);
+                        item.upcast()
+                    } else {
+                        GtkMenuItem::new()
+                    };
+                    match icon {
+                        Some(ItemIcon::Named(icon)) => {
+                            let image = Image::new_from_icon_name(Some(&icon), IconSize::Menu);
+                            add_icon_and_label(&item, &name, &image);
+                        }
+                        Some(ItemIcon::Image(icon)) => {
+                            let image = Image::new_from_pixbuf(Some(&make_pixbuf(&icon)));
+                            add_icon_and_label(&item, &name, &image);
+                        }
+                        None => item.set_label(&name),
+                    }
+                    item.set_sensitive(enabled);
+                    // keep `show_all` from showing the item
+                    item.set_no_show_all(hidden);
 
                     if let Some(k) = key {
                         register_accelerator(&item, accel_group, k);
@@ -92,10 +210,13 @@ impl Menu {
                     });
 
                     menu.append(&item);
+                    items.insert(id, item);
                 }
-                MenuItem::SubMenu(name, submenu) => {
+                MenuItem::SubMenu(name, submenu, enabled) => {
                     let item = GtkMenuItem::new_with_label(&name);
-                    item.set_submenu(Some(&submenu.into_gtk_menu(handle, accel_group)));
+                    item.set_sensitive(enabled);
+                    let submenu = submenu.build_gtk_menu(handle, accel_group, items);
+                    item.set_submenu(Some(&submenu));
 
                     menu.append(&item);
                 }
@@ -110,22 +231,68 @@ impl Menu {
         accel_group: &AccelGroup,
     ) -> GtkMenuBar {
         let mut menu = GtkMenuBar::new();
+        let mut items = HashMap::new();
 
-        self.append_items_to_menu(&mut menu, handle, accel_group);
+        self.append_items_to_menu(&mut menu, handle, accel_group, &mut items);
 
+        // remember the items, so that they can be changed later
+        if let Some(state) = handle.state.upgrade() {
+            state.menu_items.replace(items);
+        }
         menu
     }
 
     pub fn into_gtk_menu(self, handle: &WindowHandle, accel_group: &AccelGroup) -> GtkMenu {
+        self.build_gtk_menu(handle, accel_group, &mut HashMap::new())
+    }
+
+    fn build_gtk_menu(
+        self,
+        handle: &WindowHandle,
+        accel_group: &AccelGroup,
+        items: &mut HashMap<u32, GtkMenuItem>,
+    ) -> GtkMenu {
         let mut menu = GtkMenu::new();
         menu.set_accel_group(Some(accel_group));
 
-        self.append_items_to_menu(&mut menu, handle, accel_group);
+        self.append_items_to_menu(&mut menu, handle, accel_group, items);
 
         menu
     }
 }
 
+/// Change the text of an item made by `append_items_to_menu`.
+pub(crate) fn set_item_title(item: &GtkMenuItem, title: &str) {
+    let title = strip_access_key(title);
+    match item
+        .get_child()
+        .and_then(|child| child.downcast::<gtk::Box>().ok())
+    {
+        // an item with an icon; see `add_icon_and_label`
+        Some(hbox) => {
+            for child in hbox.get_children() {
+                if let Ok(label) = child.downcast::<AccelLabel>() {
+                    label.set_text(&title);
+                }
+            }
+        }
+        None => item.set_label(&title),
+    }
+}
+
+/// Fill `item` with a box holding an icon and a label, in place of the
+/// deprecated `GtkImageMenuItem`.
+fn add_icon_and_label(item: &GtkMenuItem, name: &str, image: &Image) {
+    let hbox = gtk::Box::new(Orientation::Horizontal, 6);
+    // an accel label, so that hotkeys are still displayed
+    let label = AccelLabel::new(name);
+    label.set_xalign(0.0);
+    label.set_accel_widget(Some(item));
+    hbox.pack_start(image, false, false, 0);
+    hbox.pack_start(&label, true, true, 0);
+    item.add(&hbox);
+}
+
 fn register_accelerator(item: &GtkMenuItem, accel_group: &AccelGroup, menu_key: HotKey) {
     let wc = match menu_key.key {
         KeyCompare::Code(key_code) => key_code.into(),
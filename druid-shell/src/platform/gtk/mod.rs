@@ -18,7 +18,9 @@ pub mod application;
 pub mod clipboard;
 pub mod dialog;
 pub mod error;
+mod global_hotkey;
 pub mod keycodes;
 pub mod menu;
+pub mod screen;
 pub mod util;
 pub mod window;
@@ -0,0 +1,47 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GTK monitor information.
+
+use gdk::{Rectangle, Screen};
+
+use crate::kurbo::Rect;
+use crate::screen::Monitor;
+
+// The per-monitor `Screen` methods are deprecated in favor of `gdk::Monitor`,
+// which needs GTK 3.22; we support older versions.
+pub(crate) fn get_monitors() -> Vec<Monitor> {
+    let screen = match Screen::get_default() {
+        Some(screen) => screen,
+        None => return Vec::new(),
+    };
+    let primary = screen.get_primary_monitor();
+    (0..screen.get_n_monitors())
+        .map(|i| Monitor {
+            primary: i == primary,
+            rect: to_rect(screen.get_monitor_geometry(i)),
+            work_rect: to_rect(screen.get_monitor_workarea(i)),
+            scale: f64::from(screen.get_monitor_scale_factor(i)),
+        })
+        .collect()
+}
+
+fn to_rect(rect: Rectangle) -> Rect {
+    Rect::new(
+        f64::from(rect.x),
+        f64::from(rect.y),
+        f64::from(rect.x + rect.width),
+        f64::from(rect.y + rect.height),
+    )
+}
@@ -16,6 +16,7 @@
 
 use std::any::Any;
 use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::ffi::c_void;
 use std::ffi::OsString;
@@ -25,24 +26,38 @@ use std::slice;
 use std::sync::{Arc, Mutex, Weak};
 use std::time::Instant;
 
-use gdk::{EventKey, EventMask, ModifierType, ScrollDirection, WindowExt};
-use gio::ApplicationExt;
+use gdk::{Atom, DragAction, EventKey, EventMask, ModifierType, ScrollDirection, WindowExt};
+use gdk_pixbuf::{Colorspace, Pixbuf};
+use gio::{ApplicationExt, SettingsExt};
 use gtk::prelude::*;
-use gtk::{AccelGroup, ApplicationWindow};
+use gtk::{
+    AccelGroup, ApplicationWindow, DestDefaults, SelectionData, TargetEntry, TargetFlags,
+    TargetList,
+};
+
+#[cfg(feature = "raw-win-handle")]
+use raw_window_handle::{
+    RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle,
+    XlibDisplayHandle, XlibWindowHandle,
+};
 
 use crate::kurbo::{Point, Size, Vec2};
 use crate::piet::{Piet, RenderContext};
 
-use super::application::with_application;
+use super::application::{self, with_application};
 use super::dialog;
-use super::menu::Menu;
+use super::global_hotkey;
+use super::menu::{self, Menu};
 use super::util::assert_main_thread;
 
 use crate::common_util::IdleCallback;
 use crate::dialog::{FileDialogOptions, FileDialogType, FileInfo};
+use crate::drag::{DragData, DropAction};
+use crate::hotkey::HotKey;
 use crate::keyboard;
-use crate::mouse::{Cursor, MouseButton, MouseEvent};
-use crate::window::{IdleToken, Text, TimerToken, WinHandler};
+use crate::mouse::{Cursor, CursorDesc, MouseButton, MouseEvent};
+use crate::touch_bar::TouchBar;
+use crate::window::{GlobalHotKeyToken, Icon, IdleToken, Text, TimerToken, WinHandler};
 use crate::Error;
 
 /// Taken from https://gtk-rs.org/docs-src/tutorial/closures
@@ -75,6 +90,9 @@ macro_rules! clone {
     );
 }
 
+#[derive(Clone)]
+pub struct CustomCursor(gdk::Cursor);
+
 #[derive(Clone, Default)]
 pub struct WindowHandle {
     pub(crate) state: Weak<WindowState>,
@@ -86,6 +104,11 @@ pub struct WindowBuilder {
     title: String,
     menu: Option<Menu>,
     size: Size,
+    always_on_top: bool,
+    skip_taskbar: bool,
+    utility: bool,
+    #[cfg(feature = "raw-win-handle")]
+    parent: Option<RawWindowHandle>,
 }
 
 #[derive(Clone)]
@@ -101,12 +124,29 @@ enum IdleKind {
 }
 
 pub(crate) struct WindowState {
-    window: ApplicationWindow,
+    window: gtk::Window,
     pub(crate) handler: RefCell<Box<dyn WinHandler>>,
     idle_queue: Arc<Mutex<Vec<IdleKind>>>,
     current_keyval: RefCell<Option<u32>>,
+    /// The input method, which composes dead keys, Compose sequences and
+    /// the text of other input methods from the keys it is given.
+    im_context: gtk::IMMulticontext,
+    /// The last key the input method was given; the text it commits is sent
+    /// as typed by this key.
+    im_key: Cell<Option<keyboard::KeyEvent>>,
+    /// Kept alive so that we are notified of text scale changes.
+    interface_settings: Option<gio::Settings>,
+    /// The data of a drag started by this window, until the drag ends.
+    drag_source: RefCell<Option<DragData>>,
+    /// The items of the window's menu bar, by id.
+    pub(crate) menu_items: RefCell<HashMap<u32, gtk::MenuItem>>,
 }
 
+// Drag target info ids; custom formats are numbered from `DRAG_INFO_FORMATS`.
+const DRAG_INFO_URIS: u32 = 0;
+const DRAG_INFO_TEXT: u32 = 1;
+const DRAG_INFO_FORMATS: u32 = 2;
+
 impl WindowBuilder {
     pub fn new() -> WindowBuilder {
         WindowBuilder {
@@ -114,6 +154,11 @@ impl WindowBuilder {
             title: String::new(),
             menu: None,
             size: Size::new(500.0, 400.0),
+            always_on_top: false,
+            skip_taskbar: false,
+            utility: false,
+            #[cfg(feature = "raw-win-handle")]
+            parent: None,
         }
     }
 
@@ -133,16 +178,53 @@ impl WindowBuilder {
         self.menu = Some(menu);
     }
 
+    pub fn set_always_on_top(&mut self, on_top: bool) {
+        self.always_on_top = on_top;
+    }
+
+    pub fn set_skip_taskbar(&mut self, skip: bool) {
+        self.skip_taskbar = skip;
+    }
+
+    pub fn set_utility(&mut self, utility: bool) {
+        self.utility = utility;
+    }
+
+    #[cfg(feature = "raw-win-handle")]
+    pub fn set_parent(&mut self, parent: RawWindowHandle) {
+        self.parent = Some(parent);
+    }
+
+    /// Make the top-level window: our own, or a plug that goes in the parent.
+    fn make_window(&self) -> Result<gtk::Window, Error> {
+        #[cfg(feature = "raw-win-handle")]
+        match self.parent {
+            // this uses XEmbed, so the parent needs to be a socket.
+            Some(RawWindowHandle::Xlib(parent)) => {
+                return Ok(gtk::Plug::new(parent.window as gtk::xlib::Window).upcast())
+            }
+            Some(_) => {
+                return Err(Error::Other(
+                    "GTK windows can only be embedded in an X11 window",
+                ))
+            }
+            None => (),
+        }
+        Ok(with_application(|app| ApplicationWindow::new(&app)).upcast())
+    }
+
     pub fn build(self) -> Result<WindowHandle, Error> {
         assert_main_thread();
 
+        let window = self.make_window()?;
         let handler = self
             .handler
             .expect("Tried to build a window without setting the handler");
 
-        let window = with_application(|app| ApplicationWindow::new(&app));
-
         window.set_title(&self.title);
+        window.set_keep_above(self.always_on_top);
+        set_skip_taskbar(&window, self.skip_taskbar);
+        window.set_type_hint(type_hint(self.utility));
 
         let dpi_scale = window
             .get_display()
@@ -166,6 +248,11 @@ impl WindowBuilder {
             handler: RefCell::new(handler),
             idle_queue: Arc::new(Mutex::new(vec![])),
             current_keyval: RefCell::new(None),
+            im_context: gtk::IMMulticontext::new(),
+            im_key: Cell::new(None),
+            interface_settings: application::interface_settings(),
+            drag_source: RefCell::new(None),
+            menu_items: RefCell::new(HashMap::new()),
         });
 
         with_application(|app| {
@@ -341,16 +428,45 @@ impl WindowBuilder {
             Inhibit(true)
         }));
 
-        drawing_area.connect_key_press_event(clone!(handle => move |_widget, key| {
+        // we don't draw the text being composed, so the input method shows it
+        win_state.im_context.set_use_preedit(false);
+        win_state
+            .im_context
+            .connect_commit(clone!(handle => move |_context, text| {
+                if let Some(state) = handle.state.upgrade() {
+                    if let Some(key) = state.im_key.get() {
+                        for key_event in keyboard::KeyEvent::for_committed_text(key, text) {
+                            if let Ok(mut handler) = state.handler.try_borrow_mut() {
+                                handler.key_down(key_event);
+                            }
+                        }
+                    }
+                }
+            }));
+
+        drawing_area.connect_realize(clone!(handle => move |widget| {
             if let Some(state) = handle.state.upgrade() {
+                state.im_context.set_client_window(widget.get_window().as_ref());
+            }
+        }));
 
-                let mut current_keyval = state.current_keyval.borrow_mut();
-                let repeat = *current_keyval == Some(key.get_keyval());
+        drawing_area.connect_key_press_event(clone!(handle => move |_widget, key| {
+            if let Some(state) = handle.state.upgrade() {
 
-                *current_keyval = Some(key.get_keyval());
+                let repeat = {
+                    let mut current_keyval = state.current_keyval.borrow_mut();
+                    let repeat = *current_keyval == Some(key.get_keyval());
+                    *current_keyval = Some(key.get_keyval());
+                    repeat
+                };
 
                 let key_event = make_key_event(key, repeat);
-                state.handler.borrow_mut().key_down(key_event);
+                // the input method sees keys first, and commits the text it
+                // makes of them; keys it doesn't want are sent as they are.
+                state.im_key.set(Some(key_event));
+                if !state.im_context.filter_keypress(key) {
+                    state.handler.borrow_mut().key_down(key_event);
+                }
             }
 
             Inhibit(true)
@@ -361,19 +477,141 @@ impl WindowBuilder {
 
                 *(state.current_keyval.borrow_mut()) = None;
 
-                let key_event = make_key_event(key, false);
-                state.handler.borrow_mut().key_up(key_event);
+                if !state.im_context.filter_keypress(key) {
+                    let key_event = make_key_event(key, false);
+                    state.handler.borrow_mut().key_up(key_event);
+                }
             }
 
             Inhibit(true)
         }));
 
+        let drop_targets = [
+            TargetEntry::new("text/uri-list", TargetFlags::OTHER_APP, 0),
+            TargetEntry::new("text/plain;charset=utf-8", TargetFlags::OTHER_APP, 0),
+            TargetEntry::new("UTF8_STRING", TargetFlags::OTHER_APP, 0),
+        ];
+        // GTK negotiates the action and requests the data for us; we only
+        // forward the hover position and the data once it arrives.
+        drawing_area.drag_dest_set(
+            DestDefaults::MOTION | DestDefaults::DROP,
+            &drop_targets,
+            DragAction::COPY,
+        );
+
+        drawing_area.connect_drag_motion(clone!(handle => move |_widget, _context, x, y, _time| {
+            if let Some(state) = handle.state.upgrade() {
+                let pos = Point::new(f64::from(x), f64::from(y));
+                state.handler.borrow_mut().drag_moved(pos);
+            }
+            Inhibit(true)
+        }));
+
+        drawing_area.connect_drag_leave(clone!(handle => move |_widget, _context, _time| {
+            if let Some(state) = handle.state.upgrade() {
+                state.handler.borrow_mut().drag_left();
+            }
+        }));
+
+        drawing_area.connect_drag_data_received(
+            clone!(handle => move |_widget, _context, x, y, selection, _info, _time| {
+                if let Some(state) = handle.state.upgrade() {
+                    let pos = Point::new(f64::from(x), f64::from(y));
+                    state.handler.borrow_mut().dropped(pos, get_drag_data(selection));
+                }
+            }),
+        );
+
+        win_state
+            .window
+            .connect_focus_in_event(clone!(handle => move |_widget, _event| {
+                if let Some(state) = handle.state.upgrade() {
+                    state.im_context.focus_in();
+                    if let Ok(mut handler) = state.handler.try_borrow_mut() {
+                        handler.got_focus();
+                    }
+                }
+                Inhibit(false)
+            }));
+
+        win_state
+            .window
+            .connect_focus_out_event(clone!(handle => move |_widget, _event| {
+                if let Some(state) = handle.state.upgrade() {
+                    state.im_context.focus_out();
+                    if let Ok(mut handler) = state.handler.try_borrow_mut() {
+                        handler.lost_focus();
+                    }
+                }
+                Inhibit(false)
+            }));
+
+        win_state.window.connect_drag_data_get(
+            clone!(handle => move |_widget, _context, selection, info, _time| {
+                if let Some(state) = handle.state.upgrade() {
+                    if let Some(data) = state.drag_source.borrow().as_ref() {
+                        set_drag_data(selection, info, data);
+                    }
+                }
+            }),
+        );
+
+        win_state
+            .window
+            .connect_drag_end(clone!(handle => move |_widget, context| {
+                if let Some(state) = handle.state.upgrade() {
+                    state.drag_source.borrow_mut().take();
+                    let action = context.get_selected_action();
+                    let action = if action.contains(DragAction::MOVE) {
+                        Some(DropAction::Move)
+                    } else if action.contains(DragAction::COPY) {
+                        Some(DropAction::Copy)
+                    } else {
+                        None
+                    };
+                    state.handler.borrow_mut().drag_ended(action);
+                }
+            }));
+
         drawing_area.connect_destroy(clone!(handle => move |_widget| {
+            global_hotkey::unregister_window(&handle);
             if let Some(state) = handle.state.upgrade() {
                 state.handler.borrow_mut().destroy();
             }
         }));
 
+        if let Some(settings) = win_state.interface_settings.as_ref() {
+            settings.connect_changed(clone!(handle => move |settings, key| {
+                if let Some(state) = handle.state.upgrade() {
+                    if key == application::TEXT_SCALE_KEY {
+                        let scale = application::text_scale_from_settings(settings);
+                        state.handler.borrow_mut().text_scale_changed(scale);
+                    } else if key == application::ENABLE_ANIMATIONS_KEY {
+                        let reduce = !settings.get_boolean(key);
+                        state.handler.borrow_mut().reduce_motion_changed(reduce);
+                    }
+                }
+            }));
+        }
+
+        win_state
+            .window
+            .connect_property_scale_factor_notify(clone!(handle => move |window| {
+                if let Some(state) = handle.state.upgrade() {
+                    let scale = f64::from(window.get_scale_factor());
+                    state.handler.borrow_mut().scale_changed(scale);
+                    window.queue_draw();
+                }
+            }));
+
+        if let Some(screen) = win_state.window.get_screen() {
+            screen.connect_monitors_changed(clone!(handle => move |_screen| {
+                if let Some(state) = handle.state.upgrade() {
+                    state.handler.borrow_mut().monitors_changed();
+                }
+            }));
+        }
+
         vbox.pack_end(&drawing_area, true, true, 0);
 
         win_state
@@ -455,6 +693,30 @@ impl WindowHandle {
         }
     }
 
+    pub fn make_cursor(&self, desc: &CursorDesc) -> Option<Cursor> {
+        let display = self.state.upgrade()?.window.get_display()?;
+        // cairo wants premultiplied ARGB, as native-endian 32-bit values
+        let mut data = Vec::with_capacity(desc.rgba.len());
+        for pixel in desc.rgba.chunks_exact(4) {
+            let alpha = u32::from(pixel[3]);
+            let premul = |c: u8| (u32::from(c) * alpha + 127) / 255;
+            let argb =
+                alpha << 24 | premul(pixel[0]) << 16 | premul(pixel[1]) << 8 | premul(pixel[2]);
+            data.extend_from_slice(&argb.to_ne_bytes());
+        }
+        let surface = cairo::ImageSurface::create_for_data(
+            data,
+            cairo::Format::ARgb32,
+            desc.width as i32,
+            desc.height as i32,
+            desc.width as i32 * 4,
+        )
+        .map_err(|e| log::error!("failed to create cursor surface: {:?}", e))
+        .ok()?;
+        let cursor = gdk::Cursor::new_from_surface(&display, &surface, desc.hot.x, desc.hot.y);
+        Some(Cursor::Custom(CustomCursor(cursor)))
+    }
+
     pub fn open_file_sync(&mut self, options: FileDialogOptions) -> Option<FileInfo> {
         self.file_dialog(FileDialogType::Open, options)
             .ok()
@@ -487,6 +749,13 @@ impl WindowHandle {
             .unwrap_or(96.0)
     }
 
+    pub fn get_scale(&self) -> f64 {
+        self.state
+            .upgrade()
+            .map(|s| f64::from(s.window.get_scale_factor()))
+            .unwrap_or(1.0)
+    }
+
     // TODO: the following methods are cut'n'paste code. A good way to DRY
     // would be to have a platform-independent trait with these as methods with
     // default implementations.
@@ -535,16 +804,171 @@ impl WindowHandle {
         }
     }
 
-    pub fn show_context_menu(&self, menu: Menu, _pos: Point) {
+    pub fn set_menu_item_enabled(&self, id: u32, enabled: bool) {
+        if let Some(item) = self.menu_item(id) {
+            item.set_sensitive(enabled);
+        }
+    }
+
+    pub fn set_menu_item_title(&self, id: u32, title: &str) {
+        if let Some(item) = self.menu_item(id) {
+            menu::set_item_title(&item, title);
+        }
+    }
+
+    pub fn set_menu_item_hidden(&self, id: u32, hidden: bool) {
+        if let Some(item) = self.menu_item(id) {
+            item.set_visible(!hidden);
+        }
+    }
+
+    fn menu_item(&self, id: u32) -> Option<gtk::MenuItem> {
+        let state = self.state.upgrade()?;
+        let item = state.menu_items.borrow().get(&id).cloned();
+        item
+    }
+
+    pub fn show_context_menu(&self, menu: Menu, pos: Point) {
         if let Some(state) = self.state.upgrade() {
             let window = &state.window;
 
             let accel_group = AccelGroup::new();
             window.add_accel_group(&accel_group);
 
+            // `pos` is relative to the drawing area, which has its own gdk window.
+            let origin = window.get_children()[0]
+                .clone()
+                .downcast::<gtk::Box>()
+                .ok()
+                .and_then(|vbox| {
+                    vbox.get_children()
+                        .into_iter()
+                        .find(|child| child.is::<gtk::DrawingArea>())
+                })
+                .and_then(|area| area.get_window())
+                .map(|gdk_window| {
+                    let (_, x, y) = gdk_window.get_origin();
+                    (x, y)
+                });
+
             let menu = menu.into_gtk_menu(&self, &accel_group);
             menu.show_all();
-            menu.popup_easy(3, gtk::get_current_event_time());
+            match origin {
+                Some((x, y)) => {
+                    let (menu_x, menu_y) = (x + pos.x.round() as i32, y + pos.y.round() as i32);
+                    menu.popup(
+                        None::<&gtk::Widget>,
+                        None::<&gtk::Widget>,
+                        move |_, x, y| {
+                            *x = menu_x;
+                            *y = menu_y;
+                            // keep the menu on screen when it would cross an edge
+                            true
+                        },
+                        3,
+                        gtk::get_current_event_time(),
+                    );
+                }
+                None => menu.popup_easy(3, gtk::get_current_event_time()),
+            }
+        }
+    }
+
+    pub fn set_progress(&self, _progress: Option<f64>) {
+        log::warn!("set_progress is currently unimplemented for gtk.");
+    }
+
+    pub fn set_touch_bar(&self, _touch_bar: TouchBar) {}
+
+    pub fn register_global_hotkey(&self, hotkey: &HotKey) -> Option<GlobalHotKeyToken> {
+        global_hotkey::register(self, hotkey)
+    }
+
+    pub fn unregister_global_hotkey(&self, token: GlobalHotKeyToken) {
+        global_hotkey::unregister(token);
+    }
+
+    pub(crate) fn global_hotkey_pressed(&self, token: GlobalHotKeyToken) {
+        if let Some(state) = self.state.upgrade() {
+            if let Ok(mut handler) = state.handler.try_borrow_mut() {
+                handler.global_hotkey(token);
+            }
+        }
+    }
+
+    pub fn set_badge(&self, _badge: Option<&str>) {
+        log::warn!("set_badge is currently unimplemented for gtk.");
+    }
+
+    pub fn set_always_on_top(&self, on_top: bool) {
+        if let Some(state) = self.state.upgrade() {
+            state.window.set_keep_above(on_top);
+        }
+    }
+
+    pub fn set_skip_taskbar(&self, skip: bool) {
+        if let Some(state) = self.state.upgrade() {
+            set_skip_taskbar(&state.window, skip);
+        }
+    }
+
+    pub fn set_utility(&self, utility: bool) {
+        if let Some(state) = self.state.upgrade() {
+            let window = &state.window;
+            // the window manager only reads the hint when the window is
+            // mapped, so a shown window is hidden and shown again in place
+            if window.get_mapped() {
+                let (x, y) = window.get_position();
+                window.hide();
+                window.set_type_hint(type_hint(utility));
+                window.move_(x, y);
+                window.show_all();
+            } else {
+                window.set_type_hint(type_hint(utility));
+            }
+        }
+    }
+
+    pub fn set_icon(&self, icon: Option<&Icon>) {
+        if let Some(state) = self.state.upgrade() {
+            let pixbuf = icon.map(make_pixbuf);
+            state.window.set_icon(pixbuf.as_ref());
+        }
+    }
+
+    pub fn start_drag(&self, data: DragData, image: Option<&Icon>, allow_move: bool) {
+        if let Some(state) = self.state.upgrade() {
+            let targets = TargetList::new(&[]);
+            if !data.files.is_empty() || !data.urls.is_empty() {
+                targets.add_uri_targets(DRAG_INFO_URIS);
+            }
+            if data.text.is_some() {
+                targets.add_text_targets(DRAG_INFO_TEXT);
+            }
+            for (i, format) in data.formats.iter().enumerate() {
+                let atom = Atom::intern(format.identifier);
+                targets.add(&atom, 0, DRAG_INFO_FORMATS + i as u32);
+            }
+
+            let mut actions = DragAction::COPY;
+            if allow_move {
+                actions |= DragAction::MOVE;
+            }
+
+            state.drag_source.replace(Some(data));
+            let event = gtk::get_current_event();
+            let context = state.window.drag_begin_with_coordinates(
+                &targets,
+                actions,
+                1,
+                event.as_ref(),
+                -1,
+                -1,
+            );
+            if let (Some(context), Some(image)) = (context, image) {
+                let (hot_x, hot_y) = (image.width as i32 / 2, image.height as i32 / 2);
+                context.drag_set_icon_pixbuf(&make_pixbuf(image), hot_x, hot_y);
+            }
         }
     }
 
@@ -569,6 +993,118 @@ impl WindowHandle {
     }
 }
 
+/// A pixbuf holding a copy of `icon`.
+pub(crate) fn make_pixbuf(icon: &Icon) -> Pixbuf {
+    Pixbuf::new_from_mut_slice(
+        icon.rgba.clone(),
+        Colorspace::Rgb,
+        true,
+        8,
+        icon.width as i32,
+        icon.height as i32,
+        icon.width as i32 * 4,
+    )
+}
+
+fn set_skip_taskbar(window: &gtk::Window, skip: bool) {
+    window.set_skip_taskbar_hint(skip);
+    window.set_skip_pager_hint(skip);
+}
+
+fn type_hint(utility: bool) -> gdk::WindowTypeHint {
+    if utility {
+        gdk::WindowTypeHint::Utility
+    } else {
+        gdk::WindowTypeHint::Normal
+    }
+}
+
+// These are provided by GDK's X11 and Wayland backends, which gdk-sys does
+// not bind.
+#[cfg(feature = "raw-win-handle")]
+extern "C" {
+    fn gdk_x11_window_get_xid(window: *mut gdk_sys::GdkWindow) -> std::os::raw::c_ulong;
+    fn gdk_x11_display_get_xdisplay(display: *mut gdk_sys::GdkDisplay) -> *mut c_void;
+    fn gdk_x11_screen_get_screen_number(screen: *mut gdk_sys::GdkScreen) -> c_int;
+    fn gdk_wayland_window_get_wl_surface(window: *mut gdk_sys::GdkWindow) -> *mut c_void;
+    fn gdk_wayland_display_get_wl_display(display: *mut gdk_sys::GdkDisplay) -> *mut c_void;
+}
+
+#[cfg(feature = "raw-win-handle")]
+impl WindowHandle {
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        use glib::translate::ToGlibPtr;
+
+        let gdk_window = match self.state.upgrade().and_then(|s| s.window.get_window()) {
+            Some(window) => window,
+            None => return RawWindowHandle::Xlib(XlibWindowHandle::empty()),
+        };
+        let window_ptr: *mut gdk_sys::GdkWindow = gdk_window.to_glib_none().0;
+        match gdk_backend(&gdk_window.get_display()) {
+            GdkBackend::Wayland => {
+                let mut handle = WaylandWindowHandle::empty();
+                handle.surface = unsafe { gdk_wayland_window_get_wl_surface(window_ptr) };
+                RawWindowHandle::Wayland(handle)
+            }
+            GdkBackend::X11 => {
+                let mut handle = XlibWindowHandle::empty();
+                handle.window = unsafe { gdk_x11_window_get_xid(window_ptr) };
+                RawWindowHandle::Xlib(handle)
+            }
+            GdkBackend::Other => {
+                log::error!("raw window handles are only available on X11 and Wayland");
+                RawWindowHandle::Xlib(XlibWindowHandle::empty())
+            }
+        }
+    }
+
+    pub fn raw_display_handle(&self) -> RawDisplayHandle {
+        use glib::translate::ToGlibPtr;
+
+        let display = match gdk::Display::get_default() {
+            Some(display) => display,
+            None => return RawDisplayHandle::Xlib(XlibDisplayHandle::empty()),
+        };
+        let display_ptr: *mut gdk_sys::GdkDisplay = display.to_glib_none().0;
+        match gdk_backend(&display) {
+            GdkBackend::Wayland => {
+                let mut handle = WaylandDisplayHandle::empty();
+                handle.display = unsafe { gdk_wayland_display_get_wl_display(display_ptr) };
+                RawDisplayHandle::Wayland(handle)
+            }
+            GdkBackend::X11 => {
+                let mut handle = XlibDisplayHandle::empty();
+                handle.display = unsafe { gdk_x11_display_get_xdisplay(display_ptr) };
+                let screen = display.get_default_screen();
+                let screen_ptr: *mut gdk_sys::GdkScreen = screen.to_glib_none().0;
+                handle.screen = unsafe { gdk_x11_screen_get_screen_number(screen_ptr) };
+                RawDisplayHandle::Xlib(handle)
+            }
+            GdkBackend::Other => {
+                log::error!("raw display handles are only available on X11 and Wayland");
+                RawDisplayHandle::Xlib(XlibDisplayHandle::empty())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "raw-win-handle")]
+enum GdkBackend {
+    X11,
+    Wayland,
+    Other,
+}
+
+/// GDK picks its backend at runtime; the display's type tells us which.
+#[cfg(feature = "raw-win-handle")]
+fn gdk_backend(display: &gdk::Display) -> GdkBackend {
+    match display.get_type().name().as_str() {
+        "GdkX11Display" => GdkBackend::X11,
+        "GdkWaylandDisplay" => GdkBackend::Wayland,
+        _ => GdkBackend::Other,
+    }
+}
+
 unsafe impl Send for IdleHandle {}
 // WindowState needs to be Send + Sync so it can be passed into glib closures
 unsafe impl Send for WindowState {}
@@ -625,19 +1161,23 @@ fn run_idle(state: &Arc<WindowState>) -> bool {
 }
 
 fn make_gdk_cursor(cursor: &Cursor, gdk_window: &gdk::Window) -> Option<gdk::Cursor> {
-    gdk::Cursor::new_from_name(
-        &gdk_window.get_display(),
-        match cursor {
-            // cursor name values from https://www.w3.org/TR/css-ui-3/#cursor
-            Cursor::Arrow => "default",
-            Cursor::IBeam => "text",
-            Cursor::Crosshair => "crosshair",
-            Cursor::OpenHand => "grab",
-            Cursor::NotAllowed => "not-allowed",
-            Cursor::ResizeLeftRight => "ew-resize",
-            Cursor::ResizeUpDown => "ns-resize",
-        },
-    )
+    // cursor name values from https://www.w3.org/TR/css-ui-3/#cursor
+    let name = match cursor {
+        Cursor::Arrow => "default",
+        Cursor::IBeam => "text",
+        Cursor::Crosshair => "crosshair",
+        Cursor::OpenHand => "grab",
+        Cursor::ClosedHand => "grabbing",
+        Cursor::NotAllowed => "not-allowed",
+        Cursor::ResizeLeftRight => "ew-resize",
+        Cursor::ResizeUpDown => "ns-resize",
+        Cursor::ResizeTopLeftBottomRight => "nwse-resize",
+        Cursor::ResizeTopRightBottomLeft => "nesw-resize",
+        Cursor::ZoomIn => "zoom-in",
+        Cursor::ZoomOut => "zoom-out",
+        Cursor::Custom(custom) => return Some(custom.0.clone()),
+    };
+    gdk::Cursor::new_from_name(&gdk_window.get_display(), name)
 }
 
 fn get_mouse_button(button: u32) -> MouseButton {
@@ -735,3 +1275,48 @@ fn hardware_keycode_to_keyval(keycode: u16) -> Option<u32> {
         }
     }
 }
+
+/// Put the part of a drag's data that the drop target asked for into
+/// `selection`, by the target `info` it was offered under.
+fn set_drag_data(selection: &SelectionData, info: u32, data: &DragData) {
+    match info {
+        DRAG_INFO_URIS => {
+            let mut uris: Vec<String> = data
+                .files
+                .iter()
+                .filter_map(|path| glib::filename_to_uri(path, None).ok())
+                .map(|uri| uri.to_string())
+                .collect();
+            uris.extend(data.urls.iter().cloned());
+            let uris: Vec<&str> = uris.iter().map(String::as_str).collect();
+            selection.set_uris(&uris);
+        }
+        DRAG_INFO_TEXT => {
+            if let Some(text) = data.text.as_ref() {
+                selection.set_text(text);
+            }
+        }
+        _ => {
+            let idx = (info - DRAG_INFO_FORMATS) as usize;
+            if let Some(format) = data.formats.get(idx) {
+                selection.set(&selection.get_target(), 8, &format.data);
+            }
+        }
+    }
+}
+
+/// Extract the files, urls and text from a drop.
+fn get_drag_data(selection: &SelectionData) -> DragData {
+    let mut data = DragData::default();
+    for uri in selection.get_uris() {
+        match glib::filename_from_uri(&uri) {
+            Ok((path, _)) => data.files.push(path),
+            Err(_) => data.urls.push(uri.to_string()),
+        }
+    }
+    // a uri list is also offered as text, and we don't want it twice
+    if data.files.is_empty() && data.urls.is_empty() {
+        data.text = selection.get_text().map(|text| text.to_string());
+    }
+    data
+}
@@ -16,11 +16,14 @@
 
 #![allow(non_upper_case_globals)]
 
+use std::path::{Path, PathBuf};
+
 use super::clipboard::Clipboard;
-use super::util;
+use super::util::{self, from_nsstring, make_nsstring};
+use crate::Error;
 
 use cocoa::appkit::{NSApp, NSApplication, NSApplicationActivationPolicyRegular};
-use cocoa::base::{id, nil, YES};
+use cocoa::base::{id, nil, BOOL, NO, YES};
 use cocoa::foundation::NSAutoreleasePool;
 use objc::declare::ClassDecl;
 use objc::runtime::{Class, Object, Sel};
@@ -44,6 +47,16 @@ impl Application {
         }
     }
 
+    /// Use the host's `NSApplication` as it is, leaving its delegate and
+    /// activation policy alone.
+    #[cfg(feature = "raw-win-handle")]
+    pub fn embedded() -> Application {
+        util::assert_main_thread();
+        Application {
+            ns_app: unsafe { NSApp() },
+        }
+    }
+
     pub fn run(&mut self) {
         unsafe {
             self.ns_app.run();
@@ -88,6 +101,104 @@ impl Application {
             locale
         }
     }
+
+    pub fn get_text_scale() -> f64 {
+        // macOS has no system-wide text size preference for AppKit apps.
+        1.0
+    }
+
+    pub fn get_reduce_motion() -> bool {
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            // only available on 10.12 and later
+            let sel = sel!(accessibilityDisplayShouldReduceMotion);
+            let available: BOOL = msg_send![workspace, respondsToSelector: sel];
+            if available == NO {
+                return false;
+            }
+            let reduce: BOOL = msg_send![workspace, accessibilityDisplayShouldReduceMotion];
+            reduce == YES
+        }
+    }
+
+    pub fn open_url(url: &str) -> Result<(), Error> {
+        unsafe {
+            let nsurl: id = msg_send![class!(NSURL), URLWithString: make_nsstring(url)];
+            if nsurl == nil {
+                return Err(Error::Other("invalid url"));
+            }
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let ok: BOOL = msg_send![workspace, openURL: nsurl];
+            workspace_result(ok, "failed to open url")
+        }
+    }
+
+    pub fn open_path(path: &Path) -> Result<(), Error> {
+        let path = path
+            .to_str()
+            .ok_or(Error::Other("path is not valid unicode"))?;
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let ok: BOOL = msg_send![workspace, openFile: make_nsstring(path)];
+            workspace_result(ok, "failed to open file")
+        }
+    }
+
+    pub fn reveal_path(path: &Path) -> Result<(), Error> {
+        let path = path
+            .to_str()
+            .ok_or(Error::Other("path is not valid unicode"))?;
+        unsafe {
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let ok: BOOL = msg_send![workspace, selectFile: make_nsstring(path) inFileViewerRootedAtPath: make_nsstring("")];
+            workspace_result(ok, "failed to reveal file")
+        }
+    }
+
+    pub fn add_recent_document(path: &Path) {
+        let path = match path.to_str() {
+            Some(path) => path,
+            None => {
+                log::warn!("recent document path is not valid unicode");
+                return;
+            }
+        };
+        unsafe {
+            let url: id = msg_send![class!(NSURL), fileURLWithPath: make_nsstring(path)];
+            let controller: id = msg_send![class!(NSDocumentController), sharedDocumentController];
+            let () = msg_send![controller, noteNewRecentDocumentURL: url];
+        }
+    }
+
+    pub fn recent_documents() -> Vec<PathBuf> {
+        unsafe {
+            let controller: id = msg_send![class!(NSDocumentController), sharedDocumentController];
+            let urls: id = msg_send![controller, recentDocumentURLs];
+            let count: usize = msg_send![urls, count];
+            (0..count)
+                .map(|i| {
+                    let url: id = msg_send![urls, objectAtIndex: i];
+                    let path: id = msg_send![url, path];
+                    PathBuf::from(from_nsstring(path))
+                })
+                .collect()
+        }
+    }
+
+    pub fn clear_recent_documents() {
+        unsafe {
+            let controller: id = msg_send![class!(NSDocumentController), sharedDocumentController];
+            let () = msg_send![controller, clearRecentDocuments: nil];
+        }
+    }
+}
+
+fn workspace_result(ok: BOOL, message: &'static str) -> Result<(), Error> {
+    if ok == YES {
+        Ok(())
+    } else {
+        Err(Error::Other(message))
+    }
 }
 
 struct AppDelegate(*const Class);
@@ -102,6 +213,10 @@ lazy_static! {
             sel!(applicationDidFinishLaunching:),
             application_did_finish_launching as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(application:openFiles:),
+            application_open_files as extern "C" fn(&mut Object, Sel, id, id),
+        );
         AppDelegate(decl.register())
     };
 }
@@ -111,3 +226,22 @@ extern "C" fn application_did_finish_launching(_this: &mut Object, _: Sel, _noti
         let () = msg_send![NSApp(), activateIgnoringOtherApps: YES];
     }
 }
+
+/// Called when the user opens documents with the app, for instance from the
+/// Finder.
+extern "C" fn application_open_files(_this: &mut Object, _: Sel, app: id, filenames: id) {
+    let paths = unsafe {
+        let count: usize = msg_send![filenames, count];
+        (0..count)
+            .map(|i| {
+                let name: id = msg_send![filenames, objectAtIndex: i];
+                PathBuf::from(from_nsstring(name))
+            })
+            .collect()
+    };
+    crate::application::open_files(paths);
+    unsafe {
+        // NSApplicationDelegateReplySuccess
+        let () = msg_send![app, replyToOpenOrPrint: 0usize];
+    }
+}
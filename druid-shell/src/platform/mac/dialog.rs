@@ -18,7 +18,7 @@
 
 use std::ffi::OsString;
 
-use cocoa::base::{id, nil, YES};
+use cocoa::base::{id, nil, NO, YES};
 use cocoa::foundation::{NSArray, NSInteger};
 
 use super::util::{from_nsstring, make_nsstring};
@@ -42,6 +42,26 @@ pub(crate) fn get_file_dialog_path(
             let () = msg_send![panel, setShowsHiddenFiles: YES];
         }
 
+        if let (FileDialogType::Open, true) = (&ty, options.select_directories) {
+            let () = msg_send![panel, setCanChooseDirectories: YES];
+            let () = msg_send![panel, setCanChooseFiles: NO];
+        }
+
+        if let Some(name) = &options.default_name {
+            let () = msg_send![panel, setNameFieldStringValue: make_nsstring(name)];
+        }
+
+        if let Some(dir) = &options.starting_directory {
+            let path = make_nsstring(&dir.to_string_lossy());
+            let url: id = msg_send![class!(NSURL), fileURLWithPath: path isDirectory: YES];
+            let () = msg_send![panel, setDirectoryURL: url];
+        }
+
+        if let Some(allowed) = options.can_create_directories {
+            let allowed = if allowed { YES } else { NO };
+            let () = msg_send![panel, setCanCreateDirectories: allowed];
+        }
+
         // A vector of NSStrings. this must outlive `nsarray_allowed_types`.
         let allowed_types = options.allowed_types.as_ref().map(|specs| {
             specs
@@ -0,0 +1,276 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Global hotkeys, which AppKit has no API for, registered with Carbon.
+
+use std::cell::{Cell, RefCell};
+use std::ffi::c_void;
+use std::ptr;
+
+use cocoa::base::id;
+
+use super::window::WindowHandle;
+use crate::hotkey::{HotKey, KeyCompare};
+use crate::keyboard::KeyModifiers;
+use crate::keycodes::KeyCode;
+use crate::window::GlobalHotKeyToken;
+
+type OSStatus = i32;
+type EventRef = *mut c_void;
+type EventTargetRef = *mut c_void;
+type EventHotKeyRef = *mut c_void;
+type EventHandlerUPP =
+    unsafe extern "C" fn(call: *mut c_void, event: EventRef, data: *mut c_void) -> OSStatus;
+
+#[repr(C)]
+struct EventTypeSpec {
+    class: u32,
+    kind: u32,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct EventHotKeyID {
+    signature: u32,
+    id: u32,
+}
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    fn GetApplicationEventTarget() -> EventTargetRef;
+    fn InstallEventHandler(
+        target: EventTargetRef,
+        handler: EventHandlerUPP,
+        type_count: usize,
+        types: *const EventTypeSpec,
+        data: *mut c_void,
+        handler_ref: *mut *mut c_void,
+    ) -> OSStatus;
+    fn RegisterEventHotKey(
+        key_code: u32,
+        modifiers: u32,
+        id: EventHotKeyID,
+        target: EventTargetRef,
+        options: u32,
+        hotkey_ref: *mut EventHotKeyRef,
+    ) -> OSStatus;
+    fn UnregisterEventHotKey(hotkey: EventHotKeyRef) -> OSStatus;
+    fn GetEventParameter(
+        event: EventRef,
+        name: u32,
+        desired_type: u32,
+        actual_type: *mut u32,
+        size: usize,
+        actual_size: *mut usize,
+        data: *mut c_void,
+    ) -> OSStatus;
+}
+
+const NO_ERR: OSStatus = 0;
+const EVENT_NOT_HANDLED_ERR: OSStatus = -9874;
+
+/// `kEventClassKeyboard`, `'keyb'`.
+const EVENT_CLASS_KEYBOARD: u32 = 0x6b65_7962;
+/// `kEventHotKeyPressed`.
+const EVENT_HOT_KEY_PRESSED: u32 = 5;
+/// `kEventParamDirectObject`, `'----'`.
+const EVENT_PARAM_DIRECT_OBJECT: u32 = 0x2d2d_2d2d;
+/// `typeEventHotKeyID`, `'hkid'`.
+const TYPE_EVENT_HOT_KEY_ID: u32 = 0x686b_6964;
+/// Tells our hotkeys from those of other code in the app, `'drud'`.
+const SIGNATURE: u32 = 0x6472_7564;
+
+const CMD_KEY: u32 = 1 << 8;
+const SHIFT_KEY: u32 = 1 << 9;
+const OPTION_KEY: u32 = 1 << 11;
+const CONTROL_KEY: u32 = 1 << 12;
+
+struct Registered {
+    token: GlobalHotKeyToken,
+    window: WindowHandle,
+    hotkey_ref: EventHotKeyRef,
+}
+
+thread_local! {
+    /// Every hotkey registered by the app, which is only ever touched on
+    /// the main thread.
+    static REGISTERED: RefCell<Vec<Registered>> = const { RefCell::new(Vec::new()) };
+    /// Whether our handler has been installed, which is done once.
+    static HANDLER_INSTALLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Register `hotkey` for `window`, whose handler is told when it is
+/// pressed.
+pub(crate) fn register(window: &WindowHandle, hotkey: &HotKey) -> Option<GlobalHotKeyToken> {
+    let key_code = match key_code(hotkey) {
+        Some(key_code) => key_code,
+        None => {
+            log::warn!("no key for global hotkey {:?}", hotkey);
+            return None;
+        }
+    };
+    install_handler();
+    let token = GlobalHotKeyToken::next();
+    let id = EventHotKeyID {
+        signature: SIGNATURE,
+        id: token.into_raw() as u32,
+    };
+    let mut hotkey_ref = ptr::null_mut();
+    let status = unsafe {
+        RegisterEventHotKey(
+            u32::from(key_code),
+            carbon_modifiers(hotkey.mods.into()),
+            id,
+            GetApplicationEventTarget(),
+            0,
+            &mut hotkey_ref,
+        )
+    };
+    if status != NO_ERR {
+        log::warn!("failed to register global hotkey {:?}: {}", hotkey, status);
+        return None;
+    }
+    REGISTERED.with(|registered| {
+        registered.borrow_mut().push(Registered {
+            token,
+            window: window.clone(),
+            hotkey_ref,
+        })
+    });
+    Some(token)
+}
+
+/// Unregister the hotkey registered with `token`.
+pub(crate) fn unregister(token: GlobalHotKeyToken) {
+    remove(|registered| registered.token == token);
+}
+
+/// Unregister all the hotkeys registered for the window of `view`, which
+/// is closing.
+pub(crate) fn unregister_view(view: id) {
+    remove(|registered| registered.window.has_view(view));
+}
+
+fn remove(mut matches: impl FnMut(&Registered) -> bool) {
+    REGISTERED.with(|registered| {
+        registered.borrow_mut().retain(|registered| {
+            if matches(registered) {
+                unsafe {
+                    UnregisterEventHotKey(registered.hotkey_ref);
+                }
+                false
+            } else {
+                true
+            }
+        })
+    });
+}
+
+fn install_handler() {
+    HANDLER_INSTALLED.with(|installed| {
+        if installed.get() {
+            return;
+        }
+        let types = [EventTypeSpec {
+            class: EVENT_CLASS_KEYBOARD,
+            kind: EVENT_HOT_KEY_PRESSED,
+        }];
+        let status = unsafe {
+            InstallEventHandler(
+                GetApplicationEventTarget(),
+                hotkey_pressed,
+                types.len(),
+                types.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+        if status == NO_ERR {
+            installed.set(true);
+        } else {
+            log::warn!("failed to install the global hotkey handler: {}", status);
+        }
+    });
+}
+
+/// The virtual key code of `hotkey`.
+///
+/// Like the rest of Carbon's key codes these are for keys, not characters,
+/// so a text hotkey is the key with that character on a US keyboard.
+fn key_code(hotkey: &HotKey) -> Option<u16> {
+    let code = match hotkey.key {
+        KeyCompare::Code(code) => code,
+        KeyCompare::Text(text) => {
+            let c = text.chars().next()?.to_ascii_uppercase();
+            if !c.is_ascii_alphanumeric() {
+                return None;
+            }
+            KeyCode::from_name(&format!("Key{}", c))?
+        }
+    };
+    (0..0x80).find(|&raw| KeyCode::from(raw) == code)
+}
+
+fn carbon_modifiers(mods: KeyModifiers) -> u32 {
+    let mut result = 0;
+    if mods.shift {
+        result |= SHIFT_KEY;
+    }
+    if mods.ctrl {
+        result |= CONTROL_KEY;
+    }
+    if mods.alt {
+        result |= OPTION_KEY;
+    }
+    if mods.meta {
+        result |= CMD_KEY;
+    }
+    result
+}
+
+unsafe extern "C" fn hotkey_pressed(
+    _call: *mut c_void,
+    event: EventRef,
+    _data: *mut c_void,
+) -> OSStatus {
+    let mut id = EventHotKeyID::default();
+    let status = GetEventParameter(
+        event,
+        EVENT_PARAM_DIRECT_OBJECT,
+        TYPE_EVENT_HOT_KEY_ID,
+        ptr::null_mut(),
+        std::mem::size_of::<EventHotKeyID>(),
+        ptr::null_mut(),
+        &mut id as *mut EventHotKeyID as *mut c_void,
+    );
+    if status != NO_ERR || id.signature != SIGNATURE {
+        return EVENT_NOT_HANDLED_ERR;
+    }
+    // the handler may register or unregister hotkeys, so it is called once
+    // they are no longer borrowed
+    let pressed = REGISTERED.with(|registered| {
+        registered
+            .borrow()
+            .iter()
+            .find(|registered| registered.token.into_raw() as u32 == id.id)
+            .map(|registered| (registered.window.clone(), registered.token))
+    });
+    match pressed {
+        Some((window, token)) => {
+            window.global_hotkey_pressed(token);
+            NO_ERR
+        }
+        None => EVENT_NOT_HANDLED_ERR,
+    }
+}
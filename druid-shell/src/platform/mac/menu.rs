@@ -15,14 +15,16 @@
 //! macOS implementation of menus.
 
 use cocoa::appkit::{NSEventModifierFlags, NSMenu, NSMenuItem};
-use cocoa::base::{id, nil, NO};
+use cocoa::base::{id, nil, BOOL, NO};
 use cocoa::foundation::NSAutoreleasePool;
 
 use super::util::make_nsstring;
+use super::window::make_nsimage;
 use crate::common_util::strip_access_key;
-use crate::hotkey::{HotKey, KeyCompare};
+use crate::hotkey::{HotKey, KeyCompare, ModifierNames};
 use crate::keyboard::KeyModifiers;
 use crate::keycodes::KeyCode;
+use crate::window::Icon;
 
 pub struct Menu {
     pub menu: id,
@@ -97,6 +99,40 @@ impl Menu {
         }
     }
 
+    /// Hotkeys are shown with glyphs, not names.
+    pub fn set_modifier_names(&mut self, _names: ModifierNames) {}
+
+    pub fn set_item_icon(&mut self, id: u32, icon: &str) {
+        unsafe {
+            let item: id = msg_send![self.menu, itemWithTag: id as isize];
+            let image: id = msg_send![class!(NSImage), imageNamed: make_nsstring(icon)];
+            if item != nil && image != nil {
+                let () = msg_send![item, setImage: image];
+            }
+        }
+    }
+
+    pub fn set_item_image(&mut self, id: u32, image: &Icon) {
+        unsafe {
+            let item: id = msg_send![self.menu, itemWithTag: id as isize];
+            if item != nil {
+                let () = msg_send![item, setImage: make_nsimage(image)];
+            }
+        }
+    }
+
+    /// macOS menus show the selected choice of a group with a checkmark too.
+    pub fn set_item_radio(&mut self, _id: u32, _radio: bool) {}
+
+    pub fn set_item_hidden(&mut self, id: u32, hidden: bool) {
+        unsafe {
+            let item: id = msg_send![self.menu, itemWithTag: id as isize];
+            if item != nil {
+                let () = msg_send![item, setHidden: hidden as BOOL];
+            }
+        }
+    }
+
     pub fn add_separator(&mut self) {
         unsafe {
             let sep = id::separatorItem(self.menu);
@@ -105,6 +141,29 @@ impl Menu {
     }
 }
 
+/// Find the item with the given tag in `menu` or any of its submenus,
+/// returning `nil` if there is none.
+pub(crate) fn find_item(menu: id, tag: u32) -> id {
+    unsafe {
+        let item: id = msg_send![menu, itemWithTag: tag as isize];
+        if item != nil {
+            return item;
+        }
+        let count: isize = msg_send![menu, numberOfItems];
+        for i in 0..count {
+            let item: id = msg_send![menu, itemAtIndex: i];
+            let submenu: id = msg_send![item, submenu];
+            if submenu != nil {
+                let found = find_item(submenu, tag);
+                if found != nil {
+                    return found;
+                }
+            }
+        }
+        nil
+    }
+}
+
 impl HotKey {
     /// Return the string value of this hotkey, for use with Cocoa `NSResponder`
     /// objects.
@@ -0,0 +1,51 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! macOS monitor information.
+
+use cocoa::base::id;
+use cocoa::foundation::{NSArray, NSRect};
+
+use crate::kurbo::Rect;
+use crate::screen::Monitor;
+
+pub(crate) fn get_monitors() -> Vec<Monitor> {
+    unsafe {
+        let screens: id = msg_send![class!(NSScreen), screens];
+        let count = screens.count();
+        if count == 0 {
+            return Vec::new();
+        }
+        // AppKit's origin is the bottom left of the first screen, y up.
+        let first: NSRect = msg_send![screens.objectAtIndex(0), frame];
+        let flip = |rect: NSRect| {
+            let y = first.size.height - (rect.origin.y + rect.size.height);
+            Rect::from_origin_size((rect.origin.x, y), (rect.size.width, rect.size.height))
+        };
+        (0..count)
+            .map(|i| {
+                let screen = screens.objectAtIndex(i);
+                let frame: NSRect = msg_send![screen, frame];
+                let visible: NSRect = msg_send![screen, visibleFrame];
+                let scale: f64 = msg_send![screen, backingScaleFactor];
+                Monitor {
+                    primary: i == 0,
+                    rect: flip(frame),
+                    work_rect: flip(visible),
+                    scale,
+                }
+            })
+            .collect()
+    }
+}
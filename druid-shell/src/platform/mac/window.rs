@@ -19,6 +19,7 @@
 use std::any::Any;
 use std::ffi::c_void;
 use std::mem;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex, Weak};
 use std::time::Instant;
 
@@ -28,30 +29,73 @@ use cocoa::appkit::{
     NSWindowStyleMask,
 };
 use cocoa::base::{id, nil, BOOL, NO, YES};
-use cocoa::foundation::{NSAutoreleasePool, NSPoint, NSRect, NSSize, NSString};
+use cocoa::foundation::{
+    NSArray, NSAutoreleasePool, NSInteger, NSNotFound, NSPoint, NSRect, NSSize, NSString,
+    NSUInteger,
+};
 use objc::declare::ClassDecl;
 use objc::rc::WeakPtr;
-use objc::runtime::{Class, Object, Sel};
+use objc::runtime::{Class, Object, Protocol, Sel};
+use objc::Encode;
 
 use cairo::{Context, QuartzSurface};
 use log::{error, info};
 
+#[cfg(feature = "raw-win-handle")]
+use raw_window_handle::{
+    AppKitDisplayHandle, AppKitWindowHandle, RawDisplayHandle, RawWindowHandle,
+};
+
 use crate::kurbo::{Point, Size, Vec2};
 use crate::piet::{Piet, RenderContext};
 
 use super::dialog;
-use super::menu::Menu;
-use super::util::{assert_main_thread, make_nsstring};
-use crate::common_util::IdleCallback;
+use super::global_hotkey;
+use super::menu::{self, Menu};
+use super::util::{assert_main_thread, from_nsstring, make_nsdata, make_nsstring};
+use crate::common_util::{strip_access_key, IdleCallback};
 use crate::dialog::{FileDialogOptions, FileDialogType, FileInfo};
+use crate::drag::{DragData, DropAction};
+use crate::hotkey::HotKey;
 use crate::keyboard::{KeyEvent, KeyModifiers};
 use crate::keycodes::KeyCode;
-use crate::mouse::{Cursor, MouseButton, MouseEvent};
-use crate::window::{IdleToken, Text, TimerToken, WinHandler};
+use crate::mouse::{Cursor, CursorDesc, MouseButton, MouseEvent};
+use crate::touch_bar::{TouchBar, TouchBarEvent, TouchBarItem};
+use crate::window::{GlobalHotKeyToken, Icon, IdleToken, Text, TimerToken, WinHandler};
 use crate::Error;
 
 #[allow(non_upper_case_globals)]
 const NSWindowDidBecomeKeyNotification: &str = "NSWindowDidBecomeKeyNotification";
+#[allow(non_upper_case_globals)]
+const NSWindowDidResignKeyNotification: &str = "NSWindowDidResignKeyNotification";
+#[allow(non_upper_case_globals)]
+const NSApplicationDidChangeScreenParametersNotification: &str =
+    "NSApplicationDidChangeScreenParametersNotification";
+
+/// The pasteboard types that can be dragged to and from a window.
+const FILE_URL_TYPE: &str = "public.file-url";
+const URL_TYPE: &str = "public.url";
+const TEXT_TYPE: &str = "public.utf8-plain-text";
+
+#[allow(non_upper_case_globals)]
+const NSDragOperationCopy: NSUInteger = 1;
+#[allow(non_upper_case_globals)]
+const NSDragOperationMove: NSUInteger = 16;
+
+/// A cursor created from an image, released when the last clone is dropped.
+#[derive(Clone)]
+pub struct CustomCursor(Rc<RetainedCursor>);
+
+/// An `NSCursor` we own a reference to.
+struct RetainedCursor(id);
+
+impl Drop for RetainedCursor {
+    fn drop(&mut self) {
+        unsafe {
+            let () = msg_send![self.0, release];
+        }
+    }
+}
 
 #[derive(Clone)]
 pub(crate) struct WindowHandle {
@@ -76,6 +120,11 @@ pub(crate) struct WindowBuilder {
     title: String,
     menu: Option<Menu>,
     size: Size,
+    always_on_top: bool,
+    skip_taskbar: bool,
+    utility: bool,
+    #[cfg(feature = "raw-win-handle")]
+    parent: Option<RawWindowHandle>,
 }
 
 #[derive(Clone)]
@@ -96,6 +145,40 @@ struct ViewState {
     handler: Box<dyn WinHandler>,
     idle_queue: Arc<Mutex<Vec<IdleKind>>>,
     last_mods: KeyModifiers,
+    /// The key the text input system is interpreting, until it is sent as
+    /// text or as the key it is.
+    pending_key: Option<KeyEvent>,
+    /// The length of the text the text input system is composing, such as
+    /// the accent of a dead key.
+    marked_len: NSUInteger,
+    /// What may be done with data dragged out of the view.
+    drag_operations: NSUInteger,
+}
+
+/// An `NSRange`, which cocoa has no `Encode` for.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct TextRange {
+    location: NSUInteger,
+    length: NSUInteger,
+}
+
+impl TextRange {
+    const NOT_FOUND: TextRange = TextRange {
+        location: NSNotFound as NSUInteger,
+        length: 0,
+    };
+}
+
+unsafe impl Encode for TextRange {
+    fn encode() -> objc::Encoding {
+        let encoding = format!(
+            "{{_NSRange={}{}}}",
+            NSUInteger::encode().as_str(),
+            NSUInteger::encode().as_str()
+        );
+        unsafe { objc::Encoding::from_str(&encoding) }
+    }
 }
 
 impl WindowBuilder {
@@ -105,6 +188,11 @@ impl WindowBuilder {
             title: String::new(),
             menu: None,
             size: Size::new(500.0, 400.0),
+            always_on_top: false,
+            skip_taskbar: false,
+            utility: false,
+            #[cfg(feature = "raw-win-handle")]
+            parent: None,
         }
     }
 
@@ -124,8 +212,31 @@ impl WindowBuilder {
         self.menu = Some(menu);
     }
 
+    pub fn set_always_on_top(&mut self, on_top: bool) {
+        self.always_on_top = on_top;
+    }
+
+    pub fn set_skip_taskbar(&mut self, skip: bool) {
+        self.skip_taskbar = skip;
+    }
+
+    pub fn set_utility(&mut self, utility: bool) {
+        self.utility = utility;
+    }
+
+    #[cfg(feature = "raw-win-handle")]
+    pub fn set_parent(&mut self, parent: RawWindowHandle) {
+        self.parent = Some(parent);
+    }
+
     pub fn build(self) -> Result<WindowHandle, Error> {
         assert_main_thread();
+        #[cfg(feature = "raw-win-handle")]
+        {
+            if let Some(parent) = self.parent {
+                return self.build_embedded(parent);
+            }
+        }
         unsafe {
             let style_mask = NSWindowStyleMask::NSTitledWindowMask
                 | NSWindowStyleMask::NSClosableWindowMask
@@ -147,6 +258,9 @@ impl WindowBuilder {
             window.setTitle_(make_nsstring(&self.title));
             // TODO: this should probably be a tracking area instead
             window.setAcceptsMouseMovedEvents_(YES);
+            set_always_on_top(window, self.always_on_top);
+            set_skip_taskbar(window, self.skip_taskbar);
+            set_utility(window, self.utility);
 
             let (view, idle_queue) = make_view(self.handler.expect("view"));
             let content_view = window.contentView();
@@ -160,23 +274,56 @@ impl WindowBuilder {
             }
 
             content_view.addSubview_(view);
-            let view_state: *mut c_void = *(*view).get_ivar("viewState");
-            let view_state = &mut *(view_state as *mut ViewState);
-            let handle = WindowHandle {
-                nsview: view_state.nsview.clone(),
-                idle_queue,
-            };
-            (*view_state).handler.connect(&handle.clone().into());
-            (*view_state).handler.connected();
-            (*view_state)
-                .handler
-                .size(frame.size.width as u32, frame.size.height as u32);
+            Ok(connect_view(view, idle_queue, frame.size))
+        }
+    }
 
-            Ok(handle)
+    /// Put the view in `parent`, a view made by someone else, instead of in
+    /// a window of our own.
+    ///
+    /// The window that `parent` is in keeps its own delegate, so we don't
+    /// hear about it closing or losing focus.
+    #[cfg(feature = "raw-win-handle")]
+    fn build_embedded(self, parent: RawWindowHandle) -> Result<WindowHandle, Error> {
+        let parent = match parent {
+            RawWindowHandle::AppKit(handle) if !handle.ns_view.is_null() => handle.ns_view as id,
+            _ => {
+                return Err(Error::Other(
+                    "macOS windows can only be embedded in an NSView",
+                ))
+            }
+        };
+        unsafe {
+            let (view, idle_queue) = make_view(self.handler.expect("view"));
+            let frame = NSView::bounds(parent);
+            view.initWithFrame_(frame);
+            parent.addSubview_(view);
+            Ok(connect_view(view, idle_queue, frame.size))
         }
     }
 }
 
+/// Make the handle for a view that has just been added to the view
+/// hierarchy, and tell its handler about it.
+unsafe fn connect_view(
+    view: id,
+    idle_queue: Weak<Mutex<Vec<IdleKind>>>,
+    size: NSSize,
+) -> WindowHandle {
+    let view_state: *mut c_void = *(*view).get_ivar("viewState");
+    let view_state = &mut *(view_state as *mut ViewState);
+    let handle = WindowHandle {
+        nsview: view_state.nsview.clone(),
+        idle_queue,
+    };
+    (*view_state).handler.connect(&handle.clone().into());
+    (*view_state).handler.connected();
+    (*view_state)
+        .handler
+        .size(size.width as u32, size.height as u32);
+    handle
+}
+
 // Wrap pointer because lazy_static requires Sync.
 struct ViewClass(*const Class);
 unsafe impl Sync for ViewClass {}
@@ -185,6 +332,9 @@ lazy_static! {
     static ref VIEW_CLASS: ViewClass = unsafe {
         let mut decl = ClassDecl::new("DruidView", class!(NSView)).expect("View class defined");
         decl.add_ivar::<*mut c_void>("viewState");
+        // a `Box<TouchBar>`, kept apart from the view state so that it can be
+        // set while the handler is running.
+        decl.add_ivar::<*mut c_void>("touchBar");
 
         decl.add_method(
             sel!(isFlipped),
@@ -204,8 +354,13 @@ lazy_static! {
         extern "C" fn dealloc(this: &Object, _sel: Sel) {
             info!("view is dealloc'ed");
             unsafe {
+                remove_observers(this);
                 let view_state: *mut c_void = *this.get_ivar("viewState");
                 Box::from_raw(view_state as *mut ViewState);
+                let touch_bar: *mut c_void = *this.get_ivar("touchBar");
+                if !touch_bar.is_null() {
+                    drop(Box::from_raw(touch_bar as *mut TouchBar));
+                }
             }
         }
 
@@ -213,6 +368,18 @@ lazy_static! {
             sel!(windowDidBecomeKey:),
             window_did_become_key as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(windowDidResignKey:),
+            window_did_resign_key as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(screenParametersChanged:),
+            screen_parameters_changed as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(viewDidChangeBackingProperties),
+            view_did_change_backing_properties as extern "C" fn(&mut Object, Sel),
+        );
         decl.add_method(
             sel!(setFrameSize:),
             set_frame_size as extern "C" fn(&mut Object, Sel, NSSize),
@@ -254,6 +421,52 @@ lazy_static! {
             key_down as extern "C" fn(&mut Object, Sel, id),
         );
         decl.add_method(sel!(keyUp:), key_up as extern "C" fn(&mut Object, Sel, id));
+
+        // the text input system, which composes dead keys and the text of
+        // input methods, talks to the view through this protocol.
+        decl.add_protocol(Protocol::get("NSTextInputClient").unwrap());
+        decl.add_method(
+            sel!(insertText:replacementRange:),
+            insert_text as extern "C" fn(&mut Object, Sel, id, TextRange),
+        );
+        decl.add_method(
+            sel!(doCommandBySelector:),
+            do_command_by_selector as extern "C" fn(&mut Object, Sel, Sel),
+        );
+        decl.add_method(
+            sel!(setMarkedText:selectedRange:replacementRange:),
+            set_marked_text as extern "C" fn(&mut Object, Sel, id, TextRange, TextRange),
+        );
+        decl.add_method(sel!(unmarkText), unmark_text as extern "C" fn(&mut Object, Sel));
+        decl.add_method(
+            sel!(hasMarkedText),
+            has_marked_text as extern "C" fn(&mut Object, Sel) -> BOOL,
+        );
+        decl.add_method(
+            sel!(markedRange),
+            marked_range as extern "C" fn(&mut Object, Sel) -> TextRange,
+        );
+        decl.add_method(
+            sel!(selectedRange),
+            selected_range as extern "C" fn(&mut Object, Sel) -> TextRange,
+        );
+        decl.add_method(
+            sel!(validAttributesForMarkedText),
+            valid_attributes_for_marked_text as extern "C" fn(&mut Object, Sel) -> id,
+        );
+        decl.add_method(
+            sel!(attributedSubstringForProposedRange:actualRange:),
+            attributed_substring as extern "C" fn(&mut Object, Sel, TextRange, *mut c_void) -> id,
+        );
+        decl.add_method(
+            sel!(characterIndexForPoint:),
+            character_index_for_point as extern "C" fn(&mut Object, Sel, NSPoint) -> NSUInteger,
+        );
+        decl.add_method(
+            sel!(firstRectForCharacterRange:actualRange:),
+            first_rect_for_character_range
+                as extern "C" fn(&mut Object, Sel, TextRange, *mut c_void) -> NSRect,
+        );
         decl.add_method(
             sel!(flagsChanged:),
             mods_changed as extern "C" fn(&mut Object, Sel, id),
@@ -276,10 +489,54 @@ lazy_static! {
             sel!(showContextMenu:),
             show_context_menu as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(makeTouchBar),
+            make_touch_bar as extern "C" fn(&mut Object, Sel) -> id,
+        );
+        decl.add_method(
+            sel!(touchBar:makeItemForIdentifier:),
+            make_touch_bar_item as extern "C" fn(&mut Object, Sel, id, id) -> id,
+        );
+        decl.add_method(
+            sel!(touchBarButtonPressed:),
+            touch_bar_button_pressed as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(touchBarSliderChanged:),
+            touch_bar_slider_changed as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(touchBarSegmentSelected:),
+            touch_bar_segment_selected as extern "C" fn(&mut Object, Sel, id),
+        );
         decl.add_method(
             sel!(windowWillClose:),
             window_will_close as extern "C" fn(&mut Object, Sel, id),
         );
+        decl.add_method(
+            sel!(draggingEntered:),
+            dragging_updated as extern "C" fn(&mut Object, Sel, id) -> NSUInteger,
+        );
+        decl.add_method(
+            sel!(draggingUpdated:),
+            dragging_updated as extern "C" fn(&mut Object, Sel, id) -> NSUInteger,
+        );
+        decl.add_method(
+            sel!(draggingExited:),
+            dragging_exited as extern "C" fn(&mut Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(performDragOperation:),
+            perform_drag_operation as extern "C" fn(&mut Object, Sel, id) -> BOOL,
+        );
+        decl.add_method(
+            sel!(draggingSession:sourceOperationMaskForDraggingContext:),
+            drag_source_operations as extern "C" fn(&mut Object, Sel, id, NSInteger) -> NSUInteger,
+        );
+        decl.add_method(
+            sel!(draggingSession:endedAtPoint:operation:),
+            drag_ended as extern "C" fn(&mut Object, Sel, id, NSPoint, NSUInteger),
+        );
         ViewClass(decl.register())
     };
 }
@@ -295,11 +552,24 @@ fn make_view(handler: Box<dyn WinHandler>) -> (id, Weak<Mutex<Vec<IdleKind>>>) {
             handler,
             idle_queue,
             last_mods: KeyModifiers::default(),
+            pending_key: None,
+            marked_len: 0,
+            drag_operations: NSDragOperationCopy,
         };
         let state_ptr = Box::into_raw(Box::new(state));
         (*view).set_ivar("viewState", state_ptr as *mut c_void);
+        (*view).set_ivar("touchBar", std::ptr::null_mut::<c_void>());
         let options: NSAutoresizingMaskOptions = NSViewWidthSizable | NSViewHeightSizable;
         view.setAutoresizingMask_(options);
+        let types = NSArray::arrayWithObjects(
+            nil,
+            &[
+                make_nsstring(FILE_URL_TYPE),
+                make_nsstring(URL_TYPE),
+                make_nsstring(TEXT_TYPE),
+            ],
+        );
+        let () = msg_send![view, registerForDraggedTypes: types];
         (view.autorelease(), queue_handle)
     }
 }
@@ -316,6 +586,154 @@ extern "C" fn set_frame_size(this: &mut Object, _: Sel, size: NSSize) {
     }
 }
 
+/// Where a drag is in the view.
+unsafe fn dragging_location(view: id, info: id) -> Point {
+    let point: NSPoint = msg_send![info, draggingLocation];
+    let view_point = view.convertPoint_fromView_(point, nil);
+    Point::new(view_point.x as f64, view_point.y as f64)
+}
+
+/// Read what is being dropped from the dragging pasteboard.
+unsafe fn dragged_data(info: id) -> DragData {
+    let mut data = DragData::default();
+    let pasteboard: id = msg_send![info, draggingPasteboard];
+    let items: id = msg_send![pasteboard, pasteboardItems];
+    for i in 0..items.count() {
+        let item = items.objectAtIndex(i);
+        let file: id = msg_send![item, stringForType: make_nsstring(FILE_URL_TYPE)];
+        if !file.is_null() {
+            let url: id = msg_send![class!(NSURL), URLWithString: file];
+            let path: id = msg_send![url, path];
+            if !path.is_null() {
+                data.files.push(from_nsstring(path).into());
+            }
+            continue;
+        }
+        let url: id = msg_send![item, stringForType: make_nsstring(URL_TYPE)];
+        if !url.is_null() {
+            data.urls.push(from_nsstring(url));
+        }
+    }
+    // files and links are also offered as text, and we don't want them twice
+    if data.files.is_empty() && data.urls.is_empty() {
+        let text: id = msg_send![pasteboard, stringForType: make_nsstring(TEXT_TYPE)];
+        if !text.is_null() {
+            data.text = Some(from_nsstring(text));
+        }
+    }
+    data
+}
+
+extern "C" fn dragging_updated(this: &mut Object, _: Sel, info: id) -> NSUInteger {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let pos = dragging_location(this as id, info);
+        (*view_state).handler.drag_moved(pos);
+        // like on the other platforms, whether the drop is taken is only
+        // known once it happens
+        let allowed: NSUInteger = msg_send![info, draggingSourceOperationMask];
+        allowed & NSDragOperationCopy
+    }
+}
+
+extern "C" fn dragging_exited(this: &mut Object, _: Sel, _info: id) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        (*view_state).handler.drag_left();
+    }
+}
+
+extern "C" fn perform_drag_operation(this: &mut Object, _: Sel, info: id) -> BOOL {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let pos = dragging_location(this as id, info);
+        let data = dragged_data(info);
+        (*view_state).handler.drag_left();
+        if (*view_state).handler.dropped(pos, data) {
+            YES
+        } else {
+            NO
+        }
+    }
+}
+
+extern "C" fn drag_source_operations(
+    this: &mut Object,
+    _: Sel,
+    _session: id,
+    _context: NSInteger,
+) -> NSUInteger {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        (*view_state).drag_operations
+    }
+}
+
+extern "C" fn drag_ended(
+    this: &mut Object,
+    _: Sel,
+    _session: id,
+    _point: NSPoint,
+    operation: NSUInteger,
+) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let action = if operation & NSDragOperationMove != 0 {
+            Some(DropAction::Move)
+        } else if operation != 0 {
+            Some(DropAction::Copy)
+        } else {
+            None
+        };
+        (*view_state).handler.drag_ended(action);
+    }
+}
+
+/// The `NSDraggingItem`s for dragging `data`, with `image` shown for the
+/// first of them in `frame`.
+unsafe fn dragging_items(data: &DragData, image: id, frame: NSRect) -> Vec<id> {
+    let mut writers = Vec::new();
+    for file in &data.files {
+        let path = make_nsstring(&file.to_string_lossy());
+        let url: id = msg_send![class!(NSURL), fileURLWithPath: path];
+        writers.push(url);
+    }
+    for url in &data.urls {
+        let item: id = msg_send![class!(NSPasteboardItem), new];
+        let _: BOOL =
+            msg_send![item, setString: make_nsstring(url) forType: make_nsstring(URL_TYPE)];
+        writers.push(msg_send![item, autorelease]);
+    }
+    if data.text.is_some() || !data.formats.is_empty() {
+        let item: id = msg_send![class!(NSPasteboardItem), new];
+        if let Some(text) = &data.text {
+            let _: BOOL =
+                msg_send![item, setString: make_nsstring(text) forType: make_nsstring(TEXT_TYPE)];
+        }
+        for format in &data.formats {
+            let bytes = make_nsdata(&format.data);
+            let _: BOOL = msg_send![item, setData: bytes forType: make_nsstring(format.identifier)];
+        }
+        writers.push(msg_send![item, autorelease]);
+    }
+    writers
+        .into_iter()
+        .enumerate()
+        .map(|(i, writer)| {
+            let item: id = msg_send![class!(NSDraggingItem), alloc];
+            let item: id = msg_send![item, initWithPasteboardWriter: writer];
+            let contents = if i == 0 { image } else { nil };
+            let () = msg_send![item, setDraggingFrame: frame contents: contents];
+            msg_send![item, autorelease]
+        })
+        .collect()
+}
+
 // NOTE: If we know the button (because of the origin call) we pass it through,
 // otherwise we get it from the event itself.
 fn mouse_event(nsevent: id, view: id, button: Option<MouseButton>) -> MouseEvent {
@@ -436,8 +854,131 @@ extern "C" fn key_down(this: &mut Object, _: Sel, nsevent: id) {
         let view_state: *mut c_void = *this.get_ivar("viewState");
         &mut *(view_state as *mut ViewState)
     };
-    (*view_state).handler.key_down(event);
     view_state.last_mods = event.mods;
+    if event.mods.meta {
+        // command keys are shortcuts, never text
+        (*view_state).handler.key_down(event);
+        return;
+    }
+    // the text input system calls back with `insertText:replacementRange:`
+    // for the text it makes of the key, such as an `é` from a dead key and
+    // an `e`, or `doCommandBySelector:` for keys that aren't text; keys it
+    // keeps, like the dead key itself, get neither.
+    view_state.pending_key = Some(event);
+    unsafe {
+        let events = NSArray::arrayWithObjects(nil, &[nsevent]);
+        let () = msg_send![this, interpretKeyEvents: events];
+    }
+    let view_state = unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        &mut *(view_state as *mut ViewState)
+    };
+    view_state.pending_key = None;
+}
+
+extern "C" fn insert_text(this: &mut Object, _: Sel, string: id, _: TextRange) {
+    let view_state = unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        &mut *(view_state as *mut ViewState)
+    };
+    let text = unsafe {
+        let is_attributed: BOOL = msg_send![string, isKindOfClass: class!(NSAttributedString)];
+        let string: id = if is_attributed == YES {
+            msg_send![string, string]
+        } else {
+            string
+        };
+        from_nsstring(string)
+    };
+    view_state.marked_len = 0;
+    // input methods can commit text after a key, such as from a click
+    let key = view_state
+        .pending_key
+        .take()
+        .unwrap_or_else(|| KeyEvent::new(KeyCode::Unknown(0), false, view_state.last_mods, "", ""));
+    for event in KeyEvent::for_committed_text(key, &text) {
+        view_state.handler.key_down(event);
+    }
+}
+
+extern "C" fn do_command_by_selector(this: &mut Object, _: Sel, _: Sel) {
+    let view_state = unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        &mut *(view_state as *mut ViewState)
+    };
+    if let Some(event) = view_state.pending_key.take() {
+        view_state.handler.key_down(event);
+    }
+}
+
+extern "C" fn set_marked_text(this: &mut Object, _: Sel, string: id, _: TextRange, _: TextRange) {
+    let view_state = unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        &mut *(view_state as *mut ViewState)
+    };
+    view_state.marked_len = unsafe { msg_send![string, length] };
+}
+
+extern "C" fn unmark_text(this: &mut Object, _: Sel) {
+    let view_state = unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        &mut *(view_state as *mut ViewState)
+    };
+    view_state.marked_len = 0;
+}
+
+extern "C" fn has_marked_text(this: &mut Object, _: Sel) -> BOOL {
+    let view_state = unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        &mut *(view_state as *mut ViewState)
+    };
+    if view_state.marked_len > 0 {
+        YES
+    } else {
+        NO
+    }
+}
+
+extern "C" fn marked_range(this: &mut Object, _: Sel) -> TextRange {
+    let view_state = unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        &mut *(view_state as *mut ViewState)
+    };
+    match view_state.marked_len {
+        0 => TextRange::NOT_FOUND,
+        length => TextRange {
+            location: 0,
+            length,
+        },
+    }
+}
+
+// The view doesn't share its text with the text input system, so it has no
+// selection, substrings or character positions to tell it about.
+
+extern "C" fn selected_range(_: &mut Object, _: Sel) -> TextRange {
+    TextRange::NOT_FOUND
+}
+
+extern "C" fn valid_attributes_for_marked_text(_: &mut Object, _: Sel) -> id {
+    unsafe { NSArray::array(nil) }
+}
+
+extern "C" fn attributed_substring(_: &mut Object, _: Sel, _: TextRange, _: *mut c_void) -> id {
+    nil
+}
+
+extern "C" fn character_index_for_point(_: &mut Object, _: Sel, _: NSPoint) -> NSUInteger {
+    NSNotFound as NSUInteger
+}
+
+extern "C" fn first_rect_for_character_range(
+    _: &mut Object,
+    _: Sel,
+    _: TextRange,
+    _: *mut c_void,
+) -> NSRect {
+    NSRect::new(NSPoint::new(0., 0.), NSSize::new(0., 0.))
 }
 
 extern "C" fn key_up(this: &mut Object, _: Sel, nsevent: id) {
@@ -545,6 +1086,137 @@ extern "C" fn handle_menu_item(this: &mut Object, _: Sel, item: id) {
     }
 }
 
+/// Touch Bar items are identified by this prefix followed by their id.
+const TOUCH_BAR_ITEM_PREFIX: &str = "druid.touchbar.item.";
+
+unsafe fn touch_bar_items(this: &Object) -> &[TouchBarItem] {
+    let touch_bar: *mut c_void = *this.get_ivar("touchBar");
+    if touch_bar.is_null() {
+        &[]
+    } else {
+        &(*(touch_bar as *const TouchBar)).items
+    }
+}
+
+extern "C" fn make_touch_bar(this: &mut Object, _: Sel) -> id {
+    unsafe {
+        let items = touch_bar_items(this);
+        // NSTouchBar is only available from macOS 10.12.2
+        let class = match Class::get("NSTouchBar") {
+            Some(class) if !items.is_empty() => class,
+            _ => return nil,
+        };
+        let identifiers: Vec<id> = items
+            .iter()
+            .map(|item| make_nsstring(&format!("{}{}", TOUCH_BAR_ITEM_PREFIX, item.id())))
+            .collect();
+        let touch_bar: id = msg_send![class, new];
+        let () = msg_send![touch_bar, setDelegate: this as *const _];
+        let identifiers = NSArray::arrayWithObjects(nil, &identifiers);
+        let () = msg_send![touch_bar, setDefaultItemIdentifiers: identifiers];
+        touch_bar.autorelease()
+    }
+}
+
+extern "C" fn make_touch_bar_item(this: &mut Object, _: Sel, _touch_bar: id, ident: id) -> id {
+    unsafe {
+        let target = this as *const Object;
+        let item_id = from_nsstring(ident)
+            .trim_start_matches(TOUCH_BAR_ITEM_PREFIX)
+            .parse::<u32>()
+            .ok();
+        let item = match touch_bar_items(this)
+            .iter()
+            .find(|item| Some(item.id()) == item_id)
+        {
+            Some(item) => item,
+            None => return nil,
+        };
+        let tb_item: id = match item {
+            TouchBarItem::Button { id, label } => {
+                let button: id = msg_send![class!(NSButton), buttonWithTitle: make_nsstring(label) target: target action: sel!(touchBarButtonPressed:)];
+                let () = msg_send![button, setTag: *id as isize];
+                let tb_item: id = msg_send![class!(NSCustomTouchBarItem), alloc];
+                let tb_item: id = msg_send![tb_item, initWithIdentifier: ident];
+                let () = msg_send![tb_item, setView: button];
+                tb_item
+            }
+            TouchBarItem::Slider {
+                id,
+                label,
+                min,
+                max,
+                value,
+            } => {
+                let tb_item: id = msg_send![class!(NSSliderTouchBarItem), alloc];
+                let tb_item: id = msg_send![tb_item, initWithIdentifier: ident];
+                if let Some(label) = label {
+                    let () = msg_send![tb_item, setLabel: make_nsstring(label)];
+                }
+                let slider: id = msg_send![tb_item, slider];
+                let () = msg_send![slider, setTag: *id as isize];
+                let () = msg_send![slider, setMinValue: *min];
+                let () = msg_send![slider, setMaxValue: *max];
+                let () = msg_send![slider, setDoubleValue: *value];
+                let () = msg_send![tb_item, setTarget: target];
+                let () = msg_send![tb_item, setAction: sel!(touchBarSliderChanged:)];
+                tb_item
+            }
+            TouchBarItem::Segmented {
+                id,
+                labels,
+                selected,
+            } => {
+                let labels: Vec<id> = labels.iter().map(|l| make_nsstring(l)).collect();
+                let labels = NSArray::arrayWithObjects(nil, &labels);
+                // NSSegmentSwitchTrackingSelectOne
+                let tracking_mode: usize = 0;
+                let control: id = msg_send![class!(NSSegmentedControl), segmentedControlWithLabels: labels trackingMode: tracking_mode target: target action: sel!(touchBarSegmentSelected:)];
+                let () = msg_send![control, setTag: *id as isize];
+                let selected = selected.map(|i| i as isize).unwrap_or(-1);
+                let () = msg_send![control, setSelectedSegment: selected];
+                let tb_item: id = msg_send![class!(NSCustomTouchBarItem), alloc];
+                let tb_item: id = msg_send![tb_item, initWithIdentifier: ident];
+                let () = msg_send![tb_item, setView: control];
+                tb_item
+            }
+        };
+        tb_item.autorelease()
+    }
+}
+
+unsafe fn send_touch_bar_event(this: &mut Object, id: isize, event: TouchBarEvent) {
+    let view_state: *mut c_void = *this.get_ivar("viewState");
+    let view_state = &mut *(view_state as *mut ViewState);
+    (*view_state).handler.touch_bar(id as u32, event);
+}
+
+extern "C" fn touch_bar_button_pressed(this: &mut Object, _: Sel, button: id) {
+    unsafe {
+        let tag: isize = msg_send![button, tag];
+        send_touch_bar_event(this, tag, TouchBarEvent::Pressed);
+    }
+}
+
+extern "C" fn touch_bar_slider_changed(this: &mut Object, _: Sel, item: id) {
+    unsafe {
+        let slider: id = msg_send![item, slider];
+        let tag: isize = msg_send![slider, tag];
+        let value: f64 = msg_send![slider, doubleValue];
+        send_touch_bar_event(this, tag, TouchBarEvent::SliderChanged(value));
+    }
+}
+
+extern "C" fn touch_bar_segment_selected(this: &mut Object, _: Sel, control: id) {
+    unsafe {
+        let tag: isize = msg_send![control, tag];
+        let selected: isize = msg_send![control, selectedSegment];
+        if selected >= 0 {
+            send_touch_bar_event(this, tag, TouchBarEvent::SegmentSelected(selected as usize));
+        }
+    }
+}
+
 extern "C" fn show_context_menu(this: &mut Object, _: Sel, item: id) {
     unsafe {
         let window: id = msg_send![this as *const _, window];
@@ -563,8 +1235,55 @@ extern "C" fn window_did_become_key(this: &mut Object, _: Sel, _notification: id
     }
 }
 
+extern "C" fn window_did_resign_key(this: &mut Object, _: Sel, _notification: id) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        (*view_state).handler.lost_focus();
+    }
+}
+
+extern "C" fn screen_parameters_changed(this: &mut Object, _: Sel, _notification: id) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        (*view_state).handler.monitors_changed();
+    }
+}
+
+extern "C" fn view_did_change_backing_properties(this: &mut Object, _: Sel) {
+    unsafe {
+        let view_state: *mut c_void = *this.get_ivar("viewState");
+        let view_state = &mut *(view_state as *mut ViewState);
+        let scale = backing_scale(this as *mut Object as id);
+        (*view_state).handler.scale_changed(scale);
+        let () = msg_send![this as *const _, setNeedsDisplay: YES];
+    }
+}
+
+/// The scale of the window that `view` is in, or `1.0` before it is in one.
+unsafe fn backing_scale(view: id) -> f64 {
+    let window: id = msg_send![view, window];
+    if window == nil {
+        return 1.0;
+    }
+    let scale: CGFloat = msg_send![window, backingScaleFactor];
+    scale as f64
+}
+
+/// Stop the notifications `show` asked for from being sent to `view`.
+///
+/// The screen notification isn't tied to the window, so without this it
+/// would keep going to the view after the window is gone.
+unsafe fn remove_observers(view: &Object) {
+    let notif_center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+    let () = msg_send![notif_center, removeObserver: view];
+}
+
 extern "C" fn window_will_close(this: &mut Object, _: Sel, _window: id) {
     unsafe {
+        remove_observers(this);
+        global_hotkey::unregister_view(this as *mut Object as id);
         let view_state: *mut c_void = *this.get_ivar("viewState");
         let view_state = &mut *(view_state as *mut ViewState);
         (*view_state).handler.destroy();
@@ -582,6 +1301,14 @@ impl WindowHandle {
                 .autorelease();
             let notif_center: id = msg_send![notif_center_class, defaultCenter];
             let () = msg_send![notif_center, addObserver:*self.nsview.load() selector: sel!(windowDidBecomeKey:) name: notif_string object: window];
+            let resign_notif_string = NSString::alloc(nil)
+                .init_str(NSWindowDidResignKeyNotification)
+                .autorelease();
+            let () = msg_send![notif_center, addObserver:*self.nsview.load() selector: sel!(windowDidResignKey:) name: resign_notif_string object: window];
+            let screen_notif_string = NSString::alloc(nil)
+                .init_str(NSApplicationDidChangeScreenParametersNotification)
+                .autorelease();
+            let () = msg_send![notif_center, addObserver:*self.nsview.load() selector: sel!(screenParametersChanged:) name: screen_notif_string object: nil];
             window.makeKeyAndOrderFront_(nil)
         }
     }
@@ -618,14 +1345,41 @@ impl WindowHandle {
                 Cursor::IBeam => msg_send![nscursor, IBeamCursor],
                 Cursor::Crosshair => msg_send![nscursor, crosshairCursor],
                 Cursor::OpenHand => msg_send![nscursor, openHandCursor],
+                Cursor::ClosedHand => msg_send![nscursor, closedHandCursor],
                 Cursor::NotAllowed => msg_send![nscursor, operationNotAllowedCursor],
                 Cursor::ResizeLeftRight => msg_send![nscursor, resizeLeftRightCursor],
                 Cursor::ResizeUpDown => msg_send![nscursor, resizeUpDownCursor],
+                // AppKit has no public diagonal resize or zoom cursors
+                Cursor::ResizeTopLeftBottomRight
+                | Cursor::ResizeTopRightBottomLeft
+                | Cursor::ZoomIn
+                | Cursor::ZoomOut => msg_send![nscursor, crosshairCursor],
+                Cursor::Custom(custom) => (custom.0).0,
             };
             let () = msg_send![cursor, set];
         }
     }
 
+    pub fn make_cursor(&self, desc: &CursorDesc) -> Option<Cursor> {
+        let icon = Icon {
+            rgba: desc.rgba.clone(),
+            width: desc.width,
+            height: desc.height,
+        };
+        unsafe {
+            let image = make_nsimage(&icon);
+            // AppKit puts the hot spot from the top left, like we do
+            let hot = NSPoint::new(desc.hot.x, desc.hot.y);
+            let cursor: id = msg_send![class!(NSCursor), alloc];
+            let cursor: id = msg_send![cursor, initWithImage: image hotSpot: hot];
+            if cursor.is_null() {
+                error!("failed to create NSCursor");
+                return None;
+            }
+            Some(Cursor::Custom(CustomCursor(Rc::new(RetainedCursor(cursor)))))
+        }
+    }
+
     pub fn request_timer(&self, deadline: std::time::Instant) -> TimerToken {
         let ti = time_interval_from_deadline(deadline);
         let token = TimerToken::next();
@@ -669,6 +1423,207 @@ impl WindowHandle {
         }
     }
 
+    /// The menu belongs to the application, so these change the main menu.
+    /// Item ids are unique, so this can't touch another window's items.
+    fn main_menu_item(&self, item_id: u32) -> Option<id> {
+        unsafe {
+            let main_menu: id = msg_send![NSApp(), mainMenu];
+            if main_menu == nil {
+                return None;
+            }
+            let item = menu::find_item(main_menu, item_id);
+            if item == nil {
+                None
+            } else {
+                Some(item)
+            }
+        }
+    }
+
+    pub fn set_menu_item_enabled(&self, id: u32, enabled: bool) {
+        if let Some(item) = self.main_menu_item(id) {
+            unsafe {
+                let () = msg_send![item, setEnabled: enabled as BOOL];
+            }
+        }
+    }
+
+    pub fn set_menu_item_title(&self, id: u32, title: &str) {
+        if let Some(item) = self.main_menu_item(id) {
+            unsafe {
+                let title = make_nsstring(&strip_access_key(title));
+                let () = msg_send![item, setTitle: title];
+            }
+        }
+    }
+
+    pub fn set_menu_item_hidden(&self, id: u32, hidden: bool) {
+        if let Some(item) = self.main_menu_item(id) {
+            unsafe {
+                let () = msg_send![item, setHidden: hidden as BOOL];
+            }
+        }
+    }
+
+    pub fn start_drag(&self, data: DragData, image: Option<&Icon>, allow_move: bool) {
+        unsafe {
+            let view = *self.nsview.load();
+            if view.is_null() {
+                return;
+            }
+            let view_state: *mut c_void = *(*view).get_ivar("viewState");
+            let view_state = &mut *(view_state as *mut ViewState);
+            view_state.drag_operations = if allow_move {
+                NSDragOperationCopy | NSDragOperationMove
+            } else {
+                NSDragOperationCopy
+            };
+
+            // the image is centered under the cursor; without one, the frame
+            // still says where the drag starts
+            let event: id = msg_send![NSApp(), currentEvent];
+            let location = view.convertPoint_fromView_(event.locationInWindow(), nil);
+            let (image, size) = match image {
+                Some(icon) => (
+                    make_nsimage(icon),
+                    NSSize::new(icon.width as f64, icon.height as f64),
+                ),
+                None => (nil, NSSize::new(1., 1.)),
+            };
+            let origin = NSPoint::new(location.x - size.width / 2., location.y - size.height / 2.);
+            let items = dragging_items(&data, image, NSRect::new(origin, size));
+            if items.is_empty() {
+                log::warn!("start_drag called with no data");
+                return;
+            }
+            let items = NSArray::arrayWithObjects(nil, &items);
+            let _: id =
+                msg_send![view, beginDraggingSessionWithItems: items event: event source: view];
+        }
+    }
+
+    /// The dock has no progress indicator of its own, so while there is
+    /// progress to show we draw the app icon with a progress bar over it.
+    pub fn set_progress(&self, progress: Option<f64>) {
+        unsafe {
+            let dock_tile: id = msg_send![NSApp(), dockTile];
+            match progress {
+                Some(progress) => {
+                    let mut content: id = msg_send![dock_tile, contentView];
+                    if content == nil {
+                        let size: NSSize = msg_send![dock_tile, size];
+                        let frame = NSRect::new(NSPoint::new(0., 0.), size);
+                        content = msg_send![class!(NSImageView), alloc];
+                        content = msg_send![content, initWithFrame: frame];
+                        let icon: id = msg_send![NSApp(), applicationIconImage];
+                        let () = msg_send![content, setImage: icon];
+
+                        let bar_frame =
+                            NSRect::new(NSPoint::new(0., 0.), NSSize::new(size.width, 20.));
+                        let bar: id = msg_send![class!(NSProgressIndicator), alloc];
+                        let bar: id = msg_send![bar, initWithFrame: bar_frame];
+                        let () = msg_send![bar, setIndeterminate: NO];
+                        let () = msg_send![bar, setMinValue: 0.0f64];
+                        let () = msg_send![bar, setMaxValue: 1.0f64];
+                        let () = msg_send![content, addSubview: bar];
+                        let () = msg_send![bar, release];
+
+                        let () = msg_send![dock_tile, setContentView: content];
+                        let () = msg_send![content, release];
+                    }
+                    let subviews: id = msg_send![content, subviews];
+                    let bar: id = msg_send![subviews, firstObject];
+                    let () = msg_send![bar, setDoubleValue: progress];
+                }
+                None => {
+                    let () = msg_send![dock_tile, setContentView: nil];
+                }
+            }
+            let () = msg_send![dock_tile, display];
+        }
+    }
+
+    pub fn register_global_hotkey(&self, hotkey: &HotKey) -> Option<GlobalHotKeyToken> {
+        global_hotkey::register(self, hotkey)
+    }
+
+    pub fn unregister_global_hotkey(&self, token: GlobalHotKeyToken) {
+        global_hotkey::unregister(token);
+    }
+
+    pub(crate) fn global_hotkey_pressed(&self, token: GlobalHotKeyToken) {
+        unsafe {
+            let view = *self.nsview.load();
+            if view.is_null() {
+                return;
+            }
+            let view_state: *mut c_void = *(*view).get_ivar("viewState");
+            let view_state = &mut *(view_state as *mut ViewState);
+            view_state.handler.global_hotkey(token);
+        }
+    }
+
+    /// Whether this is the handle of `view`.
+    pub(crate) fn has_view(&self, view: id) -> bool {
+        *self.nsview.load() == view
+    }
+
+    pub fn set_touch_bar(&self, touch_bar: TouchBar) {
+        unsafe {
+            let nsview = self.nsview.load();
+            if *nsview == nil {
+                return;
+            }
+            let view = &mut *(*nsview as *mut Object);
+            let old: *mut c_void = *view.get_ivar("touchBar");
+            view.set_ivar(
+                "touchBar",
+                Box::into_raw(Box::new(touch_bar)) as *mut c_void,
+            );
+            if !old.is_null() {
+                drop(Box::from_raw(old as *mut TouchBar));
+            }
+            // AppKit calls `makeTouchBar` again once this is cleared
+            let () = msg_send![*nsview, setTouchBar: nil];
+        }
+    }
+
+    pub fn set_badge(&self, badge: Option<&str>) {
+        unsafe {
+            let dock_tile: id = msg_send![NSApp(), dockTile];
+            let label = badge.map(make_nsstring).unwrap_or(nil);
+            let () = msg_send![dock_tile, setBadgeLabel: label];
+        }
+    }
+
+    pub fn set_always_on_top(&self, on_top: bool) {
+        unsafe {
+            let window: id = msg_send![*self.nsview.load(), window];
+            set_always_on_top(window, on_top);
+        }
+    }
+
+    pub fn set_skip_taskbar(&self, skip: bool) {
+        unsafe {
+            let window: id = msg_send![*self.nsview.load(), window];
+            set_skip_taskbar(window, skip);
+        }
+    }
+
+    pub fn set_utility(&self, utility: bool) {
+        unsafe {
+            let window: id = msg_send![*self.nsview.load(), window];
+            set_utility(window, utility);
+        }
+    }
+
+    pub fn set_icon(&self, icon: Option<&Icon>) {
+        unsafe {
+            let image = icon.map(|icon| make_nsimage(icon)).unwrap_or(nil);
+            let () = msg_send![NSApp(), setApplicationIconImage: image];
+        }
+    }
+
     //FIXME: we should be using the x, y values passed by the caller, but then
     //we have to figure out some way to pass them along with this performSelector:
     //call. This isn't super hard, I'm just not up for it right now.
@@ -698,6 +1653,34 @@ impl WindowHandle {
         // TODO: get actual dpi
         96.0
     }
+
+    pub fn get_scale(&self) -> f64 {
+        let nsview = self.nsview.load();
+        if *nsview == nil {
+            return 1.0;
+        }
+        unsafe { backing_scale(*nsview) }
+    }
+}
+
+#[cfg(feature = "raw-win-handle")]
+impl WindowHandle {
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        let mut handle = AppKitWindowHandle::empty();
+        let nsview = self.nsview.load();
+        if *nsview != nil {
+            handle.ns_view = *nsview as *mut _;
+            unsafe {
+                let window: id = msg_send![*nsview, window];
+                handle.ns_window = window as *mut _;
+            }
+        }
+        RawWindowHandle::AppKit(handle)
+    }
+
+    pub fn raw_display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::AppKit(AppKitDisplayHandle::empty())
+    }
 }
 
 unsafe impl Send for IdleHandle {}
@@ -807,3 +1790,55 @@ fn make_modifiers(raw: NSEventModifierFlags) -> KeyModifiers {
         meta: raw.contains(NSEventModifierFlags::NSCommandKeyMask),
     }
 }
+
+/// An autoreleased `NSImage` of `icon`.
+pub(crate) unsafe fn make_nsimage(icon: &Icon) -> id {
+    // with no planes given, the image rep makes its own buffer to copy into
+    let planes: *mut *mut u8 = std::ptr::null_mut();
+    let color_space = make_nsstring("NSDeviceRGBColorSpace");
+    // NSBitmapFormatAlphaNonpremultiplied
+    let format: NSUInteger = 1 << 1;
+    let rep: id = msg_send![class!(NSBitmapImageRep), alloc];
+    let rep: id = msg_send![rep, initWithBitmapDataPlanes: planes
+        pixelsWide: icon.width as NSInteger
+        pixelsHigh: icon.height as NSInteger
+        bitsPerSample: 8 as NSInteger
+        samplesPerPixel: 4 as NSInteger
+        hasAlpha: YES
+        isPlanar: NO
+        colorSpaceName: color_space
+        bitmapFormat: format
+        bytesPerRow: (icon.width * 4) as NSInteger
+        bitsPerPixel: 32 as NSInteger];
+    let data: *mut u8 = msg_send![rep, bitmapData];
+    std::ptr::copy_nonoverlapping(icon.rgba.as_ptr(), data, icon.rgba.len());
+    let size = NSSize::new(icon.width as f64, icon.height as f64);
+    let image: id = msg_send![class!(NSImage), alloc];
+    let image: id = msg_send![image, initWithSize: size];
+    let () = msg_send![image, addRepresentation: rep];
+    let () = msg_send![rep, release];
+    msg_send![image, autorelease]
+}
+
+unsafe fn set_always_on_top(window: id, on_top: bool) {
+    // NSFloatingWindowLevel and NSNormalWindowLevel
+    let level: NSInteger = if on_top { 3 } else { 0 };
+    let () = msg_send![window, setLevel: level];
+}
+
+unsafe fn set_skip_taskbar(window: id, skip: bool) {
+    // NSWindowCollectionBehaviorIgnoresCycle
+    const IGNORES_CYCLE: NSUInteger = 1 << 6;
+    let behavior: NSUInteger = msg_send![window, collectionBehavior];
+    let behavior = if skip {
+        behavior | IGNORES_CYCLE
+    } else {
+        behavior & !IGNORES_CYCLE
+    };
+    let () = msg_send![window, setCollectionBehavior: behavior];
+    let () = msg_send![window, setExcludedFromWindowsMenu: if skip { YES } else { NO }];
+}
+
+unsafe fn set_utility(window: id, utility: bool) {
+    let () = msg_send![window, setHidesOnDeactivate: if utility { YES } else { NO }];
+}
@@ -14,23 +14,45 @@
 
 //! Windows implementation of features at the application scope.
 
+use std::cell::RefCell;
+use std::ffi::OsStr;
 use std::mem;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::ptr;
 
-use winapi::shared::minwindef::HINSTANCE;
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, HINSTANCE, UINT};
 use winapi::shared::ntdef::LPCWSTR;
 use winapi::shared::windef::HCURSOR;
+use winapi::shared::winerror::{ERROR_SUCCESS, FAILED};
+use winapi::um::ole2::OleInitialize;
+use winapi::um::shellapi::ShellExecuteW;
 use winapi::um::shellscalingapi::PROCESS_SYSTEM_DPI_AWARE;
 use winapi::um::wingdi::CreateSolidBrush;
+use winapi::um::winreg::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
 use winapi::um::winuser::{
     DispatchMessageW, GetAncestor, GetMessageW, LoadIconW, PostQuitMessage, RegisterClassW,
-    TranslateAcceleratorW, TranslateMessage, GA_ROOT, IDI_APPLICATION, MSG, WNDCLASSW,
+    SystemParametersInfoW, TranslateAcceleratorW, TranslateMessage, GA_ROOT, IDI_APPLICATION, MSG,
+    SPI_GETCLIENTAREAANIMATION, SW_SHOWNORMAL, WNDCLASSW,
 };
 
 use super::accels;
 use super::clipboard::Clipboard;
 use super::util::{self, ToWide, CLASS_NAME, OPTIONAL_FUNCTIONS};
 use super::window::win_proc_dispatch;
+use crate::Error;
+
+// TODO: remove these when they get added to winapi
+extern "system" {
+    fn SHAddToRecentDocs(flags: UINT, path: *const std::ffi::c_void);
+}
+const SHARD_PATHW: UINT = 3;
+
+thread_local! {
+    /// The documents added to the jump list since launch; Windows has no API
+    /// for reading the jump list back.
+    static RECENT_DOCUMENTS: RefCell<Vec<PathBuf>> = RefCell::new(Vec::new());
+}
 
 pub struct Application;
 
@@ -39,6 +61,16 @@ impl Application {
         Application
     }
 
+    /// The host pumps the messages, so all we need is our window class.
+    ///
+    /// Our accelerators are only translated by our own message loop, so
+    /// menu shortcuts don't work when embedded.
+    #[cfg(feature = "raw-win-handle")]
+    pub fn embedded() -> Application {
+        Application::init();
+        Application
+    }
+
     pub fn run(&mut self) {
         Application::init();
         unsafe {
@@ -75,6 +107,11 @@ impl Application {
         }
 
         unsafe {
+            // for drag and drop, and the clipboard it uses
+            let hr = OleInitialize(ptr::null_mut());
+            if FAILED(hr) {
+                log::warn!("OleInitialize failed: 0x{:x}", hr);
+            }
             let class_name = CLASS_NAME.to_wide();
             let icon = LoadIconW(0 as HINSTANCE, IDI_APPLICATION);
             let brush = CreateSolidBrush(0xff_ff_ff);
@@ -111,4 +148,103 @@ impl Application {
         //TODO ahem
         "en-US".into()
     }
+
+    /// The "Make text bigger" setting, stored as a percentage (100-225).
+    pub fn get_text_scale() -> f64 {
+        let subkey = "Software\\Microsoft\\Accessibility".to_wide();
+        let value = "TextScaleFactor".to_wide();
+        let mut percent: DWORD = 0;
+        let mut size = mem::size_of::<DWORD>() as DWORD;
+        let result = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                subkey.as_ptr(),
+                value.as_ptr(),
+                RRF_RT_REG_DWORD,
+                ptr::null_mut(),
+                &mut percent as *mut DWORD as *mut _,
+                &mut size,
+            )
+        };
+        if result as DWORD == ERROR_SUCCESS && percent > 0 {
+            f64::from(percent) / 100.0
+        } else {
+            1.0
+        }
+    }
+
+    /// The inverse of the "Show animations in Windows" setting.
+    pub fn get_reduce_motion() -> bool {
+        let mut animations: BOOL = FALSE;
+        let ok = unsafe {
+            SystemParametersInfoW(
+                SPI_GETCLIENTAREAANIMATION,
+                0,
+                &mut animations as *mut BOOL as *mut _,
+                0,
+            )
+        };
+        ok != FALSE && animations == FALSE
+    }
+
+    pub fn open_url(url: &str) -> Result<(), Error> {
+        shell_open(OsStr::new(url))
+    }
+
+    pub fn open_path(path: &Path) -> Result<(), Error> {
+        shell_open(path.as_os_str())
+    }
+
+    pub fn reveal_path(path: &Path) -> Result<(), Error> {
+        // explorer exits with an error code even when it succeeds, so we
+        // can only report failing to start it.
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path);
+        Command::new("explorer")
+            .arg(select)
+            .spawn()
+            .map(|_| ())
+            .map_err(|_| Error::Other("failed to start explorer"))
+    }
+
+    pub fn add_recent_document(path: &Path) {
+        let wide = path.as_os_str().to_wide();
+        unsafe { SHAddToRecentDocs(SHARD_PATHW, wide.as_ptr() as *const _) };
+        RECENT_DOCUMENTS.with(|recent| {
+            let mut recent = recent.borrow_mut();
+            recent.retain(|p| p != path);
+            recent.insert(0, path.to_owned());
+        });
+    }
+
+    pub fn recent_documents() -> Vec<PathBuf> {
+        RECENT_DOCUMENTS.with(|recent| recent.borrow().clone())
+    }
+
+    pub fn clear_recent_documents() {
+        // a null path clears the whole list
+        unsafe { SHAddToRecentDocs(SHARD_PATHW, ptr::null()) };
+        RECENT_DOCUMENTS.with(|recent| recent.borrow_mut().clear());
+    }
+}
+
+fn shell_open(target: &OsStr) -> Result<(), Error> {
+    let verb = "open".to_wide();
+    let target = target.to_wide();
+    let result = unsafe {
+        ShellExecuteW(
+            ptr::null_mut(),
+            verb.as_ptr(),
+            target.as_ptr(),
+            ptr::null(),
+            ptr::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // ShellExecuteW returns a value greater than 32 on success
+    if result as usize > 32 {
+        Ok(())
+    } else {
+        Err(Error::Other("ShellExecuteW failed"))
+    }
 }
@@ -167,7 +167,8 @@ impl Clipboard {
     }
 }
 
-unsafe fn make_handle(format: &ClipboardFormat) -> HANDLE {
+/// Copy `format` to a new global handle, as it goes on the clipboard.
+pub(crate) unsafe fn make_handle(format: &ClipboardFormat) -> HANDLE {
     if format.identifier == ClipboardFormat::TEXT {
         let s = std::str::from_utf8_unchecked(&format.data);
         let wstr = s.to_wide();
@@ -185,7 +186,7 @@ unsafe fn make_handle(format: &ClipboardFormat) -> HANDLE {
     }
 }
 
-fn get_format_id(format: FormatId) -> Option<UINT> {
+pub(crate) fn get_format_id(format: FormatId) -> Option<UINT> {
     if let Some((id, _)) = STANDARD_FORMATS.iter().find(|(_, s)| s == &format) {
         return Some(*id);
     }
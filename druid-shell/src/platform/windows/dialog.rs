@@ -95,6 +95,31 @@ pub(crate) unsafe fn get_file_dialog_path(
     if options.show_hidden {
         flags |= FOS_FORCESHOWHIDDEN;
     }
+    if let (FileDialogType::Open, true) = (&ty, options.select_directories) {
+        flags |= FOS_PICKFOLDERS;
+    }
+    if let (FileDialogType::Save, true) = (&ty, options.confirm_overwrite) {
+        flags |= FOS_OVERWRITEPROMPT;
+    }
+
+    if let Some(name) = &options.default_name {
+        as_result(file_dialog.SetFileName(name.to_wide().as_ptr()))?;
+    }
+
+    if let Some(dir) = &options.starting_directory {
+        let mut folder: *mut IShellItem = null_mut();
+        let made = SHCreateItemFromParsingName(
+            dir.as_os_str().to_wide().as_ptr(),
+            null_mut(),
+            &IShellItem::uuidof(),
+            &mut folder as *mut *mut IShellItem as *mut LPVOID,
+        );
+        // a directory that doesn't exist is left out, rather than failing
+        if as_result(made).is_ok() {
+            let folder = ComPtr::from_raw(folder);
+            as_result(file_dialog.SetFolder(folder.as_raw()))?;
+        }
+    }
 
     // - allowed filetypes
 
@@ -0,0 +1,659 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Drag and drop, through OLE.
+
+use std::cell::Cell;
+use std::mem;
+use std::path::PathBuf;
+use std::ptr::{self, null, null_mut};
+use std::slice;
+
+use winapi::ctypes::c_void;
+use winapi::shared::guiddef::{IsEqualGUID, GUID, REFIID};
+use winapi::shared::minwindef::{BOOL, DWORD, HGLOBAL, LPARAM, TRUE, UINT, ULONG, WPARAM};
+use winapi::shared::windef::{COLORREF, HBITMAP, HWND, POINT, POINTL, SIZE};
+use winapi::shared::winerror::{
+    DRAGDROP_S_CANCEL, DRAGDROP_S_DROP, DRAGDROP_S_USEDEFAULTCURSORS, E_NOINTERFACE, E_POINTER,
+    FAILED, HRESULT, S_OK,
+};
+use winapi::shared::wtypes::DVASPECT_CONTENT;
+use winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+use winapi::um::combaseapi::CoCreateInstance;
+use winapi::um::objidl::{IDataObject, FORMATETC, STGMEDIUM, TYMED_HGLOBAL};
+use winapi::um::ole2::{RegisterDragDrop, RevokeDragDrop};
+use winapi::um::oleidl::{
+    IDropTarget, IDropTargetVtbl, DROPEFFECT_COPY, DROPEFFECT_MOVE, DROPEFFECT_NONE,
+};
+use winapi::um::shellapi::HDROP;
+use winapi::um::unknwnbase::{IUnknown, IUnknownVtbl};
+use winapi::um::winbase::{
+    GlobalAlloc, GlobalFree, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE,
+};
+use winapi::um::wingdi::{
+    CreateDIBSection, DeleteObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+};
+use winapi::um::winuser::{
+    RegisterClipboardFormatW, ScreenToClient, SendMessageW, CF_HDROP, CF_UNICODETEXT, MK_LBUTTON,
+    WM_USER,
+};
+use winapi::Interface;
+use wio::com::ComPtr;
+
+use super::clipboard::{get_format_id, make_handle};
+use super::util::{FromWide, ToWide};
+use super::window::get_dropped_files;
+use crate::clipboard::ClipboardFormat;
+use crate::drag::{DragData, DropAction};
+use crate::window::Icon;
+
+/// Sent to the window while something is dragged over it, with the
+/// position in its client area, in pixels, as `wparam` and `lparam`.
+pub(crate) const XI_DRAG_MOVED: u32 = WM_USER + 2;
+
+/// Sent to the window when a drag leaves it, and before a drop.
+pub(crate) const XI_DRAG_LEFT: u32 = WM_USER + 3;
+
+/// Sent to the window when something is dropped on it, with a pointer to a
+/// `Dropped` as `lparam`. The window returns nonzero if it took the drop.
+pub(crate) const XI_DROPPED: u32 = WM_USER + 4;
+
+/// Sent to the window to start the drag it was asked for, once the message
+/// that asked for it has been handled.
+pub(crate) const XI_START_DRAG: u32 = WM_USER + 5;
+
+/// What was dropped, and where.
+pub(crate) struct Dropped {
+    /// In the client area, in pixels.
+    pub(crate) pos: POINT,
+    pub(crate) data: DragData,
+}
+
+/// A drag out of a window, waiting for `XI_START_DRAG`.
+pub(crate) struct OutgoingDrag {
+    pub(crate) data: DragData,
+    pub(crate) image: Option<Icon>,
+    pub(crate) allow_move: bool,
+}
+
+// winapi has none of these
+extern "system" {
+    fn ReleaseStgMedium(medium: *mut STGMEDIUM);
+    fn DoDragDrop(
+        data: *mut IDataObject,
+        source: *mut IUnknown,
+        ok_effects: DWORD,
+        effect: *mut DWORD,
+    ) -> HRESULT;
+    fn SHCreateDataObject(
+        folder: *const c_void,
+        count: UINT,
+        items: *const *const c_void,
+        inner: *mut IDataObject,
+        riid: REFIID,
+        object: *mut *mut c_void,
+    ) -> HRESULT;
+}
+
+/// The shell's `IDragSourceHelper`, which winapi doesn't have.
+#[repr(C)]
+struct DragSourceHelper {
+    vtbl: *const DragSourceHelperVtbl,
+}
+
+/// The start of `IDragSourceHelperVtbl`, as far as the method we use.
+#[repr(C)]
+struct DragSourceHelperVtbl {
+    parent: IUnknownVtbl,
+    initialize_from_bitmap: unsafe extern "system" fn(
+        *mut DragSourceHelper,
+        *mut ShDragImage,
+        *mut IDataObject,
+    ) -> HRESULT,
+}
+
+/// `CLSID_DragDropHelper`.
+const CLSID_DRAG_DROP_HELPER: GUID = GUID {
+    Data1: 0x4657_278a,
+    Data2: 0x411b,
+    Data3: 0x11d2,
+    Data4: [0x83, 0x9a, 0x00, 0xc0, 0x4f, 0xd9, 0x18, 0xd0],
+};
+
+/// `IID_IDragSourceHelper`.
+const IID_DRAG_SOURCE_HELPER: GUID = GUID {
+    Data1: 0xde5b_f786,
+    Data2: 0x477a,
+    Data3: 0x11d2,
+    Data4: [0x83, 0x9d, 0x00, 0xc0, 0x4f, 0xd9, 0x18, 0xd0],
+};
+
+/// `SHDRAGIMAGE`, the image shown while dragging.
+#[repr(C)]
+struct ShDragImage {
+    size: SIZE,
+    /// Where the cursor is in the image.
+    offset: POINT,
+    bitmap: HBITMAP,
+    color_key: COLORREF,
+}
+
+/// `DROPFILES`, the header of `CF_HDROP`.
+#[repr(C)]
+struct DropFiles {
+    /// Where the file names start, after the header.
+    files: DWORD,
+    point: POINT,
+    non_client: BOOL,
+    wide: BOOL,
+}
+
+/// Let things be dragged onto `hwnd`, which is sent the `XI_DRAG_*`
+/// messages as they are.
+///
+/// This needs OLE to have been initialized on the thread.
+pub(crate) fn register_drop_target(hwnd: HWND) {
+    let target = Box::into_raw(Box::new(DropTarget {
+        vtbl: &DROP_TARGET_VTBL,
+        refs: Cell::new(1),
+        hwnd,
+    }));
+    unsafe {
+        let hr = RegisterDragDrop(hwnd, target as *mut IDropTarget);
+        if hr != S_OK {
+            log::warn!("RegisterDragDrop failed: 0x{:x}", hr);
+        }
+        // the window holds its own reference
+        drop_target_release(target as *mut IUnknown);
+    }
+}
+
+/// Stop things being dragged onto `hwnd`, before it is destroyed.
+pub(crate) fn revoke_drop_target(hwnd: HWND) {
+    unsafe {
+        RevokeDragDrop(hwnd);
+    }
+}
+
+/// Our `IDropTarget`, which turns OLE's calls into messages to its window.
+#[repr(C)]
+struct DropTarget {
+    vtbl: *const IDropTargetVtbl,
+    refs: Cell<ULONG>,
+    hwnd: HWND,
+}
+
+static DROP_TARGET_VTBL: IDropTargetVtbl = IDropTargetVtbl {
+    parent: IUnknownVtbl {
+        QueryInterface: drop_target_query_interface,
+        AddRef: drop_target_add_ref,
+        Release: drop_target_release,
+    },
+    DragEnter: drop_target_drag_enter,
+    DragOver: drop_target_drag_over,
+    DragLeave: drop_target_drag_leave,
+    Drop: drop_target_drop,
+};
+
+unsafe extern "system" fn drop_target_query_interface(
+    this: *mut IUnknown,
+    riid: REFIID,
+    object: *mut *mut c_void,
+) -> HRESULT {
+    if object.is_null() {
+        return E_POINTER;
+    }
+    if IsEqualGUID(&*riid, &IUnknown::uuidof()) || IsEqualGUID(&*riid, &IDropTarget::uuidof()) {
+        drop_target_add_ref(this);
+        *object = this as *mut c_void;
+        S_OK
+    } else {
+        *object = null_mut();
+        E_NOINTERFACE
+    }
+}
+
+unsafe extern "system" fn drop_target_add_ref(this: *mut IUnknown) -> ULONG {
+    let target = &*(this as *const DropTarget);
+    target.refs.set(target.refs.get() + 1);
+    target.refs.get()
+}
+
+unsafe extern "system" fn drop_target_release(this: *mut IUnknown) -> ULONG {
+    let refs = {
+        let target = &*(this as *const DropTarget);
+        target.refs.set(target.refs.get() - 1);
+        target.refs.get()
+    };
+    if refs == 0 {
+        drop(Box::from_raw(this as *mut DropTarget));
+    }
+    refs
+}
+
+unsafe extern "system" fn drop_target_drag_enter(
+    this: *mut IDropTarget,
+    _data: *const IDataObject,
+    key_state: DWORD,
+    pt: *const POINTL,
+    effect: *mut DWORD,
+) -> HRESULT {
+    drop_target_drag_over(this, key_state, pt, effect)
+}
+
+unsafe extern "system" fn drop_target_drag_over(
+    this: *mut IDropTarget,
+    _key_state: DWORD,
+    pt: *const POINTL,
+    effect: *mut DWORD,
+) -> HRESULT {
+    let target = &*(this as *const DropTarget);
+    let pos = client_point(target.hwnd, pt);
+    SendMessageW(target.hwnd, XI_DRAG_MOVED, pos.x as WPARAM, pos.y as LPARAM);
+    // like on the other platforms, whether the drop is taken is only known
+    // once it happens
+    *effect &= DROPEFFECT_COPY;
+    S_OK
+}
+
+unsafe extern "system" fn drop_target_drag_leave(this: *mut IDropTarget) -> HRESULT {
+    let target = &*(this as *const DropTarget);
+    SendMessageW(target.hwnd, XI_DRAG_LEFT, 0, 0);
+    S_OK
+}
+
+unsafe extern "system" fn drop_target_drop(
+    this: *mut IDropTarget,
+    data: *const IDataObject,
+    _key_state: DWORD,
+    pt: *const POINTL,
+    effect: *mut DWORD,
+) -> HRESULT {
+    let target = &*(this as *const DropTarget);
+    SendMessageW(target.hwnd, XI_DRAG_LEFT, 0, 0);
+    let mut dropped = Dropped {
+        pos: client_point(target.hwnd, pt),
+        data: read_data(&*data),
+    };
+    let taken = SendMessageW(
+        target.hwnd,
+        XI_DROPPED,
+        0,
+        &mut dropped as *mut Dropped as LPARAM,
+    );
+    *effect = if taken != 0 {
+        *effect & DROPEFFECT_COPY
+    } else {
+        DROPEFFECT_NONE
+    };
+    S_OK
+}
+
+/// Where the screen point `pt` is in the client area of `hwnd`.
+unsafe fn client_point(hwnd: HWND, pt: *const POINTL) -> POINT {
+    let mut point = POINT {
+        x: (*pt).x,
+        y: (*pt).y,
+    };
+    ScreenToClient(hwnd, &mut point);
+    point
+}
+
+/// The files, URL and text in `object`.
+unsafe fn read_data(object: &IDataObject) -> DragData {
+    let mut data = DragData::default();
+    with_global(object, CF_HDROP, |global, _| {
+        data.files = get_dropped_files(global as HDROP).files;
+    });
+    let url_format = RegisterClipboardFormatW("UniformResourceLocatorW".to_wide().as_ptr());
+    with_global(object, url_format, |_, bytes| {
+        if let Some(url) = wide_string(bytes) {
+            data.urls.push(url);
+        }
+    });
+    // files and links are also offered as text, and we don't want them twice
+    if data.files.is_empty() && data.urls.is_empty() {
+        with_global(object, CF_UNICODETEXT, |_, bytes| {
+            data.text = wide_string(bytes);
+        });
+    }
+    data
+}
+
+/// Call `f` with the memory of `format` in `object`, and its handle, if
+/// `object` has it.
+unsafe fn with_global(object: &IDataObject, format: u32, f: impl FnOnce(HGLOBAL, &[u8])) {
+    let format = FORMATETC {
+        cfFormat: format as u16,
+        ptd: null_mut(),
+        dwAspect: DVASPECT_CONTENT,
+        lindex: -1,
+        tymed: TYMED_HGLOBAL,
+    };
+    let mut medium: STGMEDIUM = std::mem::zeroed();
+    if object.GetData(&format, &mut medium) != S_OK {
+        return;
+    }
+    if medium.tymed == TYMED_HGLOBAL {
+        // the union is only as big as the handle in it
+        let global = medium.u as HGLOBAL;
+        let ptr = GlobalLock(global);
+        if !ptr.is_null() {
+            f(
+                global,
+                slice::from_raw_parts(ptr as *const u8, GlobalSize(global)),
+            );
+            GlobalUnlock(global);
+        }
+    }
+    ReleaseStgMedium(&mut medium);
+}
+
+/// The null-terminated UTF-16 string at the start of `bytes`.
+fn wide_string(bytes: &[u8]) -> Option<String> {
+    let wide: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_ne_bytes([pair[0], pair[1]]))
+        .take_while(|&c| c != 0)
+        .collect();
+    wide.from_wide()
+}
+
+/// Drag `drag` out of the app, until it is dropped or cancelled.
+///
+/// OLE runs a message loop until then, so this must not be called while
+/// the window's handler is borrowed.
+pub(crate) fn run_drag(drag: OutgoingDrag) -> Option<DropAction> {
+    unsafe {
+        let object = make_data_object(&drag.data)?;
+        if let Some(image) = &drag.image {
+            set_drag_image(&object, image);
+        }
+        let source = Box::into_raw(Box::new(DropSource {
+            vtbl: &DROP_SOURCE_VTBL,
+            refs: Cell::new(1),
+        }));
+        let allowed = if drag.allow_move {
+            DROPEFFECT_COPY | DROPEFFECT_MOVE
+        } else {
+            DROPEFFECT_COPY
+        };
+        let mut effect = DROPEFFECT_NONE;
+        let hr = DoDragDrop(
+            object.as_raw(),
+            source as *mut IUnknown,
+            allowed,
+            &mut effect,
+        );
+        drop_source_release(source as *mut IUnknown);
+        if hr != DRAGDROP_S_DROP {
+            if FAILED(hr) {
+                log::warn!("DoDragDrop failed: 0x{:x}", hr);
+            }
+            return None;
+        }
+        if effect & DROPEFFECT_MOVE != 0 {
+            Some(DropAction::Move)
+        } else if effect & DROPEFFECT_COPY != 0 {
+            Some(DropAction::Copy)
+        } else {
+            None
+        }
+    }
+}
+
+/// Our `IDropSource`, which drops when the left button is released.
+#[repr(C)]
+struct DropSource {
+    vtbl: *const DropSourceVtbl,
+    refs: Cell<ULONG>,
+}
+
+/// `IDropSourceVtbl`, which winapi doesn't have.
+#[repr(C)]
+struct DropSourceVtbl {
+    parent: IUnknownVtbl,
+    query_continue_drag: unsafe extern "system" fn(*mut IUnknown, BOOL, DWORD) -> HRESULT,
+    give_feedback: unsafe extern "system" fn(*mut IUnknown, DWORD) -> HRESULT,
+}
+
+/// `IID_IDropSource`.
+const IID_DROP_SOURCE: GUID = GUID {
+    Data1: 0x0000_0121,
+    Data2: 0x0000,
+    Data3: 0x0000,
+    Data4: [0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46],
+};
+
+static DROP_SOURCE_VTBL: DropSourceVtbl = DropSourceVtbl {
+    parent: IUnknownVtbl {
+        QueryInterface: drop_source_query_interface,
+        AddRef: drop_source_add_ref,
+        Release: drop_source_release,
+    },
+    query_continue_drag: drop_source_query_continue_drag,
+    give_feedback: drop_source_give_feedback,
+};
+
+unsafe extern "system" fn drop_source_query_interface(
+    this: *mut IUnknown,
+    riid: REFIID,
+    object: *mut *mut c_void,
+) -> HRESULT {
+    if object.is_null() {
+        return E_POINTER;
+    }
+    if IsEqualGUID(&*riid, &IUnknown::uuidof()) || IsEqualGUID(&*riid, &IID_DROP_SOURCE) {
+        drop_source_add_ref(this);
+        *object = this as *mut c_void;
+        S_OK
+    } else {
+        *object = null_mut();
+        E_NOINTERFACE
+    }
+}
+
+unsafe extern "system" fn drop_source_add_ref(this: *mut IUnknown) -> ULONG {
+    let source = &*(this as *const DropSource);
+    source.refs.set(source.refs.get() + 1);
+    source.refs.get()
+}
+
+unsafe extern "system" fn drop_source_release(this: *mut IUnknown) -> ULONG {
+    let refs = {
+        let source = &*(this as *const DropSource);
+        source.refs.set(source.refs.get() - 1);
+        source.refs.get()
+    };
+    if refs == 0 {
+        drop(Box::from_raw(this as *mut DropSource));
+    }
+    refs
+}
+
+unsafe extern "system" fn drop_source_query_continue_drag(
+    _this: *mut IUnknown,
+    escape_pressed: BOOL,
+    key_state: DWORD,
+) -> HRESULT {
+    if escape_pressed != 0 {
+        DRAGDROP_S_CANCEL
+    } else if key_state & MK_LBUTTON as DWORD == 0 {
+        DRAGDROP_S_DROP
+    } else {
+        S_OK
+    }
+}
+
+unsafe extern "system" fn drop_source_give_feedback(
+    _this: *mut IUnknown,
+    _effect: DWORD,
+) -> HRESULT {
+    DRAGDROP_S_USEDEFAULTCURSORS
+}
+
+/// A data object with the shell's, holding everything in `data`.
+unsafe fn make_data_object(data: &DragData) -> Option<ComPtr<IDataObject>> {
+    let mut object: *mut IDataObject = null_mut();
+    let hr = SHCreateDataObject(
+        null(),
+        0,
+        null(),
+        null_mut(),
+        &IDataObject::uuidof(),
+        &mut object as *mut *mut IDataObject as *mut *mut c_void,
+    );
+    if FAILED(hr) {
+        log::warn!("SHCreateDataObject failed: 0x{:x}", hr);
+        return None;
+    }
+    let object = ComPtr::from_raw(object);
+    if !data.files.is_empty() {
+        set_global(&object, CF_HDROP, drop_files(&data.files));
+    }
+    // only one link can be dragged this way
+    if let Some(url) = data.urls.first() {
+        let format = RegisterClipboardFormatW("UniformResourceLocatorW".to_wide().as_ptr());
+        set_global(
+            &object,
+            format,
+            make_handle(&ClipboardFormat::from(url.as_str())),
+        );
+    }
+    if let Some(text) = &data.text {
+        let handle = make_handle(&ClipboardFormat::from(text.as_str()));
+        set_global(&object, CF_UNICODETEXT, handle);
+    }
+    for format in &data.formats {
+        if let Some(id) = get_format_id(format.identifier) {
+            set_global(&object, id, make_handle(format));
+        }
+    }
+    Some(object)
+}
+
+/// Give `global` to `object` as `format`.
+unsafe fn set_global(object: &IDataObject, format: UINT, global: HGLOBAL) {
+    let format = FORMATETC {
+        cfFormat: format as u16,
+        ptd: null_mut(),
+        dwAspect: DVASPECT_CONTENT,
+        lindex: -1,
+        tymed: TYMED_HGLOBAL,
+    };
+    let mut medium = STGMEDIUM {
+        tymed: TYMED_HGLOBAL,
+        // the union is only as big as the handle in it
+        u: global as *mut _,
+        pUnkForRelease: null_mut(),
+    };
+    // winapi has the medium's type wrong
+    let medium = &mut medium as *mut STGMEDIUM as *const FORMATETC;
+    if object.SetData(&format, medium, TRUE) != S_OK {
+        log::warn!("failed to set drag data for format {}", format.cfFormat);
+        GlobalFree(global);
+    }
+}
+
+/// `CF_HDROP` for `files`.
+unsafe fn drop_files(files: &[PathBuf]) -> HGLOBAL {
+    let mut names: Vec<u16> = files.iter().flat_map(|file| file.to_wide()).collect();
+    names.push(0);
+    let header_size = mem::size_of::<DropFiles>();
+    let header = DropFiles {
+        files: header_size as DWORD,
+        point: POINT { x: 0, y: 0 },
+        non_client: 0,
+        wide: TRUE,
+    };
+    let names_size = names.len() * mem::size_of::<u16>();
+    let global = GlobalAlloc(GMEM_MOVEABLE, header_size + names_size);
+    let locked = GlobalLock(global) as *mut u8;
+    ptr::write_unaligned(locked as *mut DropFiles, header);
+    ptr::copy_nonoverlapping(
+        names.as_ptr() as *const u8,
+        locked.add(header_size),
+        names_size,
+    );
+    GlobalUnlock(global);
+    global
+}
+
+/// Show `image` under the cursor while `object` is dragged.
+unsafe fn set_drag_image(object: &ComPtr<IDataObject>, image: &Icon) {
+    let mut helper: *mut DragSourceHelper = null_mut();
+    let hr = CoCreateInstance(
+        &CLSID_DRAG_DROP_HELPER,
+        null_mut(),
+        CLSCTX_INPROC_SERVER,
+        &IID_DRAG_SOURCE_HELPER,
+        &mut helper as *mut *mut DragSourceHelper as *mut *mut c_void,
+    );
+    if FAILED(hr) {
+        log::warn!("failed to create the drag image helper: 0x{:x}", hr);
+        return;
+    }
+    let bitmap = make_bitmap(image);
+    if !bitmap.is_null() {
+        let (width, height) = (image.width as i32, image.height as i32);
+        let mut drag_image = ShDragImage {
+            size: SIZE {
+                cx: width,
+                cy: height,
+            },
+            offset: POINT {
+                x: width / 2,
+                y: height / 2,
+            },
+            bitmap,
+            // CLR_NONE, since the bitmap has alpha
+            color_key: 0xffff_ffff,
+        };
+        // the helper owns the bitmap once it has it
+        let init = (*(*helper).vtbl).initialize_from_bitmap;
+        if FAILED(init(helper, &mut drag_image, object.as_raw())) {
+            log::warn!("failed to set the drag image");
+            DeleteObject(bitmap as _);
+        }
+    }
+    ((*(*helper).vtbl).parent.Release)(helper as *mut IUnknown);
+}
+
+/// A top-down bitmap of `icon`, with premultiplied alpha, or null if it
+/// can't be made.
+unsafe fn make_bitmap(icon: &Icon) -> HBITMAP {
+    let mut info: BITMAPINFO = mem::zeroed();
+    info.bmiHeader = BITMAPINFOHEADER {
+        biSize: mem::size_of::<BITMAPINFOHEADER>() as DWORD,
+        biWidth: icon.width as i32,
+        biHeight: -(icon.height as i32),
+        biPlanes: 1,
+        biBitCount: 32,
+        biCompression: BI_RGB,
+        ..mem::zeroed()
+    };
+    let mut bits: *mut c_void = null_mut();
+    let bitmap = CreateDIBSection(null_mut(), &info, DIB_RGB_COLORS, &mut bits, null_mut(), 0);
+    if bitmap.is_null() || bits.is_null() {
+        log::warn!("CreateDIBSection failed");
+        return null_mut();
+    }
+    let bgra = slice::from_raw_parts_mut(bits as *mut u8, icon.rgba.len());
+    for (dst, src) in bgra.chunks_exact_mut(4).zip(icon.rgba.chunks_exact(4)) {
+        let alpha = u32::from(src[3]);
+        let premul = |c: u8| (u32::from(c) * alpha / 255) as u8;
+        dst.copy_from_slice(&[premul(src[2]), premul(src[1]), premul(src[0]), src[3]]);
+    }
+    bitmap
+}
@@ -16,14 +16,17 @@
 
 use std::collections::HashMap;
 use std::mem;
-use std::ptr::null;
+use std::ptr::{null, null_mut};
 
 use winapi::shared::basetsd::*;
+use winapi::shared::minwindef::{FALSE, TRUE};
 use winapi::shared::windef::*;
+use winapi::um::wingdi::*;
 use winapi::um::winuser::*;
 
 use super::util::ToWide;
-use crate::hotkey::{HotKey, KeyCompare};
+use crate::hotkey::{HotKey, KeyCompare, ModifierNames};
+use crate::window::Icon;
 use crate::KeyModifiers;
 
 /// A menu object, which can be either a top-level menubar or a
@@ -31,6 +34,32 @@ use crate::KeyModifiers;
 pub struct Menu {
     hmenu: HMENU,
     accels: HashMap<u32, ACCEL>,
+    modifier_names: ModifierNames,
+    hidden: HiddenItems,
+    bitmaps: MenuBitmaps,
+}
+
+/// The bitmaps shown next to the items of a menu and its submenus.
+///
+/// Menus don't own their bitmaps, so these are deleted when this is dropped,
+/// which must not be before the menu is destroyed.
+#[derive(Default)]
+pub(crate) struct MenuBitmaps(Vec<HBITMAP>);
+
+/// The items of a menu and its submenus that are hidden.
+///
+/// Win32 menus can't hide items, so a hidden item is taken out of its menu,
+/// and put back in the same place when it is shown.
+#[derive(Default)]
+pub(crate) struct HiddenItems(HashMap<u32, HiddenItem>);
+
+struct HiddenItem {
+    /// The menu or submenu the item goes in.
+    hmenu: HMENU,
+    /// Where the item goes among all of the items of `hmenu`, hidden or not.
+    index: u32,
+    info: MENUITEMINFOW,
+    text: Vec<u16>,
 }
 
 impl Drop for Menu {
@@ -41,6 +70,16 @@ impl Drop for Menu {
     }
 }
 
+impl Drop for MenuBitmaps {
+    fn drop(&mut self) {
+        for &bitmap in &self.0 {
+            unsafe {
+                DeleteObject(bitmap as _);
+            }
+        }
+    }
+}
+
 impl Menu {
     /// Create a new menu for a window.
     pub fn new() -> Menu {
@@ -49,6 +88,9 @@ impl Menu {
             Menu {
                 hmenu,
                 accels: HashMap::default(),
+                modifier_names: ModifierNames::default(),
+                hidden: HiddenItems::default(),
+                bitmaps: MenuBitmaps::default(),
             }
         }
     }
@@ -60,10 +102,24 @@ impl Menu {
             Menu {
                 hmenu,
                 accels: HashMap::default(),
+                modifier_names: ModifierNames::default(),
+                hidden: HiddenItems::default(),
+                bitmaps: MenuBitmaps::default(),
             }
         }
     }
 
+    /// The items hidden so far, which their window keeps to show them again.
+    pub(crate) fn take_hidden(&mut self) -> HiddenItems {
+        mem::take(&mut self.hidden)
+    }
+
+    /// The bitmaps of the menu's items, which its window keeps for as long
+    /// as it shows the menu.
+    pub(crate) fn take_bitmaps(&mut self) -> MenuBitmaps {
+        mem::take(&mut self.bitmaps)
+    }
+
     pub fn into_hmenu(self) -> HMENU {
         let hmenu = self.hmenu;
         mem::forget(self);
@@ -77,6 +133,9 @@ impl Menu {
     pub fn add_dropdown(&mut self, mut menu: Menu, text: &str, enabled: bool) {
         let child_accels = std::mem::take(&mut menu.accels);
         self.accels.extend(child_accels);
+        let child_hidden = menu.take_hidden();
+        self.hidden.0.extend(child_hidden.0);
+        self.bitmaps.0.append(&mut menu.bitmaps.0);
 
         unsafe {
             let mut flags = MF_POPUP;
@@ -92,7 +151,14 @@ impl Menu {
         }
     }
 
+    pub fn set_modifier_names(&mut self, names: ModifierNames) {
+        self.modifier_names = names;
+    }
+
     /// Add an item to the menu.
+    ///
+    /// Win32 menus show the text after a tab as the accelerator, so the
+    /// hotkey is added there.
     pub fn add_item(
         &mut self,
         id: u32,
@@ -109,6 +175,10 @@ impl Menu {
             if selected {
                 flags |= MF_CHECKED;
             }
+            let text = match key {
+                Some(key) => format!("{}\t{}", text, key.display_text(&self.modifier_names)),
+                None => text.to_string(),
+            };
             AppendMenuW(self.hmenu, flags, id as UINT_PTR, text.to_wide().as_ptr());
         }
 
@@ -119,6 +189,42 @@ impl Menu {
         }
     }
 
+    /// Windows menus only display bitmaps, not named icons.
+    pub fn set_item_icon(&mut self, _id: u32, _icon: &str) {}
+
+    pub fn set_item_image(&mut self, id: u32, image: &Icon) {
+        unsafe {
+            let bitmap = create_menu_bitmap(image);
+            if bitmap.is_null() {
+                log::warn!("failed to create menu item bitmap");
+                return;
+            }
+            let mut info: MENUITEMINFOW = mem::zeroed();
+            info.cbSize = mem::size_of::<MENUITEMINFOW>() as u32;
+            info.fMask = MIIM_BITMAP;
+            info.hbmpItem = bitmap;
+            SetMenuItemInfoW(self.hmenu, id, FALSE, &info);
+            self.bitmaps.0.push(bitmap);
+        }
+    }
+
+    /// Draw the item's check mark as a bullet.
+    pub fn set_item_radio(&mut self, id: u32, radio: bool) {
+        unsafe {
+            let mut info: MENUITEMINFOW = mem::zeroed();
+            info.cbSize = mem::size_of::<MENUITEMINFOW>() as u32;
+            info.fMask = MIIM_FTYPE;
+            info.fType = if radio { MFT_RADIOCHECK } else { 0 } | MFT_STRING;
+            SetMenuItemInfoW(self.hmenu, id, FALSE, &info);
+        }
+    }
+
+    pub fn set_item_hidden(&mut self, id: u32, hidden: bool) {
+        unsafe {
+            self.hidden.set_hidden(self.hmenu, id, hidden);
+        }
+    }
+
     /// Add a separator to the menu.
     pub fn add_separator(&mut self) {
         unsafe {
@@ -135,7 +241,155 @@ impl Menu {
     }
 }
 
-fn convert_hotkey(id: u32, key: &HotKey) -> Option<ACCEL> {
+impl HiddenItems {
+    /// Whether the item with the command `id` is hidden.
+    pub(crate) fn contains(&self, id: u32) -> bool {
+        self.0.contains_key(&id)
+    }
+
+    /// Hide or show the item with the command `id`, in `root` or one of its
+    /// submenus.
+    pub(crate) unsafe fn set_hidden(&mut self, root: HMENU, id: u32, hidden: bool) {
+        if hidden {
+            self.hide(root, id);
+        } else {
+            self.show(id);
+        }
+    }
+
+    unsafe fn hide(&mut self, root: HMENU, id: u32) {
+        if self.contains(id) {
+            return;
+        }
+        let (hmenu, position) = match find_item(root, id) {
+            Some(found) => found,
+            None => return,
+        };
+        let mut info: MENUITEMINFOW = mem::zeroed();
+        info.cbSize = mem::size_of::<MENUITEMINFOW>() as u32;
+        info.fMask = MIIM_FTYPE | MIIM_STATE | MIIM_ID | MIIM_STRING | MIIM_BITMAP;
+        // the first call gives the length of the text, without the nul
+        if GetMenuItemInfoW(hmenu, position, TRUE, &mut info) == FALSE {
+            return;
+        }
+        let mut text = vec![0u16; info.cch as usize + 1];
+        info.cch += 1;
+        info.dwTypeData = text.as_mut_ptr();
+        if GetMenuItemInfoW(hmenu, position, TRUE, &mut info) == FALSE {
+            return;
+        }
+        // count the hidden items before this one, to find its place among all
+        let mut before: Vec<u32> = self
+            .0
+            .values()
+            .filter(|item| item.hmenu == hmenu)
+            .map(|item| item.index)
+            .collect();
+        before.sort_unstable();
+        let mut index = position;
+        for hidden in before {
+            if hidden <= index {
+                index += 1;
+            }
+        }
+        if RemoveMenu(hmenu, position, MF_BYPOSITION) == FALSE {
+            return;
+        }
+        let item = HiddenItem {
+            hmenu,
+            index,
+            info,
+            text,
+        };
+        self.0.insert(id, item);
+    }
+
+    unsafe fn show(&mut self, id: u32) {
+        let mut item = match self.0.remove(&id) {
+            Some(item) => item,
+            None => return,
+        };
+        let hidden_before = self
+            .0
+            .values()
+            .filter(|other| other.hmenu == item.hmenu && other.index < item.index)
+            .count() as u32;
+        item.info.dwTypeData = item.text.as_mut_ptr();
+        InsertMenuItemW(item.hmenu, item.index - hidden_before, TRUE, &item.info);
+    }
+
+    /// Enable or disable a hidden item, for when it is shown again.
+    pub(crate) fn set_enabled(&mut self, id: u32, enabled: bool) -> bool {
+        match self.0.get_mut(&id) {
+            Some(item) => {
+                if enabled {
+                    item.info.fState &= !MFS_DISABLED;
+                } else {
+                    item.info.fState |= MFS_DISABLED;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Change the text of a hidden item, for when it is shown again, keeping
+    /// the hotkey that follows a tab. Returns `false` if it isn't hidden.
+    pub(crate) fn set_title(&mut self, id: u32, title: &str) -> bool {
+        match self.0.get_mut(&id) {
+            Some(item) => {
+                let text = String::from_utf16_lossy(&item.text[..item.text.len() - 1]);
+                let hotkey = text.find('\t').map_or("", |tab| &text[tab..]);
+                item.text = format!("{}{}", title, hotkey).to_wide();
+                item.info.cch = item.text.len() as u32 - 1;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A bitmap of `image`, premultiplied as menus draw 32-bit bitmaps with
+/// their alpha channel.
+unsafe fn create_menu_bitmap(image: &Icon) -> HBITMAP {
+    let mut info: BITMAPINFO = mem::zeroed();
+    info.bmiHeader.biSize = mem::size_of::<BITMAPINFOHEADER>() as u32;
+    info.bmiHeader.biWidth = image.width as i32;
+    // a negative height puts the first row at the top
+    info.bmiHeader.biHeight = -(image.height as i32);
+    info.bmiHeader.biPlanes = 1;
+    info.bmiHeader.biBitCount = 32;
+    info.bmiHeader.biCompression = BI_RGB;
+    let mut bits = null_mut();
+    let bitmap = CreateDIBSection(null_mut(), &info, DIB_RGB_COLORS, &mut bits, null_mut(), 0);
+    if bitmap.is_null() {
+        return null_mut();
+    }
+    let bgra = std::slice::from_raw_parts_mut(bits as *mut u8, image.rgba.len());
+    for (dst, p) in bgra.chunks_exact_mut(4).zip(image.rgba.chunks_exact(4)) {
+        let premul = |c: u8| (c as u32 * p[3] as u32 / 255) as u8;
+        dst.copy_from_slice(&[premul(p[2]), premul(p[1]), premul(p[0]), p[3]]);
+    }
+    bitmap
+}
+
+/// The menu holding the item with the command `id`, `hmenu` or one of its
+/// submenus, and the item's position in it.
+unsafe fn find_item(hmenu: HMENU, id: u32) -> Option<(HMENU, u32)> {
+    for position in 0..GetMenuItemCount(hmenu).max(0) {
+        let submenu = GetSubMenu(hmenu, position);
+        if !submenu.is_null() {
+            if let Some(found) = find_item(submenu, id) {
+                return Some(found);
+            }
+        } else if GetMenuItemID(hmenu, position) == id {
+            return Some((hmenu, position as u32));
+        }
+    }
+    None
+}
+
+pub(crate) fn convert_hotkey(id: u32, key: &HotKey) -> Option<ACCEL> {
     let mut virt_key = FVIRTKEY;
     let key_mods: KeyModifiers = key.mods.into();
     if key_mods.ctrl {
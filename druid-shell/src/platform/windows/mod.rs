@@ -19,10 +19,12 @@ pub mod application;
 pub mod clipboard;
 pub mod dcomp;
 pub mod dialog;
+mod drag;
 pub mod error;
 pub mod keycodes;
 pub mod menu;
 pub mod paint;
+pub mod screen;
 mod timers;
 pub mod util;
 pub mod window;
@@ -0,0 +1,84 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Windows monitor information.
+
+use std::mem;
+use std::ptr::null_mut;
+
+use winapi::shared::minwindef::*;
+use winapi::shared::windef::*;
+use winapi::um::shellscalingapi::MDT_EFFECTIVE_DPI;
+use winapi::um::winuser::*;
+
+use super::util::OPTIONAL_FUNCTIONS;
+use crate::kurbo::Rect;
+use crate::screen::Monitor;
+
+pub(crate) fn get_monitors() -> Vec<Monitor> {
+    let mut monitors = Vec::new();
+    unsafe {
+        EnumDisplayMonitors(
+            null_mut(),
+            null_mut(),
+            Some(monitor_enum_proc),
+            &mut monitors as *mut Vec<Monitor> as LPARAM,
+        );
+    }
+    monitors
+}
+
+unsafe extern "system" fn monitor_enum_proc(
+    hmonitor: HMONITOR,
+    _hdc: HDC,
+    _rect: LPRECT,
+    data: LPARAM,
+) -> BOOL {
+    let monitors = &mut *(data as *mut Vec<Monitor>);
+    let mut info: MONITORINFO = mem::zeroed();
+    info.cbSize = mem::size_of::<MONITORINFO>() as DWORD;
+    if GetMonitorInfoW(hmonitor, &mut info) == 0 {
+        log::warn!("GetMonitorInfoW failed.");
+        return TRUE;
+    }
+    monitors.push(Monitor {
+        primary: info.dwFlags & MONITORINFOF_PRIMARY != 0,
+        rect: to_rect(info.rcMonitor),
+        work_rect: to_rect(info.rcWork),
+        scale: get_scale(hmonitor),
+    });
+    TRUE
+}
+
+fn get_scale(hmonitor: HMONITOR) -> f64 {
+    // GetDpiForMonitor is only available from windows 8.1
+    if let Some(func) = OPTIONAL_FUNCTIONS.GetDpiForMonitor {
+        let (mut dpi_x, mut dpi_y) = (96, 96);
+        unsafe {
+            func(hmonitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y);
+        }
+        f64::from(dpi_x) / 96.0
+    } else {
+        1.0
+    }
+}
+
+fn to_rect(rect: RECT) -> Rect {
+    Rect::new(
+        f64::from(rect.left),
+        f64::from(rect.top),
+        f64::from(rect.right),
+        f64::from(rect.bottom),
+    )
+}
@@ -32,10 +32,22 @@ use winapi::shared::dxgitype::*;
 use winapi::shared::minwindef::*;
 use winapi::shared::windef::*;
 use winapi::shared::winerror::*;
+use winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+use winapi::um::combaseapi::CoCreateInstance;
 use winapi::um::d2d1::*;
+use winapi::um::shellapi::{DragQueryFileW, HDROP};
+use winapi::um::shobjidl_core::{CLSID_TaskbarList, ITaskbarList3, TBPF_NOPROGRESS, TBPF_NORMAL};
 use winapi::um::unknwnbase::*;
+use winapi::um::wingdi::{CreateBitmap, DeleteObject};
 use winapi::um::winnt::*;
 use winapi::um::winuser::*;
+use winapi::Interface;
+use wio::com::ComPtr;
+
+#[cfg(feature = "raw-win-handle")]
+use raw_window_handle::{
+    RawDisplayHandle, RawWindowHandle, Win32WindowHandle, WindowsDisplayHandle,
+};
 
 use piet_common::d2d::{D2DFactory, DeviceContext};
 use piet_common::dwrite::DwriteFactory;
@@ -46,25 +58,47 @@ use crate::kurbo::{Point, Size, Vec2};
 use crate::piet::{Piet, RenderContext};
 
 use super::accels::register_accel;
+use super::application::Application;
 use super::dcomp::{D3D11Device, DCompositionDevice, DCompositionTarget, DCompositionVisual};
 use super::dialog::get_file_dialog_path;
+use super::drag::{
+    register_drop_target, revoke_drop_target, run_drag, Dropped, OutgoingDrag, XI_DRAG_LEFT,
+    XI_DRAG_MOVED, XI_DROPPED, XI_START_DRAG,
+};
 use super::error::Error;
-use super::menu::Menu;
+use super::menu::{convert_hotkey, HiddenItems, Menu, MenuBitmaps};
 use super::paint;
 use super::timers::TimerSlots;
 use super::util::{as_result, FromWide, ToWide, OPTIONAL_FUNCTIONS};
 
 use crate::common_util::IdleCallback;
 use crate::dialog::{FileDialogOptions, FileDialogType, FileInfo};
+use crate::drag::DragData;
+use crate::hotkey::HotKey;
 use crate::keyboard::{KeyEvent, KeyModifiers};
 use crate::keycodes::KeyCode;
-use crate::mouse::{Cursor, MouseButton, MouseEvent};
-use crate::window::{IdleToken, Text, TimerToken, WinHandler};
+use crate::mouse::{Cursor, CursorDesc, MouseButton, MouseEvent};
+use crate::touch_bar::TouchBar;
+use crate::window::{GlobalHotKeyToken, Icon, IdleToken, Text, TimerToken, WinHandler};
 
 extern "system" {
     pub fn DwmFlush();
 }
 
+/// A cursor created from an image, destroyed when the last clone is dropped.
+#[derive(Clone)]
+pub struct CustomCursor(Rc<HCursor>);
+
+struct HCursor(HCURSOR);
+
+impl Drop for HCursor {
+    fn drop(&mut self) {
+        unsafe {
+            DestroyCursor(self.0);
+        }
+    }
+}
+
 /// Builder abstraction for creating new windows.
 pub struct WindowBuilder {
     handler: Option<Box<dyn WinHandler>>,
@@ -73,6 +107,11 @@ pub struct WindowBuilder {
     menu: Option<Menu>,
     present_strategy: PresentStrategy,
     size: Size,
+    always_on_top: bool,
+    skip_taskbar: bool,
+    utility: bool,
+    #[cfg(feature = "raw-win-handle")]
+    parent: Option<RawWindowHandle>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -132,6 +171,16 @@ struct WindowState {
     wndproc: Box<dyn WndProc>,
     idle_queue: Arc<Mutex<Vec<IdleKind>>>,
     timers: Arc<Mutex<TimerSlots>>,
+    global_hotkeys: RefCell<Vec<GlobalHotKeyToken>>,
+    /// Whether the window's taskbar button is removed each time it is shown.
+    skip_taskbar: Cell<bool>,
+    /// The items of the window's menu that are hidden.
+    hidden_menu_items: RefCell<HiddenItems>,
+    /// The bitmaps of the window's menu items.
+    menu_bitmaps: RefCell<MenuBitmaps>,
+    /// A drag out of the window, started once the message that asked for it
+    /// has been handled.
+    pending_drag: RefCell<Option<OutgoingDrag>>,
 }
 
 /// Generic handler trait for the winapi window procedure entry point.
@@ -164,6 +213,13 @@ struct WndState {
     /// The `char` of the last `WM_CHAR` event, if there has not already been
     /// a `WM_KEYUP` event.
     stashed_char: Option<char>,
+    /// The first half of a character outside the BMP, which `WM_CHAR` sends
+    /// as two UTF-16 surrogates.
+    stashed_surrogate: Option<u16>,
+    /// The last text scale we reported, so that we only notify on change.
+    text_scale: f64,
+    /// The last reduced motion preference we reported.
+    reduce_motion: bool,
     //TODO: track surrogate orphan
 }
 
@@ -306,6 +362,100 @@ impl WndProc for MyWndProc {
                 Some(0)
             }
             WM_ERASEBKGND => Some(0),
+            WM_SETTINGCHANGE => {
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    let s = s.as_mut().unwrap();
+                    let scale = Application::get_text_scale();
+                    if scale != s.text_scale {
+                        s.text_scale = scale;
+                        s.handler.text_scale_changed(scale);
+                    }
+                    let reduce_motion = Application::get_reduce_motion();
+                    if reduce_motion != s.reduce_motion {
+                        s.reduce_motion = reduce_motion;
+                        s.handler.reduce_motion_changed(reduce_motion);
+                    }
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                None
+            }
+            WM_DPICHANGED => unsafe {
+                let dpi = f32::from(HIWORD(wparam as u32));
+                if let Some(state) = self.handle.borrow().state.upgrade() {
+                    state.dpi.set(dpi);
+                }
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    let s = s.as_mut().unwrap();
+                    s.dpi = dpi;
+                    // a swap chain's render target is rebuilt with the new
+                    // dpi when it is resized below; one on the hwnd is made
+                    // again when it is next painted.
+                    if s.dcomp_state.is_none() {
+                        s.render_target = None;
+                    }
+                    s.handler.scale_changed(f64::from(dpi) / 96.0);
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                // the size and position windows suggests for the new dpi
+                let rect = &*(lparam as *const RECT);
+                SetWindowPos(
+                    hwnd,
+                    null_mut(),
+                    rect.left,
+                    rect.top,
+                    rect.right - rect.left,
+                    rect.bottom - rect.top,
+                    SWP_NOZORDER | SWP_NOACTIVATE,
+                );
+                Some(0)
+            },
+            WM_DISPLAYCHANGE => {
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    s.as_mut().unwrap().handler.monitors_changed();
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                None
+            }
+            XI_DRAG_MOVED => {
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    let s = s.as_mut().unwrap();
+                    let (px, py) = self
+                        .handle
+                        .borrow()
+                        .pixels_to_px_xy(wparam as i32, lparam as i32);
+                    s.handler.drag_moved(Point::new(px as f64, py as f64));
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                Some(0)
+            }
+            XI_DRAG_LEFT => {
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    s.as_mut().unwrap().handler.drag_left();
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                Some(0)
+            }
+            XI_DROPPED => unsafe {
+                let dropped = &mut *(lparam as *mut Dropped);
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    let s = s.as_mut().unwrap();
+                    let (px, py) = self
+                        .handle
+                        .borrow()
+                        .pixels_to_px_xy(dropped.pos.x, dropped.pos.y);
+                    let pos = Point::new(px as f64, py as f64);
+                    let data = mem::take(&mut dropped.data);
+                    Some(s.handler.dropped(pos, data) as LRESULT)
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                    Some(0)
+                }
+            },
             WM_SETFOCUS => {
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
@@ -315,6 +465,15 @@ impl WndProc for MyWndProc {
                 }
                 Some(0)
             }
+            WM_KILLFOCUS => {
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    let s = s.as_mut().unwrap();
+                    s.handler.lost_focus();
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                Some(0)
+            }
             WM_PAINT => unsafe {
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
@@ -451,21 +610,44 @@ impl WndProc for MyWndProc {
                 Some(0)
             },
             WM_COMMAND => {
+                let id = LOWORD(wparam as u32) as u32;
+                // the hotkeys of hidden menu items are still in the table
+                let hidden = match self.handle.borrow().state.upgrade() {
+                    Some(w) => w.hidden_menu_items.borrow().contains(id),
+                    None => false,
+                };
+                if hidden {
+                    return Some(0);
+                }
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
-                    s.handler.command(LOWORD(wparam as u32) as u32);
+                    s.handler.command(id);
                 } else {
                     self.log_dropped_msg(hwnd, msg, wparam, lparam);
                 }
                 Some(0)
             }
+            // TranslateMessage composes dead keys with the key after them, so
+            // WM_CHAR has the composed character; WM_DEADCHAR, for the dead
+            // key itself, is left to DefWindowProc.
             WM_CHAR => {
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
-                    //FIXME: this can receive lone surrogate pairs?
                     let key_code = s.stashed_key_code;
 
-                    s.stashed_char = std::char::from_u32(wparam as u32);
+                    let unit = wparam as u32;
+                    if (0xD800..0xDC00).contains(&unit) {
+                        s.stashed_surrogate = Some(unit as u16);
+                        return Some(0);
+                    }
+                    s.stashed_char = match s.stashed_surrogate.take() {
+                        Some(high) if (0xDC00..0xE000).contains(&unit) => {
+                            std::char::decode_utf16([high, unit as u16].iter().copied())
+                                .next()
+                                .and_then(Result::ok)
+                        }
+                        _ => std::char::from_u32(unit),
+                    };
                     let text = match s.stashed_char {
                         Some(c) => c,
                         None => {
@@ -643,7 +825,23 @@ impl WndProc for MyWndProc {
                 }
                 Some(0)
             }
+            XI_START_DRAG => {
+                let drag = self.handle.borrow().take_pending_drag();
+                if let Some(drag) = drag {
+                    // the drag's own message loop sends us messages that need
+                    // the handler, so it is only borrowed once the drag ends
+                    let action = run_drag(drag);
+                    if let Ok(mut s) = self.state.try_borrow_mut() {
+                        s.as_mut().unwrap().handler.drag_ended(action);
+                    } else {
+                        self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                    }
+                }
+                Some(0)
+            }
             WM_DESTROY => {
+                self.handle.borrow().unregister_all_global_hotkeys();
+                revoke_drop_target(hwnd);
                 if let Ok(mut s) = self.state.try_borrow_mut() {
                     let s = s.as_mut().unwrap();
                     s.handler.destroy();
@@ -652,6 +850,15 @@ impl WndProc for MyWndProc {
                 }
                 None
             }
+            WM_HOTKEY => {
+                let token = GlobalHotKeyToken::from_raw(wparam as u64);
+                if let Ok(mut s) = self.state.try_borrow_mut() {
+                    s.as_mut().unwrap().handler.global_hotkey(token);
+                } else {
+                    self.log_dropped_msg(hwnd, msg, wparam, lparam);
+                }
+                Some(0)
+            }
             WM_TIMER => {
                 let id = wparam;
                 unsafe {
@@ -703,7 +910,32 @@ impl WindowBuilder {
             menu: None,
             present_strategy: Default::default(),
             size: Size::new(500.0, 400.0),
+            always_on_top: false,
+            skip_taskbar: false,
+            utility: false,
+            #[cfg(feature = "raw-win-handle")]
+            parent: None,
+        }
+    }
+
+    #[cfg(feature = "raw-win-handle")]
+    pub fn set_parent(&mut self, parent: RawWindowHandle) {
+        self.parent = Some(parent);
+    }
+
+    /// The parent window and the style to create the window with.
+    ///
+    /// An embedded window is a child of its parent, and has no frame.
+    fn parent_and_style(&self) -> Result<(HWND, DWORD), Error> {
+        #[cfg(feature = "raw-win-handle")]
+        match self.parent {
+            Some(RawWindowHandle::Win32(parent)) if !parent.hwnd.is_null() => {
+                return Ok((parent.hwnd as HWND, WS_CHILD | WS_VISIBLE | WS_CLIPSIBLINGS))
+            }
+            Some(_) => return Err(Error::NullHwnd),
+            None => (),
         }
+        Ok((0 as HWND, self.dwStyle))
     }
 
     /// This takes ownership, and is typically used with UiMain
@@ -723,7 +955,20 @@ impl WindowBuilder {
         self.menu = Some(menu);
     }
 
+    pub fn set_always_on_top(&mut self, on_top: bool) {
+        self.always_on_top = on_top;
+    }
+
+    pub fn set_skip_taskbar(&mut self, skip: bool) {
+        self.skip_taskbar = skip;
+    }
+
+    pub fn set_utility(&mut self, utility: bool) {
+        self.utility = utility;
+    }
+
     pub fn build(self) -> Result<WindowHandle, Error> {
+        let (parent, style) = self.parent_and_style()?;
         unsafe {
             // Maybe separate registration in build api? Probably only need to
             // register once even for multiple window creation.
@@ -745,6 +990,11 @@ impl WindowBuilder {
                 wndproc: Box::new(wndproc),
                 idle_queue: Default::default(),
                 timers: Arc::new(Mutex::new(TimerSlots::new(1))),
+                global_hotkeys: RefCell::new(Vec::new()),
+                skip_taskbar: Cell::new(self.skip_taskbar),
+                hidden_menu_items: RefCell::new(HiddenItems::default()),
+                menu_bitmaps: RefCell::new(MenuBitmaps::default()),
+                pending_drag: RefCell::new(None),
             };
             let win = Rc::new(window);
             let handle = WindowHandle {
@@ -770,15 +1020,22 @@ impl WindowBuilder {
                 dpi,
                 stashed_key_code: KeyCode::Unknown(0),
                 stashed_char: None,
+                stashed_surrogate: None,
+                text_scale: Application::get_text_scale(),
+                reduce_motion: Application::get_reduce_motion(),
             };
             win.wndproc.connect(&handle, state);
 
             let width = (self.size.width * (f64::from(dpi) / 96.0)) as i32;
             let height = (self.size.height * (f64::from(dpi) / 96.0)) as i32;
 
-            let (hmenu, accels) = match self.menu {
-                Some(menu) => {
+            // for a child window, the menu handle is its id instead.
+            let menu = if parent.is_null() { self.menu } else { None };
+            let (hmenu, accels) = match menu {
+                Some(mut menu) => {
                     let accels = menu.accels();
+                    win.hidden_menu_items.replace(menu.take_hidden());
+                    win.menu_bitmaps.replace(menu.take_bitmaps());
                     (menu.into_hmenu(), accels)
                 }
                 None => (0 as HMENU, None),
@@ -787,16 +1044,22 @@ impl WindowBuilder {
             if self.present_strategy == PresentStrategy::Flip {
                 dwExStyle |= WS_EX_NOREDIRECTIONBITMAP;
             }
+            if self.always_on_top {
+                dwExStyle |= WS_EX_TOPMOST;
+            }
+            if self.utility {
+                dwExStyle |= WS_EX_TOOLWINDOW;
+            }
             let hwnd = create_window(
                 dwExStyle,
                 class_name.as_ptr(),
                 self.title.to_wide().as_ptr(),
-                self.dwStyle,
-                CW_USEDEFAULT,
-                CW_USEDEFAULT,
+                style,
+                if parent.is_null() { CW_USEDEFAULT } else { 0 },
+                if parent.is_null() { CW_USEDEFAULT } else { 0 },
                 width,
                 height,
-                0 as HWND,
+                parent,
                 hmenu,
                 0 as HINSTANCE,
                 win,
@@ -808,6 +1071,7 @@ impl WindowBuilder {
             if let Some(accels) = accels {
                 register_accel(hwnd, &accels);
             }
+            register_drop_target(hwnd);
             Ok(handle)
         }
     }
@@ -955,6 +1219,21 @@ pub(crate) unsafe extern "system" fn win_proc_dispatch(
     }
 }
 
+/// Read the file paths from a dropped `HDROP`.
+pub(crate) unsafe fn get_dropped_files(hdrop: HDROP) -> DragData {
+    let mut data = DragData::default();
+    let count = DragQueryFileW(hdrop, 0xFFFF_FFFF, null_mut(), 0);
+    for i in 0..count {
+        // the returned length does not include the terminating null
+        let len = DragQueryFileW(hdrop, i, null_mut(), 0) + 1;
+        let mut buf = vec![0u16; len as usize];
+        DragQueryFileW(hdrop, i, buf.as_mut_ptr(), len);
+        buf.truncate(len as usize - 1);
+        data.files.push(buf.to_os_string().into());
+    }
+    data
+}
+
 /// Create a window (same parameters as CreateWindowExW) with associated WndProc.
 #[allow(clippy::too_many_arguments)]
 unsafe fn create_window(
@@ -988,16 +1267,23 @@ unsafe fn create_window(
 }
 
 impl Cursor {
-    fn get_lpcwstr(&self) -> LPCWSTR {
-        match self {
+    fn get_hcursor(&self) -> HCURSOR {
+        let name = match self {
             Cursor::Arrow => IDC_ARROW,
             Cursor::IBeam => IDC_IBEAM,
             Cursor::Crosshair => IDC_CROSS,
-            Cursor::OpenHand => IDC_HAND,
+            // Windows has no grab cursors; the hand is the closest.
+            Cursor::OpenHand | Cursor::ClosedHand => IDC_HAND,
             Cursor::NotAllowed => IDC_NO,
             Cursor::ResizeLeftRight => IDC_SIZEWE,
             Cursor::ResizeUpDown => IDC_SIZENS,
-        }
+            Cursor::ResizeTopLeftBottomRight => IDC_SIZENWSE,
+            Cursor::ResizeTopRightBottomLeft => IDC_SIZENESW,
+            // ...nor zoom cursors.
+            Cursor::ZoomIn | Cursor::ZoomOut => IDC_CROSS,
+            Cursor::Custom(custom) => return (custom.0).0,
+        };
+        unsafe { LoadCursorW(0 as HINSTANCE, name) }
     }
 }
 
@@ -1020,6 +1306,10 @@ impl WindowHandle {
                 ShowWindow(hwnd, SW_SHOWNORMAL);
                 UpdateWindow(hwnd);
             }
+            // the taskbar adds a button for the window as it is shown
+            if w.skip_taskbar.get() {
+                set_taskbar_button(hwnd, false);
+            }
         }
     }
 
@@ -1059,10 +1349,13 @@ impl WindowHandle {
         }
     }
 
-    pub fn set_menu(&self, menu: Menu) {
+    pub fn set_menu(&self, mut menu: Menu) {
         let accels = menu.accels();
+        let hidden = menu.take_hidden();
+        let bitmaps = menu.take_bitmaps();
         let hmenu = menu.into_hmenu();
         if let Some(w) = self.state.upgrade() {
+            w.hidden_menu_items.replace(hidden);
             let hwnd = w.hwnd.get();
             unsafe {
                 let old_menu = GetMenu(hwnd);
@@ -1070,6 +1363,8 @@ impl WindowHandle {
                     warn!("failed to set window menu");
                 } else {
                     DestroyMenu(old_menu);
+                    // the old menu's bitmaps are deleted now it is gone
+                    w.menu_bitmaps.replace(bitmaps);
                 }
                 if let Some(accels) = accels {
                     register_accel(hwnd, &accels);
@@ -1078,7 +1373,159 @@ impl WindowHandle {
         }
     }
 
-    pub fn show_context_menu(&self, menu: Menu, pos: Point) {
+    pub fn set_menu_item_enabled(&self, id: u32, enabled: bool) {
+        if let Some(w) = self.state.upgrade() {
+            let hwnd = w.hwnd.get();
+            if w.hidden_menu_items.borrow_mut().set_enabled(id, enabled) {
+                return;
+            }
+            let flags = if enabled { MF_ENABLED } else { MF_GRAYED };
+            unsafe {
+                // by command, so that submenus are searched as well
+                EnableMenuItem(GetMenu(hwnd), id, MF_BYCOMMAND | flags);
+                DrawMenuBar(hwnd);
+            }
+        }
+    }
+
+    pub fn set_menu_item_title(&self, id: u32, title: &str) {
+        if let Some(w) = self.state.upgrade() {
+            if w.hidden_menu_items.borrow_mut().set_title(id, title) {
+                return;
+            }
+            let hwnd = w.hwnd.get();
+            unsafe {
+                // keep the hotkey, which follows a tab
+                let hotkey = menu_item_text(GetMenu(hwnd), id)
+                    .and_then(|text| text.find('\t').map(|tab| text[tab..].to_string()))
+                    .unwrap_or_default();
+                let mut title = format!("{}{}", title, hotkey).to_wide();
+                let mut info: MENUITEMINFOW = mem::zeroed();
+                info.cbSize = mem::size_of::<MENUITEMINFOW>() as u32;
+                info.fMask = MIIM_STRING;
+                info.dwTypeData = title.as_mut_ptr();
+                if SetMenuItemInfoW(GetMenu(hwnd), id, FALSE, &info) == FALSE {
+                    warn!("failed to set menu item title");
+                }
+                DrawMenuBar(hwnd);
+            }
+        }
+    }
+
+    pub fn set_menu_item_hidden(&self, id: u32, hidden: bool) {
+        if let Some(w) = self.state.upgrade() {
+            let hwnd = w.hwnd.get();
+            unsafe {
+                let mut items = w.hidden_menu_items.borrow_mut();
+                items.set_hidden(GetMenu(hwnd), id, hidden);
+                DrawMenuBar(hwnd);
+            }
+        }
+    }
+
+    pub fn start_drag(&self, data: DragData, image: Option<&Icon>, allow_move: bool) {
+        if let Some(w) = self.state.upgrade() {
+            // like destroying the window, this can't be done while the
+            // handler is borrowed
+            w.pending_drag.replace(Some(OutgoingDrag {
+                data,
+                image: image.cloned(),
+                allow_move,
+            }));
+            unsafe {
+                PostMessageW(w.hwnd.get(), XI_START_DRAG, 0, 0);
+            }
+        }
+    }
+
+    fn take_pending_drag(&self) -> Option<OutgoingDrag> {
+        self.state
+            .upgrade()
+            .and_then(|w| w.pending_drag.borrow_mut().take())
+    }
+
+    pub fn set_progress(&self, progress: Option<f64>) {
+        let hwnd = match self.state.upgrade() {
+            Some(w) => w.hwnd.get(),
+            None => return,
+        };
+        // the progress is shown in steps of this size
+        const TOTAL: u64 = 1000;
+        let taskbar = match taskbar_list() {
+            Some(taskbar) => taskbar,
+            None => return,
+        };
+        unsafe {
+            let hr = match progress {
+                Some(progress) => {
+                    taskbar.SetProgressState(hwnd, TBPF_NORMAL);
+                    let completed = (progress * TOTAL as f64).round() as u64;
+                    taskbar.SetProgressValue(hwnd, completed, TOTAL)
+                }
+                None => taskbar.SetProgressState(hwnd, TBPF_NOPROGRESS),
+            };
+            if let Err(e) = as_result(hr) {
+                warn!("failed to set taskbar progress: {}", e);
+            }
+        }
+    }
+
+    pub fn set_touch_bar(&self, _touch_bar: TouchBar) {}
+
+    pub fn register_global_hotkey(&self, hotkey: &HotKey) -> Option<GlobalHotKeyToken> {
+        let w = self.state.upgrade()?;
+        let accel = convert_hotkey(0, hotkey)?;
+        let mut mods = MOD_NOREPEAT as UINT;
+        if accel.fVirt & FCONTROL != 0 {
+            mods |= MOD_CONTROL as UINT;
+        }
+        if accel.fVirt & FALT != 0 {
+            mods |= MOD_ALT as UINT;
+        }
+        if accel.fVirt & FSHIFT != 0 {
+            mods |= MOD_SHIFT as UINT;
+        }
+        if KeyModifiers::from(hotkey.mods).meta {
+            mods |= MOD_WIN as UINT;
+        }
+        let token = GlobalHotKeyToken::next();
+        // the id is sent back to us as the wparam of WM_HOTKEY
+        let id = token.into_raw() as c_int;
+        unsafe {
+            if RegisterHotKey(w.hwnd.get(), id, mods, accel.key as UINT) == FALSE {
+                warn!("failed to register global hotkey {:?}", hotkey);
+                return None;
+            }
+        }
+        w.global_hotkeys.borrow_mut().push(token);
+        Some(token)
+    }
+
+    pub fn unregister_global_hotkey(&self, token: GlobalHotKeyToken) {
+        if let Some(w) = self.state.upgrade() {
+            w.global_hotkeys.borrow_mut().retain(|t| *t != token);
+            unsafe {
+                UnregisterHotKey(w.hwnd.get(), token.into_raw() as c_int);
+            }
+        }
+    }
+
+    fn unregister_all_global_hotkeys(&self) {
+        if let Some(w) = self.state.upgrade() {
+            for token in w.global_hotkeys.borrow_mut().drain(..) {
+                unsafe {
+                    UnregisterHotKey(w.hwnd.get(), token.into_raw() as c_int);
+                }
+            }
+        }
+    }
+
+    pub fn set_badge(&self, _badge: Option<&str>) {
+        log::warn!("set_badge is currently unimplemented for windows.");
+    }
+
+    pub fn show_context_menu(&self, mut menu: Menu, pos: Point) {
+        let bitmaps = menu.take_bitmaps();
         let hmenu = menu.into_hmenu();
         if let Some(w) = self.state.upgrade() {
             let hwnd = w.hwnd.get();
@@ -1086,12 +1533,21 @@ impl WindowHandle {
             unsafe {
                 let mut point = POINT { x, y };
                 ClientToScreen(hwnd, &mut point);
-                if TrackPopupMenu(hmenu, TPM_LEFTALIGN, point.x, point.y, 0, hwnd, null()) == FALSE
-                {
+                // The menu is flipped to stay on the monitor near its edges, and
+                // submenus open to whichever side has room.
+                let flags = TPM_LEFTALIGN | TPM_TOPALIGN | TPM_RIGHTBUTTON;
+                if TrackPopupMenu(hmenu, flags, point.x, point.y, 0, hwnd, null()) == FALSE {
                     warn!("failed to track popup menu");
                 }
             }
         }
+        // The chosen item arrives as a WM_COMMAND posted to the window, so the
+        // menu (and its submenus) can be destroyed right away, and then its
+        // bitmaps.
+        unsafe {
+            DestroyMenu(hmenu);
+        }
+        drop(bitmaps);
     }
 
     pub fn text(&self) -> Text {
@@ -1114,8 +1570,88 @@ impl WindowHandle {
     /// Set the cursor icon.
     pub fn set_cursor(&mut self, cursor: &Cursor) {
         unsafe {
-            let cursor = LoadCursorW(0 as HINSTANCE, cursor.get_lpcwstr());
-            SetCursor(cursor);
+            SetCursor(cursor.get_hcursor());
+        }
+    }
+
+    pub fn make_cursor(&self, desc: &CursorDesc) -> Option<Cursor> {
+        let cursor = unsafe { create_icon(&desc.rgba, desc.width, desc.height, Some(desc.hot)) };
+        if cursor.is_null() {
+            warn!("CreateIconIndirect failed.");
+            return None;
+        }
+        Some(Cursor::Custom(CustomCursor(Rc::new(HCursor(cursor)))))
+    }
+
+    pub fn set_always_on_top(&self, on_top: bool) {
+        if let Some(hwnd) = self.get_hwnd() {
+            let after = if on_top { HWND_TOPMOST } else { HWND_NOTOPMOST };
+            let flags = SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE;
+            unsafe {
+                if SetWindowPos(hwnd, after, 0, 0, 0, 0, flags) == FALSE {
+                    warn!("failed to change the window's z-order");
+                }
+            }
+        }
+    }
+
+    pub fn set_skip_taskbar(&self, skip: bool) {
+        if let Some(w) = self.state.upgrade() {
+            w.skip_taskbar.set(skip);
+            let hwnd = w.hwnd.get();
+            if unsafe { IsWindowVisible(hwnd) } == TRUE {
+                set_taskbar_button(hwnd, !skip);
+            }
+        }
+    }
+
+    pub fn set_utility(&self, utility: bool) {
+        if let Some(hwnd) = self.get_hwnd() {
+            unsafe {
+                let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+                let style = if utility {
+                    style | WS_EX_TOOLWINDOW as WindowLongPtr
+                } else {
+                    style & !(WS_EX_TOOLWINDOW as WindowLongPtr)
+                };
+                SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style);
+                // the frame is only redrawn in the new style when asked to
+                let flags =
+                    SWP_FRAMECHANGED | SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE;
+                SetWindowPos(hwnd, null_mut(), 0, 0, 0, 0, flags);
+            }
+        }
+    }
+
+    pub fn set_icon(&self, icon: Option<&Icon>) {
+        let hwnd = match self.get_hwnd() {
+            Some(hwnd) => hwnd,
+            None => return,
+        };
+        unsafe {
+            let hicon = match icon {
+                Some(icon) => {
+                    let hicon = create_icon(&icon.rgba, icon.width, icon.height, None);
+                    if hicon.is_null() {
+                        warn!("CreateIconIndirect failed.");
+                        return;
+                    }
+                    hicon
+                }
+                None => null_mut(),
+            };
+            // the same icon is used at both sizes, and the ones it replaces
+            // are those set here before, which are no longer needed once
+            // neither size uses them; they may also be one icon
+            let old_big = SendMessageW(hwnd, WM_SETICON, ICON_BIG as WPARAM, hicon as LPARAM);
+            let old_small = SendMessageW(hwnd, WM_SETICON, ICON_SMALL as WPARAM, hicon as LPARAM);
+            let mut old = vec![old_big as HICON, old_small as HICON];
+            old.dedup();
+            for old in old {
+                if !old.is_null() && old != hicon {
+                    DestroyIcon(old);
+                }
+            }
         }
     }
 
@@ -1178,6 +1714,10 @@ impl WindowHandle {
         }
     }
 
+    pub fn get_scale(&self) -> f64 {
+        f64::from(self.get_dpi()) / 96.0
+    }
+
     /// Convert a dimension in px units to physical pixels (rounding).
     pub fn px_to_pixels(&self, x: f32) -> i32 {
         (x * self.get_dpi() * (1.0 / 96.0)).round() as i32
@@ -1221,6 +1761,22 @@ impl WindowHandle {
     }
 }
 
+#[cfg(feature = "raw-win-handle")]
+impl WindowHandle {
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        let mut handle = Win32WindowHandle::empty();
+        if let Some(hwnd) = self.get_hwnd() {
+            handle.hwnd = hwnd as *mut _;
+            handle.hinstance = unsafe { GetWindowLongPtrW(hwnd, GWLP_HINSTANCE) as *mut _ };
+        }
+        RawWindowHandle::Win32(handle)
+    }
+
+    pub fn raw_display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::Windows(WindowsDisplayHandle::empty())
+    }
+}
+
 // There is a tiny risk of things going wrong when hwnd is sent across threads.
 unsafe impl Send for IdleHandle {}
 
@@ -1274,3 +1830,88 @@ impl Default for WindowHandle {
         }
     }
 }
+
+/// Make an icon, or a cursor with its hotspot at `hot`, from RGBA pixels.
+/// The result is null if it can't be made.
+unsafe fn create_icon(rgba: &[u8], width: usize, height: usize, hot: Option<Point>) -> HICON {
+    // the color bitmap is BGRA; with an alpha channel, the mask is unused
+    let mut bgra = Vec::with_capacity(rgba.len());
+    for p in rgba.chunks_exact(4) {
+        bgra.extend_from_slice(&[p[2], p[1], p[0], p[3]]);
+    }
+    let mask = vec![0u8; (width + 15) / 16 * 2 * height];
+    let (width, height) = (width as c_int, height as c_int);
+    let color = CreateBitmap(width, height, 1, 32, bgra.as_ptr() as *const c_void);
+    let mask = CreateBitmap(width, height, 1, 1, mask.as_ptr() as *const c_void);
+    let mut info = ICONINFO {
+        fIcon: if hot.is_none() { TRUE } else { FALSE },
+        xHotspot: hot.map_or(0, |hot| hot.x as DWORD),
+        yHotspot: hot.map_or(0, |hot| hot.y as DWORD),
+        hbmMask: mask,
+        hbmColor: color,
+    };
+    let icon = CreateIconIndirect(&mut info);
+    DeleteObject(color as _);
+    DeleteObject(mask as _);
+    icon
+}
+
+/// The text of the menu item with the command `id`, in `hmenu` or one of its
+/// submenus.
+unsafe fn menu_item_text(hmenu: HMENU, id: u32) -> Option<String> {
+    let mut info: MENUITEMINFOW = mem::zeroed();
+    info.cbSize = mem::size_of::<MENUITEMINFOW>() as u32;
+    info.fMask = MIIM_STRING;
+    // the first call gives the length, without the terminating nul
+    if GetMenuItemInfoW(hmenu, id, FALSE, &mut info) == FALSE {
+        return None;
+    }
+    let mut text = vec![0u16; info.cch as usize + 1];
+    info.dwTypeData = text.as_mut_ptr();
+    info.cch += 1;
+    if GetMenuItemInfoW(hmenu, id, FALSE, &mut info) == FALSE {
+        return None;
+    }
+    text.truncate(info.cch as usize);
+    Some(String::from_utf16_lossy(&text))
+}
+
+/// The taskbar's list of buttons, ready to use.
+fn taskbar_list() -> Option<ComPtr<ITaskbarList3>> {
+    unsafe {
+        let mut taskbar: *mut ITaskbarList3 = null_mut();
+        let hr = CoCreateInstance(
+            &CLSID_TaskbarList,
+            null_mut(),
+            CLSCTX_INPROC_SERVER,
+            &ITaskbarList3::uuidof(),
+            &mut taskbar as *mut *mut ITaskbarList3 as *mut LPVOID,
+        );
+        if let Err(e) = as_result(hr) {
+            warn!("failed to create taskbar list: {}", e);
+            return None;
+        }
+        let taskbar = ComPtr::from_raw(taskbar);
+        if let Err(e) = as_result(taskbar.HrInit()) {
+            warn!("failed to init taskbar list: {}", e);
+            return None;
+        }
+        Some(taskbar)
+    }
+}
+
+/// Add or remove the taskbar button of a visible window.
+fn set_taskbar_button(hwnd: HWND, shown: bool) {
+    if let Some(taskbar) = taskbar_list() {
+        let hr = unsafe {
+            if shown {
+                taskbar.AddTab(hwnd)
+            } else {
+                taskbar.DeleteTab(hwnd)
+            }
+        };
+        if let Err(e) = as_result(hr) {
+            warn!("failed to change the taskbar button: {}", e);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Information about the connected monitors.
+
+use crate::kurbo::Rect;
+use crate::platform::screen as platform;
+
+/// A connected monitor.
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    /// Whether this is the primary monitor.
+    pub primary: bool,
+    /// The bounds of the monitor in desktop coordinates, where the origin is
+    /// the top left of the primary monitor.
+    ///
+    /// Desktop coordinates are physical pixels on Windows, and logical
+    /// pixels elsewhere.
+    pub rect: Rect,
+    /// The part of `rect` that is not covered by taskbars, docks or menu
+    /// bars, and where windows should normally be placed.
+    pub work_rect: Rect,
+    /// The ratio between physical pixels and logical pixels.
+    pub scale: f64,
+}
+
+/// The monitors connected to the system.
+pub struct Screen;
+
+impl Screen {
+    /// Returns the connected monitors.
+    ///
+    /// Changes to the monitor configuration are reported to each window
+    /// through [`WinHandler::monitors_changed`].
+    ///
+    /// [`WinHandler::monitors_changed`]: trait.WinHandler.html#method.monitors_changed
+    pub fn get_monitors() -> Vec<Monitor> {
+        platform::get_monitors()
+    }
+
+    /// Returns the primary monitor, if any are connected.
+    pub fn get_primary_monitor() -> Option<Monitor> {
+        let monitors = Self::get_monitors();
+        let primary = monitors.iter().position(|m| m.primary)?;
+        monitors.into_iter().nth(primary)
+    }
+}
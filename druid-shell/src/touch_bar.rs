@@ -0,0 +1,88 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Touch Bar on some MacBook Pros.
+
+/// The contents of a window's Touch Bar.
+///
+/// The Touch Bar only exists on macOS; on other platforms this is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct TouchBar {
+    pub(crate) items: Vec<TouchBarItem>,
+}
+
+/// A control in the Touch Bar.
+///
+/// Each item has an id, which is passed to [`WinHandler::touch_bar`] when
+/// the user interacts with it.
+///
+/// [`WinHandler::touch_bar`]: trait.WinHandler.html#method.touch_bar
+#[derive(Debug, Clone)]
+pub enum TouchBarItem {
+    /// A push button.
+    Button { id: u32, label: String },
+    /// A slider, with an optional label in front of it.
+    Slider {
+        id: u32,
+        label: Option<String>,
+        min: f64,
+        max: f64,
+        value: f64,
+    },
+    /// A row of segments, at most one of which is selected.
+    Segmented {
+        id: u32,
+        labels: Vec<String>,
+        selected: Option<usize>,
+    },
+}
+
+/// What the user did with a Touch Bar item.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TouchBarEvent {
+    /// A button was pressed.
+    Pressed,
+    /// A slider was moved to this value.
+    SliderChanged(f64),
+    /// The segment at this index was selected.
+    SegmentSelected(usize),
+}
+
+impl TouchBar {
+    /// Create an empty Touch Bar.
+    pub fn new() -> TouchBar {
+        TouchBar::default()
+    }
+
+    /// Add an item to the end of the Touch Bar.
+    pub fn add_item(&mut self, item: TouchBarItem) {
+        self.items.push(item);
+    }
+
+    /// Returns `true` if there are no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl TouchBarItem {
+    /// The id passed to the handler when this item is used.
+    pub fn id(&self) -> u32 {
+        match self {
+            TouchBarItem::Button { id, .. }
+            | TouchBarItem::Slider { id, .. }
+            | TouchBarItem::Segmented { id, .. } => *id,
+        }
+    }
+}
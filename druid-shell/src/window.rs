@@ -16,14 +16,22 @@
 
 use std::any::Any;
 
+#[cfg(feature = "raw-win-handle")]
+use raw_window_handle::{
+    HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle,
+};
+
 use crate::common_util::Counter;
 use crate::dialog::{FileDialogOptions, FileInfo};
+use crate::drag::{DragData, DropAction};
 use crate::error::Error;
+use crate::hotkey::HotKey;
 use crate::keyboard::{KeyEvent, KeyModifiers};
 use crate::kurbo::{Point, Size, Vec2};
 use crate::menu::Menu;
-use crate::mouse::{Cursor, MouseEvent};
+use crate::mouse::{Cursor, CursorDesc, MouseEvent};
 use crate::platform::window as platform;
+use crate::touch_bar::{TouchBar, TouchBarEvent};
 
 // It's possible we'll want to make this type alias at a lower level,
 // see https://github.com/linebender/piet/pull/37 for more discussion.
@@ -55,6 +63,28 @@ impl TimerToken {
     }
 }
 
+/// A token that identifies a registered global hotkey.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
+pub struct GlobalHotKeyToken(u64);
+
+impl GlobalHotKeyToken {
+    /// Create a new token.
+    pub fn next() -> GlobalHotKeyToken {
+        static HOTKEY_COUNTER: Counter = Counter::new();
+        GlobalHotKeyToken(HOTKEY_COUNTER.next())
+    }
+
+    /// Create a new token from a raw value.
+    pub const fn from_raw(id: u64) -> GlobalHotKeyToken {
+        GlobalHotKeyToken(id)
+    }
+
+    /// Get the raw value for a token.
+    pub const fn into_raw(self) -> u64 {
+        self.0
+    }
+}
+
 //NOTE: this has a From<platform::Handle> impl for construction
 /// A handle that can enqueue tasks on the window loop.
 #[derive(Clone)]
@@ -92,6 +122,37 @@ impl IdleToken {
     }
 }
 
+/// The image shown for a window in the title bar and taskbar, for the
+/// application in the dock, next to a menu item, or under the cursor while
+/// something is dragged.
+#[derive(Debug, Clone)]
+pub struct Icon {
+    pub(crate) rgba: Vec<u8>,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+}
+
+impl Icon {
+    /// Create an icon from unpremultiplied RGBA pixels, four bytes per
+    /// pixel, row by row from the top left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rgba` does not hold exactly `width * height` pixels.
+    pub fn from_rgba(rgba: Vec<u8>, width: usize, height: usize) -> Self {
+        assert_eq!(
+            rgba.len(),
+            width * height * 4,
+            "icon image does not match its size"
+        );
+        Icon {
+            rgba,
+            width,
+            height,
+        }
+    }
+}
+
 /// A handle to a platform window object.
 #[derive(Clone, Default)]
 pub struct WindowHandle(platform::WindowHandle);
@@ -130,6 +191,34 @@ impl WindowHandle {
         self.0.set_menu(menu.into_inner())
     }
 
+    /// Enable or disable the item with the given `id` in this window's menu,
+    /// without rebuilding the menu.
+    pub fn set_menu_item_enabled(&self, id: u32, enabled: bool) {
+        self.0.set_menu_item_enabled(id, enabled)
+    }
+
+    /// Change the text of the item with the given `id` in this window's menu,
+    /// without rebuilding the menu.
+    pub fn set_menu_item_title(&self, id: u32, title: &str) {
+        self.0.set_menu_item_title(id, title)
+    }
+
+    /// Hide or show the item with the given `id` in this window's menu,
+    /// without rebuilding the menu.
+    ///
+    /// Win32 menus cannot hide items, so on Windows a hidden item is taken
+    /// out of the menu and put back in its place when it is shown; its
+    /// hotkey does nothing in the meantime.
+    pub fn set_menu_item_hidden(&self, id: u32, hidden: bool) {
+        self.0.set_menu_item_hidden(id, hidden)
+    }
+
+    /// Set the contents of the Touch Bar, shown while this window is
+    /// focused. This is only supported on macOS, and ignored elsewhere.
+    pub fn set_touch_bar(&self, touch_bar: TouchBar) {
+        self.0.set_touch_bar(touch_bar)
+    }
+
     /// Get access to a type that can perform text layout.
     pub fn text(&self) -> Text {
         self.0.text()
@@ -156,6 +245,15 @@ impl WindowHandle {
         self.0.set_cursor(cursor)
     }
 
+    /// Create a custom cursor from an image.
+    ///
+    /// Creating a cursor is not free, so the result should be kept and
+    /// reused rather than made again each time it is set. Returns `None` if
+    /// the platform could not create the cursor.
+    pub fn make_cursor(&self, desc: &CursorDesc) -> Option<Cursor> {
+        self.0.make_cursor(desc)
+    }
+
     /// Prompt the user to chose a file to open.
     ///
     /// Blocks while the user picks the file.
@@ -177,6 +275,102 @@ impl WindowHandle {
         self.0.show_context_menu(menu.into_inner(), pos)
     }
 
+    /// Start dragging `data` out of the window.
+    ///
+    /// This should be called while handling a mouse down or mouse move
+    /// event. If `allow_move` is `false`, the drop target may only copy the
+    /// data. The handler's [`drag_ended`] method is called when the drag
+    /// finishes.
+    ///
+    /// If there is an `image`, it is shown centered under the cursor while
+    /// the data is dragged.
+    ///
+    /// On Windows the drag starts once the current event has been handled.
+    ///
+    /// [`drag_ended`]: trait.WinHandler.html#method.drag_ended
+    pub fn start_drag(&self, data: DragData, image: Option<&Icon>, allow_move: bool) {
+        self.0.start_drag(data, image, allow_move)
+    }
+
+    /// Show the progress of a long operation outside the window.
+    ///
+    /// On Windows this fills the window's taskbar button, and on macOS the
+    /// application's dock icon. `progress` is clamped to `0.0..=1.0`; pass
+    /// `None` to hide the indicator again.
+    pub fn set_progress(&self, progress: Option<f64>) {
+        self.0.set_progress(progress.map(|p| p.clamp(0.0, 1.0)))
+    }
+
+    /// Show a short label, such as an unread count, on the application's
+    /// dock icon. Pass `None` to remove it.
+    ///
+    /// This is currently only shown on macOS.
+    pub fn set_badge(&self, badge: Option<&str>) {
+        self.0.set_badge(badge)
+    }
+
+    /// Change the window's icon, such as to show that it has unsaved
+    /// changes. Pass `None` to go back to the default icon.
+    ///
+    /// On macOS windows don't have their own icons, so this sets the
+    /// application's dock icon, for all of its windows.
+    pub fn set_icon(&self, icon: Option<&Icon>) {
+        self.0.set_icon(icon)
+    }
+
+    /// Keep the window above the other windows, or stop doing so.
+    ///
+    /// See [`WindowBuilder::set_always_on_top`].
+    ///
+    /// [`WindowBuilder::set_always_on_top`]: struct.WindowBuilder.html#method.set_always_on_top
+    pub fn set_always_on_top(&self, on_top: bool) {
+        self.0.set_always_on_top(on_top)
+    }
+
+    /// Leave the window out of the taskbar and the window switcher, or put
+    /// it back.
+    ///
+    /// See [`WindowBuilder::set_skip_taskbar`].
+    ///
+    /// [`WindowBuilder::set_skip_taskbar`]: struct.WindowBuilder.html#method.set_skip_taskbar
+    pub fn set_skip_taskbar(&self, skip: bool) {
+        self.0.set_skip_taskbar(skip)
+    }
+
+    /// Style the window as a utility window, or as a normal one.
+    ///
+    /// See [`WindowBuilder::set_utility`]. With GTK a window that is shown
+    /// is hidden and shown again, since the window manager only looks at
+    /// its style then.
+    ///
+    /// [`WindowBuilder::set_utility`]: struct.WindowBuilder.html#method.set_utility
+    pub fn set_utility(&self, utility: bool) {
+        self.0.set_utility(utility)
+    }
+
+    /// Register a hotkey that is reported to this window's handler even
+    /// while the app is in the background.
+    ///
+    /// When the hotkey is pressed the handler's [`global_hotkey`] method is
+    /// called with the returned token. Returns `None` if the hotkey is
+    /// already taken. The hotkey is unregistered when the window is closed.
+    ///
+    /// On Linux this needs X11, since Wayland has no global hotkeys, and on
+    /// macOS a hotkey given as text is the key with that letter or digit on
+    /// a US keyboard.
+    ///
+    /// [`global_hotkey`]: trait.WinHandler.html#method.global_hotkey
+    pub fn register_global_hotkey(&self, hotkey: &HotKey) -> Option<GlobalHotKeyToken> {
+        self.0.register_global_hotkey(hotkey)
+    }
+
+    /// Unregister a hotkey registered with [`register_global_hotkey`].
+    ///
+    /// [`register_global_hotkey`]: #method.register_global_hotkey
+    pub fn unregister_global_hotkey(&self, token: GlobalHotKeyToken) {
+        self.0.unregister_global_hotkey(token)
+    }
+
     /// Get a handle that can be used to schedule an idle task.
     pub fn get_idle_handle(&self) -> Option<IdleHandle> {
         self.0.get_idle_handle().map(IdleHandle)
@@ -189,6 +383,31 @@ impl WindowHandle {
     pub fn get_dpi(&self) -> f32 {
         self.0.get_dpi()
     }
+
+    /// The number of physical pixels in each unit the window is drawn in,
+    /// such as `2.0` on most high-density screens.
+    ///
+    /// This can change when the window moves to another screen; see
+    /// [`WinHandler::scale_changed`].
+    ///
+    /// [`WinHandler::scale_changed`]: trait.WinHandler.html#method.scale_changed
+    pub fn get_scale(&self) -> f64 {
+        self.0.get_scale()
+    }
+}
+
+#[cfg(feature = "raw-win-handle")]
+unsafe impl HasRawWindowHandle for WindowHandle {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        self.0.raw_window_handle()
+    }
+}
+
+#[cfg(feature = "raw-win-handle")]
+unsafe impl HasRawDisplayHandle for WindowHandle {
+    fn raw_display_handle(&self) -> RawDisplayHandle {
+        self.0.raw_display_handle()
+    }
 }
 
 /// A builder type for creating new windows.
@@ -223,6 +442,48 @@ impl WindowBuilder {
         self.0.set_menu(menu.into_inner())
     }
 
+    /// Keep the window above the application's other windows, and those of
+    /// other applications, such as for a floating palette or an overlay.
+    pub fn set_always_on_top(&mut self, on_top: bool) {
+        self.0.set_always_on_top(on_top)
+    }
+
+    /// Leave the window out of the taskbar, and out of the pager and the
+    /// window switcher where the platform has them.
+    ///
+    /// On macOS, where windows aren't in the dock, the window is left out of
+    /// the Window menu and skipped when cycling through windows.
+    pub fn set_skip_taskbar(&mut self, skip: bool) {
+        self.0.set_skip_taskbar(skip)
+    }
+
+    /// Style the window as a utility window, such as a tool palette or a
+    /// picker, which the platform may give a smaller title bar.
+    ///
+    /// On Windows this is a tool window, which also isn't in the taskbar.
+    /// On macOS, where only panels have the utility style, the window is
+    /// instead hidden while the application is in the background.
+    pub fn set_utility(&mut self, utility: bool) {
+        self.0.set_utility(utility)
+    }
+
+    /// Build the window inside `parent`, a native window or view made by
+    /// another application, rather than as a window of its own.
+    ///
+    /// The window's title and menu are not used. The parent needs to be a
+    /// `Win32` handle on Windows and an `AppKit` one on macOS, where the
+    /// window becomes a child window or subview that fills the parent. With
+    /// GTK it needs to be an `Xlib` handle for a window that accepts XEmbed
+    /// clients, such as a `GtkSocket`. `build` fails for other handles.
+    ///
+    /// This is used with [`Application::embedded`].
+    ///
+    /// [`Application::embedded`]: struct.Application.html#method.embedded
+    #[cfg(feature = "raw-win-handle")]
+    pub fn set_parent(&mut self, parent: RawWindowHandle) {
+        self.0.set_parent(parent)
+    }
+
     /// Attempt to construct the platform window.
     ///
     /// If this fails, your application should exit.
@@ -314,6 +575,69 @@ pub trait WinHandler {
     #[allow(unused_variables)]
     fn mouse_up(&mut self, event: &MouseEvent) {}
 
+    /// Called when something is dragged over the window.
+    ///
+    /// The position is in px units, like mouse events. The data being
+    /// dragged is only available once it is dropped.
+    #[allow(unused_variables)]
+    fn drag_moved(&mut self, pos: Point) {}
+
+    /// Called when a drag leaves the window, and also just before it is
+    /// dropped, on every platform.
+    #[allow(unused_variables)]
+    fn drag_left(&mut self) {}
+
+    /// Called when something is dropped on the window, after
+    /// [`drag_left`].
+    ///
+    /// Return `true` if the drop was accepted.
+    ///
+    /// [`drag_left`]: #method.drag_left
+    #[allow(unused_variables)]
+    fn dropped(&mut self, pos: Point, data: DragData) -> bool {
+        false
+    }
+
+    /// Called when a drag started with [`WindowHandle::start_drag`] ends.
+    ///
+    /// The action is `None` if the data was not dropped anywhere.
+    ///
+    /// [`WindowHandle::start_drag`]: struct.WindowHandle.html#method.start_drag
+    #[allow(unused_variables)]
+    fn drag_ended(&mut self, action: Option<DropAction>) {}
+
+    /// Called when monitors are connected, disconnected, or rearranged, or
+    /// when their resolution or scale changes.
+    ///
+    /// The new configuration is available from [`Screen::get_monitors`].
+    ///
+    /// [`Screen::get_monitors`]: struct.Screen.html#method.get_monitors
+    fn monitors_changed(&mut self) {}
+
+    /// Called when the window's scale changes, such as when it moves to a
+    /// screen with a different pixel density.
+    ///
+    /// The argument is the new [`WindowHandle::get_scale`].
+    ///
+    /// [`WindowHandle::get_scale`]: struct.WindowHandle.html#method.get_scale
+    #[allow(unused_variables)]
+    fn scale_changed(&mut self, scale: f64) {}
+
+    /// Called when the user interacts with an item of the window's
+    /// [`TouchBar`], with the item's id.
+    ///
+    /// [`TouchBar`]: struct.TouchBar.html
+    #[allow(unused_variables)]
+    fn touch_bar(&mut self, id: u32, event: TouchBarEvent) {}
+
+    /// Called when a hotkey registered with
+    /// [`WindowHandle::register_global_hotkey`] is pressed, whether or not
+    /// the app is focused.
+    ///
+    /// [`WindowHandle::register_global_hotkey`]: struct.WindowHandle.html#method.register_global_hotkey
+    #[allow(unused_variables)]
+    fn global_hotkey(&mut self, token: GlobalHotKeyToken) {}
+
     /// Called on timer event.
     ///
     /// This is called at (approximately) the requested deadline by a
@@ -328,6 +652,20 @@ pub trait WinHandler {
     #[allow(unused_variables)]
     fn got_focus(&mut self) {}
 
+    /// Called when this window stops being the focused window.
+    #[allow(unused_variables)]
+    fn lost_focus(&mut self) {}
+
+    /// Called when the user changes the platform's text scale setting.
+    ///
+    /// The argument is the new scale factor, where `1.0` is the default size.
+    #[allow(unused_variables)]
+    fn text_scale_changed(&mut self, scale: f64) {}
+
+    /// Called when the user changes the platform's reduced motion setting.
+    #[allow(unused_variables)]
+    fn reduce_motion_changed(&mut self, reduce: bool) {}
+
     /// Called when the window is being destroyed. Note that this happens
     /// earlier in the sequence than drop (at WM_DESTROY, while the latter is
     /// WM_NCDESTROY).
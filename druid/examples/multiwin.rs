@@ -190,4 +190,16 @@ fn make_context_menu<T: Data>() -> MenuDesc<T> {
             LocalizedString::new("Decrement"),
             MENU_DECREMENT_ACTION,
         ))
+        .append_separator()
+        .append(
+            MenuDesc::new(LocalizedString::new("Select")).append_iter(|| {
+                (0..3).map(|i| {
+                    MenuItem::new(
+                        LocalizedString::new("hello-counter")
+                            .with_arg("count", move |_, _| i.into()),
+                        Command::new(MENU_COUNT_ACTION, i),
+                    )
+                })
+            }),
+        )
 }
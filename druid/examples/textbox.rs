@@ -54,12 +54,7 @@ fn build_widget() -> impl Widget<String> {
 }
 
 fn make_main_menu<T: Data>() -> MenuDesc<T> {
-    let edit_menu = MenuDesc::new(LocalizedString::new("common-menu-edit-menu"))
-        .append(druid::platform_menus::common::cut())
-        .append(druid::platform_menus::common::copy())
-        .append(druid::platform_menus::common::paste());
-
     MenuDesc::platform_default()
         .unwrap_or(MenuDesc::empty())
-        .append(edit_menu)
+        .append(druid::platform_menus::common::edit_menu())
 }
@@ -18,6 +18,7 @@ common-menu-file-new = Neu
 common-menu-file-new-window = Neues Fenster
 
 common-menu-file-open = Öffnen...
+common-menu-file-open-recent = Zuletzt verwendete öffnen
 common-menu-file-close = Schließen
 
 common-menu-file-save = Speichern
@@ -39,3 +40,39 @@ common-menu-copy = Kopieren
 common-menu-paste = Einfügen
 common-menu-undo = Rückgängig
 common-menu-redo = Wiederherstellen
+
+# modifier key names, as shown with menu hotkeys
+hotkey-modifier-ctrl = Strg
+hotkey-modifier-alt = Alt
+hotkey-modifier-shift = Umschalt
+hotkey-modifier-meta = Win
+
+# the date picker: month names, short weekday names from Monday,
+# and the day weeks start on, from 1 for Monday to 7 for Sunday
+date-picker-month-1 = Januar
+date-picker-month-2 = Februar
+date-picker-month-3 = März
+date-picker-month-4 = April
+date-picker-month-5 = Mai
+date-picker-month-6 = Juni
+date-picker-month-7 = Juli
+date-picker-month-8 = August
+date-picker-month-9 = September
+date-picker-month-10 = Oktober
+date-picker-month-11 = November
+date-picker-month-12 = Dezember
+date-picker-weekday-1 = Mo
+date-picker-weekday-2 = Di
+date-picker-weekday-3 = Mi
+date-picker-weekday-4 = Do
+date-picker-weekday-5 = Fr
+date-picker-weekday-6 = Sa
+date-picker-weekday-7 = So
+date-picker-week-start = 1
+
+textbox-no-spelling-suggestions = Keine Vorschläge
+
+textbox-undo-typing = Eingabe
+textbox-undo-cut = Ausschneiden
+textbox-undo-paste = Einfügen
+textbox-undo-correct-spelling = Rechtschreibung korrigieren
@@ -22,6 +22,7 @@ common-menu-file-new = New
 common-menu-file-new-window = New Window
 
 common-menu-file-open = Open...
+common-menu-file-open-recent = Open Recent
 common-menu-file-close = Close
 
 common-menu-file-save = Save
@@ -43,3 +44,41 @@ common-menu-copy = Copy
 common-menu-paste = Paste
 common-menu-undo = Undo
 common-menu-redo = Redo
+
+# modifier key names, as shown with menu hotkeys
+hotkey-modifier-ctrl = Ctrl
+hotkey-modifier-alt = Alt
+hotkey-modifier-shift = Shift
+hotkey-modifier-meta = Win
+
+# the date picker: month names, short weekday names from Monday,
+# and the day weeks start on, from 1 for Monday to 7 for Sunday
+date-picker-month-1 = January
+date-picker-month-2 = February
+date-picker-month-3 = March
+date-picker-month-4 = April
+date-picker-month-5 = May
+date-picker-month-6 = June
+date-picker-month-7 = July
+date-picker-month-8 = August
+date-picker-month-9 = September
+date-picker-month-10 = October
+date-picker-month-11 = November
+date-picker-month-12 = December
+date-picker-weekday-1 = Mo
+date-picker-weekday-2 = Tu
+date-picker-weekday-3 = We
+date-picker-weekday-4 = Th
+date-picker-weekday-5 = Fr
+date-picker-weekday-6 = Sa
+date-picker-weekday-7 = Su
+date-picker-week-start = 7
+
+# shown in a text box's context menu for a misspelled word with no corrections
+textbox-no-spelling-suggestions = No Suggestions
+
+# the names of a text box's edits, as shown with undo and redo
+textbox-undo-typing = Typing
+textbox-undo-cut = Cut
+textbox-undo-paste = Paste
+textbox-undo-correct-spelling = Correct Spelling
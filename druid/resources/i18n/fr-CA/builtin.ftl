@@ -18,6 +18,7 @@ common-menu-file-new = Nouveau
 common-menu-file-new-window = Nouvelle fenêtre
 
 common-menu-file-open = Ouvrir...
+common-menu-file-open-recent = Ouvrir un fichier récent
 common-menu-file-close = Fermer
 
 common-menu-file-save = Enregistrer
@@ -39,3 +40,39 @@ common-menu-copy = Copier
 common-menu-paste = Coller
 common-menu-undo = Annuler
 common-menu-redo = Rétablir
+
+# modifier key names, as shown with menu hotkeys
+hotkey-modifier-ctrl = Ctrl
+hotkey-modifier-alt = Alt
+hotkey-modifier-shift = Maj
+hotkey-modifier-meta = Win
+
+# the date picker: month names, short weekday names from Monday,
+# and the day weeks start on, from 1 for Monday to 7 for Sunday
+date-picker-month-1 = janvier
+date-picker-month-2 = février
+date-picker-month-3 = mars
+date-picker-month-4 = avril
+date-picker-month-5 = mai
+date-picker-month-6 = juin
+date-picker-month-7 = juillet
+date-picker-month-8 = août
+date-picker-month-9 = septembre
+date-picker-month-10 = octobre
+date-picker-month-11 = novembre
+date-picker-month-12 = décembre
+date-picker-weekday-1 = lu
+date-picker-weekday-2 = ma
+date-picker-weekday-3 = me
+date-picker-weekday-4 = je
+date-picker-weekday-5 = ve
+date-picker-weekday-6 = sa
+date-picker-weekday-7 = di
+date-picker-week-start = 7
+
+textbox-no-spelling-suggestions = Aucune suggestion
+
+textbox-undo-typing = Saisie
+textbox-undo-cut = Couper
+textbox-undo-paste = Coller
+textbox-undo-correct-spelling = Corriger l'orthographe
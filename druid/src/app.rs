@@ -15,15 +15,24 @@
 //! Window building and app lifecycle.
 
 use std::cell::RefCell;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use crate::ext_event::{ExtEventHost, ExtEventSink};
 use crate::kurbo::Size;
+use crate::menu::MenuFn;
+#[cfg(feature = "persistence")]
+use crate::persist::{self, Persistence};
+use crate::recording::{Recorder, Replay};
 use crate::shell::{Application, Error as PlatformError, WindowBuilder, WindowHandle};
+use crate::task::{BackgroundTask, Executor};
 use crate::widget::WidgetExt;
 use crate::win_handler::AppState;
 use crate::window::{PendingWindow, WindowId};
-use crate::{theme, AppDelegate, Data, DruidHandler, Env, LocalizedString, MenuDesc, Widget};
+use crate::{
+    theme, AppDelegate, Data, DruidHandler, Env, LocalizedString, MenuDesc, Recording,
+    TouchBarDesc, Widget,
+};
 
 /// A function that modifies the initial environment.
 type EnvSetupFn<T> = dyn FnOnce(&mut Env, &T);
@@ -34,6 +43,13 @@ pub struct AppLauncher<T> {
     env_setup: Option<Box<EnvSetupFn<T>>>,
     delegate: Option<Box<dyn AppDelegate<T>>>,
     ext_event_host: ExtEventHost,
+    record_path: Option<PathBuf>,
+    replay: Option<Recording>,
+    automatic_undo: bool,
+    open_files_from_args: bool,
+    executor: Option<Executor>,
+    #[cfg(feature = "persistence")]
+    persistence: Option<Persistence<T>>,
 }
 
 /// A description of a window to be instantiated.
@@ -45,6 +61,11 @@ pub struct WindowDesc<T> {
     pub(crate) title: LocalizedString<T>,
     pub(crate) size: Option<Size>,
     pub(crate) menu: Option<MenuDesc<T>>,
+    pub(crate) menu_fn: Option<Box<MenuFn<T>>>,
+    pub(crate) touch_bar: Option<TouchBarDesc<T>>,
+    pub(crate) always_on_top: bool,
+    pub(crate) skip_taskbar: bool,
+    pub(crate) utility: bool,
     /// The `WindowId` that will be assigned to this window.
     ///
     /// This can be used to track a window from when it is launched and when
@@ -60,6 +81,13 @@ impl<T: Data> AppLauncher<T> {
             env_setup: None,
             delegate: None,
             ext_event_host: ExtEventHost::new(),
+            record_path: None,
+            replay: None,
+            automatic_undo: false,
+            open_files_from_args: false,
+            executor: None,
+            #[cfg(feature = "persistence")]
+            persistence: None,
         }
     }
 
@@ -105,27 +133,190 @@ impl<T: Data> AppLauncher<T> {
         })
     }
 
+    /// Record the input to the app in a file at `path`, as it happens.
+    ///
+    /// The file can be loaded as a [`Recording`], and replayed to reproduce
+    /// a bug.
+    ///
+    /// [`Recording`]: struct.Recording.html
+    pub fn record_events(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_path = Some(path.into());
+        self
+    }
+
+    /// Replay a recording once the first window is shown, sending each event
+    /// at the time it was recorded.
+    ///
+    /// Events are sent to the first window, and the user's own input is
+    /// still handled while the recording plays.
+    pub fn replay_events(mut self, recording: Recording) -> Self {
+        self.replay = Some(recording);
+        self
+    }
+
+    /// Make every change to the data made by a click, a key press, a paste or
+    /// a drop undoable, not only those that widgets ask for with
+    /// [`EventCtx::submit_undoable`].
+    ///
+    /// [`EventCtx::submit_undoable`]: struct.EventCtx.html#method.submit_undoable
+    pub fn automatic_undo(mut self) -> Self {
+        self.automatic_undo = true;
+        self
+    }
+
+    /// Treat the command line arguments that name existing files as a
+    /// request to open them, passed to [`AppDelegate::open_files`] once the
+    /// windows are built.
+    ///
+    /// This is how documents are opened on Windows and Linux. On macOS the
+    /// system sends these requests itself, whether or not this is set.
+    ///
+    /// [`AppDelegate::open_files`]: trait.AppDelegate.html#method.open_files
+    pub fn open_files_from_args(mut self) -> Self {
+        self.open_files_from_args = true;
+        self
+    }
+
+    /// Run the tasks started with [`EventCtx::spawn`] with `spawn`, instead
+    /// of on druid's own pool of threads.
+    ///
+    /// This is for futures that need a particular async runtime. With
+    /// tokio, for instance, `spawn` can be
+    /// `move |task| { runtime.spawn(task); }` for a `Handle` to the
+    /// runtime.
+    ///
+    /// [`EventCtx::spawn`]: struct.EventCtx.html#method.spawn
+    pub fn executor(mut self, spawn: impl Fn(BackgroundTask) + 'static) -> Self {
+        self.executor = Some(Executor::App(Box::new(spawn)));
+        self
+    }
+
     /// Build the windows and start the runloop.
     ///
     /// Returns an error if a window cannot be instantiated. This is usually
     /// a fatal error.
     pub fn launch(mut self, data: T) -> Result<(), PlatformError> {
         let mut app = Application::new();
-        let mut env = theme::init();
-        if let Some(f) = self.env_setup.take() {
-            f(&mut env, &data);
-        }
-
-        let state = AppState::new(data, env, self.delegate.take(), self.ext_event_host);
-
+        let state = self.make_state(&mut app, data);
         for desc in self.windows {
             let window = desc.build_native(&state)?;
             window.show();
         }
+        if self.open_files_from_args {
+            app.open_files_from_args();
+        }
 
         app.run();
         Ok(())
     }
+
+    /// Build the first window inside `parent`, a native window or view that
+    /// belongs to another application, so that druid can be used for part of
+    /// an existing app.
+    ///
+    /// Unlike [`launch`], this returns once the windows are built. The other
+    /// application keeps running its event loop as usual, and that drives
+    /// druid from then on; the app state lives as long as the windows do.
+    /// Any other windows are built as top-level windows.
+    ///
+    /// See [`WindowBuilder::set_parent`] for the kinds of handle that each
+    /// platform accepts.
+    ///
+    /// [`launch`]: #method.launch
+    /// [`WindowBuilder::set_parent`]: ../druid_shell/struct.WindowBuilder.html#method.set_parent
+    #[cfg(feature = "raw-win-handle")]
+    pub fn embed(
+        mut self,
+        data: T,
+        parent: crate::shell::raw_window_handle::RawWindowHandle,
+    ) -> Result<(), PlatformError> {
+        let mut app = Application::embedded();
+        let state = self.make_state(&mut app, data);
+        let mut windows = std::mem::take(&mut self.windows).into_iter();
+        if let Some(desc) = windows.next() {
+            let mut builder = desc.into_builder(&state);
+            builder.set_parent(parent);
+            builder.build()?.show();
+        }
+        for desc in windows {
+            desc.build_native(&state)?.show();
+        }
+        Ok(())
+    }
+
+    /// Set up the app's state and environment.
+    fn make_state(&mut self, app: &mut Application, data: T) -> Rc<RefCell<AppState<T>>> {
+        #[cfg(feature = "persistence")]
+        let data = match self.persistence.as_mut() {
+            Some(persistence) => persistence.restore(data),
+            None => data,
+        };
+        let mut env = theme::init();
+        env.set(theme::REDUCE_MOTION, Application::get_reduce_motion());
+        if let Some(f) = self.env_setup.take() {
+            f(&mut env, &data);
+        }
+        theme::apply_text_scale(&mut env, Application::get_text_scale());
+
+        let ext_event_host = std::mem::take(&mut self.ext_event_host);
+        let state = AppState::new(data, env, self.delegate.take(), ext_event_host);
+        if let Some(path) = self.record_path.take() {
+            match Recorder::create(&path) {
+                Ok(recorder) => state.borrow_mut().recorder = Some(recorder),
+                Err(e) => log::error!("failed to record events to '{}': {}", path.display(), e),
+            }
+        }
+        state.borrow_mut().replay = self.replay.take().map(Replay::new);
+        state.borrow_mut().undo.automatic = self.automatic_undo;
+        if let Some(executor) = self.executor.take() {
+            state.borrow_mut().executor = executor;
+        }
+        #[cfg(feature = "persistence")]
+        {
+            state.borrow_mut().persistence = self.persistence.take();
+        }
+
+        let open_files_state = state.clone();
+        app.set_open_files_handler(move |paths| DruidHandler::open_files(&open_files_state, paths));
+        state
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl<T: Data + serde::Serialize + serde::de::DeserializeOwned> AppLauncher<T> {
+    /// Keep the app data between runs.
+    ///
+    /// On launch, the data saved by the last run replaces the data passed to
+    /// [`launch`]; if there is none, or it can't be read, the data passed in
+    /// is used. Changes are saved once the data has stopped changing for a
+    /// moment, and when a window closes or the app quits.
+    ///
+    /// The data is kept as JSON, in a directory named `app_id` in the
+    /// platform's place for settings, so `app_id` should be unique to the
+    /// app, such as `"com.example.notes"`. The data type needs to derive
+    /// serde's `Serialize` and `Deserialize` alongside `Data`.
+    ///
+    /// This is only available with the `persistence` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// #[derive(Clone, Data, Lens, Serialize, Deserialize)]
+    /// struct Notes {
+    ///     text: String,
+    /// }
+    ///
+    /// AppLauncher::with_window(WindowDesc::new(build_ui))
+    ///     .persist("com.example.notes")
+    ///     .launch(Notes { text: String::new() })
+    ///     .expect("launch failed");
+    /// ```
+    ///
+    /// [`launch`]: #method.launch
+    pub fn persist(mut self, app_id: &str) -> Self {
+        self.persistence = Some(Persistence::new(persist::state_path(app_id)));
+        self
+    }
 }
 
 impl<T: Data> WindowDesc<T> {
@@ -147,6 +338,11 @@ impl<T: Data> WindowDesc<T> {
             title: LocalizedString::new("app-name"),
             size: None,
             menu: MenuDesc::platform_default(),
+            menu_fn: None,
+            touch_bar: None,
+            always_on_top: false,
+            skip_taskbar: false,
+            utility: false,
             id: WindowId::next(),
         }
     }
@@ -163,6 +359,29 @@ impl<T: Data> WindowDesc<T> {
     /// Set the menu for this window.
     pub fn menu(mut self, menu: MenuDesc<T>) -> Self {
         self.menu = Some(menu);
+        self.menu_fn = None;
+        self
+    }
+
+    /// Set a function that builds the menu for this window from the
+    /// application state.
+    ///
+    /// The menu is rebuilt whenever the data changes, which makes this
+    /// suitable for menus that list recent files or enable items based on
+    /// the selection. This replaces any menu set with [`menu`].
+    ///
+    /// [`menu`]: #method.menu
+    pub fn menu_fn(mut self, f: impl Fn(&T, &Env) -> MenuDesc<T> + 'static) -> Self {
+        self.menu = None;
+        self.menu_fn = Some(Box::new(f));
+        self
+    }
+
+    /// Set the Touch Bar for this window.
+    ///
+    /// The Touch Bar is only shown on macOS, on machines that have one.
+    pub fn touch_bar(mut self, touch_bar: TouchBarDesc<T>) -> Self {
+        self.touch_bar = Some(touch_bar);
         self
     }
 
@@ -178,14 +397,58 @@ impl<T: Data> WindowDesc<T> {
         self
     }
 
+    /// Keep the window above the other windows, as for a floating palette or
+    /// an overlay.
+    ///
+    /// This can be changed later with [`SET_ALWAYS_ON_TOP`].
+    ///
+    /// [`SET_ALWAYS_ON_TOP`]: commands/constant.SET_ALWAYS_ON_TOP.html
+    pub fn always_on_top(mut self, on_top: bool) -> Self {
+        self.always_on_top = on_top;
+        self
+    }
+
+    /// Leave the window out of the taskbar and the window switcher.
+    ///
+    /// This can be changed later with [`SET_SKIP_TASKBAR`].
+    ///
+    /// [`SET_SKIP_TASKBAR`]: commands/constant.SET_SKIP_TASKBAR.html
+    pub fn skip_taskbar(mut self, skip: bool) -> Self {
+        self.skip_taskbar = skip;
+        self
+    }
+
+    /// Style the window as a utility window, such as a tool palette or a
+    /// color picker. On Windows this is a tool window, which also isn't in
+    /// the taskbar; on macOS the window hides while the app is in the
+    /// background.
+    ///
+    /// This can be changed later with [`SET_UTILITY`].
+    ///
+    /// [`SET_UTILITY`]: commands/constant.SET_UTILITY.html
+    pub fn utility(mut self, utility: bool) -> Self {
+        self.utility = utility;
+        self
+    }
+
     /// Attempt to create a platform window from this `WindowDesc`.
     pub(crate) fn build_native(
-        mut self,
+        self,
         state: &Rc<RefCell<AppState<T>>>,
     ) -> Result<WindowHandle, PlatformError> {
+        self.into_builder(state).build()
+    }
+
+    /// Add the window to the app, and prepare the platform window for it.
+    fn into_builder(mut self, state: &Rc<RefCell<AppState<T>>>) -> WindowBuilder {
         self.title
             .resolve(&state.borrow().data, &state.borrow().env);
 
+        let mut menu_data = None;
+        if let Some(menu_fn) = self.menu_fn.as_ref() {
+            self.menu = Some(menu_fn(&state.borrow().data, &state.borrow().env));
+            menu_data = Some(state.borrow().data.clone());
+        }
         let platform_menu = self
             .menu
             .as_mut()
@@ -201,13 +464,19 @@ impl<T: Data> WindowDesc<T> {
         }
 
         builder.set_title(self.title.localized_str());
+        builder.set_always_on_top(self.always_on_top);
+        builder.set_skip_taskbar(self.skip_taskbar);
+        builder.set_utility(self.utility);
         if let Some(menu) = platform_menu {
             builder.set_menu(menu);
         }
 
-        let window = PendingWindow::new(self.root, self.title, self.menu);
+        let mut window = PendingWindow::new(self.root, self.title, self.menu);
+        window.menu_fn = self.menu_fn;
+        window.menu_data = menu_data;
+        window.touch_bar = self.touch_bar;
         state.borrow_mut().add_window(self.id, window);
 
-        builder.build()
+        builder
     }
 }
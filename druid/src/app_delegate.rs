@@ -15,8 +15,10 @@
 //! Customizing application-level behaviour.
 
 use std::collections::VecDeque;
+use std::path::PathBuf;
 
-use crate::{Command, Data, Env, Event, Target, WindowId};
+use crate::shell::FileInfo;
+use crate::{commands, Command, Data, Env, Event, Target, WindowId};
 
 /// A context passed in to [`AppDelegate`] functions.
 pub struct DelegateCtx<'a> {
@@ -78,4 +80,45 @@ pub trait AppDelegate<T: Data> {
     /// The handler for window deletion events.
     /// This function is called after a window has been removed.
     fn window_removed(&mut self, id: WindowId, data: &mut T, env: &Env, ctx: &mut DelegateCtx) {}
+
+    /// Called when a window becomes the focused window.
+    fn window_got_focus(&mut self, id: WindowId, data: &mut T, env: &Env, ctx: &mut DelegateCtx) {}
+
+    /// Called when a window stops being the focused window.
+    fn window_lost_focus(&mut self, id: WindowId, data: &mut T, env: &Env, ctx: &mut DelegateCtx) {}
+
+    /// Called when the application is asked to quit, with the [`QUIT_APP`]
+    /// command or from the platform's menu.
+    ///
+    /// Return `false` to keep running; this is the place to give the user a
+    /// chance to save their work.
+    ///
+    /// [`QUIT_APP`]: commands/constant.QUIT_APP.html
+    fn quit_requested(&mut self, data: &mut T, env: &Env, ctx: &mut DelegateCtx) -> bool {
+        true
+    }
+
+    /// Called when the platform asks the application to open files, such as
+    /// when the user double-clicks a document associated with it.
+    ///
+    /// Outside macOS this only happens with
+    /// [`AppLauncher::open_files_from_args`].
+    ///
+    /// By default, an [`OPEN_FILE`] command is sent for each file, as if it
+    /// had been chosen in an open panel.
+    ///
+    /// [`AppLauncher::open_files_from_args`]: struct.AppLauncher.html#method.open_files_from_args
+    /// [`OPEN_FILE`]: commands/constant.OPEN_FILE.html
+    fn open_files(&mut self, paths: Vec<PathBuf>, data: &mut T, env: &Env, ctx: &mut DelegateCtx) {
+        submit_open_file_commands(paths, ctx);
+    }
+}
+
+/// Send an [`OPEN_FILE`] command for each path.
+///
+/// [`OPEN_FILE`]: commands/constant.OPEN_FILE.html
+pub(crate) fn submit_open_file_commands(paths: Vec<PathBuf>, ctx: &mut DelegateCtx) {
+    for path in paths {
+        ctx.submit_command(Command::new(commands::OPEN_FILE, FileInfo::new(path)), None);
+    }
 }
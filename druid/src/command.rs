@@ -17,7 +17,7 @@
 use std::any::Any;
 use std::sync::{Arc, Mutex};
 
-use crate::{WidgetId, WindowId};
+use crate::{HotKey, WidgetId, WindowId};
 
 /// An identifier for a particular command.
 ///
@@ -90,6 +90,33 @@ pub enum Target {
     Window(WindowId),
     /// The target is a specific widget.
     Widget(WidgetId),
+    /// The target is the application as a whole; the event will be
+    /// delivered to every window in turn, until one of them handles it.
+    Global,
+}
+
+/// A hotkey that sends a [`Command`] even while the app is in the
+/// background, registered with [`REGISTER_GLOBAL_HOTKEY`].
+///
+/// [`Command`]: struct.Command.html
+/// [`REGISTER_GLOBAL_HOTKEY`]: commands/constant.REGISTER_GLOBAL_HOTKEY.html
+#[derive(Debug, Clone)]
+pub struct GlobalHotKey {
+    pub(crate) hotkey: HotKey,
+    pub(crate) command: Command,
+}
+
+impl GlobalHotKey {
+    /// Create a global hotkey that sends `command` when pressed.
+    ///
+    /// The command is sent each time the hotkey is pressed, so its argument,
+    /// if any, should not be a one-shot.
+    pub fn new(hotkey: HotKey, command: impl Into<Command>) -> Self {
+        GlobalHotKey {
+            hotkey,
+            command: command.into(),
+        }
+    }
 }
 
 /// [`Command`]s with special meaning, defined by druid.
@@ -125,12 +152,259 @@ pub mod sys {
     /// [`ContextMenu`]: ../struct.ContextMenu.html
     pub const SHOW_CONTEXT_MENU: Selector = Selector::new("druid-builtin.show-context-menu");
 
+    /// Close any popups the widgets in the window have open, such as the
+    /// suggestions of an [`Autocomplete`].
+    ///
+    /// This is sent to the window when Escape is pressed and the focused
+    /// widget doesn't handle it. Widgets that show popups should close them
+    /// on this command, without marking it as handled, so that it reaches
+    /// the others too. It has no argument.
+    ///
+    /// [`Autocomplete`]: ../widget/struct.Autocomplete.html
+    pub const DISMISS_POPUPS: Selector = Selector::new("druid-builtin.dismiss-popups");
+
     /// The selector for a command to set the window's menu. The argument should
     /// be a [`MenuDesc`] object.
     ///
     /// [`MenuDesc`]: ../struct.MenuDesc.html
     pub const SET_MENU: Selector = Selector::new("druid-builtin.set-menu");
 
+    /// The selector for a command to change some items of the window's menu
+    /// in place. The argument should be a [`MenuItemUpdate`] object.
+    ///
+    /// [`MenuItemUpdate`]: ../struct.MenuItemUpdate.html
+    pub const UPDATE_MENU_ITEM: Selector = Selector::new("druid-builtin.update-menu-item");
+
+    /// The selector for a command to set the window's Touch Bar. The argument
+    /// should be a [`TouchBarDesc`] object.
+    ///
+    /// This has no effect on platforms without a Touch Bar.
+    ///
+    /// [`TouchBarDesc`]: ../struct.TouchBarDesc.html
+    pub const SET_TOUCH_BAR: Selector = Selector::new("druid-builtin.set-touch-bar");
+
+    /// Show or hide the widget inspector for a window.
+    ///
+    /// The inspector highlights the widget under the mouse and shows its
+    /// type, id, layout and constraints, along with the values in its
+    /// [`Env`] that differ from the window's. While it is shown, the arrow
+    /// keys move to the parent, first child and siblings of the highlighted
+    /// widget, and escape hides it.
+    ///
+    /// In debug builds the inspector can also be toggled with
+    /// <kbd>Ctrl</kbd>+<kbd>Shift</kbd>+<kbd>I</kbd>
+    /// (<kbd>Cmd</kbd>+<kbd>Shift</kbd>+<kbd>I</kbd> on macOS), if no widget
+    /// handles that key first.
+    ///
+    /// [`Env`]: ../struct.Env.html
+    pub const TOGGLE_INSPECTOR: Selector = Selector::new("druid-builtin.toggle-inspector");
+
+    /// Go back to the previous state of the app's data.
+    ///
+    /// With the `time-travel` feature, each change to the data is recorded
+    /// along with the event that caused it; this command and
+    /// [`HISTORY_FORWARD`] step through those states, restoring each in
+    /// turn, and log the event that led to it. Changing the data after
+    /// stepping back discards the later states. Without the feature, these
+    /// commands do nothing.
+    ///
+    /// [`HISTORY_FORWARD`]: constant.HISTORY_FORWARD.html
+    pub const HISTORY_BACK: Selector = Selector::new("druid-builtin.history-back");
+
+    /// Go forward to the next state of the app's data, after using
+    /// [`HISTORY_BACK`].
+    ///
+    /// [`HISTORY_BACK`]: constant.HISTORY_BACK.html
+    pub const HISTORY_FORWARD: Selector = Selector::new("druid-builtin.history-forward");
+
+    /// Describe the widgets in a window, for debugging.
+    ///
+    /// With no argument, the tree is logged as an indented outline. If the
+    /// argument is a `PathBuf`, the tree is written to that file as JSON
+    /// instead. See [`WidgetTree`] for the details.
+    ///
+    /// [`WidgetTree`]: ../struct.WidgetTree.html
+    pub const DUMP_WIDGET_TREE: Selector = Selector::new("druid-builtin.dump-widget-tree");
+
+    /// Show or hide the performance overlay for a window.
+    ///
+    /// The overlay shows the frame rate, the time spent in each pass for the
+    /// last frame, and how many widgets it painted. If the app uses the
+    /// [`CountingAllocator`], it also shows how many allocations were made.
+    ///
+    /// [`CountingAllocator`]: ../struct.CountingAllocator.html
+    pub const TOGGLE_PERF_HUD: Selector = Selector::new("druid-builtin.toggle-perf-hud");
+
+    /// Scroll the target widget into view.
+    ///
+    /// This should be submitted with a [`Target::Widget`]; every [`Scroll`]
+    /// around that widget scrolls, the innermost first, until the widget is
+    /// visible. It is the same as the widget calling
+    /// [`EventCtx::scroll_to_view`] itself.
+    ///
+    /// [`Target::Widget`]: ../enum.Target.html#variant.Widget
+    /// [`Scroll`]: ../widget/struct.Scroll.html
+    /// [`EventCtx::scroll_to_view`]: ../struct.EventCtx.html#method.scroll_to_view
+    pub const SCROLL_TO_VIEW: Selector = Selector::new("druid-builtin.scroll-to-view");
+
+    /// Show suggestions in an [`Autocomplete`] that asked for them.
+    ///
+    /// The argument must be a [`Suggestions`], and the command should be
+    /// submitted with a [`Target::Widget`] for the widget in the
+    /// [`SuggestionRequest`].
+    ///
+    /// [`Autocomplete`]: ../widget/struct.Autocomplete.html
+    /// [`Suggestions`]: ../widget/struct.Suggestions.html
+    /// [`Target::Widget`]: ../enum.Target.html#variant.Widget
+    /// [`SuggestionRequest`]: ../widget/struct.SuggestionRequest.html
+    pub const SHOW_SUGGESTIONS: Selector = Selector::new("druid-builtin.show-suggestions");
+
+    /// The selector for a command to change the application's locale.
+    ///
+    /// The argument should be a `String` containing a BCP47 language tag,
+    /// such as `"de-DE"`; [`Env::available_locales`] lists the locales that have
+    /// localization resources. All localized strings, window titles and menus
+    /// are updated to the new locale.
+    ///
+    /// [`Env::available_locales`]: ../struct.Env.html#method.available_locales
+    pub const SET_LOCALE: Selector = Selector::new("druid-builtin.set-locale");
+
+    /// Sent to each window when monitors are connected, disconnected, or
+    /// rearranged. The new configuration is available from
+    /// [`Screen::get_monitors`].
+    ///
+    /// [`Screen::get_monitors`]: ../struct.Screen.html#method.get_monitors
+    pub const MONITORS_CHANGED: Selector = Selector::new("druid-builtin.monitors-changed");
+
+    /// Open a URL in the default browser. The argument should be a `String`.
+    pub const OPEN_URL: Selector = Selector::new("druid-builtin.open-url");
+
+    /// Open a file or folder with its default application. The argument
+    /// should be a `PathBuf`.
+    pub const OPEN_PATH: Selector = Selector::new("druid-builtin.open-path");
+
+    /// Show a file or folder in the system file manager. The argument should
+    /// be a `PathBuf`.
+    pub const REVEAL_PATH: Selector = Selector::new("druid-builtin.reveal-path");
+
+    /// Record that a document was opened, with
+    /// [`Application::add_recent_document`], and rebuild the menus so that
+    /// their 'Open Recent' lists show it. The argument should be a `PathBuf`.
+    ///
+    /// [`Application::add_recent_document`]: ../struct.Application.html#method.add_recent_document
+    pub const ADD_RECENT_DOCUMENT: Selector = Selector::new("druid-builtin.add-recent-document");
+
+    /// Forget the recent documents, and rebuild the menus so that their
+    /// 'Open Recent' lists are empty.
+    pub const CLEAR_RECENT_DOCUMENTS: Selector =
+        Selector::new("druid-builtin.clear-recent-documents");
+
+    /// Show the progress of a long operation on the window's taskbar button
+    /// (Windows) or the dock icon (macOS), so that it is visible while the
+    /// window is minimized.
+    ///
+    /// The argument should be an `Option<f64>` between `0.0` and `1.0`;
+    /// `None` hides the indicator.
+    pub const SET_PROGRESS: Selector = Selector::new("druid-builtin.set-progress");
+
+    /// The next bytes of the image shown by a [`ProgressiveImage`].
+    ///
+    /// The argument should be a `Vec<u8>`. This is only available with the
+    /// `progressive` feature.
+    ///
+    /// [`ProgressiveImage`]: ../widget/struct.ProgressiveImage.html
+    #[cfg(feature = "progressive")]
+    pub const FEED_IMAGE: Selector = Selector::new("druid-builtin.feed-image");
+
+    /// All of the bytes of the image shown by a [`ProgressiveImage`] have
+    /// been sent with [`FEED_IMAGE`].
+    ///
+    /// This is only available with the `progressive` feature.
+    ///
+    /// [`ProgressiveImage`]: ../widget/struct.ProgressiveImage.html
+    /// [`FEED_IMAGE`]: constant.FEED_IMAGE.html
+    #[cfg(feature = "progressive")]
+    pub const FINISH_IMAGE: Selector = Selector::new("druid-builtin.finish-image");
+
+    /// Show a short label, such as an unread count, on the dock icon (macOS).
+    ///
+    /// The argument should be an `Option<String>`; `None` removes the badge.
+    pub const SET_BADGE: Selector = Selector::new("druid-builtin.set-badge");
+
+    /// Change the window's icon, or on macOS the application's dock icon,
+    /// at runtime, such as to show unread messages.
+    ///
+    /// The argument should be an `Option<ImageData>`; `None` puts back the
+    /// default icon. See [`ImageData::from_raw`] for making one in code.
+    /// The image is used at whatever size the platform shows icons, so it is
+    /// best square, and at least 32 pixels across.
+    ///
+    /// [`ImageData::from_raw`]: ../widget/struct.ImageData.html#method.from_raw
+    pub const SET_ICON: Selector = Selector::new("druid-builtin.set-icon");
+
+    /// Keep the window above the other windows, or stop doing so.
+    ///
+    /// The argument should be a `bool`.
+    pub const SET_ALWAYS_ON_TOP: Selector = Selector::new("druid-builtin.set-always-on-top");
+
+    /// Leave the window out of the taskbar and the window switcher, or put it
+    /// back.
+    ///
+    /// The argument should be a `bool`.
+    pub const SET_SKIP_TASKBAR: Selector = Selector::new("druid-builtin.set-skip-taskbar");
+
+    /// Style the window as a utility window, or as a normal one.
+    ///
+    /// The argument should be a `bool`.
+    pub const SET_UTILITY: Selector = Selector::new("druid-builtin.set-utility");
+
+    /// A segment of a [`Breadcrumbs`] was clicked. The argument is the
+    /// `usize` index of the segment.
+    ///
+    /// [`Breadcrumbs`]: ../widget/struct.Breadcrumbs.html
+    pub const BREADCRUMB_SELECTED: Selector = Selector::new("druid-builtin.breadcrumb-selected");
+
+    /// Show a message in the [`StatusBar`] of the window for a while. The
+    /// argument should be a [`StatusMessage`].
+    ///
+    /// [`StatusBar`]: ../widget/struct.StatusBar.html
+    /// [`StatusMessage`]: ../widget/struct.StatusMessage.html
+    pub const SHOW_STATUS_MESSAGE: Selector = Selector::new("druid-builtin.show-status-message");
+
+    /// Start playing a [`Video`], from where it was paused. Submit it to the
+    /// video's id, or to the window to play every video in it.
+    ///
+    /// [`Video`]: ../widget/struct.Video.html
+    pub const PLAY_VIDEO: Selector = Selector::new("druid-builtin.play-video");
+
+    /// Pause a [`Video`], on the frame it is showing.
+    ///
+    /// [`Video`]: ../widget/struct.Video.html
+    pub const PAUSE_VIDEO: Selector = Selector::new("druid-builtin.pause-video");
+
+    /// Go to a point in a [`Video`]. The argument should be a `Duration`
+    /// from the start of the video.
+    ///
+    /// [`Video`]: ../widget/struct.Video.html
+    pub const SEEK_VIDEO: Selector = Selector::new("druid-builtin.seek-video");
+
+    /// Register a hotkey that works even while the app is in the background,
+    /// for utilities like screenshot tools and media controllers.
+    ///
+    /// The argument should be a [`GlobalHotKey`]. Its command is sent to the
+    /// window this command was sent to, and the hotkey is unregistered when
+    /// that window closes. On Linux global hotkeys need X11; Wayland does
+    /// not have them.
+    ///
+    /// [`GlobalHotKey`]: ../struct.GlobalHotKey.html
+    pub const REGISTER_GLOBAL_HOTKEY: Selector =
+        Selector::new("druid-builtin.register-global-hotkey");
+
+    /// Unregister the window's global hotkeys whose command has the given
+    /// selector. The argument should be that `Selector`.
+    pub const UNREGISTER_GLOBAL_HOTKEY: Selector =
+        Selector::new("druid-builtin.unregister-global-hotkey");
+
     /// Show the application preferences.
     pub const SHOW_PREFERENCES: Selector = Selector::new("druid-builtin.menu-show-preferences");
 
@@ -146,9 +420,12 @@ pub mod sys {
     /// System command. A file picker dialog will be shown to the user, and an
     /// `OPEN_FILE` command will be sent if a file is chosen.
     ///
-    /// The argument should be a [`FileDialogOptions`] struct.
+    /// The argument should be a [`FileDialogOptions`] struct; with
+    /// [`select_directories`] it picks a directory instead, whose path is in
+    /// the `OPEN_FILE` command.
     ///
     /// [`FileDialogOptions`]: struct.FileDialogOptions.html
+    /// [`select_directories`]: struct.FileDialogOptions.html#method.select_directories
     pub const SHOW_OPEN_PANEL: Selector = Selector::new("druid-builtin.menu-file-open");
 
     /// Open a file.
@@ -205,6 +482,11 @@ impl Selector {
     pub const fn new(s: &'static str) -> Selector {
         Selector(s)
     }
+
+    /// The string the selector was made with.
+    pub(crate) fn name(&self) -> &'static str {
+        self.0
+    }
 }
 
 impl Command {
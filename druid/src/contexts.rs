@@ -14,17 +14,27 @@
 
 //! The context types that are passed into various widget methods.
 
+use std::any::Any;
+use std::future::Future;
 use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
 use std::time::Instant;
 
 use log;
 
 use crate::core::{BaseState, CommandQueue, FocusChange};
+use crate::kurbo::{RoundedRect, Shape};
 use crate::piet::Piet;
 use crate::piet::RenderContext;
+use crate::task::{Task, TaskHandle, SPAWN_TASK};
+use crate::testing::HarnessHost;
+use crate::undo::{UndoRequest, RECORD_UNDO};
+use crate::widget::image::PietImage;
+use crate::widget::image_cache::WindowImages;
+use crate::widget::ImageData;
 use crate::{
-    Affine, Command, Cursor, Insets, Rect, Size, Target, Text, TimerToken, WidgetId, WindowHandle,
-    WindowId,
+    theme, Affine, Command, Cursor, CursorDesc, DragData, Env, Insets, Point, Rect, Selector, Size,
+    Target, Text, TimerToken, WidgetId, WindowHandle, WindowId,
 };
 
 /// A mutable context provided to event handling methods of widgets.
@@ -46,6 +56,8 @@ pub struct EventCtx<'a> {
     pub(crate) had_active: bool,
     pub(crate) is_handled: bool,
     pub(crate) is_root: bool,
+    /// Present in a test harness, which keeps its own timers.
+    pub(crate) harness: Option<&'a HarnessHost>,
 }
 
 /// A mutable context provided to the [`lifecycle`] method on widgets.
@@ -87,7 +99,9 @@ pub struct UpdateCtx<'a> {
 pub struct LayoutCtx<'a, 'b: 'a> {
     pub(crate) text_factory: &'a mut Text<'b>,
     pub(crate) paint_insets: Insets,
+    pub(crate) baseline_offset: f64,
     pub(crate) window_id: WindowId,
+    pub(crate) scale: f64,
 }
 
 /// Z-order paint operations with transformations.
@@ -114,6 +128,14 @@ pub struct PaintCtx<'a, 'b: 'a> {
     pub(crate) region: Region,
     pub(crate) base_state: &'a BaseState,
     pub(crate) focus_widget: Option<WidgetId>,
+    /// The number of widgets painted so far, for the performance overlay.
+    pub(crate) widgets_painted: usize,
+    pub(crate) scale: f64,
+    /// The images made for the render context, shared by the window's
+    /// widgets.
+    pub(crate) images: &'a mut WindowImages,
+    /// Present in a test harness, which collects broken invariants.
+    pub(crate) harness: Option<&'a HarnessHost>,
 }
 
 /// A region of a widget, generally used to describe what needs to be drawn.
@@ -181,6 +203,37 @@ impl<'a> EventCtx<'a> {
         *self.cursor = Some(cursor.clone());
     }
 
+    /// Set the cursor to a custom one, like [`set_cursor`].
+    ///
+    /// The cursor is made from the description the first time it is used.
+    /// If the platform can't make it, the cursor is left as it is.
+    ///
+    /// [`set_cursor`]: #method.set_cursor
+    pub fn set_custom_cursor(&mut self, desc: &CursorDesc) {
+        if let Some(cursor) = desc.cursor(self.window) {
+            self.set_cursor(&cursor);
+        }
+    }
+
+    /// Start dragging `data` out of the window, to another application or
+    /// to the desktop.
+    ///
+    /// This should be called while handling a [`MouseDown`] or
+    /// [`MouseMoved`] event. If `allow_move` is `false`, the drop target
+    /// may only copy the data. When the drag is over, every widget receives
+    /// [`Event::DragEnded`] with the action the drop target took.
+    ///
+    /// If there is an `image`, it is shown centered under the cursor while
+    /// the data is dragged.
+    ///
+    /// [`MouseDown`]: enum.Event.html#variant.MouseDown
+    /// [`MouseMoved`]: enum.Event.html#variant.MouseMoved
+    /// [`Event::DragEnded`]: enum.Event.html#variant.DragEnded
+    pub fn start_drag(&mut self, data: DragData, image: Option<&ImageData>, allow_move: bool) {
+        let image = image.map(ImageData::to_icon);
+        self.window.start_drag(data, image.as_ref(), allow_move);
+    }
+
     /// Set the "active" state of the widget.
     ///
     /// See [`EventCtx::is_active`](struct.EventCtx.html#method.is_active).
@@ -270,6 +323,14 @@ impl<'a> EventCtx<'a> {
         self.base_state.request_focus = Some(FocusChange::Focus(self.widget_id()));
     }
 
+    /// Request keyboard focus on behalf of another widget.
+    ///
+    /// This is useful for containers that manage focus for their children,
+    /// such as a group of radio buttons moving focus with the arrow keys.
+    pub fn set_focus(&mut self, target: WidgetId) {
+        self.base_state.request_focus = Some(FocusChange::Focus(target));
+    }
+
     /// Transfer focus to the next focusable widget.
     ///
     /// This should only be called by a widget that currently has focus.
@@ -309,13 +370,37 @@ impl<'a> EventCtx<'a> {
         self.base_state.needs_inval = true;
     }
 
+    /// Scroll this widget into view.
+    ///
+    /// Every [`Scroll`] that contains this widget scrolls as little as it can
+    /// to show it, starting with the innermost one. Widgets are also scrolled
+    /// into view when they gain focus.
+    ///
+    /// [`Scroll`]: widget/struct.Scroll.html
+    pub fn scroll_to_view(&mut self) {
+        self.scroll_area_to_view(Rect::from_origin_size(Point::ORIGIN, self.size()));
+    }
+
+    /// Scroll a region of this widget into view, such as the cursor of a
+    /// text box. `area` is in this widget's coordinates.
+    ///
+    /// See [`scroll_to_view`] for more information.
+    ///
+    /// [`scroll_to_view`]: #method.scroll_to_view
+    pub fn scroll_area_to_view(&mut self, area: Rect) {
+        self.base_state.request_scroll = Some(area);
+    }
+
     /// Request a timer event.
     ///
     /// The return value is a token, which can be used to associate the
     /// request with the event.
     pub fn request_timer(&mut self, deadline: Instant) -> TimerToken {
         self.base_state.request_timer = true;
-        self.window.request_timer(deadline)
+        match self.harness {
+            Some(harness) => harness.request_timer(deadline),
+            None => self.window.request_timer(deadline),
+        }
     }
 
     /// The layout size.
@@ -358,6 +443,56 @@ impl<'a> EventCtx<'a> {
         self.base_state.id
     }
 
+    /// Run `future` in the background, and when it finishes send its output
+    /// to this widget as the argument of a command with `selector`.
+    ///
+    /// By default, tasks share a pool of as many threads as there are
+    /// cores. A future that blocks, such as by reading a file, holds on to
+    /// one of them until it is done, and futures that need an async
+    /// runtime, such as tokio's, panic there; the app can run the tasks on
+    /// its own runtime instead, with [`AppLauncher::executor`].
+    ///
+    /// The returned [`TaskHandle`] can cancel the task, such as when its
+    /// output is no longer wanted.
+    ///
+    /// In the test [`Harness`], the future is run to completion right away.
+    ///
+    /// [`AppLauncher::executor`]: struct.AppLauncher.html#method.executor
+    /// [`TaskHandle`]: struct.TaskHandle.html
+    /// [`Harness`]: testing/struct.Harness.html
+    pub fn spawn<F>(&mut self, selector: Selector, future: F) -> TaskHandle
+    where
+        F: Future + Send + 'static,
+        F::Output: Any + Send,
+    {
+        let task = Task::new(future, selector, self.widget_id().into());
+        let handle = task.handle();
+        let cmd = Command::one_shot(SPAWN_TASK, task);
+        self.command_queue.push_back((self.window_id.into(), cmd));
+        handle
+    }
+
+    /// Make the change this widget is making to the data, while handling
+    /// this event, a step that the user can undo.
+    ///
+    /// `label` describes the change, such as "Typing". Changes from the same
+    /// widget with the same label that come in quick succession are merged
+    /// into one step, so that typing a word is undone all at once.
+    ///
+    /// The [`UNDO`] and [`REDO`] commands move through the steps; they are
+    /// sent by the standard menu items and their shortcuts.
+    ///
+    /// [`UNDO`]: commands/constant.UNDO.html
+    /// [`REDO`]: commands/constant.REDO.html
+    pub fn submit_undoable(&mut self, label: impl Into<String>) {
+        let request = UndoRequest {
+            label: label.into(),
+            source: Some(self.widget_id()),
+        };
+        let cmd = Command::new(RECORD_UNDO, request);
+        self.command_queue.push_back((self.window_id.into(), cmd));
+    }
+
     pub(crate) fn make_lifecycle_ctx(&mut self) -> LifeCycleCtx {
         LifeCycleCtx {
             command_queue: self.command_queue,
@@ -421,6 +556,16 @@ impl<'a> LifeCycleCtx<'a> {
         self.base_state.request_anim = true;
     }
 
+    /// Scroll this widget into view.
+    ///
+    /// See [`EventCtx::scroll_to_view`] for more information.
+    ///
+    /// [`EventCtx::scroll_to_view`]: struct.EventCtx.html#method.scroll_to_view
+    pub fn scroll_to_view(&mut self) {
+        let size = self.base_state.size();
+        self.base_state.request_scroll = Some(Rect::from_origin_size(Point::ORIGIN, size));
+    }
+
     /// Submit a [`Command`] to be run after this event is handled.
     ///
     /// Commands are run in the order they are submitted; all commands
@@ -437,6 +582,24 @@ impl<'a> LifeCycleCtx<'a> {
         let target = target.into().unwrap_or_else(|| self.window_id.into());
         self.command_queue.push_back((target, command.into()))
     }
+
+    /// Run `future` in the background, and when it finishes send its output
+    /// to this widget as the argument of a command with `selector`.
+    ///
+    /// See [`EventCtx::spawn`] for how the future is run.
+    ///
+    /// [`EventCtx::spawn`]: struct.EventCtx.html#method.spawn
+    pub fn spawn<F>(&mut self, selector: Selector, future: F) -> TaskHandle
+    where
+        F: Future + Send + 'static,
+        F::Output: Any + Send,
+    {
+        let task = Task::new(future, selector, self.widget_id().into());
+        let handle = task.handle();
+        let cmd = Command::one_shot(SPAWN_TASK, task);
+        self.command_queue.push_back((self.window_id.into(), cmd));
+        handle
+    }
 }
 
 impl<'a> UpdateCtx<'a> {
@@ -461,6 +624,12 @@ impl<'a> UpdateCtx<'a> {
         self.base_state.needs_layout = true;
     }
 
+    /// Request an animation frame, such as to animate a change to the data.
+    pub fn request_anim_frame(&mut self) {
+        self.base_state.request_anim = true;
+        self.base_state.needs_inval = true;
+    }
+
     /// Indicate that your children have changed.
     ///
     /// Widgets must call this method after adding a new child.
@@ -501,6 +670,14 @@ impl<'a, 'b> LayoutCtx<'a, 'b> {
         self.window_id
     }
 
+    /// The number of physical pixels in each unit of the window's
+    /// coordinates, such as `2.0` on most high-density screens.
+    ///
+    /// When this changes, the window is laid out again.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
     /// Set explicit paint [`Insets`] for this widget.
     ///
     /// You are not required to set explicit paint bounds unless you need
@@ -515,6 +692,20 @@ impl<'a, 'b> LayoutCtx<'a, 'b> {
     pub fn set_paint_insets(&mut self, insets: impl Into<Insets>) {
         self.paint_insets = insets.into().nonnegative();
     }
+
+    /// Set the distance from the bottom of this widget to the baseline of
+    /// its text, so that a [`Flex`] row can line the text up with that of
+    /// the widgets beside it.
+    ///
+    /// Widgets that hold a single child should pass on the child's
+    /// [`baseline_offset`], moved by however far the child is from their
+    /// bottom edge.
+    ///
+    /// [`Flex`]: widget/struct.Flex.html
+    /// [`baseline_offset`]: struct.WidgetPod.html#method.baseline_offset
+    pub fn set_baseline_offset(&mut self, offset: f64) {
+        self.baseline_offset = offset;
+    }
 }
 
 impl<'a, 'b: 'a> PaintCtx<'a, 'b> {
@@ -542,6 +733,14 @@ impl<'a, 'b: 'a> PaintCtx<'a, 'b> {
         self.base_state.size()
     }
 
+    /// The scale of the window the widget is painted in.
+    ///
+    /// See [`LayoutCtx::scale`](struct.LayoutCtx.html#method.scale) for
+    /// additional information.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
     /// Query the focus state of the widget.
     ///
     /// This is true only if this widget has focus.
@@ -559,6 +758,17 @@ impl<'a, 'b: 'a> PaintCtx<'a, 'b> {
         &self.region
     }
 
+    /// The image the render context draws for `data`, made the first time
+    /// any of the window's widgets draws this image or a clone of it.
+    pub(crate) fn piet_image(&mut self, data: &ImageData) -> Option<Rc<PietImage>> {
+        if let Some(image) = self.images.get(data) {
+            return Some(image);
+        }
+        let image = Rc::new(data.make_piet_image(self)?);
+        self.images.insert(data, image.clone());
+        Some(image)
+    }
+
     /// Creates a temporary `PaintCtx` with a new visible region, and calls
     /// the provided function with that `PaintCtx`.
     ///
@@ -572,9 +782,14 @@ impl<'a, 'b: 'a> PaintCtx<'a, 'b> {
             window_id: self.window_id,
             focus_widget: self.focus_widget,
             region: region.into(),
+            widgets_painted: 0,
+            scale: self.scale,
+            images: self.images,
+            harness: self.harness,
         };
         f(&mut child_ctx);
         self.z_ops.append(&mut child_ctx.z_ops);
+        self.widgets_painted += child_ctx.widgets_painted;
     }
 
     /// Allows to specify order for paint operations.
@@ -592,6 +807,47 @@ impl<'a, 'b: 'a> PaintCtx<'a, 'b> {
             transform: current_transform,
         })
     }
+
+    /// Stroke `shape` as a focus ring, if this widget has focus.
+    ///
+    /// The ring is drawn with [`FOCUS_RING_COLOR`] and [`FOCUS_RING_WIDTH`],
+    /// so that focus indicators can be themed in one place; the shape should
+    /// generally be the widget's outline.
+    ///
+    /// [`FOCUS_RING_COLOR`]: theme/constant.FOCUS_RING_COLOR.html
+    /// [`FOCUS_RING_WIDTH`]: theme/constant.FOCUS_RING_WIDTH.html
+    pub fn stroke_focus_ring(&mut self, shape: impl Shape, env: &Env) {
+        if self.has_focus() {
+            let color = env.get(theme::FOCUS_RING_COLOR);
+            self.render_ctx
+                .stroke(shape, &color, env.get(theme::FOCUS_RING_WIDTH));
+        }
+    }
+
+    /// Paint a focus ring around `rect`, if this widget has focus.
+    ///
+    /// This is [`stroke_focus_ring`] with a rounded rectangle whose corners
+    /// use [`FOCUS_RING_RADIUS`], for widgets without a rounded outline of
+    /// their own.
+    ///
+    /// [`stroke_focus_ring`]: #method.stroke_focus_ring
+    /// [`FOCUS_RING_RADIUS`]: theme/constant.FOCUS_RING_RADIUS.html
+    pub fn paint_focus_ring(&mut self, rect: Rect, env: &Env) {
+        let shape = RoundedRect::from_rect(rect, env.get(theme::FOCUS_RING_RADIUS));
+        self.stroke_focus_ring(shape, env);
+    }
+
+    /// Fill `rect` with the selection highlight.
+    ///
+    /// The highlight uses [`SELECTION_COLOR`] and [`SELECTION_RADIUS`].
+    ///
+    /// [`SELECTION_COLOR`]: theme/constant.SELECTION_COLOR.html
+    /// [`SELECTION_RADIUS`]: theme/constant.SELECTION_RADIUS.html
+    pub fn paint_selection(&mut self, rect: Rect, env: &Env) {
+        let shape = RoundedRect::from_rect(rect, env.get(theme::SELECTION_RADIUS));
+        self.render_ctx
+            .fill(shape, &env.get(theme::SELECTION_COLOR));
+    }
 }
 
 impl Region {
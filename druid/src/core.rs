@@ -19,8 +19,12 @@ use std::collections::VecDeque;
 use log;
 
 use crate::bloom::Bloom;
-use crate::kurbo::{Affine, Insets, Point, Rect, Shape, Size};
+use crate::commands::SCROLL_TO_VIEW;
+use crate::kurbo::{Affine, Insets, Point, Rect, Shape, Size, Vec2};
 use crate::piet::RenderContext;
+use crate::theme;
+use crate::trace::{self, TracePass};
+use crate::widget::Axis;
 use crate::{
     BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
     PaintCtx, Target, UpdateCtx, Widget, WidgetId,
@@ -69,7 +73,13 @@ pub struct WidgetPod<T, W> {
 #[derive(Clone)]
 pub(crate) struct BaseState {
     pub(crate) id: WidgetId,
+    /// The name of the widget's type, for debugging.
+    pub(crate) type_name: &'static str,
     pub(crate) layout_rect: Rect,
+    /// The constraints of the last layout pass, for debugging.
+    pub(crate) last_constraints: Option<BoxConstraints>,
+    /// The distance from the bottom of the layout rect to the text baseline.
+    pub(crate) baseline_offset: f64,
     /// The insets applied to the layout rect to generate the paint rect.
     /// In general, these will be zero; the exception is for things like
     /// drop shadows or overflowing text.
@@ -82,6 +92,9 @@ pub(crate) struct BaseState {
 
     pub(crate) is_hot: bool,
 
+    /// Whether something being dragged from outside the app is over the widget.
+    pub(crate) is_drag_hot: bool,
+
     pub(crate) is_active: bool,
 
     pub(crate) needs_layout: bool,
@@ -100,6 +113,14 @@ pub(crate) struct BaseState {
 
     pub(crate) focus_chain: Vec<WidgetId>,
     pub(crate) request_focus: Option<FocusChange>,
+    /// A region of this widget or a descendant, in this widget's
+    /// coordinates, that should be scrolled into view.
+    pub(crate) request_scroll: Option<Rect>,
+    /// The part of a wheel event that a descendant [`Scroll`] couldn't
+    /// use, for the ones around it.
+    ///
+    /// [`Scroll`]: widget/struct.Scroll.html
+    pub(crate) wheel_remainder: Option<Vec2>,
     pub(crate) children: Bloom<WidgetId>,
     pub(crate) children_changed: bool,
 }
@@ -125,6 +146,7 @@ impl<T, W: Widget<T>> WidgetPod<T, W> {
     /// adding a child widget to a container should call this method.
     pub fn new(inner: W) -> WidgetPod<T, W> {
         let mut state = BaseState::new(inner.id().unwrap_or_else(WidgetId::next));
+        state.type_name = inner.type_name();
         state.children_changed = true;
         state.needs_layout = true;
         WidgetPod {
@@ -223,6 +245,18 @@ impl<T, W: Widget<T>> WidgetPod<T, W> {
         self.state.paint_insets
     }
 
+    /// The distance from the bottom of the widget to the baseline of its
+    /// text, or zero for widgets without any.
+    ///
+    /// A widget can set this by calling [`set_baseline_offset`] during its
+    /// [`layout`] method.
+    ///
+    /// [`set_baseline_offset`]: struct.LayoutCtx.html#method.set_baseline_offset
+    /// [`layout`]: widget/trait.Widget.html#tymethod.layout
+    pub fn baseline_offset(&self) -> f64 {
+        self.state.baseline_offset
+    }
+
     /// Given a parents layout size, determine the appropriate paint `Insets`
     /// for the parent.
     ///
@@ -253,6 +287,18 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
     /// [`paint`]: widget/trait.Widget.html#tymethod.paint
     /// [`paint_with_offset`]: #method.paint_with_offset
     pub fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        let _span = trace::widget(TracePass::Paint, &self.state);
+        if self.state.last_constraints.is_none() {
+            let msg = format!(
+                "{} {:?} was painted before it was laid out",
+                self.state.type_name,
+                self.id()
+            );
+            match paint_ctx.harness {
+                Some(harness) => harness.report_violation(msg),
+                None => log::warn!("{}", msg),
+            }
+        }
         let mut ctx = PaintCtx {
             render_ctx: paint_ctx.render_ctx,
             window_id: paint_ctx.window_id,
@@ -260,9 +306,14 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
             region: paint_ctx.region.clone(),
             base_state: &self.state,
             focus_widget: paint_ctx.focus_widget,
+            widgets_painted: 0,
+            scale: paint_ctx.scale,
+            images: paint_ctx.images,
+            harness: paint_ctx.harness,
         };
         self.inner.paint(&mut ctx, data, &env);
         paint_ctx.z_ops.append(&mut ctx.z_ops);
+        paint_ctx.widgets_painted += ctx.widgets_painted + 1;
 
         if env.get(Env::DEBUG_PAINT) {
             let rect = Rect::from_origin_size(Point::ORIGIN, ctx.size());
@@ -331,13 +382,70 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         data: &T,
         env: &Env,
     ) -> Size {
+        let _span = trace::widget(TracePass::Layout, &self.state);
         layout_ctx.paint_insets = Insets::ZERO;
+        // a parent that doesn't set its own baseline shouldn't get ours
+        let parent_baseline = std::mem::replace(&mut layout_ctx.baseline_offset, 0.0);
         let size = self.inner.layout(layout_ctx, bc, data, &env);
         self.state.paint_insets = layout_ctx.paint_insets;
+        self.state.baseline_offset =
+            std::mem::replace(&mut layout_ctx.baseline_offset, parent_baseline);
+        self.state.last_constraints = Some(*bc);
         self.state.needs_layout = false;
         size
     }
 
+    /// The smallest length along `axis` that the widget's content fits in.
+    ///
+    /// See [`Widget::min_intrinsic`] for more information.
+    ///
+    /// [`Widget::min_intrinsic`]: widget/trait.Widget.html#method.min_intrinsic
+    pub fn min_intrinsic(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        Self::intrinsic(layout_ctx, |ctx| {
+            self.inner.min_intrinsic(ctx, axis, cross, data, env)
+        })
+    }
+
+    /// The length along `axis` that the widget would take if there were no
+    /// limit.
+    ///
+    /// See [`Widget::max_intrinsic`] for more information.
+    ///
+    /// [`Widget::max_intrinsic`]: widget/trait.Widget.html#method.max_intrinsic
+    pub fn max_intrinsic(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        Self::intrinsic(layout_ctx, |ctx| {
+            self.inner.max_intrinsic(ctx, axis, cross, data, env)
+        })
+    }
+
+    /// Answer an intrinsic size query without it showing in the layout of
+    /// the widget asking.
+    ///
+    /// Widgets often answer by laying themselves out, which sets a baseline
+    /// and paint insets in the context; they are put back as they were.
+    fn intrinsic(layout_ctx: &mut LayoutCtx, query: impl FnOnce(&mut LayoutCtx) -> f64) -> f64 {
+        let paint_insets = layout_ctx.paint_insets;
+        let baseline_offset = std::mem::replace(&mut layout_ctx.baseline_offset, 0.0);
+        let length = query(layout_ctx);
+        layout_ctx.paint_insets = paint_insets;
+        layout_ctx.baseline_offset = baseline_offset;
+        length
+    }
+
     /// Propagate an event.
     ///
     /// Generally the [`event`] method of a container widget will call this
@@ -347,6 +455,7 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
     ///
     /// [`event`]: trait.Widget.html#method.event
     pub fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        let _span = trace::widget(TracePass::Event, &self.state);
         if self.old_data.is_none() {
             log::error!(
                 "widget {:?} is receiving an event without having first \
@@ -373,11 +482,13 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
             is_handled: false,
             is_root: false,
             focus_widget: ctx.focus_widget,
+            harness: ctx.harness,
         };
         let rect = child_ctx.base_state.layout_rect;
         // Note: could also represent this as `Option<Event>`.
         let mut recurse = true;
         let mut hot_changed = None;
+        let mut drag_hot_changed = None;
         let child_event = match event {
             Event::WindowConnected => Event::WindowConnected,
             Event::Size(size) => {
@@ -414,6 +525,35 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
                 mouse_event.pos -= rect.origin().to_vec2();
                 Event::MouseMoved(mouse_event)
             }
+            Event::DragMoved(drag_event) => {
+                let had_drag_hot = child_ctx.base_state.is_drag_hot;
+                child_ctx.base_state.is_drag_hot = rect.winding(drag_event.pos) != 0;
+                if had_drag_hot != child_ctx.base_state.is_drag_hot {
+                    drag_hot_changed = Some(child_ctx.base_state.is_drag_hot);
+                }
+                recurse = had_drag_hot || child_ctx.base_state.is_drag_hot;
+                let mut drag_event = drag_event.clone();
+                drag_event.pos -= rect.origin().to_vec2();
+                Event::DragMoved(drag_event)
+            }
+            // only sent to a widget by its own `WidgetPod`, below
+            Event::DragEnter(drag_event) => {
+                recurse = false;
+                Event::DragEnter(drag_event.clone())
+            }
+            Event::DragLeave => {
+                recurse = child_ctx.base_state.is_drag_hot;
+                child_ctx.base_state.is_drag_hot = false;
+                Event::DragLeave
+            }
+            Event::Drop(drop_event) => {
+                recurse = rect.winding(drop_event.pos) != 0;
+                child_ctx.base_state.is_drag_hot = false;
+                let mut drop_event = drop_event.clone();
+                drop_event.pos -= rect.origin().to_vec2();
+                Event::Drop(drop_event)
+            }
+            Event::DragEnded(action) => Event::DragEnded(*action),
             Event::KeyDown(e) => {
                 recurse = child_ctx.has_focus();
                 Event::KeyDown(*e)
@@ -440,8 +580,14 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
             }
             Event::Command(cmd) => Event::Command(cmd.clone()),
             Event::TargetedCommand(target, cmd) => match target {
-                Target::Window(_) => Event::Command(cmd.clone()),
-                Target::Widget(id) if *id == child_ctx.widget_id() => Event::Command(cmd.clone()),
+                Target::Window(_) | Target::Global => Event::Command(cmd.clone()),
+                Target::Widget(id) if *id == child_ctx.widget_id() => {
+                    if cmd.selector == SCROLL_TO_VIEW {
+                        child_ctx.scroll_to_view();
+                        recurse = false;
+                    }
+                    Event::Command(cmd.clone())
+                }
                 Target::Widget(id) => {
                     recurse = child_ctx.base_state.children.contains(id);
                     Event::TargetedCommand(*target, cmd.clone())
@@ -455,6 +601,18 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
             self.inner
                 .lifecycle(&mut lc_ctx, &hot_changed_event, data, &env);
         }
+        match (drag_hot_changed, &child_event) {
+            (Some(true), Event::DragMoved(drag_event)) => {
+                let enter = Event::DragEnter(drag_event.clone());
+                self.inner.event(&mut child_ctx, &enter, data, env);
+            }
+            (Some(false), _) => self
+                .inner
+                .event(&mut child_ctx, &Event::DragLeave, data, env),
+            _ => (),
+        }
+        // the notification shouldn't stop the event itself
+        child_ctx.is_handled = false;
         if recurse {
             child_ctx.base_state.has_active = false;
             self.inner.event(&mut child_ctx, &child_event, data, &env);
@@ -462,10 +620,13 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         };
 
         ctx.base_state.merge_up(&child_ctx.base_state);
+        child_ctx.base_state.request_scroll = None;
+        child_ctx.base_state.wheel_remainder = None;
         ctx.is_handled |= child_ctx.is_handled;
     }
 
     pub fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        let _span = trace::widget(TracePass::LifeCycle, &self.state);
         let recurse = match event {
             LifeCycle::AnimFrame(_) => {
                 let r = self.state.request_anim;
@@ -498,6 +659,12 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
                 };
 
                 if let Some(change) = this_changed {
+                    if change {
+                        // bring the newly focused widget into view, such as
+                        // when tabbing to a widget that is scrolled away
+                        self.state.request_scroll =
+                            Some(Rect::from_origin_size(Point::ORIGIN, self.state.size()));
+                    }
                     let event = LifeCycle::FocusChanged(change);
                     self.inner.lifecycle(ctx, &event, data, env);
                     false
@@ -525,6 +692,10 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
                 f.call(&self.state);
                 true
             }
+            LifeCycle::DebugCollectTree(collector) => {
+                collector.enter(&self.state, env);
+                true
+            }
         };
 
         let mut child_ctx = LifeCycleCtx {
@@ -536,8 +707,12 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
         if recurse {
             self.inner.lifecycle(&mut child_ctx, event, data, env);
         }
+        if let LifeCycle::DebugCollectTree(collector) = event {
+            collector.exit();
+        }
 
         ctx.base_state.merge_up(&self.state);
+        self.state.request_scroll = None;
 
         // we only want to update child state after this specific event.
         if let LifeCycle::WidgetAdded = event {
@@ -555,7 +730,8 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
     ///
     /// [`update`]: trait.Widget.html#method.update
     pub fn update(&mut self, ctx: &mut UpdateCtx, data: &T, env: &Env) {
-        match (self.old_data.as_ref(), self.env.as_ref()) {
+        let _span = trace::widget(TracePass::Update, &self.state);
+        let text_scale_changed = match (self.old_data.as_ref(), self.env.as_ref()) {
             (Some(d), Some(e)) if d.same(data) && e.same(env) => return,
             (None, _) => {
                 log::warn!("old_data missing in {:?}, skipping update", self.id());
@@ -563,8 +739,9 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
                 self.env = Some(env.clone());
                 return;
             }
-            _ => (),
-        }
+            (_, Some(e)) => e.try_get(theme::TEXT_SCALE) != env.try_get(theme::TEXT_SCALE),
+            (_, None) => false,
+        };
 
         let mut child_ctx = UpdateCtx {
             window: ctx.window,
@@ -572,6 +749,13 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
             window_id: ctx.window_id,
         };
 
+        // the text scale changes the size of all text, so any widget might
+        // need a new layout.
+        if text_scale_changed {
+            child_ctx.request_layout();
+            child_ctx.request_paint();
+        }
+
         self.inner
             .update(&mut child_ctx, self.old_data.as_ref().unwrap(), data, env);
         self.old_data = Some(data.clone());
@@ -595,16 +779,22 @@ impl BaseState {
     pub(crate) fn new(id: WidgetId) -> BaseState {
         BaseState {
             id,
+            type_name: "",
             layout_rect: Rect::ZERO,
+            last_constraints: None,
             paint_insets: Insets::ZERO,
+            baseline_offset: 0.0,
             needs_inval: false,
             is_hot: false,
+            is_drag_hot: false,
             needs_layout: false,
             is_active: false,
             has_active: false,
             request_anim: false,
             request_timer: false,
             request_focus: None,
+            request_scroll: None,
+            wheel_remainder: None,
             focus_chain: Vec::new(),
             children: Bloom::new(),
             children_changed: false,
@@ -620,6 +810,11 @@ impl BaseState {
         self.has_active |= child_state.has_active;
         self.children_changed |= child_state.children_changed;
         self.request_focus = self.request_focus.or(child_state.request_focus);
+        if let Some(rect) = child_state.request_scroll {
+            let rect = rect + child_state.layout_rect.origin().to_vec2();
+            self.request_scroll = self.request_scroll.or(Some(rect));
+        }
+        self.wheel_remainder = self.wheel_remainder.or(child_state.wheel_remainder);
     }
 
     #[inline]
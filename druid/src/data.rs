@@ -131,6 +131,7 @@ impl_data_simple!(usize);
 impl_data_simple!(char);
 impl_data_simple!(bool);
 impl_data_simple!(String);
+impl_data_simple!(std::time::Duration);
 
 impl Data for f32 {
     fn same(&self, other: &Self) -> bool {
@@ -27,6 +27,8 @@ use crate::piet::{Color, LinearGradient};
 use crate::localization::L10nManager;
 use crate::Data;
 
+use unic_langid::LanguageIdentifier;
+
 /// An environment passed down through all widget traversals.
 ///
 /// All widget methods have access to an environment, and it is passed
@@ -138,6 +140,20 @@ impl Env {
         self
     }
 
+    /// The values in this environment that are missing from `base` or
+    /// differ from its values, sorted by key.
+    pub(crate) fn overrides(&self, base: &Env) -> Vec<(String, Value)> {
+        let mut overrides: Vec<_> = self
+            .0
+            .map
+            .iter()
+            .filter(|(key, value)| base.0.map.get(*key).map(|v| !v.same(value)) != Some(false))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        overrides.sort_by(|a, b| a.0.cmp(&b.0));
+        overrides
+    }
+
     /// Sets a value in an environment.
     ///
     /// # Panics
@@ -168,6 +184,38 @@ impl Env {
         &self.0.l10n
     }
 
+    /// Change the locale used to resolve [`LocalizedString`]s.
+    ///
+    /// Strings are re-resolved the next time they are updated with this `Env`.
+    ///
+    /// [`LocalizedString`]: struct.LocalizedString.html
+    pub(crate) fn set_locale(&mut self, locale: LanguageIdentifier) {
+        let l10n = self.0.l10n.with_locale(locale);
+        let rtl = l10n.is_rtl();
+        Arc::make_mut(&mut self.0).l10n = Arc::new(l10n);
+        self.set(Env::RIGHT_TO_LEFT, rtl);
+    }
+
+    /// Returns the current locale, as a BCP47 language tag such as `en-US`.
+    pub fn locale(&self) -> String {
+        self.0.l10n.current_locale().to_string()
+    }
+
+    /// Returns the locales for which localization resources are available,
+    /// as BCP47 language tags.
+    ///
+    /// Any of these can be passed to the [`SET_LOCALE`] command.
+    ///
+    /// [`SET_LOCALE`]: commands/constant.SET_LOCALE.html
+    pub fn available_locales(&self) -> Vec<String> {
+        self.0
+            .l10n
+            .available_locales()
+            .iter()
+            .map(|locale| locale.to_string())
+            .collect()
+    }
+
     /// Given an id, returns one of 18 distinct colors
     #[doc(hidden)]
     pub fn get_debug_color(&self, id: u64) -> Color {
@@ -182,6 +230,22 @@ impl Env {
     ///
     /// [`AppLauncher`]: struct.AppLauncher.html
     pub(crate) const DEBUG_PAINT: Key<bool> = Key::new("debug_paint");
+
+    /// Whether text is measured as if every grapheme were the same width,
+    /// which the test harness sets so that layout is the same everywhere.
+    pub(crate) const MOCK_TEXT_METRICS: Key<bool> = Key::new("mock_text_metrics");
+
+    /// Whether layout should be mirrored for a right-to-left locale.
+    ///
+    /// When this is `true`, rows are laid out from right to left, and the
+    /// horizontal components of padding and alignment are swapped.
+    ///
+    /// This is derived from the current locale, and is reset whenever the
+    /// locale changes. It can be overridden for part of the widget tree with
+    /// an [`EnvScope`].
+    ///
+    /// [`EnvScope`]: widget/struct.EnvScope.html
+    pub const RIGHT_TO_LEFT: Key<bool> = Key::new("right_to_left");
 }
 
 impl<T> Key<T> {
@@ -279,7 +343,8 @@ impl Data for Env {
 
 impl Data for EnvImpl {
     fn same(&self, other: &EnvImpl) -> bool {
-        self.map.len() == other.map.len()
+        Arc::ptr_eq(&self.l10n, &other.l10n)
+            && self.map.len() == other.map.len()
             && self
                 .map
                 .iter()
@@ -314,13 +379,17 @@ impl Default for Env {
             Color::rgb8(0, 0, 0),
         ];
 
+        let rtl = l10n.is_rtl();
         let inner = EnvImpl {
             l10n: Arc::new(l10n),
             map: HashMap::new(),
             debug_colors,
         };
 
-        Env(Arc::new(inner)).adding(Env::DEBUG_PAINT, false)
+        Env(Arc::new(inner))
+            .adding(Env::DEBUG_PAINT, false)
+            .adding(Env::MOCK_TEXT_METRICS, false)
+            .adding(Env::RIGHT_TO_LEFT, rtl)
     }
 }
 
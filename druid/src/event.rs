@@ -14,10 +14,11 @@
 
 //! Events.
 
-use crate::kurbo::{Rect, Shape, Size, Vec2};
+use crate::kurbo::{Point, Rect, Shape, Size, Vec2};
 
-use druid_shell::{Clipboard, KeyEvent, KeyModifiers, TimerToken};
+use druid_shell::{Clipboard, DragData, DropAction, KeyEvent, KeyModifiers, TimerToken};
 
+use crate::inspector::TreeCollector;
 use crate::mouse::MouseEvent;
 use crate::{Command, Target, WidgetId};
 
@@ -105,6 +106,38 @@ pub enum Event {
     ///
     /// The value is a delta.
     Zoom(f64),
+    /// Called when something being dragged moves into the widget.
+    ///
+    /// This is sent immediately before the first `DragMoved` event inside
+    /// the widget, and is a good time to show that a drop will be accepted.
+    DragEnter(DragEvent),
+    /// Called when something being dragged moves over the widget.
+    ///
+    /// Like `MouseMoved`, this is also sent to a widget that the drag has
+    /// just left, after its `DragLeave`.
+    DragMoved(DragEvent),
+    /// Called when a drag leaves the widget, or the window.
+    ///
+    /// This is also sent just before a drop, since the drag is over, so a
+    /// widget that showed it would accept the drop can stop doing so here.
+    DragLeave,
+    /// Called when something is dropped on the widget.
+    ///
+    /// Widgets that accept the drop should call [`set_handled`]. The widget
+    /// has already been sent a `DragLeave`, and nothing is sent after the
+    /// drop.
+    ///
+    /// [`set_handled`]: struct.EventCtx.html#method.set_handled
+    Drop(DropEvent),
+    /// Called on all widgets when a drag started with
+    /// [`EventCtx::start_drag`] ends.
+    ///
+    /// The action is `None` if the data was not dropped anywhere. While the
+    /// drag is in progress the widget that started it does not get a
+    /// `MouseUp`, so it should also clear its active state here.
+    ///
+    /// [`EventCtx::start_drag`]: struct.EventCtx.html#method.start_drag
+    DragEnded(Option<DropAction>),
     /// Called on a timer event.
     ///
     /// Request a timer event through [`EventCtx::request_timer()`]. That will
@@ -203,6 +236,10 @@ pub enum LifeCycle {
     },
     #[cfg(test)]
     DebugInspectState(StateCheckFn),
+    /// Debugging only: describe each widget in the tree. This is used by the
+    /// inspector.
+    #[doc(hidden)]
+    DebugCollectTree(TreeCollector),
 }
 
 /// A mouse wheel event.
@@ -231,6 +268,23 @@ pub struct WheelEvent {
     pub mods: KeyModifiers,
 }
 
+/// Something being dragged over a window, from another application or
+/// from the desktop.
+#[derive(Debug, Clone)]
+pub struct DragEvent {
+    /// The position of the drag, in the widget's coordinate space.
+    pub pos: Point,
+}
+
+/// Something dropped on a window.
+#[derive(Debug, Clone)]
+pub struct DropEvent {
+    /// The position of the drop, in the widget's coordinate space.
+    pub pos: Point,
+    /// The files, urls or text that were dropped.
+    pub data: DragData,
+}
+
 impl Event {
     /// Transform the event for the contents of a scrolling container.
     pub fn transform_scroll(&self, offset: Vec2, viewport: Rect) -> Option<Event> {
@@ -264,6 +318,25 @@ impl Event {
                     None
                 }
             }
+            Event::DragMoved(drag_event) => {
+                if viewport.winding(drag_event.pos) != 0 {
+                    let mut drag_event = drag_event.clone();
+                    drag_event.pos += offset;
+                    Some(Event::DragMoved(drag_event))
+                } else {
+                    // the drag has left the visible content
+                    Some(Event::DragLeave)
+                }
+            }
+            Event::Drop(drop_event) => {
+                if viewport.winding(drop_event.pos) != 0 {
+                    let mut drop_event = drop_event.clone();
+                    drop_event.pos += offset;
+                    Some(Event::Drop(drop_event))
+                } else {
+                    None
+                }
+            }
             _ => Some(self.clone()),
         }
     }
@@ -305,7 +378,6 @@ mod state_cell {
             )
         }
 
-        #[allow(dead_code)]
         pub(crate) fn take(&self) -> Option<BaseState> {
             self.0.borrow_mut().take()
         }
@@ -318,7 +390,7 @@ mod state_cell {
 
         pub(crate) fn call(&self, state: &BaseState) {
             let mut panic_reporter = WidgetDrop(true, state.id);
-            (self.0)(&state);
+            (self.0)(state);
             panic_reporter.0 = false;
         }
     }
@@ -74,12 +74,15 @@ impl ExtEventHost {
         !self.queue.lock().unwrap().is_empty()
     }
 
-    pub(crate) fn recv(&mut self) -> Option<(Option<Target>, Command)> {
+    /// Take everything that has been sent to us so far, in the order it was
+    /// sent.
+    pub(crate) fn take_batch(&mut self) -> Vec<(Option<Target>, Command)> {
         self.queue
             .lock()
             .unwrap()
-            .pop_front()
+            .drain(..)
             .map(|(sel, obj, targ)| (targ, Command::from_ext(sel, obj)))
+            .collect()
     }
 }
 
@@ -99,6 +102,11 @@ impl ExtEventSink {
     ///
     /// This limitation may be removed in the future.
     ///
+    /// Commands that arrive close together are handled together, followed by
+    /// a single update, so sending many at once does not redo the work of
+    /// updating and laying out the widgets for each of them. They are handled
+    /// after any commands already waiting that came from user input.
+    ///
     /// [`Command`]: struct.Command.html
     /// [`Selector`]: struct.Selector.html
     pub fn submit_command<T: Any + Send>(
@@ -107,15 +115,32 @@ impl ExtEventSink {
         obj: impl Into<Option<T>>,
         target: impl Into<Option<Target>>,
     ) -> Result<(), ExtEventError> {
-        let target = target.into();
         let obj = obj.into().map(|o| Box::new(o) as Box<dyn Any + Send>);
-        if let Some(handle) = self.handle.lock().unwrap().as_mut() {
-            handle.schedule_idle(EXT_EVENT_IDLE_TOKEN);
+        self.submit_boxed(sel, obj, target)
+    }
+
+    /// Like [`submit_command`], with the argument already boxed.
+    ///
+    /// [`submit_command`]: #method.submit_command
+    pub(crate) fn submit_boxed(
+        &self,
+        sel: Selector,
+        obj: Option<Box<dyn Any + Send>>,
+        target: impl Into<Option<Target>>,
+    ) -> Result<(), ExtEventError> {
+        let target = target.into();
+        let was_empty = {
+            let mut queue = self.queue.lock().map_err(|_| ExtEventError)?;
+            queue.push_back((sel, obj, target));
+            queue.len() == 1
+        };
+        // if the queue wasn't empty, a wakeup is already on its way, and it
+        // will take this command with the others.
+        if was_empty {
+            if let Some(handle) = self.handle.lock().unwrap().as_mut() {
+                handle.schedule_idle(EXT_EVENT_IDLE_TOKEN);
+            }
         }
-        self.queue
-            .lock()
-            .map_err(|_| ExtEventError)?
-            .push_back((sel, obj, target));
         Ok(())
     }
 }
@@ -127,3 +152,25 @@ impl std::fmt::Display for ExtEventError {
 }
 
 impl std::error::Error for ExtEventError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_taken_as_one_batch() {
+        let mut host = ExtEventHost::new();
+        let sink = host.make_sink();
+        for i in 0..3u32 {
+            sink.submit_command(Selector::new("count"), i, None)
+                .unwrap();
+        }
+        let batch = host.take_batch();
+        let args: Vec<u32> = batch
+            .iter()
+            .map(|(_, cmd)| *cmd.get_object::<u32>().unwrap())
+            .collect();
+        assert_eq!(args, vec![0, 1, 2]);
+        assert!(!host.has_pending_items());
+    }
+}
@@ -0,0 +1,448 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Locale-aware formatting of numbers, currencies, dates and durations.
+//!
+//! The `format_*` functions take a BCP47 language tag, such as the one
+//! returned by [`Env::locale`]. The adapters ([`number`], [`currency`],
+//! [`date`] and [`duration`]) return closures that can be passed directly
+//! to [`Label::new`]; they use the current locale of the `Env`, and so
+//! follow the locale when it is changed at runtime.
+//!
+//! ```
+//! use druid::format;
+//! use druid::widget::Label;
+//!
+//! let price: Label<f64> = Label::new(format::currency("€"));
+//! let ratio: Label<f64> = Label::new(format::number(2));
+//! ```
+//!
+//! Only a small table of common conventions is built in; unknown locales
+//! fall back to their language, and then to `en-US`.
+//!
+//! [`Env::locale`]: ../struct.Env.html#method.locale
+//! [`Label::new`]: ../widget/struct.Label.html#method.new
+//! [`number`]: fn.number.html
+//! [`currency`]: fn.currency.html
+//! [`date`]: fn.date.html
+//! [`duration`]: fn.duration.html
+
+use std::time::Duration;
+
+use crate::{Data, Env};
+
+/// A calendar date, for use with [`format_date`].
+///
+/// [`format_date`]: fn.format_date.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: i32,
+    /// The month, from 1 to 12.
+    pub month: u32,
+    /// The day of the month, from 1 to 31.
+    pub day: u32,
+}
+
+impl Date {
+    /// Create a new `Date`.
+    pub fn new(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+}
+
+impl Data for Date {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CurrencyPosition {
+    /// `$1.00`
+    Prefix,
+    /// `€ 1,00`
+    PrefixSpaced,
+    /// `1,00 €`
+    Suffix,
+}
+
+/// The conventions for a particular locale.
+#[derive(Debug, Clone, Copy)]
+struct Conventions {
+    decimal: char,
+    group: &'static str,
+    date_order: DateOrder,
+    date_separator: &'static str,
+    /// Whether days and months are padded to two digits.
+    pad_date: bool,
+    time_separator: char,
+    currency: CurrencyPosition,
+}
+
+const NBSP: &str = "\u{a0}";
+const NARROW_NBSP: &str = "\u{202f}";
+
+const EN_US: Conventions = Conventions {
+    decimal: '.',
+    group: ",",
+    date_order: DateOrder::MonthDayYear,
+    date_separator: "/",
+    pad_date: false,
+    time_separator: ':',
+    currency: CurrencyPosition::Prefix,
+};
+
+const EN_GB: Conventions = Conventions {
+    date_order: DateOrder::DayMonthYear,
+    pad_date: true,
+    ..EN_US
+};
+
+const DE: Conventions = Conventions {
+    decimal: ',',
+    group: ".",
+    date_order: DateOrder::DayMonthYear,
+    date_separator: ".",
+    pad_date: true,
+    time_separator: ':',
+    currency: CurrencyPosition::Suffix,
+};
+
+const DE_CH: Conventions = Conventions {
+    decimal: '.',
+    group: "’",
+    currency: CurrencyPosition::PrefixSpaced,
+    ..DE
+};
+
+const FR: Conventions = Conventions {
+    group: NARROW_NBSP,
+    date_separator: "/",
+    ..DE
+};
+
+const FR_CA: Conventions = Conventions {
+    group: NBSP,
+    date_order: DateOrder::YearMonthDay,
+    date_separator: "-",
+    ..FR
+};
+
+const ES: Conventions = Conventions {
+    date_separator: "/",
+    pad_date: false,
+    ..DE
+};
+
+const IT: Conventions = Conventions {
+    date_separator: "/",
+    ..DE
+};
+
+const NL: Conventions = Conventions {
+    date_separator: "-",
+    pad_date: false,
+    currency: CurrencyPosition::PrefixSpaced,
+    ..DE
+};
+
+const PT: Conventions = Conventions {
+    date_separator: "/",
+    currency: CurrencyPosition::PrefixSpaced,
+    ..DE
+};
+
+const RU: Conventions = Conventions { group: NBSP, ..DE };
+
+const FI: Conventions = Conventions {
+    group: NBSP,
+    pad_date: false,
+    time_separator: '.',
+    ..DE
+};
+
+const SV: Conventions = Conventions {
+    group: NBSP,
+    date_order: DateOrder::YearMonthDay,
+    date_separator: "-",
+    ..DE
+};
+
+const JA: Conventions = Conventions {
+    date_order: DateOrder::YearMonthDay,
+    pad_date: true,
+    ..EN_US
+};
+
+impl Conventions {
+    fn for_locale(locale: &str) -> Conventions {
+        let mut subtags = locale.split(&['-', '_'][..]);
+        let language = subtags.next().unwrap_or_default().to_ascii_lowercase();
+        let region = subtags
+            .find(|tag| tag.len() == 2 || (tag.len() == 3 && tag.chars().all(char::is_numeric)))
+            .map(str::to_ascii_uppercase)
+            .unwrap_or_default();
+
+        match (language.as_str(), region.as_str()) {
+            ("en", "US") | ("en", "") => EN_US,
+            ("en", "CA") => Conventions {
+                date_order: DateOrder::YearMonthDay,
+                date_separator: "-",
+                pad_date: true,
+                ..EN_US
+            },
+            ("en", _) => EN_GB,
+            ("de", "CH") => DE_CH,
+            ("de", _) => DE,
+            ("fr", "CA") => FR_CA,
+            ("fr", "CH") => Conventions {
+                date_separator: ".",
+                ..FR
+            },
+            ("fr", _) => FR,
+            ("es", _) => ES,
+            ("it", _) => IT,
+            ("nl", _) => NL,
+            ("pt", "PT") => Conventions {
+                group: NBSP,
+                currency: CurrencyPosition::Suffix,
+                ..PT
+            },
+            ("pt", _) => PT,
+            ("ru", _) | ("uk", _) | ("pl", _) | ("cs", _) => RU,
+            ("fi", _) => FI,
+            ("sv", _) => SV,
+            ("ja", _) | ("zh", _) => JA,
+            _ => EN_US,
+        }
+    }
+}
+
+/// Format a number with `fraction_digits` digits after the decimal mark,
+/// using the grouping separator and decimal mark of `locale`.
+///
+/// ```
+/// use druid::format::format_number;
+///
+/// assert_eq!(format_number(1234.5, 2, "en-US"), "1,234.50");
+/// assert_eq!(format_number(1234.5, 2, "de-DE"), "1.234,50");
+/// ```
+pub fn format_number(value: f64, fraction_digits: usize, locale: &str) -> String {
+    format_with(value, fraction_digits, &Conventions::for_locale(locale))
+}
+
+/// Format an amount of money, placing the currency `symbol` where `locale`
+/// expects it.
+///
+/// The amount is always shown with two fraction digits.
+///
+/// ```
+/// use druid::format::format_currency;
+///
+/// assert_eq!(format_currency(-3.5, "$", "en-US"), "-$3.50");
+/// assert_eq!(format_currency(1234.5, "€", "de-DE"), "1.234,50\u{a0}€");
+/// ```
+pub fn format_currency(value: f64, symbol: &str, locale: &str) -> String {
+    let conventions = Conventions::for_locale(locale);
+    let amount = format_with(value.abs(), 2, &conventions);
+    let sign = if value < 0.0 { "-" } else { "" };
+    match conventions.currency {
+        CurrencyPosition::Prefix => format!("{}{}{}", sign, symbol, amount),
+        CurrencyPosition::PrefixSpaced => format!("{}{}{}{}", sign, symbol, NBSP, amount),
+        CurrencyPosition::Suffix => format!("{}{}{}{}", sign, amount, NBSP, symbol),
+    }
+}
+
+/// Format a date using the numeric short date style of `locale`.
+///
+/// ```
+/// use druid::format::{format_date, Date};
+///
+/// let date = Date::new(2020, 3, 14);
+/// assert_eq!(format_date(date, "en-US"), "3/14/2020");
+/// assert_eq!(format_date(date, "en-GB"), "14/03/2020");
+/// assert_eq!(format_date(date, "ja-JP"), "2020/03/14");
+/// ```
+pub fn format_date(date: Date, locale: &str) -> String {
+    let conventions = Conventions::for_locale(locale);
+    let (month, day) = if conventions.pad_date {
+        (format!("{:02}", date.month), format!("{:02}", date.day))
+    } else {
+        (date.month.to_string(), date.day.to_string())
+    };
+    let year = date.year.to_string();
+    let sep = conventions.date_separator;
+    match conventions.date_order {
+        DateOrder::DayMonthYear => [day, month, year].join(sep),
+        DateOrder::MonthDayYear => [month, day, year].join(sep),
+        DateOrder::YearMonthDay => [year, month, day].join(sep),
+    }
+}
+
+/// Format a duration as hours, minutes and seconds, using the time
+/// separator of `locale`.
+///
+/// The hours are omitted if the duration is shorter than an hour.
+///
+/// ```
+/// use std::time::Duration;
+/// use druid::format::format_duration;
+///
+/// assert_eq!(format_duration(Duration::from_secs(3723), "en-US"), "1:02:03");
+/// assert_eq!(format_duration(Duration::from_secs(65), "fi-FI"), "1.05");
+/// ```
+pub fn format_duration(duration: Duration, locale: &str) -> String {
+    let sep = Conventions::for_locale(locale).time_separator;
+    let total = duration.as_secs();
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{}{}{:02}{}{:02}", hours, sep, minutes, sep, seconds)
+    } else {
+        format!("{}{}{:02}", minutes, sep, seconds)
+    }
+}
+
+/// A [`Label`] text adapter that formats a number in the current locale.
+///
+/// See [`format_number`].
+///
+/// [`Label`]: ../widget/struct.Label.html
+/// [`format_number`]: fn.format_number.html
+pub fn number(fraction_digits: usize) -> impl Fn(&f64, &Env) -> String {
+    move |value, env| format_number(*value, fraction_digits, &env.locale())
+}
+
+/// A [`Label`] text adapter that formats an amount of money in the current
+/// locale.
+///
+/// See [`format_currency`].
+///
+/// [`Label`]: ../widget/struct.Label.html
+/// [`format_currency`]: fn.format_currency.html
+pub fn currency(symbol: impl Into<String>) -> impl Fn(&f64, &Env) -> String {
+    let symbol = symbol.into();
+    move |value, env| format_currency(*value, &symbol, &env.locale())
+}
+
+/// A [`Label`] text adapter that formats a [`Date`] in the current locale.
+///
+/// See [`format_date`].
+///
+/// [`Label`]: ../widget/struct.Label.html
+/// [`Date`]: struct.Date.html
+/// [`format_date`]: fn.format_date.html
+pub fn date() -> impl Fn(&Date, &Env) -> String {
+    |value, env| format_date(*value, &env.locale())
+}
+
+/// A [`Label`] text adapter that formats a `Duration` in the current locale.
+///
+/// See [`format_duration`].
+///
+/// [`Label`]: ../widget/struct.Label.html
+/// [`format_duration`]: fn.format_duration.html
+pub fn duration() -> impl Fn(&Duration, &Env) -> String {
+    |value, env| format_duration(*value, &env.locale())
+}
+
+fn format_with(value: f64, fraction_digits: usize, conventions: &Conventions) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    let digits = format!("{:.*}", fraction_digits, value.abs());
+    let (integer, fraction) = match digits.find('.') {
+        Some(idx) => (&digits[..idx], &digits[idx + 1..]),
+        None => (digits.as_str(), ""),
+    };
+
+    let mut out = String::new();
+    // don't show a minus sign for values that round to zero
+    if value < 0.0 && digits.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+        out.push('-');
+    }
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            out.push_str(conventions.group);
+        }
+        out.push(c);
+    }
+    if !fraction.is_empty() {
+        out.push(conventions.decimal);
+        out.push_str(fraction);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(format_number(0.0, 0, "en-US"), "0");
+        assert_eq!(format_number(999.0, 0, "en-US"), "999");
+        assert_eq!(format_number(-1234567.891, 1, "en-US"), "-1,234,567.9");
+        assert_eq!(format_number(-0.001, 2, "en-US"), "0.00");
+        assert_eq!(
+            format_number(1234567.0, 0, "fr-FR"),
+            "1\u{202f}234\u{202f}567"
+        );
+        assert_eq!(format_number(1234.5, 1, "de-CH"), "1’234.5");
+        assert_eq!(format_number(f64::NAN, 2, "en-US"), "NaN");
+    }
+
+    #[test]
+    fn locale_fallback() {
+        // region-less and unknown tags fall back to the language, then en-US
+        assert_eq!(format_number(1234.5, 1, "de"), "1.234,5");
+        assert_eq!(format_number(1234.5, 1, "pt_BR"), "1.234,5");
+        assert_eq!(format_number(1234.5, 1, "xx-YY"), "1,234.5");
+        assert_eq!(
+            format_date(Date::new(2020, 1, 2), "en-Latn-AU"),
+            "02/01/2020"
+        );
+    }
+
+    #[test]
+    fn currencies() {
+        assert_eq!(format_currency(1234.5, "$", "en-US"), "$1,234.50");
+        assert_eq!(format_currency(-1.0, "€", "nl-NL"), "-€\u{a0}1,00");
+        assert_eq!(format_currency(2.0, "€", "fr-FR"), "2,00\u{a0}€");
+    }
+
+    #[test]
+    fn dates() {
+        let date = Date::new(2020, 3, 4);
+        assert_eq!(format_date(date, "de-DE"), "04.03.2020");
+        assert_eq!(format_date(date, "sv-SE"), "2020-03-04");
+        assert_eq!(format_date(date, "nl-NL"), "4-3-2020");
+        assert_eq!(format_date(date, "en-CA"), "2020-03-04");
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(Duration::from_secs(5), "en-US"), "0:05");
+        assert_eq!(
+            format_duration(Duration::from_secs(36000), "de-DE"),
+            "10:00:00"
+        );
+    }
+}
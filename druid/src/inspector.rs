@@ -0,0 +1,596 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Describing the widget tree, and an overlay for inspecting it in a running
+//! window.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::core::BaseState;
+use crate::kurbo::{Point, Rect, Size, Vec2};
+use crate::piet::{Color, FontBuilder, Piet, RenderContext, Text, TextLayout, TextLayoutBuilder};
+use crate::shell::KeyCode;
+use crate::widget::AccessibilityInfo;
+use crate::{theme, BoxConstraints, Env, KeyEvent, WidgetId};
+
+const HIGHLIGHT_FILL: Color = Color::rgba8(0x40, 0x90, 0xff, 0x40);
+const HIGHLIGHT_STROKE: Color = Color::rgb8(0x40, 0x90, 0xff);
+const PANEL_BACKGROUND: Color = Color::rgba8(0x10, 0x10, 0x10, 0xe0);
+const PANEL_TEXT: Color = Color::rgb8(0xf0, 0xf0, 0xf0);
+const PANEL_FONT_SIZE: f64 = 12.0;
+const PANEL_PADDING: f64 = 6.0;
+
+/// A description of every widget in a window.
+///
+/// This can be printed, as an indented outline of the tree, or converted to
+/// JSON with [`to_json`], for use in tests and bug reports. It is returned
+/// by [`Harness::widget_tree`], and logged by a window when it receives the
+/// [`DUMP_WIDGET_TREE`] command.
+///
+/// [`to_json`]: #method.to_json
+/// [`Harness::widget_tree`]: testing/struct.Harness.html#method.widget_tree
+/// [`DUMP_WIDGET_TREE`]: commands/constant.DUMP_WIDGET_TREE.html
+#[derive(Debug, Clone, Default)]
+pub struct WidgetTree {
+    widgets: Vec<WidgetInfo>,
+}
+
+/// What is known about one widget in the tree.
+#[derive(Debug, Clone)]
+pub struct WidgetInfo {
+    /// The widget's id.
+    pub id: WidgetId,
+    /// The full name of the widget's type, such as
+    /// `druid::widget::Padding<u32>`.
+    pub type_name: &'static str,
+    /// The number of ancestors; the root has depth zero.
+    pub depth: usize,
+    /// The layout rect, in window coordinates.
+    pub window_rect: Rect,
+    /// The constraints of the last layout pass, if there has been one.
+    pub constraints: Option<BoxConstraints>,
+    /// Whether the mouse is over the widget.
+    pub is_hot: bool,
+    /// Whether the widget is active, such as a button being pressed.
+    pub is_active: bool,
+    /// Whether the widget has keyboard focus.
+    pub is_focused: bool,
+    /// The values in this widget's `Env` that differ from the window's, as
+    /// the key and the debug output of the value.
+    pub env_overrides: Vec<(String, String)>,
+    /// What the widget tells assistive technology about itself, if anything,
+    /// such as with [`Accessible`] or an [`Image`]'s alt text.
+    ///
+    /// [`Accessible`]: widget/struct.Accessible.html
+    /// [`Image`]: widget/struct.Image.html
+    pub accessibility: Option<AccessibilityInfo>,
+}
+
+/// Collects a [`WidgetInfo`] for each widget, in depth-first order, as it is
+/// passed down the tree in a `LifeCycle::DebugCollectTree` event.
+///
+/// [`WidgetInfo`]: struct.WidgetInfo.html
+#[derive(Clone, Default)]
+pub struct TreeCollector(Rc<RefCell<Collecting>>);
+
+#[derive(Default)]
+struct Collecting {
+    widgets: Vec<WidgetInfo>,
+    /// The window origin of each widget we are currently inside of.
+    origins: Vec<Point>,
+    /// The index in `widgets` of each widget we are currently inside of.
+    open: Vec<usize>,
+    root_env: Option<Env>,
+}
+
+/// The state of a window's inspector, while it is open.
+#[derive(Default)]
+pub(crate) struct Inspector {
+    /// The widgets as of the last paint.
+    widgets: Vec<WidgetInfo>,
+    selected: Option<WidgetId>,
+}
+
+impl TreeCollector {
+    /// Called by a `WidgetPod` before passing the event to its widget.
+    pub(crate) fn enter(&self, state: &BaseState, env: &Env) {
+        let mut collecting = self.0.borrow_mut();
+        let root_env = collecting.root_env.get_or_insert_with(|| env.clone());
+        let env_overrides = env
+            .overrides(root_env)
+            .into_iter()
+            .map(|(key, value)| (key, format!("{:?}", value)))
+            .collect();
+        let parent_origin = collecting.origins.last().copied().unwrap_or(Point::ORIGIN);
+        let window_rect = state.layout_rect + parent_origin.to_vec2();
+        let info = WidgetInfo {
+            id: state.id,
+            type_name: state.type_name,
+            depth: collecting.origins.len(),
+            window_rect,
+            constraints: state.last_constraints,
+            is_hot: state.is_hot,
+            is_active: state.is_active,
+            is_focused: false,
+            env_overrides,
+            accessibility: None,
+        };
+        let index = collecting.widgets.len();
+        collecting.widgets.push(info);
+        collecting.origins.push(window_rect.origin());
+        collecting.open.push(index);
+    }
+
+    /// Called by a `WidgetPod` once its widget has passed the event on.
+    pub(crate) fn exit(&self) {
+        let mut collecting = self.0.borrow_mut();
+        collecting.origins.pop();
+        collecting.open.pop();
+    }
+
+    /// Called by a widget, while the event is passed to it, to give its
+    /// accessibility description.
+    pub(crate) fn describe(&self, info: AccessibilityInfo) {
+        let mut collecting = self.0.borrow_mut();
+        if let Some(&index) = collecting.open.last() {
+            collecting.widgets[index].accessibility = Some(info);
+        }
+    }
+
+    /// The tree that was collected, where the widget with id `focus` has
+    /// keyboard focus.
+    pub(crate) fn tree(&self, focus: Option<WidgetId>) -> WidgetTree {
+        let mut widgets = self.0.borrow().widgets.clone();
+        for widget in widgets.iter_mut() {
+            widget.is_focused = Some(widget.id) == focus;
+        }
+        WidgetTree { widgets }
+    }
+}
+
+impl WidgetTree {
+    /// The widgets, in depth-first order, starting with the root.
+    pub fn widgets(&self) -> &[WidgetInfo] {
+        &self.widgets
+    }
+
+    /// The widget with this id, if it is in the tree.
+    pub fn get(&self, id: WidgetId) -> Option<&WidgetInfo> {
+        self.widgets.iter().find(|w| w.id == id)
+    }
+
+    /// The tree as JSON, where each widget is an object with its `children`
+    /// in an array.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        let mut iter = self.widgets.iter().peekable();
+        match iter.next() {
+            Some(root) => write_json(&mut out, root, &mut iter),
+            None => out.push_str("null"),
+        }
+        out
+    }
+}
+
+/// Write `widget` and its descendants, which are the following widgets that
+/// are deeper than it.
+fn write_json<'a>(
+    out: &mut String,
+    widget: &WidgetInfo,
+    rest: &mut std::iter::Peekable<impl Iterator<Item = &'a WidgetInfo>>,
+) {
+    let rect = widget.window_rect;
+    out.push_str(&format!(
+        "{{\"id\":{},\"type\":{},\"rect\":[{},{},{},{}],",
+        widget.id.to_raw(),
+        json_string(widget.type_name),
+        rect.x0,
+        rect.y0,
+        rect.width(),
+        rect.height()
+    ));
+    out.push_str(&format!(
+        "\"hot\":{},\"active\":{},\"focused\":{},",
+        widget.is_hot, widget.is_active, widget.is_focused
+    ));
+    if let Some(info) = &widget.accessibility {
+        let optional = |text: &Option<String>| match text {
+            Some(text) => json_string(text),
+            None => "null".to_string(),
+        };
+        out.push_str(&format!(
+            "\"accessibility\":{{\"role\":\"{:?}\",\"label\":{},\"hint\":{}}},",
+            info.role,
+            optional(&info.label),
+            optional(&info.hint)
+        ));
+    }
+    out.push_str("\"children\":[");
+    let mut first = true;
+    while let Some(child) = rest.next_if(|w| w.depth > widget.depth) {
+        if !first {
+            out.push(',');
+        }
+        first = false;
+        write_json(out, child, rest);
+    }
+    out.push_str("]}");
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl std::fmt::Display for WidgetTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for widget in self.widgets.iter() {
+            let rect = widget.window_rect;
+            write!(
+                f,
+                "{:indent$}{} #{} ({}, {}) {}×{}",
+                "",
+                short_type_name(widget.type_name),
+                widget.id.to_raw(),
+                rect.x0,
+                rect.y0,
+                rect.width(),
+                rect.height(),
+                indent = widget.depth * 2
+            )?;
+            for (flag, name) in &[
+                (widget.is_hot, "hot"),
+                (widget.is_active, "active"),
+                (widget.is_focused, "focused"),
+            ] {
+                if *flag {
+                    write!(f, " {}", name)?;
+                }
+            }
+            if let Some(info) = &widget.accessibility {
+                write!(f, " [{:?}", info.role)?;
+                if let Some(label) = &info.label {
+                    write!(f, " {:?}", label)?;
+                }
+                write!(f, "]")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for TreeCollector {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "TreeCollector({} widgets)",
+            self.0.borrow().widgets.len()
+        )
+    }
+}
+
+impl Inspector {
+    /// Replace the widgets with those from a new traversal of the tree.
+    pub(crate) fn set_tree(&mut self, tree: WidgetTree) {
+        let widgets = tree.widgets;
+        if !widgets.iter().any(|w| Some(w.id) == self.selected) {
+            self.selected = None;
+        }
+        self.widgets = widgets;
+    }
+
+    /// Select the innermost widget under the mouse. Returns `true` if the
+    /// selection changed.
+    pub(crate) fn mouse_moved(&mut self, pos: Point) -> bool {
+        let mut best: Option<&WidgetInfo> = None;
+        for widget in self.widgets.iter() {
+            if widget.window_rect.contains(pos)
+                && best.map(|b| widget.depth >= b.depth).unwrap_or(true)
+            {
+                best = Some(widget);
+            }
+        }
+        let selected = best.map(|w| w.id);
+        let changed = selected != self.selected;
+        self.selected = selected;
+        changed
+    }
+
+    /// Walk the tree with the arrow keys: up and down move to the parent and
+    /// the first child, left and right to the siblings.
+    ///
+    /// Returns `true` if the key was used.
+    pub(crate) fn key_down(&mut self, key: &KeyEvent) -> bool {
+        let idx = match self.selected_index() {
+            Some(idx) => idx,
+            None => {
+                // start from the root
+                self.selected = self.widgets.first().map(|w| w.id);
+                return self.selected.is_some();
+            }
+        };
+        let depth = self.widgets[idx].depth;
+        let new_idx = match key.key_code {
+            KeyCode::ArrowUp => self.widgets[..idx].iter().rposition(|w| w.depth < depth),
+            KeyCode::ArrowDown => self
+                .widgets
+                .get(idx + 1)
+                .filter(|w| w.depth == depth + 1)
+                .map(|_| idx + 1),
+            KeyCode::ArrowLeft => self.widgets[..idx]
+                .iter()
+                .rposition(|w| w.depth <= depth)
+                .filter(|&i| self.widgets[i].depth == depth),
+            KeyCode::ArrowRight => self.widgets[idx + 1..]
+                .iter()
+                .position(|w| w.depth <= depth)
+                .map(|i| i + idx + 1)
+                .filter(|&i| self.widgets[i].depth == depth),
+            _ => return false,
+        };
+        if let Some(new_idx) = new_idx {
+            self.selected = Some(self.widgets[new_idx].id);
+        }
+        true
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        let selected = self.selected?;
+        self.widgets.iter().position(|w| w.id == selected)
+    }
+
+    /// Paint the highlight and the description of the selected widget.
+    pub(crate) fn paint(&self, piet: &mut Piet, window_size: Size, env: &Env) {
+        let widget = match self.selected_index() {
+            Some(idx) => &self.widgets[idx],
+            None => return,
+        };
+        piet.fill(widget.window_rect, &HIGHLIGHT_FILL);
+        piet.stroke(widget.window_rect, &HIGHLIGHT_STROKE, 1.0);
+
+        // in the bottom left corner
+        paint_panel(piet, &describe(widget), env, |panel| {
+            Point::new(0.0, window_size.height - panel.height)
+        });
+    }
+}
+
+/// Paint lines of text on a dark background, for debugging overlays.
+///
+/// `place` is given the size of the panel, and returns its origin.
+pub(crate) fn paint_panel(
+    piet: &mut Piet,
+    lines: &[String],
+    env: &Env,
+    place: impl FnOnce(Size) -> Point,
+) {
+    let font = match piet
+        .text()
+        .new_font_by_name(env.get(theme::FONT_NAME), PANEL_FONT_SIZE)
+        .build()
+    {
+        Ok(font) => font,
+        Err(e) => {
+            log::error!("debug overlay failed to load font: {:?}", e);
+            return;
+        }
+    };
+    let layouts: Vec<_> = lines
+        .iter()
+        .filter_map(|line| piet.text().new_text_layout(&font, line).build().ok())
+        .collect();
+
+    let line_height = PANEL_FONT_SIZE * 1.25;
+    let width = layouts.iter().map(|l| l.width()).fold(0.0, f64::max);
+    let height = line_height * layouts.len() as f64;
+    let size = Size::new(width + PANEL_PADDING * 2.0, height + PANEL_PADDING * 2.0);
+    let panel = Rect::from_origin_size(place(size), size);
+    piet.fill(panel, &PANEL_BACKGROUND);
+    for (i, layout) in layouts.iter().enumerate() {
+        let baseline = panel.origin()
+            + Vec2::new(
+                PANEL_PADDING,
+                PANEL_PADDING + line_height * (i as f64 + 0.8),
+            );
+        piet.draw_text(layout, baseline, &PANEL_TEXT);
+    }
+}
+
+/// The lines of text describing a widget.
+fn describe(widget: &WidgetInfo) -> Vec<String> {
+    let rect = widget.window_rect;
+    let mut lines = vec![
+        format!(
+            "{} ({:?})",
+            short_type_name(widget.type_name),
+            widget.id.to_raw()
+        ),
+        format!(
+            "origin ({:.1}, {:.1})  size {:.1}×{:.1}",
+            rect.x0,
+            rect.y0,
+            rect.width(),
+            rect.height()
+        ),
+    ];
+    if let Some(bc) = widget.constraints {
+        let (min, max) = (bc.min(), bc.max());
+        lines.push(format!(
+            "constraints {:.1}×{:.1} to {:.1}×{:.1}",
+            min.width, min.height, max.width, max.height
+        ));
+    }
+    let mut flags = Vec::new();
+    if widget.is_hot {
+        flags.push("hot");
+    }
+    if widget.is_active {
+        flags.push("active");
+    }
+    if widget.is_focused {
+        flags.push("focused");
+    }
+    if !flags.is_empty() {
+        lines.push(flags.join(", "));
+    }
+    if let Some(info) = &widget.accessibility {
+        lines.push(format!("role {:?}", info.role));
+        if let Some(label) = &info.label {
+            lines.push(format!("label {:?}", label));
+        }
+        if let Some(hint) = &info.hint {
+            lines.push(format!("hint {:?}", hint));
+        }
+    }
+    for (key, value) in widget.env_overrides.iter() {
+        lines.push(format!("{} = {}", key, value));
+    }
+    lines
+}
+
+/// Remove the module paths from a type name, so that
+/// `druid::widget::Padding<u32>` becomes `Padding<u32>`.
+pub(crate) fn short_type_name(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    let mut segment = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            short.push_str(segment.rsplit("::").next().unwrap_or(""));
+            segment.clear();
+            short.push(c);
+        }
+    }
+    short.push_str(segment.rsplit("::").next().unwrap_or(""));
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::KeyModifiers;
+
+    fn info(depth: usize, rect: Rect) -> WidgetInfo {
+        WidgetInfo {
+            id: WidgetId::next(),
+            type_name: "",
+            depth,
+            window_rect: rect,
+            constraints: None,
+            is_hot: false,
+            is_active: false,
+            is_focused: false,
+            env_overrides: Vec::new(),
+            accessibility: None,
+        }
+    }
+
+    #[test]
+    fn dump_tree() {
+        let mut root = info(0, Rect::new(0., 0., 100., 100.));
+        root.type_name = "druid::widget::Flex<u32>";
+        let mut child = info(1, Rect::new(0., 0., 50., 20.));
+        child.type_name = "druid::widget::Label<u32>";
+        child.is_focused = true;
+        let mut sibling = info(1, Rect::new(50., 0., 100., 20.));
+        sibling.type_name = "druid::widget::Button<u32>";
+        let ids = [root.id, child.id, sibling.id].map(|id| id.to_raw());
+        let tree = WidgetTree {
+            widgets: vec![root, child, sibling],
+        };
+
+        assert_eq!(
+            tree.to_string(),
+            format!(
+                "Flex<u32> #{} (0, 0) 100×100\n  Label<u32> #{} (0, 0) 50×20 focused\n  \
+                 Button<u32> #{} (50, 0) 50×20\n",
+                ids[0], ids[1], ids[2]
+            )
+        );
+        let child_json = |id, ty: &str, x, focused| {
+            format!(
+                "{{\"id\":{},\"type\":\"{}\",\"rect\":[{},0,50,20],\"hot\":false,\
+                 \"active\":false,\"focused\":{},\"children\":[]}}",
+                id, ty, x, focused
+            )
+        };
+        assert_eq!(
+            tree.to_json(),
+            format!(
+                "{{\"id\":{},\"type\":\"druid::widget::Flex<u32>\",\"rect\":[0,0,100,100],\
+                 \"hot\":false,\"active\":false,\"focused\":false,\"children\":[{},{}]}}",
+                ids[0],
+                child_json(ids[1], "druid::widget::Label<u32>", 0, true),
+                child_json(ids[2], "druid::widget::Button<u32>", 50, false)
+            )
+        );
+    }
+
+    #[test]
+    fn type_names_are_shortened() {
+        assert_eq!(
+            short_type_name("druid::widget::Padding<alloc::boxed::Box<dyn druid::Widget<u32>>>"),
+            "Padding<Box<dyn Widget<u32>>>"
+        );
+        assert_eq!(short_type_name("(u32, f64)"), "(u32, f64)");
+    }
+
+    #[test]
+    fn select_and_walk_tree() {
+        let root = info(0, Rect::new(0., 0., 100., 100.));
+        let left = info(1, Rect::new(0., 0., 50., 100.));
+        let left_child = info(2, Rect::new(10., 10., 40., 40.));
+        let right = info(1, Rect::new(50., 0., 100., 100.));
+        let ids: Vec<_> = [&root, &left, &left_child, &right]
+            .iter()
+            .map(|w| w.id)
+            .collect();
+        let mut inspector = Inspector::default();
+        inspector.set_tree(WidgetTree {
+            widgets: vec![root, left, left_child, right],
+        });
+
+        assert!(inspector.mouse_moved(Point::new(20., 20.)));
+        assert_eq!(inspector.selected, Some(ids[2]));
+        assert!(!inspector.mouse_moved(Point::new(21., 21.)));
+
+        let key = |code| KeyEvent::for_test(KeyModifiers::default(), "", code);
+        inspector.key_down(&key(KeyCode::ArrowUp));
+        assert_eq!(inspector.selected, Some(ids[1]));
+        inspector.key_down(&key(KeyCode::ArrowRight));
+        assert_eq!(inspector.selected, Some(ids[3]));
+        // no further sibling, or child
+        inspector.key_down(&key(KeyCode::ArrowRight));
+        inspector.key_down(&key(KeyCode::ArrowDown));
+        assert_eq!(inspector.selected, Some(ids[3]));
+        inspector.key_down(&key(KeyCode::ArrowLeft));
+        inspector.key_down(&key(KeyCode::ArrowDown));
+        assert_eq!(inspector.selected, Some(ids[2]));
+        inspector.key_down(&key(KeyCode::ArrowUp));
+        inspector.key_down(&key(KeyCode::ArrowUp));
+        assert_eq!(inspector.selected, Some(ids[0]));
+    }
+}
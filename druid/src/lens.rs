@@ -53,6 +53,7 @@ use std::sync::Arc;
 pub use druid_derive::Lens;
 
 use crate::kurbo::Size;
+use crate::widget::Axis;
 use crate::{
     BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
     UpdateCtx, Widget, WidgetId,
@@ -283,6 +284,34 @@ where
             .with(data, |data| inner.layout(ctx, bc, data, env))
     }
 
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let inner = &mut self.inner;
+        self.lens.with(data, |data| {
+            inner.min_intrinsic(ctx, axis, cross, data, env)
+        })
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let inner = &mut self.inner;
+        self.lens.with(data, |data| {
+            inner.max_intrinsic(ctx, axis, cross, data, env)
+        })
+    }
+
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
         let inner = &mut self.inner;
         self.lens
@@ -19,7 +19,11 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 use druid_shell as shell;
+#[cfg(feature = "raw-win-handle")]
+pub use druid_shell::raw_window_handle;
 pub use druid_shell::{kurbo, piet};
+#[cfg(feature = "tracing")]
+pub use tracing;
 
 mod app;
 mod app_delegate;
@@ -32,14 +36,27 @@ mod data;
 mod env;
 mod event;
 mod ext_event;
+pub mod format;
+mod inspector;
 pub mod lens;
 mod localization;
 mod menu;
 mod mouse;
+mod perf;
+#[cfg(feature = "persistence")]
+mod persist;
+mod recording;
+mod task;
+pub mod testing;
 #[cfg(test)]
 mod tests;
 mod text;
 pub mod theme;
+#[cfg(feature = "time-travel")]
+mod time_travel;
+mod touch_bar;
+mod trace;
+mod undo;
 pub mod widget;
 mod win_handler;
 mod window;
@@ -49,25 +66,31 @@ pub use kurbo::{Affine, Insets, Point, Rect, Size, Vec2};
 pub use piet::{Color, LinearGradient, PaintBrush, RadialGradient, RenderContext, UnitPoint};
 // these are the types from shell that we expose; others we only use internally.
 pub use shell::{
-    Application, Clipboard, ClipboardFormat, Cursor, Error as PlatformError, FileDialogOptions,
-    FileInfo, FileSpec, FormatId, HotKey, KeyCode, KeyEvent, KeyModifiers, MouseButton, RawMods,
-    SysMods, Text, TimerToken, WindowHandle,
+    Application, Clipboard, ClipboardFormat, Cursor, DragData, DropAction, Error as PlatformError,
+    FileDialogOptions, FileInfo, FileSpec, FormatId, HotKey, KeyCode, KeyEvent, KeyModifiers,
+    ModifierNames, Monitor, MouseButton, RawMods, Screen, SysMods, Text, TimerToken, WindowHandle,
 };
 
 pub use crate::core::{BoxedWidget, WidgetPod};
 pub use app::{AppLauncher, WindowDesc};
 pub use app_delegate::{AppDelegate, DelegateCtx};
 pub use box_constraints::BoxConstraints;
-pub use command::{sys as commands, Command, Selector, Target};
+pub use command::{sys as commands, Command, GlobalHotKey, Selector, Target};
 pub use contexts::{EventCtx, LayoutCtx, LifeCycleCtx, PaintCtx, UpdateCtx};
 pub use data::Data;
 pub use env::{Env, Key, Value};
-pub use event::{Event, LifeCycle, WheelEvent};
+pub use event::{DragEvent, DropEvent, Event, LifeCycle, WheelEvent};
 pub use ext_event::{ExtEventError, ExtEventSink};
+pub use inspector::{WidgetInfo, WidgetTree};
 pub use lens::{Lens, LensExt, LensWrap};
 pub use localization::LocalizedString;
-pub use menu::{sys as platform_menus, ContextMenu, MenuDesc, MenuItem};
-pub use mouse::MouseEvent;
+pub use menu::{sys as platform_menus, ContextMenu, MenuDesc, MenuItem, MenuItemUpdate};
+pub use mouse::{CursorDesc, MouseEvent};
+pub use perf::CountingAllocator;
+pub use recording::{ParseRecordingError, Recording};
+pub use task::{BackgroundTask, TaskHandle};
+pub use text::{EditAction, KeyBindings, Spellchecker, WordList};
+pub use touch_bar::TouchBarDesc;
 pub use widget::{Widget, WidgetId};
 pub use win_handler::DruidHandler;
 pub use window::{Window, WindowId};
@@ -22,17 +22,30 @@
 //! You construct a [`LocalizedString`] with a key, which identifies a 'message'
 //! in your `.flt` files. If your string requires arguments, you supply it with
 //! closures that can extract those arguments from the current [`Env`] and
-//! [`Data`].
+//! [`Data`], or with [`Lens`]es into the [`Data`].
 //!
 //! At runtime, you resolve your [`LocalizedString`] into an actual string,
-//! passing it the current [`Env`] and [`Data`].
+//! passing it the current [`Env`] and [`Data`]. The string is only formatted
+//! again when the locale or the value of one of its arguments changes.
 //!
+//! Numeric arguments are passed to Fluent as numbers, so messages can use
+//! [select expressions] to choose the correct plural form for the current
+//! locale:
+//!
+//! ```ftl
+//! unread-count = { $count ->
+//!     [one] One unread message
+//!    *[other] { $count } unread messages
+//! }
+//! ```
 //!
 //! [Fluent]: https://projectfluent.org
 //! [fluent-rs]: https://github.com/projectfluent/fluent-rs
+//! [select expressions]: https://projectfluent.org/fluent/guide/selectors.html
 //! [`LocalizedString`]: struct.LocalizedString.html
 //! [`Env`]: struct.Env.html
 //! [`Data`]: trait.Data.html
+//! [`Lens`]: trait.Lens.html
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -42,6 +55,7 @@ use log::{debug, error, warn};
 
 use crate::data::Data;
 use crate::env::Env;
+use crate::lens::Lens;
 use crate::shell::Application;
 
 use fluent_bundle::{
@@ -49,7 +63,7 @@ use fluent_bundle::{
 };
 use fluent_langneg::{negotiate_languages, NegotiationStrategy};
 use fluent_syntax::ast::Pattern as FluentPattern;
-use unic_langid::LanguageIdentifier;
+use unic_langid::{CharacterDirection, LanguageIdentifier};
 
 // Localization looks for string files in druid/resources, but this path is hardcoded;
 // it will only work if you're running an example from the druid/ directory.
@@ -58,10 +72,7 @@ use unic_langid::LanguageIdentifier;
 static FALLBACK_STRINGS: &str = include_str!("../resources/i18n/en-US/builtin.ftl");
 
 /// Provides access to the localization strings for the current locale.
-#[allow(dead_code)]
 pub(crate) struct L10nManager {
-    // these two are not currently used; will be used when we let the user
-    // add additional localization files.
     res_mgr: ResourceManager,
     resources: Vec<String>,
     current_bundle: BundleStack,
@@ -69,6 +80,7 @@ pub(crate) struct L10nManager {
 }
 
 /// Manages a collection of localization files.
+#[derive(Clone)]
 struct ResourceManager {
     resources: HashMap<String, Arc<FluentResource>>,
     locales: Vec<LanguageIdentifier>,
@@ -76,7 +88,6 @@ struct ResourceManager {
     path_scheme: String,
 }
 
-//NOTE: instead of a closure, at some point we can use something like a lens for this.
 //TODO: this is an Arc so that it can be clone, which is a bound on things like `Menu`.
 /// A closure that generates a localization value.
 type ArgClosure<T> = Arc<dyn Fn(&T, &Env) -> FluentValue<'static> + 'static>;
@@ -96,6 +107,7 @@ pub struct LocalizedString<T> {
     args: Option<Vec<(&'static str, ArgSource<T>)>>,
     resolved: Option<String>,
     resolved_lang: Option<LanguageIdentifier>,
+    resolved_args: Option<Vec<FluentValue<'static>>>,
 }
 
 /// A stack of localization resources, used for fallback.
@@ -156,7 +168,8 @@ impl ResourceManager {
         debug!("resolved: {}", PrintLocales(resolved_locales.as_slice()));
         let mut stack = Vec::new();
         for locale in &resolved_locales {
-            let mut bundle = FluentBundle::new(&resolved_locales);
+            // each bundle uses its own locale's plural rules
+            let mut bundle = FluentBundle::new(std::slice::from_ref(locale));
             for res_id in resource_ids {
                 let res = self.get_resource(&res_id, &locale.to_string());
                 bundle.add_resource(res).unwrap();
@@ -288,7 +301,34 @@ impl L10nManager {
             Some(result)
         }
     }
-    //TODO: handle locale change
+
+    /// Create a new manager for `locale`, sharing any resources that have
+    /// already been loaded by this one.
+    pub(crate) fn with_locale(&self, locale: LanguageIdentifier) -> L10nManager {
+        let mut res_mgr = self.res_mgr.clone();
+        let current_bundle = res_mgr.get_bundle(&locale, &self.resources);
+        L10nManager {
+            res_mgr,
+            current_bundle,
+            resources: self.resources.clone(),
+            current_locale: locale,
+        }
+    }
+
+    /// The locale that strings are currently resolved against.
+    pub(crate) fn current_locale(&self) -> &LanguageIdentifier {
+        &self.current_locale
+    }
+
+    /// Returns `true` if the current locale is written right-to-left.
+    pub(crate) fn is_rtl(&self) -> bool {
+        self.current_locale.get_character_direction() == CharacterDirection::RTL
+    }
+
+    /// The locales for which localization resources were found.
+    pub(crate) fn available_locales(&self) -> &[LanguageIdentifier] {
+        &self.res_mgr.locales
+    }
 }
 
 impl<T> LocalizedString<T> {
@@ -300,6 +340,7 @@ impl<T> LocalizedString<T> {
             placeholder: None,
             resolved: None,
             resolved_lang: None,
+            resolved_args: None,
         }
     }
 
@@ -339,24 +380,62 @@ impl<T: Data> LocalizedString<T> {
         self
     }
 
+    /// Add a named argument whose value is read from the data through a [`Lens`].
+    ///
+    /// Numeric values are passed to Fluent as numbers, and can be used to
+    /// select a plural form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use druid::{lens, LocalizedString};
+    ///
+    /// struct Inbox {
+    ///     unread: u32,
+    /// }
+    ///
+    /// let text: LocalizedString<Inbox> =
+    ///     LocalizedString::new("unread-count").with_lens_arg("count", lens!(Inbox, unread));
+    /// ```
+    ///
+    /// [`Lens`]: trait.Lens.html
+    pub fn with_lens_arg<U, L>(self, key: &'static str, lens: L) -> Self
+    where
+        U: Clone + Into<FluentValue<'static>>,
+        L: Lens<T, U> + 'static,
+    {
+        self.with_arg(key, move |data, _env| {
+            lens.with(data, |value| value.clone().into())
+        })
+    }
+
     /// Lazily compute the localized value for this string based on the provided
     /// environment and data.
     ///
     /// Returns `true` if the current value of the string has changed.
     pub fn resolve<'a>(&'a mut self, data: &T, env: &Env) -> bool {
-        //TODO: this recomputes the string if either the language has changed,
-        //or *anytime* we have arguments. Ideally we would be using a lens
-        //to only recompute when our actual data has changed.
-        if self.args.is_some()
-            || self.resolved_lang.as_ref() != Some(&env.localization_manager().current_locale)
+        let manager = env.localization_manager();
+        let args: Option<Vec<FluentValue<'static>>> = self
+            .args
+            .as_ref()
+            .map(|a| a.iter().map(|(_, v)| (v.0)(data, env)).collect());
+
+        if self.resolved_lang.as_ref() != Some(manager.current_locale())
+            || args != self.resolved_args
         {
-            let args: Option<FluentArgs> = self
-                .args
-                .as_ref()
-                .map(|a| a.iter().map(|(k, v)| (*k, (v.0)(data, env))).collect());
+            let fluent_args: Option<FluentArgs> = match (&self.args, &args) {
+                (Some(keys), Some(values)) => Some(
+                    keys.iter()
+                        .map(|(k, _)| *k)
+                        .zip(values.iter().cloned())
+                        .collect(),
+                ),
+                _ => None,
+            };
 
-            self.resolved_lang = Some(env.localization_manager().current_locale.clone());
-            let next = env.localization_manager().localize(self.key, args.as_ref());
+            self.resolved_lang = Some(manager.current_locale().clone());
+            self.resolved_args = args;
+            let next = manager.localize(self.key, fluent_args.as_ref());
             let result = next != self.resolved;
             self.resolved = next;
             result
@@ -431,4 +510,71 @@ mod tests {
         assert_eq!(resmgr.resolve_locales(cn_hk), vec![en_us.clone()]);
         assert_eq!(resmgr.resolve_locales(pt_pt), vec![en_us.clone()]);
     }
+
+    #[test]
+    fn plural_args() {
+        let en_us: LanguageIdentifier = "en-US".parse().unwrap();
+        let ftl = "items = { $count ->\n    [one] One item\n   *[other] { $count } items\n}\n";
+        let res = FluentResource::try_new(ftl.to_string()).unwrap();
+
+        let mut resources = HashMap::new();
+        resources.insert("test/en-US/test.ftl".to_string(), Arc::new(res));
+        let mut res_mgr = ResourceManager {
+            resources,
+            locales: vec![en_us.clone()],
+            default_locale: en_us.clone(),
+            path_scheme: "test/{locale}/{res_id}".to_string(),
+        };
+        let resources = vec!["test.ftl".to_string()];
+        let current_bundle = res_mgr.get_bundle(&en_us, &resources);
+        let manager = L10nManager {
+            res_mgr,
+            resources,
+            current_bundle,
+            current_locale: en_us,
+        };
+
+        let mut args = FluentArgs::new();
+        args.insert("count", 1.into());
+        assert_eq!(manager.localize("items", &args), Some("One item".into()));
+        args.insert("count", 3.into());
+        assert_eq!(manager.localize("items", &args), Some("3 items".into()));
+    }
+
+    #[test]
+    fn resolve_when_args_change() {
+        let env = Env::default();
+        let mut string: LocalizedString<(u32, u32)> = LocalizedString::new("hello-counter")
+            .with_lens_arg("count", crate::lens!((u32, u32), 0));
+
+        assert!(string.resolve(&(1, 0), &env));
+        assert!(string.localized_str().contains('1'));
+        // nothing the string depends on has changed
+        assert!(!string.resolve(&(1, 5), &env));
+        assert!(string.resolve(&(2, 5), &env));
+        assert!(string.localized_str().contains('2'));
+    }
+
+    #[test]
+    fn change_locale() {
+        let en_us: LanguageIdentifier = "en-US".parse().unwrap();
+        let de_de: LanguageIdentifier = "de-DE".parse().unwrap();
+
+        let manager = L10nManager::new(vec!["builtin.ftl".into()], "./resources/i18n/");
+        assert!(manager.available_locales().contains(&de_de));
+
+        let manager = manager.with_locale(en_us.clone());
+        assert_eq!(manager.current_locale(), &en_us);
+        assert_eq!(
+            manager.localize("common-menu-file-menu", None),
+            Some("File".to_string())
+        );
+
+        let manager = manager.with_locale(de_de.clone());
+        assert_eq!(manager.current_locale(), &de_de);
+        assert_eq!(
+            manager.localize("common-menu-file-menu", None),
+            Some("Datei".to_string())
+        );
+    }
 }
@@ -35,6 +35,14 @@
 //! To change the menu for a window, you issue a [`SET_MENU`] command, the payload
 //! of which should be a new [`MenuDesc`]. The new menu will replace the old menu.
 //!
+//! A menu that depends on the application state, such as a list of recent
+//! files, can instead be given to [`WindowDesc::menu_fn`] as a function of
+//! the data; the menu is rebuilt whenever the data changes.
+//!
+//! Individual items can be enabled, relabeled or hidden in place by issuing an
+//! [`UPDATE_MENU_ITEM`] command with a [`MenuItemUpdate`]. This avoids
+//! rebuilding the menu, which can flicker and closes any open submenus.
+//!
 //! ## The macOS app menu
 //!
 //! On macOS, the main menu belongs to the application, not to the window.
@@ -104,12 +112,59 @@
 //! [`Command` event]: ../enum.Event.html#variant.Command
 //! [`Selector`]: ../struct.Selector.html
 //! [`SET_MENU`]: ../struct.Selector.html#associatedconstant.SET_MENU
+//! [`WindowDesc::menu_fn`]: ../struct.WindowDesc.html#method.menu_fn
+//! [`UPDATE_MENU_ITEM`]: ../commands/constant.UPDATE_MENU_ITEM.html
+//! [`MenuItemUpdate`]: struct.MenuItemUpdate.html
 
 use std::num::NonZeroU32;
+use std::rc::Rc;
 
 use crate::kurbo::Point;
-use crate::shell::{HotKey, KeyCompare, Menu as PlatformMenu, RawMods, SysMods};
-use crate::{commands, Command, Data, Env, KeyCode, LocalizedString, Selector};
+use crate::shell::{
+    Application, FileInfo, HotKey, Icon, KeyCompare, Menu as PlatformMenu, ModifierNames, RawMods,
+    SysMods, WindowHandle,
+};
+use crate::widget::ImageData;
+use crate::{commands, Command, Data, Env, KeyCode, Lens, LocalizedString, Selector};
+
+/// The names of the modifier keys in the current locale.
+fn modifier_names(env: &Env) -> ModifierNames {
+    let l10n = env.localization_manager();
+    let localize = |key, default: String| l10n.localize(key, None).unwrap_or(default);
+    let defaults = ModifierNames::default();
+    ModifierNames {
+        ctrl: localize("hotkey-modifier-ctrl", defaults.ctrl),
+        alt: localize("hotkey-modifier-alt", defaults.alt),
+        shift: localize("hotkey-modifier-shift", defaults.shift),
+        meta: localize("hotkey-modifier-meta", defaults.meta),
+    }
+}
+
+/// The command sent by items made with [`MenuItem::checkbox`] and
+/// [`MenuItem::radio`]; its argument is a [`DataUpdate`].
+///
+/// [`MenuItem::checkbox`]: struct.MenuItem.html#method.checkbox
+/// [`MenuItem::radio`]: struct.MenuItem.html#method.radio
+/// [`DataUpdate`]: struct.DataUpdate.html
+pub(crate) const UPDATE_DATA: Selector = Selector::new("druid-builtin.menu-update-data");
+
+/// A change to the application data, applied when a menu item is chosen.
+pub(crate) struct DataUpdate<T>(pub(crate) Rc<dyn Fn(&mut T)>);
+
+type StatePredicate<T> = dyn Fn(&T, &Env) -> bool;
+
+/// A part of a menu item's state that is derived from the data.
+#[derive(Clone)]
+struct StateFn<T>(Rc<StatePredicate<T>>);
+
+impl<T> std::fmt::Debug for StateFn<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "StateFn")
+    }
+}
+
+/// A function that builds a window's menu from the application state.
+pub(crate) type MenuFn<T> = dyn Fn(&T, &Env) -> MenuDesc<T>;
 
 /// A platform-agnostic description of an application, window, or context
 /// menu.
@@ -118,6 +173,9 @@ pub struct MenuDesc<T> {
     item: MenuItem<T>,
     //TODO: make me an RC if we're cloning regularly?
     items: Vec<MenuEntry<T>>,
+    /// Whether the items are the recent documents, listed again each time
+    /// the menu is built.
+    recent_documents: bool,
 }
 
 /// An item in a menu, which may be a normal item, a submenu, or a separator.
@@ -145,14 +203,67 @@ pub struct MenuItem<T> {
     command: Command,
     hotkey: Option<HotKey>,
     tool_tip: Option<LocalizedString<T>>,
+    icon: Option<MenuIcon>,
     //highlighted: bool,
     selected: bool,
     enabled: bool, // (or state is stored elsewhere)
+    hidden: bool,
+    /// Whether this is one of a group made with `radio`.
+    radio: bool,
+    /// If set, overrides `selected` with a value computed from the data.
+    selected_fn: Option<StateFn<T>>,
+    /// If set, overrides `enabled` with a value computed from the data.
+    enabled_fn: Option<StateFn<T>>,
     /// Identifies the platform object corresponding to this item.
     platform_id: MenuItemId,
 }
 
+/// The icon shown next to a menu item.
+#[derive(Debug, Clone)]
+enum MenuIcon {
+    /// A platform icon name; see [`MenuItem::icon`].
+    ///
+    /// [`MenuItem::icon`]: struct.MenuItem.html#method.icon
+    Named(String),
+    Image(Icon),
+}
+
+/// A change to the items of a window's menu, applied without rebuilding it.
+///
+/// The change applies to every item whose [`Command`] has the given
+/// [`Selector`]. Send it to a window as the argument of an
+/// [`UPDATE_MENU_ITEM`] command. Submenu titles are not affected.
+///
+/// The change is kept if the menu is later rebuilt, for instance because the
+/// locale changed; a menu built by [`WindowDesc::menu_fn`] is replaced
+/// wholesale when the data changes, however, and forgets it.
+///
+/// # Examples
+///
+/// ```
+/// # use druid::{commands, Command, LocalizedString, MenuItemUpdate, Selector};
+/// const PLAY_PAUSE: Selector = Selector::new("my-app.play-pause");
+///
+/// let update = MenuItemUpdate::<()>::new(PLAY_PAUSE).title(LocalizedString::new("Pause"));
+/// let command = Command::new(commands::UPDATE_MENU_ITEM, update);
+/// ```
+///
+/// [`Command`]: ../struct.Command.html
+/// [`Selector`]: ../struct.Selector.html
+/// [`UPDATE_MENU_ITEM`]: ../commands/constant.UPDATE_MENU_ITEM.html
+/// [`WindowDesc::menu_fn`]: ../struct.WindowDesc.html#method.menu_fn
+#[derive(Debug, Clone)]
+pub struct MenuItemUpdate<T> {
+    selector: Selector,
+    title: Option<LocalizedString<T>>,
+    enabled: Option<bool>,
+    hidden: Option<bool>,
+}
+
 /// A menu displayed as a pop-over.
+///
+/// A `ContextMenu<()>` can be shown in any window, whatever the type of its
+/// data; this is for widgets that don't know the application's data type.
 #[derive(Debug, Clone)]
 pub struct ContextMenu<T> {
     pub(crate) menu: MenuDesc<T>,
@@ -176,8 +287,13 @@ impl<T> MenuItem<T> {
             command: command.into(),
             hotkey: None,
             tool_tip: None,
+            icon: None,
             selected: false,
             enabled: true,
+            hidden: false,
+            radio: false,
+            selected_fn: None,
+            enabled_fn: None,
             platform_id: MenuItemId::PLACEHOLDER,
         }
     }
@@ -200,6 +316,28 @@ impl<T> MenuItem<T> {
         self
     }
 
+    /// A builder method that shows a named icon next to this item, on
+    /// platforms that support it.
+    ///
+    /// Icon names are platform specific: GTK looks them up in the icon theme
+    /// (such as `"document-open"`), and macOS takes `NSImage` names (such as
+    /// `"NSFolder"`). The icon is ignored on Windows, or if it is not found;
+    /// [`image`] shows an icon on every platform.
+    ///
+    /// [`image`]: #method.image
+    pub fn icon(mut self, name: impl Into<String>) -> Self {
+        self.icon = Some(MenuIcon::Named(name.into()));
+        self
+    }
+
+    /// A builder method that shows `image` next to this item.
+    ///
+    /// The image is shown at its own size; 16 by 16 pixels suits most menus.
+    pub fn image(mut self, image: &ImageData) -> Self {
+        self.icon = Some(MenuIcon::Image(image.to_icon()));
+        self
+    }
+
     /// Disable this menu item.
     pub fn disabled(mut self) -> Self {
         self.enabled = false;
@@ -214,6 +352,16 @@ impl<T> MenuItem<T> {
         self
     }
 
+    /// Hide this menu item.
+    ///
+    /// The item can be shown again with a [`MenuItemUpdate`].
+    ///
+    /// [`MenuItemUpdate`]: struct.MenuItemUpdate.html
+    pub fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
     /// Mark this menu item as selected. This will usually be indicated by
     /// a checkmark.
     pub fn selected(mut self) -> Self {
@@ -228,6 +376,120 @@ impl<T> MenuItem<T> {
         }
         self
     }
+
+    /// Mark this item as selected whenever `f` returns `true` for the
+    /// current data.
+    ///
+    /// The window's menu is rebuilt when the data changes, so the item
+    /// stays up to date.
+    pub fn selected_when(mut self, f: impl Fn(&T, &Env) -> bool + 'static) -> Self {
+        self.selected_fn = Some(StateFn(Rc::new(f)));
+        self
+    }
+
+    /// Enable this item only when `f` returns `true` for the current data.
+    ///
+    /// The window's menu is rebuilt when the data changes, so the item
+    /// stays up to date.
+    pub fn enabled_when(mut self, f: impl Fn(&T, &Env) -> bool + 'static) -> Self {
+        self.enabled_fn = Some(StateFn(Rc::new(f)));
+        self
+    }
+
+    fn depends_on_data(&self) -> bool {
+        self.selected_fn.is_some() || self.enabled_fn.is_some()
+    }
+
+    /// Apply `update` to this item, and to its platform counterpart
+    /// through `handle`.
+    fn apply_update(
+        &mut self,
+        update: &MenuItemUpdate<T>,
+        data: &T,
+        env: &Env,
+        handle: &WindowHandle,
+    ) where
+        T: Data,
+    {
+        let id = self.platform_id.as_u32();
+        if let Some(title) = update.title.as_ref() {
+            self.title = title.clone();
+            self.title.resolve(data, env);
+            handle.set_menu_item_title(id, self.title.localized_str());
+        }
+        if let Some(enabled) = update.enabled {
+            // an explicit state replaces one computed from the data
+            self.enabled_fn = None;
+            self.enabled = enabled;
+            handle.set_menu_item_enabled(id, enabled);
+        }
+        if let Some(hidden) = update.hidden {
+            self.hidden = hidden;
+            handle.set_menu_item_hidden(id, hidden);
+        }
+    }
+
+    fn resolve_state(&mut self, data: &T, env: &Env) {
+        if let Some(StateFn(f)) = self.selected_fn.as_ref() {
+            self.selected = f(data, env);
+        }
+        if let Some(StateFn(f)) = self.enabled_fn.as_ref() {
+            self.enabled = f(data, env);
+        }
+    }
+}
+
+impl<T: 'static> MenuItem<T> {
+    /// Create an item that is checked when the `bool` at `lens` is `true`,
+    /// and toggles it when chosen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use druid::{Data, Lens, LocalizedString, MenuDesc, MenuItem};
+    /// #[derive(Clone, Data, Lens)]
+    /// struct AppState {
+    ///     show_sidebar: bool,
+    /// }
+    ///
+    /// let item = MenuItem::checkbox(
+    ///     LocalizedString::new("menu-view-show-sidebar").with_placeholder("Show Sidebar".into()),
+    ///     AppState::show_sidebar,
+    /// );
+    /// # MenuDesc::<AppState>::empty().append(item);
+    /// ```
+    pub fn checkbox(title: LocalizedString<T>, lens: impl Lens<T, bool> + 'static) -> Self {
+        let lens = Rc::new(lens);
+        let update = DataUpdate(Rc::new({
+            let lens = lens.clone();
+            move |data: &mut T| lens.with_mut(data, |value| *value = !*value)
+        }));
+        MenuItem::new(title, Command::new(UPDATE_DATA, update))
+            .selected_when(move |data, _| lens.with(data, |value| *value))
+    }
+
+    /// Create an item that is checked when the value at `lens` is the same
+    /// as `value`, and sets it to `value` when chosen.
+    ///
+    /// A group of these with the same lens behaves like a set of radio
+    /// buttons, and is drawn as one where the platform has a separate mark
+    /// for them.
+    pub fn radio<U: Data>(
+        title: LocalizedString<T>,
+        lens: impl Lens<T, U> + 'static,
+        value: U,
+    ) -> Self {
+        let lens = Rc::new(lens);
+        let update = DataUpdate(Rc::new({
+            let lens = lens.clone();
+            let value = value.clone();
+            move |data: &mut T| lens.with_mut(data, |field| *field = value.clone())
+        }));
+        let mut item = MenuItem::new(title, Command::new(UPDATE_DATA, update))
+            .selected_when(move |data, _| lens.with(data, |field| field.same(&value)));
+        item.radio = true;
+        item
+    }
 }
 
 impl<T: Data> MenuDesc<T> {
@@ -242,9 +504,20 @@ impl<T: Data> MenuDesc<T> {
         MenuDesc {
             item,
             items: Vec::new(),
+            recent_documents: false,
         }
     }
 
+    /// An 'Open Recent' menu, listing the documents recorded with
+    /// [`Application::add_recent_document`].
+    ///
+    /// [`Application::add_recent_document`]: ../struct.Application.html#method.add_recent_document
+    fn open_recent() -> Self {
+        let mut menu = MenuDesc::new(LocalizedString::new("common-menu-file-open-recent"));
+        menu.recent_documents = true;
+        menu
+    }
+
     /// If this platform always expects windows to have a menu by default,
     /// returns a menu. Otherwise returns `None`.
     #[allow(unreachable_code)]
@@ -318,6 +591,16 @@ impl<T: Data> MenuDesc<T> {
         self.items.is_empty()
     }
 
+    /// Returns `true` if the state of any item in this menu or its
+    /// submenus is computed from the data.
+    pub(crate) fn depends_on_data(&self) -> bool {
+        self.items.iter().any(|entry| match entry {
+            MenuEntry::Item(item) => item.depends_on_data(),
+            MenuEntry::SubMenu(menu) => menu.item.depends_on_data() || menu.depends_on_data(),
+            MenuEntry::Separator => false,
+        })
+    }
+
     /// Build an application or window menu for the current platform.
     ///
     /// This takes self as &mut because it resolves localization.
@@ -339,10 +622,16 @@ impl<T: Data> MenuDesc<T> {
         } else {
             PlatformMenu::new()
         };
+        menu.set_modifier_names(modifier_names(env));
+        if self.recent_documents {
+            self.items = recent_document_items();
+            self.item.enabled = !self.items.is_empty();
+        }
         for item in &mut self.items {
             match item {
                 MenuEntry::Item(ref mut item) => {
                     item.title.resolve(data, env);
+                    item.resolve_state(data, env);
                     item.platform_id = MenuItemId::next();
                     menu.add_item(
                         item.platform_id.as_u32(),
@@ -351,11 +640,28 @@ impl<T: Data> MenuDesc<T> {
                         item.enabled,
                         item.selected,
                     );
+                    match item.icon.as_ref() {
+                        Some(MenuIcon::Named(icon)) => {
+                            menu.set_item_icon(item.platform_id.as_u32(), icon)
+                        }
+                        Some(MenuIcon::Image(image)) => {
+                            menu.set_item_image(item.platform_id.as_u32(), image)
+                        }
+                        None => (),
+                    }
+                    if item.radio {
+                        menu.set_item_radio(item.platform_id.as_u32(), true);
+                    }
+                    if item.hidden {
+                        menu.set_item_hidden(item.platform_id.as_u32(), true);
+                    }
                 }
                 MenuEntry::Separator => menu.add_separator(),
                 MenuEntry::SubMenu(ref mut submenu) => {
-                    let sub = submenu.build_native_menu(data, env, false);
+                    // submenus are popups on every platform, even in the menu bar
+                    let sub = submenu.build_native_menu(data, env, true);
                     submenu.item.title.resolve(data, env);
+                    submenu.item.resolve_state(data, env);
                     menu.add_dropdown(
                         sub,
                         &submenu.item.title.localized_str(),
@@ -367,6 +673,25 @@ impl<T: Data> MenuDesc<T> {
         menu
     }
 
+    /// Apply `update` to the matching items in this menu and its submenus.
+    pub(crate) fn apply_update(
+        &mut self,
+        update: &MenuItemUpdate<T>,
+        data: &T,
+        env: &Env,
+        handle: &WindowHandle,
+    ) {
+        for entry in &mut self.items {
+            match entry {
+                MenuEntry::Item(item) if item.command.selector == update.selector => {
+                    item.apply_update(update, data, env, handle)
+                }
+                MenuEntry::SubMenu(menu) => menu.apply_update(update, data, env, handle),
+                _ => (),
+            }
+        }
+    }
+
     /// Given a command identifier from druid-shell, returns the command
     /// corresponding to that id in this menu, if one exists.
     pub(crate) fn command_for_id(&self, id: u32) -> Option<Command> {
@@ -387,6 +712,53 @@ impl<T: Data> MenuDesc<T> {
     }
 }
 
+/// An item for each recent document, which opens it.
+fn recent_document_items<T: Data>() -> Vec<MenuEntry<T>> {
+    Application::recent_documents()
+        .into_iter()
+        .map(|path| {
+            let name = path
+                .file_name()
+                .unwrap_or_else(|| path.as_os_str())
+                .to_string_lossy()
+                .into_owned();
+            let cmd = Command::new(commands::OPEN_FILE, FileInfo::new(path));
+            MenuItem::new(LocalizedString::new("").with_placeholder(name), cmd).into()
+        })
+        .collect()
+}
+
+impl<T> MenuItemUpdate<T> {
+    /// Create an update, which does not yet change anything, for the items
+    /// that send commands with `selector`.
+    pub fn new(selector: Selector) -> Self {
+        MenuItemUpdate {
+            selector,
+            title: None,
+            enabled: None,
+            hidden: None,
+        }
+    }
+
+    /// Change the items' title.
+    pub fn title(mut self, title: LocalizedString<T>) -> Self {
+        self.title = Some(title);
+        self
+    }
+
+    /// Enable or disable the items.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    /// Hide or show the items.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = Some(hidden);
+        self
+    }
+}
+
 impl<T> ContextMenu<T> {
     pub fn new(menu: MenuDesc<T>, location: Point) -> Self {
         ContextMenu { menu, location }
@@ -476,6 +848,17 @@ pub mod sys {
                 .hotkey(SysMods::Cmd, "v")
         }
 
+        /// An 'Edit' menu, with the standard items.
+        pub fn edit_menu<T: Data>() -> MenuDesc<T> {
+            MenuDesc::new(LocalizedString::new("common-menu-edit-menu"))
+                .append(undo())
+                .append(redo())
+                .append_separator()
+                .append(cut())
+                .append(copy())
+                .append(paste())
+        }
+
         /// The 'Undo' menu item.
         pub fn undo<T: Data>() -> MenuItem<T> {
             MenuItem::new(LocalizedString::new("common-menu-undo"), commands::UNDO)
@@ -546,6 +929,18 @@ pub mod sys {
                 .hotkey(RawMods::Ctrl, "o")
             }
 
+            /// The 'Open Recent' submenu.
+            ///
+            /// It lists the documents recorded with [`ADD_RECENT_DOCUMENT`],
+            /// and choosing one sends [`OPEN_FILE`] with its [`FileInfo`].
+            ///
+            /// [`ADD_RECENT_DOCUMENT`]: ../../../commands/constant.ADD_RECENT_DOCUMENT.html
+            /// [`OPEN_FILE`]: ../../../commands/constant.OPEN_FILE.html
+            /// [`FileInfo`]: ../../../struct.FileInfo.html
+            pub fn open_recent<T: Data>() -> MenuDesc<T> {
+                MenuDesc::open_recent()
+            }
+
             /// The 'Close' menu item.
             pub fn close<T: Data>() -> MenuItem<T> {
                 MenuItem::new(
@@ -713,7 +1108,7 @@ pub mod sys {
                 MenuDesc::new(LocalizedString::new("common-menu-file-menu"))
                     .append(new_file())
                     .append(open_file())
-                    // open recent?
+                    .append(open_recent())
                     .append_separator()
                     .append(close())
                     .append(save().disabled())
@@ -747,6 +1142,18 @@ pub mod sys {
                 .hotkey(RawMods::Meta, "o")
             }
 
+            /// The 'Open Recent' submenu.
+            ///
+            /// It lists the documents recorded with [`ADD_RECENT_DOCUMENT`],
+            /// and choosing one sends [`OPEN_FILE`] with its [`FileInfo`].
+            ///
+            /// [`ADD_RECENT_DOCUMENT`]: ../../../commands/constant.ADD_RECENT_DOCUMENT.html
+            /// [`OPEN_FILE`]: ../../../commands/constant.OPEN_FILE.html
+            /// [`FileInfo`]: ../../../struct.FileInfo.html
+            pub fn open_recent<T: Data>() -> MenuDesc<T> {
+                MenuDesc::open_recent()
+            }
+
             /// The 'Close' menu item.
             pub fn close<T: Data>() -> MenuItem<T> {
                 MenuItem::new(
@@ -805,3 +1212,73 @@ pub mod sys {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lens::Field;
+
+    fn apply(item: &MenuItem<(bool, u32)>, data: &mut (bool, u32)) {
+        let DataUpdate(update) = item.command.get_object::<DataUpdate<_>>().unwrap();
+        update(data);
+    }
+
+    #[test]
+    fn checkbox_and_radio_follow_data() {
+        let env = Env::default();
+        let flag = Field::new(|d: &(bool, u32)| &d.0, |d| &mut d.0);
+        let choice = || Field::new(|d: &(bool, u32)| &d.1, |d| &mut d.1);
+        let mut check = MenuItem::checkbox(LocalizedString::new(""), flag);
+        let mut one = MenuItem::radio(LocalizedString::new(""), choice(), 1);
+        let two = MenuItem::radio(LocalizedString::new(""), choice(), 2);
+        assert!(MenuDesc::empty().append(check.clone()).depends_on_data());
+
+        let mut data = (false, 1);
+        check.resolve_state(&data, &env);
+        one.resolve_state(&data, &env);
+        assert!(!check.selected);
+        assert!(one.selected);
+
+        apply(&check, &mut data);
+        apply(&two, &mut data);
+        assert_eq!(data, (true, 2));
+        check.resolve_state(&data, &env);
+        one.resolve_state(&data, &env);
+        assert!(check.selected);
+        assert!(!one.selected);
+    }
+
+    #[test]
+    fn updates_apply_to_matching_items() {
+        const PLAY: Selector = Selector::new("play");
+        const STOP: Selector = Selector::new("stop");
+        let mut menu = MenuDesc::<()>::empty()
+            .append(MenuItem::new(LocalizedString::new("play"), PLAY).enabled_when(|_, _| true))
+            .append(MenuDesc::empty().append(MenuItem::new(LocalizedString::new("stop"), STOP)));
+
+        let update = MenuItemUpdate::new(PLAY)
+            .title(LocalizedString::new("pause"))
+            .enabled(false);
+        let env = Env::default();
+        menu.apply_update(&update, &(), &env, &WindowHandle::default());
+        menu.apply_update(
+            &MenuItemUpdate::new(STOP).hidden(true),
+            &(),
+            &env,
+            &WindowHandle::default(),
+        );
+
+        match (&menu.items[0], &menu.items[1]) {
+            (MenuEntry::Item(play), MenuEntry::SubMenu(sub)) => {
+                assert_eq!(play.title.key, "pause");
+                assert!(!play.enabled);
+                assert!(!play.depends_on_data());
+                match &sub.items[0] {
+                    MenuEntry::Item(stop) => assert!(stop.hidden),
+                    _ => panic!("expected an item"),
+                }
+            }
+            _ => panic!("unexpected menu structure"),
+        }
+    }
+}
@@ -14,8 +14,12 @@
 
 //! The mousey bits
 
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use crate::kurbo::Point;
-use crate::{KeyModifiers, MouseButton};
+use crate::widget::ImageData;
+use crate::{Cursor, KeyModifiers, MouseButton, WindowHandle};
 
 /// The state of the mouse for a click, mouse-up, or move event.
 ///
@@ -54,3 +58,50 @@ impl From<druid_shell::MouseEvent> for MouseEvent {
         }
     }
 }
+
+/// A custom cursor, made from an image.
+///
+/// The platform cursor is made the first time the description is given to
+/// [`EventCtx::set_custom_cursor`], and is kept and shared by its clones
+/// after that, so a widget should make the description once and keep it.
+///
+/// [`EventCtx::set_custom_cursor`]: struct.EventCtx.html#method.set_custom_cursor
+#[derive(Clone)]
+pub struct CursorDesc {
+    image: ImageData,
+    hot: Point,
+    /// The cursor once it has been made; `Some(None)` if the platform
+    /// couldn't make it, so that it isn't tried again.
+    cursor: Rc<RefCell<Option<Option<Cursor>>>>,
+}
+
+impl CursorDesc {
+    /// Create a cursor description from an image.
+    ///
+    /// `hot` is the point in the image, in pixels, that is placed at the
+    /// mouse position. The image is shown at its size in pixels.
+    pub fn new(image: ImageData, hot: impl Into<Point>) -> Self {
+        CursorDesc {
+            image,
+            hot: hot.into(),
+            cursor: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// The platform cursor, made the first time it is asked for.
+    pub(crate) fn cursor(&self, window: &WindowHandle) -> Option<Cursor> {
+        let mut cursor = self.cursor.borrow_mut();
+        cursor
+            .get_or_insert_with(|| {
+                let size = self.image.size();
+                let desc = druid_shell::CursorDesc::from_rgba(
+                    self.image.rgba_pixels().into_owned(),
+                    size.width as usize,
+                    size.height as usize,
+                    self.hot,
+                );
+                window.make_cursor(&desc)
+            })
+            .clone()
+    }
+}
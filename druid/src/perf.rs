@@ -0,0 +1,196 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An overlay showing how long each frame takes.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::inspector::paint_panel;
+use crate::kurbo::{Point, Size};
+use crate::piet::Piet;
+use crate::Env;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static COUNTING: AtomicBool = AtomicBool::new(false);
+
+/// A global allocator that counts allocations, so that the performance
+/// overlay can show how many happen in each frame.
+///
+/// It forwards to the system allocator. To use it, add this to your
+/// application:
+///
+/// ```no_run
+/// #[global_allocator]
+/// static ALLOCATOR: druid::CountingAllocator = druid::CountingAllocator;
+/// ```
+///
+/// The overlay is shown with the [`TOGGLE_PERF_HUD`] command.
+///
+/// [`TOGGLE_PERF_HUD`]: commands/constant.TOGGLE_PERF_HUD.html
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CountingAllocator;
+
+// The allocator only forwards to `System`, which upholds the contract.
+#[allow(unsafe_code)]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        COUNTING.store(true, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        COUNTING.store(true, Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// The passes that are timed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Pass {
+    Event,
+    Update,
+    Layout,
+    Paint,
+}
+
+/// Timing for one frame, which includes the events and updates since the
+/// frame before.
+#[derive(Debug, Clone, Default)]
+struct FrameStats {
+    event: Duration,
+    update: Duration,
+    layout: Duration,
+    paint: Duration,
+    widgets_painted: usize,
+    allocations: usize,
+}
+
+/// The state of the performance overlay.
+pub(crate) struct PerfHud {
+    /// The stats being gathered for the frame in progress.
+    current: FrameStats,
+    /// The stats of the last frame, which are shown.
+    last: FrameStats,
+    /// When recent frames were painted, for the frame rate.
+    frame_times: VecDeque<Instant>,
+    allocations_at_frame_start: usize,
+}
+
+impl PerfHud {
+    pub(crate) fn new() -> Self {
+        PerfHud {
+            current: FrameStats::default(),
+            last: FrameStats::default(),
+            frame_times: VecDeque::new(),
+            allocations_at_frame_start: ALLOCATIONS.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Add time spent in a pass to the current frame.
+    pub(crate) fn add_time(&mut self, pass: Pass, time: Duration) {
+        let total = match pass {
+            Pass::Event => &mut self.current.event,
+            Pass::Update => &mut self.current.update,
+            Pass::Layout => &mut self.current.layout,
+            Pass::Paint => &mut self.current.paint,
+        };
+        *total += time;
+    }
+
+    /// Finish a frame, in which `widgets_painted` widgets were painted.
+    pub(crate) fn end_frame(&mut self, widgets_painted: usize) {
+        let now = Instant::now();
+        self.frame_times.push_back(now);
+        while let Some(time) = self.frame_times.front() {
+            if now.duration_since(*time) > Duration::from_secs(1) {
+                self.frame_times.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        self.current.widgets_painted = widgets_painted;
+        self.current.allocations = allocations.wrapping_sub(self.allocations_at_frame_start);
+        self.allocations_at_frame_start = allocations;
+        self.last = std::mem::take(&mut self.current);
+    }
+
+    /// The number of frames painted in the last second.
+    fn fps(&self) -> usize {
+        self.frame_times.len()
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let last = &self.last;
+        let allocations = if COUNTING.load(Ordering::Relaxed) {
+            last.allocations.to_string()
+        } else {
+            "not counted".to_string()
+        };
+        vec![
+            format!("{} fps", self.fps()),
+            format!("event   {:.2} ms", ms(last.event)),
+            format!("update  {:.2} ms", ms(last.update)),
+            format!("layout  {:.2} ms", ms(last.layout)),
+            format!("paint   {:.2} ms", ms(last.paint)),
+            format!("widgets painted  {}", last.widgets_painted),
+            format!("allocations  {}", allocations),
+        ]
+    }
+
+    /// Paint the overlay in the top right corner of the window.
+    pub(crate) fn paint(&self, piet: &mut Piet, window_size: Size, env: &Env) {
+        paint_panel(piet, &self.lines(), env, |panel| {
+            Point::new(window_size.width - panel.width, 0.0)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_reset_stats() {
+        let mut hud = PerfHud::new();
+        hud.add_time(Pass::Event, Duration::from_millis(2));
+        hud.add_time(Pass::Event, Duration::from_millis(3));
+        hud.add_time(Pass::Paint, Duration::from_millis(4));
+        hud.end_frame(7);
+        assert_eq!(hud.last.event, Duration::from_millis(5));
+        assert_eq!(hud.last.paint, Duration::from_millis(4));
+        assert_eq!(hud.last.widgets_painted, 7);
+        assert_eq!(hud.current.event, Duration::from_secs(0));
+
+        hud.end_frame(0);
+        assert_eq!(hud.fps(), 2);
+        assert_eq!(hud.last.event, Duration::from_secs(0));
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Keeping the app data between runs.
+//!
+//! This is only built with the `persistence` feature.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Data, TimerToken};
+
+/// How long the data has to stay the same before it is saved, so that we
+/// don't write the file on every key press.
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+
+/// Where the data is kept, and what has been saved.
+pub(crate) struct Persistence<T> {
+    path: PathBuf,
+    load: fn(&Path) -> io::Result<T>,
+    save: fn(&T, &Path) -> io::Result<()>,
+    /// The data as it was last loaded or saved.
+    saved: Option<T>,
+    /// The data as it was after the last update.
+    latest: Option<T>,
+    /// When the data will have been left alone for long enough.
+    due: Option<Instant>,
+    /// The timer that saves the data, if a save is waiting.
+    pub(crate) timer: Option<TimerToken>,
+}
+
+impl<T: Data + Serialize + DeserializeOwned> Persistence<T> {
+    /// Keep the data in `path`.
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Persistence {
+            path,
+            load: load_json,
+            save: save_json,
+            saved: None,
+            latest: None,
+            due: None,
+            timer: None,
+        }
+    }
+}
+
+impl<T: Data> Persistence<T> {
+    /// The data from the last run, or `initial` if there isn't any.
+    pub(crate) fn restore(&mut self, initial: T) -> T {
+        let data = match (self.load)(&self.path) {
+            Ok(data) => data,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => initial,
+            Err(e) => {
+                log::warn!("failed to restore from '{}': {}", self.path.display(), e);
+                initial
+            }
+        };
+        self.saved = Some(data.clone());
+        self.latest = Some(data.clone());
+        data
+    }
+
+    /// Whether the data has changed since it was last saved.
+    pub(crate) fn is_dirty(&self, data: &T) -> bool {
+        self.saved.as_ref().map(|saved| saved.same(data)) != Some(true)
+    }
+
+    /// Note the data after an update. Returns when a timer should go off to
+    /// save it, if it has changed and there is no timer already.
+    pub(crate) fn updated(&mut self, data: &T, now: Instant) -> Option<Instant> {
+        if self.latest.as_ref().map(|latest| latest.same(data)) == Some(true) {
+            return None;
+        }
+        self.latest = Some(data.clone());
+        self.due = Some(now + SAVE_DELAY);
+        match self.timer {
+            Some(_) => None,
+            None => self.due,
+        }
+    }
+
+    /// The save timer went off. If the data has changed since the timer was
+    /// set, returns when the next one should go off; otherwise saves.
+    pub(crate) fn timer_fired(&mut self, data: &T, now: Instant) -> Option<Instant> {
+        self.timer = None;
+        match self.due {
+            Some(due) if due > now => Some(due),
+            _ => {
+                self.save(data);
+                None
+            }
+        }
+    }
+
+    /// Save the data, if it has changed.
+    ///
+    /// This forgets any waiting timer, which may belong to a window that is
+    /// closing, so that the next change sets a new one.
+    pub(crate) fn save(&mut self, data: &T) {
+        self.due = None;
+        self.timer = None;
+        if !self.is_dirty(data) {
+            return;
+        }
+        match (self.save)(data, &self.path) {
+            Ok(()) => self.saved = Some(data.clone()),
+            Err(e) => log::error!("failed to save to '{}': {}", self.path.display(), e),
+        }
+    }
+}
+
+/// Where the data of the app with `app_id` is kept: in its own directory in
+/// the platform's place for settings.
+pub(crate) fn state_path(app_id: &str) -> PathBuf {
+    let mut path = config_dir().unwrap_or_else(std::env::temp_dir);
+    path.push(app_id);
+    path.push("state.json");
+    path
+}
+
+#[cfg(target_os = "windows")]
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("APPDATA").map(PathBuf::from)
+}
+
+#[cfg(target_os = "macos")]
+fn config_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(Path::new(&home).join("Library/Application Support"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn config_dir() -> Option<PathBuf> {
+    match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Some(dir.into()),
+        _ => Some(Path::new(&std::env::var_os("HOME")?).join(".config")),
+    }
+}
+
+fn load_json<T: DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let file = io::BufReader::new(fs::File::open(path)?);
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// Write to a temporary file first, so that a crash while saving doesn't
+/// lose the last good state.
+fn save_json<T: Serialize>(data: &T, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp = path.with_extension("json.tmp");
+    let mut file = io::BufWriter::new(fs::File::create(&temp)?);
+    serde_json::to_writer(&mut file, data)?;
+    // the file must be on disk before it replaces the old one
+    file.flush()?;
+    file.get_ref().sync_all()?;
+    fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_restore() {
+        let path = std::env::temp_dir()
+            .join(format!("druid-persist-test-{}", std::process::id()))
+            .join("state.json");
+        let mut persistence = Persistence::new(path.clone());
+        let initial = (1u32, String::from("one"));
+        assert_eq!(persistence.restore(initial.clone()), initial);
+
+        let changed = (2u32, String::from("two"));
+        assert!(persistence.is_dirty(&changed));
+        persistence.save(&changed);
+        assert!(!persistence.is_dirty(&changed));
+
+        let mut next_run = Persistence::new(path.clone());
+        assert_eq!(next_run.restore(initial), changed);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn saving_waits_for_changes_to_stop() {
+        let path = std::env::temp_dir()
+            .join(format!("druid-persist-wait-{}", std::process::id()))
+            .join("state.json");
+        let mut persistence = Persistence::new(path.clone());
+        let start = Instant::now();
+        persistence.restore(0u32);
+
+        let mut data = 1;
+        let first = persistence.updated(&data, start).unwrap();
+        persistence.timer = Some(TimerToken::next());
+        // an update that changes nothing doesn't delay the save
+        assert_eq!(persistence.updated(&data, start + SAVE_DELAY / 2), None);
+
+        let later = start + SAVE_DELAY / 2;
+        data = 2;
+        assert_eq!(persistence.updated(&data, later), None);
+        // the data changed after the timer was set, so wait some more
+        assert_eq!(
+            persistence.timer_fired(&data, first),
+            Some(later + SAVE_DELAY)
+        );
+        assert!(persistence.is_dirty(&data));
+
+        assert_eq!(persistence.timer_fired(&data, later + SAVE_DELAY), None);
+        assert!(!persistence.is_dirty(&data));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn saving_early_lets_the_next_change_set_a_timer() {
+        let path = std::env::temp_dir()
+            .join(format!("druid-persist-early-{}", std::process::id()))
+            .join("state.json");
+        let mut persistence = Persistence::new(path.clone());
+        let start = Instant::now();
+        persistence.restore(0u32);
+
+        assert!(persistence.updated(&1, start).is_some());
+        persistence.timer = Some(TimerToken::next());
+        // the window with the timer closes, and the data is saved then
+        persistence.save(&1);
+        assert_eq!(persistence.timer, None);
+        assert_eq!(persistence.updated(&2, start), Some(start + SAVE_DELAY));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn state_is_kept_per_app() {
+        let path = state_path("com.example.notes");
+        assert!(path.ends_with("com.example.notes/state.json"));
+    }
+}
@@ -0,0 +1,732 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording input events, and replaying them.
+
+use std::borrow::Cow;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{self, Write as _};
+use std::fs::File;
+use std::io::{self, Write as _};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::command::ArgumentError;
+use crate::kurbo::{Point, Size, Vec2};
+use crate::{
+    Command, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, Selector, TimerToken,
+    WheelEvent,
+};
+
+/// A sequence of input events, with the time at which each happened.
+///
+/// A recording contains the mouse, keyboard, wheel and resize events sent to
+/// a window, and the commands sent from outside the widget tree, such as from
+/// menus, hotkeys and [`ExtEventSink`]s. Commands are only recorded if they
+/// have no argument, or an argument that is a `String`, `bool`, `f64`, `i64`,
+/// `u64` or `usize`. Timers are not recorded; they are requested again when
+/// the events are replayed, and fire as time passes.
+///
+/// A recording can be made from a running app with
+/// [`AppLauncher::record_events`], and replayed with
+/// [`AppLauncher::replay_events`] or, deterministically, with
+/// [`Harness::replay`].
+///
+/// Recordings are saved as text, one event per line, so that they can be
+/// read and edited by hand.
+///
+/// [`ExtEventSink`]: struct.ExtEventSink.html
+/// [`AppLauncher::record_events`]: struct.AppLauncher.html#method.record_events
+/// [`AppLauncher::replay_events`]: struct.AppLauncher.html#method.replay_events
+/// [`Harness::replay`]: testing/struct.Harness.html#method.replay
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    entries: Vec<(Duration, Input)>,
+}
+
+/// An event in a recording.
+#[derive(Debug, Clone)]
+pub(crate) enum Input {
+    Event(Event),
+    /// A command, to be sent to the window.
+    Command(SavedCommand),
+}
+
+/// A command in a recording, which is made into a [`Command`] when it is
+/// replayed.
+///
+/// [`Command`]: struct.Command.html
+#[derive(Debug, Clone)]
+pub(crate) struct SavedCommand {
+    /// Borrowed from the selector when recorded, and owned when read.
+    selector: Cow<'static, str>,
+    arg: Option<Arg>,
+}
+
+/// The argument of a recorded command.
+#[derive(Debug, Clone)]
+enum Arg {
+    String(String),
+    Bool(bool),
+    F64(f64),
+    I64(i64),
+    U64(u64),
+    Usize(usize),
+}
+
+/// The selectors of replayed commands.
+///
+/// A [`Selector`] needs a `&'static str`, so each name read from a recording
+/// is leaked; this keeps them, so that each is leaked only once.
+///
+/// [`Selector`]: struct.Selector.html
+#[derive(Default)]
+pub(crate) struct Selectors(HashSet<&'static str>);
+
+/// An event or command from a [`Replay`] that is due.
+///
+/// [`Replay`]: struct.Replay.html
+pub(crate) enum Replayed {
+    Event(Event),
+    Command(Command),
+}
+
+/// An error from parsing a [`Recording`].
+///
+/// [`Recording`]: struct.Recording.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseRecordingError {
+    line: usize,
+    msg: String,
+}
+
+/// Writes events to a file as they happen, in a running app.
+pub(crate) struct Recorder {
+    file: File,
+    start: Instant,
+}
+
+/// Events waiting to be replayed in a running app.
+pub(crate) struct Replay {
+    entries: VecDeque<(Duration, Input)>,
+    start: Instant,
+    selectors: Selectors,
+    pub(crate) timer: Option<TimerToken>,
+}
+
+impl Recording {
+    /// Create an empty recording.
+    pub fn new() -> Self {
+        Recording::default()
+    }
+
+    /// The number of events in the recording.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the recording has no events.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Read a recording from a file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Recording> {
+        let text = std::fs::read_to_string(path)?;
+        text.parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write the recording to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// Add an event that happened at `time`, if it is one that can be
+    /// recorded. Returns `true` if it was added.
+    pub fn push_event(&mut self, time: Duration, event: &Event) -> bool {
+        match recordable_event(event) {
+            Some(input) => {
+                self.entries.push((time, input));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Add a command that was sent at `time`, if its argument can be
+    /// recorded. Returns `true` if it was added.
+    pub fn push_command(&mut self, time: Duration, cmd: &Command) -> bool {
+        match recordable_command(cmd) {
+            Some(input) => {
+                self.entries.push((time, input));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn entries(&self) -> impl Iterator<Item = &(Duration, Input)> {
+        self.entries.iter()
+    }
+}
+
+impl Recorder {
+    pub(crate) fn create(path: &Path) -> io::Result<Recorder> {
+        let mut file = File::create(path)?;
+        writeln!(file, "{}", HEADER)?;
+        Ok(Recorder {
+            file,
+            start: Instant::now(),
+        })
+    }
+
+    pub(crate) fn record_event(&mut self, event: &Event) {
+        if let Some(input) = recordable_event(event) {
+            self.write(&input);
+        }
+    }
+
+    pub(crate) fn record_command(&mut self, cmd: &Command) {
+        match recordable_command(cmd) {
+            Some(input) => self.write(&input),
+            None => log::warn!(
+                "not recording {}: its argument can't be saved",
+                cmd.selector
+            ),
+        }
+    }
+
+    /// Each event is written as it happens, so that the recording survives
+    /// a crash.
+    fn write(&mut self, input: &Input) {
+        let mut line = String::new();
+        write_entry(&mut line, self.start.elapsed(), input).unwrap();
+        if let Err(e) = writeln!(self.file, "{}", line) {
+            log::error!("failed to write event recording: {}", e);
+        }
+    }
+}
+
+impl Replay {
+    pub(crate) fn new(recording: Recording) -> Replay {
+        Replay {
+            entries: recording.entries.into(),
+            start: Instant::now(),
+            selectors: Selectors::default(),
+            timer: None,
+        }
+    }
+
+    /// Start the clock, when the window the events go to is ready.
+    pub(crate) fn start(&mut self) {
+        self.start = Instant::now();
+    }
+
+    /// When the next event is due, if there is one.
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        self.entries.front().map(|(time, _)| self.start + *time)
+    }
+
+    /// The next event, if it is due.
+    pub(crate) fn next_due(&mut self) -> Option<Replayed> {
+        let input = match self.entries.front() {
+            Some((time, _)) if *time <= self.start.elapsed() => self.entries.pop_front()?.1,
+            _ => return None,
+        };
+        match input {
+            Input::Event(event) => Some(Replayed::Event(event)),
+            Input::Command(cmd) => Some(Replayed::Command(cmd.to_command(&mut self.selectors))),
+        }
+    }
+}
+
+impl SavedCommand {
+    /// The command this was saved from, if its argument can be saved.
+    fn new(cmd: &Command) -> Option<SavedCommand> {
+        Some(SavedCommand {
+            selector: Cow::Borrowed(cmd.selector.name()),
+            arg: saved_arg(cmd)?,
+        })
+    }
+
+    /// The command to replay, with a selector from `selectors`.
+    pub(crate) fn to_command(&self, selectors: &mut Selectors) -> Command {
+        let selector = match &self.selector {
+            Cow::Borrowed(name) => Selector::new(name),
+            Cow::Owned(name) => selectors.intern(name),
+        };
+        match &self.arg {
+            None => selector.into(),
+            Some(Arg::String(arg)) => Command::new(selector, arg.clone()),
+            Some(Arg::Bool(arg)) => Command::new(selector, *arg),
+            Some(Arg::F64(arg)) => Command::new(selector, *arg),
+            Some(Arg::I64(arg)) => Command::new(selector, *arg),
+            Some(Arg::U64(arg)) => Command::new(selector, *arg),
+            Some(Arg::Usize(arg)) => Command::new(selector, *arg),
+        }
+    }
+}
+
+impl Selectors {
+    fn intern(&mut self, name: &str) -> Selector {
+        match self.0.get(name) {
+            Some(name) => Selector::new(name),
+            None => {
+                let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+                self.0.insert(name);
+                Selector::new(name)
+            }
+        }
+    }
+}
+
+/// The argument of `cmd`, or `None` inside if it has none; `None` if it is
+/// of a type we can't save.
+fn saved_arg(cmd: &Command) -> Option<Option<Arg>> {
+    macro_rules! try_arg {
+        ($ty:ty, $variant:ident) => {
+            if let Ok(arg) = cmd.get_object::<$ty>() {
+                return Some(Some(Arg::$variant(arg.clone())));
+            }
+        };
+    }
+    if let Err(ArgumentError::NoArgument) = cmd.get_object::<()>() {
+        return Some(None);
+    }
+    try_arg!(String, String);
+    try_arg!(bool, Bool);
+    try_arg!(f64, F64);
+    try_arg!(i64, I64);
+    try_arg!(u64, U64);
+    try_arg!(usize, Usize);
+    None
+}
+
+fn recordable_event(event: &Event) -> Option<Input> {
+    match event {
+        // these have no name to save them by
+        Event::KeyDown(key) | Event::KeyUp(key) if matches!(key.key_code, KeyCode::Unknown(_)) => {
+            None
+        }
+        Event::MouseDown(_)
+        | Event::MouseUp(_)
+        | Event::MouseMoved(_)
+        | Event::Wheel(_)
+        | Event::KeyDown(_)
+        | Event::KeyUp(_)
+        | Event::Size(_)
+        | Event::Zoom(_) => Some(Input::Event(event.clone())),
+        _ => None,
+    }
+}
+
+fn recordable_command(cmd: &Command) -> Option<Input> {
+    SavedCommand::new(cmd).map(Input::Command)
+}
+
+const HEADER: &str = "# druid event recording";
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for (time, input) in &self.entries {
+            let mut line = String::new();
+            write_entry(&mut line, *time, input)?;
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write one line, starting with the time in microseconds.
+fn write_entry(out: &mut String, time: Duration, input: &Input) -> fmt::Result {
+    write!(out, "{} ", time.as_micros())?;
+    match input {
+        Input::Event(Event::MouseDown(mouse)) => write_mouse(out, "mouse-down", mouse),
+        Input::Event(Event::MouseUp(mouse)) => write_mouse(out, "mouse-up", mouse),
+        Input::Event(Event::MouseMoved(mouse)) => write_mouse(out, "mouse-move", mouse),
+        Input::Event(Event::Wheel(wheel)) => write!(
+            out,
+            "wheel {} {} {}",
+            wheel.delta.x,
+            wheel.delta.y,
+            mods_to_str(wheel.mods)
+        ),
+        Input::Event(Event::KeyDown(key)) => write_key(out, "key-down", key),
+        Input::Event(Event::KeyUp(key)) => write_key(out, "key-up", key),
+        Input::Event(Event::Size(size)) => write!(out, "size {} {}", size.width, size.height),
+        Input::Event(Event::Zoom(delta)) => write!(out, "zoom {}", delta),
+        Input::Command(cmd) => {
+            write!(out, "command {}", quote(&cmd.selector))?;
+            write_arg(out, &cmd.arg)
+        }
+        Input::Event(_) => Err(fmt::Error),
+    }
+}
+
+fn write_mouse(out: &mut String, name: &str, mouse: &MouseEvent) -> fmt::Result {
+    let button = match mouse.button {
+        MouseButton::Left => "left",
+        MouseButton::Middle => "middle",
+        MouseButton::Right => "right",
+        MouseButton::X1 => "x1",
+        MouseButton::X2 => "x2",
+    };
+    write!(
+        out,
+        "{} {} {} {} {} {}",
+        name,
+        mouse.window_pos.x,
+        mouse.window_pos.y,
+        button,
+        mouse.count,
+        mods_to_str(mouse.mods)
+    )
+}
+
+fn write_key(out: &mut String, name: &str, key: &KeyEvent) -> fmt::Result {
+    write!(
+        out,
+        "{} {:?} {} {} {} {}",
+        name,
+        key.key_code,
+        key.is_repeat,
+        mods_to_str(key.mods),
+        quote(key.text().unwrap_or("")),
+        quote(key.unmod_text().unwrap_or(""))
+    )
+}
+
+/// Write the command's argument, if it has one.
+fn write_arg(out: &mut String, arg: &Option<Arg>) -> fmt::Result {
+    match arg {
+        None => Ok(()),
+        Some(Arg::String(arg)) => write!(out, " string {}", quote(arg)),
+        Some(Arg::Bool(arg)) => write!(out, " bool {}", arg),
+        Some(Arg::F64(arg)) => write!(out, " f64 {}", arg),
+        Some(Arg::I64(arg)) => write!(out, " i64 {}", arg),
+        Some(Arg::U64(arg)) => write!(out, " u64 {}", arg),
+        Some(Arg::Usize(arg)) => write!(out, " usize {}", arg),
+    }
+}
+
+fn mods_to_str(mods: KeyModifiers) -> String {
+    let mut s = String::new();
+    for (on, c) in &[
+        (mods.shift, 's'),
+        (mods.alt, 'a'),
+        (mods.ctrl, 'c'),
+        (mods.meta, 'm'),
+    ] {
+        if *on {
+            s.push(*c);
+        }
+    }
+    if s.is_empty() {
+        s.push('-');
+    }
+    s
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl FromStr for Recording {
+    type Err = ParseRecordingError;
+
+    fn from_str(s: &str) -> Result<Recording, ParseRecordingError> {
+        let mut entries = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry =
+                parse_entry(line).map_err(|msg| ParseRecordingError { line: i + 1, msg })?;
+            entries.push(entry);
+        }
+        Ok(Recording { entries })
+    }
+}
+
+fn parse_entry(line: &str) -> Result<(Duration, Input), String> {
+    let mut tokens = Tokens::new(line)?;
+    let time = Duration::from_micros(tokens.parse("time")?);
+    let kind = tokens.next("event")?;
+    let input = match kind.as_str() {
+        "mouse-down" => Input::Event(Event::MouseDown(parse_mouse(&mut tokens)?)),
+        "mouse-up" => Input::Event(Event::MouseUp(parse_mouse(&mut tokens)?)),
+        "mouse-move" => Input::Event(Event::MouseMoved(parse_mouse(&mut tokens)?)),
+        "wheel" => {
+            let delta = Vec2::new(tokens.parse("x")?, tokens.parse("y")?);
+            let mods = parse_mods(&tokens.next("modifiers")?)?;
+            Input::Event(Event::Wheel(WheelEvent { delta, mods }))
+        }
+        "key-down" => Input::Event(Event::KeyDown(parse_key(&mut tokens)?)),
+        "key-up" => Input::Event(Event::KeyUp(parse_key(&mut tokens)?)),
+        "size" => Input::Event(Event::Size(Size::new(
+            tokens.parse("width")?,
+            tokens.parse("height")?,
+        ))),
+        "zoom" => Input::Event(Event::Zoom(tokens.parse("delta")?)),
+        "command" => Input::Command(parse_command(&mut tokens)?),
+        other => return Err(format!("unknown event '{}'", other)),
+    };
+    tokens.finish()?;
+    Ok((time, input))
+}
+
+fn parse_mouse(tokens: &mut Tokens) -> Result<MouseEvent, String> {
+    let pos = Point::new(tokens.parse("x")?, tokens.parse("y")?);
+    let button = match tokens.next("button")?.as_str() {
+        "left" => MouseButton::Left,
+        "middle" => MouseButton::Middle,
+        "right" => MouseButton::Right,
+        "x1" => MouseButton::X1,
+        "x2" => MouseButton::X2,
+        other => return Err(format!("unknown mouse button '{}'", other)),
+    };
+    let count = tokens.parse("click count")?;
+    let mods = parse_mods(&tokens.next("modifiers")?)?;
+    Ok(MouseEvent {
+        pos,
+        window_pos: pos,
+        mods,
+        count,
+        button,
+    })
+}
+
+fn parse_key(tokens: &mut Tokens) -> Result<KeyEvent, String> {
+    let name = tokens.next("key code")?;
+    let key_code =
+        KeyCode::from_name(&name).ok_or_else(|| format!("unknown key code '{}'", name))?;
+    let is_repeat = tokens.parse("repeat")?;
+    let mods = parse_mods(&tokens.next("modifiers")?)?;
+    let text = tokens.next("text")?;
+    let unmod_text = tokens.next("unmodified text")?;
+    Ok(KeyEvent::from_parts(
+        key_code,
+        is_repeat,
+        mods,
+        &text,
+        &unmod_text,
+    ))
+}
+
+fn parse_command(tokens: &mut Tokens) -> Result<SavedCommand, String> {
+    let selector = Cow::Owned(tokens.next("selector")?);
+    let arg = match tokens.try_next() {
+        None => None,
+        Some(ty) => Some(match ty.as_str() {
+            "string" => Arg::String(tokens.next("argument")?),
+            "bool" => Arg::Bool(tokens.parse("argument")?),
+            "f64" => Arg::F64(tokens.parse("argument")?),
+            "i64" => Arg::I64(tokens.parse("argument")?),
+            "u64" => Arg::U64(tokens.parse("argument")?),
+            "usize" => Arg::Usize(tokens.parse("argument")?),
+            other => return Err(format!("unknown argument type '{}'", other)),
+        }),
+    };
+    Ok(SavedCommand { selector, arg })
+}
+
+fn parse_mods(s: &str) -> Result<KeyModifiers, String> {
+    let mut mods = KeyModifiers::default();
+    if s == "-" {
+        return Ok(mods);
+    }
+    for c in s.chars() {
+        match c {
+            's' => mods.shift = true,
+            'a' => mods.alt = true,
+            'c' => mods.ctrl = true,
+            'm' => mods.meta = true,
+            other => return Err(format!("unknown modifier '{}'", other)),
+        }
+    }
+    Ok(mods)
+}
+
+/// The words of a line, where a quoted string is one word.
+struct Tokens {
+    tokens: VecDeque<String>,
+}
+
+impl Tokens {
+    fn new(line: &str) -> Result<Tokens, String> {
+        let mut tokens = VecDeque::new();
+        let mut chars = line.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+            } else if c == '"' {
+                chars.next();
+                let mut token = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => token.push('\n'),
+                            Some('r') => token.push('\r'),
+                            Some('t') => token.push('\t'),
+                            Some(c) => token.push(c),
+                            None => return Err("unterminated string".into()),
+                        },
+                        Some(c) => token.push(c),
+                        None => return Err("unterminated string".into()),
+                    }
+                }
+                tokens.push_back(token);
+            } else {
+                let mut token = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() {
+                        break;
+                    }
+                    token.push(c);
+                    chars.next();
+                }
+                tokens.push_back(token);
+            }
+        }
+        Ok(Tokens { tokens })
+    }
+
+    fn try_next(&mut self) -> Option<String> {
+        self.tokens.pop_front()
+    }
+
+    fn next(&mut self, what: &str) -> Result<String, String> {
+        self.try_next().ok_or_else(|| format!("missing {}", what))
+    }
+
+    fn parse<T: FromStr>(&mut self, what: &str) -> Result<T, String> {
+        let token = self.next(what)?;
+        token
+            .parse()
+            .map_err(|_| format!("invalid {} '{}'", what, token))
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        match self.try_next() {
+            None => Ok(()),
+            Some(extra) => Err(format!("unexpected '{}'", extra)),
+        }
+    }
+}
+
+impl fmt::Display for ParseRecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+impl std::error::Error for ParseRecordingError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut recording = Recording::new();
+        let mouse = MouseEvent {
+            pos: Point::new(10.0, 20.5),
+            window_pos: Point::new(10.0, 20.5),
+            mods: KeyModifiers {
+                shift: true,
+                ..Default::default()
+            },
+            count: 2,
+            button: MouseButton::Right,
+        };
+        let ms = Duration::from_millis;
+        assert!(recording.push_event(ms(5), &Event::MouseDown(mouse)));
+        let key = KeyEvent::for_test(KeyModifiers::default(), "\"", KeyCode::Quote);
+        assert!(recording.push_event(ms(10), &Event::KeyDown(key)));
+        assert!(!recording.push_event(ms(15), &Event::Timer(TimerToken::next())));
+        let cmd = Command::new(Selector::new("my-app.rename"), "a b".to_string());
+        assert!(recording.push_command(ms(20), &cmd));
+        let cmd = Command::new(Selector::new("my-app.open"), vec![1]);
+        assert!(!recording.push_command(ms(25), &cmd));
+
+        let text = recording.to_string();
+        let parsed: Recording = text.parse().unwrap();
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed.to_string(), text);
+
+        match &parsed.entries[0] {
+            (time, Input::Event(Event::MouseDown(mouse))) => {
+                assert_eq!(*time, ms(5));
+                assert_eq!(mouse.pos, Point::new(10.0, 20.5));
+                assert_eq!(mouse.count, 2);
+                assert!(mouse.mods.shift);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match &parsed.entries[2] {
+            (_, Input::Command(cmd)) => {
+                let cmd = cmd.to_command(&mut Selectors::default());
+                assert_eq!(cmd.selector, Selector::new("my-app.rename"));
+                assert_eq!(cmd.get_object::<String>(), Ok(&"a b".to_string()));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn selectors_are_interned() {
+        let recording: Recording = "1 command \"my-app.next\"\n2 command \"my-app.next\""
+            .parse()
+            .unwrap();
+        let mut selectors = Selectors::default();
+        let names: Vec<_> = recording
+            .entries()
+            .map(|(_, input)| match input {
+                Input::Command(cmd) => cmd.to_command(&mut selectors).selector.name(),
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        assert_eq!(names[0], "my-app.next");
+        assert!(std::ptr::eq(names[0], names[1]));
+        assert_eq!(selectors.0.len(), 1);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = "# comment\n\n12 mouse-down 1 2 left".parse::<Recording>();
+        assert_eq!(err.unwrap_err().to_string(), "line 3: missing click count");
+        let err = "12 key-down NotAKey false - \"\" \"\"".parse::<Recording>();
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "line 1: unknown key code 'NotAKey'"
+        );
+    }
+}
@@ -0,0 +1,352 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Running futures in the background.
+
+use std::any::Any;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use crate::{Command, ExtEventSink, Selector, Target};
+
+/// Asks the app to start running the `Task` in the argument.
+pub(crate) const SPAWN_TASK: Selector = Selector::new("druid-builtin.spawn-task");
+
+/// A task for an executor to run: a widget's future, followed by sending
+/// its output back to the app.
+///
+/// See [`AppLauncher::executor`].
+///
+/// [`AppLauncher::executor`]: struct.AppLauncher.html#method.executor
+pub type BackgroundTask = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+type BoxedFuture = Pin<Box<dyn Future<Output = Box<dyn Any + Send>> + Send>>;
+
+/// A future, and where to send its output when it is done.
+pub(crate) struct Task {
+    future: BoxedFuture,
+    selector: Selector,
+    target: Target,
+    cancel: Arc<Cancel>,
+}
+
+/// Lets a widget stop a task it has spawned.
+///
+/// This is returned by [`EventCtx::spawn`]. Dropping it does not cancel the
+/// task.
+///
+/// [`EventCtx::spawn`]: struct.EventCtx.html#method.spawn
+#[derive(Clone)]
+pub struct TaskHandle(Arc<Cancel>);
+
+/// Whether a task has been cancelled, and how to wake it when it is.
+#[derive(Default)]
+struct Cancel {
+    cancelled: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl TaskHandle {
+    /// Stop the task: its future is dropped the next time it would be
+    /// polled, and no command is sent.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        if let Some(waker) = self.0.waker.lock().ok().and_then(|mut w| w.take()) {
+            waker.wake();
+        }
+    }
+
+    /// Whether [`cancel`] has been called.
+    ///
+    /// [`cancel`]: #method.cancel
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Task {
+    pub(crate) fn new<F>(future: F, selector: Selector, target: Target) -> Self
+    where
+        F: Future + Send + 'static,
+        F::Output: Any + Send,
+    {
+        let future = async { Box::new(future.await) as Box<dyn Any + Send> };
+        Task {
+            future: Box::pin(future),
+            selector,
+            target,
+            cancel: Arc::default(),
+        }
+    }
+
+    pub(crate) fn handle(&self) -> TaskHandle {
+        TaskHandle(self.cancel.clone())
+    }
+
+    /// The future to give the executor, which submits the output through
+    /// `sink`, unless the task is cancelled first.
+    pub(crate) fn into_background(self, sink: ExtEventSink) -> BackgroundTask {
+        let Task {
+            future,
+            selector,
+            target,
+            cancel,
+        } = self;
+        Box::pin(async move {
+            let output = match (Cancellable { future, cancel }).await {
+                Some(output) => output,
+                None => return,
+            };
+            if sink
+                .submit_boxed(selector.clone(), Some(output), target)
+                .is_err()
+            {
+                log::warn!("task {} finished after the app went away", selector);
+            }
+        })
+    }
+
+    /// Run the future on this thread, returning the command with its output
+    /// and where it should go, unless the task was cancelled.
+    pub(crate) fn run(self) -> Option<(Target, Command)> {
+        let output = block_on(Cancellable {
+            future: self.future,
+            cancel: self.cancel,
+        })?;
+        Some((self.target, Command::from_ext(self.selector, Some(output))))
+    }
+}
+
+/// A task's future, which stops early if the task is cancelled.
+struct Cancellable {
+    future: BoxedFuture,
+    cancel: Arc<Cancel>,
+}
+
+impl Future for Cancellable {
+    type Output = Option<Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if self.cancel.cancelled.load(Ordering::SeqCst) {
+            return Poll::Ready(None);
+        }
+        if let Ok(mut waker) = self.cancel.waker.lock() {
+            *waker = Some(cx.waker().clone());
+        }
+        self.future.as_mut().poll(cx).map(Some)
+    }
+}
+
+/// Where the app runs background tasks.
+pub(crate) enum Executor {
+    Pool(ThreadPool),
+    /// Given with `AppLauncher::executor`.
+    App(Box<dyn Fn(BackgroundTask)>),
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Executor::Pool(ThreadPool::default())
+    }
+}
+
+impl Executor {
+    pub(crate) fn spawn(&mut self, task: BackgroundTask) {
+        match self {
+            Executor::Pool(pool) => pool.spawn(task),
+            Executor::App(spawn) => spawn(task),
+        }
+    }
+}
+
+/// Runs tasks on as many threads as there are cores, which are started
+/// when the first task arrives.
+#[derive(Default)]
+pub(crate) struct ThreadPool {
+    queue: Option<Sender<Arc<PoolTask>>>,
+}
+
+/// A task in a `ThreadPool`, which goes back in the queue when it is woken.
+struct PoolTask {
+    /// `None` once the task has finished.
+    future: Mutex<Option<BackgroundTask>>,
+    queue: Mutex<Sender<Arc<PoolTask>>>,
+}
+
+impl ThreadPool {
+    fn spawn(&mut self, future: BackgroundTask) {
+        let queue = match &self.queue {
+            Some(queue) => queue.clone(),
+            None => match ThreadPool::start() {
+                Some(queue) => self.queue.get_or_insert(queue).clone(),
+                None => return,
+            },
+        };
+        let task = Arc::new(PoolTask {
+            future: Mutex::new(Some(future)),
+            queue: Mutex::new(queue.clone()),
+        });
+        let _ = queue.send(task);
+    }
+
+    fn start() -> Option<Sender<Arc<PoolTask>>> {
+        let threads = thread::available_parallelism().map_or(4, |n| n.get());
+        let (queue, tasks) = mpsc::channel();
+        let tasks = Arc::new(Mutex::new(tasks));
+        for i in 0..threads {
+            let tasks = tasks.clone();
+            let result = thread::Builder::new()
+                .name(format!("druid task {}", i))
+                .spawn(move || work(&tasks));
+            if let Err(e) = result {
+                log::error!("failed to start a thread for tasks: {}", e);
+                if i == 0 {
+                    return None;
+                }
+                break;
+            }
+        }
+        Some(queue)
+    }
+}
+
+/// Poll tasks from the queue, until the app goes away.
+fn work(tasks: &Mutex<Receiver<Arc<PoolTask>>>) {
+    loop {
+        let task = match tasks.lock().map(|tasks| tasks.recv()) {
+            Ok(Ok(task)) => task,
+            _ => return,
+        };
+        // held while polling, so that a wake in the meantime waits for it
+        let mut slot = match task.future.lock() {
+            Ok(slot) => slot,
+            Err(_) => continue,
+        };
+        if let Some(mut future) = slot.take() {
+            let waker = Waker::from(task.clone());
+            let mut cx = Context::from_waker(&waker);
+            match panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(&mut cx))) {
+                Ok(Poll::Pending) => *slot = Some(future),
+                Ok(Poll::Ready(())) => (),
+                Err(_) => log::error!(
+                    "a task panicked; futures that need an async runtime have to be run by \
+                     one, given with AppLauncher::executor"
+                ),
+            }
+        }
+    }
+}
+
+impl Wake for PoolTask {
+    fn wake(self: Arc<Self>) {
+        if let Ok(queue) = self.queue.lock() {
+            let _ = queue.send(self.clone());
+        }
+    }
+}
+
+/// Wakes a thread that is waiting for a future.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Poll `future` until it is ready, parking the thread whenever it is not.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// A future that is pending until another thread wakes it.
+    struct WakeLater(Arc<AtomicBool>);
+
+    impl Future for WakeLater {
+        type Output = u32;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<u32> {
+            if self.0.load(Ordering::SeqCst) {
+                return Poll::Ready(7);
+            }
+            let done = self.0.clone();
+            let waker = cx.waker().clone();
+            thread::spawn(move || {
+                done.store(true, Ordering::SeqCst);
+                waker.wake();
+            });
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn block_on_waits_for_wake() {
+        let future = WakeLater(Arc::new(AtomicBool::new(false)));
+        assert_eq!(block_on(async { future.await * 6 }), 42);
+    }
+
+    #[test]
+    fn pool_runs_woken_tasks() {
+        let mut pool = ThreadPool::default();
+        let (send, done) = mpsc::channel();
+        for i in 0..10 {
+            let send = send.clone();
+            let future = WakeLater(Arc::new(AtomicBool::new(false)));
+            pool.spawn(Box::pin(async move {
+                let output = future.await + i;
+                let _ = send.send(output);
+            }));
+        }
+        let mut outputs: Vec<u32> = done.iter().take(10).collect();
+        outputs.sort();
+        assert_eq!(outputs, (7..17).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn cancelled_task_sends_nothing() {
+        let target = Target::Global;
+        let task = Task::new(async { 1 }, Selector::new("finished"), target);
+        task.handle().cancel();
+        assert!(task.run().is_none());
+    }
+
+    #[test]
+    fn run_makes_command() {
+        let target = Target::Widget(crate::WidgetId::next());
+        let task = Task::new(async { "done" }, Selector::new("finished"), target);
+        let (to, cmd) = task.run().unwrap();
+        assert_eq!(to, target);
+        assert_eq!(cmd.selector, Selector::new("finished"));
+        assert_eq!(cmd.get_object::<&str>(), Ok(&"done"));
+    }
+}
@@ -0,0 +1,702 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tools and infrastructure for testing widgets.
+//!
+//! The main type here is the [`Harness`], which runs a widget tree without
+//! a real window. What the widgets paint can be captured as a [`Snapshot`];
+//! with the `snapshot` feature, snapshots can be saved as PNG files and
+//! compared against known good ("golden") images.
+//!
+//! To look for bugs in how widgets handle unusual sequences of input,
+//! [`fuzz_events`] can be driven by a fuzzer.
+//!
+//! [`Harness`]: struct.Harness.html
+//! [`Snapshot`]: struct.Snapshot.html
+//! [`fuzz_events`]: fn.fuzz_events.html
+
+use std::cell::RefCell;
+#[cfg(feature = "snapshot")]
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+use crate::core::BaseState;
+use crate::core::CommandQueue;
+use crate::piet::{BitmapTarget, Device, ImageFormat, Piet};
+use crate::recording::{Input, Selectors};
+use crate::task::{Task, SPAWN_TASK};
+use crate::window::PendingWindow;
+use crate::*;
+
+mod fuzz;
+
+pub use fuzz::fuzz_events;
+
+/// The size of the window the harness pretends to have.
+pub const DEFAULT_SIZE: Size = Size::new(400., 400.);
+
+/// The scale the harness paints at, so that snapshots are of a high
+/// resolution display.
+const SCALE: f64 = 2.0;
+
+/// What a [`Harness`] provides in place of the platform.
+///
+/// The harness's window hands this to the contexts, which use it instead
+/// of the window handle where it is there.
+///
+/// [`Harness`]: struct.Harness.html
+#[derive(Default)]
+pub(crate) struct HarnessHost {
+    timers: RefCell<Timers>,
+    /// Broken framework invariants noticed since they were last checked.
+    violations: RefCell<Vec<String>>,
+}
+
+/// A virtual clock, and the timers waiting on it.
+#[derive(Default)]
+struct Timers {
+    /// The time since the harness was created.
+    now: Duration,
+    pending: Vec<(Duration, TimerToken)>,
+}
+
+/// A type that tries very hard to provide a comforting and safe environment
+/// for widgets who are trying to find their way.
+///
+/// You create a `Harness` with some widget and its initial data; then you
+/// can send events to that widget and verify that expected conditions are met.
+///
+/// Harness tries to act like the normal druid environment; for instance, it will
+/// attempt to dispatch any `Command`s that are sent during event handling, and
+/// it will call `update` automatically after an event.
+///
+/// That said, it _is_ missing a bunch of logic that would normally be handled
+/// in `AppState`: for instance it does not clear the `needs_inval` flag on
+/// the window after an update.
+///
+/// In addition, layout and paint **are not called automatically**. This is
+/// because paint is triggered by druid-shell, and there is no druid-shell here;
+///
+/// if you want those functions run you will need to call them yourself.
+///
+/// Timers run on a virtual clock, which only moves when you call
+/// [`advance_time`]; this keeps tests that use them fast and deterministic.
+///
+/// Futures started with [`EventCtx::spawn`] are run to completion as soon
+/// as the commands of the current event are processed.
+///
+/// Text is measured as if every grapheme were half as wide as the font size,
+/// so that the layout of labels and text boxes is the same on every
+/// platform. Text is still drawn with the platform's fonts, so snapshots of
+/// it will differ.
+///
+/// # Examples
+///
+/// ```
+/// use druid::testing::Harness;
+/// use druid::widget::Button;
+/// use druid::{Point, WidgetExt};
+///
+/// let button = Button::new("+1", |_, data: &mut u32, _| *data += 1).fix_size(100., 40.);
+/// Harness::create(0, button, |harness| {
+///     harness.send_initial_events();
+///     harness.just_layout();
+///     harness.click(Point::new(20., 20.));
+///     assert_eq!(*harness.data(), 1);
+/// });
+/// ```
+///
+/// [`advance_time`]: #method.advance_time
+/// [`EventCtx::spawn`]: ../struct.EventCtx.html#method.spawn
+pub struct Harness<'a, T> {
+    piet: Piet<'a>,
+    inner: Inner<T>,
+}
+
+/// All of the state except for the `Piet` (render context). We need to pass
+/// that in to get around some lifetime issues.
+struct Inner<T> {
+    data: T,
+    env: Env,
+    window: Window<T>,
+    cmds: CommandQueue,
+    host: Rc<HarnessHost>,
+}
+
+/// A way to clean up resources when our target goes out of scope.
+// the inner type is an option so that we can take ownership in `drop` even
+// though self is `& mut`.
+struct TargetGuard<'a>(Option<BitmapTarget<'a>>);
+
+/// The pixels painted by a [`Harness`].
+///
+/// The window is painted at twice its size, so a snapshot has two pixels
+/// for each unit of the window in each direction.
+///
+/// [`Harness`]: struct.Harness.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    width: usize,
+    height: usize,
+    /// RGBA, with premultiplied alpha.
+    pixels: Vec<u8>,
+}
+
+impl<T: Data> Harness<'_, T> {
+    /// Create a new `Harness` with the given data and a root widget,
+    /// and provide that harness to the passed in function.
+    ///
+    /// The harness borrows the render target it paints into, which lives in
+    /// this function, so it is lent to `f` instead of returned.
+    pub fn create(data: T, root: impl Widget<T> + 'static, f: impl FnMut(&mut Harness<T>)) {
+        Harness::create_with_render(data, root, f, |_| ())
+    }
+
+    /// Like [`create`], but once the harness closure returns, `render` is
+    /// given a [`Snapshot`] of what was last painted.
+    ///
+    /// Call [`paint`] in the harness closure to have something to capture.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use druid::testing::Harness;
+    /// use druid::widget::Label;
+    ///
+    /// Harness::create_with_render(
+    ///     (),
+    ///     Label::new("hello"),
+    ///     |harness| {
+    ///         harness.send_initial_events();
+    ///         harness.paint();
+    ///     },
+    ///     |snapshot| assert_eq!(snapshot.width(), 800),
+    /// );
+    /// ```
+    ///
+    /// [`create`]: #method.create
+    /// [`paint`]: #method.paint
+    /// [`Snapshot`]: struct.Snapshot.html
+    pub fn create_with_render(
+        data: T,
+        root: impl Widget<T> + 'static,
+        mut f: impl FnMut(&mut Harness<T>),
+        mut render: impl FnMut(Snapshot),
+    ) {
+        let width = (DEFAULT_SIZE.width * SCALE) as usize;
+        let height = (DEFAULT_SIZE.height * SCALE) as usize;
+        let mut device = Device::new().expect("harness failed to get device");
+        let target = device
+            .bitmap_target(width, height, SCALE)
+            .expect("bitmap_target");
+        let mut target = TargetGuard(Some(target));
+        let piet = target.0.as_mut().unwrap().render_context();
+
+        let host = Rc::new(HarnessHost::default());
+        let mut window = PendingWindow::new(root, LocalizedString::new(""), None)
+            .into_window(WindowId::next(), Default::default());
+        window.harness = Some(host.clone());
+        let inner = Inner {
+            data,
+            env: theme::init().adding(Env::MOCK_TEXT_METRICS, true),
+            window,
+            cmds: Default::default(),
+            host,
+        };
+
+        let mut harness = Harness { piet, inner };
+        f(&mut harness);
+        harness
+            .piet
+            .finish()
+            .expect("harness failed to finish painting");
+        drop(harness);
+
+        let pixels = target
+            .0
+            .take()
+            .unwrap()
+            .into_raw_pixels(ImageFormat::RgbaPremul)
+            .expect("harness failed to read pixels");
+        render(Snapshot {
+            width,
+            height,
+            pixels,
+        });
+    }
+
+    pub fn window(&self) -> &Window<T> {
+        &self.inner.window
+    }
+
+    pub fn window_mut(&mut self) -> &mut Window<T> {
+        &mut self.inner.window
+    }
+
+    /// The current data.
+    pub fn data(&self) -> &T {
+        &self.inner.data
+    }
+
+    /// Change the data from outside the widget tree, then call `update`.
+    pub fn update_data(&mut self, f: impl FnOnce(&mut T)) {
+        f(&mut self.inner.data);
+        self.update();
+    }
+
+    /// Retrieve a copy of this widget's `BaseState`, or die trying.
+    #[cfg(test)]
+    pub(crate) fn get_state(&mut self, widget: WidgetId) -> BaseState {
+        match self.try_get_state(widget) {
+            Some(thing) => thing,
+            None => panic!("get_state failed for widget {:?}", widget),
+        }
+    }
+
+    /// Attempt to retrieve a copy of this widget's `BaseState`.
+    #[cfg(test)]
+    pub(crate) fn try_get_state(&mut self, widget: WidgetId) -> Option<BaseState> {
+        let cell = StateCell::default();
+        let state_cell = cell.clone();
+        self.lifecycle(LifeCycle::DebugRequestState { widget, state_cell });
+        cell.take()
+    }
+
+    /// Inspect the `BaseState` of each widget in the tree.
+    ///
+    /// The provided closure will be called on each widget.
+    #[cfg(test)]
+    #[allow(dead_code)]
+    pub(crate) fn inspect_state(&mut self, f: impl Fn(&BaseState) + 'static) {
+        let checkfn = StateCheckFn::new(f);
+        self.lifecycle(LifeCycle::DebugInspectState(checkfn))
+    }
+
+    /// The layout rect of the widget with this id, in its parent's
+    /// coordinate space, as of the last layout pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such widget.
+    pub fn layout_rect(&mut self, widget: WidgetId) -> Rect {
+        let tree = self.widget_tree();
+        let widgets = tree.widgets();
+        let idx = widgets
+            .iter()
+            .position(|info| info.id == widget)
+            .unwrap_or_else(|| panic!("layout_rect failed for widget {:?}", widget));
+        let info = &widgets[idx];
+        // the parent is the nearest widget before this one that is one
+        // level up
+        let parent_origin = widgets[..idx]
+            .iter()
+            .rev()
+            .find(|parent| parent.depth + 1 == info.depth)
+            .map(|parent| parent.window_rect.origin())
+            .unwrap_or(Point::ORIGIN);
+        info.window_rect - parent_origin.to_vec2()
+    }
+
+    /// Describe the widgets in the window.
+    ///
+    /// The layout rects are as of the last layout pass.
+    pub fn widget_tree(&mut self) -> WidgetTree {
+        let inner = &mut self.inner;
+        inner
+            .window
+            .widget_tree(&mut inner.cmds, &inner.data, &inner.env)
+    }
+
+    /// Send a command to a target.
+    pub fn submit_command(&mut self, cmd: impl Into<Command>, target: impl Into<Option<Target>>) {
+        let target = target.into().unwrap_or_else(|| self.inner.window.id.into());
+        let event = Event::TargetedCommand(target, cmd.into());
+        self.event(event);
+    }
+
+    /// Send the events that would normally be sent when the app starts.
+    // should we do this automatically? Also these will change regularly?
+    pub fn send_initial_events(&mut self) {
+        self.event(Event::WindowConnected);
+        self.event(Event::Size(DEFAULT_SIZE));
+    }
+
+    /// Send an event to the widget.
+    ///
+    /// Any lifecycle events it triggers are dispatched too. The commands
+    /// submitted while handling it are then sent, each as an event of its
+    /// own, and finally the widgets are updated with the new data. Widgets
+    /// can't submit commands during `update`; those submitted while painting
+    /// wait for the next event.
+    pub fn event(&mut self, event: Event) {
+        self.inner.event(event);
+        self.process_commands();
+        self.update();
+    }
+
+    /// Move the mouse to `pos`, in window coordinates.
+    pub fn mouse_move(&mut self, pos: impl Into<Point>) {
+        self.event(Event::MouseMoved(mouse_event(pos.into(), 0)));
+    }
+
+    /// Press the left mouse button at `pos`.
+    pub fn mouse_down(&mut self, pos: impl Into<Point>) {
+        self.event(Event::MouseDown(mouse_event(pos.into(), 1)));
+    }
+
+    /// Release the left mouse button at `pos`.
+    pub fn mouse_up(&mut self, pos: impl Into<Point>) {
+        self.event(Event::MouseUp(mouse_event(pos.into(), 0)));
+    }
+
+    /// Move the mouse to `pos`, then press and release the left button.
+    pub fn click(&mut self, pos: impl Into<Point>) {
+        let pos = pos.into();
+        self.mouse_move(pos);
+        self.mouse_down(pos);
+        self.mouse_up(pos);
+    }
+
+    /// Press and release a key.
+    ///
+    /// Key events can be made with [`KeyEvent::for_test`].
+    ///
+    /// [`KeyEvent::for_test`]: ../struct.KeyEvent.html#method.for_test
+    pub fn key_press(&mut self, key: KeyEvent) {
+        self.event(Event::KeyDown(key));
+        self.event(Event::KeyUp(key));
+    }
+
+    /// Move the virtual clock forward, sending the timers that come due, in
+    /// the order of their deadlines.
+    ///
+    /// Timers requested in response are sent too, if they are due before the
+    /// new time.
+    pub fn advance_time(&mut self, duration: Duration) {
+        let now = {
+            let mut timers = self.inner.host.timers.borrow_mut();
+            timers.now += duration;
+            timers.now
+        };
+        while let Some(token) = self.inner.host.next_due_timer(now) {
+            self.event(Event::Timer(token));
+        }
+    }
+
+    /// Send the events in a recording, advancing the virtual clock between
+    /// them as the recorded times say, so that timers fire where they did.
+    ///
+    /// Commands in the recording are sent to the window.
+    pub fn replay(&mut self, recording: &Recording) {
+        let mut selectors = Selectors::default();
+        let mut last = Duration::from_secs(0);
+        for (time, input) in recording.entries() {
+            if *time > last {
+                self.advance_time(*time - last);
+                last = *time;
+            }
+            match input {
+                Input::Event(event) => self.event(event.clone()),
+                Input::Command(cmd) => self.submit_command(cmd.to_command(&mut selectors), None),
+            }
+        }
+    }
+
+    fn process_commands(&mut self) {
+        loop {
+            let cmd = self.inner.cmds.pop_front();
+            match cmd {
+                Some((_, cmd)) if cmd.selector == SPAWN_TASK => match cmd.take_object::<Task>() {
+                    Ok(task) => {
+                        if let Some((target, cmd)) = task.run() {
+                            self.event(Event::TargetedCommand(target, cmd));
+                        }
+                    }
+                    Err(e) => log::warn!("spawn-task object error: '{}'", e),
+                },
+                Some((target, cmd)) => self.event(Event::TargetedCommand(target, cmd)),
+                None => break,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    fn lifecycle(&mut self, event: LifeCycle) {
+        self.inner.lifecycle(event)
+    }
+
+    //TODO: should we expose this? I don't think so?
+    fn update(&mut self) {
+        self.inner.update()
+    }
+
+    /// Only do a layout pass, without painting
+    pub fn just_layout(&mut self) {
+        self.inner.layout(&mut self.piet)
+    }
+
+    /// Paint the window. This includes a layout pass, if one is needed.
+    pub fn paint(&mut self) {
+        self.inner.paint(&mut self.piet)
+    }
+}
+
+impl<T: Data> Inner<T> {
+    fn event(&mut self, event: Event) {
+        self.window
+            .event(&mut self.cmds, event, &mut self.data, &self.env);
+    }
+
+    fn lifecycle(&mut self, event: LifeCycle) {
+        self.window
+            .lifecycle(&mut self.cmds, &event, &self.data, &self.env);
+    }
+
+    fn update(&mut self) {
+        self.window.update(&self.data, &self.env);
+        // as the app does, add any widgets that were created in update
+        if self.window.root.state().children_changed {
+            self.lifecycle(LifeCycle::WidgetAdded);
+        }
+    }
+
+    fn layout(&mut self, piet: &mut Piet) {
+        self.window.just_layout(piet, &self.data, &self.env);
+    }
+
+    fn paint(&mut self, piet: &mut Piet) {
+        self.window
+            .do_paint(piet, &mut self.cmds, &self.data, &self.env);
+    }
+}
+
+fn mouse_event(pos: Point, count: u32) -> MouseEvent {
+    MouseEvent {
+        pos,
+        window_pos: pos,
+        mods: KeyModifiers::default(),
+        count,
+        button: MouseButton::Left,
+    }
+}
+
+impl HarnessHost {
+    /// Schedule a timer on the virtual clock.
+    pub(crate) fn request_timer(&self, deadline: Instant) -> TimerToken {
+        let mut timers = self.timers.borrow_mut();
+        let delay = deadline.saturating_duration_since(Instant::now());
+        let token = TimerToken::next();
+        let due = timers.now + delay;
+        timers.pending.push((due, token));
+        token
+    }
+
+    /// Note that the framework has broken one of its own rules, such as
+    /// painting a widget that was never laid out, so that [`fuzz_events`]
+    /// can fail.
+    ///
+    /// [`fuzz_events`]: fn.fuzz_events.html
+    pub(crate) fn report_violation(&self, msg: String) {
+        self.violations.borrow_mut().push(msg);
+    }
+
+    /// Remove and return the timer with the earliest deadline, if it is due.
+    fn next_due_timer(&self, now: Duration) -> Option<TimerToken> {
+        let pending = &mut self.timers.borrow_mut().pending;
+        let (idx, _) = pending
+            .iter()
+            .enumerate()
+            .filter(|(_, (deadline, _))| *deadline <= now)
+            .min_by_key(|(_, (deadline, _))| *deadline)?;
+        Some(pending.remove(idx).1)
+    }
+
+    /// Remove and return the violations reported so far.
+    fn take_violations(&self) -> Vec<String> {
+        self.violations.replace(Vec::new())
+    }
+}
+
+impl Snapshot {
+    /// The width, in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height, in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The pixels, as RGBA with premultiplied alpha, row by row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The number of pixels that differ from those in `other` by more than
+    /// `tolerance` in any channel.
+    ///
+    /// Snapshots of different sizes differ in every pixel.
+    pub fn count_differences(&self, other: &Snapshot, tolerance: u8) -> usize {
+        if (self.width, self.height) != (other.width, other.height) {
+            return self.width.max(other.width) * self.height.max(other.height);
+        }
+        self.pixels
+            .chunks(4)
+            .zip(other.pixels.chunks(4))
+            .filter(|(a, b)| {
+                a.iter()
+                    .zip(b.iter())
+                    .any(|(a, b)| (i16::from(*a) - i16::from(*b)).abs() > i16::from(tolerance))
+            })
+            .count()
+    }
+
+    /// Save this snapshot as a PNG file.
+    #[cfg(feature = "snapshot")]
+    pub fn save_png(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(to_io_error)?;
+        let pixels: Vec<u8> = self.pixels.chunks(4).flat_map(unpremultiply).collect();
+        writer.write_image_data(&pixels).map_err(to_io_error)
+    }
+
+    /// Load a snapshot from an 8-bit RGBA PNG file, such as one written by
+    /// [`save_png`].
+    ///
+    /// [`save_png`]: #method.save_png
+    #[cfg(feature = "snapshot")]
+    pub fn load_png(path: impl AsRef<Path>) -> std::io::Result<Snapshot> {
+        let decoder = png::Decoder::new(std::fs::File::open(path)?);
+        let (info, mut reader) = decoder.read_info().map_err(to_io_error)?;
+        if info.color_type != png::ColorType::RGBA || info.bit_depth != png::BitDepth::Eight {
+            return Err(to_io_error("snapshots must be 8-bit RGBA"));
+        }
+        let mut pixels = vec![0; info.buffer_size()];
+        reader.next_frame(&mut pixels).map_err(to_io_error)?;
+        Ok(Snapshot {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels: pixels.chunks(4).flat_map(premultiply).collect(),
+        })
+    }
+
+    /// Compare this snapshot against the golden image at `path`, allowing
+    /// each channel to differ by `tolerance`.
+    ///
+    /// If there is no golden image yet, or the `DRUID_UPDATE_SNAPSHOTS`
+    /// environment variable is set, this snapshot is saved as the golden
+    /// image instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot does not match. This snapshot is then saved
+    /// next to the golden image, with `.actual` added to its name, so that
+    /// the two can be compared.
+    #[cfg(feature = "snapshot")]
+    pub fn assert_matches_golden(&self, path: impl AsRef<Path>, tolerance: u8) {
+        let path = path.as_ref();
+        if !path.exists() || std::env::var_os("DRUID_UPDATE_SNAPSHOTS").is_some() {
+            self.save_png(path).expect("failed to save golden image");
+            return;
+        }
+        let golden = Snapshot::load_png(path).expect("failed to load golden image");
+        let differences = self.count_differences(&golden, tolerance);
+        if differences > 0 {
+            let actual = path.with_extension("actual.png");
+            let _ = self.save_png(&actual);
+            panic!(
+                "{} pixels differ from {}; the snapshot was saved to {}",
+                differences,
+                path.display(),
+                actual.display()
+            );
+        }
+    }
+}
+
+// `io::Error::other` needs Rust 1.74
+#[cfg(feature = "snapshot")]
+#[allow(clippy::io_other_error)]
+fn to_io_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, e)
+}
+
+#[cfg(any(feature = "snapshot", test))]
+fn unpremultiply(px: &[u8]) -> Vec<u8> {
+    let alpha = u16::from(px[3]);
+    if alpha == 0 {
+        return vec![0, 0, 0, 0];
+    }
+    let channel = |c: u8| ((u16::from(c) * 255 + alpha / 2) / alpha).min(255) as u8;
+    vec![channel(px[0]), channel(px[1]), channel(px[2]), px[3]]
+}
+
+#[cfg(any(feature = "snapshot", test))]
+fn premultiply(px: &[u8]) -> Vec<u8> {
+    let alpha = u16::from(px[3]);
+    let channel = |c: u8| ((u16::from(c) * alpha + 127) / 255) as u8;
+    vec![channel(px[0]), channel(px[1]), channel(px[2]), px[3]]
+}
+
+impl Drop for TargetGuard<'_> {
+    fn drop(&mut self) {
+        // we need to call this to clean up the context
+        let _ = self
+            .0
+            .take()
+            .map(|t| t.into_raw_pixels(piet::ImageFormat::RgbaPremul));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(pixels: Vec<u8>) -> Snapshot {
+        Snapshot {
+            width: pixels.len() / 4,
+            height: 1,
+            pixels,
+        }
+    }
+
+    #[test]
+    fn differences_respect_tolerance() {
+        let a = snapshot(vec![10, 20, 30, 255, 0, 0, 0, 255]);
+        let b = snapshot(vec![12, 20, 30, 255, 0, 0, 9, 255]);
+        assert_eq!(a.count_differences(&b, 0), 2);
+        assert_eq!(a.count_differences(&b, 2), 1);
+        assert_eq!(a.count_differences(&b, 9), 0);
+        assert_eq!(a.count_differences(&snapshot(vec![0; 12]), 255), 3);
+    }
+
+    #[test]
+    fn premultiplied_alpha_round_trips() {
+        for px in &[
+            [255, 128, 0, 255],
+            [100, 50, 0, 128],
+            [3, 2, 1, 4],
+            [0, 0, 0, 0],
+        ] {
+            let straight = unpremultiply(px);
+            assert_eq!(premultiply(&straight), px.to_vec());
+        }
+    }
+}
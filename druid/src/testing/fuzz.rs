@@ -0,0 +1,262 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sending widgets arbitrary sequences of events, to find lifecycle bugs.
+
+use std::time::Duration;
+
+use super::{Harness, DEFAULT_SIZE};
+use crate::kurbo::Vec2;
+use crate::*;
+
+/// The keys that are pressed, with the text they produce.
+const KEYS: &[(KeyCode, &str)] = &[
+    (KeyCode::Tab, ""),
+    (KeyCode::Return, "\r"),
+    (KeyCode::Escape, ""),
+    (KeyCode::Backspace, ""),
+    (KeyCode::Delete, ""),
+    (KeyCode::ArrowLeft, ""),
+    (KeyCode::ArrowRight, ""),
+    (KeyCode::ArrowUp, ""),
+    (KeyCode::ArrowDown, ""),
+    (KeyCode::Home, ""),
+    (KeyCode::End, ""),
+    (KeyCode::Space, " "),
+    (KeyCode::KeyA, "a"),
+    (KeyCode::KeyX, "x"),
+    (KeyCode::Key1, "1"),
+];
+
+/// The input decoded from the fuzzer's bytes.
+struct Input<'a>(&'a [u8]);
+
+impl Input<'_> {
+    fn byte(&mut self) -> Option<u8> {
+        let (first, rest) = self.0.split_first()?;
+        self.0 = rest;
+        Some(*first)
+    }
+
+    /// A position in the window, or a little outside it.
+    fn point(&mut self) -> Option<Point> {
+        let coord = |b: u8, max: f64| f64::from(b) / 255.0 * (max + 20.0) - 10.0;
+        let x = coord(self.byte()?, DEFAULT_SIZE.width);
+        let y = coord(self.byte()?, DEFAULT_SIZE.height);
+        Some(Point::new(x, y))
+    }
+
+    fn mods(&mut self) -> Option<KeyModifiers> {
+        let bits = self.byte()?;
+        Some(KeyModifiers {
+            shift: bits & 1 != 0,
+            alt: bits & 2 != 0,
+            ctrl: bits & 4 != 0,
+            meta: bits & 8 != 0,
+        })
+    }
+
+    fn mouse(&mut self) -> Option<MouseEvent> {
+        let pos = self.point()?;
+        let detail = self.byte()?;
+        let button = match detail % 3 {
+            0 => MouseButton::Left,
+            1 => MouseButton::Right,
+            _ => MouseButton::Middle,
+        };
+        Some(MouseEvent {
+            pos,
+            window_pos: pos,
+            mods: self.mods()?,
+            count: u32::from(detail / 3 % 3),
+            button,
+        })
+    }
+
+    fn key(&mut self) -> Option<KeyEvent> {
+        let (code, text) = KEYS[usize::from(self.byte()?) % KEYS.len()];
+        let mods = self.mods()?;
+        Some(KeyEvent::from_parts(code, false, mods, text, text))
+    }
+}
+
+/// Send `root` a sequence of events decoded from `bytes`, checking after each
+/// one that the framework's invariants still hold.
+///
+/// Every sequence of bytes is valid input. The events include mouse
+/// movement, clicks and scrolling, key presses, resizing the window, the
+/// passing of time, and layout and paint passes, in whatever order the bytes
+/// say; this is meant to turn up the orders a widget's author didn't think
+/// of.
+///
+/// This is intended to be called from a fuzzer, such as `cargo fuzz`:
+///
+/// ```ignore
+/// fuzz_target!(|bytes: &[u8]| {
+///     druid::testing::fuzz_events(AppState::default(), make_ui(), bytes);
+/// });
+/// ```
+///
+/// # Panics
+///
+/// Panics if a widget panics, or if one of these is broken:
+///
+/// - every widget is laid out before it is painted;
+/// - after a layout pass, no widget still needs layout;
+/// - the focused widget, if there is one, is in the tree.
+pub fn fuzz_events<T: Data>(data: T, root: impl Widget<T> + 'static, bytes: &[u8]) {
+    Harness::create(data, root, |harness| {
+        let mut input = Input(bytes);
+        harness.send_initial_events();
+        check_invariants(harness, "the initial events");
+        while let Some(op) = input.byte() {
+            let action = match step(harness, op, &mut input) {
+                Some(action) => action,
+                None => break,
+            };
+            check_invariants(harness, action);
+        }
+    });
+}
+
+/// Do the action `op` selects, returning its name, or `None` if the input
+/// ran out.
+fn step<T: Data>(harness: &mut Harness<T>, op: u8, input: &mut Input) -> Option<&'static str> {
+    let action = match op % 10 {
+        0 => {
+            harness.event(Event::MouseMoved(input.mouse()?));
+            "mouse move"
+        }
+        1 => {
+            harness.event(Event::MouseDown(input.mouse()?));
+            "mouse down"
+        }
+        2 => {
+            harness.event(Event::MouseUp(input.mouse()?));
+            "mouse up"
+        }
+        3 => {
+            let dx = f64::from(input.byte()? as i8);
+            let dy = f64::from(input.byte()? as i8);
+            let mods = input.mods()?;
+            harness.event(Event::Wheel(WheelEvent {
+                delta: Vec2::new(dx, dy),
+                mods,
+            }));
+            "wheel"
+        }
+        4 => {
+            harness.key_press(input.key()?);
+            "key press"
+        }
+        5 => {
+            harness.event(Event::KeyDown(input.key()?));
+            "key down"
+        }
+        6 => {
+            let width = f64::from(input.byte()?) * 2.0;
+            let height = f64::from(input.byte()?) * 2.0;
+            harness.event(Event::Size(Size::new(width, height)));
+            "resize"
+        }
+        7 => {
+            let millis = u64::from(input.byte()?) * 4;
+            harness.advance_time(Duration::from_millis(millis));
+            "advance time"
+        }
+        8 => {
+            harness.just_layout();
+            if harness.inner.window.root.state().needs_layout {
+                panic!("a widget still needs layout after a layout pass");
+            }
+            "layout"
+        }
+        _ => {
+            harness.paint();
+            "paint"
+        }
+    };
+    Some(action)
+}
+
+fn check_invariants<T: Data>(harness: &mut Harness<T>, after: &str) {
+    let violations = harness.inner.host.take_violations();
+    if !violations.is_empty() {
+        panic!("after {}: {}", after, violations.join("; "));
+    }
+    if let Some(focus) = harness.inner.window.focus {
+        if harness.widget_tree().get(focus).is_none() {
+            panic!(
+                "after {}: the focused widget {:?} is not in the tree",
+                after, focus
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::{Button, Checkbox, Flex, Label, TextBox, WidgetExt};
+
+    /// Some text, whether a box is checked, and how many clicks there were.
+    type Form = (String, bool, u32);
+
+    fn form() -> impl Widget<Form> {
+        Flex::column()
+            .with_child(TextBox::new().lens(lens!(Form, 0)), 0.0)
+            .with_child(Checkbox::new().lens(lens!(Form, 1)), 0.0)
+            .with_child(
+                Button::new("click", |_, data: &mut Form, _| data.2 += 1),
+                1.0,
+            )
+    }
+
+    #[test]
+    fn random_events_keep_invariants() {
+        // a small linear congruential generator, so that the test is the same
+        // on every run.
+        let mut seed = 0x2545_f491_u32;
+        let bytes: Vec<u8> = (0..4096)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (seed >> 16) as u8
+            })
+            .collect();
+        for chunk in bytes.chunks(512) {
+            fuzz_events((String::new(), false, 0), form(), chunk);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "painted before it was laid out")]
+    fn painting_without_layout_is_caught() {
+        struct NoLayout;
+
+        impl Widget<()> for NoLayout {
+            fn event(&mut self, _: &mut EventCtx, _: &Event, _: &mut (), _: &Env) {}
+            fn lifecycle(&mut self, _: &mut LifeCycleCtx, _: &LifeCycle, _: &(), _: &Env) {}
+            fn update(&mut self, _: &mut UpdateCtx, _: &(), _: &(), _: &Env) {}
+            fn layout(&mut self, _: &mut LayoutCtx, bc: &BoxConstraints, _: &(), _: &Env) -> Size {
+                bc.max()
+            }
+            fn paint(&mut self, ctx: &mut PaintCtx, data: &(), env: &Env) {
+                // the child is never laid out
+                WidgetPod::new(Label::new("oops")).paint(ctx, data, env);
+            }
+        }
+
+        fuzz_events((), NoLayout, &[9]);
+    }
+}
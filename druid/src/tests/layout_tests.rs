@@ -171,3 +171,37 @@ fn flex_paint_rect_overflow() {
         assert_eq!(state.paint_rect().size(), expected_paint_rect.size());
     })
 }
+
+#[test]
+fn right_to_left_row() {
+    let (id1, id2, id3) = widget_id3();
+
+    let row = Flex::row()
+        .with_child(SizedBox::empty().width(100.).with_id(id1), 0.0)
+        .with_child(
+            SizedBox::empty()
+                .width(50.)
+                .with_id(id3)
+                .padding((10., 0., 30., 0.))
+                .with_id(id2),
+            0.0,
+        );
+    let widget = EnvScope::new(|env, _| env.set(Env::RIGHT_TO_LEFT, true), row);
+
+    Harness::create((), widget, |harness| {
+        harness.send_initial_events();
+        harness.just_layout();
+
+        // the row is 100 + (50 + 40) wide, and the first child is at the right
+        assert_eq!(
+            harness.get_state(id1).layout_rect.origin(),
+            Point::new(90., 0.)
+        );
+        assert_eq!(harness.get_state(id2).layout_rect.origin(), Point::ZERO);
+        // the left and right padding are swapped
+        assert_eq!(
+            harness.get_state(id3).layout_rect.origin(),
+            Point::new(30., 0.)
+        );
+    })
+}
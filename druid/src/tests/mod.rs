@@ -14,17 +14,19 @@
 
 //! Additional unit tests that cross file or module boundaries.
 
-mod harness;
 mod helpers;
 mod layout_tests;
 
 use std::cell::Cell;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
+use crate::testing::*;
 use crate::widget::*;
 use crate::*;
-use harness::*;
 use helpers::*;
+// not the event `Recording` from the crate root
+use helpers::Recording;
 
 /// test that the first widget to request focus during an event gets it.
 #[test]
@@ -285,3 +287,442 @@ fn child_tracking() {
         assert_eq!(split.children.entry_count(), 2);
     });
 }
+
+#[test]
+fn tab_focus_traversal() {
+    /// A widget that is focusable but doesn't handle any keys itself.
+    fn make_focusable() -> impl Widget<bool> {
+        ModularWidget::new(()).lifecycle_fn(|_, ctx, event, _data, _env| {
+            if let LifeCycle::WidgetAdded = event {
+                ctx.register_for_focus();
+            }
+        })
+    }
+
+    let (id_1, id_2, id_3) = widget_id3();
+    let widget = Flex::row()
+        .with_child(make_focusable().with_id(id_1), 1.0)
+        .with_child(make_focusable().with_id(id_2), 1.0)
+        .with_child(make_focusable().with_id(id_3), 1.0);
+
+    let tab = KeyEvent::for_test(RawMods::None, "\t", KeyCode::Tab);
+    let shift_tab = KeyEvent::for_test(RawMods::Shift, "\t", KeyCode::Tab);
+
+    Harness::create(true, widget, |harness| {
+        harness.send_initial_events();
+        assert_eq!(harness.window().focus, None);
+
+        // with nothing focused, tab starts at the beginning of the chain
+        harness.event(Event::KeyDown(tab));
+        assert_eq!(harness.window().focus, Some(id_1));
+        harness.event(Event::KeyDown(tab));
+        assert_eq!(harness.window().focus, Some(id_2));
+
+        harness.event(Event::KeyDown(shift_tab));
+        assert_eq!(harness.window().focus, Some(id_1));
+        // and wraps around at the ends
+        harness.event(Event::KeyDown(shift_tab));
+        assert_eq!(harness.window().focus, Some(id_3));
+    })
+}
+
+#[test]
+fn keyboard_toggles_checkbox() {
+    let tab = KeyEvent::for_test(RawMods::None, "\t", KeyCode::Tab);
+    let space = KeyEvent::for_test(RawMods::None, " ", KeyCode::Space);
+
+    Harness::create(false, Checkbox::new(), |harness| {
+        harness.send_initial_events();
+        harness.event(Event::KeyDown(space));
+        // not focused yet, so nothing should happen
+        assert!(!*harness.data());
+
+        harness.event(Event::KeyDown(tab));
+        harness.event(Event::KeyDown(space));
+        assert!(*harness.data());
+        harness.event(Event::KeyDown(space));
+        assert!(!*harness.data());
+    })
+}
+
+#[test]
+fn text_scale_is_relative() {
+    let mut env = theme::init();
+    assert_eq!(env.get(theme::TEXT_SIZE_NORMAL), 15.0);
+
+    theme::apply_text_scale(&mut env, 2.0);
+    assert_eq!(env.get(theme::TEXT_SIZE_NORMAL), 30.0);
+    assert_eq!(env.get(theme::TEXT_SCALE), 2.0);
+
+    // a later change replaces the previous scale instead of compounding it
+    theme::apply_text_scale(&mut env, 1.5);
+    assert_eq!(env.get(theme::TEXT_SIZE_NORMAL), 22.5);
+}
+
+#[test]
+fn menu_fn_rebuilds_on_data_change() {
+    use crate::window::{PendingWindow, WindowId};
+
+    let calls = Rc::new(Cell::new(0));
+    let mut pending = PendingWindow::new(SizedBox::empty(), LocalizedString::new(""), None);
+    pending.menu_fn = Some(Box::new({
+        let calls = calls.clone();
+        move |recent: &u32, _env: &Env| {
+            calls.set(calls.get() + 1);
+            MenuDesc::empty().append_iter(|| {
+                (0..*recent).map(|_| MenuItem::new(LocalizedString::new(""), Selector::NOOP))
+            })
+        }
+    }));
+    let mut window = pending.into_window(WindowId::next(), Default::default());
+    let env = theme::init();
+
+    window.update(&2, &env);
+    assert_eq!(window.menu.as_ref().map(MenuDesc::len), Some(2));
+
+    // unchanged data does not rebuild the menu
+    window.update(&2, &env);
+    assert_eq!(calls.get(), 1);
+
+    window.update(&3, &env);
+    assert_eq!(calls.get(), 2);
+    assert_eq!(window.menu.as_ref().map(MenuDesc::len), Some(3));
+}
+
+#[test]
+fn menu_fn_is_not_rebuilt_for_the_data_it_was_first_built_from() {
+    use crate::window::{PendingWindow, WindowId};
+
+    let calls = Rc::new(Cell::new(0));
+    let mut pending = PendingWindow::new(SizedBox::empty(), LocalizedString::new(""), None);
+    pending.menu_fn = Some(Box::new({
+        let calls = calls.clone();
+        move |_: &u32, _env: &Env| {
+            calls.set(calls.get() + 1);
+            MenuDesc::empty()
+        }
+    }));
+    // as the app does when it builds the window's first menu
+    pending.menu_data = Some(2);
+    let mut window = pending.into_window(WindowId::next(), Default::default());
+
+    window.update(&2, &theme::init());
+    assert_eq!(calls.get(), 0);
+}
+
+#[test]
+fn drag_enter_leave_and_drop() {
+    let (empty, target) = widget_id2();
+    let target_rec = Recording::default();
+
+    let widget = Split::vertical(
+        SizedBox::empty().with_id(empty),
+        SizedBox::empty()
+            .expand()
+            .record(&target_rec)
+            .with_id(target),
+    );
+
+    let drag = |x| {
+        Event::DragMoved(DragEvent {
+            pos: Point::new(x, 10.),
+        })
+    };
+
+    Harness::create((), widget, |harness| {
+        harness.send_initial_events();
+        harness.just_layout();
+        target_rec.clear();
+
+        harness.event(drag(210.));
+        assert!(harness.get_state(target).is_drag_hot);
+        assert_matches!(target_rec.next(), Record::E(Event::DragEnter(_)));
+        assert_matches!(target_rec.next(), Record::E(Event::DragMoved(_)));
+        assert!(target_rec.is_empty());
+
+        harness.event(drag(10.));
+        assert!(!harness.get_state(target).is_drag_hot);
+        assert_matches!(target_rec.next(), Record::E(Event::DragLeave));
+        assert_matches!(target_rec.next(), Record::E(Event::DragMoved(_)));
+        assert!(target_rec.is_empty());
+
+        harness.event(drag(210.));
+        target_rec.clear();
+        harness.event(Event::Drop(DropEvent {
+            pos: Point::new(210., 10.),
+            data: DragData::default(),
+        }));
+        assert!(!harness.get_state(target).is_drag_hot);
+        assert_matches!(target_rec.next(), Record::E(Event::Drop(_)));
+        assert!(target_rec.is_empty());
+
+        // the end of an outgoing drag reaches widgets wherever they are
+        harness.event(Event::DragEnded(Some(DropAction::Copy)));
+        assert_matches!(
+            target_rec.next(),
+            Record::E(Event::DragEnded(Some(DropAction::Copy)))
+        );
+    });
+}
+
+/// The harness fires timers from its virtual clock, and reports layout rects.
+#[test]
+fn harness_timers_and_layout() {
+    let id = WidgetId::next();
+    let fired = Rc::new(Cell::new(0));
+    let fired_clone = fired.clone();
+    let widget = ModularWidget::new(None)
+        .event_fn(move |token, ctx, event, _data: &(), _env| match event {
+            Event::MouseDown(_) => {
+                let deadline = Instant::now() + Duration::from_millis(100);
+                *token = Some(ctx.request_timer(deadline));
+            }
+            Event::Timer(t) if Some(*t) == *token => fired_clone.set(fired_clone.get() + 1),
+            _ => (),
+        })
+        .fix_size(100., 40.)
+        .with_id(id)
+        .padding(10.);
+
+    Harness::create((), widget, |harness| {
+        harness.send_initial_events();
+        harness.just_layout();
+        let rect = harness.layout_rect(id);
+        assert_eq!(rect.origin(), Point::new(10., 10.));
+        assert_eq!(rect.size(), Size::new(100., 40.));
+
+        harness.click((20., 20.));
+        harness.advance_time(Duration::from_millis(50));
+        assert_eq!(fired.get(), 0);
+        harness.advance_time(Duration::from_millis(50));
+        assert_eq!(fired.get(), 1);
+        harness.advance_time(Duration::from_millis(500));
+        assert_eq!(fired.get(), 1);
+    });
+}
+
+#[test]
+fn dump_widget_tree() {
+    let id = WidgetId::next();
+    let widget = SizedBox::empty()
+        .fix_size(100., 40.)
+        .with_id(id)
+        .padding(10.);
+
+    Harness::create((), widget, |harness| {
+        harness.send_initial_events();
+        harness.just_layout();
+        harness.mouse_move((20., 20.));
+        let tree = harness.widget_tree();
+        assert_eq!(tree.widgets().len(), 2);
+        assert_eq!(tree.widgets()[0].depth, 0);
+
+        let info = tree.get(id).unwrap();
+        assert_eq!(info.depth, 1);
+        assert_eq!(info.window_rect.origin(), Point::new(10., 10.));
+        assert!(info.is_hot);
+        assert!(info.type_name.contains("SizedBox"));
+        assert!(tree.to_string().contains("SizedBox"));
+        assert!(tree.to_json().starts_with("{\"id\":"));
+    });
+}
+
+#[test]
+fn widget_tree_shows_accessibility() {
+    let id = WidgetId::next();
+    let widget = Image::new(ImageData::empty())
+        .alt_text("A cat")
+        .with_id(id)
+        .padding(10.);
+
+    Harness::create((), widget, |harness| {
+        harness.send_initial_events();
+        let tree = harness.widget_tree();
+        let info = tree.get(id).unwrap().accessibility.clone().unwrap();
+        assert_eq!(info.role, Role::Image);
+        assert_eq!(info.label.as_deref(), Some("A cat"));
+        assert!(tree.widgets()[0].accessibility.is_none());
+        assert!(tree.to_string().contains("[Image \"A cat\"]"));
+        assert!(tree.to_json().contains("\"label\":\"A cat\""));
+    });
+}
+
+#[test]
+fn harness_text_metrics_are_fixed_width() {
+    let id = WidgetId::next();
+    let label = Label::new("héllo").with_id(id).center();
+
+    Harness::create((), label, |harness| {
+        harness.send_initial_events();
+        harness.just_layout();
+        let font_size = theme::init().get(theme::TEXT_SIZE_NORMAL);
+        let size = harness.layout_rect(id).size();
+        assert_eq!(size.width, 5. * font_size / 2.);
+        assert_eq!(size.height, font_size * 1.2);
+    });
+}
+
+#[test]
+fn spawned_task_output_is_sent_back() {
+    const LOADED: Selector = Selector::new("druid-tests.loaded");
+
+    let loaded: Rc<Cell<u32>> = Default::default();
+    let widget = ModularWidget::new(loaded.clone())
+        .lifecycle_fn(|_, ctx, event, _data, _env| {
+            if let LifeCycle::WidgetAdded = event {
+                ctx.spawn(LOADED, async { 21u32 });
+            }
+        })
+        .event_fn(|loaded, ctx, event, _data, _env| match event {
+            Event::Command(cmd) if cmd.selector == LOADED => {
+                loaded.set(*cmd.get_object::<u32>().unwrap());
+            }
+            Event::MouseDown(_) => {
+                ctx.spawn(LOADED, async { 42u32 });
+            }
+            _ => (),
+        });
+
+    Harness::create((), widget, |harness| {
+        harness.send_initial_events();
+        assert_eq!(loaded.get(), 21);
+        harness.mouse_down((10., 10.));
+        assert_eq!(loaded.get(), 42);
+    });
+}
+
+#[test]
+fn controller_filters_events() {
+    /// Lets through only every other mouse down.
+    struct EveryOther(bool);
+
+    impl<W: Widget<u32>> Controller<u32, W> for EveryOther {
+        fn event(
+            &mut self,
+            child: &mut W,
+            ctx: &mut EventCtx,
+            event: &Event,
+            data: &mut u32,
+            env: &Env,
+        ) {
+            if let Event::MouseDown(_) = event {
+                self.0 = !self.0;
+                if !self.0 {
+                    return;
+                }
+            }
+            child.event(ctx, event, data, env)
+        }
+    }
+
+    let counter =
+        Button::new("+1", |_, data: &mut u32, _| *data += 1).controller(EveryOther(false));
+
+    Harness::create(0u32, counter, |harness| {
+        harness.send_initial_events();
+        harness.just_layout();
+        for _ in 0..4 {
+            harness.click((10., 10.));
+        }
+        assert_eq!(*harness.data(), 2);
+    });
+}
+
+#[test]
+fn view_switcher_rebuilds_when_picked_view_changes() {
+    let builds = Rc::new(Cell::new(0));
+    let builds_2 = builds.clone();
+    let switcher = ViewSwitcher::new(
+        |data: &u32, _env| *data / 10,
+        move |decade, _data, _env| {
+            builds_2.set(builds_2.get() + 1);
+            Label::new(format!("{}0s", decade)).boxed()
+        },
+    );
+
+    Harness::create(1u32, switcher, |harness| {
+        harness.send_initial_events();
+        harness.just_layout();
+        assert_eq!(builds.get(), 1);
+
+        // the same view is picked
+        harness.update_data(|data| *data = 5);
+        assert_eq!(builds.get(), 1);
+
+        harness.update_data(|data| *data = 25);
+        assert_eq!(builds.get(), 2);
+        let tree = harness.widget_tree();
+        assert!(tree.widgets().iter().any(|w| w.type_name.contains("Label")));
+        assert!(harness.window().root.state().needs_layout);
+        harness.just_layout();
+    });
+}
+
+#[test]
+fn either_lays_out_the_branch_it_switches_to() {
+    let (small, big) = widget_id2();
+    let either = Either::new(
+        |data: &bool, _env| *data,
+        SizedBox::empty().fix_size(10., 10.).with_id(small),
+        SizedBox::empty().fix_size(50., 50.).with_id(big),
+    )
+    .center();
+
+    Harness::create(true, either, |harness| {
+        harness.send_initial_events();
+        harness.just_layout();
+        assert_eq!(harness.layout_rect(small).size(), Size::new(10., 10.));
+
+        harness.update_data(|data| *data = false);
+        assert!(harness.window().root.state().needs_layout);
+        harness.just_layout();
+        assert_eq!(harness.layout_rect(big).size(), Size::new(50., 50.));
+    });
+}
+
+#[test]
+fn handled_command_is_not_delivered_further() {
+    const PING: Selector = Selector::new("druid-test.ping");
+
+    fn make_receiver(received: Rc<Cell<u32>>, handle: bool) -> impl Widget<bool> {
+        ModularWidget::new(received).event_fn(move |received, ctx, event, _data, _env| {
+            if let Event::Command(cmd) = event {
+                if cmd.selector == PING {
+                    received.set(received.get() + 1);
+                    if handle {
+                        ctx.set_handled();
+                    }
+                }
+            }
+        })
+    }
+
+    let first = Rc::new(Cell::new(0));
+    let second = Rc::new(Cell::new(0));
+    let (first_id, second_id) = widget_id2();
+    let widget = Flex::column()
+        .with_child(make_receiver(first.clone(), true).with_id(first_id), 0.0)
+        .with_child(make_receiver(second.clone(), false).with_id(second_id), 0.0);
+
+    Harness::create(true, widget, |harness| {
+        harness.send_initial_events();
+
+        harness.submit_command(PING, Target::Global);
+        assert_eq!((first.get(), second.get()), (1, 0));
+
+        // a command for one widget isn't seen by its siblings
+        harness.submit_command(PING, second_id);
+        assert_eq!((first.get(), second.get()), (1, 1));
+    });
+}
+
+#[test]
+fn split_does_not_take_a_baseline_from_sizing_its_children() {
+    let split = Split::vertical(Label::new("left"), Label::new("right"));
+
+    Harness::create((), split, |harness| {
+        harness.send_initial_events();
+        harness.just_layout();
+        assert_eq!(harness.window().root.state().baseline_offset, 0.0);
+    });
+}
@@ -0,0 +1,177 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The undo history of a text field.
+
+use std::time::{Duration, Instant};
+
+use super::Selection;
+
+/// How many edits are kept before the oldest are dropped.
+const MAX_EDITS: usize = 100;
+
+/// Edits of the same kind closer together than this are undone together,
+/// as long as each starts where the last left the caret.
+const COALESCE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What an edit was, for deciding which edits are undone together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum EditKind {
+    Typing,
+    Deleting,
+    /// Pasting, cutting and such, which are always undone on their own.
+    Other,
+}
+
+/// The text and selection from before or after an edit.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    text: String,
+    selection: Selection,
+}
+
+/// The edits made to a text field, for undoing and redoing them.
+///
+/// This is separate from the app's undo: it only knows the text, and if the
+/// text is changed by anything else the history is forgotten.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EditHistory {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    /// The text as the last edit, undo or redo left it.
+    current: Option<String>,
+    /// The kind of the last edit, the caret after it, and when it was made,
+    /// while the next edit can be merged into it.
+    last: Option<(EditKind, usize, Instant)>,
+}
+
+impl EditHistory {
+    /// Note an edit that changed `before`, with `selection`, to `after`.
+    pub(crate) fn record(
+        &mut self,
+        before: &str,
+        selection: Selection,
+        after: &str,
+        kind: EditKind,
+        caret: usize,
+        now: Instant,
+    ) {
+        if before == after {
+            return;
+        }
+        self.sync(before);
+        self.redo.clear();
+        let merge = match self.last {
+            Some((last_kind, last_caret, time)) => {
+                kind != EditKind::Other
+                    && kind == last_kind
+                    && selection.is_caret()
+                    && selection.end == last_caret
+                    && now.duration_since(time) < COALESCE_INTERVAL
+            }
+            None => false,
+        };
+        if !merge {
+            if self.undo.len() == MAX_EDITS {
+                self.undo.remove(0);
+            }
+            self.undo.push(Snapshot {
+                text: before.to_owned(),
+                selection,
+            });
+        }
+        self.current = Some(after.to_owned());
+        self.last = Some((kind, caret, now));
+    }
+
+    /// Forget the history if `text` isn't what it led to.
+    pub(crate) fn sync(&mut self, text: &str) {
+        if self.current.is_some() && self.current.as_deref() != Some(text) {
+            *self = EditHistory::default();
+        }
+    }
+
+    /// Undo the last edit, returning `false` if there was none.
+    pub(crate) fn undo(&mut self, text: &mut String, selection: &mut Selection) -> bool {
+        self.sync(text);
+        Self::step(&mut self.undo, &mut self.redo, text, selection) && self.stop_merging(text)
+    }
+
+    /// Redo the last undone edit, returning `false` if there was none.
+    pub(crate) fn redo(&mut self, text: &mut String, selection: &mut Selection) -> bool {
+        self.sync(text);
+        Self::step(&mut self.redo, &mut self.undo, text, selection) && self.stop_merging(text)
+    }
+
+    /// Go back to the top of `from`, keeping the text as it is on `to`.
+    fn step(
+        from: &mut Vec<Snapshot>,
+        to: &mut Vec<Snapshot>,
+        text: &mut String,
+        selection: &mut Selection,
+    ) -> bool {
+        let snapshot = match from.pop() {
+            Some(snapshot) => snapshot,
+            None => return false,
+        };
+        to.push(Snapshot {
+            text: std::mem::replace(text, snapshot.text),
+            selection: std::mem::replace(selection, snapshot.selection),
+        });
+        true
+    }
+
+    /// The next edit after an undo or redo is undone on its own.
+    fn stop_merging(&mut self, text: &str) -> bool {
+        self.current = Some(text.to_owned());
+        self.last = None;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typing_is_undone_a_run_at_a_time() {
+        let mut history = EditHistory::default();
+        let start = Instant::now();
+        let type_at = |history: &mut EditHistory, before: &str, after: &str, secs| {
+            let caret = Selection::caret(before.len());
+            let now = start + Duration::from_millis(secs);
+            history.record(before, caret, after, EditKind::Typing, after.len(), now);
+        };
+        type_at(&mut history, "", "a", 0);
+        type_at(&mut history, "a", "ab", 100);
+        // a pause starts a new run
+        type_at(&mut history, "ab", "abc", 2000);
+
+        let mut text = "abc".to_string();
+        let mut selection = Selection::caret(3);
+        assert!(history.undo(&mut text, &mut selection));
+        assert_eq!(text, "ab");
+        assert!(history.undo(&mut text, &mut selection));
+        assert_eq!(text, "");
+        assert!(!history.undo(&mut text, &mut selection));
+        assert!(history.redo(&mut text, &mut selection));
+        assert_eq!(text, "ab");
+        assert_eq!(selection.end, 2);
+
+        // a change from elsewhere makes the history stale
+        text.push('!');
+        assert!(!history.undo(&mut text, &mut selection));
+        assert_eq!(text, "ab!");
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The keys that edit text.
+
+use crate::{HotKey, KeyCode, KeyEvent, RawMods, SysMods};
+
+/// Something a key does in a [`TextBox`].
+///
+/// [`TextBox`]: widget/struct.TextBox.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditAction {
+    /// Move the caret one grapheme to the left.
+    MoveLeft,
+    /// Move the caret one grapheme to the right.
+    MoveRight,
+    /// Move the caret to the start of the line.
+    MoveToLineStart,
+    /// Move the caret to the end of the line.
+    MoveToLineEnd,
+    /// Extend the selection one grapheme to the left.
+    SelectLeft,
+    /// Extend the selection one grapheme to the right.
+    SelectRight,
+    /// Extend the selection to the start of the line.
+    SelectToLineStart,
+    /// Extend the selection to the end of the line.
+    SelectToLineEnd,
+    /// Select all of the text.
+    SelectAll,
+    /// Delete the selection, or the grapheme before the caret.
+    DeleteBackward,
+    /// Delete the selection, or the grapheme after the caret.
+    DeleteForward,
+    /// Undo the last edit.
+    Undo,
+    /// Redo the last undone edit.
+    Redo,
+    /// Move the focus to the next widget.
+    FocusNext,
+    /// Move the focus to the previous widget.
+    FocusPrevious,
+}
+
+/// The keys a [`TextBox`] responds to, and what each of them does.
+///
+/// The [`default`] bindings are the usual ones for the platform, including,
+/// on macOS, the emacs-style control keys such as `Ctrl+A` and `Ctrl+E`.
+/// Apps can add to them, or start from [`empty`] for bindings of their own;
+/// keys without a binding that produce text are typed.
+///
+/// ```
+/// use druid::{EditAction, HotKey, KeyBindings, RawMods};
+/// use druid::widget::TextBox;
+///
+/// let emacs = KeyBindings::default()
+///     .bind(HotKey::new(RawMods::Ctrl, "a"), EditAction::MoveToLineStart)
+///     .bind(HotKey::new(RawMods::Ctrl, "e"), EditAction::MoveToLineEnd)
+///     .bind(HotKey::new(RawMods::Ctrl, "b"), EditAction::MoveLeft)
+///     .bind(HotKey::new(RawMods::Ctrl, "f"), EditAction::MoveRight);
+/// let textbox = TextBox::raw().key_bindings(emacs);
+/// ```
+///
+/// [`TextBox`]: widget/struct.TextBox.html
+/// [`default`]: #method.default
+/// [`empty`]: #method.empty
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    bindings: Vec<(HotKey, EditAction)>,
+}
+
+impl KeyBindings {
+    /// Bindings with no keys in them.
+    pub fn empty() -> Self {
+        KeyBindings {
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Builder-style method to make `hotkey` do `action`, in place of what
+    /// it did before.
+    pub fn bind(mut self, hotkey: HotKey, action: EditAction) -> Self {
+        // the last binding for a key wins
+        self.bindings.insert(0, (hotkey, action));
+        self
+    }
+
+    /// What `key` does, if anything.
+    pub fn action_for(&self, key: &KeyEvent) -> Option<EditAction> {
+        self.bindings
+            .iter()
+            .find(|(hotkey, _)| hotkey.matches(key))
+            .map(|(_, action)| *action)
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        use EditAction::*;
+        let mut bindings = KeyBindings::empty()
+            .bind(HotKey::new(None, KeyCode::ArrowLeft), MoveLeft)
+            .bind(HotKey::new(None, KeyCode::ArrowRight), MoveRight)
+            .bind(
+                HotKey::new(SysMods::Cmd, KeyCode::ArrowLeft),
+                MoveToLineStart,
+            )
+            .bind(
+                HotKey::new(SysMods::Cmd, KeyCode::ArrowRight),
+                MoveToLineEnd,
+            )
+            .bind(HotKey::new(None, KeyCode::Home), MoveToLineStart)
+            .bind(HotKey::new(None, KeyCode::End), MoveToLineEnd)
+            .bind(HotKey::new(RawMods::Shift, KeyCode::ArrowLeft), SelectLeft)
+            .bind(
+                HotKey::new(RawMods::Shift, KeyCode::ArrowRight),
+                SelectRight,
+            )
+            .bind(
+                HotKey::new(SysMods::CmdShift, KeyCode::ArrowLeft),
+                SelectToLineStart,
+            )
+            .bind(
+                HotKey::new(SysMods::CmdShift, KeyCode::ArrowRight),
+                SelectToLineEnd,
+            )
+            .bind(
+                HotKey::new(RawMods::Shift, KeyCode::Home),
+                SelectToLineStart,
+            )
+            .bind(HotKey::new(RawMods::Shift, KeyCode::End), SelectToLineEnd)
+            .bind(HotKey::new(SysMods::Cmd, "a"), SelectAll)
+            .bind(HotKey::new(None, KeyCode::Backspace), DeleteBackward)
+            .bind(HotKey::new(None, KeyCode::Delete), DeleteForward)
+            .bind(HotKey::new(SysMods::Cmd, "z"), Undo)
+            .bind(HotKey::new(SysMods::CmdShift, "z"), Redo)
+            .bind(HotKey::new(None, KeyCode::Tab), FocusNext)
+            .bind(HotKey::new(RawMods::Shift, KeyCode::Tab), FocusPrevious);
+        if cfg!(target_os = "windows") {
+            bindings = bindings.bind(HotKey::new(RawMods::Ctrl, "y"), Redo);
+        }
+        if cfg!(target_os = "macos") {
+            bindings = bindings
+                .bind(HotKey::new(RawMods::Ctrl, "a"), MoveToLineStart)
+                .bind(HotKey::new(RawMods::Ctrl, "e"), MoveToLineEnd)
+                .bind(HotKey::new(RawMods::Ctrl, "b"), MoveLeft)
+                .bind(HotKey::new(RawMods::Ctrl, "f"), MoveRight)
+                .bind(HotKey::new(RawMods::Ctrl, "h"), DeleteBackward)
+                .bind(HotKey::new(RawMods::Ctrl, "d"), DeleteForward);
+        }
+        bindings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyModifiers;
+
+    #[test]
+    fn later_bindings_replace_earlier_ones() {
+        let ctrl = KeyModifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        let ctrl_e = KeyEvent::for_test(ctrl, "e", KeyCode::KeyE);
+        let home = KeyEvent::for_test(KeyModifiers::default(), "", KeyCode::Home);
+        let left = KeyEvent::for_test(KeyModifiers::default(), "", KeyCode::ArrowLeft);
+
+        let bindings = KeyBindings::default()
+            .bind(HotKey::new(RawMods::Ctrl, "e"), EditAction::MoveToLineEnd)
+            .bind(HotKey::new(None, KeyCode::Home), EditAction::SelectAll);
+        assert_eq!(
+            bindings.action_for(&ctrl_e),
+            Some(EditAction::MoveToLineEnd)
+        );
+        assert_eq!(bindings.action_for(&home), Some(EditAction::SelectAll));
+        assert_eq!(bindings.action_for(&left), Some(EditAction::MoveLeft));
+        assert_eq!(KeyBindings::empty().action_for(&left), None);
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Patterns that text such as phone numbers and dates is kept to.
+
+use std::ops::Range;
+
+/// A character of a mask.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Slot {
+    Digit,
+    Letter,
+    Alphanumeric,
+    /// Shown as it is, and never typed.
+    Literal(char),
+}
+
+impl Slot {
+    fn accepts(self, c: char) -> bool {
+        match self {
+            Slot::Digit => c.is_ascii_digit(),
+            Slot::Letter => c.is_alphabetic(),
+            Slot::Alphanumeric => c.is_alphanumeric(),
+            Slot::Literal(_) => false,
+        }
+    }
+}
+
+/// A pattern for the text in a masked `TextBox`, such as `(###) ###-####`.
+///
+/// The text is kept formatted: the characters the user enters fill the
+/// slots of the pattern in order, and the literals between them are put in
+/// as the slots after them are filled. Edits and caret positions are
+/// worked out in entered characters, so the caret steps over the literals.
+#[derive(Debug, Clone)]
+pub(crate) struct Mask {
+    slots: Vec<Slot>,
+}
+
+impl Mask {
+    /// Parse `pattern`, in which `#` and `_` are digits, `A` is a letter,
+    /// `*` is a letter or digit, and anything else is literal; a `\` makes
+    /// the character after it literal.
+    pub(crate) fn new(pattern: &str) -> Mask {
+        let mut slots = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            slots.push(match c {
+                '#' | '_' => Slot::Digit,
+                'A' => Slot::Letter,
+                '*' => Slot::Alphanumeric,
+                '\\' => Slot::Literal(chars.next().unwrap_or('\\')),
+                c => Slot::Literal(c),
+            });
+        }
+        Mask { slots }
+    }
+
+    /// The pattern with each slot shown as `_`, for an empty text box.
+    pub(crate) fn placeholder(&self) -> String {
+        self.slots
+            .iter()
+            .map(|slot| match slot {
+                Slot::Literal(c) => *c,
+                _ => '_',
+            })
+            .collect()
+    }
+
+    /// For each character of `text`, the byte offset after it and whether it
+    /// fills a slot. Literals are matched where they are, and characters
+    /// that don't fit are passed over.
+    fn classify(&self, text: &str) -> Vec<(usize, bool)> {
+        let mut classes = Vec::new();
+        let mut slot = 0;
+        for (offset, c) in text.char_indices() {
+            let end = offset + c.len_utf8();
+            loop {
+                match self.slots.get(slot) {
+                    Some(Slot::Literal(literal)) => {
+                        slot += 1;
+                        if *literal == c {
+                            classes.push((end, false));
+                            break;
+                        }
+                    }
+                    Some(s) if s.accepts(c) => {
+                        slot += 1;
+                        classes.push((end, true));
+                        break;
+                    }
+                    _ => {
+                        classes.push((end, false));
+                        break;
+                    }
+                }
+            }
+        }
+        classes
+    }
+
+    /// The characters entered into the slots of `text`.
+    fn entered(&self, text: &str) -> Vec<char> {
+        let classes = self.classify(text);
+        text.chars()
+            .zip(classes)
+            .filter(|(_, (_, fills))| *fills)
+            .map(|(c, _)| c)
+            .collect()
+    }
+
+    /// The characters of `input` that fill the slots in order, leaving out
+    /// those that don't fit the slot they would go in.
+    fn fit(&self, input: impl Iterator<Item = char>) -> Vec<char> {
+        let mut slots = self
+            .slots
+            .iter()
+            .filter(|slot| !matches!(slot, Slot::Literal(_)))
+            .peekable();
+        let mut fitted = Vec::new();
+        for c in input {
+            match slots.peek() {
+                Some(slot) if slot.accepts(c) => {
+                    fitted.push(c);
+                    slots.next();
+                }
+                Some(_) => (),
+                None => break,
+            }
+        }
+        fitted
+    }
+
+    /// The text with `entered` in the slots.
+    fn format(&self, entered: &[char]) -> String {
+        let mut text = String::new();
+        let mut entered = entered.iter();
+        let mut rest = entered.len();
+        for slot in &self.slots {
+            if rest == 0 {
+                break;
+            }
+            match slot {
+                Slot::Literal(c) => text.push(*c),
+                _ => {
+                    text.extend(entered.next());
+                    rest -= 1;
+                }
+            }
+        }
+        text
+    }
+
+    /// The number of characters entered before byte `offset` of `text`.
+    pub(crate) fn count_before(&self, text: &str, offset: usize) -> usize {
+        self.classify(text)
+            .iter()
+            .filter(|(end, fills)| *fills && *end <= offset)
+            .count()
+    }
+
+    /// The byte offset in `text` just after `count` entered characters.
+    fn offset_after(&self, text: &str, count: usize) -> usize {
+        if count == 0 {
+            return 0;
+        }
+        self.classify(text)
+            .iter()
+            .filter(|(_, fills)| *fills)
+            .nth(count - 1)
+            .map_or(text.len(), |(end, _)| *end)
+    }
+
+    /// Replace the entered characters in `range` with those of `new` that
+    /// fit, returning the new text and the offset of the caret after them.
+    pub(crate) fn edit(&self, text: &str, range: Range<usize>, new: &str) -> (String, usize) {
+        let entered = self.entered(text);
+        let start = range.start.min(entered.len());
+        let end = range.end.min(entered.len()).max(start);
+        let head = entered[..start].iter().copied().chain(new.chars());
+        let caret = self.fit(head.clone()).len();
+        let all = self.fit(head.chain(entered[end..].iter().copied()));
+        let text = self.format(&all);
+        let caret = self.offset_after(&text, caret);
+        (text, caret)
+    }
+
+    /// The offset of the caret after moving one entered character from
+    /// `offset`, over any literals.
+    pub(crate) fn step(&self, text: &str, offset: usize, forward: bool) -> usize {
+        let count = self.count_before(text, offset);
+        let count = if forward {
+            (count + 1).min(self.entered(text).len())
+        } else {
+            count.saturating_sub(1)
+        };
+        self.offset_after(text, count)
+    }
+
+    /// The caret position closest to `offset` that is after an entered
+    /// character, or at the start.
+    pub(crate) fn snap(&self, text: &str, offset: usize) -> usize {
+        self.offset_after(text, self.count_before(text, offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pasted_text_is_fitted_to_the_pattern() {
+        let phone = Mask::new("(###) ###-####");
+        assert_eq!(phone.placeholder(), "(___) ___-____");
+        let (text, caret) = phone.edit("", 0..0, "555 123 4567 ext. 89");
+        assert_eq!(text, "(555) 123-4567");
+        assert_eq!(caret, text.len());
+
+        // letters don't go in digit slots, and escaped characters are literal
+        let serial = Mask::new("\\#AA-##");
+        assert_eq!(serial.edit("", 0..0, "x1y2").0, "#xy-2");
+        assert_eq!(serial.edit("", 0..0, "1").0, "");
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Measuring a line of text.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::kurbo::Point;
+use crate::piet::{FontBuilder, PietText, PietTextLayout, Text, TextLayout, TextLayoutBuilder};
+use crate::Env;
+
+/// In the test harness, every grapheme is this fraction of the font size wide.
+const MOCK_ADVANCE: f64 = 0.5;
+
+/// A line of text laid out by the platform, and measured.
+///
+/// Widgets should measure text with this, rather than the layout itself, so
+/// that in the test harness the measurements can come from a simple
+/// fixed-width model that gives the same results on every platform. The
+/// layout is still used to draw the text.
+pub(crate) struct TextLine {
+    layout: PietTextLayout,
+    /// The width of each grapheme, when using the fixed-width model.
+    mock_advance: Option<f64>,
+    /// The byte offset of each grapheme boundary, including the end.
+    boundaries: Vec<usize>,
+}
+
+impl TextLine {
+    pub(crate) fn new(
+        piet_text: &mut PietText,
+        font_name: &str,
+        font_size: f64,
+        text: &str,
+        env: &Env,
+    ) -> TextLine {
+        let font = piet_text
+            .new_font_by_name(font_name, font_size)
+            .build()
+            .unwrap();
+        let layout = piet_text.new_text_layout(&font, text).build().unwrap();
+        let mock_advance = if env.get(Env::MOCK_TEXT_METRICS) {
+            Some(font_size * MOCK_ADVANCE)
+        } else {
+            None
+        };
+        let boundaries = match mock_advance {
+            Some(_) => text
+                .grapheme_indices(true)
+                .map(|(i, _)| i)
+                .chain(std::iter::once(text.len()))
+                .collect(),
+            None => Vec::new(),
+        };
+        TextLine {
+            layout,
+            mock_advance,
+            boundaries,
+        }
+    }
+
+    /// The layout, for drawing.
+    pub(crate) fn layout(&self) -> &PietTextLayout {
+        &self.layout
+    }
+
+    /// The width of the text.
+    pub(crate) fn width(&self) -> f64 {
+        match self.mock_advance {
+            Some(advance) => advance * (self.boundaries.len() - 1) as f64,
+            None => self.layout.width(),
+        }
+    }
+
+    /// The byte offset of the grapheme boundary closest to `x`.
+    pub(crate) fn offset_for_x(&self, x: f64) -> usize {
+        match self.mock_advance {
+            Some(advance) => {
+                let idx = (x / advance).round().max(0.0) as usize;
+                self.boundaries[idx.min(self.boundaries.len() - 1)]
+            }
+            None => {
+                self.layout
+                    .hit_test_point(Point::new(x, 0.0))
+                    .metrics
+                    .text_position
+            }
+        }
+    }
+
+    /// The x position of the grapheme boundary at byte `offset`, if there
+    /// is one there.
+    pub(crate) fn x_for_offset(&self, offset: usize) -> Option<f64> {
+        match self.mock_advance {
+            Some(advance) => self
+                .boundaries
+                .iter()
+                .position(|b| *b == offset)
+                .map(|idx| idx as f64 * advance),
+            None => self
+                .layout
+                .hit_test_text_position(offset)
+                .map(|pos| pos.point.x),
+        }
+    }
+}
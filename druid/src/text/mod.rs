@@ -17,7 +17,24 @@
 mod editable_text;
 pub use self::editable_text::{EditableText, EditableTextCursor, StringCursor};
 
+mod metrics;
+pub(crate) use self::metrics::TextLine;
+
+mod history;
+pub(crate) use self::history::{EditHistory, EditKind};
+
+mod keybindings;
+pub use self::keybindings::{EditAction, KeyBindings};
+
+mod mask;
+pub(crate) use self::mask::Mask;
+
+mod spellcheck;
+pub(crate) use self::spellcheck::Spelling;
+pub use self::spellcheck::{Spellchecker, WordList};
+
 pub mod selection;
+pub(crate) use self::selection::Granularity;
 pub use self::selection::Selection;
 
 pub mod movement;
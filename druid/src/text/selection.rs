@@ -17,6 +17,8 @@
 use std::cmp::{max, min};
 use std::ops::Range;
 
+use unicode_segmentation::UnicodeSegmentation;
+
 use crate::text::EditableText;
 
 /// A Selection type for EditableText.
@@ -80,3 +82,81 @@ impl Selection {
         self
     }
 }
+
+/// The units a mouse selection is made of: clicking once selects by
+/// grapheme, twice by word and three times by line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Granularity {
+    Grapheme,
+    Word,
+    Line,
+}
+
+impl Granularity {
+    /// The granularity of a click that is the `count`th in a row.
+    pub(crate) fn from_click_count(count: u32) -> Self {
+        match count {
+            0 | 1 => Granularity::Grapheme,
+            2 => Granularity::Word,
+            _ => Granularity::Line,
+        }
+    }
+
+    /// The unit of `text` at `offset`.
+    pub(crate) fn unit_at(self, text: &str, offset: usize) -> Range<usize> {
+        let offset = offset.min(text.len());
+        match self {
+            Granularity::Grapheme => offset..offset,
+            Granularity::Word => text
+                .split_word_bound_indices()
+                .map(|(start, word)| start..start + word.len())
+                .find(|word| offset < word.end)
+                .or_else(|| {
+                    text.split_word_bound_indices()
+                        .next_back()
+                        .map(|(start, _)| start..text.len())
+                })
+                .unwrap_or(offset..offset),
+            Granularity::Line => {
+                let start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+                let end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+                start..end
+            }
+        }
+    }
+
+    /// The selection made by dragging from the unit `anchor` to `offset`;
+    /// it covers both, and its active edge is on the side of `offset`.
+    pub(crate) fn extend(self, text: &str, anchor: Range<usize>, offset: usize) -> Selection {
+        let unit = self.unit_at(text, offset);
+        if unit.start < anchor.start {
+            Selection::new(anchor.end, unit.start)
+        } else {
+            Selection::new(anchor.start, unit.end.max(anchor.end))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dragging_selects_whole_units() {
+        let text = "one two three\nfour";
+        let word = Granularity::Word;
+        assert_eq!(word.unit_at(text, 5), 4..7);
+        assert_eq!(word.unit_at(text, text.len()), 14..18);
+
+        // dragging back from "two" to "one" keeps all of "two"
+        let drag = word.extend(text, 4..7, 1);
+        assert_eq!((drag.start, drag.end), (7, 0));
+        let drag = word.extend(text, 4..7, 9);
+        assert_eq!((drag.start, drag.end), (4, 13));
+
+        let line = Granularity::Line;
+        assert_eq!(line.unit_at(text, 5), 0..13);
+        let drag = line.extend(text, 0..13, 15);
+        assert_eq!((drag.start, drag.end), (0, 18));
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checking the spelling of text.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A dictionary that a [`TextBox`] checks the spelling of its text against.
+///
+/// Implement this to plug in a spellchecking library or the platform's
+/// checker; a [`WordList`] is a simple one made from a list of words.
+///
+/// [`TextBox`]: widget/struct.TextBox.html
+/// [`WordList`]: struct.WordList.html
+pub trait Spellchecker {
+    /// Whether `word` is spelled correctly.
+    fn is_correct(&self, word: &str) -> bool;
+
+    /// The words that `word` may have been meant to be, best first.
+    fn suggestions(&self, word: &str) -> Vec<String>;
+}
+
+/// A [`Spellchecker`] that knows the words it is given.
+///
+/// Words are matched ignoring case. The suggestions for a word are the known
+/// words one edit away from it: with a letter added, left out, changed, or
+/// swapped with the one after it.
+///
+/// [`Spellchecker`]: trait.Spellchecker.html
+#[derive(Debug, Clone, Default)]
+pub struct WordList {
+    words: HashSet<String>,
+}
+
+impl WordList {
+    /// Create a `WordList` that knows `words`.
+    pub fn new<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Self {
+        let mut list = WordList::default();
+        for word in words {
+            list.add(word.as_ref());
+        }
+        list
+    }
+
+    /// Add a word to the list.
+    pub fn add(&mut self, word: &str) {
+        self.words.insert(word.to_lowercase());
+    }
+}
+
+impl Spellchecker for WordList {
+    fn is_correct(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+
+    fn suggestions(&self, word: &str) -> Vec<String> {
+        let chars: Vec<char> = word.to_lowercase().chars().collect();
+        let mut suggestions: Vec<String> = self
+            .words
+            .iter()
+            .filter(|known| one_edit_apart(&chars, &known.chars().collect::<Vec<_>>()))
+            .map(|known| match_case(word, known))
+            .collect();
+        suggestions.sort();
+        suggestions
+    }
+}
+
+/// Whether one edit turns `a` into `b`.
+fn one_edit_apart(a: &[char], b: &[char]) -> bool {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if long.len() - short.len() > 1 {
+        return false;
+    }
+    let same = short.iter().zip(long).take_while(|(a, b)| a == b).count();
+    if short.len() < long.len() {
+        return short[same..] == long[same + 1..];
+    }
+    if same == short.len() {
+        return false;
+    }
+    let next = same + 1;
+    let swapped = next < short.len()
+        && short[same] == long[next]
+        && short[next] == long[same]
+        && short[next + 1..] == long[next + 1..];
+    swapped || short[next..] == long[next..]
+}
+
+/// `known`, capitalized if `word` is.
+fn match_case(word: &str, known: &str) -> String {
+    let mut chars = known.chars();
+    match (word.chars().next(), chars.next()) {
+        (Some(first), Some(known_first)) if first.is_uppercase() => {
+            known_first.to_uppercase().chain(chars).collect()
+        }
+        _ => known.to_owned(),
+    }
+}
+
+/// A spellchecker, and the misspellings it last found.
+#[derive(Clone)]
+pub(crate) struct Spelling {
+    checker: Arc<dyn Spellchecker>,
+    checked: Option<String>,
+    misspelled: Vec<Range<usize>>,
+}
+
+impl Spelling {
+    pub(crate) fn new(checker: impl Spellchecker + 'static) -> Self {
+        Spelling {
+            checker: Arc::new(checker),
+            checked: None,
+            misspelled: Vec::new(),
+        }
+    }
+
+    /// The byte ranges of the misspelled words in `text`.
+    pub(crate) fn misspelled(&mut self, text: &str) -> &[Range<usize>] {
+        if self.checked.as_deref() != Some(text) {
+            let checker = &self.checker;
+            self.misspelled = text
+                .split_word_bound_indices()
+                .filter(|(_, word)| word.chars().any(char::is_alphabetic))
+                .filter(|(_, word)| !checker.is_correct(word))
+                .map(|(start, word)| start..start + word.len())
+                .collect();
+            self.checked = Some(text.to_owned());
+        }
+        &self.misspelled
+    }
+
+    /// The misspelled word of `text` that `offset` is in or at the end of.
+    pub(crate) fn misspelling_at(&mut self, text: &str, offset: usize) -> Option<Range<usize>> {
+        self.misspelled(text)
+            .iter()
+            .find(|range| range.start <= offset && offset <= range.end)
+            .cloned()
+    }
+
+    pub(crate) fn suggestions(&self, word: &str) -> Vec<String> {
+        self.checker.suggestions(word)
+    }
+}
+
+impl fmt::Debug for Spelling {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Spelling")
+            .field("misspelled", &self.misspelled)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn misspelled_words_are_found_and_corrected() {
+        let words = WordList::new(["the", "quick", "brown", "fox", "don't"]);
+        let mut spelling = Spelling::new(words.clone());
+        let text = "The qiuck brwn fox, 42 times; don't.";
+        assert_eq!(spelling.misspelled(text), &[4..9, 10..14]);
+        assert_eq!(spelling.misspelling_at(text, 14), Some(10..14));
+        assert_eq!(spelling.misspelling_at(text, 16), None);
+
+        assert_eq!(words.suggestions("qiuck"), vec!["quick"]);
+        assert_eq!(words.suggestions("Brwn"), vec!["Brown"]);
+        assert_eq!(words.suggestions("foxes"), Vec::<String>::new());
+    }
+}
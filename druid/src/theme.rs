@@ -37,10 +37,40 @@ pub const BUTTON_BORDER_WIDTH: Key<f64> = Key::new("button_border_width");
 pub const BORDER_DARK: Key<Color> = Key::new("border");
 pub const BORDER_LIGHT: Key<Color> = Key::new("border_light");
 pub const SELECTION_COLOR: Key<Color> = Key::new("selection_color");
+pub const SELECTION_RADIUS: Key<f64> = Key::new("selection_radius");
 pub const CURSOR_COLOR: Key<Color> = Key::new("cursor_color");
+pub const FOCUS_RING_COLOR: Key<Color> = Key::new("focus_ring_color");
+pub const FOCUS_RING_WIDTH: Key<f64> = Key::new("focus_ring_width");
+pub const FOCUS_RING_RADIUS: Key<f64> = Key::new("focus_ring_radius");
 
 pub const FONT_NAME: Key<&str> = Key::new("font_name");
+/// The font for code, such as in a [`Markdown`] code block.
+///
+/// [`Markdown`]: ../widget/struct.Markdown.html
+pub const MONOSPACE_FONT_NAME: Key<&str> = Key::new("monospace_font_name");
 pub const TEXT_SIZE_NORMAL: Key<f64> = Key::new("text_size_normal");
+/// The platform text scale factor that has been applied to the text sizes
+/// in the environment.
+///
+/// Widgets with their own text size keys can multiply by this to honor the
+/// user's readability settings.
+pub const TEXT_SCALE: Key<f64> = Key::new("text_scale");
+
+/// `true` if the user has asked the system to minimize motion.
+///
+/// Widgets should replace animations with instant changes when this is set.
+/// It is initialized from the platform preference and kept up to date.
+///
+/// Of the built-in widgets, [`Scroll`] hides its scroll bars without fading
+/// them, [`Switch`] and [`Pager`] jump instead of sliding, and [`Spinner`]
+/// stands still. Other animations, including those in custom widgets, have
+/// to check it themselves.
+///
+/// [`Scroll`]: ../widget/struct.Scroll.html
+/// [`Switch`]: ../widget/struct.Switch.html
+/// [`Pager`]: ../widget/struct.Pager.html
+/// [`Spinner`]: ../widget/struct.Spinner.html
+pub const REDUCE_MOTION: Key<bool> = Key::new("reduce_motion");
 pub const BASIC_WIDGET_HEIGHT: Key<f64> = Key::new("basic_widget_height");
 pub const BORDERED_WIDGET_HEIGHT: Key<f64> = Key::new("bordered_widget_height");
 
@@ -54,6 +84,58 @@ pub const SCROLL_BAR_WIDTH: Key<f64> = Key::new("scroll_bar_width");
 pub const SCROLL_BAR_PAD: Key<f64> = Key::new("scroll_bar_pad");
 pub const SCROLL_BAR_RADIUS: Key<f64> = Key::new("scroll_bar_radius");
 pub const SCROLL_BAR_EDGE_WIDTH: Key<f64> = Key::new("scroll_bar_edge_width");
+/// The color a [`Scroll`] with [`edge_fades`] fades its content into.
+///
+/// [`Scroll`]: ../widget/struct.Scroll.html
+/// [`edge_fades`]: ../widget/struct.Scroll.html#method.edge_fades
+pub const SCROLL_FADE_COLOR: Key<Color> = Key::new("scroll_fade_color");
+/// How far the edge fades of a [`Scroll`] reach into the viewport.
+///
+/// [`Scroll`]: ../widget/struct.Scroll.html
+pub const SCROLL_FADE_LENGTH: Key<f64> = Key::new("scroll_fade_length");
+
+pub const ICON_SIZE: Key<f64> = Key::new("icon_size");
+pub const ICON_COLOR: Key<Color> = Key::new("icon_color");
+
+/// The track of a [`Switch`] that is on.
+///
+/// [`Switch`]: ../widget/struct.Switch.html
+pub const SWITCH_TRACK_ON_COLOR: Key<Color> = Key::new("switch_track_on_color");
+/// The track of a [`Switch`] that is off.
+///
+/// [`Switch`]: ../widget/struct.Switch.html
+pub const SWITCH_TRACK_OFF_COLOR: Key<Color> = Key::new("switch_track_off_color");
+/// The knob of a [`Switch`].
+///
+/// [`Switch`]: ../widget/struct.Switch.html
+pub const SWITCH_KNOB_COLOR: Key<Color> = Key::new("switch_knob_color");
+
+pub const BADGE_COLOR: Key<Color> = Key::new("badge_color");
+pub const BADGE_TEXT_COLOR: Key<Color> = Key::new("badge_text_color");
+
+/// The text of a [`Link`], and of the links in [`Markdown`].
+///
+/// [`Link`]: ../widget/struct.Link.html
+/// [`Markdown`]: ../widget/struct.Markdown.html
+pub const LINK_COLOR: Key<Color> = Key::new("link_color");
+/// A link the mouse is over.
+pub const LINK_HOVER_COLOR: Key<Color> = Key::new("link_hover_color");
+/// A link that has been followed.
+pub const LINK_VISITED_COLOR: Key<Color> = Key::new("link_visited_color");
+
+/// The errors shown under the fields of a [`Form`].
+///
+/// [`Form`]: ../widget/struct.Form.html
+pub const FORM_ERROR_COLOR: Key<Color> = Key::new("form_error_color");
+/// The line under misspelled words in a [`TextBox`] with spellchecking.
+///
+/// [`TextBox`]: ../widget/struct.TextBox.html
+pub const MISSPELLING_COLOR: Key<Color> = Key::new("misspelling_color");
+
+pub const STATUS_BAR_HEIGHT: Key<f64> = Key::new("status_bar_height");
+/// How long a status message is shown for, in milliseconds, unless it has
+/// a timeout of its own.
+pub const STATUS_MESSAGE_TIMEOUT: Key<u64> = Key::new("status_message_timeout");
 
 /// An initial theme.
 pub fn init() -> Env {
@@ -75,8 +157,14 @@ pub fn init() -> Env {
         .adding(BORDER_DARK, Color::rgb8(0x3a, 0x3a, 0x3a))
         .adding(BORDER_LIGHT, Color::rgb8(0xa1, 0xa1, 0xa1))
         .adding(SELECTION_COLOR, Color::rgb8(0xf3, 0x00, 0x21))
+        .adding(SELECTION_RADIUS, 1.)
         .adding(CURSOR_COLOR, Color::WHITE)
+        .adding(FOCUS_RING_COLOR, Color::rgb8(0x5c, 0xc4, 0xff))
+        .adding(FOCUS_RING_WIDTH, 2.)
+        .adding(FOCUS_RING_RADIUS, 4.)
         .adding(TEXT_SIZE_NORMAL, 15.0)
+        .adding(TEXT_SCALE, 1.0)
+        .adding(REDUCE_MOTION, false)
         .adding(BASIC_WIDGET_HEIGHT, 18.0)
         .adding(BORDERED_WIDGET_HEIGHT, 24.0)
         .adding(TEXTBOX_BORDER_RADIUS, 2.)
@@ -87,21 +175,56 @@ pub fn init() -> Env {
         .adding(SCROLL_BAR_WIDTH, 8.)
         .adding(SCROLL_BAR_PAD, 2.)
         .adding(SCROLL_BAR_RADIUS, 5.)
-        .adding(SCROLL_BAR_EDGE_WIDTH, 1.);
+        .adding(SCROLL_BAR_EDGE_WIDTH, 1.)
+        .adding(SCROLL_FADE_COLOR, Color::rgb8(0x29, 0x29, 0x29))
+        .adding(SCROLL_FADE_LENGTH, 24.)
+        .adding(ICON_SIZE, 18.)
+        .adding(ICON_COLOR, Color::rgb8(0xf0, 0xf0, 0xea))
+        .adding(SWITCH_TRACK_ON_COLOR, Color::rgb8(0x00, 0x8d, 0xdd))
+        .adding(SWITCH_TRACK_OFF_COLOR, Color::rgb8(0x31, 0x31, 0x31))
+        .adding(SWITCH_KNOB_COLOR, Color::rgb8(0xf9, 0xf9, 0xf9))
+        .adding(BADGE_COLOR, Color::rgb8(0xe5, 0x39, 0x35))
+        .adding(BADGE_TEXT_COLOR, Color::WHITE)
+        .adding(LINK_COLOR, Color::rgb8(0x5c, 0xc4, 0xff))
+        .adding(LINK_HOVER_COLOR, Color::rgb8(0x9d, 0xdc, 0xff))
+        .adding(LINK_VISITED_COLOR, Color::rgb8(0xb3, 0x9d, 0xdb))
+        .adding(FORM_ERROR_COLOR, Color::rgb8(0xff, 0x6b, 0x6b))
+        .adding(MISSPELLING_COLOR, Color::rgb8(0xe5, 0x39, 0x35))
+        .adding(STATUS_BAR_HEIGHT, 22.)
+        .adding(STATUS_MESSAGE_TIMEOUT, 5000u64);
 
     #[cfg(target_os = "windows")]
     {
-        env = env.adding(FONT_NAME, "Segoe UI");
+        env = env
+            .adding(FONT_NAME, "Segoe UI")
+            .adding(MONOSPACE_FONT_NAME, "Consolas");
     }
     #[cfg(target_os = "macos")]
     {
         // Ideally this would be a reference to San Francisco, but Cairo's
         // "toy text" API doesn't seem to be able to access it easily.
-        env = env.adding(FONT_NAME, "Arial");
+        env = env
+            .adding(FONT_NAME, "Arial")
+            .adding(MONOSPACE_FONT_NAME, "Menlo");
     }
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     {
-        env = env.adding(FONT_NAME, "sans-serif");
+        env = env
+            .adding(FONT_NAME, "sans-serif")
+            .adding(MONOSPACE_FONT_NAME, "monospace");
     }
     env
 }
+
+/// Scale the text sizes in `env` to match the platform text scale.
+///
+/// Scaling is relative to the currently applied [`TEXT_SCALE`], so this can
+/// be called again whenever the platform setting changes.
+///
+/// [`TEXT_SCALE`]: constant.TEXT_SCALE.html
+pub(crate) fn apply_text_scale(env: &mut Env, scale: f64) {
+    let current = env.get(TEXT_SCALE);
+    let size = env.get(TEXT_SIZE_NORMAL);
+    env.set(TEXT_SIZE_NORMAL, size * scale / current);
+    env.set(TEXT_SCALE, scale);
+}
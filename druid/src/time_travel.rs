@@ -0,0 +1,158 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A history of the application's data, for debugging.
+//!
+//! This is only built with the `time-travel` feature.
+
+use std::collections::VecDeque;
+
+use crate::{Data, Event};
+
+/// How many states are kept before the oldest are dropped.
+const MAX_ENTRIES: usize = 1000;
+
+/// Every state the app data has been in, along with what changed it.
+pub(crate) struct History<T> {
+    entries: VecDeque<Entry<T>>,
+    /// The index of the entry the app is currently showing.
+    cursor: usize,
+    /// A description of the most recent event, which is blamed for the
+    /// next change.
+    last_event: String,
+}
+
+struct Entry<T> {
+    data: T,
+    cause: String,
+}
+
+impl<T: Data> History<T> {
+    pub(crate) fn new(initial: &T) -> Self {
+        let mut entries = VecDeque::new();
+        entries.push_back(Entry {
+            data: initial.clone(),
+            cause: "initial data".to_string(),
+        });
+        History {
+            entries,
+            cursor: 0,
+            last_event: String::new(),
+        }
+    }
+
+    /// Note the event that is about to be handled.
+    pub(crate) fn event(&mut self, event: &Event) {
+        self.last_event = describe(event);
+    }
+
+    /// Record `data` if it has changed since the current entry.
+    ///
+    /// If we had stepped back, the states after the current one are
+    /// discarded, as with undo.
+    pub(crate) fn record(&mut self, data: &T) {
+        if self.entries[self.cursor].data.same(data) {
+            return;
+        }
+        self.entries.truncate(self.cursor + 1);
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry {
+            data: data.clone(),
+            cause: std::mem::take(&mut self.last_event),
+        });
+        self.cursor = self.entries.len() - 1;
+    }
+
+    /// Move back one state, returning the data to show.
+    pub(crate) fn back(&mut self) -> Option<T> {
+        let cursor = self.cursor.checked_sub(1)?;
+        Some(self.step_to(cursor))
+    }
+
+    /// Move forward one state, returning the data to show.
+    pub(crate) fn forward(&mut self) -> Option<T> {
+        if self.cursor + 1 >= self.entries.len() {
+            return None;
+        }
+        Some(self.step_to(self.cursor + 1))
+    }
+
+    fn step_to(&mut self, cursor: usize) -> T {
+        self.cursor = cursor;
+        let entry = &self.entries[cursor];
+        log::info!(
+            "time travel: state {}/{}, after {}",
+            cursor + 1,
+            self.entries.len(),
+            entry.cause
+        );
+        entry.data.clone()
+    }
+}
+
+/// A one-line description of an event; the full debug output of some events
+/// is far too long.
+fn describe(event: &Event) -> String {
+    match event {
+        Event::TargetedCommand(_, cmd) => format!("command {}", cmd.selector),
+        Event::KeyDown(key) => format!("KeyDown {:?}", key.key_code),
+        Event::KeyUp(key) => format!("KeyUp {:?}", key.key_code),
+        Event::MouseDown(mouse) => format!("MouseDown at {:?}", mouse.pos),
+        Event::MouseUp(mouse) => format!("MouseUp at {:?}", mouse.pos),
+        other => {
+            let debug = format!("{:?}", other);
+            let end = debug
+                .find(|c: char| !c.is_alphanumeric())
+                .unwrap_or(debug.len());
+            debug[..end].to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, Selector};
+
+    #[test]
+    fn step_back_and_branch() {
+        let mut history = History::new(&0u32);
+        history.event(&Event::TargetedCommand(
+            crate::WidgetId::next().into(),
+            Command::from(Selector::new("increment")),
+        ));
+        history.record(&1);
+        history.record(&1);
+        history.record(&2);
+        assert_eq!(history.entries.len(), 3);
+        assert_eq!(history.entries[1].cause, "command increment");
+
+        assert_eq!(history.back(), Some(1));
+        assert_eq!(history.back(), Some(0));
+        assert_eq!(history.back(), None);
+        assert_eq!(history.forward(), Some(1));
+
+        // restoring a state doesn't record it again
+        history.record(&1);
+        assert_eq!(history.entries.len(), 3);
+
+        // a new change drops the states after this one
+        history.record(&5);
+        assert_eq!(history.entries.len(), 3);
+        assert_eq!(history.forward(), None);
+        assert_eq!(history.back(), Some(1));
+    }
+}
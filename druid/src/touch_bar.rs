@@ -0,0 +1,216 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Touch Bar items that send commands.
+
+use crate::shell::{TouchBar, TouchBarEvent, TouchBarItem};
+use crate::{Command, Data, Env, LocalizedString, Selector};
+
+/// The contents of a window's Touch Bar, on the MacBook Pros that have one.
+///
+/// Each item sends a [`Command`] when used. The Touch Bar is only shown on
+/// macOS; elsewhere it is ignored, so it can be set unconditionally.
+///
+/// # Examples
+///
+/// ```
+/// # use druid::{Command, LocalizedString, Selector, TouchBarDesc};
+/// const PLAY: Selector = Selector::new("my-app.play");
+/// const SET_VOLUME: Selector = Selector::new("my-app.set-volume");
+///
+/// let touch_bar = TouchBarDesc::<()>::new()
+///     .button(LocalizedString::new("Play"), PLAY)
+///     .slider(Some(LocalizedString::new("Volume")), 0.0, 1.0, 0.5, SET_VOLUME);
+/// ```
+///
+/// [`Command`]: struct.Command.html
+#[derive(Debug, Clone)]
+pub struct TouchBarDesc<T> {
+    items: Vec<Item<T>>,
+}
+
+#[derive(Debug, Clone)]
+enum Item<T> {
+    Button {
+        label: LocalizedString<T>,
+        command: Command,
+    },
+    Slider {
+        label: Option<LocalizedString<T>>,
+        min: f64,
+        max: f64,
+        value: f64,
+        selector: Selector,
+    },
+    Segmented {
+        labels: Vec<LocalizedString<T>>,
+        selected: Option<usize>,
+        selector: Selector,
+    },
+}
+
+impl<T> Default for TouchBarDesc<T> {
+    fn default() -> Self {
+        TouchBarDesc { items: Vec::new() }
+    }
+}
+
+impl<T: Data> TouchBarDesc<T> {
+    /// Create an empty Touch Bar.
+    pub fn new() -> Self {
+        TouchBarDesc::default()
+    }
+
+    /// Add a button that sends `command` when pressed.
+    pub fn button(mut self, label: LocalizedString<T>, command: impl Into<Command>) -> Self {
+        self.items.push(Item::Button {
+            label,
+            command: command.into(),
+        });
+        self
+    }
+
+    /// Add a slider between `min` and `max`, starting at `value`.
+    ///
+    /// As it is moved, the slider sends a command with `selector`, whose
+    /// argument is the new value as an `f64`.
+    pub fn slider(
+        mut self,
+        label: Option<LocalizedString<T>>,
+        min: f64,
+        max: f64,
+        value: f64,
+        selector: Selector,
+    ) -> Self {
+        self.items.push(Item::Slider {
+            label,
+            min,
+            max,
+            value,
+            selector,
+        });
+        self
+    }
+
+    /// Add a row of segments, of which one can be selected.
+    ///
+    /// Selecting a segment sends a command with `selector`, whose argument
+    /// is the segment's index as a `usize`.
+    pub fn segmented(
+        mut self,
+        labels: Vec<LocalizedString<T>>,
+        selected: Option<usize>,
+        selector: Selector,
+    ) -> Self {
+        self.items.push(Item::Segmented {
+            labels,
+            selected,
+            selector,
+        });
+        self
+    }
+
+    /// Build the platform Touch Bar. Items are identified by their index.
+    pub(crate) fn build_native(&mut self, data: &T, env: &Env) -> TouchBar {
+        let mut touch_bar = TouchBar::new();
+        for (id, item) in self.items.iter_mut().enumerate() {
+            let id = id as u32;
+            let native = match item {
+                Item::Button { label, .. } => {
+                    label.resolve(data, env);
+                    TouchBarItem::Button {
+                        id,
+                        label: label.localized_str().to_string(),
+                    }
+                }
+                Item::Slider {
+                    label,
+                    min,
+                    max,
+                    value,
+                    ..
+                } => TouchBarItem::Slider {
+                    id,
+                    label: label.as_mut().map(|label| {
+                        label.resolve(data, env);
+                        label.localized_str().to_string()
+                    }),
+                    min: *min,
+                    max: *max,
+                    value: *value,
+                },
+                Item::Segmented {
+                    labels, selected, ..
+                } => TouchBarItem::Segmented {
+                    id,
+                    labels: labels
+                        .iter_mut()
+                        .map(|label| {
+                            label.resolve(data, env);
+                            label.localized_str().to_string()
+                        })
+                        .collect(),
+                    selected: *selected,
+                },
+            };
+            touch_bar.add_item(native);
+        }
+        touch_bar
+    }
+
+    /// The command for an event on the item with this id, if any.
+    pub(crate) fn command_for_event(&self, id: u32, event: TouchBarEvent) -> Option<Command> {
+        match (self.items.get(id as usize)?, event) {
+            (Item::Button { command, .. }, TouchBarEvent::Pressed) => Some(command.clone()),
+            (Item::Slider { selector, .. }, TouchBarEvent::SliderChanged(value)) => {
+                Some(Command::new(selector.clone(), value))
+            }
+            (Item::Segmented { selector, .. }, TouchBarEvent::SegmentSelected(index)) => {
+                Some(Command::new(selector.clone(), index))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_map_to_commands() {
+        const PLAY: Selector = Selector::new("play");
+        const VOLUME: Selector = Selector::new("volume");
+        const MODE: Selector = Selector::new("mode");
+        let desc = TouchBarDesc::<()>::new()
+            .button(LocalizedString::new(""), PLAY)
+            .slider(None, 0.0, 1.0, 0.5, VOLUME)
+            .segmented(vec![LocalizedString::new("")], None, MODE);
+
+        let play = desc.command_for_event(0, TouchBarEvent::Pressed).unwrap();
+        assert_eq!(play.selector, PLAY);
+        let volume = desc
+            .command_for_event(1, TouchBarEvent::SliderChanged(0.25))
+            .unwrap();
+        assert_eq!(volume.get_object::<f64>(), Ok(&0.25));
+        let mode = desc
+            .command_for_event(2, TouchBarEvent::SegmentSelected(0))
+            .unwrap();
+        assert_eq!(mode.get_object::<usize>(), Ok(&0));
+        assert!(desc
+            .command_for_event(0, TouchBarEvent::SegmentSelected(0))
+            .is_none());
+        assert!(desc.command_for_event(3, TouchBarEvent::Pressed).is_none());
+    }
+}
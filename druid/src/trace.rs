@@ -0,0 +1,76 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spans for the [`tracing`] crate.
+//!
+//! With the `tracing` feature, each pass over a window opens a span, and
+//! so does each widget's part of it, recording the widget's id and type.
+//! Anything a widget logs with `tracing` is then attributed to it. Without
+//! the feature, none of this costs anything.
+//!
+//! [`tracing`]: https://docs.rs/tracing
+
+use crate::core::BaseState;
+use crate::WindowId;
+
+/// The passes over the widget tree.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum TracePass {
+    Event,
+    LifeCycle,
+    Update,
+    Layout,
+    Paint,
+}
+
+/// While this is alive, we are inside the span it was made for.
+#[must_use]
+pub(crate) struct Entered {
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+/// Enter the span for a pass over a whole window.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn window(pass: TracePass, id: WindowId) -> Entered {
+    Entered {
+        #[cfg(feature = "tracing")]
+        _span: match pass {
+            TracePass::Event => tracing::debug_span!("event", window = ?id),
+            TracePass::LifeCycle => tracing::debug_span!("lifecycle", window = ?id),
+            TracePass::Update => tracing::debug_span!("update", window = ?id),
+            TracePass::Layout => tracing::debug_span!("layout", window = ?id),
+            TracePass::Paint => tracing::debug_span!("paint", window = ?id),
+        }
+        .entered(),
+    }
+}
+
+/// Enter the span for one widget's part of a pass.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn widget(pass: TracePass, state: &BaseState) -> Entered {
+    #[cfg(feature = "tracing")]
+    let (id, widget) = (state.id.to_raw(), state.type_name);
+    Entered {
+        #[cfg(feature = "tracing")]
+        _span: match pass {
+            TracePass::Event => tracing::trace_span!("event", id, widget),
+            TracePass::LifeCycle => tracing::trace_span!("lifecycle", id, widget),
+            TracePass::Update => tracing::trace_span!("update", id, widget),
+            TracePass::Layout => tracing::trace_span!("layout", id, widget),
+            TracePass::Paint => tracing::trace_span!("paint", id, widget),
+        }
+        .entered(),
+    }
+}
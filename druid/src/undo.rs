@@ -0,0 +1,284 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Undoing and redoing changes to the app data.
+//!
+//! Because `Data` is cheap to clone, an undo step is just the state of the
+//! data from before the change. A step is recorded when the handling of an
+//! input event changes the data, and a widget asked for it with
+//! [`EventCtx::submit_undoable`]; or, with [`AppLauncher::automatic_undo`],
+//! for any change made by a click, a key press, a paste or a drop.
+//!
+//! [`EventCtx::submit_undoable`]: ../struct.EventCtx.html#method.submit_undoable
+//! [`AppLauncher::automatic_undo`]: ../struct.AppLauncher.html#method.automatic_undo
+
+use std::time::{Duration, Instant};
+
+use crate::{Data, Event, Selector, WidgetId};
+
+/// Asks for the change made by the current event to be undoable. The
+/// argument is an `UndoRequest`.
+pub(crate) const RECORD_UNDO: Selector = Selector::new("druid-builtin.record-undo");
+
+/// How many steps are kept before the oldest are dropped.
+const MAX_STEPS: usize = 100;
+
+/// Steps with the same label, from the same widget, that are closer together
+/// than this are merged, so that typing a word is undone all at once.
+const COALESCE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The label automatic steps get.
+const AUTOMATIC_LABEL: &str = "Edit";
+
+/// A widget's request to make its change undoable.
+#[derive(Debug, Clone)]
+pub(crate) struct UndoRequest {
+    pub(crate) label: String,
+    pub(crate) source: Option<WidgetId>,
+}
+
+/// The undo and redo stacks.
+pub(crate) struct UndoManager<T> {
+    undo: Vec<Step<T>>,
+    redo: Vec<Step<T>>,
+    /// The data as it was before the current event.
+    before: Option<T>,
+    /// Whether the current event can make a step without being asked to.
+    automatic_event: bool,
+    pending: Option<UndoRequest>,
+    /// Record a step for every change made by an input event.
+    pub(crate) automatic: bool,
+    /// Whether the top of the undo stack can take more changes.
+    can_coalesce: bool,
+}
+
+struct Step<T> {
+    data: T,
+    request: UndoRequest,
+    time: Instant,
+}
+
+impl<T: Data> UndoManager<T> {
+    pub(crate) fn new() -> Self {
+        UndoManager {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            before: None,
+            automatic_event: false,
+            pending: None,
+            automatic: false,
+            can_coalesce: false,
+        }
+    }
+
+    /// Note the data as it is before `event` is handled.
+    pub(crate) fn begin(&mut self, data: &T, event: &Event) {
+        self.before = Some(data.clone());
+        self.automatic_event = matches!(
+            event,
+            Event::MouseDown(_)
+                | Event::MouseUp(_)
+                | Event::KeyDown(_)
+                | Event::Paste(_)
+                | Event::Drop(_)
+        );
+    }
+
+    /// Ask for the change made by the current event to be a step.
+    pub(crate) fn request(&mut self, request: UndoRequest) {
+        self.pending = Some(request);
+    }
+
+    /// Record a step for the current event, if one is wanted and the data
+    /// has changed.
+    pub(crate) fn commit(&mut self, data: &T, now: Instant) {
+        let before = match self.before.take() {
+            Some(before) => before,
+            None => return,
+        };
+        let request = match self.pending.take() {
+            Some(request) => request,
+            None if self.automatic && self.automatic_event => UndoRequest {
+                label: AUTOMATIC_LABEL.to_string(),
+                source: None,
+            },
+            None => return,
+        };
+        if before.same(data) {
+            return;
+        }
+        self.redo.clear();
+        if let Some(top) = self.undo.last_mut() {
+            if self.can_coalesce
+                && request.source.is_some()
+                && top.request.source == request.source
+                && top.request.label == request.label
+                && now.duration_since(top.time) < COALESCE_INTERVAL
+            {
+                top.time = now;
+                return;
+            }
+        }
+        if self.undo.len() == MAX_STEPS {
+            self.undo.remove(0);
+        }
+        self.undo.push(Step {
+            data: before,
+            request,
+            time: now,
+        });
+        self.can_coalesce = true;
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Go back a step, replacing `data`. Returns `false` if there was
+    /// nothing to undo.
+    pub(crate) fn undo(&mut self, data: &mut T) -> bool {
+        Self::swap(&mut self.undo, &mut self.redo, data) && self.stop_recording()
+    }
+
+    /// Go forward a step, replacing `data`. Returns `false` if there was
+    /// nothing to redo.
+    pub(crate) fn redo(&mut self, data: &mut T) -> bool {
+        Self::swap(&mut self.redo, &mut self.undo, data) && self.stop_recording()
+    }
+
+    /// Replace `data` with the top of `from`, and keep the old data on `to`.
+    fn swap(from: &mut Vec<Step<T>>, to: &mut Vec<Step<T>>, data: &mut T) -> bool {
+        let step = match from.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+        let old = std::mem::replace(data, step.data);
+        to.push(Step {
+            data: old,
+            request: step.request,
+            time: step.time,
+        });
+        true
+    }
+
+    /// The change an undo or redo makes is not a step of its own, and the
+    /// next edit starts a new one.
+    fn stop_recording(&mut self) -> bool {
+        self.before = None;
+        self.pending = None;
+        self.can_coalesce = false;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn click() -> Event {
+        Event::MouseDown(crate::MouseEvent {
+            pos: crate::Point::ORIGIN,
+            window_pos: crate::Point::ORIGIN,
+            mods: Default::default(),
+            count: 1,
+            button: crate::MouseButton::Left,
+        })
+    }
+
+    fn typing(source: WidgetId) -> UndoRequest {
+        UndoRequest {
+            label: "Typing".into(),
+            source: Some(source),
+        }
+    }
+
+    /// Change `data` to `to` during a click, asking for `request`.
+    fn edit(
+        undo: &mut UndoManager<u32>,
+        data: &mut u32,
+        to: u32,
+        request: Option<UndoRequest>,
+        now: Instant,
+    ) {
+        undo.begin(data, &click());
+        *data = to;
+        if let Some(request) = request {
+            undo.request(request);
+        }
+        undo.commit(data, now);
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut undo = UndoManager::new();
+        let mut data = 0;
+        let id = WidgetId::next();
+        let start = Instant::now();
+        edit(&mut undo, &mut data, 1, Some(typing(id)), start);
+        edit(&mut undo, &mut data, 2, None, start);
+        assert!(undo.can_undo());
+
+        // the unrequested change isn't a step of its own
+        assert!(undo.undo(&mut data));
+        assert_eq!(data, 0);
+        assert!(!undo.undo(&mut data));
+
+        assert!(undo.redo(&mut data));
+        assert_eq!(data, 2);
+        assert!(!undo.can_redo());
+    }
+
+    #[test]
+    fn rapid_edits_are_coalesced() {
+        let mut undo = UndoManager::new();
+        let mut data = 0;
+        let (typer, other) = (WidgetId::next(), WidgetId::next());
+        let start = Instant::now();
+        edit(&mut undo, &mut data, 1, Some(typing(typer)), start);
+        let soon = start + Duration::from_millis(200);
+        edit(&mut undo, &mut data, 2, Some(typing(typer)), soon);
+        // another widget
+        edit(&mut undo, &mut data, 3, Some(typing(other)), soon);
+        // too late to join
+        let later = soon + COALESCE_INTERVAL;
+        edit(&mut undo, &mut data, 4, Some(typing(other)), later);
+
+        undo.undo(&mut data);
+        assert_eq!(data, 3);
+        undo.undo(&mut data);
+        assert_eq!(data, 2);
+        undo.undo(&mut data);
+        assert_eq!(data, 0);
+    }
+
+    #[test]
+    fn automatic_steps() {
+        let mut undo = UndoManager::new();
+        undo.automatic = true;
+        let mut data = 0;
+        edit(&mut undo, &mut data, 1, None, Instant::now());
+
+        // a timer is not an edit
+        undo.begin(&data, &Event::Timer(crate::TimerToken::INVALID));
+        data = 2;
+        undo.commit(&data, Instant::now());
+
+        undo.undo(&mut data);
+        assert_eq!(data, 0);
+    }
+}
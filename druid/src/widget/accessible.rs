@@ -0,0 +1,167 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that describes its child to assistive technology.
+
+use crate::kurbo::Size;
+use crate::widget::LabelText;
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    UpdateCtx, Widget, WidgetId,
+};
+
+/// The kind of control a widget presents to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// A widget with no particular role, such as a container.
+    Generic,
+    Button,
+    Checkbox,
+    Image,
+    Label,
+    Link,
+    List,
+    ProgressIndicator,
+    RadioButton,
+    ScrollArea,
+    Slider,
+    Switch,
+    TextField,
+}
+
+/// The description of a widget that is exposed to assistive technology.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessibilityInfo {
+    /// The kind of control.
+    pub role: Role,
+    /// A short name for the widget, such as "Save".
+    pub label: Option<String>,
+    /// A longer description of what interacting with the widget does.
+    pub hint: Option<String>,
+}
+
+/// A wrapper that attaches accessibility metadata to its child.
+///
+/// This is usually created with the [`accessibility_label`],
+/// [`accessibility_role`] and [`accessibility_hint`] methods on
+/// [`WidgetExt`]; calling a second one of these on the result adds to the
+/// same wrapper instead of nesting another.
+///
+/// [`accessibility_label`]: trait.WidgetExt.html#method.accessibility_label
+/// [`accessibility_role`]: trait.WidgetExt.html#method.accessibility_role
+/// [`accessibility_hint`]: trait.WidgetExt.html#method.accessibility_hint
+/// [`WidgetExt`]: trait.WidgetExt.html
+pub struct Accessible<T, W> {
+    inner: W,
+    role: Role,
+    label: Option<LabelText<T>>,
+    hint: Option<LabelText<T>>,
+}
+
+impl<T: Data, W: Widget<T>> Accessible<T, W> {
+    /// Wrap `inner`, with no metadata set.
+    pub fn new(inner: W) -> Self {
+        Accessible {
+            inner,
+            role: Role::Generic,
+            label: None,
+            hint: None,
+        }
+    }
+
+    /// Set the label, which can be localized or computed from the data.
+    pub fn accessibility_label(mut self, label: impl Into<LabelText<T>>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Set the role.
+    pub fn accessibility_role(mut self, role: Role) -> Self {
+        self.role = role;
+        self
+    }
+
+    /// Set the hint, which can be localized or computed from the data.
+    pub fn accessibility_hint(mut self, hint: impl Into<LabelText<T>>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Returns the current description of the child.
+    pub fn info(&self, data: &T, env: &Env) -> AccessibilityInfo {
+        let resolve = |text: &LabelText<T>| text.with_display_text(data, env, str::to_owned);
+        AccessibilityInfo {
+            role: self.role,
+            label: self.label.as_ref().map(resolve),
+            hint: self.hint.as_ref().map(resolve),
+        }
+    }
+
+    fn resolve(&mut self, data: &T, env: &Env) {
+        for text in self.label.iter_mut().chain(self.hint.iter_mut()) {
+            text.resolve(data, env);
+        }
+    }
+}
+
+impl<T: Data, W: Widget<T>> Widget<T> for Accessible<T, W> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.inner.event(ctx, event, data, env);
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => self.resolve(data, env),
+            LifeCycle::DebugCollectTree(collector) => collector.describe(self.info(data, env)),
+            _ => (),
+        }
+        self.inner.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        self.resolve(data, env);
+        self.inner.update(ctx, old_data, data, env);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        self.inner.layout(ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.inner.paint(paint_ctx, data, env);
+    }
+
+    fn id(&self) -> Option<WidgetId> {
+        self.inner.id()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::{Label, WidgetExt};
+
+    #[test]
+    fn metadata_accumulates() {
+        let widget = Label::new("x")
+            .accessibility_role(Role::Button)
+            .accessibility_label(|data: &u32, _env: &_| format!("{} items", data))
+            .accessibility_hint("Opens the cart");
+
+        let info = widget.info(&3, &Env::default());
+        assert_eq!(info.role, Role::Button);
+        assert_eq!(info.label.as_deref(), Some("3 items"));
+        assert_eq!(info.hint.as_deref(), Some("Opens the cart"));
+    }
+}
@@ -15,6 +15,7 @@
 //! A widget that aligns its child (for example, centering it).
 
 use crate::kurbo::{Rect, Size};
+use crate::widget::Axis;
 use crate::{
     BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
     UpdateCtx, Widget, WidgetPod,
@@ -34,8 +35,10 @@ impl<T> Align<T> {
     /// Create widget with alignment.
     ///
     /// Note that the `align` parameter is specified as a `UnitPoint` in
-    /// terms of left and right. This is inadequate for bidi-aware layout
-    /// and thus the API will change when druid gains bidi capability.
+    /// terms of left and right; in a right-to-left layout (see
+    /// [`Env::RIGHT_TO_LEFT`]) the horizontal alignment is mirrored.
+    ///
+    /// [`Env::RIGHT_TO_LEFT`]: ../struct.Env.html#associatedconstant.RIGHT_TO_LEFT
     pub fn new(align: UnitPoint, child: impl Widget<T> + 'static) -> Align<T> {
         Align {
             align,
@@ -122,17 +125,44 @@ impl<T: Data> Widget<T> for Align<T> {
         my_size = bc.constrain(my_size);
         let extra_width = (my_size.width - size.width).max(0.);
         let extra_height = (my_size.height - size.height).max(0.);
-        let origin = self
+        let mut origin = self
             .align
             .resolve(Rect::new(0., 0., extra_width, extra_height));
+        if env.get(Env::RIGHT_TO_LEFT) {
+            origin.x = extra_width - origin.x;
+        }
         self.child
             .set_layout_rect(Rect::from_origin_size(origin, size));
 
         let my_insets = self.child.compute_parent_paint_insets(my_size);
         layout_ctx.set_paint_insets(my_insets);
+        let below = my_size.height - origin.y - size.height;
+        layout_ctx.set_baseline_offset(self.child.baseline_offset() + below);
         my_size
     }
 
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        self.child.min_intrinsic(ctx, axis, cross, data, env)
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        self.child.max_intrinsic(ctx, axis, cross, data, env)
+    }
+
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
         self.child.paint_with_offset(paint_ctx, data, env);
     }
@@ -0,0 +1,387 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A text box that suggests what to type.
+
+use crate::commands;
+use crate::kurbo::{Point, Rect, Size};
+use crate::text::TextLine;
+use crate::theme;
+use crate::widget::TextBox;
+use crate::{
+    BoxConstraints, Command, Data, Env, Event, EventCtx, KeyCode, LayoutCtx, Lens, LifeCycle,
+    LifeCycleCtx, PaintCtx, RenderContext, Selector, UpdateCtx, Widget, WidgetId, WidgetPod,
+};
+
+/// The most suggestions shown at once.
+const MAX_SUGGESTIONS: usize = 8;
+
+/// The popup is painted after the rest of the window, over the widgets
+/// below the text box.
+const POPUP_Z_INDEX: u32 = 1;
+
+/// The argument of the command an [`Autocomplete`] asks for suggestions
+/// with, when they come from the app.
+///
+/// [`Autocomplete`]: struct.Autocomplete.html
+#[derive(Debug, Clone)]
+pub struct SuggestionRequest {
+    /// What has been typed.
+    pub query: String,
+    /// The widget to send the [`Suggestions`] to.
+    ///
+    /// [`Suggestions`]: struct.Suggestions.html
+    pub widget: WidgetId,
+}
+
+/// The suggestions for a query, sent to an [`Autocomplete`] with
+/// [`SHOW_SUGGESTIONS`].
+///
+/// [`Autocomplete`]: struct.Autocomplete.html
+/// [`SHOW_SUGGESTIONS`]: ../commands/constant.SHOW_SUGGESTIONS.html
+#[derive(Debug, Clone)]
+pub struct Suggestions {
+    /// The query they are for; they aren't shown if something else has been
+    /// typed since.
+    pub query: String,
+    /// The suggestions, best first.
+    pub items: Vec<String>,
+}
+
+type Candidates<T> = Box<dyn Fn(&T) -> Vec<String>>;
+
+/// Where the suggestions come from.
+enum Source<T> {
+    /// All the candidates, from the data, which are filtered by the query.
+    Data(Candidates<T>),
+    /// The app, which is asked with a command with this selector.
+    Command(Selector),
+}
+
+/// A text box that shows suggestions in a popup below it as the user types.
+///
+/// The suggestions come from the data, with [`new`], which are the
+/// candidates that contain what has been typed, or from the app, with
+/// [`requesting`]. The arrow keys go through them, Enter puts the one
+/// picked in the text box, and Escape closes the popup.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::Autocomplete;
+/// use druid::{Data, Lens, Widget};
+/// use std::sync::Arc;
+///
+/// #[derive(Clone, Data, Lens)]
+/// struct Search {
+///     country: String,
+///     countries: Arc<Vec<String>>,
+/// }
+///
+/// fn search() -> impl Widget<Search> {
+///     Autocomplete::new(Search::country, |search: &Search| {
+///         search.countries.to_vec()
+///     })
+/// }
+/// ```
+///
+/// [`new`]: #method.new
+/// [`requesting`]: #method.requesting
+pub struct Autocomplete<T, L> {
+    query: L,
+    textbox: WidgetPod<String, TextBox>,
+    source: Source<T>,
+    suggestions: Vec<String>,
+    selected: Option<usize>,
+    open: bool,
+}
+
+impl<T: Data, L: Lens<T, String>> Autocomplete<T, L> {
+    /// Create an autocomplete with `query` leading to the text, suggesting
+    /// the `candidates` from the data that contain it.
+    ///
+    /// Candidates that start with the text come first, and case is ignored.
+    pub fn new(query: L, candidates: impl Fn(&T) -> Vec<String> + 'static) -> Self {
+        Autocomplete::with_source(query, Source::Data(Box::new(candidates)))
+    }
+
+    /// Create an autocomplete with `query` leading to the text, asking the
+    /// app for suggestions.
+    ///
+    /// Each time the text changes, a command with `selector` and a
+    /// [`SuggestionRequest`] is submitted to the window. The app answers,
+    /// now or later, with a [`SHOW_SUGGESTIONS`] command for the widget in
+    /// the request.
+    ///
+    /// [`SuggestionRequest`]: struct.SuggestionRequest.html
+    /// [`SHOW_SUGGESTIONS`]: ../commands/constant.SHOW_SUGGESTIONS.html
+    pub fn requesting(query: L, selector: Selector) -> Self {
+        Autocomplete::with_source(query, Source::Command(selector))
+    }
+
+    fn with_source(query: L, source: Source<T>) -> Self {
+        Autocomplete {
+            query,
+            textbox: WidgetPod::new(TextBox::raw()),
+            source,
+            suggestions: Vec::new(),
+            selected: None,
+            open: false,
+        }
+    }
+
+    fn text(&self, data: &T) -> String {
+        self.query.with(data, |text| text.clone())
+    }
+
+    /// Show `items`, with none of them picked yet.
+    fn show(&mut self, mut items: Vec<String>) {
+        items.truncate(MAX_SUGGESTIONS);
+        self.open = !items.is_empty();
+        self.suggestions = items;
+        self.selected = None;
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        self.selected = None;
+    }
+
+    /// Find the suggestions for what has just been typed.
+    fn refresh(&mut self, ctx: &mut EventCtx, data: &T) {
+        let query = self.text(data);
+        if query.is_empty() {
+            self.close();
+            return;
+        }
+        match &self.source {
+            Source::Data(candidates) => {
+                let items = filter(candidates(data), &query);
+                self.show(items);
+            }
+            Source::Command(selector) => {
+                let request = SuggestionRequest {
+                    query,
+                    widget: ctx.widget_id(),
+                };
+                ctx.submit_command(Command::new(selector.clone(), request), None);
+            }
+        }
+    }
+
+    /// Move the pick `by` suggestions, wrapping around.
+    fn move_selection(&mut self, by: isize) {
+        let len = self.suggestions.len() as isize;
+        if len == 0 {
+            return;
+        }
+        let next = match self.selected {
+            Some(index) => (index as isize + by).rem_euclid(len),
+            None if by > 0 => 0,
+            None => len - 1,
+        };
+        self.selected = Some(next as usize);
+    }
+}
+
+/// The `candidates` that contain `query`, ignoring case, with those that
+/// start with it first.
+fn filter(candidates: Vec<String>, query: &str) -> Vec<String> {
+    let query = query.to_lowercase();
+    let (mut starting, containing): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .filter(|candidate| candidate.to_lowercase().contains(&query))
+        .partition(|candidate| candidate.to_lowercase().starts_with(&query));
+    starting.extend(containing);
+    starting.truncate(MAX_SUGGESTIONS);
+    starting
+}
+
+impl<T: Data, L: Lens<T, String>> Widget<T> for Autocomplete<T, L> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        match event {
+            Event::KeyDown(key) if self.open => {
+                let handled = match key.key_code {
+                    KeyCode::ArrowDown => {
+                        self.move_selection(1);
+                        true
+                    }
+                    KeyCode::ArrowUp => {
+                        self.move_selection(-1);
+                        true
+                    }
+                    KeyCode::Return | KeyCode::NumpadEnter => match self.selected {
+                        Some(index) => {
+                            let picked = self.suggestions[index].clone();
+                            self.query.with_mut(data, |text| *text = picked);
+                            self.close();
+                            true
+                        }
+                        None => false,
+                    },
+                    KeyCode::Escape => {
+                        self.close();
+                        true
+                    }
+                    _ => false,
+                };
+                if handled {
+                    ctx.set_handled();
+                    ctx.request_paint();
+                    return;
+                }
+            }
+            // other popups may be open too, so this isn't handled here
+            Event::Command(cmd) if cmd.selector == commands::DISMISS_POPUPS => {
+                if self.open {
+                    self.close();
+                    ctx.request_paint();
+                }
+                return;
+            }
+            Event::Command(cmd) if cmd.selector == commands::SHOW_SUGGESTIONS => {
+                if let Ok(suggestions) = cmd.get_object::<Suggestions>() {
+                    if suggestions.query == self.text(data) {
+                        self.show(suggestions.items.clone());
+                        ctx.request_paint();
+                    }
+                }
+                ctx.set_handled();
+                return;
+            }
+            _ => (),
+        }
+
+        let old = self.text(data);
+        let textbox = &mut self.textbox;
+        self.query
+            .with_mut(data, |text| textbox.event(ctx, event, text, env));
+        if self.query.with(data, |text| *text != old) {
+            self.refresh(ctx, data);
+            ctx.request_paint();
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        if let LifeCycle::RouteFocusChanged { old, .. } = event {
+            if *old == Some(self.textbox.id()) {
+                self.close();
+                ctx.request_paint();
+            }
+        }
+        let textbox = &mut self.textbox;
+        self.query
+            .with(data, |text| textbox.lifecycle(ctx, event, text, env));
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        let textbox = &mut self.textbox;
+        self.query.with(data, |text| textbox.update(ctx, text, env));
+        // the candidates may have changed while they are shown
+        if let (true, Source::Data(candidates)) = (self.open, &self.source) {
+            let items = filter(candidates(data), &self.text(data));
+            self.show(items);
+            ctx.request_paint();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Autocomplete");
+        let textbox = &mut self.textbox;
+        let size = self
+            .query
+            .with(data, |text| textbox.layout(layout_ctx, bc, text, env));
+        self.textbox
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        layout_ctx.set_baseline_offset(self.textbox.baseline_offset());
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        let textbox = &mut self.textbox;
+        self.query
+            .with(data, |text| textbox.paint_with_offset(paint_ctx, text, env));
+
+        if !self.open {
+            return;
+        }
+        let items = self.suggestions.clone();
+        let selected = self.selected;
+        let width = paint_ctx.size().width;
+        let top = paint_ctx.size().height + 2.0;
+        let env = env.clone();
+        paint_ctx.paint_with_z_index(POPUP_Z_INDEX, move |ctx| {
+            let font_name = env.get(theme::FONT_NAME);
+            let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+            let row_height = font_size * 1.6;
+            let popup = Rect::new(0.0, top, width, top + row_height * items.len() as f64);
+            ctx.fill(popup, &env.get(theme::BACKGROUND_LIGHT));
+
+            for (index, item) in items.iter().enumerate() {
+                let row_top = top + row_height * index as f64;
+                if selected == Some(index) {
+                    let row = Rect::new(0.0, row_top, width, row_top + row_height);
+                    ctx.fill(row, &env.get(theme::SELECTION_COLOR));
+                }
+                let line = TextLine::new(ctx.text(), font_name, font_size, item, &env);
+                // the same baseline as a label's
+                let baseline = row_top + (row_height + font_size * 0.6) / 2.0;
+                let origin = Point::new(4.0, baseline);
+                ctx.draw_text(line.layout(), origin, &env.get(theme::LABEL_COLOR));
+            }
+            ctx.stroke(popup, &env.get(theme::BORDER_DARK), 1.0);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggestions_that_start_with_the_query_come_first() {
+        let countries = ["Germany", "Nigeria", "Niger", "Algeria", "Norway"];
+        let candidates = countries.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            filter(candidates, "nig"),
+            vec!["Nigeria".to_string(), "Niger".to_string()]
+        );
+        let candidates = countries.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            filter(candidates, "ER"),
+            vec![
+                "Germany".to_string(),
+                "Nigeria".to_string(),
+                "Niger".to_string(),
+                "Algeria".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn arrows_wrap_around_the_suggestions() {
+        let mut autocomplete = Autocomplete::new(crate::lens::Id, |_: &String| Vec::new());
+        autocomplete.show(vec!["one".into(), "two".into(), "three".into()]);
+        autocomplete.move_selection(-1);
+        assert_eq!(autocomplete.selected, Some(2));
+        autocomplete.move_selection(1);
+        assert_eq!(autocomplete.selected, Some(0));
+    }
+}
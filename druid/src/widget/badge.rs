@@ -0,0 +1,220 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A count or a dot on the corner of a widget.
+
+use crate::kurbo::{Insets, Point, Rect, RoundedRect, Size};
+use crate::piet::{Color, UnitPoint};
+use crate::text::TextLine;
+use crate::theme;
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    RenderContext, UpdateCtx, Widget, WidgetPod,
+};
+
+/// The largest count that is shown as it is; more is shown as `99+`.
+const MAX_COUNT: usize = 99;
+
+/// The size of the badge's text, relative to the normal text size.
+const TEXT_SCALE: f64 = 0.75;
+
+/// The diameter of a dot.
+const DOT_SIZE: f64 = 8.0;
+
+/// A widget with a small count or dot on one of its corners, such as the
+/// number of unread messages on an inbox icon.
+///
+/// The count comes from the data, and the badge is hidden while it is zero.
+/// It is drawn in [`theme::BADGE_COLOR`] unless it is given a [`color`].
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Badge, Icon};
+/// use druid::Data;
+///
+/// #[derive(Clone, Data)]
+/// struct Inbox {
+///     unread: usize,
+/// }
+///
+/// let inbox = Badge::new(Icon::new("menu"), |inbox: &Inbox| inbox.unread);
+/// ```
+///
+/// [`theme::BADGE_COLOR`]: ../theme/constant.BADGE_COLOR.html
+/// [`color`]: #method.color
+pub struct Badge<T> {
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+    count: Box<dyn Fn(&T) -> usize>,
+    corner: UnitPoint,
+    dot: bool,
+    color: Option<Color>,
+    /// The count being shown, and its text from the last layout.
+    shown: usize,
+    text: Option<TextLine>,
+    /// The size of the badge, from the last layout.
+    size: Size,
+}
+
+impl<T: Data> Badge<T> {
+    /// Create a badge on the top right corner of `child`, showing the count
+    /// that `count` gets from the data.
+    pub fn new(child: impl Widget<T> + 'static, count: impl Fn(&T) -> usize + 'static) -> Self {
+        Badge {
+            child: WidgetPod::new(child).boxed(),
+            count: Box::new(count),
+            corner: UnitPoint::TOP_RIGHT,
+            dot: false,
+            color: None,
+            shown: 0,
+            text: None,
+            size: Size::ZERO,
+        }
+    }
+
+    /// Builder-style method for choosing the corner, or other point, of the
+    /// child that the middle of the badge is on.
+    pub fn corner(mut self, corner: UnitPoint) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    /// Builder-style method for showing a dot, rather than the count, when
+    /// the count is not zero.
+    pub fn dot(mut self) -> Self {
+        self.dot = true;
+        self
+    }
+
+    /// Builder-style method for setting the color of the badge.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+/// The text of the badge for `count`, or `None` when it is hidden.
+fn badge_text(count: usize, dot: bool) -> Option<String> {
+    match count {
+        0 => None,
+        _ if dot => Some(String::new()),
+        count if count > MAX_COUNT => Some(format!("{}+", MAX_COUNT)),
+        count => Some(count.to_string()),
+    }
+}
+
+impl<T: Data> Widget<T> for Badge<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.child.event(ctx, event, data, env);
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        if let LifeCycle::WidgetAdded = event {
+            self.shown = (self.count)(data);
+        }
+        self.child.lifecycle(ctx, event, data, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        let count = (self.count)(data);
+        if count != self.shown {
+            self.shown = count;
+            // the badge gets wider or narrower with the count
+            ctx.request_layout();
+        }
+        self.child.update(ctx, data, env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Badge");
+        let size = self.child.layout(layout_ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL) * TEXT_SCALE;
+        self.text = None;
+        self.size = match badge_text(self.shown, self.dot) {
+            None => Size::ZERO,
+            Some(_) if self.dot => Size::new(DOT_SIZE, DOT_SIZE),
+            Some(text) => {
+                let font_name = env.get(theme::FONT_NAME);
+                let line = TextLine::new(layout_ctx.text(), font_name, font_size, &text, env);
+                let height = font_size * 1.4;
+                let width = (line.width() + height / 2.0).max(height);
+                self.text = Some(line);
+                Size::new(width, height)
+            }
+        };
+
+        // the badge, and the ring around it, can stick out of the child
+        let child = self.child.paint_insets();
+        let reach = self.size.width.max(self.size.height) / 2.0 + 1.5;
+        layout_ctx.set_paint_insets(Insets::new(
+            child.x0.max(reach),
+            child.y0.max(reach),
+            child.x1.max(reach),
+            child.y1.max(reach),
+        ));
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.child.paint_with_offset(paint_ctx, data, env);
+        if self.size == Size::ZERO {
+            return;
+        }
+
+        let center = self
+            .corner
+            .resolve(Rect::from_origin_size(Point::ORIGIN, paint_ctx.size()));
+        let origin = center - self.size.to_vec2() / 2.0;
+        let rect = Rect::from_origin_size(origin, self.size);
+        let color = self
+            .color
+            .clone()
+            .unwrap_or_else(|| env.get(theme::BADGE_COLOR));
+        paint_ctx.fill(RoundedRect::from_rect(rect, rect.height() / 2.0), &color);
+        // a ring in the window background sets the badge apart from the child
+        let ring = RoundedRect::from_rect(rect.inflate(0.75, 0.75), rect.height() / 2.0 + 0.75);
+        paint_ctx.stroke(ring, &env.get(theme::WINDOW_BACKGROUND_COLOR), 1.5);
+
+        if let Some(line) = &self.text {
+            let font_size = env.get(theme::TEXT_SIZE_NORMAL) * TEXT_SCALE;
+            // centered, with the baseline a little below the middle
+            let origin = Point::new(center.x - line.width() / 2.0, center.y + font_size * 0.35);
+            paint_ctx.draw_text(line.layout(), origin, &env.get(theme::BADGE_TEXT_COLOR));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn badges_hide_at_zero() {
+        assert_eq!(badge_text(0, false), None);
+        assert_eq!(badge_text(7, false), Some("7".to_string()));
+        assert_eq!(badge_text(99, false), Some("99".to_string()));
+        assert_eq!(badge_text(100, false), Some("99+".to_string()));
+        assert_eq!(badge_text(0, true), None);
+        assert_eq!(badge_text(5, true), Some(String::new()));
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A row of the segments of a path, for going back up it.
+
+use std::sync::Arc;
+
+use crate::kurbo::{Point, Size};
+use crate::text::TextLine;
+use crate::theme;
+use crate::{
+    commands, BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle,
+    LifeCycleCtx, PaintCtx, RenderContext, UpdateCtx, Widget,
+};
+
+/// What stands in for the segments that don't fit.
+const ELLIPSIS: &str = "…";
+
+/// The space on either side of a separator.
+const SEPARATOR_PADDING: f64 = 4.0;
+
+/// The path to where the user is, such as the folders above a folder in a
+/// file browser, with a separator between each segment.
+///
+/// Clicking a segment other than the last one, which is where the user is
+/// now, submits [`commands::BREADCRUMB_SELECTED`] to the window with the
+/// index of the segment; handle it with a [`Controller`] or an
+/// [`AppDelegate`]. When the path is too long to fit, the segments after
+/// the first collapse into `…`, and clicking that selects the last of the
+/// hidden segments.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use druid::widget::Breadcrumbs;
+/// use druid::Widget;
+///
+/// fn path_bar() -> impl Widget<Arc<Vec<String>>> {
+///     Breadcrumbs::new().separator("/")
+/// }
+/// ```
+///
+/// [`commands::BREADCRUMB_SELECTED`]: ../commands/constant.BREADCRUMB_SELECTED.html
+/// [`Controller`]: trait.Controller.html
+/// [`AppDelegate`]: ../trait.AppDelegate.html
+pub struct Breadcrumbs {
+    separator: String,
+    /// What is shown, from the last layout.
+    crumbs: Vec<Placed>,
+    separator_line: Option<TextLine>,
+    /// The crumb the mouse is over, and the one it was pressed on.
+    hot: Option<usize>,
+    pressed: Option<usize>,
+}
+
+/// A segment of the path, or the ellipsis standing in for the hidden ones
+/// and selecting the last of them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Crumb {
+    Segment(usize),
+    Ellipsis(usize),
+}
+
+/// A crumb, laid out.
+struct Placed {
+    crumb: Crumb,
+    line: TextLine,
+    x: f64,
+}
+
+impl Breadcrumbs {
+    /// Create breadcrumbs separated by `›`.
+    pub fn new() -> Breadcrumbs {
+        Breadcrumbs {
+            separator: "›".to_string(),
+            crumbs: Vec::new(),
+            separator_line: None,
+            hot: None,
+            pressed: None,
+        }
+    }
+
+    /// Builder-style method for setting the text between segments.
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// The index of the crumb at `x`, if it can be clicked.
+    fn crumb_at(&self, x: f64) -> Option<usize> {
+        // the last crumb is where the user already is
+        let clickable = self.crumbs.len().saturating_sub(1);
+        self.crumbs[..clickable]
+            .iter()
+            .position(|placed| x >= placed.x && x < placed.x + placed.line.width())
+    }
+}
+
+impl Default for Breadcrumbs {
+    fn default() -> Self {
+        Breadcrumbs::new()
+    }
+}
+
+impl Crumb {
+    /// The index of the segment that clicking this selects.
+    fn index(self) -> usize {
+        match self {
+            Crumb::Segment(index) | Crumb::Ellipsis(index) => index,
+        }
+    }
+}
+
+/// The crumbs to show for segments `widths` wide in `max_width`: all of
+/// them if they fit, and otherwise the first, the ellipsis, and as many of
+/// the last as fit, which is always at least the very last.
+fn collapse(widths: &[f64], separator: f64, ellipsis: f64, max_width: f64) -> Vec<Crumb> {
+    let total = widths.iter().sum::<f64>() + separator * widths.len().saturating_sub(1) as f64;
+    let count = widths.len();
+    if total <= max_width || count <= 2 {
+        return (0..count).map(Crumb::Segment).collect();
+    }
+
+    let head = widths[0] + separator + ellipsis + separator;
+    let mut tail = widths[count - 1];
+    let mut first_shown = count - 1;
+    // leave at least one segment hidden behind the ellipsis
+    while first_shown > 2 && head + tail + separator + widths[first_shown - 1] <= max_width {
+        first_shown -= 1;
+        tail += separator + widths[first_shown];
+    }
+
+    let mut crumbs = vec![Crumb::Segment(0), Crumb::Ellipsis(first_shown - 1)];
+    crumbs.extend((first_shown..count).map(Crumb::Segment));
+    crumbs
+}
+
+impl Widget<Arc<Vec<String>>> for Breadcrumbs {
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        _data: &mut Arc<Vec<String>>,
+        _env: &Env,
+    ) {
+        match event {
+            Event::MouseDown(mouse) => {
+                self.pressed = self.crumb_at(mouse.pos.x);
+                if self.pressed.is_some() {
+                    ctx.set_active(true);
+                    ctx.request_paint();
+                }
+            }
+            Event::MouseUp(mouse) if ctx.is_active() => {
+                ctx.set_active(false);
+                if let Some(pressed) = self.pressed.take() {
+                    if self.crumb_at(mouse.pos.x) == Some(pressed) {
+                        let index = self.crumbs[pressed].crumb.index();
+                        let command = Command::new(commands::BREADCRUMB_SELECTED, index);
+                        ctx.submit_command(command, None);
+                    }
+                }
+                ctx.request_paint();
+            }
+            Event::MouseMoved(mouse) => {
+                let hot = self.crumb_at(mouse.pos.x).filter(|_| ctx.is_hot());
+                if hot != self.hot {
+                    self.hot = hot;
+                    ctx.request_paint();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        _data: &Arc<Vec<String>>,
+        _env: &Env,
+    ) {
+        if let LifeCycle::HotChanged(false) = event {
+            self.hot = None;
+            ctx.request_paint();
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: &Arc<Vec<String>>,
+        data: &Arc<Vec<String>>,
+        _env: &Env,
+    ) {
+        if !old_data.same(data) {
+            self.hot = None;
+            self.pressed = None;
+            ctx.request_layout();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &Arc<Vec<String>>,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Breadcrumbs");
+        let font_name = env.get(theme::FONT_NAME);
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let text = layout_ctx.text();
+
+        let separator = TextLine::new(text, font_name, font_size, &self.separator, env);
+        let separator_width = separator.width() + 2.0 * SEPARATOR_PADDING;
+        let ellipsis = TextLine::new(text, font_name, font_size, ELLIPSIS, env);
+        let ellipsis_width = ellipsis.width();
+        let mut lines: Vec<Option<TextLine>> = data
+            .iter()
+            .map(|segment| Some(TextLine::new(text, font_name, font_size, segment, env)))
+            .collect();
+        let widths: Vec<f64> = lines
+            .iter()
+            .map(|line| line.as_ref().map_or(0.0, |line| line.width()))
+            .collect();
+
+        let mut ellipsis = Some(ellipsis);
+        let mut x = 0.0;
+        self.crumbs = collapse(&widths, separator_width, ellipsis_width, bc.max().width)
+            .into_iter()
+            .map(|crumb| {
+                let line = match crumb {
+                    Crumb::Segment(index) => lines[index].take(),
+                    Crumb::Ellipsis(_) => ellipsis.take(),
+                };
+                let line = line.expect("each crumb is shown once");
+                let placed = Placed { crumb, line, x };
+                x += placed.line.width() + separator_width;
+                placed
+            })
+            .collect();
+        self.separator_line = Some(separator);
+
+        let width = (x - separator_width).max(0.0);
+        bc.constrain(Size::new(width, font_size * 1.2))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _data: &Arc<Vec<String>>, env: &Env) {
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        // the same baseline as a label's
+        let baseline = (paint_ctx.size().height + font_size * 0.6) / 2.0;
+        let current = env.get(theme::LABEL_COLOR);
+        let parent = env.get(theme::PLACEHOLDER_COLOR);
+
+        let last = self.crumbs.len().saturating_sub(1);
+        for (index, placed) in self.crumbs.iter().enumerate() {
+            let color = if index == last || self.hot == Some(index) {
+                &current
+            } else {
+                &parent
+            };
+            let origin = Point::new(placed.x, baseline);
+            paint_ctx.draw_text(placed.line.layout(), origin, color);
+
+            if index != last {
+                if let Some(separator) = &self.separator_line {
+                    let x = placed.x + placed.line.width() + SEPARATOR_PADDING;
+                    paint_ctx.draw_text(separator.layout(), Point::new(x, baseline), &parent);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Crumb::{Ellipsis, Segment};
+
+    #[test]
+    fn long_paths_collapse() {
+        let widths = [10.0, 20.0, 30.0, 40.0, 50.0];
+        let crumbs = |max_width| collapse(&widths, 5.0, 8.0, max_width);
+
+        // 150 of segments and 20 of separators
+        let all = vec![Segment(0), Segment(1), Segment(2), Segment(3), Segment(4)];
+        assert_eq!(crumbs(170.0), all);
+
+        // the first, the ellipsis, and as many of the last as fit
+        assert_eq!(
+            crumbs(169.0),
+            vec![Segment(0), Ellipsis(1), Segment(2), Segment(3), Segment(4)]
+        );
+        assert_eq!(
+            crumbs(150.0),
+            vec![Segment(0), Ellipsis(2), Segment(3), Segment(4)]
+        );
+        assert_eq!(crumbs(50.0), vec![Segment(0), Ellipsis(3), Segment(4)]);
+
+        assert_eq!(
+            collapse(&[100.0, 100.0], 5.0, 8.0, 50.0),
+            vec![Segment(0), Segment(1)]
+        );
+        assert!(collapse(&[], 5.0, 8.0, 50.0).is_empty());
+    }
+}
@@ -18,8 +18,8 @@ use crate::kurbo::{Point, RoundedRect, Size};
 use crate::theme;
 use crate::widget::{Label, LabelText};
 use crate::{
-    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, LinearGradient,
-    PaintCtx, RenderContext, UnitPoint, UpdateCtx, Widget,
+    BoxConstraints, Data, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, LifeCycleCtx,
+    LinearGradient, PaintCtx, RenderContext, UnitPoint, UpdateCtx, Widget,
 };
 
 /// A button with a text label.
@@ -59,6 +59,7 @@ impl<T: Data> Widget<T> for Button<T> {
         match event {
             Event::MouseDown(_) => {
                 ctx.set_active(true);
+                ctx.request_focus();
                 ctx.request_paint();
             }
             Event::MouseUp(_) => {
@@ -70,13 +71,22 @@ impl<T: Data> Widget<T> for Button<T> {
                     }
                 }
             }
+            Event::KeyDown(key)
+                if key.key_code == KeyCode::Space || key.key_code == KeyCode::Return =>
+            {
+                ctx.set_handled();
+                ctx.request_paint();
+                (self.action)(ctx, data, env);
+            }
             _ => (),
         }
     }
 
     fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
-        if let LifeCycle::HotChanged(_) = event {
-            ctx.request_paint();
+        match event {
+            LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            LifeCycle::HotChanged(_) | LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
         }
         self.label.lifecycle(ctx, event, data, env)
     }
@@ -133,6 +143,7 @@ impl<T: Data> Widget<T> for Button<T> {
         );
 
         paint_ctx.fill(rounded_rect, &bg_gradient);
+        paint_ctx.stroke_focus_ring(rounded_rect, env);
 
         self.label.paint(paint_ctx, data, env);
     }
@@ -0,0 +1,672 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Line, bar and scatter charts.
+
+use std::sync::Arc;
+
+use crate::kurbo::{BezPath, Circle, Line, Point, Rect, RoundedRect, Size};
+use crate::piet::Color;
+use crate::text::TextLine;
+use crate::theme;
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    RenderContext, UpdateCtx, Widget,
+};
+
+/// The colors of the series that don't have their own.
+const PALETTE: [Color; 6] = [
+    Color::rgb8(0x5c, 0xc4, 0xff),
+    Color::rgb8(0xfb, 0x8c, 0x00),
+    Color::rgb8(0x66, 0xbb, 0x6a),
+    Color::rgb8(0xef, 0x53, 0x50),
+    Color::rgb8(0xab, 0x47, 0xbc),
+    Color::rgb8(0xfd, 0xd8, 0x35),
+];
+
+/// The size of a chart when it is not given one.
+const DEFAULT_SIZE: Size = Size::new(300.0, 200.0);
+
+/// The space around the plot, and between it and the labels.
+const PADDING: f64 = 6.0;
+
+/// The length of the tick marks.
+const TICK_LENGTH: f64 = 4.0;
+
+/// About how far apart the ticks are, along each axis.
+const X_TICK_SPACING: f64 = 80.0;
+const Y_TICK_SPACING: f64 = 40.0;
+
+/// How close the mouse has to be to a point to show its tooltip.
+const HOVER_DISTANCE: f64 = 10.0;
+
+/// The part of the space for each x value that its bars take up.
+const BAR_FILL: f64 = 0.8;
+
+/// A named set of points, for plotting in a [`Chart`].
+///
+/// [`Chart`]: struct.Chart.html
+#[derive(Debug, Clone)]
+pub struct Series {
+    name: String,
+    points: Arc<Vec<Point>>,
+    color: Option<Color>,
+}
+
+impl Series {
+    /// Create a series of `points`, as `(x, y)` pairs.
+    ///
+    /// The name is shown in the legend, and a series without one is left
+    /// out of it.
+    pub fn new(name: impl Into<String>, points: impl IntoIterator<Item = (f64, f64)>) -> Self {
+        Series {
+            name: name.into(),
+            points: Arc::new(points.into_iter().map(Point::from).collect()),
+            color: None,
+        }
+    }
+
+    /// Builder-style method for setting the color of the series.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// The points of the series.
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    fn color_or_default(&self, index: usize) -> Color {
+        self.color
+            .clone()
+            .unwrap_or_else(|| PALETTE[index % PALETTE.len()].clone())
+    }
+}
+
+impl Data for Series {
+    fn same(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.points.same(&other.points)
+            && self.color.as_ref().map(Color::as_rgba_u32)
+                == other.color.as_ref().map(Color::as_rgba_u32)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Line,
+    Bar,
+    Scatter,
+}
+
+/// A chart of one or more [`Series`], as lines, bars or points.
+///
+/// The axes fit the data, with ticks at round numbers, and there is a
+/// legend of the named series above the plot. Hovering over a point shows
+/// its value.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use druid::widget::{Chart, Series};
+/// use druid::Widget;
+///
+/// fn temperatures() -> impl Widget<Arc<Vec<Series>>> {
+///     Chart::line()
+/// }
+///
+/// let data = Arc::new(vec![
+///     Series::new("Inside", vec![(0.0, 20.5), (1.0, 21.0), (2.0, 21.2)]),
+///     Series::new("Outside", vec![(0.0, 8.0), (1.0, 9.5), (2.0, 7.0)]),
+/// ]);
+/// ```
+///
+/// [`Series`]: struct.Series.html
+pub struct Chart {
+    kind: Kind,
+    plot: Option<Plot>,
+    /// The series and point the mouse is over.
+    hovered: Option<(usize, usize)>,
+}
+
+/// Where everything goes, from the last layout.
+struct Plot {
+    area: Rect,
+    x: Axis,
+    y: Axis,
+    /// The width of the bars, and the space for each x value.
+    bar_width: f64,
+    labels: Vec<(TextLine, Point)>,
+    legend: Vec<(TextLine, Point, Color)>,
+}
+
+/// The range and ticks along one axis.
+#[derive(Debug, Clone, PartialEq)]
+struct Axis {
+    min: f64,
+    max: f64,
+    step: f64,
+}
+
+impl Axis {
+    /// An axis from a round number at or below `low` to one at or above
+    /// `high`, with about `max_ticks` ticks.
+    fn fit(low: f64, high: f64, max_ticks: usize) -> Axis {
+        let (low, high) = if high > low {
+            (low, high)
+        } else {
+            // a single value goes in the middle
+            let margin = if low == 0.0 { 1.0 } else { low.abs() * 0.1 };
+            (low - margin, low + margin)
+        };
+        let step = nice_step((high - low) / max_ticks.max(1) as f64);
+        Axis {
+            min: (low / step).floor() * step,
+            max: (high / step).ceil() * step,
+            step,
+        }
+    }
+
+    fn ticks(&self) -> impl Iterator<Item = f64> + '_ {
+        let count = ((self.max - self.min) / self.step).round() as usize;
+        (0..=count).map(move |i| self.min + i as f64 * self.step)
+    }
+
+    /// Where `value` goes, between `start` for the minimum and `end` for
+    /// the maximum.
+    fn position(&self, value: f64, start: f64, end: f64) -> f64 {
+        start + (value - self.min) / (self.max - self.min) * (end - start)
+    }
+}
+
+/// The step of 1, 2 or 5 times a power of ten closest above `raw`.
+fn nice_step(raw: f64) -> f64 {
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let fraction = raw / magnitude;
+    let nice = if fraction <= 1.0 {
+        1.0
+    } else if fraction <= 2.0 {
+        2.0
+    } else if fraction <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+/// `value` with as many decimals as ticks `step` apart need.
+fn format_value(value: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    // avoid showing "-0"
+    let value = if value.abs() < step * 1e-6 {
+        0.0
+    } else {
+        value
+    };
+    format!("{:.*}", decimals, value)
+}
+
+/// The smallest distance between two different x values, which is how far
+/// apart bars can be.
+fn bar_spacing(series: &[Series]) -> f64 {
+    let mut xs: Vec<f64> = series
+        .iter()
+        .flat_map(|series| series.points.iter().map(|point| point.x))
+        .collect();
+    xs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    xs.windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|gap| *gap > 0.0)
+        .fold(None, |min: Option<f64>, gap| {
+            Some(min.map_or(gap, |min| min.min(gap)))
+        })
+        .unwrap_or(1.0)
+}
+
+/// The lowest and highest of `values`, or `None` if there are none.
+fn bounds(values: impl Iterator<Item = f64>) -> Option<(f64, f64)> {
+    values.fold(None, |bounds, value| match bounds {
+        None => Some((value, value)),
+        Some((low, high)) => Some((low.min(value), high.max(value))),
+    })
+}
+
+impl Chart {
+    /// Create a chart that joins the points of each series with lines.
+    pub fn line() -> Chart {
+        Chart::new(Kind::Line)
+    }
+
+    /// Create a chart with a bar for each point, side by side where the
+    /// series have points at the same x.
+    pub fn bar() -> Chart {
+        Chart::new(Kind::Bar)
+    }
+
+    /// Create a chart of the points on their own.
+    pub fn scatter() -> Chart {
+        Chart::new(Kind::Scatter)
+    }
+
+    fn new(kind: Kind) -> Chart {
+        Chart {
+            kind,
+            plot: None,
+            hovered: None,
+        }
+    }
+
+    /// Where a point is drawn.
+    fn screen_point(plot: &Plot, point: Point) -> Point {
+        let area = plot.area;
+        Point::new(
+            plot.x.position(point.x, area.x0, area.x1),
+            plot.y.position(point.y, area.y1, area.y0),
+        )
+    }
+
+    /// The bar for `point` of series `index` of `count`.
+    fn bar_rect(plot: &Plot, index: usize, count: usize, point: Point) -> Rect {
+        let center = Self::screen_point(plot, point);
+        let group = plot.bar_width * count as f64;
+        let x0 = center.x - group / 2.0 + plot.bar_width * index as f64;
+        let zero = plot
+            .y
+            .position(0.0, plot.area.y1, plot.area.y0)
+            .max(plot.area.y0)
+            .min(plot.area.y1);
+        Rect::new(x0, zero, x0 + plot.bar_width, center.y).abs()
+    }
+
+    /// The series and point at `pos`, if there is one.
+    fn hit_test(&self, series: &[Series], pos: Point) -> Option<(usize, usize)> {
+        let plot = self.plot.as_ref()?;
+        let mut nearest = None;
+        let mut nearest_distance = HOVER_DISTANCE;
+        for (series_index, series_entry) in series.iter().enumerate() {
+            for (index, point) in series_entry.points.iter().enumerate() {
+                if self.kind == Kind::Bar {
+                    let bar = Self::bar_rect(plot, series_index, series.len(), *point);
+                    if bar.contains(pos) {
+                        return Some((series_index, index));
+                    }
+                    continue;
+                }
+                let distance = Self::screen_point(plot, *point).distance(pos);
+                if distance < nearest_distance {
+                    nearest = Some((series_index, index));
+                    nearest_distance = distance;
+                }
+            }
+        }
+        nearest
+    }
+}
+
+impl Widget<Arc<Vec<Series>>> for Chart {
+    fn event(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut Arc<Vec<Series>>,
+        _env: &Env,
+    ) {
+        if let Event::MouseMoved(mouse) = event {
+            let hovered = self.hit_test(data, mouse.pos).filter(|_| ctx.is_hot());
+            if hovered != self.hovered {
+                self.hovered = hovered;
+                ctx.request_paint();
+            }
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        _data: &Arc<Vec<Series>>,
+        _env: &Env,
+    ) {
+        if let LifeCycle::HotChanged(false) = event {
+            self.hovered = None;
+            ctx.request_paint();
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: &Arc<Vec<Series>>,
+        data: &Arc<Vec<Series>>,
+        _env: &Env,
+    ) {
+        if !old_data.same(data) {
+            self.hovered = None;
+            ctx.request_layout();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &Arc<Vec<Series>>,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Chart");
+        let size = Size::new(
+            if bc.is_width_bounded() {
+                bc.max().width
+            } else {
+                DEFAULT_SIZE.width
+            },
+            if bc.is_height_bounded() {
+                bc.max().height
+            } else {
+                DEFAULT_SIZE.height
+            },
+        );
+        let size = bc.constrain(size);
+        let font_name = env.get(theme::FONT_NAME);
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL) * 0.8;
+        let line_height = font_size * 1.2;
+        // the same baseline as a label's
+        let baseline = (line_height + font_size * 0.6) / 2.0;
+        let text = layout_ctx.text();
+
+        // the legend, in a row along the top
+        let mut legend = Vec::new();
+        let mut x = PADDING;
+        for (index, series) in data.iter().enumerate() {
+            if series.name.is_empty() {
+                continue;
+            }
+            let line = TextLine::new(text, font_name, font_size, &series.name, env);
+            let width = line.width();
+            legend.push((line, Point::new(x, PADDING), series.color_or_default(index)));
+            x += line_height + width + 2.0 * PADDING;
+        }
+        let top = if legend.is_empty() {
+            PADDING
+        } else {
+            PADDING * 2.0 + line_height
+        };
+        let bottom = size.height - PADDING - line_height - TICK_LENGTH;
+
+        let points = || data.iter().flat_map(|series| series.points.iter());
+        let (x_low, x_high) = bounds(points().map(|point| point.x)).unwrap_or((0.0, 1.0));
+        let (mut y_low, mut y_high) = bounds(points().map(|point| point.y)).unwrap_or((0.0, 1.0));
+        let spacing = bar_spacing(data);
+        let (x_low, x_high) = if self.kind == Kind::Bar {
+            // bars grow from zero, and need room on either side
+            y_low = y_low.min(0.0);
+            y_high = y_high.max(0.0);
+            (x_low - spacing / 2.0, x_high + spacing / 2.0)
+        } else {
+            (x_low, x_high)
+        };
+        let y_ticks = ((bottom - top) / Y_TICK_SPACING).floor() as usize;
+        let y = Axis::fit(y_low, y_high, y_ticks.max(2));
+
+        // the y labels decide where the plot starts
+        let mut labels = Vec::new();
+        let mut y_labels = Vec::new();
+        for tick in y.ticks() {
+            let line = TextLine::new(text, font_name, font_size, &format_value(tick, y.step), env);
+            y_labels.push((line, tick));
+        }
+        let label_width = y_labels
+            .iter()
+            .map(|(line, _)| line.width())
+            .fold(0.0, f64::max);
+        let left = PADDING + label_width + TICK_LENGTH + PADDING / 2.0;
+        let right = (size.width - PADDING).max(left + 1.0);
+        let area = Rect::new(left, top, right, bottom.max(top + 1.0));
+
+        let x_ticks = (area.width() / X_TICK_SPACING).floor() as usize;
+        let x = if self.kind == Kind::Bar {
+            // the ticks are at the bars, so no closer together than they are
+            let axis = Axis::fit(x_low, x_high, x_ticks.max(2));
+            let step = axis.step.max(spacing);
+            Axis {
+                min: x_low,
+                max: x_high,
+                step,
+            }
+        } else {
+            Axis::fit(x_low, x_high, x_ticks.max(2))
+        };
+
+        for (line, tick) in y_labels {
+            let pos = y.position(tick, area.y1, area.y0);
+            let origin = Point::new(
+                area.x0 - TICK_LENGTH - PADDING / 2.0 - line.width(),
+                pos - line_height / 2.0 + baseline,
+            );
+            labels.push((line, origin));
+        }
+        let x_ticks: Vec<f64> = if self.kind == Kind::Bar {
+            let first = (x_low / x.step).ceil() * x.step;
+            let count = ((x_high - first) / x.step).floor().max(0.0) as usize;
+            (0..=count).map(|i| first + i as f64 * x.step).collect()
+        } else {
+            x.ticks().collect()
+        };
+        for tick in x_ticks {
+            let line = TextLine::new(text, font_name, font_size, &format_value(tick, x.step), env);
+            let pos = x.position(tick, area.x0, area.x1);
+            let origin = Point::new(pos - line.width() / 2.0, area.y1 + TICK_LENGTH + baseline);
+            labels.push((line, origin));
+        }
+
+        let slot = (x.position(x.min + spacing, area.x0, area.x1) - area.x0) * BAR_FILL;
+        let bar_width = slot / data.len().max(1) as f64;
+        self.plot = Some(Plot {
+            area,
+            x,
+            y,
+            bar_width,
+            labels,
+            legend,
+        });
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &Arc<Vec<Series>>, env: &Env) {
+        let plot = match &self.plot {
+            Some(plot) => plot,
+            None => return,
+        };
+        let area = plot.area;
+        let axis_color = env.get(theme::BORDER_LIGHT);
+        let grid_color = env.get(theme::BORDER_DARK);
+        let text_color = env.get(theme::LABEL_COLOR);
+        let dim_text_color = env.get(theme::PLACEHOLDER_COLOR);
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL) * 0.8;
+        let line_height = font_size * 1.2;
+
+        // the grid, the axes, and the ticks
+        for tick in plot.y.ticks() {
+            let y = plot.y.position(tick, area.y1, area.y0);
+            paint_ctx.stroke(Line::new((area.x0, y), (area.x1, y)), &grid_color, 1.0);
+            let tick = Line::new((area.x0 - TICK_LENGTH, y), (area.x0, y));
+            paint_ctx.stroke(tick, &axis_color, 1.0);
+        }
+        paint_ctx.stroke(
+            Line::new((area.x0, area.y0), (area.x0, area.y1)),
+            &axis_color,
+            1.0,
+        );
+        paint_ctx.stroke(
+            Line::new((area.x0, area.y1), (area.x1, area.y1)),
+            &axis_color,
+            1.0,
+        );
+        for (line, origin) in &plot.labels {
+            paint_ctx.draw_text(line.layout(), *origin, &dim_text_color);
+        }
+
+        paint_ctx
+            .with_save(|paint_ctx| {
+                paint_ctx.clip(area);
+                for (index, series) in data.iter().enumerate() {
+                    let color = series.color_or_default(index);
+                    match self.kind {
+                        Kind::Line => {
+                            let mut path = BezPath::new();
+                            for (i, point) in series.points.iter().enumerate() {
+                                let point = Self::screen_point(plot, *point);
+                                if i == 0 {
+                                    path.move_to(point);
+                                } else {
+                                    path.line_to(point);
+                                }
+                            }
+                            paint_ctx.stroke(path, &color, 2.0);
+                        }
+                        Kind::Bar => {
+                            for point in series.points.iter() {
+                                let bar = Self::bar_rect(plot, index, data.len(), *point);
+                                paint_ctx.fill(bar, &color);
+                            }
+                        }
+                        Kind::Scatter => {
+                            for point in series.points.iter() {
+                                let center = Self::screen_point(plot, *point);
+                                paint_ctx.fill(Circle::new(center, 3.0), &color);
+                            }
+                        }
+                    }
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        for (line, origin, color) in &plot.legend {
+            let swatch = Rect::from_origin_size(*origin, (line_height, line_height));
+            paint_ctx.fill(
+                RoundedRect::from_rect(swatch.inflate(-2.0, -2.0), 2.0),
+                color,
+            );
+            let text_origin = Point::new(
+                swatch.x1 + PADDING / 2.0,
+                origin.y + (line_height + font_size * 0.6) / 2.0,
+            );
+            paint_ctx.draw_text(line.layout(), text_origin, &text_color);
+        }
+
+        // the tooltip of the point under the mouse
+        let (series_index, index) = match self.hovered {
+            Some(hovered) => hovered,
+            None => return,
+        };
+        let series = match data.get(series_index) {
+            Some(series) => series,
+            None => return,
+        };
+        let point = match series.points.get(index) {
+            Some(point) => *point,
+            None => return,
+        };
+        let anchor = if self.kind == Kind::Bar {
+            let bar = Self::bar_rect(plot, series_index, data.len(), point);
+            Point::new(bar.center().x, bar.y0)
+        } else {
+            let center = Self::screen_point(plot, point);
+            paint_ctx.stroke(Circle::new(center, 5.0), &text_color, 1.5);
+            center
+        };
+        let value = format!(
+            "{}, {}",
+            format_value(point.x, plot.x.step / 10.0),
+            format_value(point.y, plot.y.step / 10.0)
+        );
+        let text = if series.name.is_empty() {
+            value
+        } else {
+            format!("{}: {}", series.name, value)
+        };
+        let font_name = env.get(theme::FONT_NAME);
+        let line = TextLine::new(paint_ctx.text(), font_name, font_size, &text, env);
+        let size = paint_ctx.size();
+        let tip_size = Size::new(line.width() + 2.0 * PADDING, line_height + PADDING);
+        // above the point, and inside the chart
+        let x = (anchor.x - tip_size.width / 2.0)
+            .min(size.width - tip_size.width)
+            .max(0.0);
+        let y = (anchor.y - tip_size.height - PADDING).max(0.0);
+        let tip = Rect::from_origin_size((x, y), tip_size);
+        paint_ctx.fill(
+            RoundedRect::from_rect(tip, 4.0),
+            &env.get(theme::BACKGROUND_LIGHT),
+        );
+        paint_ctx.stroke(RoundedRect::from_rect(tip, 4.0), &axis_color, 1.0);
+        let origin = Point::new(
+            tip.x0 + PADDING,
+            tip.y0 + PADDING / 2.0 + (line_height + font_size * 0.6) / 2.0,
+        );
+        paint_ctx.draw_text(line.layout(), origin, &text_color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axes_fit_round_numbers() {
+        assert_eq!(nice_step(0.7), 1.0);
+        assert_eq!(nice_step(1.3), 2.0);
+        assert_eq!(nice_step(3.0), 5.0);
+        assert_eq!(nice_step(7.0), 10.0);
+        assert!((nice_step(0.013) - 0.02).abs() < 1e-12);
+
+        let axis = Axis::fit(3.2, 97.0, 5);
+        assert_eq!((axis.min, axis.max, axis.step), (0.0, 100.0, 20.0));
+        let ticks: Vec<f64> = axis.ticks().collect();
+        assert_eq!(ticks, vec![0.0, 20.0, 40.0, 60.0, 80.0, 100.0]);
+
+        let axis = Axis::fit(-0.35, 0.12, 4);
+        assert_eq!(axis.step, 0.2);
+        assert!((axis.min + 0.4).abs() < 1e-12 && (axis.max - 0.2).abs() < 1e-12);
+        assert_eq!(axis.position(axis.min, 10.0, 20.0), 10.0);
+
+        // a single value still gets a range
+        let axis = Axis::fit(5.0, 5.0, 4);
+        assert!(axis.min < 5.0 && axis.max > 5.0);
+    }
+
+    #[test]
+    fn values_are_formatted_for_their_ticks() {
+        assert_eq!(format_value(20.0, 20.0), "20");
+        assert_eq!(format_value(0.4, 0.2), "0.4");
+        assert_eq!(format_value(0.25, 0.05), "0.25");
+        assert_eq!(format_value(-1e-17, 0.1), "0.0");
+    }
+
+    #[test]
+    fn bars_are_as_far_apart_as_the_closest_values() {
+        let series = vec![
+            Series::new("a", vec![(0.0, 1.0), (2.0, 3.0)]),
+            Series::new("b", vec![(0.0, 2.0), (3.0, 1.0)]),
+        ];
+        assert_eq!(bar_spacing(&series), 1.0);
+        assert_eq!(bar_spacing(&[Series::new("", vec![(4.0, 1.0)])]), 1.0);
+        assert_eq!(bounds(vec![3.0, -1.0, 2.0].into_iter()), Some((-1.0, 3.0)));
+        assert_eq!(bounds(std::iter::empty()), None);
+    }
+}
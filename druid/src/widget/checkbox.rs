@@ -19,8 +19,8 @@ use crate::piet::{LineCap, LineJoin, LinearGradient, RenderContext, StrokeStyle,
 use crate::theme;
 use crate::widget::Align;
 use crate::{
-    BoxConstraints, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, UpdateCtx,
-    Widget,
+    BoxConstraints, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    UpdateCtx, Widget,
 };
 
 /// A checkbox that toggles a boolean
@@ -38,6 +38,7 @@ impl Widget<bool> for Checkbox {
         match event {
             Event::MouseDown(_) => {
                 ctx.set_active(true);
+                ctx.request_focus();
                 ctx.request_paint();
             }
             Event::MouseUp(_) => {
@@ -53,13 +54,22 @@ impl Widget<bool> for Checkbox {
                     ctx.request_paint();
                 }
             }
+            Event::KeyDown(key)
+                if key.key_code == KeyCode::Space || key.key_code == KeyCode::Return =>
+            {
+                *data = !*data;
+                ctx.set_handled();
+                ctx.request_paint();
+            }
             _ => (),
         }
     }
 
     fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &bool, _env: &Env) {
-        if let LifeCycle::HotChanged(_) = event {
-            ctx.request_paint();
+        match event {
+            LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            LifeCycle::HotChanged(_) | LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
         }
     }
 
@@ -107,6 +117,7 @@ impl Widget<bool> for Checkbox {
         };
 
         paint_ctx.stroke(rect, &border_color, 1.);
+        paint_ctx.stroke_focus_ring(rect, env);
 
         if *data {
             let mut path = BezPath::new();
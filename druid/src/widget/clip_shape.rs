@@ -0,0 +1,216 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that clips its child to a shape.
+
+use log::error;
+
+use crate::kurbo::{BezPath, Circle, ParamCurve, PathSeg, Point, Rect, RoundedRect, Shape, Size};
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    RenderContext, UpdateCtx, Widget, WidgetPod,
+};
+
+/// How closely curves are followed when they are turned into paths.
+const TOLERANCE: f64 = 0.1;
+
+enum Clip {
+    RoundedRect(f64),
+    Circle,
+    Path(BezPath),
+}
+
+/// A widget that clips its child to a rounded rectangle, a circle, or any
+/// path, such as for round avatars made from an [`Image`].
+///
+/// The child is only drawn inside the shape, and the mouse only reaches it
+/// there, so the corners that are cut off aren't hot and can't be clicked.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{ClipShape, Image, ImageData, WidgetExt};
+///
+/// let avatar = ClipShape::<()>::circle(Image::new(ImageData::empty())).fix_size(48.0, 48.0);
+/// ```
+///
+/// [`Image`]: struct.Image.html
+pub struct ClipShape<T> {
+    clip: Clip,
+    child: WidgetPod<T, Box<dyn Widget<T>>>,
+}
+
+impl<T: Data> ClipShape<T> {
+    /// Clip `child` to its rectangle, with the corners rounded by `radius`.
+    pub fn rounded(radius: f64, child: impl Widget<T> + 'static) -> Self {
+        ClipShape::new(Clip::RoundedRect(radius), child)
+    }
+
+    /// Clip `child` to the largest circle that fits in the middle of it.
+    pub fn circle(child: impl Widget<T> + 'static) -> Self {
+        ClipShape::new(Clip::Circle, child)
+    }
+
+    /// Clip `child` to `path`, which is in the child's coordinates.
+    pub fn path(path: BezPath, child: impl Widget<T> + 'static) -> Self {
+        ClipShape::new(Clip::Path(path), child)
+    }
+
+    fn new(clip: Clip, child: impl Widget<T> + 'static) -> Self {
+        ClipShape {
+            clip,
+            child: WidgetPod::new(child).boxed(),
+        }
+    }
+
+    fn rounded_rect(&self, size: Size, radius: f64) -> RoundedRect {
+        RoundedRect::from_rect(Rect::from_origin_size(Point::ORIGIN, size), radius)
+    }
+
+    fn circle_in(&self, size: Size) -> Circle {
+        let center = Point::new(size.width / 2.0, size.height / 2.0);
+        Circle::new(center, size.width.min(size.height) / 2.0)
+    }
+
+    /// The shape, for a widget of `size`.
+    fn shape(&self, size: Size) -> BezPath {
+        match &self.clip {
+            Clip::RoundedRect(radius) => self.rounded_rect(size, *radius).into_bez_path(TOLERANCE),
+            Clip::Circle => self.circle_in(size).into_bez_path(TOLERANCE),
+            Clip::Path(path) => path.clone(),
+        }
+    }
+
+    fn contains(&self, size: Size, pos: Point) -> bool {
+        match &self.clip {
+            Clip::RoundedRect(radius) => self.rounded_rect(size, *radius).winding(pos) != 0,
+            Clip::Circle => self.circle_in(size).winding(pos) != 0,
+            Clip::Path(path) => path_contains(path, pos),
+        }
+    }
+}
+
+/// Whether `pos` is inside `path`, by the nonzero rule, with the curves of
+/// the path followed closely enough for the mouse.
+fn path_contains(path: &BezPath, pos: Point) -> bool {
+    let mut winding = 0;
+    for segment in path.segments() {
+        let steps = match segment {
+            PathSeg::Line(_) => 1,
+            _ => 16,
+        };
+        let mut last = segment.eval(0.0);
+        for step in 1..=steps {
+            let next = segment.eval(step as f64 / steps as f64);
+            // count the pieces that cross the line going right from `pos`
+            if (last.y <= pos.y) != (next.y <= pos.y) {
+                let x = last.x + (pos.y - last.y) / (next.y - last.y) * (next.x - last.x);
+                if x > pos.x {
+                    winding += if next.y > last.y { 1 } else { -1 };
+                }
+            }
+            last = next;
+        }
+    }
+    winding != 0
+}
+
+impl<T: Data> Widget<T> for ClipShape<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        // the mouse misses the child outside the shape, unless the child is
+        // being dragged
+        let outside = match event {
+            Event::MouseDown(mouse) | Event::MouseUp(mouse) | Event::MouseMoved(mouse) => {
+                !self.child.has_active() && !self.contains(ctx.size(), mouse.pos)
+            }
+            _ => false,
+        };
+        match event {
+            Event::MouseMoved(mouse) if outside => {
+                // moved out of the child's rectangle, so that it isn't hot
+                let mut mouse = mouse.clone();
+                mouse.pos = Point::new(-1.0, -1.0);
+                self.child.event(ctx, &Event::MouseMoved(mouse), data, env);
+            }
+            _ if outside => (),
+            _ => self.child.event(ctx, event, data, env),
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.child.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        self.child.update(ctx, data, env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("ClipShape");
+        let size = self.child.layout(layout_ctx, bc, data, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        if let Err(e) = paint_ctx.save() {
+            error!("saving render context failed: {:?}", e);
+            return;
+        }
+        let shape = self.shape(paint_ctx.size());
+        paint_ctx.clip(shape);
+        self.child.paint_with_offset(paint_ctx, data, env);
+        if let Err(e) = paint_ctx.restore() {
+            error!("restoring render context failed: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Label;
+
+    #[test]
+    fn shapes_contain_the_points_inside_them() {
+        let size = Size::new(100.0, 60.0);
+        let circle = ClipShape::<()>::circle(Label::new("avatar"));
+        assert!(circle.contains(size, Point::new(50.0, 30.0)));
+        assert!(circle.contains(size, Point::new(75.0, 30.0)));
+        // inside the rectangle, but beside the circle
+        assert!(!circle.contains(size, Point::new(90.0, 30.0)));
+        assert!(!circle.contains(size, Point::new(5.0, 5.0)));
+
+        let rounded = ClipShape::<()>::rounded(10.0, Label::new("card"));
+        assert!(rounded.contains(size, Point::new(5.0, 30.0)));
+        assert!(rounded.contains(size, Point::new(50.0, 2.0)));
+        assert!(!rounded.contains(size, Point::new(1.0, 1.0)));
+
+        let mut triangle = BezPath::new();
+        triangle.move_to((0.0, 0.0));
+        triangle.line_to((100.0, 0.0));
+        triangle.line_to((0.0, 60.0));
+        triangle.close_path();
+        let triangle = ClipShape::<()>::path(triangle, Label::new("corner"));
+        assert!(triangle.contains(size, Point::new(10.0, 10.0)));
+        assert!(!triangle.contains(size, Point::new(90.0, 50.0)));
+    }
+}
@@ -0,0 +1,571 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget for choosing a color.
+
+use crate::kurbo::{Circle, Line, Point, Rect, Size};
+use crate::piet::{Color, GradientStop};
+use crate::theme;
+use crate::widget::TextBox;
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, LinearGradient,
+    PaintCtx, RenderContext, UnitPoint, UpdateCtx, Widget, WidgetPod,
+};
+
+/// The swatches a picker has unless it is given others.
+const DEFAULT_SWATCHES: [Color; 10] = [
+    Color::rgb8(0x00, 0x00, 0x00),
+    Color::rgb8(0x80, 0x80, 0x80),
+    Color::rgb8(0xff, 0xff, 0xff),
+    Color::rgb8(0xe5, 0x39, 0x35),
+    Color::rgb8(0xfb, 0x8c, 0x00),
+    Color::rgb8(0xfd, 0xd8, 0x35),
+    Color::rgb8(0x43, 0xa0, 0x47),
+    Color::rgb8(0x00, 0x8d, 0xdd),
+    Color::rgb8(0x5e, 0x35, 0xb1),
+    Color::rgb8(0xd8, 0x1b, 0x60),
+];
+
+/// The width of a picker when it is not given one.
+const DEFAULT_WIDTH: f64 = 200.0;
+
+/// The space between the parts of the picker.
+const GAP: f64 = 8.0;
+
+/// The size of one square of the checkerboard behind transparent colors.
+const CHECKER_SIZE: f64 = 6.0;
+
+/// A color picker.
+///
+/// The color is chosen by its hue, saturation and value, from a square of
+/// saturation and value over a strip of hues; below those are a strip for
+/// the opacity, a box for typing the color as hex, such as `#ff8000` or
+/// `#ff800080`, and swatches of colors to choose with a click.
+///
+/// It edits a [`Color`]; use a [`lens`] to pick a color in part of the
+/// data.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{ColorPicker, WidgetExt};
+/// use druid::{Color, Data, Lens};
+///
+/// #[derive(Clone, Data, Lens)]
+/// struct Theme {
+///     background: Color,
+///     accent: Color,
+/// }
+///
+/// let picker = ColorPicker::new()
+///     .swatches(vec![Color::WHITE, Color::BLACK])
+///     .lens(Theme::accent);
+/// ```
+///
+/// [`Color`]: ../struct.Color.html
+/// [`lens`]: trait.WidgetExt.html#method.lens
+pub struct ColorPicker {
+    /// The color as it is being chosen; this keeps the hue of greys, which
+    /// a `Color` does not have.
+    hsva: Hsva,
+    hex: WidgetPod<String, TextBox>,
+    /// The text in the hex box.
+    text: String,
+    swatches: Vec<Color>,
+    regions: Regions,
+    /// The part that the mouse is choosing with.
+    dragging: Option<Part>,
+}
+
+/// A color as hue, saturation, value and alpha, each from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Hsva {
+    hue: f64,
+    saturation: f64,
+    value: f64,
+    alpha: f64,
+}
+
+/// The parts of the picker that can be clicked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Part {
+    SaturationValue,
+    Hue,
+    Alpha,
+    Swatch(usize),
+}
+
+/// Where the parts of the picker are.
+#[derive(Debug, Clone, Default)]
+struct Regions {
+    saturation_value: Rect,
+    hue: Rect,
+    alpha: Rect,
+    /// A box of the color as it is.
+    preview: Rect,
+    hex: Rect,
+    swatches: Vec<Rect>,
+    size: Size,
+}
+
+impl ColorPicker {
+    /// Create a color picker.
+    pub fn new() -> ColorPicker {
+        ColorPicker {
+            hsva: Hsva::from_color(&Color::BLACK),
+            hex: WidgetPod::new(TextBox::raw()),
+            text: String::new(),
+            swatches: DEFAULT_SWATCHES.to_vec(),
+            regions: Regions::default(),
+            dragging: None,
+        }
+    }
+
+    /// Builder-style method for setting the colors of the swatches.
+    pub fn swatches(mut self, swatches: impl IntoIterator<Item = Color>) -> Self {
+        self.swatches = swatches.into_iter().collect();
+        self
+    }
+
+    /// Choose the color for the mouse at `pos` on `part`.
+    fn choose(&mut self, part: Part, pos: Point) -> Color {
+        let regions = &self.regions;
+        let fraction = |rect: Rect, x: f64| ((x - rect.x0) / rect.width()).clamp(0.0, 1.0);
+        match part {
+            Part::SaturationValue => {
+                let rect = regions.saturation_value;
+                self.hsva.saturation = fraction(rect, pos.x);
+                self.hsva.value = 1.0 - ((pos.y - rect.y0) / rect.height()).clamp(0.0, 1.0);
+            }
+            Part::Hue => self.hsva.hue = fraction(regions.hue, pos.x),
+            Part::Alpha => self.hsva.alpha = fraction(regions.alpha, pos.x),
+            Part::Swatch(index) => self.hsva = Hsva::from_color(&self.swatches[index]),
+        }
+        self.hsva.to_color()
+    }
+
+    /// Show `color`, unless it is the color already being shown.
+    fn show(&mut self, color: &Color) {
+        if !self.hsva.to_color().same(color) {
+            self.hsva = self.hsva.with_color(color);
+        }
+        if !matches!(parse_hex(&self.text), Some(text) if text.same(color)) {
+            self.text = to_hex(color);
+        }
+    }
+}
+
+impl Default for ColorPicker {
+    fn default() -> Self {
+        ColorPicker::new()
+    }
+}
+
+impl Hsva {
+    fn from_color(color: &Color) -> Hsva {
+        let rgba = color.as_rgba_u32();
+        let channel = |shift: u32| f64::from((rgba >> shift) & 0xff) / 255.0;
+        let (r, g, b, alpha) = (channel(24), channel(16), channel(8), channel(0));
+        let max = r.max(g).max(b);
+        let range = max - r.min(g).min(b);
+        let hue = if range == 0.0 {
+            0.0
+        } else if max == r {
+            ((g - b) / range).rem_euclid(6.0) / 6.0
+        } else if max == g {
+            ((b - r) / range + 2.0) / 6.0
+        } else {
+            ((r - g) / range + 4.0) / 6.0
+        };
+        let saturation = if max == 0.0 { 0.0 } else { range / max };
+        Hsva {
+            hue,
+            saturation,
+            value: max,
+            alpha,
+        }
+    }
+
+    /// `color`, keeping this hue and saturation where the color has none,
+    /// so that dragging through grey or black does not lose them.
+    fn with_color(self, color: &Color) -> Hsva {
+        let mut hsva = Hsva::from_color(color);
+        if hsva.value == 0.0 {
+            hsva.saturation = self.saturation;
+        }
+        if hsva.saturation == 0.0 || hsva.value == 0.0 {
+            hsva.hue = self.hue;
+        }
+        hsva
+    }
+
+    fn to_color(self) -> Color {
+        let (r, g, b) = hsv_to_rgb(self.hue, self.saturation, self.value);
+        Color::rgba(r, g, b, self.alpha)
+    }
+}
+
+/// The red, green and blue of a hue, saturation and value, all from 0 to 1.
+fn hsv_to_rgb(hue: f64, saturation: f64, value: f64) -> (f64, f64, f64) {
+    let sector = (hue * 6.0).rem_euclid(6.0);
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let min = value - chroma;
+    (r + min, g + min, b + min)
+}
+
+/// A color from hex, such as `#ff8000`, `#ff800080`, `#f80` or `#f808`; the
+/// `#` is optional.
+fn parse_hex(text: &str) -> Option<Color> {
+    let text = text.trim();
+    let digits = text.strip_prefix('#').unwrap_or(text);
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits = match digits.len() {
+        3 | 4 => digits.chars().flat_map(|c| vec![c, c]).collect(),
+        6 | 8 => digits.to_string(),
+        _ => return None,
+    };
+    let rgba = u32::from_str_radix(&digits, 16).ok()?;
+    if digits.len() == 6 {
+        Some(Color::from_rgba32_u32(rgba << 8 | 0xff))
+    } else {
+        Some(Color::from_rgba32_u32(rgba))
+    }
+}
+
+/// A color as hex, leaving out the alpha when it is opaque.
+fn to_hex(color: &Color) -> String {
+    let rgba = color.as_rgba_u32();
+    if rgba & 0xff == 0xff {
+        format!("#{:06x}", rgba >> 8)
+    } else {
+        format!("#{:08x}", rgba)
+    }
+}
+
+impl Regions {
+    /// Lay out a picker `width` wide, with strips `strip` high and text and
+    /// swatches `row` high.
+    fn new(width: f64, strip: f64, row: f64, swatches: usize) -> Regions {
+        let saturation_value = Rect::new(0.0, 0.0, width, (width * 0.6).round());
+        let mut y = saturation_value.y1 + GAP;
+        let hue = Rect::new(0.0, y, width, y + strip);
+        y = hue.y1 + GAP;
+        let alpha = Rect::new(0.0, y, width, y + strip);
+        y = alpha.y1 + GAP;
+        let preview = Rect::new(0.0, y, 2.0 * row, y + row);
+        let hex = Rect::new(preview.x1 + GAP, y, width.max(preview.x1 + GAP), y + row);
+        y = hex.y1 + GAP;
+
+        let per_row = (((width + GAP) / (row + GAP)).floor() as usize).max(1);
+        let swatches: Vec<Rect> = (0..swatches)
+            .map(|index| {
+                let x = (index % per_row) as f64 * (row + GAP);
+                let y = y + (index / per_row) as f64 * (row + GAP);
+                Rect::new(x, y, x + row, y + row)
+            })
+            .collect();
+        let height = swatches.last().map_or(hex.y1, |swatch| swatch.y1);
+        Regions {
+            saturation_value,
+            hue,
+            alpha,
+            preview,
+            hex,
+            swatches,
+            size: Size::new(width, height),
+        }
+    }
+
+    fn hit_test(&self, pos: Point) -> Option<Part> {
+        if self.saturation_value.contains(pos) {
+            Some(Part::SaturationValue)
+        } else if self.hue.contains(pos) {
+            Some(Part::Hue)
+        } else if self.alpha.contains(pos) {
+            Some(Part::Alpha)
+        } else {
+            let index = self.swatches.iter().position(|rect| rect.contains(pos))?;
+            Some(Part::Swatch(index))
+        }
+    }
+}
+
+impl Widget<Color> for ColorPicker {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut Color, env: &Env) {
+        let text = self.text.clone();
+        self.hex.event(ctx, event, &mut self.text, env);
+        if self.text != text {
+            if let Some(color) = parse_hex(&self.text) {
+                self.hsva = self.hsva.with_color(&color);
+                *data = color;
+            }
+            return;
+        }
+
+        match event {
+            Event::MouseDown(mouse) => {
+                if let Some(part) = self.regions.hit_test(mouse.pos) {
+                    ctx.set_active(true);
+                    self.dragging = Some(part);
+                    *data = self.choose(part, mouse.pos);
+                }
+            }
+            Event::MouseMoved(mouse) => {
+                if let Some(part) = self.dragging.filter(|_| ctx.is_active()) {
+                    *data = self.choose(part, mouse.pos);
+                }
+            }
+            Event::MouseUp(_) => {
+                ctx.set_active(false);
+                self.dragging = None;
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &Color, env: &Env) {
+        if let LifeCycle::WidgetAdded = event {
+            self.hsva = Hsva::from_color(data);
+            self.text = to_hex(data);
+        }
+        self.hex.lifecycle(ctx, event, &self.text, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &Color, data: &Color, env: &Env) {
+        if !old_data.same(data) {
+            self.show(data);
+            ctx.request_paint();
+        }
+        self.hex.update(ctx, &self.text, env);
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &Color,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("ColorPicker");
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            DEFAULT_WIDTH
+        };
+        self.regions = Regions::new(
+            width,
+            env.get(theme::BASIC_WIDGET_HEIGHT),
+            env.get(theme::BORDERED_WIDGET_HEIGHT),
+            self.swatches.len(),
+        );
+        let hex = self.regions.hex;
+        let hex_bc = BoxConstraints::tight(hex.size());
+        self.hex.layout(ctx, &hex_bc, &self.text, env);
+        self.hex.set_layout_rect(hex);
+        bc.constrain(self.regions.size)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &Color, env: &Env) {
+        let regions = self.regions.clone();
+        let Hsva {
+            hue,
+            saturation,
+            value,
+            ..
+        } = self.hsva;
+        let border = env.get(theme::BORDER_DARK);
+        let marker = Color::WHITE;
+
+        // saturation goes to the right, over value going down
+        let full = Hsva {
+            saturation: 1.0,
+            value: 1.0,
+            alpha: 1.0,
+            ..self.hsva
+        };
+        let square = regions.saturation_value;
+        paint_ctx.fill(square, &full.to_color());
+        let whites = (Color::WHITE, Color::WHITE.with_alpha(0.0));
+        paint_ctx.fill(
+            square,
+            &LinearGradient::new(UnitPoint::LEFT, UnitPoint::RIGHT, whites),
+        );
+        let blacks = (Color::BLACK.with_alpha(0.0), Color::BLACK);
+        paint_ctx.fill(
+            square,
+            &LinearGradient::new(UnitPoint::TOP, UnitPoint::BOTTOM, blacks),
+        );
+        paint_ctx.stroke(square, &border, 1.0);
+        let chosen = Point::new(
+            square.x0 + saturation * square.width(),
+            square.y0 + (1.0 - value) * square.height(),
+        );
+        paint_ctx.stroke(Circle::new(chosen, 5.0), &Color::BLACK, 3.0);
+        paint_ctx.stroke(Circle::new(chosen, 5.0), &marker, 1.5);
+
+        let hues: Vec<GradientStop> = (0..=6)
+            .map(|sector| {
+                let (r, g, b) = hsv_to_rgb(f64::from(sector) / 6.0, 1.0, 1.0);
+                GradientStop {
+                    pos: sector as f32 / 6.0,
+                    color: Color::rgb(r, g, b),
+                }
+            })
+            .collect();
+        paint_ctx.fill(
+            regions.hue,
+            &LinearGradient::new(UnitPoint::LEFT, UnitPoint::RIGHT, hues),
+        );
+        paint_ctx.stroke(regions.hue, &border, 1.0);
+
+        paint_checkerboard(paint_ctx, regions.alpha);
+        let opaque = Hsva {
+            alpha: 1.0,
+            ..self.hsva
+        }
+        .to_color();
+        let alphas = (opaque.clone().with_alpha(0.0), opaque);
+        paint_ctx.fill(
+            regions.alpha,
+            &LinearGradient::new(UnitPoint::LEFT, UnitPoint::RIGHT, alphas),
+        );
+        paint_ctx.stroke(regions.alpha, &border, 1.0);
+
+        for (strip, fraction) in &[(regions.hue, hue), (regions.alpha, self.hsva.alpha)] {
+            let x = strip.x0 + fraction * strip.width();
+            let line = Line::new((x, strip.y0 - 2.0), (x, strip.y1 + 2.0));
+            paint_ctx.stroke(line, &Color::BLACK, 4.0);
+            paint_ctx.stroke(line, &marker, 2.0);
+        }
+
+        paint_checkerboard(paint_ctx, regions.preview);
+        paint_ctx.fill(regions.preview, data);
+        paint_ctx.stroke(regions.preview, &border, 1.0);
+        self.hex.paint_with_offset(paint_ctx, &self.text, env);
+
+        for (swatch, color) in regions.swatches.iter().zip(&self.swatches) {
+            paint_checkerboard(paint_ctx, *swatch);
+            paint_ctx.fill(*swatch, color);
+            let stroke = if color.same(data) { &marker } else { &border };
+            paint_ctx.stroke(*swatch, stroke, 1.0);
+        }
+    }
+}
+
+/// Fill `rect` with grey squares, to show through transparent colors.
+fn paint_checkerboard(paint_ctx: &mut PaintCtx, rect: Rect) {
+    paint_ctx.fill(rect, &Color::grey8(0xcc));
+    let columns = (rect.width() / CHECKER_SIZE).ceil() as usize;
+    let rows = (rect.height() / CHECKER_SIZE).ceil() as usize;
+    for row in 0..rows {
+        for column in (row % 2..columns).step_by(2) {
+            let origin = Point::new(
+                rect.x0 + column as f64 * CHECKER_SIZE,
+                rect.y0 + row as f64 * CHECKER_SIZE,
+            );
+            let square = Rect::from_origin_size(origin, (CHECKER_SIZE, CHECKER_SIZE));
+            paint_ctx.fill(square.intersect(rect), &Color::grey8(0x88));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_and_hsv() {
+        let orange = Hsva::from_color(&Color::rgb8(0xff, 0x80, 0x00));
+        assert!((orange.hue - 30.1 / 360.0).abs() < 1e-3);
+        assert_eq!(
+            (orange.saturation, orange.value, orange.alpha),
+            (1.0, 1.0, 1.0)
+        );
+
+        for rgba in &[
+            0x0000_00ff,
+            0xff80_00ff,
+            0x1234_5678,
+            0x00ff_ff80,
+            0xc0c0_c000,
+        ] {
+            let color = Color::from_rgba32_u32(*rgba);
+            assert_eq!(Hsva::from_color(&color).to_color().as_rgba_u32(), *rgba);
+        }
+
+        // black and greys keep the hue that was chosen
+        let red = Hsva::from_color(&Color::rgb8(0xff, 0x00, 0x00));
+        let green = Hsva::from_color(&Color::rgb8(0x00, 0xff, 0x00));
+        assert_eq!(green.with_color(&Color::BLACK).hue, green.hue);
+        assert_eq!(green.with_color(&Color::grey8(0x80)).hue, green.hue);
+        assert_eq!(green.with_color(&Color::rgb8(0xff, 0, 0)).hue, red.hue);
+    }
+
+    #[test]
+    fn hex() {
+        let orange = Color::rgb8(0xff, 0x80, 0x00);
+        assert_eq!(to_hex(&orange), "#ff8000");
+        assert_eq!(to_hex(&orange.clone().with_alpha(0.0)), "#ff800000");
+        assert!(parse_hex("#ff8000").unwrap().same(&orange));
+        assert!(parse_hex(" FF8000 ").unwrap().same(&orange));
+        assert!(parse_hex("#f80")
+            .unwrap()
+            .same(&Color::rgb8(0xff, 0x88, 0x00)));
+        assert_eq!(parse_hex("#f808").unwrap().as_rgba_u32(), 0xff88_0088);
+        assert_eq!(parse_hex("#12345678").unwrap().as_rgba_u32(), 0x1234_5678);
+        assert!(parse_hex("#ff80").is_none());
+        assert!(parse_hex("#gg8000").is_none());
+        assert!(parse_hex("").is_none());
+    }
+
+    #[test]
+    fn parts_of_the_picker() {
+        let regions = Regions::new(200.0, 18.0, 24.0, 10);
+        assert_eq!(regions.saturation_value.height(), 120.0);
+        // six swatches fit on a row
+        assert_eq!(regions.swatches[5].y0, regions.swatches[0].y0);
+        assert_eq!(regions.swatches[6].x0, 0.0);
+        assert_eq!(regions.size.height, regions.swatches[9].y1);
+
+        let hit = |x, y| regions.hit_test(Point::new(x, y));
+        assert_eq!(hit(100.0, 60.0), Some(Part::SaturationValue));
+        assert_eq!(hit(100.0, 130.0), Some(Part::Hue));
+        assert_eq!(hit(100.0, 158.0), Some(Part::Alpha));
+        assert_eq!(hit(100.0, 190.0), None);
+        assert_eq!(hit(40.0, 220.0), Some(Part::Swatch(1)));
+        assert_eq!(hit(28.0, 220.0), None);
+
+        let mut picker = ColorPicker::new();
+        picker.regions = regions;
+        let color = picker.choose(Part::SaturationValue, Point::new(200.0, 0.0));
+        assert!(color.same(&Color::rgb8(0xff, 0x00, 0x00)));
+        let color = picker.choose(Part::Hue, Point::new(200.0 / 3.0, 130.0));
+        assert!(color.same(&Color::rgb8(0x00, 0xff, 0x00)));
+        let color = picker.choose(Part::Alpha, Point::new(-10.0, 158.0));
+        assert_eq!(color.as_rgba_u32(), 0x00ff_0000);
+        let color = picker.choose(Part::Swatch(2), Point::ORIGIN);
+        assert!(color.same(&Color::WHITE));
+    }
+}
@@ -0,0 +1,605 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A container that places its children by solving constraints.
+
+use cassowary::strength::{MEDIUM, REQUIRED, STRONG, WEAK};
+use cassowary::{Expression, RelationalOperator, Solver, Term, Variable};
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    UpdateCtx, Widget, WidgetPod,
+};
+
+/// How strongly children keep to the size they want to be: weaker than
+/// any constraint but [`Priority::Weak`] ones.
+///
+/// [`Priority::Weak`]: enum.Priority.html#variant.Weak
+const PREFERRED_SIZE: f64 = MEDIUM / 10.0;
+
+/// How strongly the layout fills the space it is given.
+const FILL: f64 = WEAK / 2.0;
+
+/// A child of a [`ConstraintLayout`], or the layout itself, whose edges
+/// constraints are made from.
+///
+/// [`ConstraintLayout`]: struct.ConstraintLayout.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LayoutItem(Option<usize>);
+
+/// A line or length of a [`LayoutItem`].
+///
+/// [`LayoutItem`]: struct.LayoutItem.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Anchor {
+    item: LayoutItem,
+    edge: Edge,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    CenterX,
+    CenterY,
+    Width,
+    Height,
+}
+
+/// How much a [`Constraint`] matters when they can't all be met.
+///
+/// [`Constraint`]: struct.Constraint.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Priority {
+    /// It must be met; one that conflicts with other required constraints
+    /// is ignored, with a warning.
+    Required,
+    /// It gives way only to required constraints.
+    Strong,
+    /// It gives way to strong and required constraints.
+    Medium,
+    /// It gives way to all others, and to the size children want to be.
+    Weak,
+}
+
+/// What an [`Anchor`] is constrained to: another anchor, or a fixed length.
+///
+/// [`Anchor`]: struct.Anchor.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ConstraintTarget {
+    /// Another anchor.
+    Anchor(Anchor),
+    /// A length in pixels, or a position from the layout's top left.
+    Value(f64),
+}
+
+/// A relation between an [`Anchor`] and a [`ConstraintTarget`], such as
+/// `field.left()` being 8 pixels to the right of `label.right()`.
+///
+/// The relation is `anchor (=, >= or <=) target × ratio + offset`. Make one
+/// with the methods on [`Anchor`] and adjust it with the builder methods.
+///
+/// [`Anchor`]: struct.Anchor.html
+/// [`ConstraintTarget`]: enum.ConstraintTarget.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    anchor: Anchor,
+    relation: Relation,
+    target: ConstraintTarget,
+    ratio: f64,
+    offset: f64,
+    priority: Priority,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Relation {
+    Equal,
+    AtLeast,
+    AtMost,
+}
+
+/// A container that places its children by solving constraints between
+/// their edges, centers and sizes, using the [Cassowary] algorithm.
+///
+/// This is for layouts that are awkward to build from nested [`Flex`]es,
+/// such as dialogs where fields in different rows line up. Each child is
+/// added with [`add_child`], which returns a [`LayoutItem`] to make
+/// [`Constraint`]s from; [`LayoutItem::PARENT`] is the layout itself.
+///
+/// Children keep to the size they want to be unless constraints say
+/// otherwise, and a child with nothing placing it sits at the top left.
+/// The layout fills the space it is given when that is bounded, and fits
+/// around its children otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{ConstraintLayout, Label, LayoutItem, TextBox};
+///
+/// let mut form = ConstraintLayout::<String>::new();
+/// let label = form.add_child(Label::new("Name"));
+/// let field = form.add_child(TextBox::new());
+/// let parent = LayoutItem::PARENT;
+///
+/// form.constrain(label.left().equal_to(parent.left()).plus(12.0));
+/// form.constrain(field.left().equal_to(label.right()).plus(8.0));
+/// form.constrain(field.right().equal_to(parent.right()).plus(-12.0));
+/// form.constrain(label.center_y().equal_to(field.center_y()));
+/// form.constrain(field.top().equal_to(parent.top()).plus(12.0));
+/// ```
+///
+/// [Cassowary]: https://constraints.cs.washington.edu/cassowary/
+/// [`Flex`]: struct.Flex.html
+/// [`add_child`]: #method.add_child
+/// [`LayoutItem`]: struct.LayoutItem.html
+/// [`LayoutItem::PARENT`]: struct.LayoutItem.html#associatedconstant.PARENT
+/// [`Constraint`]: struct.Constraint.html
+pub struct ConstraintLayout<T> {
+    children: Vec<WidgetPod<T, Box<dyn Widget<T>>>>,
+    constraints: Vec<Constraint>,
+}
+
+/// The solver's variables for one child.
+#[derive(Copy, Clone)]
+struct Frame {
+    left: Variable,
+    top: Variable,
+    width: Variable,
+    height: Variable,
+}
+
+impl Frame {
+    fn new() -> Frame {
+        Frame {
+            left: Variable::new(),
+            top: Variable::new(),
+            width: Variable::new(),
+            height: Variable::new(),
+        }
+    }
+}
+
+impl LayoutItem {
+    /// The [`ConstraintLayout`] itself, whose left and top edges are zero.
+    ///
+    /// [`ConstraintLayout`]: struct.ConstraintLayout.html
+    pub const PARENT: LayoutItem = LayoutItem(None);
+
+    fn anchor(self, edge: Edge) -> Anchor {
+        Anchor { item: self, edge }
+    }
+
+    /// The left edge.
+    pub fn left(self) -> Anchor {
+        self.anchor(Edge::Left)
+    }
+
+    /// The right edge.
+    pub fn right(self) -> Anchor {
+        self.anchor(Edge::Right)
+    }
+
+    /// The top edge.
+    pub fn top(self) -> Anchor {
+        self.anchor(Edge::Top)
+    }
+
+    /// The bottom edge.
+    pub fn bottom(self) -> Anchor {
+        self.anchor(Edge::Bottom)
+    }
+
+    /// The line down the middle.
+    pub fn center_x(self) -> Anchor {
+        self.anchor(Edge::CenterX)
+    }
+
+    /// The line across the middle.
+    pub fn center_y(self) -> Anchor {
+        self.anchor(Edge::CenterY)
+    }
+
+    /// The width.
+    pub fn width(self) -> Anchor {
+        self.anchor(Edge::Width)
+    }
+
+    /// The height.
+    pub fn height(self) -> Anchor {
+        self.anchor(Edge::Height)
+    }
+}
+
+impl Anchor {
+    fn constraint(self, relation: Relation, target: impl Into<ConstraintTarget>) -> Constraint {
+        Constraint {
+            anchor: self,
+            relation,
+            target: target.into(),
+            ratio: 1.0,
+            offset: 0.0,
+            priority: Priority::Required,
+        }
+    }
+
+    /// A constraint that this is at `target`, which is another anchor or
+    /// a length.
+    pub fn equal_to(self, target: impl Into<ConstraintTarget>) -> Constraint {
+        self.constraint(Relation::Equal, target)
+    }
+
+    /// A constraint that this is at or after `target`.
+    pub fn at_least(self, target: impl Into<ConstraintTarget>) -> Constraint {
+        self.constraint(Relation::AtLeast, target)
+    }
+
+    /// A constraint that this is at or before `target`.
+    pub fn at_most(self, target: impl Into<ConstraintTarget>) -> Constraint {
+        self.constraint(Relation::AtMost, target)
+    }
+}
+
+impl From<Anchor> for ConstraintTarget {
+    fn from(anchor: Anchor) -> ConstraintTarget {
+        ConstraintTarget::Anchor(anchor)
+    }
+}
+
+impl From<f64> for ConstraintTarget {
+    fn from(value: f64) -> ConstraintTarget {
+        ConstraintTarget::Value(value)
+    }
+}
+
+impl Constraint {
+    /// Builder-style method to multiply the target, such as to make one
+    /// child half as wide as another.
+    pub fn times(mut self, ratio: f64) -> Self {
+        self.ratio = ratio;
+        self
+    }
+
+    /// Builder-style method to add `offset` to the target, such as for the
+    /// space between two children.
+    pub fn plus(mut self, offset: f64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Builder-style method to set how much this constraint matters; it is
+    /// [`Priority::Required`] by default.
+    ///
+    /// [`Priority::Required`]: enum.Priority.html#variant.Required
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+impl Priority {
+    fn strength(self) -> f64 {
+        match self {
+            Priority::Required => REQUIRED,
+            Priority::Strong => STRONG,
+            Priority::Medium => MEDIUM,
+            Priority::Weak => WEAK,
+        }
+    }
+}
+
+fn term(variable: Variable, coefficient: f64) -> Term {
+    Term {
+        variable,
+        coefficient,
+    }
+}
+
+/// Add `constraint` to `solver`, with `expression` on the left of `op`
+/// and zero on the right.
+fn add(solver: &mut Solver, expression: Expression, op: RelationalOperator, strength: f64) {
+    let constraint = cassowary::Constraint::new(expression, op, strength);
+    if let Err(e) = solver.add_constraint(constraint) {
+        log::warn!("ConstraintLayout ignored a constraint: {:?}", e);
+    }
+}
+
+impl<T> ConstraintLayout<T> {
+    /// Create a layout without any children.
+    pub fn new() -> Self {
+        ConstraintLayout {
+            children: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Add a child, returning the item to constrain it with.
+    pub fn add_child(&mut self, child: impl Widget<T> + 'static) -> LayoutItem {
+        self.children.push(WidgetPod::new(child).boxed());
+        LayoutItem(Some(self.children.len() - 1))
+    }
+
+    /// Add a constraint.
+    pub fn constrain(&mut self, constraint: Constraint) {
+        self.constraints.push(constraint);
+    }
+
+    /// Builder-style variant of [`constrain`].
+    ///
+    /// [`constrain`]: #method.constrain
+    pub fn with_constraint(mut self, constraint: Constraint) -> Self {
+        self.constrain(constraint);
+        self
+    }
+}
+
+impl<T> Default for ConstraintLayout<T> {
+    fn default() -> Self {
+        ConstraintLayout::new()
+    }
+}
+
+/// The expression for `anchor`, given the frames of the children and the
+/// parent's size.
+fn anchor_expression(anchor: Anchor, frames: &[Frame], parent: Frame) -> Expression {
+    let frame = match anchor.item.0 {
+        Some(index) => frames[index],
+        None => parent,
+    };
+    let is_parent = anchor.item.0.is_none();
+    let mut terms = Vec::new();
+    // the parent's left and top edges are at zero
+    match anchor.edge {
+        Edge::Left | Edge::Right | Edge::CenterX if !is_parent => terms.push(term(frame.left, 1.0)),
+        Edge::Top | Edge::Bottom | Edge::CenterY if !is_parent => terms.push(term(frame.top, 1.0)),
+        _ => (),
+    }
+    match anchor.edge {
+        Edge::Right | Edge::Width => terms.push(term(frame.width, 1.0)),
+        Edge::CenterX => terms.push(term(frame.width, 0.5)),
+        Edge::Bottom | Edge::Height => terms.push(term(frame.height, 1.0)),
+        Edge::CenterY => terms.push(term(frame.height, 0.5)),
+        Edge::Left | Edge::Top => (),
+    }
+    Expression::new(terms, 0.0)
+}
+
+/// Solve for the frame of each child and the size of the parent, given the
+/// size each child wants to be and the constraints from the parent.
+fn solve(constraints: &[Constraint], preferred: &[Size], bc: &BoxConstraints) -> (Vec<Rect>, Size) {
+    let mut solver = Solver::new();
+    let parent = Frame::new();
+    let frames: Vec<Frame> = preferred.iter().map(|_| Frame::new()).collect();
+
+    let limits = [
+        (parent.width, bc.min().width, bc.max().width),
+        (parent.height, bc.min().height, bc.max().height),
+    ];
+    for &(variable, min, max) in &limits {
+        let length = Expression::from_term(term(variable, 1.0));
+        let mut at_least = length.clone();
+        at_least.constant = -min;
+        add(
+            &mut solver,
+            at_least,
+            RelationalOperator::GreaterOrEqual,
+            REQUIRED,
+        );
+        let mut fill = length;
+        if max.is_finite() {
+            let mut at_most = fill.clone();
+            at_most.constant = -max;
+            add(
+                &mut solver,
+                at_most,
+                RelationalOperator::LessOrEqual,
+                REQUIRED,
+            );
+            fill.constant = -max;
+        }
+        add(&mut solver, fill, RelationalOperator::Equal, FILL);
+    }
+
+    for (frame, size) in frames.iter().zip(preferred) {
+        for &(variable, length) in &[(frame.width, size.width), (frame.height, size.height)] {
+            let expression = Expression::from_term(term(variable, 1.0));
+            add(
+                &mut solver,
+                expression.clone(),
+                RelationalOperator::GreaterOrEqual,
+                REQUIRED,
+            );
+            let mut wanted = expression;
+            wanted.constant = -length;
+            add(
+                &mut solver,
+                wanted,
+                RelationalOperator::Equal,
+                PREFERRED_SIZE,
+            );
+        }
+        // children stay inside the layout
+        for &(start, length, outer) in &[
+            (frame.left, frame.width, parent.width),
+            (frame.top, frame.height, parent.height),
+        ] {
+            let near = Expression::from_term(term(start, 1.0));
+            add(
+                &mut solver,
+                near,
+                RelationalOperator::GreaterOrEqual,
+                REQUIRED,
+            );
+            let far = Expression::new(
+                vec![term(start, 1.0), term(length, 1.0), term(outer, -1.0)],
+                0.0,
+            );
+            add(&mut solver, far, RelationalOperator::LessOrEqual, STRONG);
+        }
+    }
+
+    for constraint in constraints {
+        let target_item = match constraint.target {
+            ConstraintTarget::Anchor(anchor) => anchor.item,
+            ConstraintTarget::Value(_) => LayoutItem::PARENT,
+        };
+        let missing = [constraint.anchor.item, target_item]
+            .iter()
+            .any(|item| matches!(item.0, Some(index) if index >= frames.len()));
+        if missing {
+            log::warn!("ConstraintLayout has a constraint on a child it doesn't have");
+            continue;
+        }
+        let mut expression = anchor_expression(constraint.anchor, &frames, parent);
+        let target = match constraint.target {
+            ConstraintTarget::Anchor(anchor) => anchor_expression(anchor, &frames, parent),
+            ConstraintTarget::Value(value) => Expression::from_constant(value),
+        };
+        for target_term in &target.terms {
+            expression.terms.push(term(
+                target_term.variable,
+                -target_term.coefficient * constraint.ratio,
+            ));
+        }
+        expression.constant -= target.constant * constraint.ratio + constraint.offset;
+        let op = match constraint.relation {
+            Relation::Equal => RelationalOperator::Equal,
+            Relation::AtLeast => RelationalOperator::GreaterOrEqual,
+            Relation::AtMost => RelationalOperator::LessOrEqual,
+        };
+        add(&mut solver, expression, op, constraint.priority.strength());
+    }
+
+    let rects = frames
+        .iter()
+        .map(|frame| {
+            let origin = Point::new(solver.get_value(frame.left), solver.get_value(frame.top));
+            let width = solver.get_value(frame.width).max(0.0);
+            let height = solver.get_value(frame.height).max(0.0);
+            Rect::from_origin_size(origin, Size::new(width, height))
+        })
+        .collect();
+    let size = Size::new(
+        solver.get_value(parent.width),
+        solver.get_value(parent.height),
+    );
+    (rects, size)
+}
+
+impl<T: Data> Widget<T> for ConstraintLayout<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        for child in &mut self.children {
+            child.event(ctx, event, data, env);
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        for child in &mut self.children {
+            child.lifecycle(ctx, event, data, env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        for child in &mut self.children {
+            child.update(ctx, data, env);
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("ConstraintLayout");
+
+        let loose = bc.loosen();
+        let preferred: Vec<Size> = self
+            .children
+            .iter_mut()
+            .map(|child| child.layout(layout_ctx, &loose, data, env))
+            .collect();
+        let (rects, size) = solve(&self.constraints, &preferred, bc);
+
+        let mut paint_rect = Rect::ZERO;
+        for (child, rect) in self.children.iter_mut().zip(rects) {
+            let child_bc = BoxConstraints::tight(rect.size());
+            let child_size = child.layout(layout_ctx, &child_bc, data, env);
+            child.set_layout_rect(Rect::from_origin_size(rect.origin(), child_size));
+            paint_rect = paint_rect.union(child.paint_rect());
+        }
+
+        let size = bc.constrain(size);
+        let insets = paint_rect - Rect::ZERO.with_size(size);
+        layout_ctx.set_paint_insets(insets);
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        for child in &mut self.children {
+            child.paint_with_offset(paint_ctx, data, env);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn children_follow_their_constraints() {
+        let label = LayoutItem(Some(0));
+        let field = LayoutItem(Some(1));
+        let parent = LayoutItem::PARENT;
+        let constraints = vec![
+            label.left().equal_to(parent.left()).plus(12.0),
+            field.left().equal_to(label.right()).plus(8.0),
+            field.right().equal_to(parent.right()).plus(-12.0),
+            label.center_y().equal_to(field.center_y()),
+            field.top().equal_to(20.0),
+        ];
+        let preferred = [Size::new(40.0, 20.0), Size::new(100.0, 30.0)];
+        let bc = BoxConstraints::new(Size::ZERO, Size::new(300.0, 200.0));
+        let (rects, size) = solve(&constraints, &preferred, &bc);
+
+        assert!(approx(size.width, 300.0) && approx(size.height, 200.0));
+        // the label keeps its width, and the field stretches to the edge
+        assert!(approx(rects[0].x0, 12.0) && approx(rects[0].width(), 40.0));
+        assert!(approx(rects[1].x0, 60.0) && approx(rects[1].x1, 288.0));
+        // and they are lined up on their middles
+        assert!(approx(rects[1].y0, 20.0) && approx(rects[0].y0, 25.0));
+    }
+
+    #[test]
+    fn weaker_constraints_give_way() {
+        let button = LayoutItem(Some(0));
+        let constraints = vec![
+            button.width().equal_to(200.0).priority(Priority::Weak),
+            button
+                .width()
+                .at_most(LayoutItem::PARENT.width())
+                .times(0.5),
+        ];
+        let bc = BoxConstraints::tight(Size::new(300.0, 100.0));
+        let (rects, _) = solve(&constraints, &[Size::new(80.0, 20.0)], &bc);
+        assert!(approx(rects[0].width(), 150.0));
+    }
+}
@@ -15,6 +15,7 @@
 //! A widget that provides simple visual styling options to a child.
 
 use crate::shell::kurbo::{Point, Rect, RoundedRect, Size};
+use crate::widget::Axis;
 use crate::{
     BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintBrush,
     PaintCtx, RenderContext, UpdateCtx, Widget, WidgetPod,
@@ -111,9 +112,42 @@ impl<T: Data> Widget<T> for Container<T> {
 
         let my_insets = self.inner.compute_parent_paint_insets(my_size);
         ctx.set_paint_insets(my_insets);
+        ctx.set_baseline_offset(self.inner.baseline_offset() + border_width);
         my_size
     }
 
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let border_width = match self.border {
+            Some(ref border) => border.width,
+            None => 0.0,
+        };
+        let cross = (cross - 2.0 * border_width).max(0.0);
+        2.0 * border_width + self.inner.min_intrinsic(ctx, axis, cross, data, env)
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let border_width = match self.border {
+            Some(ref border) => border.width,
+            None => 0.0,
+        };
+        let cross = (cross - 2.0 * border_width).max(0.0);
+        2.0 * border_width + self.inner.max_intrinsic(ctx, axis, cross, data, env)
+    }
+
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
         let panel = RoundedRect::from_origin_size(
             Point::ORIGIN,
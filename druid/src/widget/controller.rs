@@ -0,0 +1,172 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A trait for adding behaviour to a widget, and the widget that hosts it.
+
+use crate::kurbo::Size;
+use crate::widget::Axis;
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    UpdateCtx, Widget, WidgetId,
+};
+
+/// A type that customizes the behaviour of a widget, without changing how
+/// it looks.
+///
+/// A controller sees the events, lifecycle events and updates sent to its
+/// child, and decides what to do with them: it can act on them, change
+/// them, or not pass them on at all. Each method passes everything on by
+/// default, so you only need to write the ones you care about.
+///
+/// A controller is attached to a widget with [`WidgetExt::controller`].
+///
+/// # Examples
+///
+/// A controller that clears a text box when Escape is pressed:
+///
+/// ```
+/// use druid::widget::{Controller, TextBox, WidgetExt};
+/// use druid::{Env, Event, EventCtx, KeyCode, Widget};
+///
+/// struct ClearOnEscape;
+///
+/// impl<W: Widget<String>> Controller<String, W> for ClearOnEscape {
+///     fn event(
+///         &mut self,
+///         child: &mut W,
+///         ctx: &mut EventCtx,
+///         event: &Event,
+///         data: &mut String,
+///         env: &Env,
+///     ) {
+///         match event {
+///             Event::KeyDown(key) if key.key_code == KeyCode::Escape => data.clear(),
+///             _ => child.event(ctx, event, data, env),
+///         }
+///     }
+/// }
+///
+/// let text_box = TextBox::new().controller(ClearOnEscape);
+/// ```
+///
+/// [`WidgetExt::controller`]: trait.WidgetExt.html#method.controller
+pub trait Controller<T, W: Widget<T>> {
+    /// Analogous to [`Widget::event`].
+    ///
+    /// [`Widget::event`]: trait.Widget.html#tymethod.event
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        child.event(ctx, event, data, env)
+    }
+
+    /// Analogous to [`Widget::lifecycle`].
+    ///
+    /// [`Widget::lifecycle`]: trait.Widget.html#tymethod.lifecycle
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &T,
+        env: &Env,
+    ) {
+        child.lifecycle(ctx, event, data, env)
+    }
+
+    /// Analogous to [`Widget::update`].
+    ///
+    /// [`Widget::update`]: trait.Widget.html#tymethod.update
+    fn update(&mut self, child: &mut W, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        child.update(ctx, old_data, data, env)
+    }
+}
+
+/// A widget that passes everything through a [`Controller`] on its way to
+/// the child.
+///
+/// This is usually created with [`WidgetExt::controller`].
+///
+/// [`Controller`]: trait.Controller.html
+/// [`WidgetExt::controller`]: trait.WidgetExt.html#method.controller
+pub struct ControllerHost<W, C> {
+    widget: W,
+    controller: C,
+}
+
+impl<W, C> ControllerHost<W, C> {
+    /// Wrap `widget`, to be controlled by `controller`.
+    pub fn new(widget: W, controller: C) -> ControllerHost<W, C> {
+        ControllerHost { widget, controller }
+    }
+
+    /// The widget being controlled.
+    pub fn widget(&self) -> &W {
+        &self.widget
+    }
+
+    /// The widget being controlled, mutably.
+    pub fn widget_mut(&mut self) -> &mut W {
+        &mut self.widget
+    }
+}
+
+impl<T: Data, W: Widget<T>, C: Controller<T, W>> Widget<T> for ControllerHost<W, C> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.controller
+            .event(&mut self.widget, ctx, event, data, env)
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.controller
+            .lifecycle(&mut self.widget, ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        self.controller
+            .update(&mut self.widget, ctx, old_data, data, env)
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        self.widget.layout(ctx, bc, data, env)
+    }
+
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        self.widget.min_intrinsic(ctx, axis, cross, data, env)
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        self.widget.max_intrinsic(ctx, axis, cross, data, env)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.widget.paint(ctx, data, env)
+    }
+
+    fn id(&self) -> Option<WidgetId> {
+        self.widget.id()
+    }
+}
@@ -0,0 +1,423 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A calendar for picking a date.
+
+use crate::format::Date;
+use crate::kurbo::{BezPath, Circle, Point, Rect, RoundedRect, Size};
+use crate::piet::{Color, FontBuilder, PietFont, Text, TextLayout, TextLayoutBuilder};
+use crate::theme;
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, RenderContext, UpdateCtx, Widget,
+};
+
+/// The rows of the calendar: the month and arrows, the names of the days,
+/// and the weeks.
+const ROWS: usize = 8;
+const WEEKS: usize = 6;
+
+fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The day of the week, from 0 for Monday to 6 for Sunday.
+fn weekday(date: Date) -> u32 {
+    // 1970-01-01 was a Thursday
+    (days_since_epoch(date) + 3).rem_euclid(7) as u32
+}
+
+/// The date `days` days later, or earlier if it is negative.
+fn add_days(date: Date, days: i64) -> Date {
+    from_days_since_epoch(days_since_epoch(date) + days)
+}
+
+/// The same day `months` months later, or earlier if it is negative, or
+/// the last day of that month if it is shorter.
+fn add_months(date: Date, months: i32) -> Date {
+    let index = date.year * 12 + date.month as i32 - 1 + months;
+    let year = index.div_euclid(12);
+    let month = index.rem_euclid(12) as u32 + 1;
+    let day = date.day.min(days_in_month(year, month));
+    Date::new(year, month, day)
+}
+
+fn first_of_month(date: Date) -> Date {
+    Date { day: 1, ..date }
+}
+
+/// The number of days since 1970-01-01.
+fn days_since_epoch(date: Date) -> i64 {
+    // from Howard Hinnant's `days_from_civil`
+    let year = i64::from(date.year) - if date.month <= 2 { 1 } else { 0 };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (i64::from(date.month) + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + i64::from(date.day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn from_days_since_epoch(days: i64) -> Date {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    Date::new(year as i32, month as u32, day as u32)
+}
+
+/// A calendar of a month, for picking a [`Date`].
+///
+/// Clicking a day picks it, and the arrows at the top go to the months
+/// before and after. With focus, the arrow keys move the date by a day or a
+/// week, Page Up and Page Down by a month, and Home and End go to the start
+/// and end of the month.
+///
+/// The names of the months and days, and the day weeks start on, come from
+/// the current locale.
+///
+/// [`Date`]: ../format/struct.Date.html
+#[derive(Debug, Clone, Default)]
+pub struct DatePicker {
+    /// The month that is shown; any day of it.
+    shown: Option<Date>,
+}
+
+impl DatePicker {
+    /// Create a date picker.
+    pub fn new() -> DatePicker {
+        DatePicker::default()
+    }
+
+    /// The first day in the grid, which may be in the month before.
+    fn grid_start(shown: Date, week_start: u32) -> Date {
+        let first = first_of_month(shown);
+        let offset = (weekday(first) + 7 - week_start) % 7;
+        add_days(first, -i64::from(offset))
+    }
+
+    fn cell_size(env: &Env) -> f64 {
+        env.get(theme::BORDERED_WIDGET_HEIGHT)
+    }
+
+    /// Where a click at `pos` goes.
+    fn hit_test(shown: Date, week_start: u32, cell: f64, pos: Point) -> Option<Hit> {
+        if pos.x < 0.0 || pos.y < 0.0 {
+            return None;
+        }
+        let (column, row) = ((pos.x / cell) as usize, (pos.y / cell) as usize);
+        if column >= 7 || row >= ROWS {
+            return None;
+        }
+        match row {
+            0 if column == 0 => Some(Hit::Month(-1)),
+            0 if column == 6 => Some(Hit::Month(1)),
+            0 | 1 => None,
+            _ => {
+                let index = (row - 2) * 7 + column;
+                let date = add_days(Self::grid_start(shown, week_start), index as i64);
+                Some(Hit::Day(date))
+            }
+        }
+    }
+}
+
+/// Part of the calendar.
+#[derive(Debug, PartialEq)]
+enum Hit {
+    /// An arrow, going back or forward by this many months.
+    Month(i32),
+    Day(Date),
+}
+
+/// The date a key press moves `date` to, if the key moves it.
+fn navigate(date: Date, key: KeyCode) -> Option<Date> {
+    let days_in_month = i64::from(days_in_month(date.year, date.month));
+    match key {
+        KeyCode::ArrowLeft => Some(add_days(date, -1)),
+        KeyCode::ArrowRight => Some(add_days(date, 1)),
+        KeyCode::ArrowUp => Some(add_days(date, -7)),
+        KeyCode::ArrowDown => Some(add_days(date, 7)),
+        KeyCode::PageUp => Some(add_months(date, -1)),
+        KeyCode::PageDown => Some(add_months(date, 1)),
+        KeyCode::Home => Some(first_of_month(date)),
+        KeyCode::End => Some(add_days(first_of_month(date), days_in_month - 1)),
+        _ => None,
+    }
+}
+
+/// The names the calendar shows, in the current locale.
+struct Names {
+    months: Vec<String>,
+    weekdays: Vec<String>,
+    /// The day weeks start on, from 0 for Monday.
+    week_start: u32,
+}
+
+impl Names {
+    fn new(env: &Env) -> Names {
+        let l10n = env.localization_manager();
+        // like a `LocalizedString`, a missing string shows its key
+        let localize = |key: String| l10n.localize(&key, None).unwrap_or(key);
+        let months = (1..=12)
+            .map(|i| localize(format!("date-picker-month-{}", i)))
+            .collect();
+        let weekdays = (1..=7)
+            .map(|i| localize(format!("date-picker-weekday-{}", i)))
+            .collect();
+        // 1 for Monday to 7 for Sunday, like ISO 8601
+        let week_start = localize("date-picker-week-start".into())
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|day| (1..=7).contains(day))
+            .unwrap_or(1)
+            - 1;
+        Names {
+            months,
+            weekdays,
+            week_start,
+        }
+    }
+}
+
+impl Widget<Date> for DatePicker {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut Date, env: &Env) {
+        let shown = self.shown.unwrap_or(*data);
+        match event {
+            Event::MouseDown(mouse) => {
+                ctx.set_active(true);
+                ctx.request_focus();
+                let week_start = Names::new(env).week_start;
+                let cell = Self::cell_size(env);
+                match Self::hit_test(shown, week_start, cell, mouse.pos) {
+                    Some(Hit::Month(delta)) => {
+                        self.shown = Some(add_months(shown, delta));
+                        ctx.request_paint();
+                    }
+                    Some(Hit::Day(date)) => *data = date,
+                    None => (),
+                }
+            }
+            Event::MouseUp(_) => ctx.set_active(false),
+            Event::KeyDown(key) => {
+                if let Some(date) = navigate(*data, key.key_code) {
+                    *data = date;
+                    ctx.set_handled();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &Date, _env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => {
+                self.shown = Some(*data);
+                ctx.register_for_focus();
+            }
+            LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &Date, data: &Date, _env: &Env) {
+        if !old_data.same(data) {
+            // show the month of the new date
+            self.shown = Some(*data);
+            ctx.request_paint();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &Date,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("DatePicker");
+        let cell = Self::cell_size(env);
+        bc.constrain(Size::new(cell * 7.0, cell * ROWS as f64))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &Date, env: &Env) {
+        let shown = self.shown.unwrap_or(*data);
+        let names = Names::new(env);
+        let cell = Self::cell_size(env);
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let font = paint_ctx
+            .text()
+            .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+            .build()
+            .unwrap();
+        let label_color = env.get(theme::LABEL_COLOR);
+        let dim_color = env.get(theme::PLACEHOLDER_COLOR);
+
+        let bounds = RoundedRect::from_origin_size(Point::ORIGIN, paint_ctx.size().to_vec2(), 4.);
+        paint_ctx.fill(bounds, &env.get(theme::BACKGROUND_DARK));
+        paint_ctx.stroke(bounds, &env.get(theme::BORDER_DARK), 1.0);
+        paint_ctx.stroke_focus_ring(bounds, env);
+
+        let center_of = |column: usize, row: usize| {
+            Point::new((column as f64 + 0.5) * cell, (row as f64 + 0.5) * cell)
+        };
+
+        // the month, and the arrows on either side
+        let title = format!("{} {}", names.months[shown.month as usize - 1], shown.year);
+        draw_centered(
+            paint_ctx,
+            &font,
+            font_size,
+            &title,
+            center_of(3, 0),
+            &label_color,
+        );
+        let mut arrows = BezPath::new();
+        let (left, right) = (center_of(0, 0), center_of(6, 0));
+        let arm = cell / 6.0;
+        arrows.move_to((left.x + arm, left.y - 2.0 * arm));
+        arrows.line_to((left.x - arm, left.y));
+        arrows.line_to((left.x + arm, left.y + 2.0 * arm));
+        arrows.move_to((right.x - arm, right.y - 2.0 * arm));
+        arrows.line_to((right.x + arm, right.y));
+        arrows.line_to((right.x - arm, right.y + 2.0 * arm));
+        paint_ctx.stroke(arrows, &label_color, 1.5);
+
+        for column in 0..7 {
+            let weekday = (names.week_start as usize + column) % 7;
+            let name = &names.weekdays[weekday];
+            draw_centered(
+                paint_ctx,
+                &font,
+                font_size,
+                name,
+                center_of(column, 1),
+                &dim_color,
+            );
+        }
+
+        let start = Self::grid_start(shown, names.week_start);
+        for index in 0..WEEKS * 7 {
+            let date = add_days(start, index as i64);
+            let center = center_of(index % 7, index / 7 + 2);
+            let color = if date == *data {
+                let circle = Circle::new(center, cell / 2.0 - 1.0);
+                paint_ctx.fill(circle, &env.get(theme::PRIMARY_DARK));
+                &label_color
+            } else if date.month == shown.month {
+                &label_color
+            } else {
+                &dim_color
+            };
+            let day = date.day.to_string();
+            draw_centered(paint_ctx, &font, font_size, &day, center, color);
+        }
+
+        // the line between the weekdays and the weeks
+        let line = Rect::new(0.0, 2.0 * cell - 0.5, 7.0 * cell, 2.0 * cell + 0.5);
+        paint_ctx.fill(line, &env.get(theme::BORDER_DARK));
+    }
+}
+
+/// Draw `text` with its middle at `center`.
+fn draw_centered(
+    paint_ctx: &mut PaintCtx,
+    font: &PietFont,
+    font_size: f64,
+    text: &str,
+    center: Point,
+    color: &Color,
+) {
+    let layout = paint_ctx
+        .text()
+        .new_text_layout(font, text)
+        .build()
+        .unwrap();
+    // the origin is on the baseline
+    let origin = Point::new(center.x - layout.width() / 2.0, center.y + font_size * 0.35);
+    paint_ctx.draw_text(&layout, origin, color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date::new(year, month, day)
+    }
+
+    #[test]
+    fn date_arithmetic() {
+        assert_eq!(days_since_epoch(date(1970, 1, 1)), 0);
+        assert_eq!(weekday(date(2020, 3, 14)), 5);
+        assert_eq!(weekday(date(1969, 12, 31)), 2);
+
+        assert_eq!(add_days(date(2020, 2, 28), 1), date(2020, 2, 29));
+        assert_eq!(add_days(date(2020, 2, 29), 1), date(2020, 3, 1));
+        assert_eq!(add_days(date(2000, 1, 1), -1), date(1999, 12, 31));
+        assert_eq!(add_months(date(2020, 1, 31), 1), date(2020, 2, 29));
+        assert_eq!(add_months(date(2020, 1, 15), -13), date(2018, 12, 15));
+    }
+
+    #[test]
+    fn the_grid_starts_on_the_locale_week_start() {
+        // March 2020 starts on a Sunday
+        let march = date(2020, 3, 14);
+        assert_eq!(DatePicker::grid_start(march, 0), date(2020, 2, 24));
+        assert_eq!(DatePicker::grid_start(march, 6), date(2020, 3, 1));
+
+        let cell = 10.0;
+        let hit = |x, y| DatePicker::hit_test(march, 0, cell, Point::new(x, y));
+        assert_eq!(hit(5.0, 5.0), Some(Hit::Month(-1)));
+        assert_eq!(hit(65.0, 5.0), Some(Hit::Month(1)));
+        assert_eq!(hit(35.0, 15.0), None);
+        assert_eq!(hit(5.0, 25.0), Some(Hit::Day(date(2020, 2, 24))));
+        assert_eq!(hit(65.0, 35.0), Some(Hit::Day(date(2020, 3, 8))));
+        assert_eq!(hit(75.0, 35.0), None);
+    }
+
+    #[test]
+    fn keyboard_navigation() {
+        let day = date(2020, 3, 31);
+        assert_eq!(navigate(day, KeyCode::ArrowRight), Some(date(2020, 4, 1)));
+        assert_eq!(navigate(day, KeyCode::ArrowUp), Some(date(2020, 3, 24)));
+        assert_eq!(navigate(day, KeyCode::PageDown), Some(date(2020, 4, 30)));
+        assert_eq!(navigate(day, KeyCode::Home), Some(date(2020, 3, 1)));
+        assert_eq!(
+            navigate(date(2020, 2, 3), KeyCode::End),
+            Some(date(2020, 2, 29))
+        );
+        assert_eq!(navigate(day, KeyCode::Tab), None);
+    }
+}
@@ -21,6 +21,24 @@ use crate::{
 };
 
 /// A widget that switches between two possible child views.
+///
+/// Which one is shown is decided by a predicate on the data. For choosing
+/// between more than two views, or views that should be rebuilt when they
+/// are shown, see [`ViewSwitcher`].
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Either, Label};
+///
+/// let greeting = Either::new(
+///     |name: &String, _env| name.is_empty(),
+///     Label::new("Who are you?"),
+///     Label::new(|name: &String, _env: &_| format!("Hello, {}!", name)),
+/// );
+/// ```
+///
+/// [`ViewSwitcher`]: struct.ViewSwitcher.html
 pub struct Either<T> {
     closure: Box<dyn Fn(&T, &Env) -> bool>,
     true_branch: WidgetPod<T, Box<dyn Widget<T>>>,
@@ -68,8 +86,8 @@ impl<T: Data> Widget<T> for Either<T> {
         let current = (self.closure)(data, env);
         if current != self.current {
             self.current = current;
+            ctx.request_layout();
             ctx.request_paint();
-            // TODO: more event flow to request here.
         }
         if self.current {
             self.true_branch.update(ctx, data, env);
@@ -95,7 +113,7 @@ impl<T: Data> Widget<T> for Either<T> {
             let size = self.false_branch.layout(layout_ctx, bc, data, env);
             self.false_branch
                 .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
-            layout_ctx.set_paint_insets(self.true_branch.paint_insets());
+            layout_ctx.set_paint_insets(self.false_branch.paint_insets());
             size
         }
     }
@@ -15,6 +15,7 @@
 //! A widget that accepts a closure to update the environment for its child.
 
 use crate::kurbo::Size;
+use crate::widget::Axis;
 use crate::{
     BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
     UpdateCtx, Widget, WidgetId,
@@ -67,7 +68,8 @@ impl<T: Data, W: Widget<T>> Widget<T> for EnvScope<T, W> {
     fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
         let mut new_env = env.clone();
         (self.f)(&mut new_env, &data);
-        self.child.lifecycle(ctx, event, data, env)
+
+        self.child.lifecycle(ctx, event, data, &new_env)
     }
 
     fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
@@ -92,6 +94,32 @@ impl<T: Data, W: Widget<T>> Widget<T> for EnvScope<T, W> {
         self.child.layout(layout_ctx, &bc, data, &new_env)
     }
 
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let mut new_env = env.clone();
+        (self.f)(&mut new_env, data);
+        self.child.min_intrinsic(ctx, axis, cross, data, &new_env)
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let mut new_env = env.clone();
+        (self.f)(&mut new_env, data);
+        self.child.max_intrinsic(ctx, axis, cross, data, &new_env)
+    }
+
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
         let mut new_env = env.clone();
         (self.f)(&mut new_env, &data);
@@ -24,25 +24,159 @@ use crate::{
 /// A container with either horizontal or vertical layout.
 pub struct Flex<T> {
     direction: Axis,
+    cross_alignment: CrossAxisAlignment,
     children: Vec<ChildWidget<T>>,
 }
 
+/// How the children of a [`Flex`] are placed across its axis: vertically
+/// in a row, horizontally in a column.
+///
+/// [`Flex`]: struct.Flex.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CrossAxisAlignment {
+    /// At the top of a row, or the leading edge of a column.
+    Start,
+    /// In the middle.
+    Center,
+    /// At the bottom of a row, or the trailing edge of a column.
+    End,
+    /// With the baselines of their text lined up, such as a [`Label`] beside
+    /// a [`TextBox`]. Children without text sit on the baseline. In a column
+    /// this is the same as `Start`.
+    ///
+    /// [`Label`]: struct.Label.html
+    /// [`TextBox`]: struct.TextBox.html
+    Baseline,
+}
+
 struct ChildWidget<T> {
     widget: WidgetPod<T, Box<dyn Widget<T>>>,
-    params: Params,
+    params: FlexParams,
 }
 
-pub(crate) enum Axis {
+/// A direction in which widgets are laid out.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Axis {
+    /// Left to right.
     Horizontal,
+    /// Top to bottom.
     Vertical,
 }
 
-#[derive(Copy, Clone, Default)]
-struct Params {
-    flex: f64,
+/// How a child of a [`Flex`] is sized along its main axis.
+///
+/// Each child starts at its basis, which is either a fixed length or the
+/// size the child wants to be. If there is space left over, it is shared
+/// out among the children in proportion to how much they grow; if there
+/// isn't enough, they shrink to fit, in proportion to how much they shrink
+/// and how big they are, rather than overflowing.
+///
+/// A plain `f64` is a flex factor, as taken by [`Flex::with_child`]: zero
+/// is a child that keeps its size, and anything else grows from nothing.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Flex, FlexParams, Label, TextBox};
+///
+/// let form = Flex::row()
+///     .with_child(Label::new("Name"), FlexParams::new().shrink(1.0))
+///     .with_child(TextBox::new(), FlexParams::new().basis(200.0).grow(1.0).shrink(1.0));
+/// # let _: Flex<String> = form;
+/// ```
+///
+/// [`Flex`]: struct.Flex.html
+/// [`Flex::with_child`]: struct.Flex.html#method.with_child
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct FlexParams {
+    grow: f64,
+    shrink: f64,
+    basis: Option<f64>,
+}
+
+impl FlexParams {
+    /// Parameters for a child that keeps the size it wants to be.
+    pub fn new() -> FlexParams {
+        FlexParams::default()
+    }
+
+    /// Builder-style method to set how much of the space left over the
+    /// child gets, relative to the other children.
+    pub fn grow(mut self, grow: f64) -> Self {
+        self.grow = grow;
+        self
+    }
+
+    /// Builder-style method to set how much the child gives up when there
+    /// isn't enough space, relative to the other children. This is weighted
+    /// by the child's basis, so bigger children shrink more.
+    pub fn shrink(mut self, shrink: f64) -> Self {
+        self.shrink = shrink;
+        self
+    }
+
+    /// Builder-style method to set the size the child starts from along
+    /// the main axis, instead of the size it wants to be.
+    pub fn basis(mut self, basis: f64) -> Self {
+        self.basis = Some(basis);
+        self
+    }
+}
+
+impl From<f64> for FlexParams {
+    fn from(flex: f64) -> FlexParams {
+        if flex == 0.0 {
+            FlexParams::new()
+        } else {
+            FlexParams::new().grow(flex).basis(0.0)
+        }
+    }
+}
+
+/// The main axis length of each child, given their parameters and bases,
+/// and the `total` length available.
+fn resolve_lengths(total: f64, children: &[(FlexParams, f64)]) -> Vec<f64> {
+    let used: f64 = children.iter().map(|(_, basis)| basis).sum();
+    let free = total - used;
+    if free >= 0.0 {
+        let grow_sum: f64 = children.iter().map(|(params, _)| params.grow).sum();
+        children
+            .iter()
+            .map(|(params, basis)| {
+                if grow_sum > 0.0 {
+                    basis + free * params.grow / grow_sum
+                } else {
+                    *basis
+                }
+            })
+            .collect()
+    } else {
+        let shrink_sum: f64 = children
+            .iter()
+            .map(|(params, basis)| params.shrink * basis)
+            .sum();
+        children
+            .iter()
+            .map(|(params, basis)| {
+                if shrink_sum > 0.0 {
+                    (basis + free * params.shrink * basis / shrink_sum).max(0.0)
+                } else {
+                    *basis
+                }
+            })
+            .collect()
+    }
 }
 
 impl Axis {
+    /// The other axis.
+    pub fn cross(self) -> Axis {
+        match self {
+            Axis::Horizontal => Axis::Vertical,
+            Axis::Vertical => Axis::Horizontal,
+        }
+    }
+
     fn major(&self, coords: Size) -> f64 {
         match *self {
             Axis::Horizontal => coords.width,
@@ -72,6 +206,7 @@ impl<T> Flex<T> {
     pub fn row() -> Self {
         Flex {
             direction: Axis::Horizontal,
+            cross_alignment: CrossAxisAlignment::Start,
             children: Vec::new(),
         }
     }
@@ -82,31 +217,48 @@ impl<T> Flex<T> {
     pub fn column() -> Self {
         Flex {
             direction: Axis::Vertical,
+            cross_alignment: CrossAxisAlignment::Start,
             children: Vec::new(),
         }
     }
 
+    /// Builder-style method to set how the children are placed across the
+    /// axis; they are at the start by default.
+    pub fn cross_axis_alignment(mut self, alignment: CrossAxisAlignment) -> Self {
+        self.cross_alignment = alignment;
+        self
+    }
+
     /// Builder-style variant of `add_child`
     ///
     /// Convenient for assembling a group of widgets in a single expression.
-    pub fn with_child(mut self, child: impl Widget<T> + 'static, flex: f64) -> Self {
-        self.add_child(child, flex);
+    pub fn with_child(
+        mut self,
+        child: impl Widget<T> + 'static,
+        params: impl Into<FlexParams>,
+    ) -> Self {
+        self.add_child(child, params);
         self
     }
 
     /// Add a child widget.
     ///
-    /// If `flex` is zero, then the child is non-flex. It is given the same
-    /// constraints on the "minor axis" as its parent, but unconstrained on the
-    /// "major axis".
+    /// If `params` is a flex of zero, then the child is non-flex. It is given
+    /// the same constraints on the "minor axis" as its parent, but
+    /// unconstrained on the "major axis".
     ///
-    /// If `flex` is non-zero, then all the space left over after layout of
+    /// If it is non-zero, then all the space left over after layout of
     /// the non-flex children is divided up, in proportion to the `flex` value,
     /// among the flex children.
     ///
+    /// For children that shrink when space is short, or start from a given
+    /// size, see [`FlexParams`].
+    ///
     /// See also `with_child`.
-    pub fn add_child(&mut self, child: impl Widget<T> + 'static, flex: f64) {
-        let params = Params { flex };
+    ///
+    /// [`FlexParams`]: struct.FlexParams.html
+    pub fn add_child(&mut self, child: impl Widget<T> + 'static, params: impl Into<FlexParams>) {
+        let params = params.into();
         let child = ChildWidget {
             widget: WidgetPod::new(child).boxed(),
             params,
@@ -143,68 +295,96 @@ impl<T: Data> Widget<T> for Flex<T> {
     ) -> Size {
         bc.debug_check("Flex");
 
-        // Measure non-flex children.
-        let mut total_non_flex = 0.0;
+        // Measure the children without a basis.
         let mut minor = self.direction.minor(bc.min());
+        let mut bases = Vec::with_capacity(self.children.len());
         for child in &mut self.children {
-            if child.params.flex == 0.0 {
-                let child_bc = match self.direction {
-                    Axis::Horizontal => BoxConstraints::new(
-                        Size::new(0.0, bc.min().height),
-                        Size::new(std::f64::INFINITY, bc.max().height),
-                    ),
-                    Axis::Vertical => BoxConstraints::new(
-                        Size::new(bc.min().width, 0.0),
-                        Size::new(bc.max().width, std::f64::INFINITY),
-                    ),
-                };
-                let child_size = child.widget.layout(layout_ctx, &child_bc, data, env);
-                minor = minor.max(self.direction.minor(child_size));
-                total_non_flex += self.direction.major(child_size);
-                // Stash size.
-                let rect = Rect::from_origin_size(Point::ORIGIN, child_size);
-                child.widget.set_layout_rect(rect);
-            }
+            let basis = match child.params.basis {
+                Some(basis) => basis,
+                None => {
+                    let child_bc = match self.direction {
+                        Axis::Horizontal => BoxConstraints::new(
+                            Size::new(0.0, bc.min().height),
+                            Size::new(f64::INFINITY, bc.max().height),
+                        ),
+                        Axis::Vertical => BoxConstraints::new(
+                            Size::new(bc.min().width, 0.0),
+                            Size::new(bc.max().width, f64::INFINITY),
+                        ),
+                    };
+                    let child_size = child.widget.layout(layout_ctx, &child_bc, data, env);
+                    minor = minor.max(self.direction.minor(child_size));
+                    // Stash size.
+                    let rect = Rect::from_origin_size(Point::ORIGIN, child_size);
+                    child.widget.set_layout_rect(rect);
+                    self.direction.major(child_size)
+                }
+            };
+            bases.push((child.params, basis));
         }
 
         let total_major = self.direction.major(bc.max());
-        let remaining = (total_major - total_non_flex).max(0.0);
-        let flex_sum: f64 = self.children.iter().map(|child| child.params.flex).sum();
+        let lengths = resolve_lengths(total_major, &bases);
+        let flex_sum: f64 = self.children.iter().map(|child| child.params.grow).sum();
 
-        // Measure flex children.
-        for child in &mut self.children {
-            if child.params.flex != 0.0 {
-                let major = remaining * child.params.flex / flex_sum;
-
-                let min_major = if major.is_infinite() { 0.0 } else { major };
-
-                let child_bc = match self.direction {
-                    Axis::Horizontal => BoxConstraints::new(
-                        Size::new(min_major, bc.min().height),
-                        Size::new(major, bc.max().height),
-                    ),
-                    Axis::Vertical => BoxConstraints::new(
-                        Size::new(bc.min().width, min_major),
-                        Size::new(bc.max().width, major),
-                    ),
-                };
-                let child_size = child.widget.layout(layout_ctx, &child_bc, data, env);
-                minor = minor.max(self.direction.minor(child_size));
-                // Stash size.
-                let rect = Rect::from_origin_size(Point::ORIGIN, child_size);
-                child.widget.set_layout_rect(rect);
+        // Lay out the children that grew, shrank, or have a basis, at their
+        // new length.
+        for (child, (&length, &(params, basis))) in
+            self.children.iter_mut().zip(lengths.iter().zip(&bases))
+        {
+            if params.basis.is_none() && length == basis {
+                continue;
             }
+            let min_major = if length.is_infinite() { 0.0 } else { length };
+
+            let child_bc = match self.direction {
+                Axis::Horizontal => BoxConstraints::new(
+                    Size::new(min_major, bc.min().height),
+                    Size::new(length, bc.max().height),
+                ),
+                Axis::Vertical => BoxConstraints::new(
+                    Size::new(bc.min().width, min_major),
+                    Size::new(bc.max().width, length),
+                ),
+            };
+            let child_size = child.widget.layout(layout_ctx, &child_bc, data, env);
+            minor = minor.max(self.direction.minor(child_size));
+            // Stash size.
+            let rect = Rect::from_origin_size(Point::ORIGIN, child_size);
+            child.widget.set_layout_rect(rect);
         }
 
+        // In a row lined up on the baseline, the tallest text above and the
+        // deepest below it set the height.
+        let baseline = match (&self.direction, self.cross_alignment) {
+            (Axis::Horizontal, CrossAxisAlignment::Baseline) => {
+                let mut above = 0.0f64;
+                let mut below = 0.0f64;
+                for child in &self.children {
+                    let height = child.widget.layout_rect().height();
+                    let offset = child.widget.baseline_offset();
+                    above = above.max(height - offset);
+                    below = below.max(offset);
+                }
+                minor = minor.max(above + below);
+                Some(above)
+            }
+            _ => None,
+        };
+
         // Finalize layout, assigning positions to each child.
         let mut major = 0.0;
-        let mut child_paint_rect = Rect::ZERO;
         for child in &mut self.children {
-            // top-align, could do center etc. based on child height
             let rect = child.widget.layout_rect();
-            let pos: Point = self.direction.pack(major, 0.0).into();
+            let extra = minor - self.direction.minor(rect.size());
+            let cross = match (self.cross_alignment, baseline) {
+                (_, Some(baseline)) => baseline - (rect.height() - child.widget.baseline_offset()),
+                (CrossAxisAlignment::Center, _) => extra / 2.0,
+                (CrossAxisAlignment::End, _) => extra,
+                _ => 0.0,
+            };
+            let pos: Point = self.direction.pack(major, cross).into();
             child.widget.set_layout_rect(rect.with_origin(pos));
-            child_paint_rect = child_paint_rect.union(child.widget.paint_rect());
             major += self.direction.major(rect.size());
         }
 
@@ -216,17 +396,107 @@ impl<T: Data> Widget<T> for Flex<T> {
             major = total_major;
         }
 
+        // In a right-to-left layout, rows start at the right edge.
+        let mirror = match self.direction {
+            Axis::Horizontal => env.get(Env::RIGHT_TO_LEFT) && major.is_finite(),
+            Axis::Vertical => false,
+        };
+        let mut child_paint_rect = Rect::ZERO;
+        for child in &mut self.children {
+            if mirror {
+                let rect = child.widget.layout_rect();
+                let pos = Point::new(major - rect.x1, rect.y0);
+                child.widget.set_layout_rect(rect.with_origin(pos));
+            }
+            child_paint_rect = child_paint_rect.union(child.widget.paint_rect());
+        }
+
         let (width, height) = self.direction.pack(major, minor);
         let my_size = Size::new(width, height);
+
+        // our baseline is that of the first child in a row, or the last in
+        // a column
+        let baseline_child = match self.direction {
+            Axis::Horizontal => self.children.first(),
+            Axis::Vertical => self.children.last(),
+        };
+        if let Some(child) = baseline_child {
+            let baseline_y = child.widget.layout_rect().y1 - child.widget.baseline_offset();
+            layout_ctx.set_baseline_offset(my_size.height - baseline_y);
+        }
         let my_bounds = Rect::ZERO.with_size(my_size);
         let insets = child_paint_rect - my_bounds;
         layout_ctx.set_paint_insets(insets);
         my_size
     }
 
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let children = self.children.iter_mut().map(|child| &mut child.widget);
+        if axis == self.direction {
+            children
+                .map(|child| child.min_intrinsic(ctx, axis, cross, data, env))
+                .sum()
+        } else {
+            children
+                .map(|child| child.min_intrinsic(ctx, axis, f64::INFINITY, data, env))
+                .fold(0.0, f64::max)
+        }
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let children = self.children.iter_mut().map(|child| &mut child.widget);
+        if axis == self.direction {
+            children
+                .map(|child| child.max_intrinsic(ctx, axis, cross, data, env))
+                .sum()
+        } else {
+            children
+                .map(|child| child.max_intrinsic(ctx, axis, f64::INFINITY, data, env))
+                .fold(0.0, f64::max)
+        }
+    }
+
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
         for child in &mut self.children {
             child.widget.paint_with_offset(paint_ctx, data, env);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn children_grow_and_shrink_to_fit() {
+        let fixed = FlexParams::new();
+        let grows = FlexParams::new().grow(1.0);
+        let shrinks = FlexParams::new().shrink(1.0);
+
+        // the space left over goes to the children that grow
+        let lengths = resolve_lengths(300.0, &[(fixed, 100.0), (grows, 50.0), (2.0.into(), 0.0)]);
+        assert_eq!(lengths, vec![100.0, 100.0, 100.0]);
+
+        // short of space, children shrink in proportion to their size
+        let lengths = resolve_lengths(200.0, &[(shrinks, 200.0), (shrinks, 100.0), (fixed, 50.0)]);
+        assert_eq!(lengths, vec![100.0, 50.0, 50.0]);
+
+        // and without any that shrink, they overflow as before
+        let lengths = resolve_lengths(100.0, &[(fixed, 80.0), (1.0.into(), 0.0), (fixed, 40.0)]);
+        assert_eq!(lengths, vec![80.0, 0.0, 40.0]);
+    }
+}
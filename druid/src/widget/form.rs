@@ -0,0 +1,506 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A form of fields that are checked before it is submitted.
+
+use std::any::Any;
+use std::mem;
+use std::sync::Arc;
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::text::TextLine;
+use crate::theme;
+use crate::widget::{Button, LabelText};
+use crate::{
+    BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, RenderContext, Selector, UpdateCtx, Widget, WidgetPod,
+};
+
+/// The space between a field and its error.
+const ERROR_GAP: f64 = 2.0;
+
+type Validator<T> = Box<dyn Fn(&T) -> Result<(), String>>;
+
+/// The data of the widgets in a [`Form`]: the data being edited, and the
+/// errors the validators found in it.
+///
+/// [`Form`]: struct.Form.html
+#[derive(Clone)]
+pub struct FormState<T> {
+    value: T,
+    /// The first error of each field that has one, in the order the fields'
+    /// validators were added.
+    errors: Arc<Vec<(&'static str, String)>>,
+    /// Whether the submit button was pressed.
+    submitting: bool,
+}
+
+impl<T: Data> FormState<T> {
+    fn new(value: T, validators: &[(&'static str, Validator<T>)]) -> Self {
+        let mut state = FormState {
+            value,
+            errors: Arc::new(Vec::new()),
+            submitting: false,
+        };
+        state.validate(validators);
+        state
+    }
+
+    /// Check the value again, after it has changed.
+    fn validate(&mut self, validators: &[(&'static str, Validator<T>)]) {
+        let mut errors: Vec<(&'static str, String)> = Vec::new();
+        for (field, validator) in validators {
+            if errors.iter().any(|(other, _)| other == field) {
+                continue;
+            }
+            if let Err(error) = validator(&self.value) {
+                errors.push((field, error));
+            }
+        }
+        // keep the old errors if they are the same, so that the fields
+        // aren't updated
+        if *self.errors != errors {
+            self.errors = Arc::new(errors);
+        }
+    }
+
+    /// The data being edited.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// The error that `field` has, if any.
+    pub fn error(&self, field: &str) -> Option<&str> {
+        self.errors
+            .iter()
+            .find(|(other, _)| *other == field)
+            .map(|(_, error)| error.as_str())
+    }
+
+    /// Whether no field has an error, so that the form can be submitted.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl<T: Data> Data for FormState<T> {
+    fn same(&self, other: &Self) -> bool {
+        self.value.same(&other.value)
+            && self.errors.same(&other.errors)
+            && self.submitting == other.submitting
+    }
+}
+
+/// A form of fields that are checked before the data they edit is submitted.
+///
+/// The validators are added to the form with [`validate`], each for a named
+/// field, and are run each time the data changes. Inside the form, each
+/// input is wrapped in a [`FormField`] with its name, which shows the
+/// field's error under it once the user has edited it, and a
+/// [`SubmitButton`] can only be pressed when there are no errors.
+///
+/// Pressing it submits a command with the selector given to [`new`], with
+/// the value made by its `build` closure as the argument; this can be of
+/// any type, such as a struct of the numbers parsed from the fields' text.
+///
+/// The widgets in the form have a [`FormState`] as their data, and the
+/// fields give their inputs the data of the form.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Flex, Form, FormField, SubmitButton, TextBox, WidgetExt};
+/// use druid::{Data, Lens, Selector};
+///
+/// #[derive(Clone, Data, Lens)]
+/// struct Signup {
+///     name: String,
+///     age: String,
+/// }
+///
+/// struct Person {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// const SIGN_UP: Selector = Selector::new("signup.sign-up");
+///
+/// let fields = Flex::column()
+///     .with_child(FormField::new("name", TextBox::new().lens(Signup::name)), 0.0)
+///     .with_child(FormField::new("age", TextBox::new().lens(Signup::age)), 0.0)
+///     .with_child(SubmitButton::new("Sign up"), 0.0);
+///
+/// let form = Form::new(fields, SIGN_UP, |signup: &Signup| Person {
+///     name: signup.name.clone(),
+///     age: signup.age.parse().unwrap(),
+/// })
+/// .validate("name", |signup| {
+///     if signup.name.is_empty() {
+///         Err("Enter your name".to_string())
+///     } else {
+///         Ok(())
+///     }
+/// })
+/// .validate("age", |signup| match signup.age.parse::<u32>() {
+///     Ok(_) => Ok(()),
+///     Err(_) => Err("Enter your age in years".to_string()),
+/// });
+/// ```
+///
+/// [`validate`]: #method.validate
+/// [`new`]: #method.new
+/// [`FormField`]: struct.FormField.html
+/// [`SubmitButton`]: struct.SubmitButton.html
+/// [`FormState`]: struct.FormState.html
+pub struct Form<T, V> {
+    child: WidgetPod<FormState<T>, Box<dyn Widget<FormState<T>>>>,
+    validators: Vec<(&'static str, Validator<T>)>,
+    submit: Selector,
+    build: Box<dyn Fn(&T) -> V>,
+    /// The data of the child, once we have been given ours.
+    state: Option<FormState<T>>,
+}
+
+impl<T: Data, V: Any> Form<T, V> {
+    /// Create a form around `child`, which submits a command with `submit`
+    /// and the value made by `build`.
+    pub fn new(
+        child: impl Widget<FormState<T>> + 'static,
+        submit: Selector,
+        build: impl Fn(&T) -> V + 'static,
+    ) -> Self {
+        Form {
+            child: WidgetPod::new(child).boxed(),
+            validators: Vec::new(),
+            submit,
+            build: Box::new(build),
+            state: None,
+        }
+    }
+
+    /// Builder-style method to check `field` with `validator`, which returns
+    /// the error to show if the data isn't valid.
+    ///
+    /// A field can have more than one validator; only the first error it
+    /// finds is shown.
+    pub fn validate(
+        mut self,
+        field: &'static str,
+        validator: impl Fn(&T) -> Result<(), String> + 'static,
+    ) -> Self {
+        self.validators.push((field, Box::new(validator)));
+        self
+    }
+}
+
+/// The form's state, brought up to date with `data`.
+fn sync<'a, T: Data>(
+    state: &'a mut Option<FormState<T>>,
+    validators: &[(&'static str, Validator<T>)],
+    data: &T,
+) -> &'a mut FormState<T> {
+    match state {
+        Some(state) if state.value.same(data) => (),
+        Some(state) => {
+            state.value = data.clone();
+            state.validate(validators);
+        }
+        None => *state = Some(FormState::new(data.clone(), validators)),
+    }
+    state.as_mut().unwrap()
+}
+
+impl<T: Data, V: Any> Widget<T> for Form<T, V> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        let state = sync(&mut self.state, &self.validators, data);
+        self.child.event(ctx, event, state, env);
+        if !state.value.same(data) {
+            *data = state.value.clone();
+            state.validate(&self.validators);
+        }
+        if mem::replace(&mut state.submitting, false) && state.is_valid() {
+            let value = (self.build)(&state.value);
+            ctx.submit_command(Command::new(self.submit.clone(), value), None);
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        let state = sync(&mut self.state, &self.validators, data);
+        self.child.lifecycle(ctx, event, state, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        let state = sync(&mut self.state, &self.validators, data);
+        self.child.update(ctx, state, env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Form");
+        let state = sync(&mut self.state, &self.validators, data);
+        let size = self.child.layout(layout_ctx, bc, state, env);
+        self.child
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        layout_ctx.set_baseline_offset(self.child.baseline_offset());
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        let state = sync(&mut self.state, &self.validators, data);
+        self.child.paint(paint_ctx, state, env);
+    }
+}
+
+/// An input in a [`Form`], with its error shown under it.
+///
+/// The error of the field with this name is shown once the user has edited
+/// the input, so that a new form doesn't start out covered in errors.
+///
+/// [`Form`]: struct.Form.html
+pub struct FormField<T> {
+    field: &'static str,
+    input: WidgetPod<T, Box<dyn Widget<T>>>,
+    /// Whether the user has changed the data in the input.
+    touched: bool,
+    /// The error being shown.
+    shown: Option<String>,
+    line: Option<TextLine>,
+}
+
+impl<T: Data> FormField<T> {
+    /// Create the field with the name `field`, around `input`.
+    pub fn new(field: &'static str, input: impl Widget<T> + 'static) -> Self {
+        FormField {
+            field,
+            input: WidgetPod::new(input).boxed(),
+            touched: false,
+            shown: None,
+            line: None,
+        }
+    }
+
+    /// The error to show, if there is one.
+    fn visible_error(&self, state: &FormState<T>) -> Option<String> {
+        if self.touched {
+            state.error(self.field).map(String::from)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Data> Widget<FormState<T>> for FormField<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut FormState<T>, env: &Env) {
+        let old = data.value.clone();
+        self.input.event(ctx, event, &mut data.value, env);
+        if !old.same(&data.value) {
+            self.touched = true;
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &FormState<T>,
+        env: &Env,
+    ) {
+        self.input.lifecycle(ctx, event, &data.value, env);
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        _old_data: &FormState<T>,
+        data: &FormState<T>,
+        env: &Env,
+    ) {
+        self.input.update(ctx, &data.value, env);
+        let error = self.visible_error(data);
+        if error != self.shown {
+            self.shown = error;
+            ctx.request_layout();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &FormState<T>,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("FormField");
+
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        self.line = self.shown.as_ref().map(|error| {
+            let font_name = env.get(theme::FONT_NAME);
+            TextLine::new(layout_ctx.text(), font_name, font_size, error, env)
+        });
+        let error_height = match self.line {
+            Some(_) => ERROR_GAP + font_size * 1.2,
+            None => 0.0,
+        };
+
+        let input_bc = bc.shrink((0.0, error_height));
+        let input_size = self.input.layout(layout_ctx, &input_bc, &data.value, env);
+        self.input
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, input_size));
+        layout_ctx.set_baseline_offset(self.input.baseline_offset() + error_height);
+
+        let error_width = self.line.as_ref().map_or(0.0, |line| line.width());
+        bc.constrain(Size::new(
+            input_size.width.max(error_width),
+            input_size.height + error_height,
+        ))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &FormState<T>, env: &Env) {
+        self.input.paint_with_offset(paint_ctx, &data.value, env);
+        if let Some(line) = &self.line {
+            let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+            let top = self.input.layout_rect().y1 + ERROR_GAP;
+            // the same baseline as a label's
+            let origin = Point::new(0.0, top + font_size * 0.9);
+            let color = env.get(theme::FORM_ERROR_COLOR);
+            paint_ctx.draw_text(line.layout(), origin, &color);
+        }
+    }
+}
+
+/// The button that submits a [`Form`], which can't be pressed while any of
+/// its fields has an error.
+///
+/// [`Form`]: struct.Form.html
+pub struct SubmitButton<T> {
+    button: Button<FormState<T>>,
+}
+
+impl<T: Data> SubmitButton<T> {
+    /// Create a submit button with the label `text`.
+    pub fn new(text: impl Into<LabelText<FormState<T>>>) -> Self {
+        let button = Button::new(text, |_ctx, state: &mut FormState<T>, _env| {
+            state.submitting = true;
+        });
+        SubmitButton { button }
+    }
+}
+
+impl<T: Data> Widget<FormState<T>> for SubmitButton<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut FormState<T>, env: &Env) {
+        if data.is_valid() {
+            self.button.event(ctx, event, data, env);
+        } else if ctx.is_active() {
+            ctx.set_active(false);
+            ctx.request_paint();
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &FormState<T>,
+        env: &Env,
+    ) {
+        self.button.lifecycle(ctx, event, data, env);
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: &FormState<T>,
+        data: &FormState<T>,
+        env: &Env,
+    ) {
+        if old_data.is_valid() != data.is_valid() {
+            ctx.request_paint();
+        }
+        self.button.update(ctx, old_data, data, env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &FormState<T>,
+        env: &Env,
+    ) -> Size {
+        self.button.layout(layout_ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &FormState<T>, env: &Env) {
+        if data.is_valid() {
+            self.button.paint(paint_ctx, data, env);
+        } else {
+            // greyed out
+            let env = env
+                .clone()
+                .adding(theme::LABEL_COLOR, env.get(theme::PLACEHOLDER_COLOR))
+                .adding(theme::BUTTON_LIGHT, env.get(theme::BUTTON_DARK));
+            self.button.paint(paint_ctx, data, &env);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validators() -> Vec<(&'static str, Validator<String>)> {
+        let not_empty: Validator<String> = Box::new(|name: &String| {
+            if name.is_empty() {
+                Err("Enter a name".to_string())
+            } else {
+                Ok(())
+            }
+        });
+        let short: Validator<String> = Box::new(|name: &String| {
+            if name.len() > 8 {
+                Err("That's too long".to_string())
+            } else {
+                Ok(())
+            }
+        });
+        vec![("name", not_empty), ("name", short)]
+    }
+
+    #[test]
+    fn errors_follow_the_data() {
+        let validators = validators();
+        let mut state = FormState::new(String::new(), &validators);
+        assert_eq!(state.error("name"), Some("Enter a name"));
+        assert!(!state.is_valid());
+
+        state.value = "Ferris".to_string();
+        state.validate(&validators);
+        assert_eq!(state.error("name"), None);
+        assert!(state.is_valid());
+
+        // only the first error of a field is kept
+        state.value = "Ferris the crab".to_string();
+        state.validate(&validators);
+        assert_eq!(state.error("name"), Some("That's too long"));
+        // and the same errors are left alone
+        let errors = state.errors.clone();
+        state.value = "Ferris the crustacean".to_string();
+        state.validate(&validators);
+        assert!(Arc::ptr_eq(&errors, &state.errors));
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that draws a named vector icon.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::kurbo::{Affine, BezPath, Size, SvgParseError, Vec2};
+use crate::piet::Color;
+use crate::theme;
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    RenderContext, UpdateCtx, Widget,
+};
+
+/// The width and height of the grid the icons are drawn on.
+pub(crate) const GRID_SIZE: f64 = 24.0;
+
+/// The icons that come with druid, as SVG path data. These are from the
+/// Material Design icons, which are under the Apache License 2.0.
+const BUILT_IN: &[(&str, &str)] = &[
+    ("add", "M19 13h-6v6h-2v-6H5v-2h6V5h2v6h6v2z"),
+    ("remove", "M19 13H5v-2h14v2z"),
+    (
+        "close",
+        "M19 6.41L17.59 5 12 10.59 6.41 5 5 6.41 10.59 12 5 17.59 6.41 19 12 13.41 17.59 19 19 17.59 13.41 12z",
+    ),
+    ("check", "M9 16.17L4.83 12l-1.42 1.41L9 19 21 7l-1.41-1.41z"),
+    ("chevron-left", "M15.41 7.41L14 6l-6 6 6 6 1.41-1.41L10.83 12z"),
+    ("chevron-right", "M10 6L8.59 7.41 13.17 12l-4.58 4.59L10 18l6-6z"),
+    ("chevron-up", "M12 8l-6 6 1.41 1.41L12 10.83l4.59 4.58L18 14z"),
+    ("chevron-down", "M16.59 8.59L12 13.17 7.41 8.59 6 10l6 6 6-6z"),
+    (
+        "arrow-back",
+        "M20 11H7.83l5.59-5.59L12 4l-8 8 8 8 1.41-1.41L7.83 13H20v-2z",
+    ),
+    (
+        "arrow-forward",
+        "M12 4l-1.41 1.41L16.17 11H4v2h12.17l-5.58 5.59L12 20l8-8z",
+    ),
+    ("menu", "M3 18h18v-2H3v2zm0-5h18v-2H3v2zm0-7v2h18V6H3z"),
+    (
+        "more",
+        "M12 8c1.1 0 2-.9 2-2s-.9-2-2-2-2 .9-2 2 .9 2 2 2zm0 2c-1.1 0-2 .9-2 2s.9 2 2 2 2-.9 2-2-.9-2-2-2zm0 6c-1.1 0-2 .9-2 2s.9 2 2 2 2-.9 2-2-.9-2-2-2z",
+    ),
+    (
+        "search",
+        "M15.5 14h-.79l-.28-.27C15.41 12.59 16 11.11 16 9.5 16 5.91 13.09 3 9.5 3S3 5.91 3 9.5 5.91 16 9.5 16c1.61 0 3.09-.59 4.23-1.57l.27.28v.79l5 4.99L20.49 19l-4.99-5zm-6 0C7.01 14 5 11.99 5 9.5S7.01 5 9.5 5 14 7.01 14 9.5 11.99 14 9.5 14z",
+    ),
+    ("play", "M8 5v14l11-7z"),
+    ("pause", "M6 19h4V5H6v14zm8-14v14h4V5h-4z"),
+];
+
+thread_local! {
+    /// The icons that have been looked up or registered, by name.
+    static ICONS: RefCell<HashMap<String, Rc<BezPath>>> = RefCell::new(HashMap::new());
+}
+
+/// A widget that draws a vector icon, such as the arrow of a back button.
+///
+/// Icons are found by name. Druid comes with `add`, `remove`, `close`,
+/// `check`, `chevron-left`, `chevron-right`, `chevron-up`, `chevron-down`,
+/// `arrow-back`, `arrow-forward`, `menu`, `more`, `search`, `play` and
+/// `pause`; others can be added with [`Icon::register`].
+///
+/// Unlike an [`Image`], an icon is sharp at any size. It is
+/// [`theme::ICON_SIZE`] across, in [`theme::ICON_COLOR`], unless it is
+/// given a [`size`] or [`color`] of its own.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Flex, Icon};
+///
+/// let toolbar = Flex::<u32>::row()
+///     .with_child(Icon::new("remove"))
+///     .with_child(Icon::new("add").size(32.0));
+/// ```
+///
+/// [`Icon::register`]: #method.register
+/// [`Image`]: struct.Image.html
+/// [`theme::ICON_SIZE`]: ../theme/constant.ICON_SIZE.html
+/// [`theme::ICON_COLOR`]: ../theme/constant.ICON_COLOR.html
+/// [`size`]: #method.size
+/// [`color`]: #method.color
+pub struct Icon {
+    name: String,
+    /// The outline, once it has been found.
+    path: Option<Rc<BezPath>>,
+    size: Option<f64>,
+    color: Option<Color>,
+}
+
+impl Icon {
+    /// Create an icon that draws the icon called `name`.
+    ///
+    /// If there is no such icon when the widget is added, a warning is
+    /// logged, and nothing is drawn.
+    pub fn new(name: impl Into<String>) -> Self {
+        Icon {
+            name: name.into(),
+            path: None,
+            size: None,
+            color: None,
+        }
+    }
+
+    /// Add an icon, or replace one, for all the icons created afterwards.
+    ///
+    /// `svg_path` is the `d` attribute of an SVG `path`, drawn on a grid of
+    /// 24 by 24 and filled. Icons are registered for the thread they are
+    /// registered on, which is the UI thread.
+    pub fn register(name: impl Into<String>, svg_path: &str) -> Result<(), SvgParseError> {
+        let path = BezPath::from_svg(svg_path)?;
+        ICONS.with(|icons| icons.borrow_mut().insert(name.into(), Rc::new(path)));
+        Ok(())
+    }
+
+    /// Whether there is an icon called `name`.
+    pub fn exists(name: &str) -> bool {
+        find(name).is_some()
+    }
+
+    /// Builder-style method for setting the width and height of the icon.
+    pub fn size(mut self, size: f64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Builder-style method for setting the color of the icon.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    fn icon_size(&self, env: &Env) -> f64 {
+        self.size.unwrap_or_else(|| env.get(theme::ICON_SIZE))
+    }
+}
+
+/// The outline of the icon called `name`: a registered one, or else a
+/// built-in one.
+pub(crate) fn find(name: &str) -> Option<Rc<BezPath>> {
+    ICONS.with(|icons| {
+        let mut icons = icons.borrow_mut();
+        if let Some(path) = icons.get(name) {
+            return Some(path.clone());
+        }
+        let (_, svg_path) = BUILT_IN.iter().find(|(built_in, _)| *built_in == name)?;
+        let path = Rc::new(BezPath::from_svg(svg_path).expect("built-in icons are valid"));
+        icons.insert(name.to_string(), path.clone());
+        Some(path)
+    })
+}
+
+impl<T: Data> Widget<T> for Icon {
+    fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut T, _env: &Env) {}
+
+    fn lifecycle(&mut self, _ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &T, _env: &Env) {
+        if let LifeCycle::WidgetAdded = event {
+            self.path = find(&self.name);
+            if self.path.is_none() {
+                log::warn!("there is no icon called '{}'", self.name);
+            }
+        }
+    }
+
+    fn update(&mut self, _ctx: &mut UpdateCtx, _old_data: &T, _data: &T, _env: &Env) {}
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints, _data: &T, env: &Env) -> Size {
+        bc.debug_check("Icon");
+        let size = self.icon_size(env);
+        bc.constrain(Size::new(size, size))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _data: &T, env: &Env) {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return,
+        };
+        let size = paint_ctx.size();
+        // as big as it was asked to be, and still fitting, and centered
+        let side = self.icon_size(env).min(size.width).min(size.height);
+        let offset = Vec2::new(size.width - side, size.height - side) / 2.0;
+        let transform = Affine::translate(offset) * Affine::scale(side / GRID_SIZE);
+        let color = self
+            .color
+            .clone()
+            .unwrap_or_else(|| env.get(theme::ICON_COLOR));
+        paint_ctx.fill(transform * &**path, &color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kurbo::{Rect, Shape};
+
+    #[test]
+    fn built_in_icons_fit_the_grid() {
+        let grid = Rect::new(0.0, 0.0, GRID_SIZE, GRID_SIZE);
+        for (name, _) in BUILT_IN {
+            let bounds = find(name).unwrap().bounding_box();
+            assert_eq!(
+                bounds.union(grid).area(),
+                grid.area(),
+                "{} is too big",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn registered_icons() {
+        assert!(!Icon::exists("triangle"));
+        Icon::register("triangle", "M12 4L20 20H4z").unwrap();
+        assert!(Icon::exists("triangle"));
+        assert!(Icon::register("broken", "M12 4L").is_err());
+        assert!(!Icon::exists("broken"));
+
+        // a built-in icon can be replaced
+        assert_eq!(find("add").unwrap().bounding_box().x0, 5.0);
+        Icon::register("add", "M11 5h2v14h-2z").unwrap();
+        assert_eq!(find("add").unwrap().bounding_box().x0, 11.0);
+    }
+}
@@ -15,6 +15,7 @@
 //! A widget that provides an explicit identity to a child.
 
 use crate::kurbo::Size;
+use crate::widget::Axis;
 use crate::{
     BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
     UpdateCtx, Widget, WidgetId,
@@ -50,6 +51,28 @@ impl<T: Data, W: Widget<T>> Widget<T> for IdentityWrapper<W> {
         self.inner.layout(ctx, bc, data, env)
     }
 
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        self.inner.min_intrinsic(ctx, axis, cross, data, env)
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        self.inner.max_intrinsic(ctx, axis, cross, data, env)
+    }
+
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
         self.inner.paint(paint_ctx, data, env);
     }
@@ -0,0 +1,936 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that draws a bitmap image.
+
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use log::error;
+
+use crate::piet::{ImageFormat, InterpolationMode, Piet};
+use crate::shell::Icon;
+use crate::widget::{AccessibilityInfo, LabelText, Role};
+use crate::{
+    Affine, BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, Rect, RenderContext, Size, UpdateCtx, Widget,
+};
+
+/// The image a render context makes from an `ImageData`.
+pub(crate) type PietImage = <Piet<'static> as RenderContext>::Image;
+
+/// A widget that draws an [`ImageData`].
+///
+/// By default the image is scaled to fit inside the widget, keeping its
+/// aspect ratio; see [`fill_mode`] for the other ways it can fill the space.
+/// It can also draw only part of the image, with [`source_rect`], and turn
+/// it with [`rotation`], [`flip_horizontal`] and [`flip_vertical`].
+///
+/// For sharp images on high-density screens, it can be given the same image
+/// at several resolutions, with [`from_resolutions`]; it draws the one that
+/// best suits the scale of its window.
+///
+/// [`ImageData`]: struct.ImageData.html
+/// [`fill_mode`]: #method.fill_mode
+/// [`source_rect`]: #method.source_rect
+/// [`rotation`]: #method.rotation
+/// [`flip_horizontal`]: #method.flip_horizontal
+/// [`flip_vertical`]: #method.flip_vertical
+/// [`from_resolutions`]: #method.from_resolutions
+pub struct Image<T> {
+    image_data: ImageData,
+    /// How many pixels of `image_data` there are in each unit of the window.
+    image_scale: f64,
+    /// The versions of the image to choose from, by scale, if there are
+    /// more than one.
+    resolutions: Vec<(f64, ImageData)>,
+    /// The image made from `image_data` for the render context, kept
+    /// between paints, and shared with the window's other widgets drawing
+    /// the same pixels.
+    piet_image: Option<Rc<PietImage>>,
+    source: Option<Rect>,
+    /// Clockwise, in degrees.
+    rotation: f64,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    fill: FillStrat,
+    interpolation: InterpolationMode,
+    /// What the image shows, for those who can't see it.
+    alt_text: Option<LabelText<T>>,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Data> Image<T> {
+    /// Create an image-drawing widget from `ImageData`.
+    pub fn new(image_data: ImageData) -> Self {
+        Image {
+            image_data,
+            image_scale: 1.0,
+            resolutions: Vec::new(),
+            piet_image: None,
+            source: None,
+            rotation: 0.0,
+            flip_horizontal: false,
+            flip_vertical: false,
+            fill: FillStrat::default(),
+            interpolation: InterpolationMode::Bilinear,
+            alt_text: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Create an image-drawing widget from the same image at several
+    /// resolutions, each with its scale: `1.0` for the normal size, `2.0`
+    /// for one with twice as many pixels each way, and so on.
+    ///
+    /// The widget draws the smallest one that is at least as detailed as
+    /// the screen, or the largest if there is none, and picks again when
+    /// the window moves to a screen with another scale. The sizes are those
+    /// of the images divided by their scales, so they should all be the
+    /// same.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no images, or if a scale is not positive.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use druid::widget::{Image, ImageData};
+    ///
+    /// let icon = Image::<()>::from_resolutions(vec![
+    ///     (1.0, ImageData::from_file("icon.png").unwrap()),
+    ///     (2.0, ImageData::from_file("icon@2x.png").unwrap()),
+    ///     (3.0, ImageData::from_file("icon@3x.png").unwrap()),
+    /// ]);
+    /// ```
+    pub fn from_resolutions(resolutions: impl IntoIterator<Item = (f64, ImageData)>) -> Self {
+        let mut resolutions: Vec<_> = resolutions.into_iter().collect();
+        assert!(!resolutions.is_empty(), "an image needs a resolution");
+        assert!(
+            resolutions.iter().all(|(scale, _)| *scale > 0.0),
+            "image scales must be positive"
+        );
+        resolutions.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let mut image = Image::new(resolutions[0].1.clone());
+        image.image_scale = resolutions[0].0;
+        image.resolutions = resolutions;
+        image
+    }
+
+    /// Builder-style method for setting how the image fills the widget.
+    pub fn fill_mode(mut self, fill: FillStrat) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Builder-style method for describing what the image shows, for
+    /// screen readers. The text can be localized or computed from the data.
+    pub fn alt_text(mut self, text: impl Into<LabelText<T>>) -> Self {
+        self.alt_text = Some(text.into());
+        self
+    }
+
+    /// The image being drawn.
+    pub fn image_data(&self) -> &ImageData {
+        &self.image_data
+    }
+
+    /// Change the image being drawn, to one with a single resolution.
+    ///
+    /// The widget has to be laid out again if the new image has a different
+    /// size, and repainted in any case.
+    pub fn set_image_data(&mut self, image_data: ImageData) {
+        self.image_data = image_data;
+        self.image_scale = 1.0;
+        self.resolutions.clear();
+        self.piet_image = None;
+    }
+
+    /// Builder-style method for setting how the image is sampled when it is
+    /// scaled.
+    pub fn interpolation_mode(mut self, interpolation: InterpolationMode) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Builder-style method for drawing only the part of the image inside
+    /// `rect`, in pixels, such as one icon of a strip of them. With several
+    /// resolutions, these are the pixels of the one with scale `1.0`.
+    ///
+    /// The widget then behaves as if that part were the whole image: it is
+    /// what is sized, and what the [`fill_mode`] fits into the widget.
+    ///
+    /// [`fill_mode`]: #method.fill_mode
+    pub fn source_rect(mut self, rect: Rect) -> Self {
+        self.source = Some(rect);
+        self
+    }
+
+    /// Builder-style method for turning the image clockwise by `degrees`,
+    /// such as a photo or a scan that was taken sideways.
+    ///
+    /// The widget is sized, and the [`fill_mode`] applied, as if the image
+    /// were the box around the turned image.
+    ///
+    /// [`fill_mode`]: #method.fill_mode
+    pub fn rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees;
+        self
+    }
+
+    /// Builder-style method for mirroring the image left to right.
+    ///
+    /// The image is mirrored before it is turned by the [`rotation`].
+    ///
+    /// [`rotation`]: #method.rotation
+    pub fn flip_horizontal(mut self, flip: bool) -> Self {
+        self.flip_horizontal = flip;
+        self
+    }
+
+    /// Builder-style method for mirroring the image top to bottom.
+    ///
+    /// The image is mirrored before it is turned by the [`rotation`].
+    ///
+    /// [`rotation`]: #method.rotation
+    pub fn flip_vertical(mut self, flip: bool) -> Self {
+        self.flip_vertical = flip;
+        self
+    }
+
+    /// Switch to the resolution that suits a window with `scale`.
+    fn select_resolution(&mut self, scale: f64) {
+        let best = match best_resolution(&self.resolutions, scale) {
+            Some(best) => best,
+            None => return,
+        };
+        let (image_scale, image_data) = &self.resolutions[best];
+        if !image_scale.same(&self.image_scale) {
+            self.image_scale = *image_scale;
+            self.image_data = image_data.clone();
+            self.piet_image = None;
+        }
+    }
+
+    /// The size of the image in the window's units.
+    fn image_size(&self) -> Size {
+        let size = self.image_data.size();
+        Size::new(
+            size.width / self.image_scale,
+            size.height / self.image_scale,
+        )
+    }
+
+    /// The part of the image that is drawn, inside the image's bounds.
+    fn visible_rect(&self) -> Rect {
+        let bounds = Rect::from_origin_size((0.0, 0.0), self.image_size());
+        match self.source {
+            Some(source) => source.abs().intersect(bounds),
+            None => bounds,
+        }
+    }
+
+    /// The transform that flips and turns content of `size`, placed at the
+    /// origin, and the size of the box around the result, which is also at
+    /// the origin.
+    fn orientation(&self, size: Size) -> (Affine, Size) {
+        let flip = Affine::scale_non_uniform(
+            if self.flip_horizontal { -1.0 } else { 1.0 },
+            if self.flip_vertical { -1.0 } else { 1.0 },
+        );
+        let (sin, cos) = sin_cos(self.rotation);
+        let turn = Affine::new([cos, sin, -sin, cos, 0.0, 0.0]) * flip;
+        let bounds = turn.transform_rect_bbox(Rect::from_origin_size((0.0, 0.0), size));
+        (
+            Affine::translate(-bounds.origin().to_vec2()) * turn,
+            bounds.size(),
+        )
+    }
+}
+
+/// The sine and cosine of an angle in degrees, exact for quarter turns so
+/// that the image isn't blurred by them.
+fn sin_cos(degrees: f64) -> (f64, f64) {
+    let degrees = degrees.rem_euclid(360.0);
+    let quarters = degrees / 90.0;
+    if quarters.fract() != 0.0 {
+        return degrees.to_radians().sin_cos();
+    }
+    match quarters as u8 {
+        0 => (0.0, 1.0),
+        1 => (1.0, 0.0),
+        2 => (0.0, -1.0),
+        _ => (-1.0, 0.0),
+    }
+}
+
+impl<T: Data> Widget<T> for Image<T> {
+    fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut T, _env: &Env) {}
+
+    fn lifecycle(&mut self, _ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => {
+                if let Some(text) = &mut self.alt_text {
+                    text.resolve(data, env);
+                }
+            }
+            LifeCycle::DebugCollectTree(collector) => collector.describe(AccessibilityInfo {
+                role: Role::Image,
+                label: self
+                    .alt_text
+                    .as_ref()
+                    .map(|text| text.with_display_text(data, env, str::to_owned)),
+                hint: None,
+            }),
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, _ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        if let Some(text) = &mut self.alt_text {
+            text.resolve(data, env);
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, _data: &T, _env: &Env) -> Size {
+        bc.debug_check("Image");
+        self.select_resolution(ctx.scale());
+
+        if bc.is_width_bounded() {
+            bc.max()
+        } else {
+            let (_, size) = self.orientation(self.visible_rect().size());
+            bc.constrain(size)
+        }
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _data: &T, _env: &Env) {
+        let visible = self.visible_rect();
+        let (orientation, turned_size) = self.orientation(visible.size());
+        let transform = self.fill.affine_to_fill(paint_ctx.size(), turned_size)
+            * orientation
+            * Affine::translate(-visible.origin().to_vec2());
+
+        if let Err(e) = paint_ctx.save() {
+            error!("saving render context failed: {:?}", e);
+            return;
+        }
+        // the image may be bigger than the widget, with some fill modes
+        let clip_rect = Rect::from_origin_size((0.0, 0.0), paint_ctx.size());
+        paint_ctx.clip(clip_rect);
+        paint_ctx.transform(transform);
+        // and the rest of the image, when only part of it is drawn
+        paint_ctx.clip(visible);
+        if self.piet_image.is_none() {
+            self.piet_image = paint_ctx.piet_image(&self.image_data);
+        }
+        if let Some(image) = self.piet_image.as_ref() {
+            let rect = Rect::from_origin_size((0.0, 0.0), self.image_size());
+            paint_ctx.draw_image(image, rect, self.interpolation);
+        }
+        if let Err(e) = paint_ctx.restore() {
+            error!("restoring render context failed: {:?}", e);
+        }
+    }
+}
+
+/// The index of the smallest of `resolutions` whose scale is at least
+/// `scale`, or of the largest if they are all smaller; `resolutions` is
+/// sorted by scale.
+fn best_resolution(resolutions: &[(f64, ImageData)], scale: f64) -> Option<usize> {
+    resolutions
+        .iter()
+        .position(|(image_scale, _)| *image_scale >= scale)
+        .or_else(|| resolutions.len().checked_sub(1))
+}
+
+/// How an image is fitted into the space it is given.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FillStrat {
+    /// As large as possible without cropping, keeping the aspect ratio.
+    #[default]
+    Contain,
+    /// As small as possible while covering the space, keeping the aspect
+    /// ratio; the rest is cropped.
+    Cover,
+    /// Stretched to fill the space exactly.
+    Fill,
+    /// As tall as the space, keeping the aspect ratio.
+    FitHeight,
+    /// As wide as the space, keeping the aspect ratio.
+    FitWidth,
+    /// At its own size, centered.
+    None,
+    /// Like `Contain`, but never scaled up.
+    ScaleDown,
+}
+
+impl FillStrat {
+    /// The transform that fits something of `content` size into `parent`,
+    /// centered.
+    pub fn affine_to_fill(self, parent: Size, content: Size) -> Affine {
+        if content.width <= 0.0 || content.height <= 0.0 {
+            return Affine::default();
+        }
+        let scale_x = parent.width / content.width;
+        let scale_y = parent.height / content.height;
+
+        let (scale_x, scale_y) = match self {
+            FillStrat::Contain => {
+                let scale = scale_x.min(scale_y);
+                (scale, scale)
+            }
+            FillStrat::Cover => {
+                let scale = scale_x.max(scale_y);
+                (scale, scale)
+            }
+            FillStrat::Fill => (scale_x, scale_y),
+            FillStrat::FitHeight => (scale_y, scale_y),
+            FillStrat::FitWidth => (scale_x, scale_x),
+            FillStrat::None => (1.0, 1.0),
+            FillStrat::ScaleDown => {
+                let scale = scale_x.min(scale_y).min(1.0);
+                (scale, scale)
+            }
+        };
+
+        let origin_x = (parent.width - content.width * scale_x) / 2.0;
+        let origin_y = (parent.height - content.height * scale_y) / 2.0;
+        Affine::new([scale_x, 0., 0., scale_y, origin_x, origin_y])
+    }
+}
+
+/// How the pixels in a buffer passed to [`ImageData::from_raw`] are laid out.
+///
+/// [`ImageData::from_raw`]: struct.ImageData.html#method.from_raw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 1 byte per pixel, the brightness.
+    Grayscale,
+    /// 2 bytes per pixel, the brightness and then the alpha.
+    GrayscaleAlpha,
+    /// 3 bytes per pixel, in RGB order.
+    Rgb,
+    /// 4 bytes per pixel, in RGBA order, with separate alpha.
+    Rgba,
+    /// 4 bytes per pixel, in RGBA order, with premultiplied alpha.
+    RgbaPremul,
+}
+
+impl PixelFormat {
+    /// The number of bytes each pixel takes.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Grayscale => 1,
+            PixelFormat::GrayscaleAlpha => 2,
+            PixelFormat::Rgb => 3,
+            PixelFormat::Rgba | PixelFormat::RgbaPremul => 4,
+        }
+    }
+}
+
+/// Stored bitmap data, that can be drawn with the [`Image`] widget.
+///
+/// Cloning is cheap: the pixels are shared.
+///
+/// [`Image`]: struct.Image.html
+#[derive(Clone)]
+pub struct ImageData {
+    pixels: Arc<[u8]>,
+    width: usize,
+    height: usize,
+    format: ImageFormat,
+}
+
+impl ImageData {
+    /// An image with no pixels.
+    pub fn empty() -> Self {
+        ImageData {
+            pixels: Arc::new([]),
+            width: 0,
+            height: 0,
+            format: ImageFormat::RgbaSeparate,
+        }
+    }
+
+    /// Create an image from pixels the caller has already decoded or made,
+    /// such as the frames of a camera or a procedurally drawn pattern.
+    ///
+    /// `pixels` holds `height` rows of `width` pixels each, top to bottom,
+    /// with no padding between rows. Grayscale pixels are converted to RGB
+    /// as the image is made; the other formats are kept as they are.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pixels` is not exactly the size `width`, `height` and
+    /// `format` call for, or if that size overflows a `usize`.
+    pub fn from_raw(
+        pixels: impl Into<Vec<u8>>,
+        width: usize,
+        height: usize,
+        format: PixelFormat,
+    ) -> Self {
+        let pixels = pixels.into();
+        let len = width
+            .checked_mul(height)
+            .and_then(|n| n.checked_mul(format.bytes_per_pixel()))
+            .unwrap_or_else(|| panic!("{}x{} {:?} image is too large", width, height, format));
+        assert_eq!(
+            pixels.len(),
+            len,
+            "{}x{} {:?} image needs {} bytes",
+            width,
+            height,
+            format,
+            len,
+        );
+        let (pixels, format) = match format {
+            PixelFormat::Grayscale => {
+                let mut rgb = Vec::with_capacity(pixels.len() * 3);
+                for &v in &pixels {
+                    rgb.extend_from_slice(&[v, v, v]);
+                }
+                (rgb, ImageFormat::Rgb)
+            }
+            PixelFormat::GrayscaleAlpha => {
+                let mut rgba = Vec::with_capacity(pixels.len() * 2);
+                for va in pixels.chunks_exact(2) {
+                    rgba.extend_from_slice(&[va[0], va[0], va[0], va[1]]);
+                }
+                (rgba, ImageFormat::RgbaSeparate)
+            }
+            PixelFormat::Rgb => (pixels, ImageFormat::Rgb),
+            PixelFormat::Rgba => (pixels, ImageFormat::RgbaSeparate),
+            PixelFormat::RgbaPremul => (pixels, ImageFormat::RgbaPremul),
+        };
+        ImageData {
+            pixels: pixels.into(),
+            width,
+            height,
+            format,
+        }
+    }
+
+    /// Decode an image in any of the formats the `image` crate is built
+    /// with here: PNG, JPEG, GIF and BMP, and also WebP with the `webp`
+    /// feature and AVIF with the `avif` feature.
+    ///
+    /// AVIF decoding uses the dav1d library, which has to be installed.
+    ///
+    /// With the `exif` feature, the rotation and flip that a camera records
+    /// in the image's EXIF metadata is applied, so that photos come out
+    /// upright.
+    ///
+    /// This is only available with the `image` feature.
+    #[cfg(feature = "image")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+    pub fn from_data(raw: &[u8]) -> Result<Self, ImageError> {
+        let image = image::load_from_memory(raw).map_err(|e| match e {
+            image::ImageError::Unsupported(_) => ImageError::UnsupportedFormat(
+                image::guess_format(raw)
+                    .ok()
+                    .map(|format| format!("{:?}", format)),
+            ),
+            e => ImageError::Decode(Box::new(e)),
+        })?;
+        #[cfg(feature = "exif")]
+        let image = match exif_orientation(raw) {
+            Some(orientation) => apply_orientation(image, orientation),
+            None => image,
+        };
+        Ok(ImageData::from_dynamic_image(image))
+    }
+
+    /// Load and decode an image file.
+    ///
+    /// This is only available with the `image` feature.
+    #[cfg(feature = "image")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, ImageError> {
+        ImageData::from_data(&std::fs::read(path).map_err(ImageError::Io)?)
+    }
+
+    #[cfg(feature = "image")]
+    fn from_dynamic_image(image: image::DynamicImage) -> Self {
+        let rgba = image.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        ImageData {
+            pixels: rgba.into_raw().into(),
+            width: width as usize,
+            height: height as usize,
+            format: ImageFormat::RgbaSeparate,
+        }
+    }
+
+    /// The pixels, in the format that the image has been converted to.
+    #[cfg(test)]
+    pub(crate) fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The size of the image, in pixels.
+    pub fn size(&self) -> Size {
+        Size::new(self.width as f64, self.height as f64)
+    }
+
+    /// The shared pixels, which tell clones of the image apart from other
+    /// images.
+    pub(crate) fn pixel_buffer(&self) -> &Arc<[u8]> {
+        &self.pixels
+    }
+
+    /// The pixels as RGBA, without premultiplied alpha.
+    pub(crate) fn rgba_pixels(&self) -> Cow<'_, [u8]> {
+        match self.format {
+            ImageFormat::Rgb => {
+                let mut rgba = Vec::with_capacity(self.width * self.height * 4);
+                for rgb in self.pixels.chunks(3) {
+                    rgba.extend_from_slice(rgb);
+                    rgba.push(255);
+                }
+                Cow::Owned(rgba)
+            }
+            ImageFormat::RgbaPremul => {
+                let mut rgba = self.pixels.to_vec();
+                for pixel in rgba.chunks_mut(4) {
+                    let alpha = u32::from(pixel[3]);
+                    for c in &mut pixel[..3] {
+                        *c = match alpha {
+                            0 => 0,
+                            _ => (u32::from(*c) * 255 / alpha).min(255) as u8,
+                        };
+                    }
+                }
+                Cow::Owned(rgba)
+            }
+            _ => Cow::Borrowed(&self.pixels),
+        }
+    }
+
+    /// A copy of the image, for window and menu item icons and drag images.
+    pub(crate) fn to_icon(&self) -> Icon {
+        Icon::from_rgba(self.rgba_pixels().into_owned(), self.width, self.height)
+    }
+
+    /// Draw the image, with `transform` taking it from pixels to the
+    /// context's coordinates.
+    ///
+    /// The image the render context draws is made once for each window,
+    /// and shared with the clones of this `ImageData`.
+    pub fn to_piet(
+        &self,
+        transform: Affine,
+        paint_ctx: &mut PaintCtx,
+        interpolation: InterpolationMode,
+    ) {
+        if let Some(image) = paint_ctx.piet_image(self) {
+            paint_ctx.transform(transform);
+            paint_ctx.draw_image(
+                &image,
+                Rect::from_origin_size((0.0, 0.0), self.size()),
+                interpolation,
+            );
+        }
+    }
+
+    /// Make the image that the render context draws, unless it is empty.
+    pub(crate) fn make_piet_image(&self, paint_ctx: &mut PaintCtx) -> Option<PietImage> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+        match paint_ctx.make_image(self.width, self.height, &self.pixels, self.format) {
+            Ok(image) => Some(image),
+            Err(e) => {
+                error!("making an image failed: {:?}", e);
+                None
+            }
+        }
+    }
+}
+
+/// An error from loading an [`ImageData`].
+///
+/// This is only available with the `image` feature.
+///
+/// [`ImageData`]: struct.ImageData.html
+#[cfg(feature = "image")]
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+#[derive(Debug)]
+pub enum ImageError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The data is not in a format that can be decoded here. This has the
+    /// format it seems to be in, if it was recognised, such as `"WebP"`
+    /// when the `webp` feature is off.
+    UnsupportedFormat(Option<String>),
+    /// The data could not be decoded; it may be damaged.
+    Decode(Box<dyn std::error::Error + Send + Sync>),
+}
+
+#[cfg(feature = "image")]
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "failed to read image: {}", e),
+            ImageError::UnsupportedFormat(Some(format)) => {
+                write!(f, "{} images are not supported", format)
+            }
+            ImageError::UnsupportedFormat(None) => write!(f, "unrecognised image format"),
+            ImageError::Decode(e) => write!(f, "failed to decode image: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageError::Io(e) => Some(e),
+            ImageError::UnsupportedFormat(_) => None,
+            ImageError::Decode(e) => Some(&**e),
+        }
+    }
+}
+
+/// The orientation recorded in the EXIF metadata of an encoded image, if it
+/// has any.
+#[cfg(feature = "exif")]
+fn exif_orientation(raw: &[u8]) -> Option<u32> {
+    let exif = exif::Reader::new()
+        .read_from_container(&mut std::io::Cursor::new(raw))
+        .ok()?;
+    exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
+        .value
+        .get_uint(0)
+}
+
+/// Turn an image that has the EXIF `orientation` upright.
+///
+/// The orientations are numbered 1 to 8; 1 is already upright, and unknown
+/// values are treated the same.
+#[cfg(feature = "exif")]
+fn apply_orientation(image: image::DynamicImage, orientation: u32) -> image::DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+impl Default for ImageData {
+    fn default() -> Self {
+        ImageData::empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Point;
+
+    #[test]
+    fn grayscale_becomes_rgb() {
+        let image = ImageData::from_raw(vec![0, 128, 255, 7], 2, 2, PixelFormat::Grayscale);
+        assert_eq!(image.size(), Size::new(2.0, 2.0));
+        assert_eq!(image.format, ImageFormat::Rgb);
+        assert_eq!(&image.pixels()[..6], &[0, 0, 0, 128, 128, 128]);
+
+        let image = ImageData::from_raw(vec![9, 200], 1, 1, PixelFormat::GrayscaleAlpha);
+        assert_eq!(image.pixels(), &[9, 9, 9, 200]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_buffer_size() {
+        ImageData::from_raw(vec![0; 11], 2, 2, PixelFormat::Rgb);
+    }
+
+    #[test]
+    #[should_panic(expected = "too large")]
+    fn overflowing_size() {
+        ImageData::from_raw(vec![], usize::MAX / 2, 3, PixelFormat::Rgb);
+    }
+
+    #[cfg(feature = "exif")]
+    #[test]
+    fn exif_orientations() {
+        use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+
+        // a 2x1 image, red on the left
+        let mut wide = RgbaImage::new(2, 1);
+        wide.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        let wide = DynamicImage::ImageRgba8(wide);
+        let red = Rgba([255, 0, 0, 255]);
+
+        assert_eq!(apply_orientation(wide.clone(), 1).get_pixel(0, 0), red);
+        assert_eq!(apply_orientation(wide.clone(), 2).get_pixel(1, 0), red);
+        // a camera held sideways: rotate clockwise, red ends up on top
+        let upright = apply_orientation(wide.clone(), 6);
+        assert_eq!(upright.dimensions(), (1, 2));
+        assert_eq!(upright.get_pixel(0, 0), red);
+        let upright = apply_orientation(wide.clone(), 8);
+        assert_eq!(upright.get_pixel(0, 1), red);
+        // transpose: red stays at the origin
+        assert_eq!(apply_orientation(wide.clone(), 5).get_pixel(0, 0), red);
+        assert_eq!(apply_orientation(wide.clone(), 7).get_pixel(0, 1), red);
+        // no EXIF data at all
+        assert_eq!(exif_orientation(b"not an image"), None);
+    }
+
+    fn rect_coords(rect: Rect) -> (f64, f64, f64, f64) {
+        (rect.x0, rect.y0, rect.x1, rect.y1)
+    }
+
+    #[test]
+    fn source_rect_is_kept_inside_the_image() {
+        let data = ImageData::from_raw(vec![0; 40 * 10 * 3], 40, 10, PixelFormat::Rgb);
+        let whole = Image::<()>::new(data.clone());
+        assert_eq!(rect_coords(whole.visible_rect()), (0., 0., 40., 10.));
+
+        let icon = Image::<()>::new(data.clone()).source_rect(Rect::new(10., 0., 20., 10.));
+        assert_eq!(rect_coords(icon.visible_rect()), (10., 0., 20., 10.));
+
+        let overhang = Image::<()>::new(data).source_rect(Rect::new(50., 5., 30., -5.));
+        assert_eq!(rect_coords(overhang.visible_rect()), (30., 0., 40., 5.));
+    }
+
+    #[test]
+    #[cfg(all(feature = "image", not(feature = "webp")))]
+    fn webp_needs_its_feature() {
+        let webp = b"RIFF\x1a\0\0\0WEBPVP8 ";
+        match ImageData::from_data(webp) {
+            Err(ImageError::UnsupportedFormat(Some(format))) => assert_eq!(format, "WebP"),
+            other => panic!("unexpected {:?}", other.map(|image| image.size())),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn damaged_images() {
+        let png = b"\x89PNG\r\n\x1a\nnot really";
+        assert!(matches!(
+            ImageData::from_data(png),
+            Err(ImageError::Decode(_))
+        ));
+        assert!(matches!(
+            ImageData::from_data(b"neither"),
+            Err(ImageError::UnsupportedFormat(None))
+        ));
+        assert!(matches!(
+            ImageData::from_file("/does/not/exist.png"),
+            Err(ImageError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn rotated_and_flipped() {
+        let data = ImageData::from_raw(vec![0; 40 * 10 * 3], 40, 10, PixelFormat::Rgb);
+        let size = data.size();
+        let corners = |image: &Image<()>| {
+            let (transform, bounds) = image.orientation(size);
+            let top_left = transform * Point::ORIGIN;
+            let bottom_right = transform * Point::new(40.0, 10.0);
+            (
+                (bounds.width, bounds.height),
+                (top_left.x, top_left.y),
+                (bottom_right.x, bottom_right.y),
+            )
+        };
+
+        let upright = Image::new(data.clone());
+        assert_eq!(corners(&upright), ((40., 10.), (0., 0.), (40., 10.)));
+        // the top left corner goes to the top right
+        let quarter = Image::new(data.clone()).rotation(90.0);
+        assert_eq!(corners(&quarter), ((10., 40.), (10., 0.), (0., 40.)));
+        let back = Image::new(data.clone()).rotation(-270.0);
+        assert_eq!(corners(&back), corners(&quarter));
+
+        let mirrored = Image::new(data.clone()).flip_horizontal(true);
+        assert_eq!(corners(&mirrored), ((40., 10.), (40., 0.), (0., 10.)));
+        let upside_down = Image::new(data.clone())
+            .flip_horizontal(true)
+            .flip_vertical(true);
+        assert_eq!(
+            corners(&upside_down),
+            corners(&Image::new(data.clone()).rotation(180.0))
+        );
+
+        let (_, tilted) = Image::<()>::new(data).rotation(45.0).orientation(size);
+        let side = 50.0 * std::f64::consts::FRAC_1_SQRT_2;
+        assert!((tilted.width - side).abs() < 1e-9 && (tilted.height - side).abs() < 1e-9);
+    }
+
+    #[test]
+    fn resolution_for_the_scale() {
+        let at = |scale: usize| {
+            let side = 10 * scale;
+            let data =
+                ImageData::from_raw(vec![0; side * side], side, side, PixelFormat::Grayscale);
+            (scale as f64, data)
+        };
+        let mut image = Image::<()>::from_resolutions(vec![at(3), at(1), at(2)]);
+        assert_eq!(image.image_scale, 1.0);
+
+        image.select_resolution(2.0);
+        assert_eq!(image.image_data.size(), Size::new(20.0, 20.0));
+        // the sharper image, scaled down
+        image.select_resolution(1.5);
+        assert_eq!(image.image_scale, 2.0);
+        // the sharpest there is, scaled up
+        image.select_resolution(4.0);
+        assert_eq!(image.image_scale, 3.0);
+        image.select_resolution(0.5);
+        assert_eq!(image.image_scale, 1.0);
+        // the image is the same size in the window
+        assert_eq!(image.image_size(), Size::new(10.0, 10.0));
+
+        image.set_image_data(at(2).1);
+        image.select_resolution(1.0);
+        assert_eq!(image.image_size(), Size::new(20.0, 20.0));
+    }
+
+    #[test]
+    fn fill_strategies() {
+        let parent = Size::new(200.0, 100.0);
+        let content = Size::new(50.0, 50.0);
+        assert_eq!(
+            FillStrat::Contain
+                .affine_to_fill(parent, content)
+                .as_coeffs(),
+            [2.0, 0., 0., 2.0, 50.0, 0.0]
+        );
+        assert_eq!(
+            FillStrat::Cover.affine_to_fill(parent, content).as_coeffs(),
+            [4.0, 0., 0., 4.0, 0.0, -50.0]
+        );
+        assert_eq!(
+            FillStrat::Fill.affine_to_fill(parent, content).as_coeffs(),
+            [4.0, 0., 0., 2.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            FillStrat::ScaleDown
+                .affine_to_fill(parent, content)
+                .as_coeffs(),
+            [1.0, 0., 0., 1.0, 75.0, 25.0]
+        );
+    }
+}
@@ -0,0 +1,394 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sharing images between widgets.
+//!
+//! Clones of an [`ImageData`] share their pixels, and each window makes one
+//! image for its render context from them, however many widgets draw them.
+//! The [`ImageCache`] hands out those clones by where the image came from,
+//! and an [`IconAtlas`] puts many small images into one.
+//!
+//! [`ImageData`]: struct.ImageData.html
+//! [`ImageCache`]: struct.ImageCache.html
+//! [`IconAtlas`]: struct.IconAtlas.html
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::widget::image::PietImage;
+use crate::widget::{Image, ImageData, PixelFormat};
+use crate::{Data, Rect};
+
+/// Where a cached image came from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ImageSource {
+    /// A file.
+    Path(PathBuf),
+    /// An address the image was fetched from.
+    Url(String),
+    /// The hash of the encoded image, for images that came from anywhere
+    /// else; see [`ImageSource::hash`].
+    ///
+    /// [`ImageSource::hash`]: #method.hash
+    Hash(u64),
+}
+
+impl ImageSource {
+    /// The source for an image encoded in `bytes`.
+    pub fn hash(bytes: &[u8]) -> Self {
+        let mut hasher = fnv::FnvHasher::default();
+        bytes.hash(&mut hasher);
+        ImageSource::Hash(hasher.finish())
+    }
+}
+
+/// Decoded images, by where they came from, for the whole app.
+///
+/// Widgets showing the same image get clones of one `ImageData`, so that
+/// it is decoded once and kept in memory once. This is a handle: clones of
+/// it are the same cache, and it can be used from any thread, such as one
+/// that loads images ahead of time.
+///
+/// Images stay in the cache until they are [`remove`]d, or [`trim`] finds
+/// that nothing else is using them.
+///
+/// # Examples
+///
+/// ```no_run
+/// use druid::widget::{Image, ImageCache};
+///
+/// let cache = ImageCache::new();
+/// // both widgets draw the same pixels
+/// let first = Image::<()>::new(cache.load("avatar.png").unwrap());
+/// let second = Image::<()>::new(cache.load("avatar.png").unwrap());
+/// ```
+///
+/// [`remove`]: #method.remove
+/// [`trim`]: #method.trim
+#[derive(Clone, Default)]
+pub struct ImageCache {
+    images: Arc<Mutex<HashMap<ImageSource, ImageData>>>,
+}
+
+impl ImageCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        ImageCache::default()
+    }
+
+    /// The image from `source`, if it is in the cache.
+    pub fn get(&self, source: &ImageSource) -> Option<ImageData> {
+        self.images.lock().unwrap().get(source).cloned()
+    }
+
+    /// Keep `image` as the one from `source`, replacing any that was there.
+    pub fn insert(&self, source: ImageSource, image: ImageData) {
+        self.images.lock().unwrap().insert(source, image);
+    }
+
+    /// The image from `source`, making it with `make` if it isn't in the
+    /// cache yet.
+    ///
+    /// The cache isn't locked while `make` runs, so other threads can use
+    /// it in the meantime; if one of them adds the same image first, that
+    /// is the one returned.
+    pub fn get_or_insert_with<E>(
+        &self,
+        source: ImageSource,
+        make: impl FnOnce() -> Result<ImageData, E>,
+    ) -> Result<ImageData, E> {
+        if let Some(image) = self.get(&source) {
+            return Ok(image);
+        }
+        let image = make()?;
+        let mut images = self.images.lock().unwrap();
+        Ok(images.entry(source).or_insert(image).clone())
+    }
+
+    /// Load and decode an image file, unless it is already in the cache.
+    ///
+    /// This is only available with the `image` feature.
+    #[cfg(feature = "image")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+    pub fn load(&self, path: impl Into<PathBuf>) -> Result<ImageData, crate::widget::ImageError> {
+        let path = path.into();
+        let source = ImageSource::Path(path.clone());
+        self.get_or_insert_with(source, || ImageData::from_file(path))
+    }
+
+    /// Decode an image, unless one with the same bytes is already in the
+    /// cache.
+    ///
+    /// This is only available with the `image` feature.
+    #[cfg(feature = "image")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+    pub fn decode(&self, raw: &[u8]) -> Result<ImageData, crate::widget::ImageError> {
+        self.get_or_insert_with(ImageSource::hash(raw), || ImageData::from_data(raw))
+    }
+
+    /// Drop the image from `source`.
+    ///
+    /// Widgets that are showing it keep their clones.
+    pub fn remove(&self, source: &ImageSource) {
+        self.images.lock().unwrap().remove(source);
+    }
+
+    /// Drop the images that only the cache is holding on to.
+    pub fn trim(&self) {
+        self.images
+            .lock()
+            .unwrap()
+            .retain(|_, image| Arc::strong_count(image.pixel_buffer()) > 1);
+    }
+
+    /// How many images are in the cache.
+    pub fn len(&self) -> usize {
+        self.images.lock().unwrap().len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The spacing around each icon in an atlas, which repeats the icon's edge
+/// so that neighbouring icons don't bleed into it when it is scaled.
+const ATLAS_PADDING: usize = 1;
+
+/// Many small images, such as the icons of a toolbar, packed into one.
+///
+/// Drawing them from one image means one image for the render context to
+/// make and keep, rather than one for each icon. The [`Image`] widgets made
+/// by [`icon`] each draw their part of it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use druid::widget::{Flex, IconAtlas, ImageData};
+///
+/// let atlas = IconAtlas::new(vec![
+///     ("cut", ImageData::from_file("cut.png").unwrap()),
+///     ("copy", ImageData::from_file("copy.png").unwrap()),
+///     ("paste", ImageData::from_file("paste.png").unwrap()),
+/// ]);
+/// let toolbar = Flex::<()>::row()
+///     .with_child(atlas.icon(&"cut").unwrap())
+///     .with_child(atlas.icon(&"copy").unwrap())
+///     .with_child(atlas.icon(&"paste").unwrap());
+/// ```
+///
+/// [`Image`]: struct.Image.html
+/// [`icon`]: #method.icon
+pub struct IconAtlas<K> {
+    image: ImageData,
+    icons: HashMap<K, Rect>,
+}
+
+impl<K: Hash + Eq> IconAtlas<K> {
+    /// Pack `icons` into one image.
+    ///
+    /// The icons can have any of the pixel formats; the atlas has
+    /// unpremultiplied alpha.
+    pub fn new(icons: impl IntoIterator<Item = (K, ImageData)>) -> Self {
+        let mut icons: Vec<_> = icons.into_iter().collect();
+        // tallest first, so that the rows waste little space
+        icons.sort_by(|a, b| b.1.size().height.partial_cmp(&a.1.size().height).unwrap());
+
+        let padded = |icon: &ImageData| {
+            let size = icon.size();
+            (
+                size.width as usize + 2 * ATLAS_PADDING,
+                size.height as usize + 2 * ATLAS_PADDING,
+            )
+        };
+        let area: usize = icons
+            .iter()
+            .map(|(_, icon)| padded(icon))
+            .map(|(w, h)| w * h)
+            .sum();
+        let widest = icons
+            .iter()
+            .map(|(_, icon)| padded(icon).0)
+            .max()
+            .unwrap_or(0);
+        let width = widest.max((area as f64).sqrt().ceil() as usize);
+
+        // place the icons in rows, left to right
+        let mut places = Vec::with_capacity(icons.len());
+        let (mut x, mut y, mut row_height) = (0, 0, 0);
+        for (_, icon) in &icons {
+            let (w, h) = padded(icon);
+            if x + w > width {
+                x = 0;
+                y += row_height;
+                row_height = 0;
+            }
+            places.push((x, y));
+            x += w;
+            row_height = row_height.max(h);
+        }
+        let height = y + row_height;
+
+        let mut pixels = vec![0; width * height * 4];
+        let mut rects = HashMap::with_capacity(icons.len());
+        for ((key, icon), (x, y)) in icons.into_iter().zip(places) {
+            copy_padded(&icon, &mut pixels, width, x, y);
+            let size = icon.size();
+            let origin = ((x + ATLAS_PADDING) as f64, (y + ATLAS_PADDING) as f64);
+            rects.insert(key, Rect::from_origin_size(origin, size));
+        }
+        IconAtlas {
+            image: ImageData::from_raw(pixels, width, height, PixelFormat::Rgba),
+            icons: rects,
+        }
+    }
+
+    /// The image that the icons are packed into.
+    pub fn image(&self) -> &ImageData {
+        &self.image
+    }
+
+    /// Where the icon for `key` is in the [`image`], in pixels.
+    ///
+    /// [`image`]: #method.image
+    pub fn rect(&self, key: &K) -> Option<Rect> {
+        self.icons.get(key).copied()
+    }
+
+    /// A widget that draws the icon for `key`, if there is one.
+    pub fn icon<T: Data>(&self, key: &K) -> Option<Image<T>> {
+        let rect = self.rect(key)?;
+        Some(Image::new(self.image.clone()).source_rect(rect))
+    }
+}
+
+/// Copy `icon` into the atlas `pixels`, which are `width` pixels wide, with
+/// its padding starting at `x` and `y`.
+fn copy_padded(icon: &ImageData, pixels: &mut [u8], width: usize, x: usize, y: usize) {
+    let rgba = icon.rgba_pixels();
+    let size = icon.size();
+    let (icon_width, icon_height) = (size.width as usize, size.height as usize);
+    if icon_width == 0 || icon_height == 0 {
+        return;
+    }
+    for row in 0..icon_height + 2 * ATLAS_PADDING {
+        // the rows and columns of padding repeat the nearest of the icon's
+        let from_row = row.saturating_sub(ATLAS_PADDING).min(icon_height - 1);
+        for column in 0..icon_width + 2 * ATLAS_PADDING {
+            let from_column = column.saturating_sub(ATLAS_PADDING).min(icon_width - 1);
+            let from = (from_row * icon_width + from_column) * 4;
+            let to = ((y + row) * width + x + column) * 4;
+            pixels[to..to + 4].copy_from_slice(&rgba[from..from + 4]);
+        }
+    }
+}
+
+/// The images a window has made for its render context, so that clones of
+/// an `ImageData` share one.
+#[derive(Default)]
+pub(crate) struct WindowImages {
+    images: HashMap<*const u8, (Weak<[u8]>, Rc<PietImage>)>,
+}
+
+impl WindowImages {
+    /// The image made from `data`, if there is one.
+    pub(crate) fn get(&self, data: &ImageData) -> Option<Rc<PietImage>> {
+        let pixels = data.pixel_buffer();
+        let (weak, image) = self.images.get(&(pixels.as_ptr()))?;
+        // the pixels the image was made from may have been dropped, and
+        // others put in their place
+        match weak.upgrade() {
+            Some(made_from) if Arc::ptr_eq(&made_from, pixels) => Some(image.clone()),
+            _ => None,
+        }
+    }
+
+    /// Keep the image made from `data`.
+    pub(crate) fn insert(&mut self, data: &ImageData, image: Rc<PietImage>) {
+        // drop the images of pixels that are gone
+        self.images.retain(|_, (weak, _)| weak.strong_count() > 0);
+        let pixels = data.pixel_buffer();
+        self.images
+            .insert(pixels.as_ptr(), (Arc::downgrade(pixels), image));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: usize, height: usize, value: u8) -> ImageData {
+        let pixels = vec![value; width * height];
+        ImageData::from_raw(pixels, width, height, PixelFormat::Grayscale)
+    }
+
+    #[test]
+    fn cached_images_are_shared() {
+        let cache = ImageCache::new();
+        let source = ImageSource::Url("https://example.com/a.png".into());
+        let first = cache
+            .get_or_insert_with(source.clone(), || Ok::<_, ()>(image(2, 2, 0)))
+            .unwrap();
+        let second = cache
+            .get_or_insert_with(source.clone(), || Err(()))
+            .unwrap();
+        assert!(Arc::ptr_eq(first.pixel_buffer(), second.pixel_buffer()));
+
+        let other = ImageSource::hash(b"other");
+        assert_eq!(other, ImageSource::hash(b"other"));
+        cache.insert(other, image(1, 1, 0));
+        assert_eq!(cache.len(), 2);
+
+        // the other image isn't in use
+        cache.trim();
+        assert_eq!(cache.len(), 1);
+        drop((first, second));
+        cache.trim();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn icons_are_packed_with_padding() {
+        let atlas = IconAtlas::new(vec![
+            ("wide", image(6, 2, 10)),
+            ("tall", image(2, 5, 20)),
+            ("small", image(1, 1, 30)),
+        ]);
+        let size = atlas.image().size();
+        let pixels = atlas.image().pixels();
+        let mut rects = Vec::new();
+        for (key, value) in &[("wide", 10), ("tall", 20), ("small", 30)] {
+            let rect = atlas.rect(key).unwrap();
+            // the icon and its padding are all the icon's colour
+            let outer = rect.inflate(ATLAS_PADDING as f64, ATLAS_PADDING as f64);
+            assert!(outer.x0 >= 0.0 && outer.y0 >= 0.0);
+            assert!(outer.x1 <= size.width && outer.y1 <= size.height);
+            for y in outer.y0 as usize..outer.y1 as usize {
+                for x in outer.x0 as usize..outer.x1 as usize {
+                    let at = (y * size.width as usize + x) * 4;
+                    assert_eq!(pixels[at..at + 4], [*value, *value, *value, 255]);
+                }
+            }
+            for other in &rects {
+                assert!(outer.intersect(*other).area() == 0.0, "{} overlaps", key);
+            }
+            rects.push(outer);
+        }
+        assert!(atlas.rect(&"missing").is_none());
+    }
+}
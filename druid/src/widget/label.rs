@@ -15,10 +15,8 @@
 //! A label widget.
 
 use crate::kurbo::{Point, Rect, Size};
-use crate::piet::{
-    FontBuilder, PietText, PietTextLayout, RenderContext, Text, TextLayout, TextLayoutBuilder,
-    UnitPoint,
-};
+use crate::piet::{PietText, RenderContext, UnitPoint};
+use crate::text::TextLine;
 use crate::theme;
 use crate::{
     BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
@@ -73,14 +71,24 @@ impl<T: Data> Label<T> {
         self
     }
 
-    fn get_layout(&mut self, t: &mut PietText, env: &Env, data: &T) -> PietTextLayout {
+    /// How far down a label of `height` the baseline of its text is.
+    fn baseline(&self, height: f64, font_size: f64) -> f64 {
+        let space = Size::new(0.0, height + (font_size * 1.2) / 2.);
+        let y = self
+            .align
+            .resolve(Rect::from_origin_size(Point::ORIGIN, space))
+            .y;
+        //Make sure we don't draw the text too low
+        y.min(height)
+    }
+
+    fn get_layout(&mut self, t: &mut PietText, env: &Env, data: &T) -> TextLine {
         let font_name = env.get(theme::FONT_NAME);
         let font_size = env.get(theme::TEXT_SIZE_NORMAL);
 
         // TODO: caching of both the format and the layout
-        let font = t.new_font_by_name(font_name, font_size).build().unwrap();
         self.text.with_display_text(data, env, |text| {
-            t.new_text_layout(&font, &text).build().unwrap()
+            TextLine::new(t, font_name, font_size, text, env)
         })
     }
 }
@@ -115,6 +123,7 @@ impl<T: Data> Widget<T> for Label<T> {
 
     fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
         if self.text.resolve(data, env) {
+            ctx.request_layout();
             ctx.request_paint();
         }
     }
@@ -131,7 +140,9 @@ impl<T: Data> Widget<T> for Label<T> {
         let font_size = env.get(theme::TEXT_SIZE_NORMAL);
         let text_layout = self.get_layout(layout_ctx.text(), env, data);
         // This magical 1.2 constant helps center the text vertically in the rect it's given
-        bc.constrain(Size::new(text_layout.width(), font_size * 1.2))
+        let size = bc.constrain(Size::new(text_layout.width(), font_size * 1.2));
+        layout_ctx.set_baseline_offset(size.height - self.baseline(size.height, font_size));
+        size
     }
 
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
@@ -139,18 +150,19 @@ impl<T: Data> Widget<T> for Label<T> {
         let text_layout = self.get_layout(paint_ctx.text(), env, data);
 
         // Find the origin for the text
+        let extra_width = (paint_ctx.size().width - text_layout.width()).max(0.0);
         let mut origin = self.align.resolve(Rect::from_origin_size(
             Point::ORIGIN,
-            Size::new(
-                (paint_ctx.size().width - text_layout.width()).max(0.0),
-                paint_ctx.size().height + (font_size * 1.2) / 2.,
-            ),
+            Size::new(extra_width, 0.0),
         ));
+        origin.y = self.baseline(paint_ctx.size().height, font_size);
 
-        //Make sure we don't draw the text too low
-        origin.y = origin.y.min(paint_ctx.size().height);
+        // Mirror the alignment in a right-to-left layout
+        if env.get(Env::RIGHT_TO_LEFT) {
+            origin.x = extra_width - origin.x;
+        }
 
-        paint_ctx.draw_text(&text_layout, origin, &env.get(theme::LABEL_COLOR));
+        paint_ctx.draw_text(text_layout.layout(), origin, &env.get(theme::LABEL_COLOR));
     }
 }
 
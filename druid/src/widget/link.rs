@@ -0,0 +1,205 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A hyperlink.
+
+use crate::kurbo::{Line, Point, Rect, Size};
+use crate::text::TextLine;
+use crate::theme;
+use crate::widget::LabelText;
+use crate::{
+    commands, BoxConstraints, Command, Data, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle,
+    LifeCycleCtx, PaintCtx, RenderContext, UpdateCtx, Widget,
+};
+
+/// A piece of text that opens a URL, or submits some other command, when it
+/// is clicked, or when Return is pressed while it has focus.
+///
+/// The text is drawn in [`theme::LINK_COLOR`], and is underlined in
+/// [`theme::LINK_HOVER_COLOR`] while the mouse is over it. Once it has been
+/// followed it is drawn in [`theme::LINK_VISITED_COLOR`].
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::Link;
+/// use druid::commands;
+///
+/// // opens the page in the browser
+/// let homepage = Link::<()>::new("Homepage", "https://example.com");
+///
+/// // lets the app handle it
+/// let settings = Link::<()>::with_command("Preferences", commands::SHOW_PREFERENCES);
+/// ```
+///
+/// [`theme::LINK_COLOR`]: ../theme/constant.LINK_COLOR.html
+/// [`theme::LINK_HOVER_COLOR`]: ../theme/constant.LINK_HOVER_COLOR.html
+/// [`theme::LINK_VISITED_COLOR`]: ../theme/constant.LINK_VISITED_COLOR.html
+pub struct Link<T> {
+    text: LabelText<T>,
+    target: Target,
+    visited: bool,
+    /// The text, from the last layout.
+    line: Option<TextLine>,
+}
+
+enum Target {
+    Url(String),
+    Command(Command),
+}
+
+impl<T: Data> Link<T> {
+    /// Create a link that opens `url` in the default browser, by submitting
+    /// [`commands::OPEN_URL`] to the window.
+    ///
+    /// [`commands::OPEN_URL`]: ../commands/constant.OPEN_URL.html
+    pub fn new(text: impl Into<LabelText<T>>, url: impl Into<String>) -> Self {
+        Link::with_target(text.into(), Target::Url(url.into()))
+    }
+
+    /// Create a link that submits `command` to the window.
+    pub fn with_command(text: impl Into<LabelText<T>>, command: impl Into<Command>) -> Self {
+        Link::with_target(text.into(), Target::Command(command.into()))
+    }
+
+    fn with_target(text: LabelText<T>, target: Target) -> Self {
+        Link {
+            text,
+            target,
+            visited: false,
+            line: None,
+        }
+    }
+
+    /// The command that following the link submits.
+    fn command(&self) -> Command {
+        match &self.target {
+            Target::Url(url) => Command::new(commands::OPEN_URL, url.clone()),
+            Target::Command(command) => command.clone(),
+        }
+    }
+
+    fn follow(&mut self, ctx: &mut EventCtx) {
+        ctx.submit_command(self.command(), None);
+        self.visited = true;
+        ctx.request_paint();
+    }
+}
+
+impl<T: Data> Widget<T> for Link<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut T, _env: &Env) {
+        match event {
+            Event::MouseDown(_) => {
+                ctx.set_active(true);
+                ctx.request_focus();
+                ctx.request_paint();
+            }
+            Event::MouseUp(_) if ctx.is_active() => {
+                ctx.set_active(false);
+                ctx.request_paint();
+                if ctx.is_hot() {
+                    self.follow(ctx);
+                }
+            }
+            Event::KeyDown(key) if key.key_code == KeyCode::Return => {
+                ctx.set_handled();
+                self.follow(ctx);
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => {
+                self.text.resolve(data, env);
+                ctx.register_for_focus();
+            }
+            LifeCycle::HotChanged(_) | LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        if self.text.resolve(data, env) || matches!(self.text, LabelText::Dynamic(_)) {
+            ctx.request_layout();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Link");
+        let font_name = env.get(theme::FONT_NAME);
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let text = layout_ctx.text();
+        let line = self.text.with_display_text(data, env, |display| {
+            TextLine::new(text, font_name, font_size, display, env)
+        });
+        let width = line.width();
+        self.line = Some(line);
+        bc.constrain(Size::new(width, font_size * 1.2))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _data: &T, env: &Env) {
+        let line = match &self.line {
+            Some(line) => line,
+            None => return,
+        };
+        let hot = paint_ctx.is_hot();
+        let color = if hot {
+            env.get(theme::LINK_HOVER_COLOR)
+        } else if self.visited {
+            env.get(theme::LINK_VISITED_COLOR)
+        } else {
+            env.get(theme::LINK_COLOR)
+        };
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let size = paint_ctx.size();
+        // the same baseline as a label's
+        let baseline = (size.height + font_size * 0.6) / 2.0;
+        paint_ctx.draw_text(line.layout(), Point::new(0.0, baseline), &color);
+
+        if hot {
+            let y = baseline + 2.5;
+            let underline = Line::new((0.0, y), (line.width(), y));
+            paint_ctx.stroke(underline, &color, 1.0);
+        }
+        let rect = Rect::from_origin_size(Point::ORIGIN, size).inflate(2.0, 0.0);
+        paint_ctx.paint_focus_ring(rect, env);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_submit_their_command() {
+        let link = Link::<()>::new("Homepage", "https://example.com");
+        let command = link.command();
+        assert_eq!(command.selector, commands::OPEN_URL);
+        assert_eq!(
+            command.get_object::<String>().unwrap(),
+            "https://example.com"
+        );
+
+        let link = Link::<()>::with_command("Preferences", commands::SHOW_PREFERENCES);
+        assert_eq!(link.command().selector, commands::SHOW_PREFERENCES);
+    }
+}
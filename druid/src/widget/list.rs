@@ -17,7 +17,7 @@
 use std::cmp::Ordering;
 use std::sync::Arc;
 
-use crate::kurbo::{Point, Rect, Size};
+use crate::kurbo::{Point, Rect, Shape, Size};
 
 use crate::{
     BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
@@ -25,9 +25,41 @@ use crate::{
 };
 
 /// A list widget for a variable-size collection of items.
+///
+/// The items can be grouped into sections with [`with_sections`], each with
+/// a header that sticks to the top of the [`Scroll`] the list is in.
+///
+/// Items can also be highlighted as selected, with [`with_selection`].
+///
+/// [`with_sections`]: #method.with_sections
+/// [`with_selection`]: #method.with_selection
+/// [`Scroll`]: struct.Scroll.html
 pub struct List<T> {
     closure: Box<dyn Fn() -> Box<dyn Widget<T>>>,
     children: Vec<WidgetPod<T, Box<dyn Widget<T>>>>,
+    sections: Option<Sections<T>>,
+    is_selected: Option<IsSelected<T>>,
+}
+
+/// Whether two items are in the same section.
+type SameSection<T> = Box<dyn Fn(&T, &T) -> bool>;
+
+/// Whether an item is selected.
+type IsSelected<T> = Box<dyn Fn(&T) -> bool>;
+
+/// The section headers of a [`List`].
+///
+/// [`List`]: struct.List.html
+struct Sections<T> {
+    same_section: SameSection<T>,
+    closure: Box<dyn Fn() -> Box<dyn Widget<T>>>,
+    /// The index of the first item of each section.
+    starts: Vec<usize>,
+    headers: Vec<WidgetPod<T, Box<dyn Widget<T>>>>,
+    /// Where each header is in the list, before it sticks.
+    rects: Vec<Rect>,
+    /// The header that is stuck to the top of the viewport.
+    stuck: Option<usize>,
 }
 
 impl<T: Data> List<T> {
@@ -37,9 +69,63 @@ impl<T: Data> List<T> {
         List {
             closure: Box::new(move || Box::new(closure())),
             children: Vec::new(),
+            sections: None,
+            is_selected: None,
         }
     }
 
+    /// Highlight the items that `is_selected` returns `true` for, behind
+    /// the item, with [`PaintCtx::paint_selection`].
+    ///
+    /// The selection is part of the items' data; the list only shows it.
+    ///
+    /// [`PaintCtx::paint_selection`]: ../struct.PaintCtx.html#method.paint_selection
+    pub fn with_selection(mut self, is_selected: impl Fn(&T) -> bool + 'static) -> Self {
+        self.is_selected = Some(Box::new(is_selected));
+        self
+    }
+
+    /// Group the items into sections, such as contacts by their first letter.
+    ///
+    /// Consecutive items with the same `key` are in the same section, and
+    /// `header` is called to make a header for each section, which is shown
+    /// above its first item and gets that item as its data. While a section
+    /// is scrolled past, its header sticks to the top of the viewport, until
+    /// the next header pushes it out. Headers are as wide as the list can be
+    /// and are drawn over the items, so they should have a background.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use druid::widget::{Label, List, WidgetExt};
+    /// use druid::Color;
+    ///
+    /// let contacts = List::new(|| Label::new(|name: &String, _: &_| name.clone()))
+    ///     .with_sections(
+    ///         |name: &String| name.chars().next(),
+    ///         || {
+    ///             Label::new(|name: &String, _: &_| name.chars().take(1).collect::<String>().to_uppercase())
+    ///                 .padding(4.0)
+    ///                 .background(Color::grey8(0x30))
+    ///         },
+    ///     );
+    /// ```
+    pub fn with_sections<K: PartialEq, W: Widget<T> + 'static>(
+        mut self,
+        key: impl Fn(&T) -> K + 'static,
+        header: impl Fn() -> W + 'static,
+    ) -> Self {
+        self.sections = Some(Sections {
+            same_section: Box::new(move |a, b| key(a) == key(b)),
+            closure: Box::new(move || Box::new(header())),
+            starts: Vec::new(),
+            headers: Vec::new(),
+            rects: Vec::new(),
+            stuck: None,
+        });
+        self
+    }
+
     /// When the widget is created or the data changes, create or remove children as needed
     ///
     /// Returns `true` if children were added or removed.
@@ -55,7 +141,86 @@ impl<T: Data> List<T> {
             }),
             Ordering::Equal => (),
         }
-        len != data.data_len()
+        let headers_changed = match &mut self.sections {
+            Some(sections) => sections.update(data),
+            None => false,
+        };
+        len != data.data_len() || headers_changed
+    }
+
+    /// The rectangle of the stuck header, which the items beneath it
+    /// shouldn't get the mouse in.
+    fn stuck_rect(&self) -> Option<Rect> {
+        let sections = self.sections.as_ref()?;
+        let stuck = sections.stuck?;
+        Some(sections.headers[stuck].layout_rect())
+    }
+}
+
+impl<T: Data> Sections<T> {
+    /// Find where the sections start, and create or remove headers as needed.
+    ///
+    /// Returns `true` if headers were added or removed.
+    fn update(&mut self, data: &impl ListIter<T>) -> bool {
+        let mut starts = Vec::new();
+        let mut previous: Option<T> = None;
+        let same_section = &self.same_section;
+        data.for_each(|item, i| {
+            let starts_section = match &previous {
+                Some(previous) => !same_section(previous, item),
+                None => true,
+            };
+            if starts_section {
+                starts.push(i);
+            }
+            previous = Some(item.clone());
+        });
+        let count = starts.len();
+        self.starts = starts;
+
+        let len = self.headers.len();
+        let closure = &self.closure;
+        self.headers
+            .resize_with(count, || WidgetPod::new(closure()));
+        self.rects.resize(count, Rect::ZERO);
+        self.stuck = self.stuck.filter(|&stuck| stuck < count);
+        len != count
+    }
+
+    /// Move the header of the section at `top` to it, keeping it inside
+    /// its section, in a list of `height`.
+    fn stick(&mut self, top: f64, height: f64) {
+        if let Some(stuck) = self.stuck.take() {
+            self.headers[stuck].set_layout_rect(self.rects[stuck]);
+        }
+        for (i, rect) in self.rects.iter().enumerate() {
+            let end = self.rects.get(i + 1).map_or(height, |next| next.y0);
+            if rect.y0 < top && top < end {
+                let y = top.min(end - rect.height());
+                self.headers[i].set_layout_rect(rect.with_origin(Point::new(rect.x0, y)));
+                self.stuck = Some(i);
+                break;
+            }
+        }
+    }
+}
+
+/// The event for the items under a header stuck `over` them: the mouse is
+/// moved away from them, and they don't get clicks. `None` if the event
+/// shouldn't be sent.
+fn event_under(over: Option<Rect>, event: &Event) -> Option<Event> {
+    match (over, event) {
+        (Some(over), Event::MouseMoved(mouse)) if over.winding(mouse.pos) != 0 => {
+            let mut mouse = mouse.clone();
+            mouse.pos = Point::new(-1.0, -1.0);
+            Some(Event::MouseMoved(mouse))
+        }
+        (Some(over), Event::MouseDown(mouse)) | (Some(over), Event::MouseUp(mouse))
+            if over.winding(mouse.pos) != 0 =>
+        {
+            None
+        }
+        _ => Some(event.clone()),
     }
 }
 
@@ -144,10 +309,35 @@ impl<T1: Data, T: Data> ListIter<(T1, T)> for (T1, Arc<Vec<T>>) {
 
 impl<C: Data, T: ListIter<C>> Widget<T> for List<C> {
     fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        let stuck_rect = self.stuck_rect();
+        let covered_event = event_under(stuck_rect, event);
+        let (starts, stuck, headers) = match &mut self.sections {
+            Some(sections) => (
+                &sections.starts[..],
+                sections.stuck,
+                &mut sections.headers[..],
+            ),
+            None => (&[][..], None, Default::default()),
+        };
+        let mut headers = starts.iter().zip(headers.iter_mut()).enumerate().peekable();
         let mut children = self.children.iter_mut();
-        data.for_each_mut(|child_data, _| {
+        data.for_each_mut(|child_data, i| {
+            let covered_event = covered_event.as_ref();
+            if let Some((header_index, (_, header))) =
+                headers.next_if(|(_, (start, _))| **start == i)
+            {
+                if Some(header_index) == stuck || header.has_active() {
+                    header.event(ctx, event, child_data, env);
+                } else if let Some(event) = covered_event {
+                    header.event(ctx, event, child_data, env);
+                }
+            }
             if let Some(child) = children.next() {
-                child.event(ctx, event, child_data, env);
+                if child.has_active() {
+                    child.event(ctx, event, child_data, env);
+                } else if let Some(event) = covered_event {
+                    child.event(ctx, event, child_data, env);
+                }
             }
         });
     }
@@ -159,8 +349,16 @@ impl<C: Data, T: ListIter<C>> Widget<T> for List<C> {
             }
         }
 
+        let (starts, headers) = match &mut self.sections {
+            Some(sections) => (&sections.starts[..], &mut sections.headers[..]),
+            None => (&[][..], Default::default()),
+        };
+        let mut headers = starts.iter().zip(headers.iter_mut()).peekable();
         let mut children = self.children.iter_mut();
-        data.for_each(|child_data, _| {
+        data.for_each(|child_data, i| {
+            if let Some((_, header)) = headers.next_if(|(start, _)| **start == i) {
+                header.lifecycle(ctx, event, child_data, env);
+            }
             if let Some(child) = children.next() {
                 child.lifecycle(ctx, event, child_data, env);
             }
@@ -171,8 +369,16 @@ impl<C: Data, T: ListIter<C>> Widget<T> for List<C> {
         // we send update to children first, before adding or removing children;
         // this way we avoid sending update to newly added children, at the cost
         // of potentially updating children that are going to be removed.
+        let (starts, headers) = match &mut self.sections {
+            Some(sections) => (&sections.starts[..], &mut sections.headers[..]),
+            None => (&[][..], Default::default()),
+        };
+        let mut headers = starts.iter().zip(headers.iter_mut()).peekable();
         let mut children = self.children.iter_mut();
-        data.for_each(|child_data, _| {
+        data.for_each(|child_data, i| {
+            if let Some((_, header)) = headers.next_if(|(start, _)| **start == i) {
+                header.update(ctx, child_data, env);
+            }
             if let Some(child) = children.next() {
                 child.update(ctx, child_data, env);
             }
@@ -181,6 +387,10 @@ impl<C: Data, T: ListIter<C>> Widget<T> for List<C> {
         if self.update_child_count(data, env) {
             ctx.children_changed();
         }
+        // an item's child might not repaint when only its selection changes
+        if self.is_selected.is_some() {
+            ctx.request_paint();
+        }
     }
 
     fn layout(
@@ -194,18 +404,50 @@ impl<C: Data, T: ListIter<C>> Widget<T> for List<C> {
         let mut y = 0.0;
 
         let mut paint_rect = Rect::ZERO;
+        let child_bc = BoxConstraints::new(
+            Size::new(bc.min().width, 0.0),
+            Size::new(bc.max().width, f64::INFINITY),
+        );
+        let header_width = if bc.max().width.is_finite() {
+            bc.max().width
+        } else {
+            bc.min().width
+        };
+        let header_bc = BoxConstraints::new(
+            Size::new(header_width, 0.0),
+            Size::new(bc.max().width, f64::INFINITY),
+        );
+        let (starts, headers, rects) = match &mut self.sections {
+            Some(sections) => {
+                sections.stuck = None;
+                (
+                    &sections.starts[..],
+                    &mut sections.headers[..],
+                    &mut sections.rects[..],
+                )
+            }
+            None => (&[][..], Default::default(), Default::default()),
+        };
+        let mut headers = starts
+            .iter()
+            .zip(headers.iter_mut().zip(rects.iter_mut()))
+            .peekable();
         let mut children = self.children.iter_mut();
-        data.for_each(|child_data, _| {
+        data.for_each(|child_data, i| {
+            if let Some((_, (header, header_rect))) = headers.next_if(|(start, _)| **start == i) {
+                let header_size = header.layout(layout_ctx, &header_bc, child_data, env);
+                *header_rect = Rect::from_origin_size(Point::new(0.0, y), header_size);
+                header.set_layout_rect(*header_rect);
+                paint_rect = paint_rect.union(header.paint_rect());
+                width = width.max(header_size.width);
+                y += header_size.height;
+            }
             let child = match children.next() {
                 Some(child) => child,
                 None => {
                     return;
                 }
             };
-            let child_bc = BoxConstraints::new(
-                Size::new(bc.min().width, 0.0),
-                Size::new(bc.max().width, std::f64::INFINITY),
-            );
             let child_size = child.layout(layout_ctx, &child_bc, child_data, env);
             let rect = Rect::from_origin_size(Point::new(0.0, y), child_size);
             child.set_layout_rect(rect);
@@ -221,11 +463,97 @@ impl<C: Data, T: ListIter<C>> Widget<T> for List<C> {
     }
 
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        let top = paint_ctx.region().to_rect().y0;
+        let height = paint_ctx.size().height;
+        let (starts, stuck, headers) = match &mut self.sections {
+            Some(sections) => {
+                sections.stick(top, height);
+                (
+                    &sections.starts[..],
+                    sections.stuck,
+                    &mut sections.headers[..],
+                )
+            }
+            None => (&[][..], None, Default::default()),
+        };
+        let mut stuck_header = None;
+        let mut headers = starts.iter().zip(headers.iter_mut()).enumerate().peekable();
         let mut children = self.children.iter_mut();
-        data.for_each(|child_data, _| {
+        let is_selected = self.is_selected.as_ref();
+        data.for_each(|child_data, i| {
+            if let Some((header_index, (_, header))) =
+                headers.next_if(|(_, (start, _))| **start == i)
+            {
+                if Some(header_index) == stuck {
+                    stuck_header = Some((header, child_data.clone()));
+                } else {
+                    header.paint_with_offset(paint_ctx, child_data, env);
+                }
+            }
             if let Some(child) = children.next() {
+                if is_selected.map(|f| f(child_data)).unwrap_or(false) {
+                    paint_ctx.paint_selection(child.layout_rect(), env);
+                }
                 child.paint_with_offset(paint_ctx, child_data, env);
             }
         });
+        // over the items it is stuck to
+        if let Some((header, header_data)) = stuck_header {
+            header.paint_with_offset(paint_ctx, &header_data, env);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Label;
+
+    fn contacts() -> List<String> {
+        List::new(|| Label::new("contact"))
+            .with_sections(|name: &String| name.chars().next(), || Label::new("letter"))
+    }
+
+    #[test]
+    fn sections_start_where_the_key_changes() {
+        let mut list = contacts();
+        let names: Arc<Vec<String>> = Arc::new(
+            ["Ada", "Alan", "Barbara", "Carl", "Chris"]
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        );
+        assert!(list.update_child_count(&names, &Env::default()));
+        let sections = list.sections.as_ref().unwrap();
+        assert_eq!(sections.starts, vec![0, 2, 3]);
+        assert_eq!(sections.headers.len(), 3);
+    }
+
+    #[test]
+    fn headers_stick_until_the_next_one_pushes_them() {
+        let mut list = contacts();
+        let sections = list.sections.as_mut().unwrap();
+        sections.headers = (0..2)
+            .map(|_| WidgetPod::new((sections.closure)()))
+            .collect();
+        sections.rects = vec![
+            Rect::new(0.0, 0.0, 100.0, 20.0),
+            Rect::new(0.0, 200.0, 100.0, 220.0),
+        ];
+        let header_y = |sections: &Sections<String>, i: usize| sections.headers[i].layout_rect().y0;
+
+        sections.stick(-10.0, 400.0);
+        assert_eq!(sections.stuck, None);
+        sections.stick(50.0, 400.0);
+        assert_eq!(sections.stuck, Some(0));
+        assert_eq!(header_y(sections, 0), 50.0);
+        // the second header is 10 below the top, pushing the first up
+        sections.stick(190.0, 400.0);
+        assert_eq!(header_y(sections, 0), 180.0);
+        // and at the end of the list, the last one stays inside it
+        sections.stick(390.0, 400.0);
+        assert_eq!(sections.stuck, Some(1));
+        assert_eq!(header_y(sections, 0), 0.0);
+        assert_eq!(header_y(sections, 1), 380.0);
     }
 }
@@ -0,0 +1,220 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that shows frames sent from another thread.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+
+use crate::piet::InterpolationMode;
+use crate::widget::{FillStrat, Image, ImageData};
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, ExtEventError, ExtEventSink, LayoutCtx, LifeCycle,
+    LifeCycleCtx, PaintCtx, Selector, Size, UpdateCtx, Widget, WidgetId,
+};
+
+/// Tells a `LiveImage` that there are frames waiting for it.
+const NEW_FRAMES: Selector = Selector::new("druid-builtin.live-image-new-frames");
+
+/// A widget that shows the latest of the frames sent to it through a
+/// [`FrameSender`], such as those of a webcam or a video decoder.
+///
+/// The frames are sent from any thread, and the widget is woken through the
+/// app's [`ExtEventSink`]. If frames arrive faster than they can be drawn,
+/// the ones in between are skipped. Each frame is turned into an image for
+/// the render context once, however often it is painted.
+///
+/// The widget has to be given the id that was passed to [`new`], with
+/// [`WidgetExt::with_id`], so that the frames can find it.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// use druid::widget::{ImageData, LiveImage, PixelFormat, WidgetExt};
+/// use druid::{ExtEventSink, Widget, WidgetId};
+///
+/// fn build_ui(sink: ExtEventSink) -> impl Widget<()> {
+///     let id = WidgetId::next();
+///     let (image, frames) = LiveImage::new(sink, id);
+///     thread::spawn(move || loop {
+///         // the next frame from the camera
+///         let pixels = vec![128; 320 * 240 * 3];
+///         let frame = ImageData::from_raw(pixels, 320, 240, PixelFormat::Rgb);
+///         if frames.send(frame).is_err() {
+///             break;
+///         }
+///     });
+///     image.with_id(id)
+/// }
+/// ```
+///
+/// [`FrameSender`]: struct.FrameSender.html
+/// [`ExtEventSink`]: ../struct.ExtEventSink.html
+/// [`new`]: #method.new
+/// [`WidgetExt::with_id`]: trait.WidgetExt.html#method.with_id
+pub struct LiveImage<T> {
+    frames: Receiver<ImageData>,
+    waiting: Arc<AtomicBool>,
+    image: Image<T>,
+}
+
+/// Sends frames to a [`LiveImage`].
+///
+/// This can be cloned, and moved to other threads.
+///
+/// [`LiveImage`]: struct.LiveImage.html
+#[derive(Clone)]
+pub struct FrameSender {
+    frames: Sender<ImageData>,
+    /// Whether the widget has been woken, and hasn't taken the frames yet.
+    waiting: Arc<AtomicBool>,
+    sink: ExtEventSink,
+    target: WidgetId,
+}
+
+impl<T: Data> LiveImage<T> {
+    /// Create the widget, and the sender of its frames.
+    ///
+    /// `sink` comes from [`AppLauncher::get_external_handle`], and `id` is
+    /// the id the widget will be given.
+    ///
+    /// [`AppLauncher::get_external_handle`]: ../struct.AppLauncher.html#method.get_external_handle
+    pub fn new(sink: ExtEventSink, id: WidgetId) -> (Self, FrameSender) {
+        let (sender, receiver) = channel();
+        let waiting = Arc::new(AtomicBool::new(false));
+        let image = LiveImage {
+            frames: receiver,
+            waiting: waiting.clone(),
+            image: Image::new(ImageData::empty()),
+        };
+        let sender = FrameSender {
+            frames: sender,
+            waiting,
+            sink,
+            target: id,
+        };
+        (image, sender)
+    }
+
+    /// Builder-style method for setting how the frames fill the widget.
+    pub fn fill_mode(mut self, fill: FillStrat) -> Self {
+        self.image = self.image.fill_mode(fill);
+        self
+    }
+
+    /// Builder-style method for setting how the frames are sampled when they
+    /// are scaled.
+    pub fn interpolation_mode(mut self, interpolation: InterpolationMode) -> Self {
+        self.image = self.image.interpolation_mode(interpolation);
+        self
+    }
+
+    /// The newest of the frames that have arrived, if any.
+    fn take_latest(&mut self) -> Option<ImageData> {
+        // frames sent from now on need another wakeup
+        self.waiting.store(false, Ordering::SeqCst);
+        self.frames.try_iter().last()
+    }
+}
+
+impl FrameSender {
+    /// Send the next frame to be shown.
+    ///
+    /// Returns an error if the widget or the app has gone away.
+    pub fn send(&self, frame: ImageData) -> Result<(), ExtEventError> {
+        self.frames.send(frame).map_err(|_| ExtEventError)?;
+        if !self.waiting.swap(true, Ordering::SeqCst) {
+            self.sink
+                .submit_command::<()>(NEW_FRAMES, None, self.target)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Data> Widget<T> for LiveImage<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut T, _env: &Env) {
+        if let Event::Command(cmd) = event {
+            if cmd.selector == NEW_FRAMES {
+                if let Some(frame) = self.take_latest() {
+                    if frame.size() != self.image.image_data().size() {
+                        ctx.request_layout();
+                    }
+                    self.image.set_image_data(frame);
+                    ctx.request_paint();
+                }
+                ctx.set_handled();
+            }
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.image.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        self.image.update(ctx, old_data, data, env)
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        self.image.layout(ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.image.paint(ctx, data, env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ext_event::ExtEventHost;
+    use crate::widget::PixelFormat;
+    use crate::Target;
+
+    fn frame(width: usize) -> ImageData {
+        ImageData::from_raw(vec![0; width], width, 1, PixelFormat::Grayscale)
+    }
+
+    #[test]
+    fn frames_wake_the_widget_once() {
+        let mut host = ExtEventHost::new();
+        let id = WidgetId::next();
+        let (mut live, frames) = LiveImage::<()>::new(host.make_sink(), id);
+        for width in 1..=3 {
+            frames.send(frame(width)).unwrap();
+        }
+        let batch = host.take_batch();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].0, Some(Target::Widget(id)));
+        assert_eq!(batch[0].1.selector, NEW_FRAMES);
+
+        // only the newest frame is shown
+        assert_eq!(live.take_latest().unwrap().size(), Size::new(3.0, 1.0));
+        assert!(live.take_latest().is_none());
+
+        frames.send(frame(4)).unwrap();
+        assert_eq!(host.take_batch().len(), 1);
+    }
+
+    #[test]
+    fn sending_fails_once_the_widget_is_gone() {
+        let host = ExtEventHost::new();
+        let (live, frames) = LiveImage::<()>::new(host.make_sink(), WidgetId::next());
+        drop(live);
+        assert!(frames.send(frame(1)).is_err());
+    }
+}
@@ -0,0 +1,907 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rendering Markdown.
+
+use pulldown_cmark::{Event as MdEvent, Options, Parser, Tag};
+
+use crate::kurbo::{Affine, Line, Point, Rect, RoundedRect, Size};
+use crate::piet::InterpolationMode;
+use crate::text::TextLine;
+use crate::theme;
+use crate::widget::ImageData;
+use crate::{
+    commands, BoxConstraints, Command, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, RenderContext, UpdateCtx, Widget,
+};
+
+/// The sizes of the first three levels of headings, relative to the normal
+/// text size; the rest are the normal size.
+const HEADING_SCALES: [f64; 3] = [2.0, 1.5, 1.25];
+
+/// The size of code, relative to the text around it.
+const CODE_SCALE: f64 = 0.9;
+
+/// The distance between lines, relative to the text size.
+const LINE_SPACING: f64 = 1.35;
+
+/// The space between blocks, and the indent of each level of lists and
+/// quotes, relative to the normal text size.
+const BLOCK_SPACING: f64 = 0.6;
+const INDENT: f64 = 1.5;
+
+/// The space around the text of a code block.
+const CODE_PADDING: f64 = 6.0;
+
+/// How far emphasized text leans.
+const EMPHASIS_SLANT: f64 = 0.2;
+
+/// The width of the bar beside a quote.
+const QUOTE_BAR_WIDTH: f64 = 3.0;
+
+/// The width to lay out for, when there is no limit.
+const DEFAULT_WIDTH: f64 = 400.0;
+
+type ImageLookup = dyn Fn(&str) -> Option<ImageData>;
+
+/// A widget that shows [CommonMark] text, such as the pages of a help pane
+/// or the messages of a chat.
+///
+/// Headings, emphasis, lists, quotes, code and rules are shown as they
+/// would be on a web page, and the text wraps to the width of the widget.
+/// Clicking a link submits [`commands::OPEN_URL`] with its URL to the
+/// window, unless the widget was given some other command to submit with
+/// [`on_link`]. Images are shown if the function given to [`images`] has
+/// one for their URL, and as their alt text otherwise.
+///
+/// This is only available with the `markdown` feature.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Markdown, Scroll};
+/// use druid::Widget;
+///
+/// fn help_pane() -> impl Widget<String> {
+///     Scroll::new(Markdown::new()).vertical()
+/// }
+///
+/// let page = "# Shortcuts\n\nPress **Ctrl+S** to save. See the [manual](https://example.com).";
+/// ```
+///
+/// [CommonMark]: https://commonmark.org
+/// [`commands::OPEN_URL`]: ../commands/constant.OPEN_URL.html
+/// [`on_link`]: #method.on_link
+/// [`images`]: #method.images
+pub struct Markdown {
+    on_link: Box<dyn Fn(&str) -> Command>,
+    images: Box<ImageLookup>,
+    /// The parsed text, and its images.
+    blocks: Vec<Block>,
+    image_data: Vec<Option<ImageData>>,
+    /// Where everything goes, from the last layout.
+    words: Vec<Word>,
+    shapes: Vec<Shape>,
+    placed_images: Vec<(ImageData, Rect)>,
+    links: Vec<String>,
+    /// The link the mouse is over, and the one it was pressed on.
+    hot_link: Option<usize>,
+    pressed_link: Option<usize>,
+}
+
+/// A block of the document, and how deep in lists and quotes it is.
+#[derive(Debug, Clone, PartialEq)]
+struct Block {
+    content: Content,
+    indent: usize,
+    quote: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Content {
+    /// A paragraph, heading or list item.
+    Text {
+        heading: Option<u32>,
+        marker: Option<String>,
+        spans: Vec<Span>,
+    },
+    Code(String),
+    Image {
+        url: String,
+        alt: String,
+    },
+    Rule,
+}
+
+/// A run of text in the same style.
+#[derive(Debug, Clone, PartialEq)]
+struct Span {
+    text: String,
+    style: Style,
+    link: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Style {
+    strong: bool,
+    emphasis: bool,
+    code: bool,
+    strikethrough: bool,
+}
+
+/// A word, or other piece of text, laid out.
+struct Word {
+    line: TextLine,
+    /// The start of the baseline.
+    origin: Point,
+    size: f64,
+    style: Style,
+    link: Option<usize>,
+    /// Alt text standing in for an image.
+    faded: bool,
+    /// The code block the word is in, which it is clipped to.
+    clip: Option<Rect>,
+}
+
+enum Shape {
+    CodeBlock(Rect),
+    QuoteBar(Rect),
+    Rule(Line),
+}
+
+/// A word of a paragraph, for wrapping.
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    text: String,
+    span: usize,
+    /// Whether there is a space between this word and the next.
+    space_after: bool,
+    /// Whether this word starts a new line.
+    break_before: bool,
+}
+
+/// Builds the blocks of a document from the events of the parser.
+#[derive(Default)]
+struct Builder {
+    blocks: Vec<Block>,
+    spans: Vec<Span>,
+    heading: Option<u32>,
+    marker: Option<String>,
+    /// The number of the next item of each list, or `None` for bullets.
+    lists: Vec<Option<u64>>,
+    quote: usize,
+    strong: usize,
+    emphasis: usize,
+    strikethrough: usize,
+    link: Option<String>,
+    code: Option<String>,
+    image: Option<(String, String)>,
+}
+
+impl Builder {
+    fn event(&mut self, event: MdEvent) {
+        match event {
+            MdEvent::Start(tag) => self.start(tag),
+            MdEvent::End(tag) => self.end(tag),
+            MdEvent::Text(text) => match &mut self.code {
+                Some(code) => code.push_str(&text),
+                None => self.push_span(&text, false),
+            },
+            MdEvent::Code(text) => self.push_span(&text, true),
+            MdEvent::SoftBreak => self.push_span(" ", false),
+            MdEvent::HardBreak => self.push_span("\n", false),
+            MdEvent::Rule => {
+                self.finish_text();
+                self.push(Content::Rule);
+            }
+            MdEvent::TaskListMarker(done) => {
+                self.marker = Some(if done { "☑" } else { "☐" }.to_string());
+            }
+            // raw HTML and footnotes are left out
+            MdEvent::Html(_) | MdEvent::FootnoteReference(_) => (),
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading(level) => {
+                self.finish_text();
+                self.heading = Some(level);
+            }
+            Tag::BlockQuote => {
+                self.finish_text();
+                self.quote += 1;
+            }
+            Tag::CodeBlock(_) => {
+                self.finish_text();
+                self.code = Some(String::new());
+            }
+            Tag::List(first) => {
+                self.finish_text();
+                self.lists.push(first);
+            }
+            Tag::Item => {
+                self.finish_text();
+                self.marker = Some(match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", *number - 1)
+                    }
+                    _ => "•".to_string(),
+                });
+            }
+            Tag::Emphasis => self.emphasis += 1,
+            Tag::Strong => self.strong += 1,
+            Tag::Strikethrough => self.strikethrough += 1,
+            Tag::Link(_, url, _) => self.link = Some(url.to_string()),
+            Tag::Image(_, url, _) => {
+                self.finish_text();
+                self.image = Some((url.to_string(), String::new()));
+            }
+            _ => (),
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph | Tag::Item => self.finish_text(),
+            Tag::Heading(_) => {
+                self.finish_text();
+                self.heading = None;
+            }
+            Tag::BlockQuote => {
+                self.finish_text();
+                self.quote = self.quote.saturating_sub(1);
+            }
+            Tag::CodeBlock(_) => {
+                if let Some(code) = self.code.take() {
+                    self.push(Content::Code(code.trim_end_matches('\n').to_string()));
+                }
+            }
+            Tag::List(_) => {
+                self.finish_text();
+                self.lists.pop();
+            }
+            Tag::Emphasis => self.emphasis = self.emphasis.saturating_sub(1),
+            Tag::Strong => self.strong = self.strong.saturating_sub(1),
+            Tag::Strikethrough => self.strikethrough = self.strikethrough.saturating_sub(1),
+            Tag::Link(..) => self.link = None,
+            Tag::Image(..) => {
+                if let Some((url, alt)) = self.image.take() {
+                    self.push(Content::Image { url, alt });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn push_span(&mut self, text: &str, code: bool) {
+        if let Some((_, alt)) = &mut self.image {
+            alt.push_str(text);
+            return;
+        }
+        let style = Style {
+            strong: self.strong > 0,
+            emphasis: self.emphasis > 0,
+            code,
+            strikethrough: self.strikethrough > 0,
+        };
+        match self.spans.last_mut() {
+            Some(last) if last.style == style && last.link == self.link => {
+                last.text.push_str(text);
+            }
+            _ => self.spans.push(Span {
+                text: text.to_string(),
+                style,
+                link: self.link.clone(),
+            }),
+        }
+    }
+
+    /// End the text block being built, if there is one.
+    fn finish_text(&mut self) {
+        if self.spans.is_empty() && self.marker.is_none() {
+            return;
+        }
+        let content = Content::Text {
+            heading: self.heading,
+            marker: self.marker.take(),
+            spans: std::mem::take(&mut self.spans),
+        };
+        self.push(content);
+    }
+
+    fn push(&mut self, content: Content) {
+        self.blocks.push(Block {
+            content,
+            indent: self.lists.len(),
+            quote: self.quote,
+        });
+    }
+}
+
+/// Parse CommonMark, with strikethrough and task lists.
+fn parse(markdown: &str) -> Vec<Block> {
+    let mut builder = Builder::default();
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    for event in Parser::new_ext(markdown, options) {
+        builder.event(event);
+    }
+    builder.finish_text();
+    builder.blocks
+}
+
+/// Split the text of `spans` into words.
+fn tokenize(spans: &[Span]) -> Vec<Token> {
+    fn finish_word(
+        tokens: &mut Vec<Token>,
+        word: &mut String,
+        span: usize,
+        break_before: &mut bool,
+    ) {
+        if !word.is_empty() {
+            tokens.push(Token {
+                text: std::mem::take(word),
+                span,
+                space_after: false,
+                break_before: std::mem::take(break_before),
+            });
+        }
+    }
+
+    let mut tokens = Vec::new();
+    let mut break_before = false;
+    for (index, span) in spans.iter().enumerate() {
+        let mut word = String::new();
+        for c in span.text.chars() {
+            if !c.is_whitespace() {
+                word.push(c);
+                continue;
+            }
+            finish_word(&mut tokens, &mut word, index, &mut break_before);
+            if c == '\n' {
+                break_before = true;
+            } else if let Some(last) = tokens.last_mut() {
+                last.space_after = true;
+            }
+        }
+        // a word can go on into the next span
+        finish_word(&mut tokens, &mut word, index, &mut break_before);
+    }
+    tokens
+}
+
+/// The line and the x position of each word, given the width of the word
+/// and of the space after it, wrapping at `max_width`.
+fn wrap(words: &[(f64, f64, bool)], max_width: f64) -> Vec<(usize, f64)> {
+    let mut line = 0;
+    let mut x = 0.0;
+    let mut placed = Vec::with_capacity(words.len());
+    let mut last_space = 0.0;
+    for &(width, space, break_before) in words {
+        if !placed.is_empty() && (break_before || x + last_space + width > max_width) {
+            line += 1;
+            x = 0.0;
+        } else if !placed.is_empty() {
+            x += last_space;
+        }
+        placed.push((line, x));
+        x += width;
+        last_space = space;
+    }
+    placed
+}
+
+impl Markdown {
+    /// Create a widget that shows its data as Markdown.
+    pub fn new() -> Markdown {
+        Markdown {
+            on_link: Box::new(|url| Command::new(commands::OPEN_URL, url.to_string())),
+            images: Box::new(|_| None),
+            blocks: Vec::new(),
+            image_data: Vec::new(),
+            words: Vec::new(),
+            shapes: Vec::new(),
+            placed_images: Vec::new(),
+            links: Vec::new(),
+            hot_link: None,
+            pressed_link: None,
+        }
+    }
+
+    /// Builder-style method for choosing the command that clicking a link
+    /// submits, given its URL.
+    pub fn on_link(mut self, on_link: impl Fn(&str) -> Command + 'static) -> Self {
+        self.on_link = Box::new(on_link);
+        self
+    }
+
+    /// Builder-style method for providing the images, given their URLs.
+    ///
+    /// The images are looked up whenever the text changes.
+    pub fn images(mut self, images: impl Fn(&str) -> Option<ImageData> + 'static) -> Self {
+        self.images = Box::new(images);
+        self
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.blocks = parse(text);
+        let images = &self.images;
+        self.image_data = self
+            .blocks
+            .iter()
+            .filter_map(|block| match &block.content {
+                Content::Image { url, .. } => Some(images(url)),
+                _ => None,
+            })
+            .collect();
+        self.hot_link = None;
+        self.pressed_link = None;
+    }
+
+    /// The link at `pos`, if there is one.
+    fn link_at(&self, pos: Point) -> Option<usize> {
+        self.words.iter().find_map(|word| {
+            let link = word.link?;
+            let rect = Rect::new(
+                word.origin.x,
+                word.origin.y - word.size,
+                word.origin.x + word.line.width(),
+                word.origin.y + word.size * 0.35,
+            );
+            if rect.contains(pos) {
+                Some(link)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl Default for Markdown {
+    fn default() -> Self {
+        Markdown::new()
+    }
+}
+
+impl Widget<String> for Markdown {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut String, _env: &Env) {
+        match event {
+            Event::MouseDown(mouse) => {
+                self.pressed_link = self.link_at(mouse.pos);
+                if self.pressed_link.is_some() {
+                    ctx.set_active(true);
+                }
+            }
+            Event::MouseUp(mouse) if ctx.is_active() => {
+                ctx.set_active(false);
+                if let Some(pressed) = self.pressed_link.take() {
+                    if self.link_at(mouse.pos) == Some(pressed) {
+                        let command = (self.on_link)(&self.links[pressed]);
+                        ctx.submit_command(command, None);
+                    }
+                }
+            }
+            Event::MouseMoved(mouse) => {
+                let hot = self.link_at(mouse.pos).filter(|_| ctx.is_hot());
+                if hot != self.hot_link {
+                    self.hot_link = hot;
+                    ctx.request_paint();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &String, _env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => self.set_text(data),
+            LifeCycle::HotChanged(false) => {
+                self.hot_link = None;
+                ctx.request_paint();
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &String, data: &String, _env: &Env) {
+        if old_data != data {
+            self.set_text(data);
+            ctx.request_layout();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &String,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Markdown");
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            DEFAULT_WIDTH
+        };
+        let font_name = env.get(theme::FONT_NAME);
+        let monospace = env.get(theme::MONOSPACE_FONT_NAME);
+        let normal = env.get(theme::TEXT_SIZE_NORMAL);
+        let spacing = normal * BLOCK_SPACING;
+        let text = layout_ctx.text();
+
+        self.words.clear();
+        self.shapes.clear();
+        self.placed_images.clear();
+        self.links.clear();
+        let mut images = self.image_data.iter();
+        let mut y = 0.0;
+        for (index, block) in self.blocks.iter().enumerate() {
+            if index > 0 {
+                y += spacing;
+            }
+            let top = y;
+            let left = (block.indent + block.quote) as f64 * normal * INDENT;
+            let max_width = (width - left).max(0.0);
+            match &block.content {
+                Content::Text {
+                    heading,
+                    marker,
+                    spans,
+                } => {
+                    let scale = heading
+                        .and_then(|level| HEADING_SCALES.get(level as usize - 1))
+                        .copied()
+                        .unwrap_or(1.0);
+                    let size = normal * scale;
+                    let pitch = size * LINE_SPACING;
+                    // the same baseline as a label's
+                    let baseline = (pitch + size * 0.6) / 2.0;
+
+                    let tokens = tokenize(spans);
+                    let space = TextLine::new(text, font_name, size, " ", env).width();
+                    let mut lines = Vec::with_capacity(tokens.len());
+                    let mut widths = Vec::with_capacity(tokens.len());
+                    for token in &tokens {
+                        let style = spans[token.span].style;
+                        let (font, font_size) = if style.code {
+                            (monospace, size * CODE_SCALE)
+                        } else {
+                            (font_name, size)
+                        };
+                        let line = TextLine::new(text, font, font_size, &token.text, env);
+                        let space = if token.space_after { space } else { 0.0 };
+                        widths.push((line.width(), space, token.break_before));
+                        lines.push((line, font_size));
+                    }
+
+                    let mut rows = 1;
+                    let placed = wrap(&widths, max_width);
+                    for ((token, (line, font_size)), (row, x)) in
+                        tokens.iter().zip(lines).zip(placed)
+                    {
+                        let span = &spans[token.span];
+                        // the words of a link all share it
+                        let links = &mut self.links;
+                        let link = span.link.as_ref().map(|url| {
+                            if links.last() != Some(url) {
+                                links.push(url.clone());
+                            }
+                            links.len() - 1
+                        });
+                        let mut style = span.style;
+                        style.strong |= heading.is_some();
+                        rows = row + 1;
+                        self.words.push(Word {
+                            line,
+                            origin: Point::new(left + x, y + row as f64 * pitch + baseline),
+                            size: font_size,
+                            style,
+                            link,
+                            faded: false,
+                            clip: None,
+                        });
+                    }
+
+                    if let Some(marker) = marker {
+                        let line = TextLine::new(text, font_name, size, marker, env);
+                        let x = left - line.width() - size * 0.4;
+                        self.words.push(Word {
+                            line,
+                            origin: Point::new(x, y + baseline),
+                            size,
+                            style: Style::default(),
+                            link: None,
+                            faded: false,
+                            clip: None,
+                        });
+                    }
+                    y += rows as f64 * pitch;
+                }
+                Content::Code(code) => {
+                    let size = normal * CODE_SCALE;
+                    let pitch = size * LINE_SPACING;
+                    let baseline = (pitch + size * 0.6) / 2.0;
+                    let rows = code.lines().count().max(1);
+                    let rect =
+                        Rect::new(left, y, width, y + rows as f64 * pitch + 2.0 * CODE_PADDING);
+                    self.shapes.push(Shape::CodeBlock(rect));
+                    for (row, code_line) in code.lines().enumerate() {
+                        let line = TextLine::new(text, monospace, size, code_line, env);
+                        let origin = Point::new(
+                            left + CODE_PADDING,
+                            y + CODE_PADDING + row as f64 * pitch + baseline,
+                        );
+                        self.words.push(Word {
+                            line,
+                            origin,
+                            size,
+                            style: Style::default(),
+                            link: None,
+                            faded: false,
+                            clip: Some(rect),
+                        });
+                    }
+                    y = rect.y1;
+                }
+                Content::Image { alt, .. } => match images.next().cloned().flatten() {
+                    Some(image) => {
+                        let size = image.size();
+                        let scale = (max_width / size.width).min(1.0);
+                        let rect = Rect::from_origin_size((left, y), size * scale);
+                        self.placed_images.push((image, rect));
+                        y = rect.y1;
+                    }
+                    None => {
+                        let pitch = normal * LINE_SPACING;
+                        let line = TextLine::new(text, font_name, normal, alt, env);
+                        self.words.push(Word {
+                            line,
+                            origin: Point::new(left, y + (pitch + normal * 0.6) / 2.0),
+                            size: normal,
+                            style: Style::default(),
+                            link: None,
+                            faded: true,
+                            clip: None,
+                        });
+                        y += pitch;
+                    }
+                },
+                Content::Rule => {
+                    let middle = (y + normal / 2.0).round() + 0.5;
+                    self.shapes
+                        .push(Shape::Rule(Line::new((left, middle), (width, middle))));
+                    y += normal;
+                }
+            }
+
+            // quotes have a bar beside them, through the space between blocks
+            for level in 0..block.quote {
+                let x = level as f64 * normal * INDENT + normal * 0.4;
+                let bar = Rect::new(
+                    x,
+                    (top - spacing / 2.0).max(0.0),
+                    x + QUOTE_BAR_WIDTH,
+                    y + spacing / 2.0,
+                );
+                self.shapes.push(Shape::QuoteBar(bar));
+            }
+        }
+        bc.constrain(Size::new(width, y))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _data: &String, env: &Env) {
+        for shape in &self.shapes {
+            match shape {
+                Shape::CodeBlock(rect) => {
+                    let rect = RoundedRect::from_rect(*rect, 4.0);
+                    paint_ctx.fill(rect, &env.get(theme::BACKGROUND_DARK));
+                }
+                Shape::QuoteBar(rect) => paint_ctx.fill(*rect, &env.get(theme::BORDER_LIGHT)),
+                Shape::Rule(line) => paint_ctx.stroke(*line, &env.get(theme::BORDER_LIGHT), 1.0),
+            }
+        }
+
+        for (image, rect) in &self.placed_images {
+            if let Some(piet_image) = paint_ctx.piet_image(image) {
+                paint_ctx.draw_image(&piet_image, *rect, InterpolationMode::Bilinear);
+            }
+        }
+
+        let label_color = env.get(theme::LABEL_COLOR);
+        let link_color = env.get(theme::LINK_COLOR);
+        let hot_link_color = env.get(theme::LINK_HOVER_COLOR);
+        let faded_color = env.get(theme::PLACEHOLDER_COLOR);
+        let code_background = env.get(theme::BACKGROUND_LIGHT);
+        for word in &self.words {
+            let color = match word.link {
+                Some(link) if self.hot_link == Some(link) => &hot_link_color,
+                Some(_) => &link_color,
+                None if word.faded => &faded_color,
+                None => &label_color,
+            };
+            let width = word.line.width();
+            let origin = word.origin;
+            if word.style.code && word.clip.is_none() {
+                let rect = Rect::new(
+                    origin.x - 2.0,
+                    origin.y - word.size,
+                    origin.x + width + 2.0,
+                    origin.y + word.size * 0.35,
+                );
+                paint_ctx.fill(RoundedRect::from_rect(rect, 2.0), &code_background);
+            }
+
+            let style = word.style;
+            let clip = word.clip;
+            let layout = word.line.layout();
+            paint_ctx
+                .with_save(|rc| {
+                    if let Some(clip) = clip {
+                        rc.clip(clip);
+                    }
+                    // there are no bold or italic fonts to choose from, so
+                    // strong text is drawn twice and emphasis is slanted
+                    rc.transform(Affine::translate(origin.to_vec2()));
+                    if style.emphasis {
+                        rc.transform(Affine::new([1.0, 0.0, -EMPHASIS_SLANT, 1.0, 0.0, 0.0]));
+                    }
+                    rc.draw_text(layout, Point::ORIGIN, color);
+                    if style.strong {
+                        rc.draw_text(layout, Point::new(0.6, 0.0), color);
+                    }
+                    Ok(())
+                })
+                .unwrap();
+
+            if word.link.is_some() {
+                let y = origin.y + 2.5;
+                let underline = Line::new((origin.x, y), (origin.x + width, y));
+                paint_ctx.stroke(underline, color, 1.0);
+            }
+            if style.strikethrough {
+                let y = origin.y - word.size * 0.3;
+                let line = Line::new((origin.x, y), (origin.x + width, y));
+                paint_ctx.stroke(line, color, 1.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(heading: Option<u32>, marker: Option<&str>, spans: Vec<Span>) -> Content {
+        Content::Text {
+            heading,
+            marker: marker.map(String::from),
+            spans,
+        }
+    }
+
+    fn span(text: &str, style: Style, link: Option<&str>) -> Span {
+        Span {
+            text: text.to_string(),
+            style,
+            link: link.map(String::from),
+        }
+    }
+
+    #[test]
+    fn documents_are_parsed_into_blocks() {
+        let blocks = parse(
+            "# Title\n\nSome *soft* and **strong** [text](https://example.com).\n\n\
+             1. one\n2. two\n   - nested\n\n```\nlet x = 1;\n```\n\n> quoted\n\n---\n\n![a cat](cat.png)",
+        );
+        let plain = Style::default();
+        let emphasis = Style {
+            emphasis: true,
+            ..plain
+        };
+        let strong = Style {
+            strong: true,
+            ..plain
+        };
+        let contents: Vec<(&Content, usize, usize)> = blocks
+            .iter()
+            .map(|block| (&block.content, block.indent, block.quote))
+            .collect();
+        assert_eq!(
+            contents,
+            vec![
+                (&text(Some(1), None, vec![span("Title", plain, None)]), 0, 0),
+                (
+                    &text(
+                        None,
+                        None,
+                        vec![
+                            span("Some ", plain, None),
+                            span("soft", emphasis, None),
+                            span(" and ", plain, None),
+                            span("strong", strong, None),
+                            span(" ", plain, None),
+                            span("text", plain, Some("https://example.com")),
+                            span(".", plain, None),
+                        ]
+                    ),
+                    0,
+                    0
+                ),
+                (
+                    &text(None, Some("1."), vec![span("one", plain, None)]),
+                    1,
+                    0
+                ),
+                (
+                    &text(None, Some("2."), vec![span("two", plain, None)]),
+                    1,
+                    0
+                ),
+                (
+                    &text(None, Some("•"), vec![span("nested", plain, None)]),
+                    2,
+                    0
+                ),
+                (&Content::Code("let x = 1;".to_string()), 0, 0),
+                (&text(None, None, vec![span("quoted", plain, None)]), 0, 1),
+                (&Content::Rule, 0, 0),
+                (
+                    &Content::Image {
+                        url: "cat.png".to_string(),
+                        alt: "a cat".to_string(),
+                    },
+                    0,
+                    0
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn paragraphs_wrap_between_words() {
+        let plain = Style::default();
+        let code = Style {
+            code: true,
+            ..plain
+        };
+        let spans = vec![
+            span("run ", plain, None),
+            span("cargo", code, None),
+            span(", then\nwait", plain, None),
+        ];
+        let words: Vec<(String, bool, bool)> = tokenize(&spans)
+            .into_iter()
+            .map(|token| (token.text, token.space_after, token.break_before))
+            .collect();
+        assert_eq!(
+            words,
+            vec![
+                ("run".to_string(), true, false),
+                ("cargo".to_string(), false, false),
+                (",".to_string(), true, false),
+                ("then".to_string(), false, false),
+                ("wait".to_string(), false, true),
+            ]
+        );
+
+        let widths = [(30.0, 5.0, false), (50.0, 5.0, false), (40.0, 0.0, false)];
+        assert_eq!(wrap(&widths, 200.0), vec![(0, 0.0), (0, 35.0), (0, 90.0)]);
+        assert_eq!(wrap(&widths, 90.0), vec![(0, 0.0), (0, 35.0), (1, 0.0)]);
+        // a word too wide for a line still gets one of its own
+        assert_eq!(wrap(&widths, 10.0), vec![(0, 0.0), (1, 0.0), (2, 0.0)]);
+        let broken = [(30.0, 5.0, false), (30.0, 0.0, true)];
+        assert_eq!(wrap(&broken, 200.0), vec![(0, 0.0), (1, 0.0)]);
+    }
+}
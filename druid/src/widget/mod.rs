@@ -14,56 +14,140 @@
 
 //! Common widgets.
 
+mod accessible;
 mod align;
+mod autocomplete;
+mod badge;
+mod breadcrumbs;
 mod button;
+mod chart;
 mod checkbox;
+mod clip_shape;
+mod color_picker;
+#[cfg(feature = "constraint-layout")]
+#[cfg_attr(docsrs, doc(cfg(feature = "constraint-layout")))]
+mod constraint_layout;
 mod container;
+mod controller;
+mod date_picker;
 mod either;
 mod env_scope;
 mod flex;
+mod form;
+mod icon;
 mod identity_wrapper;
+pub(crate) mod image;
+pub(crate) mod image_cache;
 mod label;
+mod link;
 mod list;
+mod live_image;
+#[cfg(feature = "markdown")]
+#[cfg_attr(docsrs, doc(cfg(feature = "markdown")))]
+mod markdown;
 mod padding;
+mod pager;
 mod parse;
+#[cfg(feature = "pdf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "pdf")))]
+mod pdf;
 mod progress_bar;
+#[cfg(feature = "progressive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "progressive")))]
+mod progressive_image;
 mod radio;
 mod scroll;
 mod sized_box;
 mod slider;
+mod slider_field;
+mod spinner;
 mod split;
+mod status_bar;
 mod stepper;
 #[cfg(feature = "svg")]
 #[cfg_attr(docsrs, doc(cfg(feature = "svg")))]
 mod svg;
 mod switch;
+#[cfg(feature = "terminal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "terminal")))]
+mod terminal;
 mod textbox;
+mod tile_map;
+mod toolbar;
+#[cfg(feature = "video")]
+#[cfg_attr(docsrs, doc(cfg(feature = "video")))]
+mod video;
+mod view_switcher;
 mod widget_ext;
 
+pub use accessible::{AccessibilityInfo, Accessible, Role};
 pub use align::Align;
+pub use autocomplete::{Autocomplete, SuggestionRequest, Suggestions};
+pub use badge::Badge;
+pub use breadcrumbs::Breadcrumbs;
 pub use button::Button;
+pub use chart::{Chart, Series};
 pub use checkbox::Checkbox;
+pub use clip_shape::ClipShape;
+pub use color_picker::ColorPicker;
+#[cfg(feature = "constraint-layout")]
+#[cfg_attr(docsrs, doc(cfg(feature = "constraint-layout")))]
+pub use constraint_layout::{
+    Anchor, Constraint, ConstraintLayout, ConstraintTarget, LayoutItem, Priority,
+};
 pub use container::Container;
+pub use controller::{Controller, ControllerHost};
+pub use date_picker::DatePicker;
 pub use either::Either;
 pub use env_scope::EnvScope;
-pub use flex::Flex;
+pub use flex::{Axis, CrossAxisAlignment, Flex, FlexParams};
+pub use form::{Form, FormField, FormState, SubmitButton};
+pub use icon::Icon;
 pub use identity_wrapper::IdentityWrapper;
+#[cfg(feature = "image")]
+pub use image::ImageError;
+pub use image::{FillStrat, Image, ImageData, PixelFormat};
+pub use image_cache::{IconAtlas, ImageCache, ImageSource};
 pub use label::{Label, LabelText};
+pub use link::Link;
 pub use list::{List, ListIter};
+pub use live_image::{FrameSender, LiveImage};
+#[cfg(feature = "markdown")]
+#[cfg_attr(docsrs, doc(cfg(feature = "markdown")))]
+pub use markdown::Markdown;
 pub use padding::Padding;
+pub use pager::Pager;
 pub use parse::Parse;
+#[cfg(feature = "pdf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "pdf")))]
+pub use pdf::{PdfError, PdfView};
 pub use progress_bar::ProgressBar;
+#[cfg(feature = "progressive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "progressive")))]
+pub use progressive_image::{ImageStream, ProgressiveImage};
 pub use radio::{Radio, RadioGroup};
 pub use scroll::Scroll;
 pub use sized_box::SizedBox;
 pub use slider::Slider;
+pub use slider_field::SliderField;
+pub use spinner::Spinner;
 pub use split::Split;
-pub use stepper::Stepper;
+pub use status_bar::{StatusBar, StatusMessage};
+pub use stepper::{Stepper, StepperValue};
 #[cfg(feature = "svg")]
 #[cfg_attr(docsrs, doc(cfg(feature = "svg")))]
 pub use svg::{Svg, SvgData};
 pub use switch::Switch;
+#[cfg(feature = "terminal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "terminal")))]
+pub use terminal::{Terminal, TerminalError};
 pub use textbox::TextBox;
+pub use tile_map::{TileKey, TileMap};
+pub use toolbar::{Toolbar, ToolbarItem};
+#[cfg(feature = "video")]
+#[cfg_attr(docsrs, doc(cfg(feature = "video")))]
+pub use video::{Video, VideoDecoder, VideoError, VideoFrame};
+pub use view_switcher::ViewSwitcher;
 pub use widget_ext::WidgetExt;
 
 use std::num::NonZeroU64;
@@ -225,12 +309,86 @@ pub trait Widget<T> {
     /// [`RenderContext`]: ../trait.RenderContext.html
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env);
 
+    /// The smallest length along `axis` that the widget's content fits in.
+    ///
+    /// `cross` is the length the widget has along the other axis, or
+    /// infinity if that isn't known. This is the narrowest the widget can be
+    /// made without its content overflowing, which containers such as
+    /// [`Split`] use to size their parts to their content.
+    ///
+    /// The default is the same as [`max_intrinsic`]. Widgets that can be
+    /// made smaller than they would like to be, such as a [`Flex`] with
+    /// children that shrink or a [`Scroll`], should override it.
+    ///
+    /// [`Split`]: struct.Split.html
+    /// [`Flex`]: struct.Flex.html
+    /// [`Scroll`]: struct.Scroll.html
+    /// [`max_intrinsic`]: #method.max_intrinsic
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        self.max_intrinsic(ctx, axis, cross, data, env)
+    }
+
+    /// The length along `axis` that the widget would take if there were no
+    /// limit, such as the width of a label's text on one line.
+    ///
+    /// `cross` is as for [`min_intrinsic`]. The default lays the widget out
+    /// unbounded along `axis`, and uses the size it picks. That lays out the
+    /// widget's children too, for those constraints, so a container should
+    /// only ask a child through its [`WidgetPod`], during its own layout and
+    /// before laying the child out, as [`Split`] does.
+    ///
+    /// [`min_intrinsic`]: #method.min_intrinsic
+    /// [`WidgetPod`]: ../struct.WidgetPod.html
+    /// [`Split`]: struct.Split.html
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let cross_range = if cross.is_finite() {
+            (cross, cross)
+        } else {
+            (0.0, f64::INFINITY)
+        };
+        let bc = match axis {
+            Axis::Horizontal => BoxConstraints::new(
+                Size::new(0.0, cross_range.0),
+                Size::new(f64::INFINITY, cross_range.1),
+            ),
+            Axis::Vertical => BoxConstraints::new(
+                Size::new(cross_range.0, 0.0),
+                Size::new(cross_range.1, f64::INFINITY),
+            ),
+        };
+        let size = self.layout(ctx, &bc, data, env);
+        match axis {
+            Axis::Horizontal => size.width,
+            Axis::Vertical => size.height,
+        }
+    }
+
     #[doc(hidden)]
     /// Get the identity of the widget; this is basically only implemented by
     /// `IdentityWrapper`. Widgets should not implement this on their own.
     fn id(&self) -> Option<WidgetId> {
         None
     }
+
+    /// The name of the widget's type, for debugging tools such as the
+    /// inspector. There is no need to implement this.
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
 }
 
 impl WidgetId {
@@ -289,7 +447,33 @@ impl<T> Widget<T> for Box<dyn Widget<T>> {
         self.deref_mut().paint(paint_ctx, data, env);
     }
 
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        self.deref_mut().min_intrinsic(ctx, axis, cross, data, env)
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        self.deref_mut().max_intrinsic(ctx, axis, cross, data, env)
+    }
+
     fn id(&self) -> Option<WidgetId> {
         self.deref().id()
     }
+
+    fn type_name(&self) -> &'static str {
+        self.deref().type_name()
+    }
 }
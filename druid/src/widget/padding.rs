@@ -15,6 +15,7 @@
 //! A widget that just adds padding during layout.
 
 use crate::kurbo::{Insets, Point, Rect, Size};
+use crate::widget::Axis;
 use crate::{
     BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
     UpdateCtx, Widget, WidgetPod,
@@ -99,16 +100,55 @@ impl<T: Data> Widget<T> for Padding<T> {
 
         let child_bc = bc.shrink((hpad, vpad));
         let size = self.child.layout(layout_ctx, &child_bc, data, env);
-        let origin = Point::new(self.left, self.top);
+        // leading and trailing padding swap sides in a right-to-left layout
+        let left = if env.get(Env::RIGHT_TO_LEFT) {
+            self.right
+        } else {
+            self.left
+        };
+        let origin = Point::new(left, self.top);
         self.child
             .set_layout_rect(Rect::from_origin_size(origin, size));
 
         let my_size = Size::new(size.width + hpad, size.height + vpad);
         let my_insets = self.child.compute_parent_paint_insets(my_size);
         layout_ctx.set_paint_insets(my_insets);
+        layout_ctx.set_baseline_offset(self.child.baseline_offset() + self.bottom);
         my_size
     }
 
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let (pad, cross_pad) = match axis {
+            Axis::Horizontal => (self.left + self.right, self.top + self.bottom),
+            Axis::Vertical => (self.top + self.bottom, self.left + self.right),
+        };
+        let cross = (cross - cross_pad).max(0.0);
+        pad + self.child.min_intrinsic(ctx, axis, cross, data, env)
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let (pad, cross_pad) = match axis {
+            Axis::Horizontal => (self.left + self.right, self.top + self.bottom),
+            Axis::Vertical => (self.top + self.bottom, self.left + self.right),
+        };
+        let cross = (cross - cross_pad).max(0.0);
+        pad + self.child.max_intrinsic(ctx, axis, cross, data, env)
+    }
+
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
         self.child.paint_with_offset(paint_ctx, data, env);
     }
@@ -0,0 +1,403 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that shows one of its pages at a time.
+
+use crate::kurbo::{Circle, Point, Rect, Size};
+use crate::theme;
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, KeyCode, LayoutCtx, Lens, LensExt, LifeCycle,
+    LifeCycleCtx, PaintCtx, RenderContext, UpdateCtx, Widget, WidgetPod,
+};
+
+/// The height of the strip with the dots in it, below the pages.
+const DOTS_HEIGHT: f64 = 20.0;
+const DOT_RADIUS: f64 = 3.5;
+const DOT_SPACING: f64 = 14.0;
+
+/// How far the mouse has to move before a press becomes a swipe.
+const SWIPE_THRESHOLD: f64 = 8.0;
+
+/// How much of a page a swipe has to cover to go to the next page.
+const SWIPE_FRACTION: f64 = 0.2;
+
+/// How quickly the pages slide; they go most of the way in a fifth of a
+/// second.
+const SLIDE_SPEED: f64 = 14.0;
+
+/// A press that may become a swipe.
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    start: Point,
+    /// Where the pages were when the press started.
+    position: f64,
+    swiping: bool,
+}
+
+/// A widget that shows one of its pages at a time, such as the steps of an
+/// introduction or the photos of a carousel, with the index of the page in
+/// the data.
+///
+/// Swiping sideways with the mouse, the arrow keys, and clicking the dots
+/// below the pages go from page to page, and the pages slide into place
+/// whichever way the index changes. When [`theme::REDUCE_MOTION`] is set
+/// they jump there instead.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Label, Pager};
+/// use druid::{Data, Lens, Widget};
+///
+/// #[derive(Clone, Data, Lens)]
+/// struct Intro {
+///     page: usize,
+/// }
+///
+/// fn intro() -> impl Widget<Intro> {
+///     Pager::new(Intro::page)
+///         .with_page(Label::new("Welcome"))
+///         .with_page(Label::new("Getting started"))
+///         .with_page(Label::new("You're all set"))
+/// }
+/// ```
+///
+/// [`theme::REDUCE_MOTION`]: ../theme/constant.REDUCE_MOTION.html
+pub struct Pager<T, L> {
+    index: L,
+    pages: Vec<WidgetPod<T, Box<dyn Widget<T>>>>,
+    show_dots: bool,
+    /// Where the pages have slid to, in pages; this is the index once they
+    /// have stopped.
+    position: f64,
+    drag: Option<Drag>,
+    /// The size of the area the pages are shown in, from the last layout.
+    page_size: Size,
+}
+
+impl<T: Data, L: Lens<T, usize>> Pager<T, L> {
+    /// Create a pager with no pages, with `index` leading to the index of
+    /// the page being shown.
+    pub fn new(index: L) -> Self {
+        Pager {
+            index,
+            pages: Vec::new(),
+            show_dots: true,
+            position: 0.0,
+            drag: None,
+            page_size: Size::ZERO,
+        }
+    }
+
+    /// Builder-style variant of [`add_page`].
+    ///
+    /// [`add_page`]: #method.add_page
+    pub fn with_page(mut self, page: impl Widget<T> + 'static) -> Self {
+        self.add_page(page);
+        self
+    }
+
+    /// Add a page after the others.
+    ///
+    /// If the pager is already in the window, call [`children_changed`].
+    ///
+    /// [`children_changed`]: ../struct.EventCtx.html#method.children_changed
+    pub fn add_page(&mut self, page: impl Widget<T> + 'static) {
+        self.pages.push(WidgetPod::new(page).boxed());
+    }
+
+    /// Builder-style method for setting whether the dots that show which
+    /// page this is are shown. They are by default.
+    pub fn show_dots(mut self, show: bool) -> Self {
+        self.show_dots = show;
+        self
+    }
+
+    /// The index of the page to show, which is the last page if the data
+    /// goes past it.
+    fn current(&self, data: &T) -> usize {
+        self.index.get(data).min(self.pages.len().saturating_sub(1))
+    }
+
+    fn go_to(&mut self, ctx: &mut EventCtx, data: &mut T, page: usize) {
+        let page = page.min(self.pages.len().saturating_sub(1));
+        if page != self.index.get(data) {
+            self.index.put(data, page);
+        }
+        ctx.request_anim_frame();
+    }
+
+    /// Put each page where it has slid to, in the middle of the area the
+    /// pages are shown in.
+    fn place_pages(&mut self) {
+        let area = self.page_size;
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            let size = page.layout_rect().size();
+            let origin = Point::new(
+                (index as f64 - self.position) * area.width + (area.width - size.width) / 2.0,
+                (area.height - size.height) / 2.0,
+            );
+            page.set_layout_rect(Rect::from_origin_size(origin, size));
+        }
+    }
+
+    /// Where the dot for each page is.
+    fn dot_centers(&self, size: Size) -> Vec<Point> {
+        let count = self.pages.len();
+        let first = (size.width - (count as f64 - 1.0) * DOT_SPACING) / 2.0;
+        let y = size.height - DOTS_HEIGHT / 2.0;
+        (0..count)
+            .map(|index| Point::new(first + index as f64 * DOT_SPACING, y))
+            .collect()
+    }
+
+    /// The page whose dot is at `pos`, if any.
+    fn dot_at(&self, size: Size, pos: Point) -> Option<usize> {
+        if !self.show_dots || pos.y < size.height - DOTS_HEIGHT {
+            return None;
+        }
+        self.dot_centers(size)
+            .iter()
+            .position(|center| (pos.x - center.x).abs() <= DOT_SPACING / 2.0)
+    }
+
+    /// The page a swipe of `dx` from page `from` ends on.
+    fn swipe_target(&self, from: usize, dx: f64) -> usize {
+        let distance = -dx / self.page_size.width.max(1.0);
+        if distance > SWIPE_FRACTION {
+            (from + 1).min(self.pages.len().saturating_sub(1))
+        } else if distance < -SWIPE_FRACTION {
+            from.saturating_sub(1)
+        } else {
+            from
+        }
+    }
+}
+
+impl<T: Data, L: Lens<T, usize>> Widget<T> for Pager<T, L> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if self.pages.is_empty() {
+            return;
+        }
+        let current = self.current(data);
+        match event {
+            Event::MouseDown(mouse) => {
+                if let Some(page) = self.dot_at(ctx.size(), mouse.pos) {
+                    self.go_to(ctx, data, page);
+                    ctx.set_handled();
+                    return;
+                }
+                self.drag = Some(Drag {
+                    start: mouse.pos,
+                    position: self.position,
+                    swiping: false,
+                });
+            }
+            Event::MouseMoved(mouse) => {
+                if let Some(drag) = &mut self.drag {
+                    let dx = mouse.pos.x - drag.start.x;
+                    // a press that a page has taken, such as on a button,
+                    // doesn't swipe
+                    if !drag.swiping
+                        && dx.abs() > SWIPE_THRESHOLD
+                        && !self.pages[current].has_active()
+                    {
+                        drag.swiping = true;
+                        ctx.set_active(true);
+                    }
+                    if drag.swiping {
+                        let last = (self.pages.len() - 1) as f64;
+                        let position = drag.position - dx / self.page_size.width.max(1.0);
+                        self.position = position.max(0.0).min(last);
+                        self.place_pages();
+                        ctx.request_paint();
+                        ctx.set_handled();
+                        return;
+                    }
+                }
+            }
+            Event::MouseUp(mouse) => {
+                if let Some(drag) = self.drag.take() {
+                    if drag.swiping {
+                        ctx.set_active(false);
+                        let target = self.swipe_target(current, mouse.pos.x - drag.start.x);
+                        self.go_to(ctx, data, target);
+                        ctx.set_handled();
+                        return;
+                    }
+                }
+            }
+            _ => (),
+        }
+
+        for page in &mut self.pages {
+            page.event(ctx, event, data, env);
+        }
+
+        if let Event::KeyDown(key) = event {
+            if !ctx.is_handled() {
+                match key.key_code {
+                    KeyCode::ArrowRight => self.go_to(ctx, data, current + 1),
+                    KeyCode::ArrowLeft => self.go_to(ctx, data, current.saturating_sub(1)),
+                    _ => return,
+                }
+                ctx.set_handled();
+            }
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        if let LifeCycle::WidgetAdded = event {
+            self.position = self.current(data) as f64;
+        }
+        if let LifeCycle::AnimFrame(interval) = event {
+            let target = self.current(data) as f64;
+            let swiping = self.drag.map(|drag| drag.swiping).unwrap_or(false);
+            if self.position != target && !swiping {
+                let step = 1.0 - (-(*interval as f64) * 1e-9 * SLIDE_SPEED).exp();
+                self.position += (target - self.position) * step;
+                if (target - self.position).abs() < 0.001 || env.get(theme::REDUCE_MOTION) {
+                    self.position = target;
+                } else {
+                    ctx.request_anim_frame();
+                }
+                self.place_pages();
+                ctx.request_paint();
+            }
+        }
+        for page in &mut self.pages {
+            page.lifecycle(ctx, event, data, env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        if self.index.get(old_data) != self.index.get(data) {
+            ctx.request_anim_frame();
+        }
+        for page in &mut self.pages {
+            page.update(ctx, data, env);
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Pager");
+        let dots_height = if self.show_dots { DOTS_HEIGHT } else { 0.0 };
+        let page_bc = bc.shrink((0.0, dots_height)).loosen();
+        let mut largest = Size::ZERO;
+        for page in &mut self.pages {
+            let size = page.layout(layout_ctx, &page_bc, data, env);
+            page.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+            largest = Size::new(
+                largest.width.max(size.width),
+                largest.height.max(size.height),
+            );
+        }
+        // the pages fill the space they are given, or are as big as the
+        // biggest of them
+        let max = page_bc.max();
+        let area = Size::new(
+            if bc.is_width_bounded() {
+                max.width
+            } else {
+                largest.width
+            },
+            if bc.is_height_bounded() {
+                max.height
+            } else {
+                largest.height
+            },
+        );
+        self.page_size = area;
+        self.place_pages();
+        bc.constrain(Size::new(area.width, area.height + dots_height))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        let area = Rect::from_origin_size(Point::ORIGIN, self.page_size);
+        if let Err(e) = paint_ctx.save() {
+            log::error!("saving render context failed: {:?}", e);
+            return;
+        }
+        paint_ctx.clip(area);
+        for page in &mut self.pages {
+            // only the pages that are sliding past are drawn
+            if page.layout_rect().intersect(area).area() > 0.0 {
+                page.paint_with_offset(paint_ctx, data, env);
+            }
+        }
+        if let Err(e) = paint_ctx.restore() {
+            log::error!("restoring render context failed: {:?}", e);
+        }
+
+        if self.show_dots && self.pages.len() > 1 {
+            let current = self.current(data);
+            let color = env.get(theme::LABEL_COLOR);
+            let dim = env.get(theme::BORDER_LIGHT).with_alpha(0.5);
+            for (index, center) in self.dot_centers(paint_ctx.size()).into_iter().enumerate() {
+                let dot = Circle::new(center, DOT_RADIUS);
+                if index == current {
+                    paint_ctx.fill(dot, &color);
+                } else {
+                    paint_ctx.fill(dot, &dim);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Label;
+
+    #[test]
+    fn swipes_and_dots_pick_pages() {
+        let mut pager = Pager::new(crate::lens::Id)
+            .with_page(Label::new("one"))
+            .with_page(Label::new("two"))
+            .with_page(Label::new("three"));
+        pager.page_size = Size::new(300.0, 200.0);
+        assert_eq!(pager.current(&7), 2);
+
+        // a short swipe stays, and a longer one goes to the next page, but
+        // not past the ends
+        assert_eq!(pager.swipe_target(1, -40.0), 1);
+        assert_eq!(pager.swipe_target(1, -90.0), 2);
+        assert_eq!(pager.swipe_target(1, 90.0), 0);
+        assert_eq!(pager.swipe_target(2, -200.0), 2);
+        assert_eq!(pager.swipe_target(0, 200.0), 0);
+
+        let size = Size::new(300.0, 220.0);
+        assert_eq!(pager.dot_at(size, Point::new(150.0, 210.0)), Some(1));
+        assert_eq!(pager.dot_at(size, Point::new(137.0, 210.0)), Some(0));
+        assert_eq!(pager.dot_at(size, Point::new(150.0, 100.0)), None);
+        assert_eq!(pager.dot_at(size, Point::new(20.0, 210.0)), None);
+
+        // pages sit side by side, with the one being shown in view
+        pager.position = 1.5;
+        pager.place_pages();
+        let x: Vec<_> = pager
+            .pages
+            .iter()
+            .map(|page| page.layout_rect().x0)
+            .collect();
+        assert_eq!(x, vec![-300.0, 0.0, 300.0]);
+    }
+}
@@ -0,0 +1,450 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that shows the pages of a PDF document.
+
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use log::error;
+use pdfium_render::prelude::{PdfDocument, PdfRenderConfig, Pdfium};
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::piet::{Color, InterpolationMode};
+use crate::theme;
+use crate::widget::{ImageData, PixelFormat};
+use crate::{
+    commands, Application, BoxConstraints, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle,
+    LifeCycleCtx, PaintCtx, RenderContext, UpdateCtx, Widget,
+};
+
+/// How many pixels a point of the page takes up at a zoom of 1: there are
+/// 72 points to an inch, and 96 pixels.
+const PIXELS_PER_POINT: f64 = 96.0 / 72.0;
+
+/// How far the zoom can go, either way.
+const MIN_ZOOM: f64 = 0.1;
+const MAX_ZOOM: f64 = 8.0;
+
+/// How much each step of Ctrl and the wheel, or Ctrl and +, zooms by.
+const ZOOM_STEP: f64 = 1.25;
+
+/// A widget that shows a page of a PDF document, with the index of the
+/// page, from 0, as its data.
+///
+/// The page is shown at its printed size, times the [`zoom`]; put the view
+/// in a [`Scroll`] to pan around a page that doesn't fit. While it has
+/// focus, Page Up and Page Down, or the arrow keys, go through the pages,
+/// and Ctrl with the wheel or with + and - zooms. Dragging over the text
+/// selects it, for [`commands::COPY`].
+///
+/// PDFs are drawn by [Pdfium], which has to be installed where the system
+/// finds its libraries. This is only available with the `pdf` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use druid::widget::{PdfView, Scroll};
+/// use druid::Widget;
+///
+/// fn viewer() -> impl Widget<usize> {
+///     let view = PdfView::open("manual.pdf", None).unwrap().zoom(1.5);
+///     Scroll::new(view)
+/// }
+/// ```
+///
+/// [`zoom`]: #method.zoom
+/// [`Scroll`]: struct.Scroll.html
+/// [`commands::COPY`]: ../commands/constant.COPY.html
+/// [Pdfium]: https://pdfium.googlesource.com/pdfium/
+pub struct PdfView {
+    document: PdfDocument<'static>,
+    page_count: usize,
+    zoom: f64,
+    page: Option<Page>,
+    /// The page drawn at a width in pixels.
+    rendered: Option<(usize, u32, ImageData)>,
+    /// The characters from the one the drag started at to the one it is
+    /// at now.
+    selection: Option<(usize, usize)>,
+}
+
+/// The page being shown, and where the characters on it are.
+struct Page {
+    index: usize,
+    /// The size of the page, in points.
+    size: Size,
+    glyphs: Vec<Glyph>,
+}
+
+/// A character of a page, and where it is, in points from the top left.
+#[derive(Debug, Clone)]
+struct Glyph {
+    c: char,
+    rect: Rect,
+}
+
+/// An error from opening a PDF document.
+#[derive(Debug, Clone)]
+pub struct PdfError {
+    message: String,
+}
+
+impl fmt::Display for PdfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "opening the PDF failed: {}", self.message)
+    }
+}
+
+impl std::error::Error for PdfError {}
+
+impl PdfError {
+    fn new(message: impl fmt::Display) -> Self {
+        PdfError {
+            message: message.to_string(),
+        }
+    }
+}
+
+/// The Pdfium library, which can only be loaded once.
+fn pdfium() -> Result<&'static Pdfium, PdfError> {
+    static PDFIUM: OnceLock<Result<Pdfium, String>> = OnceLock::new();
+    PDFIUM
+        .get_or_init(|| {
+            Pdfium::bind_to_system_library()
+                .map(Pdfium::new)
+                .map_err(|e| e.to_string())
+        })
+        .as_ref()
+        .map_err(PdfError::new)
+}
+
+impl PdfView {
+    /// Open the PDF file at `path`, unlocking it with `password` if it
+    /// needs one.
+    pub fn open(path: impl AsRef<Path>, password: Option<&str>) -> Result<Self, PdfError> {
+        let document = pdfium()?
+            .load_pdf_from_file(path.as_ref(), password)
+            .map_err(PdfError::new)?;
+        Ok(PdfView::new(document))
+    }
+
+    /// Open a PDF document from its bytes, unlocking it with `password` if
+    /// it needs one.
+    pub fn from_bytes(bytes: Vec<u8>, password: Option<&str>) -> Result<Self, PdfError> {
+        let document = pdfium()?
+            .load_pdf_from_byte_vec(bytes, password)
+            .map_err(PdfError::new)?;
+        Ok(PdfView::new(document))
+    }
+
+    fn new(document: PdfDocument<'static>) -> Self {
+        let page_count = document.pages().len().max(0) as usize;
+        PdfView {
+            document,
+            page_count,
+            zoom: 1.0,
+            page: None,
+            rendered: None,
+            selection: None,
+        }
+    }
+
+    /// Builder-style method for setting the zoom, where 1 is the printed
+    /// size of the page.
+    pub fn zoom(mut self, zoom: f64) -> Self {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self
+    }
+
+    /// The number of pages in the document.
+    pub fn page_count(&self) -> usize {
+        self.page_count
+    }
+
+    /// How many pixels of the widget a point of the page takes up.
+    fn scale(&self) -> f64 {
+        self.zoom * PIXELS_PER_POINT
+    }
+
+    fn set_zoom(&mut self, ctx: &mut EventCtx, zoom: f64) {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        ctx.request_layout();
+        ctx.request_paint();
+    }
+
+    /// Make `index` the page being shown, finding where its characters are.
+    fn load_page(&mut self, index: usize) {
+        if self.page.as_ref().map(|page| page.index) == Some(index) {
+            return;
+        }
+        self.page = None;
+        self.selection = None;
+        let page = match self.document.pages().get(index as _) {
+            Ok(page) => page,
+            Err(e) => {
+                error!("loading page {} of the PDF failed: {}", index + 1, e);
+                return;
+            }
+        };
+        let size = Size::new(page.width().value as f64, page.height().value as f64);
+        let glyphs = match page.text() {
+            Ok(text) => text
+                .chars()
+                .iter()
+                .filter_map(|c| {
+                    let bounds = c.loose_bounds().ok()?;
+                    // the page's y axis goes up from the bottom
+                    let rect = Rect::new(
+                        bounds.left().value as f64,
+                        size.height - bounds.top().value as f64,
+                        bounds.right().value as f64,
+                        size.height - bounds.bottom().value as f64,
+                    );
+                    Some(Glyph {
+                        c: c.unicode_char()?,
+                        rect,
+                    })
+                })
+                .collect(),
+            Err(e) => {
+                error!("reading the text of the PDF failed: {}", e);
+                Vec::new()
+            }
+        };
+        self.page = Some(Page {
+            index,
+            size,
+            glyphs,
+        });
+    }
+
+    /// Draw the page at `width` pixels across, unless it already has been.
+    fn render(&mut self, index: usize, width: u32) -> Option<&ImageData> {
+        let stale = match &self.rendered {
+            Some((page, rendered_width, _)) => (*page, *rendered_width) != (index, width),
+            None => true,
+        };
+        if stale {
+            self.rendered = None;
+            let page = self.document.pages().get(index as _).ok()?;
+            let config = PdfRenderConfig::new().set_target_width(width as _);
+            let image = page.render_with_config(&config).map(|bitmap| {
+                let (w, h) = (bitmap.width() as usize, bitmap.height() as usize);
+                ImageData::from_raw(bitmap.as_rgba_bytes(), w, h, PixelFormat::Rgba)
+            });
+            match image {
+                Ok(image) => self.rendered = Some((index, width, image)),
+                Err(e) => error!("drawing page {} of the PDF failed: {}", index + 1, e),
+            }
+        }
+        self.rendered.as_ref().map(|(_, _, image)| image)
+    }
+}
+
+/// The character nearest to `pos`, in points.
+fn glyph_at(glyphs: &[Glyph], pos: Point) -> Option<usize> {
+    let distance = |rect: &Rect| {
+        let x = pos.x.max(rect.x0).min(rect.x1);
+        let y = pos.y.max(rect.y0).min(rect.y1);
+        // characters on the same line are nearer than those above and below
+        (pos.x - x).abs() + (pos.y - y).abs() * 4.0
+    };
+    glyphs
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(&a.rect).partial_cmp(&distance(&b.rect)).unwrap())
+        .map(|(index, _)| index)
+}
+
+/// The characters from one selected character to another, in order.
+fn selected(selection: (usize, usize), len: usize) -> Range<usize> {
+    let (start, end) = selection;
+    start.min(end)..(start.max(end) + 1).min(len)
+}
+
+fn selected_text(glyphs: &[Glyph], range: Range<usize>) -> String {
+    glyphs[range].iter().map(|glyph| glyph.c).collect()
+}
+
+impl Widget<usize> for PdfView {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut usize, _env: &Env) {
+        match event {
+            Event::MouseDown(mouse) => {
+                ctx.request_focus();
+                ctx.set_active(true);
+                let scale = self.scale();
+                let glyph = self.page.as_ref().and_then(|page| {
+                    glyph_at(&page.glyphs, (mouse.pos.to_vec2() / scale).to_point())
+                });
+                self.selection = glyph.map(|glyph| (glyph, glyph));
+                ctx.request_paint();
+            }
+            Event::MouseMoved(mouse) if ctx.is_active() => {
+                let scale = self.scale();
+                if let (Some(page), Some((_, end))) = (&self.page, &mut self.selection) {
+                    if let Some(glyph) =
+                        glyph_at(&page.glyphs, (mouse.pos.to_vec2() / scale).to_point())
+                    {
+                        *end = glyph;
+                    }
+                }
+                ctx.request_paint();
+            }
+            Event::MouseUp(_) if ctx.is_active() => ctx.set_active(false),
+            Event::Command(cmd) if cmd.selector == commands::COPY && ctx.has_focus() => {
+                if let (Some(page), Some(selection)) = (&self.page, self.selection) {
+                    let range = selected(selection, page.glyphs.len());
+                    Application::clipboard().put_string(selected_text(&page.glyphs, range));
+                }
+                ctx.set_handled();
+            }
+            Event::Wheel(wheel) if wheel.mods.ctrl => {
+                let steps = -wheel.delta.y / 120.0;
+                self.set_zoom(ctx, self.zoom * ZOOM_STEP.powf(steps));
+                ctx.set_handled();
+            }
+            Event::KeyDown(key) => {
+                let last = self.page_count.saturating_sub(1);
+                let page = match key.key_code {
+                    KeyCode::PageDown | KeyCode::ArrowRight | KeyCode::ArrowDown => {
+                        (*data + 1).min(last)
+                    }
+                    KeyCode::PageUp | KeyCode::ArrowLeft | KeyCode::ArrowUp => {
+                        data.saturating_sub(1)
+                    }
+                    KeyCode::Home => 0,
+                    KeyCode::End => last,
+                    _ => {
+                        if key.mods.ctrl {
+                            match key.text() {
+                                Some("+") | Some("=") => self.set_zoom(ctx, self.zoom * ZOOM_STEP),
+                                Some("-") => self.set_zoom(ctx, self.zoom / ZOOM_STEP),
+                                Some("0") => self.set_zoom(ctx, 1.0),
+                                _ => return,
+                            }
+                            ctx.set_handled();
+                        }
+                        return;
+                    }
+                };
+                *data = page;
+                ctx.set_handled();
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &usize, _env: &Env) {
+        if let LifeCycle::WidgetAdded = event {
+            ctx.register_for_focus();
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &usize, data: &usize, _env: &Env) {
+        if old_data != data {
+            ctx.request_layout();
+            ctx.request_paint();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &usize,
+        _env: &Env,
+    ) -> Size {
+        bc.debug_check("PdfView");
+        if self.page_count == 0 {
+            return bc.min();
+        }
+        self.load_page((*data).min(self.page_count - 1));
+        let size = match &self.page {
+            Some(page) => page.size,
+            None => return bc.min(),
+        };
+        let scale = self.scale();
+        bc.constrain(Size::new(size.width * scale, size.height * scale))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _data: &usize, env: &Env) {
+        let (index, page_size) = match &self.page {
+            Some(page) => (page.index, page.size),
+            None => return,
+        };
+        let scale = self.scale();
+        let rect = Rect::from_origin_size(
+            Point::ORIGIN,
+            (page_size.width * scale, page_size.height * scale),
+        );
+        paint_ctx.fill(rect, &Color::WHITE);
+
+        // drawn at the resolution of the screen, so that it is sharp
+        let width = (rect.width() * paint_ctx.scale()).round().max(1.0) as u32;
+        if let Some(image) = self.render(index, width).cloned() {
+            if let Some(image) = paint_ctx.piet_image(&image) {
+                paint_ctx.draw_image(&*image, rect, InterpolationMode::Bilinear);
+            }
+        }
+
+        if let (Some(page), Some(selection)) = (&self.page, self.selection) {
+            let color = env.get(theme::SELECTION_COLOR).with_alpha(0.4);
+            for glyph in &page.glyphs[selected(selection, page.glyphs.len())] {
+                let rect = &glyph.rect;
+                let rect = Rect::new(
+                    rect.x0 * scale,
+                    rect.y0 * scale,
+                    rect.x1 * scale,
+                    rect.y1 * scale,
+                );
+                paint_ctx.fill(rect, &color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dragging_selects_the_characters_between() {
+        // "Hi" on one line, and "yo" below it
+        let glyphs: Vec<_> = [
+            ('H', 10.0, 10.0),
+            ('i', 20.0, 10.0),
+            ('y', 10.0, 30.0),
+            ('o', 20.0, 30.0),
+        ]
+        .iter()
+        .map(|&(c, x, y)| Glyph {
+            c,
+            rect: Rect::new(x, y, x + 8.0, y + 12.0),
+        })
+        .collect();
+        assert_eq!(glyph_at(&glyphs, Point::new(12.0, 15.0)), Some(0));
+        // past the end of the first line is still on it
+        assert_eq!(glyph_at(&glyphs, Point::new(60.0, 20.0)), Some(1));
+        assert_eq!(glyph_at(&glyphs, Point::new(0.0, 40.0)), Some(2));
+        assert_eq!(glyph_at(&[], Point::ORIGIN), None);
+
+        let range = selected((3, 1), glyphs.len());
+        assert_eq!(range, 1..4);
+        assert_eq!(selected_text(&glyphs, range), "iyo");
+        assert_eq!(selected_text(&glyphs, selected((2, 2), glyphs.len())), "y");
+    }
+}
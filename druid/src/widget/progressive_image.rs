@@ -0,0 +1,517 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Showing an image while its bytes are still arriving.
+
+use std::io::{self, Read};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use log::error;
+
+use crate::commands;
+use crate::piet::InterpolationMode;
+use crate::widget::{FillStrat, Image, ImageData, ImageError, PixelFormat};
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, Size,
+    UpdateCtx, Widget,
+};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Where each of the seven Adam7 passes starts, how far apart its pixels
+/// are, and so how big a block each of its pixels stands in for until the
+/// later passes arrive: `(x, y, x_step, y_step, block_width, block_height)`.
+const ADAM7: [(usize, usize, usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8, 8, 8),
+    (4, 0, 8, 8, 4, 8),
+    (0, 4, 4, 8, 4, 4),
+    (2, 0, 4, 4, 2, 4),
+    (0, 2, 2, 4, 2, 2),
+    (1, 0, 2, 2, 1, 2),
+    (0, 1, 1, 2, 1, 1),
+];
+
+/// An image being decoded as its bytes arrive, such as over the network.
+///
+/// Only PNG images are shown progressively. They are decoded as far as the
+/// bytes go, on another thread that picks up where it stopped with each
+/// [`push`]: rows of a plain PNG appear top to bottom, and an interlaced PNG
+/// appears blurry at first and gets sharper with each pass. The decoder
+/// holds back the last few tens of kilobytes until more arrive, so small
+/// images only appear at the end.
+///
+/// Every other format, progressive JPEG included, is only shown once
+/// [`finish`] is called.
+///
+/// This is only available with the `progressive` feature.
+///
+/// [`push`]: #method.push
+/// [`finish`]: #method.finish
+#[derive(Default)]
+pub struct ImageStream {
+    bytes: Vec<u8>,
+    image: Option<ImageData>,
+    decoding: Decoding,
+}
+
+/// What is being done with the bytes as they arrive.
+#[derive(Default)]
+enum Decoding {
+    /// Not enough bytes have arrived to tell the format.
+    #[default]
+    Undecided,
+    Png(PngDecoder),
+    /// Waiting for `finish`.
+    Other,
+}
+
+impl ImageStream {
+    /// Start a new, empty image.
+    pub fn new() -> Self {
+        ImageStream::default()
+    }
+
+    /// Add the next bytes of the image. Returns `true` if more of the image
+    /// can be shown.
+    pub fn push(&mut self, bytes: &[u8]) -> bool {
+        self.bytes.extend_from_slice(bytes);
+        let image = match &self.decoding {
+            Decoding::Undecided if self.bytes.len() < PNG_SIGNATURE.len() => None,
+            Decoding::Undecided if self.bytes.starts_with(PNG_SIGNATURE) => {
+                let decoder = PngDecoder::new();
+                let image = decoder.push(self.bytes.clone());
+                self.decoding = Decoding::Png(decoder);
+                image
+            }
+            Decoding::Undecided => {
+                self.decoding = Decoding::Other;
+                None
+            }
+            Decoding::Png(decoder) => decoder.push(bytes.to_vec()),
+            Decoding::Other => None,
+        };
+        match image {
+            Some(image) => {
+                self.image = Some(image);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// All of the bytes have arrived: decode the whole image.
+    pub fn finish(&mut self) -> Result<ImageData, ImageError> {
+        let image = ImageData::from_data(&self.bytes)?;
+        self.image = Some(image.clone());
+        Ok(image)
+    }
+
+    /// As much of the image as has been decoded, if any.
+    pub fn image(&self) -> Option<&ImageData> {
+        self.image.as_ref()
+    }
+}
+
+/// A PNG being decoded on another thread, as its bytes are pushed.
+struct PngDecoder {
+    input: Sender<Vec<u8>>,
+    /// Told once for each chunk of `input`, when all that can be decoded
+    /// from it has been.
+    caught_up: Receiver<()>,
+    decoded: Arc<Mutex<Decoded>>,
+}
+
+/// What a `PngDecoder` has decoded so far.
+#[derive(Default)]
+struct Decoded {
+    rgba: Vec<u8>,
+    width: usize,
+    height: usize,
+    /// Whether there is more to show since it was last looked at.
+    changed: bool,
+}
+
+/// The bytes pushed to a `PngDecoder`, as its thread reads them.
+struct Incoming {
+    chunks: Receiver<Vec<u8>>,
+    caught_up: Sender<()>,
+    chunk: Vec<u8>,
+    read: usize,
+    /// Whether `chunk` came from `chunks`, and so has to be answered.
+    pushed: bool,
+}
+
+impl PngDecoder {
+    fn new() -> Self {
+        let (input, chunks) = mpsc::channel();
+        let (done, caught_up) = mpsc::channel();
+        let decoded = Arc::new(Mutex::new(Decoded::default()));
+        let incoming = Incoming {
+            chunks,
+            caught_up: done,
+            chunk: Vec::new(),
+            read: 0,
+            pushed: false,
+        };
+        let shared = decoded.clone();
+        // until the image ends or is broken, or the stream is dropped
+        thread::spawn(move || decode_png(incoming, &shared));
+        PngDecoder {
+            input,
+            caught_up,
+            decoded,
+        }
+    }
+
+    /// Decode `bytes`, returning the image if more of it can be shown.
+    fn push(&self, bytes: Vec<u8>) -> Option<ImageData> {
+        // if the thread has stopped, neither of these waits
+        if self.input.send(bytes).is_ok() {
+            let _ = self.caught_up.recv();
+        }
+        let mut decoded = self.decoded.lock().ok()?;
+        if !decoded.changed {
+            return None;
+        }
+        decoded.changed = false;
+        let rgba = decoded.rgba.clone();
+        Some(ImageData::from_raw(
+            rgba,
+            decoded.width,
+            decoded.height,
+            PixelFormat::Rgba,
+        ))
+    }
+}
+
+impl Read for Incoming {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.read == self.chunk.len() {
+            if self.pushed {
+                let _ = self.caught_up.send(());
+            }
+            match self.chunks.recv() {
+                Ok(chunk) => self.chunk = chunk,
+                // the stream was dropped
+                Err(_) => return Ok(0),
+            }
+            self.read = 0;
+            self.pushed = true;
+        }
+        let count = buf.len().min(self.chunk.len() - self.read);
+        buf[..count].copy_from_slice(&self.chunk[self.read..self.read + count]);
+        self.read += count;
+        Ok(count)
+    }
+}
+
+/// Decode a PNG into `decoded` as its bytes arrive, with the pixels that
+/// haven't arrived yet left transparent.
+fn decode_png(bytes: impl Read, decoded: &Mutex<Decoded>) {
+    let (info, mut reader) = match png::Decoder::new(bytes).read_info() {
+        Ok(header) => header,
+        Err(_) => return,
+    };
+    let (width, height) = (info.width as usize, info.height as usize);
+    let channels = match reader.output_color_type() {
+        (png::ColorType::Grayscale, png::BitDepth::Eight) => 1,
+        (png::ColorType::GrayscaleAlpha, png::BitDepth::Eight) => 2,
+        (png::ColorType::RGB, png::BitDepth::Eight) => 3,
+        (png::ColorType::RGBA, png::BitDepth::Eight) => 4,
+        // the decoder expands everything else to one of these
+        _ => return,
+    };
+    match decoded.lock() {
+        Ok(mut decoded) => {
+            decoded.rgba = vec![0u8; width * height * 4];
+            decoded.width = width;
+            decoded.height = height;
+            decoded.changed = true;
+        }
+        Err(_) => return,
+    }
+    let mut next_row = 0;
+
+    // the rows stop with an error if the image is broken
+    while let Ok(Some((row, interlace))) = reader.next_interlaced_row() {
+        let mut decoded = match decoded.lock() {
+            Ok(decoded) => decoded,
+            Err(_) => return,
+        };
+        decoded.changed = true;
+        let rgba = &mut decoded.rgba;
+        let (x0, y, x_step, block_width, block_height) = match interlace {
+            Some((pass, line, _)) => {
+                let (x0, y0, x_step, y_step, bw, bh) = ADAM7[pass as usize - 1];
+                (x0, y0 + line as usize * y_step, x_step, bw, bh)
+            }
+            None => {
+                next_row += 1;
+                (0, next_row - 1, 1, 1, 1)
+            }
+        };
+        for (i, pixel) in row.chunks(channels).enumerate() {
+            let color = match *pixel {
+                [v] => [v, v, v, 255],
+                [v, a] => [v, v, v, a],
+                [r, g, b] => [r, g, b, 255],
+                [r, g, b, a] => [r, g, b, a],
+                _ => continue,
+            };
+            let x = x0 + i * x_step;
+            for block_y in y..(y + block_height).min(height) {
+                for block_x in x..(x + block_width).min(width) {
+                    let at = (block_y * width + block_x) * 4;
+                    rgba[at..at + 4].copy_from_slice(&color);
+                }
+            }
+        }
+    }
+}
+
+/// A widget that shows an [`ImageStream`], fed by commands.
+///
+/// Send the bytes of the image to this widget, as they arrive, in
+/// [`FEED_IMAGE`] commands, and then send [`FINISH_IMAGE`]. The commands can
+/// come from another thread through an [`ExtEventSink`]. The widget is
+/// repainted as more of the image can be shown.
+///
+/// This is only available with the `progressive` feature.
+///
+/// [`ImageStream`]: struct.ImageStream.html
+/// [`FEED_IMAGE`]: ../commands/constant.FEED_IMAGE.html
+/// [`FINISH_IMAGE`]: ../commands/constant.FINISH_IMAGE.html
+/// [`ExtEventSink`]: ../struct.ExtEventSink.html
+pub struct ProgressiveImage<T> {
+    stream: ImageStream,
+    image: Image<T>,
+}
+
+impl<T: Data> ProgressiveImage<T> {
+    /// Create a widget that shows nothing until bytes arrive.
+    pub fn new() -> Self {
+        ProgressiveImage {
+            stream: ImageStream::new(),
+            image: Image::new(ImageData::empty()),
+        }
+    }
+
+    /// Builder-style method for setting how the image fills the widget.
+    pub fn fill_mode(mut self, fill: FillStrat) -> Self {
+        self.image = self.image.fill_mode(fill);
+        self
+    }
+
+    /// Builder-style method for setting how the image is sampled when it is
+    /// scaled.
+    pub fn interpolation_mode(mut self, interpolation: InterpolationMode) -> Self {
+        self.image = self.image.interpolation_mode(interpolation);
+        self
+    }
+
+    fn show(&mut self, ctx: &mut EventCtx, image: ImageData) {
+        if image.size() != self.image.image_data().size() {
+            ctx.request_layout();
+        }
+        self.image.set_image_data(image);
+        ctx.request_paint();
+    }
+}
+
+impl<T: Data> Default for ProgressiveImage<T> {
+    fn default() -> Self {
+        ProgressiveImage::new()
+    }
+}
+
+impl<T: Data> Widget<T> for ProgressiveImage<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut T, _env: &Env) {
+        if let Event::Command(cmd) = event {
+            if cmd.selector == commands::FEED_IMAGE {
+                if let Ok(bytes) = cmd.get_object::<Vec<u8>>() {
+                    if self.stream.push(bytes) {
+                        let image = self.stream.image().cloned().unwrap_or_default();
+                        self.show(ctx, image);
+                    }
+                }
+                ctx.set_handled();
+            } else if cmd.selector == commands::FINISH_IMAGE {
+                match self.stream.finish() {
+                    Ok(image) => self.show(ctx, image),
+                    Err(e) => error!("failed to decode image: {}", e),
+                }
+                ctx.set_handled();
+            }
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.image.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        self.image.update(ctx, old_data, data, env)
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        self.image.layout(ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.image.paint(ctx, data, env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: usize = 256;
+
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in bytes {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    fn chunk(out: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = out.len();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(data);
+        let crc = crc32(&out[start..]);
+        out.extend_from_slice(&crc.to_be_bytes());
+    }
+
+    /// An RGB PNG, red on the top half and blue on the bottom, with its rows
+    /// stored uncompressed, so that the bytes arrive at a steady rate.
+    fn two_tone_png(interlaced: bool) -> Vec<u8> {
+        let mut rows = Vec::new();
+        let passes: &[_] = if interlaced {
+            &ADAM7
+        } else {
+            &[(0, 0, 1, 1, 1, 1)]
+        };
+        for &(x0, y0, x_step, y_step, _, _) in passes {
+            for y in (y0..SIZE).step_by(y_step) {
+                // no filter
+                rows.push(0);
+                for _ in (x0..SIZE).step_by(x_step) {
+                    rows.extend_from_slice(if y < SIZE / 2 {
+                        &[255, 0, 0]
+                    } else {
+                        &[0, 0, 255]
+                    });
+                }
+            }
+        }
+
+        // a zlib stream of stored blocks
+        let mut stream = vec![0x78, 0x01];
+        let blocks = rows.chunks(0xffff).collect::<Vec<_>>();
+        for (i, block) in blocks.iter().enumerate() {
+            stream.push((i == blocks.len() - 1) as u8);
+            stream.extend_from_slice(&(block.len() as u16).to_le_bytes());
+            stream.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+            stream.extend_from_slice(block);
+        }
+        let (mut a, mut b) = (1u32, 0u32);
+        for &byte in &rows {
+            a = (a + byte as u32) % 65521;
+            b = (b + a) % 65521;
+        }
+        stream.extend_from_slice(&((b << 16) | a).to_be_bytes());
+
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&(SIZE as u32).to_be_bytes());
+        ihdr.extend_from_slice(&(SIZE as u32).to_be_bytes());
+        // 8 bits, RGB, deflate, standard filters, and the interlace method
+        ihdr.extend_from_slice(&[8, 2, 0, 0, interlaced as u8]);
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        chunk(&mut png, b"IHDR", &ihdr);
+        for idat in stream.chunks(8192) {
+            chunk(&mut png, b"IDAT", idat);
+        }
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    fn pixel(image: &ImageData, x: usize, y: usize) -> [u8; 4] {
+        let at = (y * SIZE + x) * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&image.pixels()[at..at + 4]);
+        pixel
+    }
+
+    #[test]
+    fn rows_appear_as_bytes_arrive() {
+        let png = two_tone_png(false);
+        let mut stream = ImageStream::new();
+        assert!(!stream.push(&png[..20]));
+        assert!(stream.image().is_none());
+
+        assert!(stream.push(&png[20..png.len() / 2]));
+        let image = stream.image().unwrap();
+        assert_eq!(image.size(), Size::new(SIZE as f64, SIZE as f64));
+        assert_eq!(pixel(image, 3, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(image, 3, SIZE - 1), [0, 0, 0, 0]);
+
+        stream.push(&png[png.len() / 2..]);
+        let image = stream.finish().unwrap();
+        assert_eq!(pixel(&image, SIZE - 1, SIZE - 1), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn interlaced_image_gets_sharper() {
+        let png = two_tone_png(true);
+        let mut stream = ImageStream::new();
+        // the first five passes, but not the last two
+        assert!(stream.push(&png[..png.len() * 6 / 10]));
+        let image = stream.image().unwrap();
+        // filled in from the pixel up and to the left
+        assert_eq!(pixel(image, 1, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(image, SIZE - 1, SIZE - 1), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn small_pushes_pick_up_where_they_stopped() {
+        let png = two_tone_png(false);
+        let mut stream = ImageStream::new();
+        for bytes in png.chunks(100) {
+            stream.push(bytes);
+        }
+        let image = stream.image().unwrap();
+        assert_eq!(pixel(image, SIZE - 1, SIZE - 1), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn other_formats_wait_for_finish() {
+        let mut stream = ImageStream::new();
+        assert!(!stream.push(b"GIF89a"));
+        assert!(stream.image().is_none());
+        assert!(stream.finish().is_err());
+    }
+}
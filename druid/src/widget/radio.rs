@@ -16,10 +16,10 @@
 
 use crate::kurbo::{Circle, Point, Rect, Size};
 use crate::theme;
-use crate::widget::{Align, Flex, Label, LabelText, Padding};
+use crate::widget::{Align, Flex, IdentityWrapper, Label, LabelText, Padding};
 use crate::{
-    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, LinearGradient,
-    PaintCtx, RenderContext, UnitPoint, UpdateCtx, Widget, WidgetPod,
+    BoxConstraints, Data, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, LifeCycleCtx,
+    LinearGradient, PaintCtx, RenderContext, UnitPoint, UpdateCtx, Widget, WidgetId, WidgetPod,
 };
 
 /// A group of radio buttons
@@ -28,15 +28,76 @@ pub struct RadioGroup;
 
 impl RadioGroup {
     /// Given a vector of `(label_text, enum_variant)` tuples, create a group of Radio buttons
+    ///
+    /// When one of the buttons has focus, the arrow keys move the selection
+    /// (and the focus) to the next or previous button in the group.
     pub fn new<T: Data + PartialEq>(
         variants: impl IntoIterator<Item = (impl Into<LabelText<T>> + 'static, T)>,
     ) -> impl Widget<T> {
         let mut col = Flex::column();
+        let mut ids = Vec::new();
         for (label, variant) in variants.into_iter() {
-            let radio = Radio::new(label, variant);
+            let id = WidgetId::next();
+            let radio = Radio::raw(label, variant.clone());
+            let radio = Align::vertical(UnitPoint::LEFT, IdentityWrapper::wrap(radio, id));
             col.add_child(Padding::new(5.0, radio), 0.0);
+            ids.push((id, variant));
         }
-        col
+        RadioGroupInner {
+            variants: ids,
+            inner: col,
+        }
+    }
+}
+
+/// The widget behind a `RadioGroup`, which handles arrow key navigation.
+struct RadioGroupInner<T> {
+    variants: Vec<(WidgetId, T)>,
+    inner: Flex<T>,
+}
+
+impl<T: Data + PartialEq> Widget<T> for RadioGroupInner<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        self.inner.event(ctx, event, data, env);
+        if ctx.is_handled() || self.variants.is_empty() {
+            return;
+        }
+        if let Event::KeyDown(key) = event {
+            let len = self.variants.len();
+            let delta = match key.key_code {
+                KeyCode::ArrowDown | KeyCode::ArrowRight => 1,
+                KeyCode::ArrowUp | KeyCode::ArrowLeft => len - 1,
+                _ => return,
+            };
+            // start from the focused button, falling back to the selected one
+            let current = self
+                .variants
+                .iter()
+                .position(|(id, _)| ctx.focus_widget == Some(*id))
+                .or_else(|| self.variants.iter().position(|(_, v)| v == data));
+            if let Some(idx) = current {
+                let (id, variant) = &self.variants[(idx + delta) % len];
+                *data = variant.clone();
+                ctx.set_focus(*id);
+                ctx.set_handled();
+            }
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.inner.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        self.inner.update(ctx, old_data, data, env)
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        self.inner.layout(ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.inner.paint(paint_ctx, data, env)
     }
 }
 
@@ -49,11 +110,14 @@ pub struct Radio<T> {
 impl<T: Data + PartialEq> Radio<T> {
     /// Create a lone Radio button from label text and an enum variant
     pub fn new(label: impl Into<LabelText<T>>, variant: T) -> impl Widget<T> {
-        let radio = Self {
+        Align::vertical(UnitPoint::LEFT, Self::raw(label, variant))
+    }
+
+    fn raw(label: impl Into<LabelText<T>>, variant: T) -> Self {
+        Radio {
             variant,
             child_label: WidgetPod::new(Label::new(label)).boxed(),
-        };
-        Align::vertical(UnitPoint::LEFT, radio)
+        }
     }
 }
 
@@ -62,6 +126,7 @@ impl<T: Data + PartialEq> Widget<T> for Radio<T> {
         match event {
             Event::MouseDown(_) => {
                 ctx.set_active(true);
+                ctx.request_focus();
                 ctx.request_paint();
             }
             Event::MouseUp(_) => {
@@ -73,13 +138,22 @@ impl<T: Data + PartialEq> Widget<T> for Radio<T> {
                     ctx.request_paint();
                 }
             }
+            Event::KeyDown(key)
+                if key.key_code == KeyCode::Space || key.key_code == KeyCode::Return =>
+            {
+                *data = self.variant.clone();
+                ctx.set_handled();
+                ctx.request_paint();
+            }
             _ => (),
         }
     }
 
     fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &T, _env: &Env) {
-        if let LifeCycle::HotChanged(_) = event {
-            ctx.request_paint();
+        match event {
+            LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            LifeCycle::HotChanged(_) | LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
         }
     }
 
@@ -134,6 +208,7 @@ impl<T: Data + PartialEq> Widget<T> for Radio<T> {
         };
 
         paint_ctx.stroke(circle, &border_color, 1.);
+        paint_ctx.stroke_focus_ring(circle, env);
 
         // Check if data enum matches our variant
         if *data == self.variant {
@@ -16,13 +16,16 @@
 
 use log::error;
 use std::f64::INFINITY;
+use std::rc::Rc;
 use std::time::{Duration, Instant};
 
-use crate::kurbo::{Affine, Point, Rect, RoundedRect, Size, Vec2};
+use crate::kurbo::{Affine, Insets, Point, Rect, RoundedRect, Size, Vec2};
 use crate::theme;
+use crate::widget::Axis;
 use crate::{
-    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
-    RenderContext, TimerToken, UpdateCtx, Widget, WidgetPod,
+    BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx, Lens, LifeCycle, LifeCycleCtx,
+    LinearGradient, PaintCtx, RenderContext, Selector, TimerToken, UnitPoint, UpdateCtx, Widget,
+    WidgetPod,
 };
 
 #[derive(Debug, Clone)]
@@ -99,18 +102,67 @@ impl ScrollBarsState {
     }
 }
 
+/// How long after the last wheel event a gesture is over, and the next one
+/// can pick a new axis to lock to.
+const AXIS_LOCK_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// When to ask for more content.
+struct LoadMore {
+    selector: Selector,
+    distance: f64,
+    /// The length of the content when the command was last sent.
+    sent_at: Option<f64>,
+    /// Whether to check for the end once the content is laid out.
+    check: bool,
+}
+
+type PutOffset<T> = Box<dyn Fn(&mut T, Vec2)>;
+
+/// Where the scroll offset is kept in the data.
+struct OffsetLens<T> {
+    get: Box<dyn Fn(&T) -> Vec2>,
+    put: PutOffset<T>,
+}
+
 /// A container that scrolls its contents.
 ///
 /// This container holds a single child, and uses the wheel to scroll it
 /// when the child's bounds are larger than the viewport.
 ///
 /// The child is laid out with completely unconstrained layout bounds.
+///
+/// For content that is loaded a page at a time, [`load_more`] asks for
+/// more as the end comes into view, and a [`footer`] can show that it's
+/// on its way.
+///
+/// [`edge_fades`] show where there is more to scroll to.
+///
+/// The offset can be kept in the data with [`offset_lens`], such as to
+/// scroll two panes together or to remember where the user was, and
+/// [`notify_offset`] tells the rest of the app when it changes.
+///
+/// [`load_more`]: #method.load_more
+/// [`footer`]: #method.footer
+/// [`edge_fades`]: #method.edge_fades
+/// [`offset_lens`]: #method.offset_lens
+/// [`notify_offset`]: #method.notify_offset
 pub struct Scroll<T, W> {
     child: WidgetPod<T, W>,
+    footer: Option<WidgetPod<T, Box<dyn Widget<T>>>>,
+    /// The size of the child and the footer.
     child_size: Size,
     scroll_offset: Vec2,
     direction: ScrollDirection,
     scroll_bars: ScrollBarsState,
+    load_more: Option<LoadMore>,
+    axis_lock: bool,
+    edge_fades: bool,
+    offset_lens: Option<OffsetLens<T>>,
+    notify_offset: Option<Selector>,
+    /// The offset last put in the data, or sent with `notify_offset`.
+    reported_offset: Vec2,
+    /// Whether the current wheel gesture is vertical, and when it last moved.
+    gesture: Option<(bool, Instant)>,
 }
 
 impl<T, W: Widget<T>> Scroll<T, W> {
@@ -122,24 +174,130 @@ impl<T, W: Widget<T>> Scroll<T, W> {
     pub fn new(child: W) -> Scroll<T, W> {
         Scroll {
             child: WidgetPod::new(child),
+            footer: None,
             child_size: Default::default(),
             scroll_offset: Vec2::new(0.0, 0.0),
             direction: ScrollDirection::All,
             scroll_bars: ScrollBarsState::default(),
+            load_more: None,
+            axis_lock: false,
+            edge_fades: false,
+            offset_lens: None,
+            notify_offset: None,
+            reported_offset: Vec2::ZERO,
+            gesture: None,
         }
     }
 
     /// Limit scroll behavior to allow only vertical scrolling (Y-axis).
     /// The child is laid out with constrained width and infinite height.
+    ///
+    /// This also turns on [`axis_lock`].
+    ///
+    /// [`axis_lock`]: #method.axis_lock
     pub fn vertical(mut self) -> Self {
         self.direction = ScrollDirection::Vertical;
+        self.axis_lock = true;
         self
     }
 
     /// Limit scroll behavior to allow only horizontal scrolling (X-axis).
     /// The child is laid out with constrained height and infinite width.
+    ///
+    /// This also turns on [`axis_lock`].
+    ///
+    /// [`axis_lock`]: #method.axis_lock
     pub fn horizontal(mut self) -> Self {
         self.direction = ScrollDirection::Horizontal;
+        self.axis_lock = true;
+        self
+    }
+
+    /// Whether each wheel gesture only scrolls along the axis it mostly
+    /// moves in.
+    ///
+    /// Whatever a `Scroll` doesn't use of a wheel event goes to the `Scroll`
+    /// around it, so when the inner one reaches its edge, the outer one
+    /// carries on. With the axis locked, a horizontal list inside a vertical
+    /// page doesn't take the sideways wobble of scrolling down the page, and
+    /// the page gets all of it.
+    pub fn axis_lock(mut self, lock: bool) -> Self {
+        self.axis_lock = lock;
+        self
+    }
+
+    /// Whether to fade out the content at each edge of the viewport that
+    /// there is more content beyond, so that it's clear there is more to
+    /// scroll to.
+    ///
+    /// The fades are drawn in [`SCROLL_FADE_COLOR`], which is the window
+    /// background by default; a dark translucent color makes them look like
+    /// shadows instead. They are [`SCROLL_FADE_LENGTH`] long, or shorter when
+    /// there is less than that left to scroll.
+    ///
+    /// [`SCROLL_FADE_COLOR`]: ../theme/constant.SCROLL_FADE_COLOR.html
+    /// [`SCROLL_FADE_LENGTH`]: ../theme/constant.SCROLL_FADE_LENGTH.html
+    pub fn edge_fades(mut self, fades: bool) -> Self {
+        self.edge_fades = fades;
+        self
+    }
+
+    /// Keep the scroll offset in the data at `lens`.
+    ///
+    /// The offset is put there when the user scrolls, and changing it in
+    /// the data scrolls to it, so two `Scroll`s with the same lens move
+    /// together. The offset in the data when the `Scroll` is added is where
+    /// it starts out.
+    pub fn offset_lens(mut self, lens: impl Lens<T, Vec2> + 'static) -> Self
+    where
+        T: 'static,
+    {
+        let lens = Rc::new(lens);
+        let get = {
+            let lens = lens.clone();
+            Box::new(move |data: &T| lens.with(data, |offset| *offset))
+        };
+        let put = Box::new(move |data: &mut T, offset| lens.with_mut(data, |old| *old = offset));
+        self.offset_lens = Some(OffsetLens { get, put });
+        self
+    }
+
+    /// Submit a command with `selector` each time the scroll offset changes,
+    /// with the new offset, a [`Vec2`], as its argument.
+    ///
+    /// [`Vec2`]: ../kurbo/struct.Vec2.html
+    pub fn notify_offset(mut self, selector: Selector) -> Self {
+        self.notify_offset = Some(selector);
+        self
+    }
+
+    /// Submit a command with `selector` when the viewport comes within
+    /// `distance` of the end of the content, so that the app can add the
+    /// next page of it.
+    ///
+    /// The command is sent to the window once, and is sent again when the
+    /// viewport nears the end after the content has grown. When the content
+    /// doesn't fill the viewport, it is sent straight away.
+    pub fn load_more(mut self, selector: Selector, distance: f64) -> Self {
+        self.load_more = Some(LoadMore {
+            selector,
+            distance,
+            sent_at: None,
+            check: true,
+        });
+        self
+    }
+
+    /// Show `footer` after the content, such as a [`Spinner`] in an
+    /// [`Either`] while more content is loading.
+    ///
+    /// The footer is below the content, or to the right of it when scrolling
+    /// horizontally, and is as wide (or as tall) as the content.
+    ///
+    /// [`Spinner`]: struct.Spinner.html
+    /// [`Either`]: struct.Either.html
+    pub fn footer(mut self, footer: impl Widget<T> + 'static) -> Self {
+        self.footer = Some(WidgetPod::new(Box::new(footer)));
         self
     }
 
@@ -168,6 +326,161 @@ impl<T, W: Widget<T>> Scroll<T, W> {
         }
     }
 
+    /// Scroll as little as possible to show `area`, which is in the child's
+    /// coordinates. If `area` doesn't fit, its top left corner is shown.
+    ///
+    /// Returns `true` if the scroll has been updated.
+    pub fn scroll_to_view(&mut self, area: Rect, size: Size) -> bool {
+        let delta = |start: f64, end: f64, offset: f64, len: f64| {
+            if start < offset {
+                start - offset
+            } else if end > offset + len {
+                (end - offset - len).min(start - offset)
+            } else {
+                0.0
+            }
+        };
+        let offset = self.scroll_offset;
+        let dx = delta(area.x0, area.x1, offset.x, size.width);
+        let dy = delta(area.y0, area.y1, offset.y, size.height);
+        self.scroll(Vec2::new(dx, dy), size)
+    }
+
+    /// Handle a request from a descendant to be scrolled into view, leaving
+    /// the part of it that's visible here in our coordinates, for any
+    /// `Scroll` further out.
+    fn handle_scroll_request(&mut self, request: &mut Option<Rect>, size: Size) -> bool {
+        let area = match *request {
+            Some(area) => area,
+            None => return false,
+        };
+        let changed = self.scroll_to_view(area, size);
+        let viewport = Rect::from_origin_size(Point::ORIGIN, size);
+        *request = Some((area - self.scroll_offset).intersect(viewport));
+        changed
+    }
+
+    /// The part of a wheel `delta` along the axis of the gesture it is in.
+    fn lock_axis(&mut self, delta: Vec2, now: Instant) -> Vec2 {
+        let vertical = match self.gesture {
+            Some((vertical, last)) if now.duration_since(last) < AXIS_LOCK_TIMEOUT => vertical,
+            _ => delta.y.abs() >= delta.x.abs(),
+        };
+        self.gesture = Some((vertical, now));
+        if vertical {
+            Vec2::new(0.0, delta.y)
+        } else {
+            Vec2::new(delta.x, 0.0)
+        }
+    }
+
+    /// Scroll by as much of a wheel `delta` as we can, returning the rest.
+    fn scroll_wheel(&mut self, delta: Vec2, size: Size, now: Instant) -> Vec2 {
+        let allowed = if self.axis_lock {
+            self.lock_axis(delta, now)
+        } else {
+            delta
+        };
+        let before = self.scroll_offset;
+        self.scroll(allowed, size);
+        delta - (self.scroll_offset - before)
+    }
+
+    /// The selector of the command to submit, if the viewport of `size` is
+    /// near the end of new content.
+    fn check_load_more(&mut self, size: Size) -> Option<Selector> {
+        let load_more = self.load_more.as_mut()?;
+        let (offset, len, content) = match self.direction {
+            ScrollDirection::Horizontal => {
+                (self.scroll_offset.x, size.width, self.child_size.width)
+            }
+            _ => (self.scroll_offset.y, size.height, self.child_size.height),
+        };
+        if offset + len + load_more.distance >= content && load_more.sent_at != Some(content) {
+            load_more.sent_at = Some(content);
+            Some(load_more.selector.clone())
+        } else {
+            None
+        }
+    }
+
+    /// How much content is out of view past each edge of a viewport of
+    /// `size`.
+    fn hidden_content(&self, size: Size) -> Insets {
+        let offset = self.scroll_offset;
+        Insets::new(
+            offset.x,
+            offset.y,
+            (self.child_size.width - size.width - offset.x).max(0.0),
+            (self.child_size.height - size.height - offset.y).max(0.0),
+        )
+    }
+
+    /// Draw a fade at each edge of `visible` with content beyond it.
+    fn draw_fades(&self, paint_ctx: &mut PaintCtx, visible: Rect, env: &Env) {
+        let hidden = self.hidden_content(visible.size());
+        let length = env.get(theme::SCROLL_FADE_LENGTH);
+        let color = env.get(theme::SCROLL_FADE_COLOR);
+        let colors = (color.clone(), color.with_alpha(0.0));
+        let v = visible;
+        let (left, top) = (length.min(hidden.x0), length.min(hidden.y0));
+        let (right, bottom) = (length.min(hidden.x1), length.min(hidden.y1));
+        let fades = [
+            (
+                Rect::new(v.x0, v.y0, v.x0 + left, v.y1),
+                UnitPoint::LEFT,
+                UnitPoint::RIGHT,
+            ),
+            (
+                Rect::new(v.x0, v.y0, v.x1, v.y0 + top),
+                UnitPoint::TOP,
+                UnitPoint::BOTTOM,
+            ),
+            (
+                Rect::new(v.x1 - right, v.y0, v.x1, v.y1),
+                UnitPoint::RIGHT,
+                UnitPoint::LEFT,
+            ),
+            (
+                Rect::new(v.x0, v.y1 - bottom, v.x1, v.y1),
+                UnitPoint::BOTTOM,
+                UnitPoint::TOP,
+            ),
+        ];
+        for &(rect, start, end) in &fades {
+            if rect.area() <= 0.0 {
+                continue;
+            }
+            let gradient = LinearGradient::new(start, end, colors.clone());
+            paint_ctx.fill(rect, &gradient);
+        }
+    }
+
+    /// Scroll to the offset in the data, if it has been changed there.
+    ///
+    /// Returns `true` if the scroll has been updated.
+    fn follow_data(&mut self, data: &T, size: Size) -> bool {
+        let offset = match &self.offset_lens {
+            Some(lens) => (lens.get)(data),
+            None => return false,
+        };
+        if (offset - self.reported_offset).hypot2() <= 1e-12 {
+            return false;
+        }
+        let changed = self.scroll(offset - self.scroll_offset, size);
+        self.reported_offset = offset;
+        changed
+    }
+
+    /// The offset to report, if it has changed since it last was.
+    fn offset_to_report(&mut self) -> Option<Vec2> {
+        if (self.scroll_offset - self.reported_offset).hypot2() <= 1e-12 {
+            return None;
+        }
+        self.reported_offset = self.scroll_offset;
+        Some(self.scroll_offset)
+    }
+
     /// Makes the scrollbars visible, and resets the fade timer.
     pub fn reset_scrollbar_fade(&mut self, ctx: &mut EventCtx, env: &Env) {
         // Display scroll bars and schedule their disappearance
@@ -182,6 +495,30 @@ impl<T, W: Widget<T>> Scroll<T, W> {
         self.scroll_offset
     }
 
+    /// Ask for more content if needed, once the content is laid out after it
+    /// changed.
+    fn check_end_after_layout(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle) {
+        match event {
+            LifeCycle::WidgetAdded if self.load_more.is_some() => ctx.request_anim_frame(),
+            LifeCycle::AnimFrame(_) => {
+                let load_more = match self.load_more.as_mut() {
+                    Some(load_more) if load_more.check => load_more,
+                    _ => return,
+                };
+                if ctx.base_state.needs_layout {
+                    // wait for the next frame, after the content is laid out
+                    ctx.request_anim_frame();
+                    return;
+                }
+                load_more.check = false;
+                if let Some(selector) = self.check_load_more(ctx.base_state.size()) {
+                    ctx.submit_command(selector, None);
+                }
+            }
+            _ => (),
+        }
+    }
+
     fn calc_vertical_bar_bounds(&self, viewport: Rect, env: &Env) -> Rect {
         let bar_width = env.get(theme::SCROLL_BAR_WIDTH);
         let bar_pad = env.get(theme::SCROLL_BAR_PAD);
@@ -191,10 +528,15 @@ impl<T, W: Widget<T>> Scroll<T, W> {
         let top_y_offset = (scale_y * self.scroll_offset.y).ceil();
         let bottom_y_offset = (scale_y * viewport.height()).ceil() + top_y_offset;
 
-        let x0 = self.scroll_offset.x + viewport.width() - bar_width - bar_pad;
+        // the vertical bar is on the leading edge in a right-to-left layout
+        let x0 = if env.get(Env::RIGHT_TO_LEFT) {
+            self.scroll_offset.x + bar_pad
+        } else {
+            self.scroll_offset.x + viewport.width() - bar_width - bar_pad
+        };
         let y0 = self.scroll_offset.y + top_y_offset + bar_pad;
 
-        let x1 = self.scroll_offset.x + viewport.width() - bar_pad;
+        let x1 = x0 + bar_width;
         let y1 = self.scroll_offset.y + bottom_y_offset - (bar_pad * 2.) - bar_width;
 
         Rect::new(x0, y0, x1, y1)
@@ -209,10 +551,17 @@ impl<T, W: Widget<T>> Scroll<T, W> {
         let left_x_offset = (scale_x * self.scroll_offset.x).ceil();
         let right_x_offset = (scale_x * viewport.width()).ceil() + left_x_offset;
 
-        let x0 = self.scroll_offset.x + left_x_offset + bar_pad;
+        // leave room for the vertical bar, on whichever side it is drawn
+        let rtl_shift = if env.get(Env::RIGHT_TO_LEFT) {
+            bar_width + bar_pad
+        } else {
+            0.0
+        };
+
+        let x0 = self.scroll_offset.x + left_x_offset + bar_pad + rtl_shift;
         let y0 = self.scroll_offset.y + viewport.height() - bar_width - bar_pad;
 
-        let x1 = self.scroll_offset.x + right_x_offset - (bar_pad * 2.) - bar_width;
+        let x1 = self.scroll_offset.x + right_x_offset - (bar_pad * 2.) - bar_width + rtl_shift;
         let y1 = self.scroll_offset.y + viewport.height() - bar_pad;
 
         Rect::new(x0, y0, x1, y1)
@@ -257,7 +606,11 @@ impl<T, W: Widget<T>> Scroll<T, W> {
         if viewport.height() < self.child_size.height {
             // Stretch hitbox to edge of widget
             let mut bounds = self.calc_vertical_bar_bounds(viewport, &env);
-            bounds.x1 = self.scroll_offset.x + viewport.width();
+            if env.get(Env::RIGHT_TO_LEFT) {
+                bounds.x0 = self.scroll_offset.x;
+            } else {
+                bounds.x1 = self.scroll_offset.x + viewport.width();
+            }
             bounds.contains(pos)
         } else {
             false
@@ -351,7 +704,10 @@ impl<T: Data, W: Widget<T>> Widget<T> for Scroll<T, W> {
         } else {
             let child_event = event.transform_scroll(self.scroll_offset, viewport);
             if let Some(child_event) = child_event {
-                self.child.event(ctx, &child_event, data, env)
+                self.child.event(ctx, &child_event, data, env);
+                if let Some(footer) = self.footer.as_mut() {
+                    footer.event(ctx, &child_event, data, env);
+                }
             };
 
             match event {
@@ -365,23 +721,54 @@ impl<T: Data, W: Widget<T>> Widget<T> for Scroll<T, W> {
                 // Show the scrollbars any time our size changes
                 Event::Size(_) => self.reset_scrollbar_fade(ctx, &env),
                 Event::Timer(id) if *id == self.scroll_bars.timer_id => {
-                    // Schedule scroll bars animation
-                    ctx.request_anim_frame();
+                    if env.get(theme::REDUCE_MOTION) {
+                        // Hide the scroll bars without fading
+                        self.scroll_bars.opacity = 0.0;
+                        ctx.request_paint();
+                    } else {
+                        // Schedule scroll bars animation
+                        ctx.request_anim_frame();
+                    }
                     self.scroll_bars.timer_id = TimerToken::INVALID;
                 }
                 _ => (),
             }
         }
 
+        if self.handle_scroll_request(&mut ctx.base_state.request_scroll, size) {
+            ctx.request_paint();
+            self.reset_scrollbar_fade(ctx, env);
+        }
+
         if !ctx.is_handled() {
             if let Event::Wheel(wheel) = event {
-                if self.scroll(wheel.delta, size) {
+                // a `Scroll` inside this one may have used some of it already
+                let delta = ctx.base_state.wheel_remainder.take().unwrap_or(wheel.delta);
+                let remainder = self.scroll_wheel(delta, size, Instant::now());
+                if (remainder - delta).hypot2() > 1e-12 {
                     ctx.request_paint();
-                    ctx.set_handled();
                     self.reset_scrollbar_fade(ctx, &env);
                 }
+                if remainder.hypot2() > 1e-12 {
+                    ctx.base_state.wheel_remainder = Some(remainder);
+                } else {
+                    ctx.set_handled();
+                }
             }
         }
+
+        if let Some(offset) = self.offset_to_report() {
+            if let Some(lens) = &self.offset_lens {
+                (lens.put)(data, offset);
+            }
+            if let Some(selector) = &self.notify_offset {
+                ctx.submit_command(Command::new(selector.clone(), offset), None);
+            }
+        }
+
+        if let Some(selector) = self.check_load_more(size) {
+            ctx.submit_command(selector, None);
+        }
     }
 
     fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
@@ -397,11 +784,36 @@ impl<T: Data, W: Widget<T>> Widget<T> for Scroll<T, W> {
                 }
             }
         }
-        self.child.lifecycle(ctx, event, data, env)
+        if let (LifeCycle::WidgetAdded, Some(lens)) = (event, &self.offset_lens) {
+            // where to start; it is kept in range once we are laid out
+            self.scroll_offset = (lens.get)(data);
+            self.reported_offset = self.scroll_offset;
+        }
+        self.check_end_after_layout(ctx, event);
+        self.child.lifecycle(ctx, event, data, env);
+        if let Some(footer) = self.footer.as_mut() {
+            footer.lifecycle(ctx, event, data, env);
+        }
+
+        let size = ctx.base_state.size();
+        if self.handle_scroll_request(&mut ctx.base_state.request_scroll, size) {
+            ctx.request_paint();
+        }
     }
 
     fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
         self.child.update(ctx, data, env);
+        if let Some(footer) = self.footer.as_mut() {
+            footer.update(ctx, data, env);
+        }
+        // the content may have grown, or still not fill the viewport
+        if let Some(load_more) = self.load_more.as_mut() {
+            load_more.check = true;
+            ctx.request_anim_frame();
+        }
+        if self.follow_data(data, ctx.base_state.size()) {
+            ctx.request_paint();
+        }
     }
 
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
@@ -409,14 +821,70 @@ impl<T: Data, W: Widget<T>> Widget<T> for Scroll<T, W> {
 
         let child_bc = BoxConstraints::new(Size::ZERO, self.direction.max_size(bc));
         let size = self.child.layout(ctx, &child_bc, data, env);
-        self.child_size = size;
         self.child
             .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+        self.child_size = size;
+        if let Some(footer) = self.footer.as_mut() {
+            let horizontal = matches!(self.direction, ScrollDirection::Horizontal);
+            let max = child_bc.max();
+            let (footer_bc, origin) = if horizontal {
+                let min = Size::new(0.0, size.height);
+                (BoxConstraints::new(min, max), Point::new(size.width, 0.0))
+            } else {
+                let min = Size::new(size.width, 0.0);
+                (BoxConstraints::new(min, max), Point::new(0.0, size.height))
+            };
+            let footer_size = footer.layout(ctx, &footer_bc, data, env);
+            footer.set_layout_rect(Rect::from_origin_size(origin, footer_size));
+            self.child_size = if horizontal {
+                Size::new(
+                    size.width + footer_size.width,
+                    size.height.max(footer_size.height),
+                )
+            } else {
+                Size::new(
+                    size.width.max(footer_size.width),
+                    size.height + footer_size.height,
+                )
+            };
+        }
         let self_size = bc.constrain(self.child_size);
         let _ = self.scroll(Vec2::new(0.0, 0.0), self_size);
         self_size
     }
 
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let scrolls = match self.direction {
+            ScrollDirection::Horizontal => axis == Axis::Horizontal,
+            ScrollDirection::Vertical => axis == Axis::Vertical,
+            ScrollDirection::All => true,
+        };
+        if scrolls {
+            // the content can be scrolled to instead
+            0.0
+        } else {
+            self.child.min_intrinsic(ctx, axis, cross, data, env)
+        }
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        self.child.max_intrinsic(ctx, axis, cross, data, env)
+    }
+
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
         if let Err(e) = paint_ctx.save() {
             error!("saving render context failed: {:?}", e);
@@ -427,8 +895,18 @@ impl<T: Data, W: Widget<T>> Widget<T> for Scroll<T, W> {
         paint_ctx.transform(Affine::translate(-self.scroll_offset));
 
         let visible = viewport.with_origin(self.scroll_offset.to_point());
-        paint_ctx.with_child_ctx(visible, |ctx| self.child.paint(ctx, data, env));
+        let footer = &mut self.footer;
+        let child = &mut self.child;
+        paint_ctx.with_child_ctx(visible, |ctx| {
+            child.paint(ctx, data, env);
+            if let Some(footer) = footer.as_mut() {
+                footer.paint_with_offset(ctx, data, env);
+            }
+        });
 
+        if self.edge_fades {
+            self.draw_fades(paint_ctx, visible, env);
+        }
         self.draw_bars(paint_ctx, viewport, env);
 
         if let Err(e) = paint_ctx.restore() {
@@ -436,3 +914,120 @@ impl<T: Data, W: Widget<T>> Widget<T> for Scroll<T, W> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Label;
+
+    #[test]
+    fn requests_scroll_as_little_as_needed() {
+        let mut scroll = Scroll::<(), _>::new(Label::new("rows")).vertical();
+        scroll.child_size = Size::new(100.0, 1000.0);
+        let size = Size::new(100.0, 200.0);
+
+        // already visible
+        assert!(!scroll.scroll_to_view(Rect::new(0.0, 50.0, 100.0, 80.0), size));
+        // below the viewport, so its bottom edge lines up with ours
+        assert!(scroll.scroll_to_view(Rect::new(0.0, 400.0, 100.0, 430.0), size));
+        assert_eq!(scroll.offset(), Vec2::new(0.0, 230.0));
+        // above it, so its top edge lines up with ours
+        assert!(scroll.scroll_to_view(Rect::new(0.0, 100.0, 100.0, 130.0), size));
+        assert_eq!(scroll.offset(), Vec2::new(0.0, 100.0));
+
+        // the request is left in our coordinates for the scrolls around us
+        let mut request = Some(Rect::new(0.0, 600.0, 100.0, 630.0));
+        assert!(scroll.handle_scroll_request(&mut request, size));
+        let area = request.unwrap();
+        assert_eq!((area.y0, area.y1), (170.0, 200.0));
+    }
+
+    #[test]
+    fn fades_only_where_there_is_more_content() {
+        let mut scroll = Scroll::<(), _>::new(Label::new("rows")).edge_fades(true);
+        scroll.child_size = Size::new(100.0, 1000.0);
+        let size = Size::new(100.0, 200.0);
+
+        // at the top, there is only more below
+        let hidden = scroll.hidden_content(size);
+        assert_eq!((hidden.y0, hidden.x1, hidden.y1), (0.0, 0.0, 800.0));
+        scroll.scroll(Vec2::new(0.0, 790.0), size);
+        let hidden = scroll.hidden_content(size);
+        assert_eq!((hidden.y0, hidden.y1), (790.0, 10.0));
+        // and none at all once it reaches the end
+        scroll.scroll(Vec2::new(0.0, 50.0), size);
+        assert_eq!(scroll.hidden_content(size).y1, 0.0);
+    }
+
+    #[test]
+    fn offsets_are_shared_through_the_data() {
+        let size = Size::new(100.0, 200.0);
+        let mut left = Scroll::new(Label::new("left")).offset_lens(crate::lens::Id);
+        let mut right = Scroll::new(Label::new("right")).offset_lens(crate::lens::Id);
+        left.child_size = Size::new(100.0, 1000.0);
+        right.child_size = Size::new(100.0, 500.0);
+
+        let mut data = Vec2::ZERO;
+        left.scroll(Vec2::new(0.0, 120.0), size);
+        let offset = left.offset_to_report().unwrap();
+        (left.offset_lens.as_ref().unwrap().put)(&mut data, offset);
+        assert_eq!(left.offset_to_report(), None);
+
+        // the other pane follows, as far as it can
+        assert!(right.follow_data(&data, size));
+        assert_eq!(right.offset(), Vec2::new(0.0, 120.0));
+        data = Vec2::new(0.0, 700.0);
+        assert!(right.follow_data(&data, size));
+        assert_eq!(right.offset(), Vec2::new(0.0, 300.0));
+        // and puts back where it stopped
+        assert_eq!(right.offset_to_report(), Some(Vec2::new(0.0, 300.0)));
+    }
+
+    #[test]
+    fn wheel_deltas_go_on_to_the_outer_scroll() {
+        let size = Size::new(100.0, 200.0);
+        let now = Instant::now();
+        let mut inner = Scroll::<(), _>::new(Label::new("row")).vertical();
+        inner.child_size = Size::new(100.0, 300.0);
+
+        // the inner one reaches its end, and the rest is left over
+        let remainder = inner.scroll_wheel(Vec2::new(0.0, 150.0), size, now);
+        assert_eq!(inner.offset(), Vec2::new(0.0, 100.0));
+        assert_eq!(remainder, Vec2::new(0.0, 50.0));
+
+        // a horizontal one doesn't take the sideways part of a vertical gesture
+        let mut carousel = Scroll::<(), _>::new(Label::new("cards")).horizontal();
+        carousel.child_size = Size::new(1000.0, 200.0);
+        let remainder = carousel.scroll_wheel(Vec2::new(5.0, 40.0), size, now);
+        assert_eq!(carousel.offset(), Vec2::ZERO);
+        assert_eq!(remainder, Vec2::new(5.0, 40.0));
+        // even when it wobbles further sideways during the gesture
+        let later = now + Duration::from_millis(50);
+        carousel.scroll_wheel(Vec2::new(30.0, 10.0), size, later);
+        assert_eq!(carousel.offset(), Vec2::ZERO);
+        // but the next gesture can go sideways
+        let next_gesture = later + AXIS_LOCK_TIMEOUT;
+        carousel.scroll_wheel(Vec2::new(30.0, 10.0), size, next_gesture);
+        assert_eq!(carousel.offset(), Vec2::new(30.0, 0.0));
+    }
+
+    #[test]
+    fn more_is_loaded_once_per_page() {
+        const LOAD_MORE: Selector = Selector::new("scroll-test.load-more");
+        let mut scroll = Scroll::<(), _>::new(Label::new("rows"))
+            .vertical()
+            .load_more(LOAD_MORE, 50.0);
+        scroll.child_size = Size::new(100.0, 1000.0);
+        let size = Size::new(100.0, 200.0);
+
+        assert!(scroll.check_load_more(size).is_none());
+        scroll.scroll(Vec2::new(0.0, 760.0), size);
+        assert_eq!(scroll.check_load_more(size), Some(LOAD_MORE));
+        // not again until the next page is added
+        assert!(scroll.check_load_more(size).is_none());
+        scroll.child_size = Size::new(100.0, 2000.0);
+        assert!(scroll.check_load_more(size).is_none());
+        scroll.scroll(Vec2::new(0.0, 1000.0), size);
+        assert_eq!(scroll.check_load_more(size), Some(LOAD_MORE));
+    }
+}
@@ -17,6 +17,7 @@
 use std::f64::INFINITY;
 
 use crate::shell::kurbo::Size;
+use crate::widget::Axis;
 use crate::{
     BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
     UpdateCtx, Widget, WidgetId,
@@ -133,6 +134,44 @@ impl<T: Data> Widget<T> for SizedBox<T> {
         }
     }
 
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let fixed = match axis {
+            Axis::Horizontal => self.width,
+            Axis::Vertical => self.height,
+        };
+        match (fixed, self.inner.as_mut()) {
+            (Some(length), _) => length,
+            (None, Some(inner)) => inner.min_intrinsic(ctx, axis, cross, data, env),
+            (None, None) => 0.0,
+        }
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let fixed = match axis {
+            Axis::Horizontal => self.width,
+            Axis::Vertical => self.height,
+        };
+        match (fixed, self.inner.as_mut()) {
+            (Some(length), _) => length,
+            (None, Some(inner)) => inner.max_intrinsic(ctx, axis, cross, data, env),
+            (None, None) => 0.0,
+        }
+    }
+
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
         if let Some(ref mut inner) = self.inner {
             inner.paint(paint_ctx, data, env);
@@ -18,10 +18,13 @@ use crate::kurbo::{Circle, Point, Rect, RoundedRect, Shape, Size};
 use crate::theme;
 use crate::widget::Align;
 use crate::{
-    BoxConstraints, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, LinearGradient,
-    PaintCtx, RenderContext, UnitPoint, UpdateCtx, Widget,
+    BoxConstraints, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, LifeCycleCtx,
+    LinearGradient, PaintCtx, RenderContext, UnitPoint, UpdateCtx, Widget,
 };
 
+// The amount an arrow key moves the slider; page up/down move ten times as far.
+const SLIDER_KEY_STEP: f64 = 0.01;
+
 /// A slider, allowing interactive update of a numeric value.
 #[derive(Debug, Clone, Default)]
 pub struct Slider {
@@ -57,6 +60,7 @@ impl Widget<f64> for Slider {
         match event {
             Event::MouseDown(mouse) => {
                 ctx.set_active(true);
+                ctx.request_focus();
                 if self.knob_hit_test(knob_size, mouse.pos) {
                     self.x_offset = self.knob_pos.x - mouse.pos.x
                 } else {
@@ -85,11 +89,31 @@ impl Widget<f64> for Slider {
                 }
                 ctx.request_paint();
             }
+            Event::KeyDown(key) => {
+                let value = match key.key_code {
+                    KeyCode::ArrowLeft | KeyCode::ArrowDown => *data - SLIDER_KEY_STEP,
+                    KeyCode::ArrowRight | KeyCode::ArrowUp => *data + SLIDER_KEY_STEP,
+                    KeyCode::PageDown => *data - SLIDER_KEY_STEP * 10.,
+                    KeyCode::PageUp => *data + SLIDER_KEY_STEP * 10.,
+                    KeyCode::Home => 0.0,
+                    KeyCode::End => 1.0,
+                    _ => return,
+                };
+                *data = value.clamp(0.0, 1.0);
+                ctx.set_handled();
+                ctx.request_paint();
+            }
             _ => (),
         }
     }
 
-    fn lifecycle(&mut self, _ctx: &mut LifeCycleCtx, _event: &LifeCycle, _data: &f64, _env: &Env) {}
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &f64, _env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
+        }
+    }
 
     fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &f64, _data: &f64, _env: &Env) {
         ctx.request_paint();
@@ -187,5 +211,6 @@ impl Widget<f64> for Slider {
 
         //Actually paint the knob
         paint_ctx.fill(knob_circle, &knob_gradient);
+        paint_ctx.stroke_focus_ring(knob_circle, env);
     }
 }
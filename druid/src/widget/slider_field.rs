@@ -0,0 +1,321 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A slider with a box for typing the value.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::widget::{Slider, TextBox};
+use crate::{
+    BoxConstraints, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    Selector, UpdateCtx, Widget, WidgetPod,
+};
+
+/// Sent by a `SliderField` to itself when its text box loses focus.
+const COMMIT_TEXT: Selector = Selector::new("druid-builtin.commit-slider-field-text");
+
+/// The width of the text box.
+const TEXTBOX_WIDTH: f64 = 60.0;
+
+/// The space between the slider and the text box.
+const SPACING: f64 = 8.0;
+
+/// A slider with a text box beside it showing the value, which can also be
+/// typed in.
+///
+/// Both edit the same `f64`, which is kept between [`min`] and [`max`] and,
+/// if there is a [`step`], to a whole number of steps from `min`. While the
+/// user types, the value follows the text whenever it is a number in range;
+/// when they press Enter or move the focus away, the value is clamped and
+/// snapped and the text is rewritten to show it. The arrow keys move a
+/// focused slider by a step, or by a hundredth of the range if there is no
+/// step, and Page Up and Page Down move it ten times as far.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::SliderField;
+/// use druid::Widget;
+///
+/// fn volume() -> impl Widget<f64> {
+///     SliderField::new().min(0.0).max(100.0).step(5.0).precision(0)
+/// }
+/// ```
+///
+/// [`min`]: #method.min
+/// [`max`]: #method.max
+/// [`step`]: #method.step
+pub struct SliderField {
+    min: f64,
+    max: f64,
+    step: Option<f64>,
+    precision: usize,
+    slider: WidgetPod<f64, Slider>,
+    textbox: WidgetPod<String, TextBox>,
+    /// What is in the text box, which is only the value when not editing.
+    text: String,
+    slider_focused: bool,
+    editing: bool,
+}
+
+impl SliderField {
+    /// Create a slider field from 0 to 1, with no step, showing two decimal
+    /// places.
+    pub fn new() -> Self {
+        SliderField {
+            min: 0.0,
+            max: 1.0,
+            step: None,
+            precision: 2,
+            slider: WidgetPod::new(Slider::default()),
+            textbox: WidgetPod::new(TextBox::raw()),
+            text: String::new(),
+            slider_focused: false,
+            editing: false,
+        }
+    }
+
+    /// Set the smallest value.
+    pub fn min(mut self, min: f64) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Set the largest value.
+    pub fn max(mut self, max: f64) -> Self {
+        self.max = max;
+        self
+    }
+
+    /// Keep the value to whole multiples of `step` above the minimum.
+    pub fn step(mut self, step: f64) -> Self {
+        self.step = Some(step).filter(|step| *step > 0.0);
+        self
+    }
+
+    /// Set the number of decimal places shown in the text box.
+    pub fn precision(mut self, decimals: usize) -> Self {
+        self.precision = decimals;
+        self
+    }
+
+    /// `value` clamped to the range and snapped to the step.
+    fn constrain(&self, value: f64) -> f64 {
+        let value = match self.step {
+            Some(step) => self.min + ((value - self.min) / step).round() * step,
+            None => value,
+        };
+        value.max(self.min).min(self.max)
+    }
+
+    /// How far along the range `value` is, for the slider.
+    fn fraction(&self, value: f64) -> f64 {
+        if self.max > self.min {
+            ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn format(&self, value: f64) -> String {
+        format!("{:.*}", self.precision, value)
+    }
+
+    /// The number in `text`, if it is one.
+    fn parse(text: &str) -> Option<f64> {
+        text.trim()
+            .parse()
+            .ok()
+            .filter(|value: &f64| value.is_finite())
+    }
+
+    /// The value the text would give once committed.
+    fn text_value(&self) -> Option<f64> {
+        Self::parse(&self.text).map(|value| self.constrain(value))
+    }
+
+    /// Take the value from the text, or put back the old value if it isn't
+    /// a number, and show it as it is stored.
+    fn commit(&mut self, data: &mut f64) {
+        if let Some(value) = self.text_value() {
+            *data = value;
+        }
+        self.text = self.format(*data);
+    }
+
+    /// The value after pressing `key` on the focused slider.
+    fn key_value(&self, value: f64, key: KeyCode) -> Option<f64> {
+        let step = self.step.unwrap_or((self.max - self.min) / 100.0);
+        let value = match key {
+            KeyCode::ArrowLeft | KeyCode::ArrowDown => value - step,
+            KeyCode::ArrowRight | KeyCode::ArrowUp => value + step,
+            KeyCode::PageDown => value - step * 10.0,
+            KeyCode::PageUp => value + step * 10.0,
+            KeyCode::Home => self.min,
+            KeyCode::End => self.max,
+            _ => return None,
+        };
+        Some(self.constrain(value))
+    }
+}
+
+impl Default for SliderField {
+    fn default() -> Self {
+        SliderField::new()
+    }
+}
+
+impl Widget<f64> for SliderField {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut f64, env: &Env) {
+        match event {
+            Event::Command(cmd) if cmd.selector == COMMIT_TEXT => {
+                self.commit(data);
+                ctx.set_handled();
+                ctx.request_paint();
+                return;
+            }
+            Event::KeyDown(key) if self.slider_focused => {
+                if let Some(value) = self.key_value(*data, key.key_code) {
+                    *data = value;
+                    ctx.set_handled();
+                    ctx.request_paint();
+                    return;
+                }
+            }
+            Event::KeyDown(key) if self.editing => {
+                if let KeyCode::Return | KeyCode::NumpadEnter = key.key_code {
+                    self.commit(data);
+                    ctx.set_handled();
+                    ctx.request_paint();
+                    return;
+                }
+            }
+            _ => (),
+        }
+
+        let old = self.fraction(*data);
+        let mut fraction = old;
+        self.slider.event(ctx, event, &mut fraction, env);
+        if fraction != old {
+            *data = self.constrain(self.min + fraction * (self.max - self.min));
+        }
+
+        let mut text = self.text.clone();
+        self.textbox.event(ctx, event, &mut text, env);
+        if text != self.text {
+            self.text = text;
+            match Self::parse(&self.text) {
+                Some(value) if value >= self.min && value <= self.max => {
+                    *data = self.constrain(value);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &f64, env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => self.text = self.format(*data),
+            LifeCycle::RouteFocusChanged { old, new } => {
+                self.slider_focused = *new == Some(self.slider.id());
+                self.editing = *new == Some(self.textbox.id());
+                if *old == Some(self.textbox.id()) && !self.editing {
+                    ctx.submit_command(COMMIT_TEXT, ctx.widget_id());
+                }
+            }
+            _ => (),
+        }
+        self.slider
+            .lifecycle(ctx, event, &self.fraction(*data), env);
+        self.textbox.lifecycle(ctx, event, &self.text, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &f64, data: &f64, env: &Env) {
+        // what is being typed is kept unless the value changed some other way
+        if !self.editing || self.text_value() != Some(*data) {
+            self.text = self.format(*data);
+        }
+        self.slider.update(ctx, &self.fraction(*data), env);
+        self.textbox.update(ctx, &self.text, env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &f64,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("SliderField");
+
+        let textbox_bc = BoxConstraints::new(
+            Size::new(TEXTBOX_WIDTH, 0.0),
+            Size::new(TEXTBOX_WIDTH, bc.max().height),
+        );
+        let textbox_size = self
+            .textbox
+            .layout(layout_ctx, &textbox_bc, &self.text, env);
+
+        let slider_max = if bc.is_width_bounded() {
+            (bc.max().width - TEXTBOX_WIDTH - SPACING).max(0.0)
+        } else {
+            f64::INFINITY
+        };
+        let slider_bc = BoxConstraints::new(Size::ZERO, Size::new(slider_max, bc.max().height));
+        let fraction = self.fraction(*data);
+        let slider_size = self.slider.layout(layout_ctx, &slider_bc, &fraction, env);
+
+        let height = slider_size.height.max(textbox_size.height);
+        let slider_origin = Point::new(0.0, (height - slider_size.height) / 2.0);
+        self.slider
+            .set_layout_rect(Rect::from_origin_size(slider_origin, slider_size));
+        let textbox_origin = Point::new(
+            slider_size.width + SPACING,
+            (height - textbox_size.height) / 2.0,
+        );
+        self.textbox
+            .set_layout_rect(Rect::from_origin_size(textbox_origin, textbox_size));
+
+        let offset = textbox_origin.y + textbox_size.height;
+        layout_ctx.set_baseline_offset(height - offset + self.textbox.baseline_offset());
+        bc.constrain(Size::new(textbox_origin.x + textbox_size.width, height))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &f64, env: &Env) {
+        self.slider
+            .paint_with_offset(paint_ctx, &self.fraction(*data), env);
+        self.textbox.paint_with_offset(paint_ctx, &self.text, env);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_clamped_and_snapped_to_the_step() {
+        let field = SliderField::new()
+            .min(-10.0)
+            .max(10.0)
+            .step(2.5)
+            .precision(1);
+        assert_eq!(field.constrain(3.0), 2.5);
+        assert_eq!(field.constrain(42.0), 10.0);
+        assert_eq!(field.key_value(7.5, KeyCode::ArrowRight), Some(10.0));
+        assert_eq!(field.key_value(0.0, KeyCode::PageDown), Some(-10.0));
+        assert_eq!(field.format(field.constrain(-4.0)), "-5.0");
+        assert_eq!(SliderField::parse(" 1e1 "), Some(10.0));
+        assert_eq!(SliderField::parse("ten"), None);
+        assert_eq!(field.fraction(5.0), 0.75);
+    }
+}
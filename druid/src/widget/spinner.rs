@@ -0,0 +1,161 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An animated activity indicator.
+
+use std::f64::consts::PI;
+
+use crate::kurbo::{Line, Point, Size, Vec2};
+use crate::piet::Color;
+use crate::theme;
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    RenderContext, UpdateCtx, Widget,
+};
+
+/// The number of spokes around the spinner.
+const SPOKES: usize = 12;
+
+/// How long one turn takes, in seconds.
+const TURN_TIME: f64 = 1.0;
+
+/// A spinning indicator, for showing that something is happening, such as
+/// waiting for a file to load.
+///
+/// It keeps animating for as long as it is in the widget tree, except when
+/// [`theme::REDUCE_MOTION`] is set, when it stands still. It is
+/// [`theme::BASIC_WIDGET_HEIGHT`] across, and in [`theme::LABEL_COLOR`]
+/// unless it is given a [`color`]; wrap it in a [`SizedBox`] to make it
+/// bigger.
+///
+/// [`theme::REDUCE_MOTION`]: ../theme/constant.REDUCE_MOTION.html
+/// [`theme::BASIC_WIDGET_HEIGHT`]: ../theme/constant.BASIC_WIDGET_HEIGHT.html
+/// [`theme::LABEL_COLOR`]: ../theme/constant.LABEL_COLOR.html
+/// [`color`]: #method.color
+/// [`SizedBox`]: struct.SizedBox.html
+#[derive(Debug, Clone, Default)]
+pub struct Spinner {
+    /// How far through a turn the spinner is, from 0 to 1.
+    turn: f64,
+    color: Option<Color>,
+    /// Whether an animation frame has been requested.
+    animating: bool,
+}
+
+impl Spinner {
+    /// Create a spinner.
+    pub fn new() -> Spinner {
+        Spinner::default()
+    }
+
+    /// Builder-style method for setting the color of the spinner.
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Move the spinner on by an animation frame `interval` nanoseconds
+    /// long.
+    fn advance(&mut self, interval: u64) {
+        let seconds = interval as f64 * 1e-9;
+        self.turn = (self.turn + seconds / TURN_TIME).fract();
+    }
+
+    /// How opaque spoke number `spoke` is: the leading spoke is solid, and
+    /// the ones behind it fade.
+    fn spoke_alpha(&self, spoke: usize) -> f64 {
+        let leading = (self.turn * SPOKES as f64).floor() as usize % SPOKES;
+        let behind = (leading + SPOKES - spoke) % SPOKES;
+        1.0 - behind as f64 / SPOKES as f64
+    }
+}
+
+impl<T: Data> Widget<T> for Spinner {
+    fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut T, _env: &Env) {}
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &T, env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded if !env.get(theme::REDUCE_MOTION) => {
+                self.animating = true;
+                ctx.request_anim_frame();
+            }
+            LifeCycle::AnimFrame(interval) if self.animating => {
+                self.advance(*interval);
+                ctx.request_paint();
+                if env.get(theme::REDUCE_MOTION) {
+                    self.animating = false;
+                } else {
+                    ctx.request_anim_frame();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, _data: &T, env: &Env) {
+        // start again if the user no longer minds motion
+        if !self.animating && !env.get(theme::REDUCE_MOTION) {
+            self.animating = true;
+            ctx.request_anim_frame();
+        }
+    }
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints, _data: &T, env: &Env) -> Size {
+        bc.debug_check("Spinner");
+        let size = env.get(theme::BASIC_WIDGET_HEIGHT);
+        bc.constrain(Size::new(size, size))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _data: &T, env: &Env) {
+        let size = paint_ctx.size();
+        let radius = size.width.min(size.height) / 2.0;
+        let center = Point::new(size.width / 2.0, size.height / 2.0);
+        let color = self
+            .color
+            .clone()
+            .unwrap_or_else(|| env.get(theme::LABEL_COLOR));
+        for spoke in 0..SPOKES {
+            // clockwise from the top
+            let angle = 2.0 * PI * spoke as f64 / SPOKES as f64;
+            let direction = Vec2::new(angle.sin(), -angle.cos());
+            let line = Line::new(
+                center + direction * radius * 0.45,
+                center + direction * radius * 0.9,
+            );
+            let alpha = self.spoke_alpha(spoke);
+            paint_ctx.stroke(line, &color.clone().with_alpha(alpha), radius * 0.16);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spokes_take_turns_leading() {
+        let mut spinner = Spinner::new();
+        assert_eq!(spinner.spoke_alpha(0), 1.0);
+        assert!(spinner.spoke_alpha(1) < spinner.spoke_alpha(11));
+
+        // a quarter of a turn
+        spinner.advance(250_000_000);
+        assert_eq!(spinner.spoke_alpha(3), 1.0);
+        assert!(spinner.spoke_alpha(2) > spinner.spoke_alpha(1));
+
+        // and all the way around
+        spinner.advance(750_000_000);
+        assert!(spinner.turn < 1e-9 || spinner.turn > 1.0 - 1e-9);
+    }
+}
@@ -29,6 +29,9 @@ pub struct Split<T> {
     splitter_size: f64,
     child1: WidgetPod<T, Box<dyn Widget<T>>>,
     child2: WidgetPod<T, Box<dyn Widget<T>>>,
+    /// The smallest lengths the children's content fits in, along the
+    /// split, from the last layout.
+    min_lengths: (f64, f64),
 }
 
 impl<T> Split<T> {
@@ -45,6 +48,7 @@ impl<T> Split<T> {
             draggable: false,
             child1: WidgetPod::new(child1).boxed(),
             child2: WidgetPod::new(child2).boxed(),
+            min_lengths: (0.0, 0.0),
         }
     }
     /// Create a new split panel, with a vertical splitter between two children.
@@ -92,6 +96,21 @@ impl<T> Split<T> {
             }
         }
     }
+    /// The axis the children are side by side along.
+    fn split_axis(&self) -> Axis {
+        self.split_direction.cross()
+    }
+    /// The length of the first child, out of `length` for both, keeping
+    /// both children at least as big as their content if they fit.
+    fn child1_length(&self, length: f64, split_point: f64) -> f64 {
+        let (min1, min2) = self.min_lengths;
+        let child1 = (length * split_point).max(0.0);
+        if min1 + min2 <= length {
+            child1.max(min1).min(length - min2)
+        } else {
+            child1
+        }
+    }
     fn update_splitter(&mut self, size: Size, mouse_pos: Point) {
         self.split_point = match self.split_direction {
             Axis::Vertical => {
@@ -120,6 +139,14 @@ impl<T> Split<T> {
                     mouse_pos.y / size.height
                 }
             }
+        };
+        // don't drag over content that doesn't fit
+        let length = match self.split_direction {
+            Axis::Vertical => size.width,
+            Axis::Horizontal => size.height,
+        } - self.splitter_size;
+        if length > 0.0 {
+            self.split_point = self.child1_length(length, self.split_point) / length;
         }
     }
 }
@@ -192,6 +219,16 @@ impl<T: Data> Widget<T> for Split<T> {
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
         bc.debug_check("Split");
 
+        let axis = self.split_axis();
+        let cross = match axis {
+            Axis::Horizontal => bc.max().height,
+            Axis::Vertical => bc.max().width,
+        };
+        self.min_lengths = (
+            self.child1.min_intrinsic(ctx, axis, cross, data, env),
+            self.child2.min_intrinsic(ctx, axis, cross, data, env),
+        );
+
         let mut my_size = bc.max();
         let reduced_width = my_size.width - self.splitter_size;
         let reduced_height = my_size.height - self.splitter_size;
@@ -200,7 +237,7 @@ impl<T: Data> Widget<T> for Split<T> {
                 if !bc.is_width_bounded() {
                     log::warn!("A Split widget was given an unbounded width to split.")
                 }
-                let child1_width = (reduced_width * self.split_point).max(0.0);
+                let child1_width = self.child1_length(reduced_width, self.split_point);
                 let child2_width = (reduced_width - child1_width).max(0.0);
                 (
                     BoxConstraints::new(
@@ -217,7 +254,7 @@ impl<T: Data> Widget<T> for Split<T> {
                 if !bc.is_width_bounded() {
                     log::warn!("A Split widget was given an unbounded height to split.")
                 }
-                let child1_height = (reduced_height * self.split_point).max(0.0);
+                let child1_height = self.child1_length(reduced_height, self.split_point);
                 let child2_height = (reduced_height - child1_height).max(0.0);
                 (
                     BoxConstraints::new(
@@ -267,6 +304,40 @@ impl<T: Data> Widget<T> for Split<T> {
         my_size
     }
 
+    fn min_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let length1 = self.child1.min_intrinsic(ctx, axis, cross, data, env);
+        let length2 = self.child2.min_intrinsic(ctx, axis, cross, data, env);
+        if axis == self.split_axis() {
+            length1 + length2 + self.splitter_size
+        } else {
+            length1.max(length2)
+        }
+    }
+
+    fn max_intrinsic(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        axis: Axis,
+        cross: f64,
+        data: &T,
+        env: &Env,
+    ) -> f64 {
+        let length1 = self.child1.max_intrinsic(ctx, axis, cross, data, env);
+        let length2 = self.child2.max_intrinsic(ctx, axis, cross, data, env);
+        if axis == self.split_axis() {
+            length1 + length2 + self.splitter_size
+        } else {
+            length1.max(length2)
+        }
+    }
+
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
         let size = paint_ctx.size();
         //third, because we're putting the lines at roughly third points.
@@ -316,3 +387,20 @@ impl<T: Data> Widget<T> for Split<T> {
         self.child2.paint_with_offset(paint_ctx, &data, env);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widget::Label;
+
+    #[test]
+    fn children_keep_room_for_their_content() {
+        let mut split = Split::<()>::vertical(Label::new("left"), Label::new("right"));
+        split.min_lengths = (100.0, 50.0);
+        assert_eq!(split.child1_length(400.0, 0.5), 200.0);
+        assert_eq!(split.child1_length(400.0, 0.1), 100.0);
+        assert_eq!(split.child1_length(400.0, 0.95), 350.0);
+        // when they can't both fit, the split point wins
+        assert_eq!(split.child1_length(120.0, 0.5), 60.0);
+    }
+}
@@ -0,0 +1,305 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A bar along the bottom of a window, for showing its status.
+
+use std::time::{Duration, Instant};
+
+use crate::kurbo::{Line, Point, Rect, Size};
+use crate::text::TextLine;
+use crate::theme;
+use crate::{
+    commands, BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle,
+    LifeCycleCtx, PaintCtx, RenderContext, TimerToken, UpdateCtx, Widget, WidgetPod,
+};
+
+/// The space between the sections and the ends of the bar.
+const SECTION_PADDING: f64 = 6.0;
+
+type Child<T> = WidgetPod<T, Box<dyn Widget<T>>>;
+
+/// A container that shows its child above a status bar, such as the bar
+/// with the line number along the bottom of a text editor.
+///
+/// The bar is [`theme::STATUS_BAR_HEIGHT`] high, and has a left, a center
+/// and a right section, each of which can hold a widget. A
+/// [`StatusMessage`] submitted to the window is shown in place of the left
+/// section for a while.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Label, StatusBar, StatusMessage, TextBox};
+/// # use druid::EventCtx;
+///
+/// let editor = StatusBar::new(TextBox::new())
+///     .left(Label::new(|text: &String, _env: &_| format!("{} characters", text.len())))
+///     .right(Label::new("UTF-8"));
+///
+/// // and later, from a widget in the window
+/// # fn saved(ctx: &mut EventCtx) {
+/// ctx.submit_command(StatusMessage::new("Saved"), None);
+/// # }
+/// ```
+///
+/// [`theme::STATUS_BAR_HEIGHT`]: ../theme/constant.STATUS_BAR_HEIGHT.html
+/// [`StatusMessage`]: struct.StatusMessage.html
+pub struct StatusBar<T> {
+    content: Child<T>,
+    left: Option<Child<T>>,
+    center: Option<Child<T>>,
+    right: Option<Child<T>>,
+    /// The message being shown, and when it goes.
+    message: Option<(String, TimerToken)>,
+    message_line: Option<TextLine>,
+}
+
+/// A message for the [`StatusBar`] of a window, which goes away after its
+/// timeout.
+///
+/// Submit it to the window as a command, which is
+/// [`commands::SHOW_STATUS_MESSAGE`](../commands/constant.SHOW_STATUS_MESSAGE.html).
+///
+/// [`StatusBar`]: struct.StatusBar.html
+#[derive(Debug, Clone)]
+pub struct StatusMessage {
+    text: String,
+    timeout: Option<Duration>,
+}
+
+impl StatusMessage {
+    /// A message shown for [`theme::STATUS_MESSAGE_TIMEOUT`].
+    ///
+    /// [`theme::STATUS_MESSAGE_TIMEOUT`]: ../theme/constant.STATUS_MESSAGE_TIMEOUT.html
+    pub fn new(text: impl Into<String>) -> Self {
+        StatusMessage {
+            text: text.into(),
+            timeout: None,
+        }
+    }
+
+    /// Builder-style method for setting how long the message is shown.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+impl From<StatusMessage> for Command {
+    fn from(message: StatusMessage) -> Command {
+        Command::new(commands::SHOW_STATUS_MESSAGE, message)
+    }
+}
+
+impl<T: Data> StatusBar<T> {
+    /// Create a status bar below `content`.
+    pub fn new(content: impl Widget<T> + 'static) -> Self {
+        StatusBar {
+            content: WidgetPod::new(content).boxed(),
+            left: None,
+            center: None,
+            right: None,
+            message: None,
+            message_line: None,
+        }
+    }
+
+    /// Builder-style method for setting the widget at the start of the bar.
+    pub fn left(mut self, widget: impl Widget<T> + 'static) -> Self {
+        self.left = Some(WidgetPod::new(widget).boxed());
+        self
+    }
+
+    /// Builder-style method for setting the widget in the middle of the bar.
+    pub fn center(mut self, widget: impl Widget<T> + 'static) -> Self {
+        self.center = Some(WidgetPod::new(widget).boxed());
+        self
+    }
+
+    /// Builder-style method for setting the widget at the end of the bar.
+    pub fn right(mut self, widget: impl Widget<T> + 'static) -> Self {
+        self.right = Some(WidgetPod::new(widget).boxed());
+        self
+    }
+
+    /// The sections, leaving out the left one while a message is shown.
+    fn sections(&mut self) -> impl Iterator<Item = &mut Child<T>> {
+        let showing_message = self.message.is_some();
+        let left = self.left.as_mut().filter(|_| !showing_message);
+        left.into_iter()
+            .chain(self.center.as_mut())
+            .chain(self.right.as_mut())
+    }
+}
+
+/// Where the left, center and right sections go, given how wide they are,
+/// in a bar `width` wide.
+fn section_origins(widths: [f64; 3], width: f64) -> [f64; 3] {
+    let [_, center, right] = widths;
+    [
+        SECTION_PADDING,
+        ((width - center) / 2.0).max(SECTION_PADDING),
+        (width - right - SECTION_PADDING).max(SECTION_PADDING),
+    ]
+}
+
+impl<T: Data> Widget<T> for StatusBar<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        match event {
+            Event::Command(cmd) if cmd.selector == commands::SHOW_STATUS_MESSAGE => {
+                if let Ok(message) = cmd.get_object::<StatusMessage>() {
+                    let timeout = message.timeout.unwrap_or_else(|| {
+                        Duration::from_millis(env.get(theme::STATUS_MESSAGE_TIMEOUT))
+                    });
+                    let timer = ctx.request_timer(Instant::now() + timeout);
+                    self.message = Some((message.text.clone(), timer));
+                    ctx.request_layout();
+                }
+            }
+            Event::Timer(id) if self.message.as_ref().map(|(_, timer)| timer) == Some(id) => {
+                self.message = None;
+                self.message_line = None;
+                ctx.request_layout();
+                ctx.set_handled();
+                return;
+            }
+            _ => (),
+        }
+
+        self.content.event(ctx, event, data, env);
+        for section in self.sections() {
+            section.event(ctx, event, data, env);
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.content.lifecycle(ctx, event, data, env);
+        // a message hides the left section, but it's still in the tree
+        let sections = self
+            .left
+            .iter_mut()
+            .chain(&mut self.center)
+            .chain(&mut self.right);
+        for section in sections {
+            section.lifecycle(ctx, event, data, env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        self.content.update(ctx, data, env);
+        let sections = self
+            .left
+            .iter_mut()
+            .chain(&mut self.center)
+            .chain(&mut self.right);
+        for section in sections {
+            section.update(ctx, data, env);
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("StatusBar");
+        let bar_height = env.get(theme::STATUS_BAR_HEIGHT);
+
+        let content_bc = bc.shrink((0.0, bar_height));
+        let content_size = self.content.layout(layout_ctx, &content_bc, data, env);
+        self.content
+            .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, content_size));
+        let size = bc.constrain(Size::new(
+            content_size.width,
+            content_size.height + bar_height,
+        ));
+        let bar_y = size.height - bar_height;
+
+        self.message_line = self.message.as_ref().map(|(text, _)| {
+            let font_name = env.get(theme::FONT_NAME);
+            let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+            TextLine::new(layout_ctx.text(), font_name, font_size, text, env)
+        });
+
+        let section_width = (size.width - 2.0 * SECTION_PADDING).max(0.0);
+        let section_bc = BoxConstraints::new(Size::ZERO, Size::new(section_width, bar_height));
+        let mut sizes = [Size::ZERO; 3];
+        let message = self.message.is_some();
+        let mut sections = [&mut self.left, &mut self.center, &mut self.right];
+        for (section, size) in sections.iter_mut().zip(&mut sizes) {
+            if let Some(section) = section {
+                *size = section.layout(layout_ctx, &section_bc, data, env);
+            }
+        }
+        if message {
+            sizes[0] = Size::ZERO;
+        }
+        let origins = section_origins([sizes[0].width, sizes[1].width, sizes[2].width], size.width);
+        let mut sections = [&mut self.left, &mut self.center, &mut self.right];
+        for ((section, section_size), x) in sections.iter_mut().zip(&sizes).zip(&origins) {
+            if let Some(section) = section {
+                let y = bar_y + (bar_height - section_size.height) / 2.0;
+                let rect = Rect::from_origin_size((*x, y), *section_size);
+                section.set_layout_rect(rect);
+            }
+        }
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.content.paint_with_offset(paint_ctx, data, env);
+
+        let size = paint_ctx.size();
+        let bar_height = env.get(theme::STATUS_BAR_HEIGHT);
+        let bar = Rect::new(0.0, size.height - bar_height, size.width, size.height);
+        paint_ctx.fill(bar, &env.get(theme::BACKGROUND_DARK));
+        let top = Line::new((0.0, bar.y0 + 0.5), (size.width, bar.y0 + 0.5));
+        paint_ctx.stroke(top, &env.get(theme::BORDER_DARK), 1.0);
+
+        for section in self.sections() {
+            section.paint_with_offset(paint_ctx, data, env);
+        }
+        if let Some(line) = &self.message_line {
+            let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+            // the same baseline as a label's
+            let baseline = bar.y0 + (bar_height + font_size * 0.6) / 2.0;
+            let origin = Point::new(SECTION_PADDING, baseline);
+            paint_ctx.draw_text(line.layout(), origin, &env.get(theme::LABEL_COLOR));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sections_are_spread_along_the_bar() {
+        assert_eq!(
+            section_origins([40.0, 20.0, 30.0], 200.0),
+            [6.0, 90.0, 164.0]
+        );
+        // a crowded bar keeps everything inside it
+        assert_eq!(section_origins([40.0, 20.0, 30.0], 10.0), [6.0, 6.0, 6.0]);
+
+        let message = StatusMessage::new("Saved").timeout(Duration::from_secs(1));
+        let command: Command = message.into();
+        assert_eq!(command.selector, commands::SHOW_STATUS_MESSAGE);
+        let message = command.get_object::<StatusMessage>().unwrap();
+        assert_eq!(message.text, "Saved");
+        assert_eq!(message.timeout, Some(Duration::from_secs(1)));
+    }
+}
@@ -15,10 +15,9 @@
 //! A stepper widget.
 
 use crate::{
-    BoxConstraints, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, Size,
-    TimerToken, UpdateCtx, Widget,
+    BoxConstraints, Data, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, Size, TimerToken, UpdateCtx, Widget,
 };
-use std::f64::EPSILON;
 use std::time::{Duration, Instant};
 
 use crate::kurbo::{BezPath, Rect, RoundedRect};
@@ -33,6 +32,12 @@ const STEPPER_REPEAT_DELAY: Duration = Duration::from_millis(500);
 const STEPPER_REPEAT: Duration = Duration::from_millis(200);
 
 /// A stepper widget for step-wise increasing and decreasing a value.
+///
+/// The value can be an `f64`, or any of the types that implement
+/// [`StepperValue`], such as the integers. Holding a button down, or an
+/// arrow key while the stepper has focus, keeps changing the value.
+///
+/// [`StepperValue`]: trait.StepperValue.html
 pub struct Stepper {
     max: f64,
     min: f64,
@@ -81,7 +86,7 @@ impl Stepper {
         self
     }
 
-    fn change_value(&mut self, _ctx: &mut EventCtx, data: &mut f64, _env: &Env) {
+    fn change_value<T: StepperValue>(&mut self, data: &mut T) {
         // increase/decrease value depending on which button is currently active
         let delta = if self.increase_active {
             self.step
@@ -90,27 +95,84 @@ impl Stepper {
         } else {
             0.0
         };
+        let value = self.stepped(data.to_f64(), delta);
+        *data = T::from_f64(value);
+    }
 
-        *data = (*data + delta).max(self.min).min(self.max);
-
-        if self.wrap {
-            if (*data - self.min).abs() < EPSILON {
-                *data = self.max
-            } else {
-                *data = self.min
-            }
+    /// The value a step of `delta` from `value` goes to.
+    fn stepped(&self, value: f64, delta: f64) -> f64 {
+        let next = value + delta;
+        if self.wrap && next > self.max {
+            self.min
+        } else if self.wrap && next < self.min {
+            self.max
+        } else {
+            next.max(self.min).min(self.max)
         }
     }
 }
 
+/// A type of value a [`Stepper`] can change.
+///
+/// Values are stepped as `f64`s; integers are rounded to the nearest one
+/// afterwards, and kept within the type's range, so their steps should be
+/// whole numbers.
+///
+/// [`Stepper`]: struct.Stepper.html
+pub trait StepperValue: Data {
+    /// The value, as an `f64`.
+    fn to_f64(&self) -> f64;
+    /// The value nearest to `value`.
+    fn from_f64(value: f64) -> Self;
+}
+
+impl StepperValue for f64 {
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+impl StepperValue for f32 {
+    fn to_f64(&self) -> f64 {
+        f64::from(*self)
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+macro_rules! impl_stepper_value_int {
+    ($($ty:ty),*) => {
+        $(
+            impl StepperValue for $ty {
+                fn to_f64(&self) -> f64 {
+                    *self as f64
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    // `as` saturates at the type's bounds
+                    value.round() as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_stepper_value_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
 impl Default for Stepper {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl Widget<f64> for Stepper {
-    fn paint(&mut self, paint_ctx: &mut PaintCtx, _data: &f64, env: &Env) {
+impl<T: StepperValue> Widget<T> for Stepper {
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _data: &T, env: &Env) {
         let rounded_rect =
             RoundedRect::from_origin_size(Point::ORIGIN, paint_ctx.size().to_vec2(), 4.);
 
@@ -119,6 +181,7 @@ impl Widget<f64> for Stepper {
         let button_size = Size::new(width, height / 2.);
 
         paint_ctx.stroke(rounded_rect, &env.get(theme::BORDER_DARK), 2.0);
+        paint_ctx.stroke_focus_ring(rounded_rect, env);
         paint_ctx.clip(rounded_rect);
 
         // draw buttons for increase/decrease
@@ -173,7 +236,7 @@ impl Widget<f64> for Stepper {
         &mut self,
         _layout_ctx: &mut LayoutCtx,
         bc: &BoxConstraints,
-        _data: &f64,
+        _data: &T,
         env: &Env,
     ) -> Size {
         bc.constrain(Size::new(
@@ -182,12 +245,13 @@ impl Widget<f64> for Stepper {
         ))
     }
 
-    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut f64, env: &Env) {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
         let height = env.get(theme::BORDERED_WIDGET_HEIGHT);
 
         match event {
             Event::MouseDown(mouse) => {
                 ctx.set_active(true);
+                ctx.request_focus();
 
                 if mouse.pos.y > height / 2. {
                     self.decrease_active = true;
@@ -195,7 +259,7 @@ impl Widget<f64> for Stepper {
                     self.increase_active = true;
                 }
 
-                self.change_value(ctx, data, env);
+                self.change_value(data);
 
                 let delay = Instant::now() + STEPPER_REPEAT_DELAY;
                 self.timer_id = ctx.request_timer(delay);
@@ -212,19 +276,82 @@ impl Widget<f64> for Stepper {
                 ctx.request_paint();
             }
             Event::Timer(id) if *id == self.timer_id => {
-                self.change_value(ctx, data, env);
+                self.change_value(data);
                 let delay = Instant::now() + STEPPER_REPEAT;
                 self.timer_id = ctx.request_timer(delay);
             }
+            // holding an arrow key relies on the platform's key repeat
+            Event::KeyDown(key)
+                if key.key_code == KeyCode::ArrowUp || key.key_code == KeyCode::ArrowDown =>
+            {
+                self.increase_active = key.key_code == KeyCode::ArrowUp;
+                self.decrease_active = key.key_code == KeyCode::ArrowDown;
+                self.change_value(data);
+                ctx.set_handled();
+                ctx.request_paint();
+            }
+            Event::KeyUp(key)
+                if key.key_code == KeyCode::ArrowUp || key.key_code == KeyCode::ArrowDown =>
+            {
+                self.decrease_active = false;
+                self.increase_active = false;
+                ctx.request_paint();
+            }
             _ => (),
         }
     }
 
-    fn lifecycle(&mut self, _ctx: &mut LifeCycleCtx, _event: &LifeCycle, _data: &f64, _env: &Env) {}
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &T, _env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
+        }
+    }
 
-    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &f64, data: &f64, _env: &Env) {
-        if (*data - old_data).abs() > EPSILON {
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, _env: &Env) {
+        if !old_data.same(data) {
             ctx.request_paint();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_stop_or_wrap_at_the_ends() {
+        let stepper = Stepper::new().min(0.0).max(10.0).step(4.0);
+        assert_eq!(stepper.stepped(4.0, 4.0), 8.0);
+        assert_eq!(stepper.stepped(8.0, 4.0), 10.0);
+        assert_eq!(stepper.stepped(2.0, -4.0), 0.0);
+
+        let stepper = stepper.wrap(true);
+        assert_eq!(stepper.stepped(4.0, 4.0), 8.0);
+        assert_eq!(stepper.stepped(10.0, 4.0), 0.0);
+        assert_eq!(stepper.stepped(0.0, -4.0), 10.0);
+    }
+
+    #[test]
+    fn integer_values() {
+        let mut stepper = Stepper::new().min(-2.0).max(300.0).step(0.6);
+        stepper.increase_active = true;
+        let mut value = 1u8;
+        stepper.change_value(&mut value);
+        assert_eq!(value, 2);
+        // past what the type can hold
+        value = 255;
+        stepper.change_value(&mut value);
+        assert_eq!(value, 255);
+        stepper.increase_active = false;
+        stepper.decrease_active = true;
+        value = 0;
+        stepper.change_value(&mut value);
+        assert_eq!(value, 0);
+
+        let mut signed = -1i32;
+        stepper.change_value(&mut signed);
+        assert_eq!(signed, -2);
+    }
+}
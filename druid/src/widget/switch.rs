@@ -15,20 +15,28 @@
 //! A toggle switch widget.
 
 use crate::kurbo::{Circle, Point, Rect, RoundedRect, Shape, Size};
-use crate::piet::{
-    FontBuilder, LinearGradient, RenderContext, Text, TextLayout, TextLayoutBuilder, UnitPoint,
-};
+use crate::piet::{FontBuilder, RenderContext, Text, TextLayout, TextLayoutBuilder, UnitPoint};
 use crate::theme;
 use crate::widget::Align;
 use crate::{
-    BoxConstraints, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, UpdateCtx,
-    Widget,
+    BoxConstraints, Env, Event, EventCtx, KeyCode, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    UpdateCtx, Widget,
 };
 
 const SWITCH_PADDING: f64 = 3.;
 const SWITCH_WIDTH_RATIO: f64 = 2.75;
+/// How long the knob takes to slide from one end to the other, in seconds.
+const SWITCH_ANIMATION_TIME: f64 = 0.15;
 
 /// A switch that toggles a boolean.
+///
+/// The knob slides across when the switch is toggled. The colors of the
+/// track and the knob are [`theme::SWITCH_TRACK_ON_COLOR`],
+/// [`theme::SWITCH_TRACK_OFF_COLOR`] and [`theme::SWITCH_KNOB_COLOR`].
+///
+/// [`theme::SWITCH_TRACK_ON_COLOR`]: ../theme/constant.SWITCH_TRACK_ON_COLOR.html
+/// [`theme::SWITCH_TRACK_OFF_COLOR`]: ../theme/constant.SWITCH_TRACK_OFF_COLOR.html
+/// [`theme::SWITCH_KNOB_COLOR`]: ../theme/constant.SWITCH_KNOB_COLOR.html
 #[derive(Debug, Clone, Default)]
 pub struct Switch {
     knob_pos: Point,
@@ -47,6 +55,34 @@ impl Switch {
         knob_circle.winding(mouse_pos) > 0
     }
 
+    /// Move the knob to match the new value, animating unless the user
+    /// prefers reduced motion.
+    fn animate_knob(&mut self, ctx: &mut EventCtx, env: &Env) {
+        if env.get(theme::REDUCE_MOTION) {
+            // paint puts the knob in its final position
+            self.animation_in_progress = false;
+            ctx.request_paint();
+        } else {
+            self.animation_in_progress = true;
+            ctx.request_anim_frame();
+        }
+    }
+
+    /// Slide the knob towards `target` for an animation frame `interval`
+    /// nanoseconds long, where the whole way across is `travel`; returns
+    /// whether it is still on its way.
+    fn slide_knob(&mut self, target: f64, travel: f64, interval: u64) -> bool {
+        let step = travel * interval as f64 * 1e-9 / SWITCH_ANIMATION_TIME;
+        let distance = target - self.knob_pos.x;
+        if distance.abs() <= step {
+            self.knob_pos.x = target;
+            false
+        } else {
+            self.knob_pos.x += step.copysign(distance);
+            true
+        }
+    }
+
     fn paint_labels(&mut self, paint_ctx: &mut PaintCtx, env: &Env, switch_width: f64) {
         let font_name = env.get(theme::FONT_NAME);
         let font_size = env.get(theme::TEXT_SIZE_NORMAL);
@@ -124,6 +160,7 @@ impl Widget<bool> for Switch {
         match event {
             Event::MouseDown(_) => {
                 ctx.set_active(true);
+                ctx.request_focus();
                 ctx.request_paint();
             }
             Event::MouseUp(_) => {
@@ -139,8 +176,7 @@ impl Widget<bool> for Switch {
 
                 ctx.request_paint();
                 self.knob_dragged = false;
-                self.animation_in_progress = true;
-                ctx.request_anim_frame();
+                self.animate_knob(ctx, env);
             }
             Event::MouseMoved(mouse) => {
                 if ctx.is_active() {
@@ -152,12 +188,31 @@ impl Widget<bool> for Switch {
                 }
                 ctx.request_paint();
             }
+            Event::KeyDown(key) => {
+                let value = match key.key_code {
+                    KeyCode::Space | KeyCode::Return => !*data,
+                    KeyCode::ArrowLeft => false,
+                    KeyCode::ArrowRight => true,
+                    _ => return,
+                };
+                ctx.set_handled();
+                if value != *data {
+                    *data = value;
+                    self.animate_knob(ctx, env);
+                }
+            }
             _ => (),
         }
     }
 
     fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &bool, env: &Env) {
-        if let LifeCycle::AnimFrame(_) = event {
+        match event {
+            LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
+        }
+
+        if let LifeCycle::AnimFrame(interval) = event {
             let switch_height = env.get(theme::BORDERED_WIDGET_HEIGHT);
             let switch_width = switch_height * SWITCH_WIDTH_RATIO;
             let knob_size = switch_height - 2. * SWITCH_PADDING;
@@ -166,14 +221,12 @@ impl Widget<bool> for Switch {
 
             // move knob to right position depending on the value
             if self.animation_in_progress {
-                let delta = if *data { 2. } else { -2. };
-                self.knob_pos.x += delta;
-
-                if self.knob_pos.x > off_pos && self.knob_pos.x < on_pos {
+                let target = if *data { on_pos } else { off_pos };
+                self.animation_in_progress = self.slide_knob(target, on_pos - off_pos, *interval);
+                if self.animation_in_progress {
                     ctx.request_anim_frame();
-                } else {
-                    self.animation_in_progress = false;
                 }
+                ctx.request_paint();
             }
         }
     }
@@ -223,57 +276,21 @@ impl Widget<bool> for Switch {
 
         // paint different background for on and off state
         // opacity of background color depends on knob position
-        // todo: make color configurable
         let opacity = (self.knob_pos.x - off_pos) / (on_pos - off_pos);
-
-        let background_gradient_on_state = LinearGradient::new(
-            UnitPoint::TOP,
-            UnitPoint::BOTTOM,
-            (
-                env.get(theme::PRIMARY_LIGHT).with_alpha(opacity),
-                env.get(theme::PRIMARY_DARK).with_alpha(opacity),
-            ),
-        );
-        let background_gradient_off_state = LinearGradient::new(
-            UnitPoint::TOP,
-            UnitPoint::BOTTOM,
-            (
-                env.get(theme::BACKGROUND_LIGHT).with_alpha(1. - opacity),
-                env.get(theme::BACKGROUND_DARK).with_alpha(1. - opacity),
-            ),
-        );
+        let track_on = env.get(theme::SWITCH_TRACK_ON_COLOR);
+        let track_off = env.get(theme::SWITCH_TRACK_OFF_COLOR);
 
         paint_ctx.stroke(background_rect, &env.get(theme::BORDER_DARK), 2.0);
-        paint_ctx.fill(background_rect, &background_gradient_on_state);
-        paint_ctx.fill(background_rect, &background_gradient_off_state);
+        paint_ctx.stroke_focus_ring(background_rect, env);
+        paint_ctx.fill(background_rect, &track_off.with_alpha(1. - opacity));
+        paint_ctx.fill(background_rect, &track_on.with_alpha(opacity));
         paint_ctx.clip(background_rect);
 
         // paint the knob
         let is_active = paint_ctx.is_active();
         let is_hovered = self.knob_hovered;
 
-        let normal_knob_gradient = LinearGradient::new(
-            UnitPoint::TOP,
-            UnitPoint::BOTTOM,
-            (
-                env.get(theme::FOREGROUND_LIGHT),
-                env.get(theme::FOREGROUND_DARK),
-            ),
-        );
-        let flipped_knob_gradient = LinearGradient::new(
-            UnitPoint::TOP,
-            UnitPoint::BOTTOM,
-            (
-                env.get(theme::FOREGROUND_DARK),
-                env.get(theme::FOREGROUND_LIGHT),
-            ),
-        );
-
-        let knob_gradient = if is_active {
-            flipped_knob_gradient
-        } else {
-            normal_knob_gradient
-        };
+        let knob_color = env.get(theme::SWITCH_KNOB_COLOR);
 
         // paint the border
         let border_color = if is_hovered || is_active {
@@ -283,9 +300,30 @@ impl Widget<bool> for Switch {
         };
 
         paint_ctx.stroke(knob_circle, &border_color, 2.);
-        paint_ctx.fill(knob_circle, &knob_gradient);
+        paint_ctx.fill(knob_circle, &knob_color);
 
         // paint on/off label
         self.paint_labels(paint_ctx, env, switch_width);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knob_slides_with_time() {
+        let mut switch = Switch::default();
+        switch.knob_pos.x = 10.0;
+
+        // a third of the animation time goes a third of the way
+        assert!(switch.slide_knob(40.0, 30.0, 50_000_000));
+        assert!((switch.knob_pos.x - 20.0).abs() < 1e-9);
+
+        // and it stops at the end, even after a long frame
+        assert!(!switch.slide_knob(40.0, 30.0, 1_000_000_000));
+        assert_eq!(switch.knob_pos.x, 40.0);
+        assert!(switch.slide_knob(10.0, 30.0, 10_000_000));
+        assert!(switch.knob_pos.x < 40.0);
+    }
+}
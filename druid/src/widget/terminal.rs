@@ -0,0 +1,1074 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A terminal emulator.
+
+use std::collections::VecDeque;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::{Read, Write};
+use std::ops::Range;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use log::error;
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use vte::{Parser, Perform};
+
+use crate::kurbo::{Line, Point, Rect, Size, Vec2};
+use crate::piet::Color;
+use crate::text::TextLine;
+use crate::theme;
+use crate::{
+    commands, Application, BoxConstraints, Data, Env, Event, EventCtx, ExtEventSink, KeyCode,
+    KeyModifiers, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, RenderContext, Selector, UpdateCtx,
+    Widget, WidgetId,
+};
+
+/// Brings the output of the program to its `Terminal`.
+const OUTPUT: Selector = Selector::new("druid-builtin.terminal-output");
+
+/// The number of lines kept after they scroll off the top, by default.
+const DEFAULT_SCROLLBACK: usize = 1000;
+
+/// The size of the terminal when it is not given one, in cells.
+const DEFAULT_COLS: usize = 80;
+const DEFAULT_ROWS: usize = 24;
+
+/// The first sixteen colors, which programs use the most.
+const ANSI_COLORS: [Color; 16] = [
+    Color::rgb8(0x00, 0x00, 0x00),
+    Color::rgb8(0xcd, 0x31, 0x31),
+    Color::rgb8(0x0d, 0xbc, 0x79),
+    Color::rgb8(0xe5, 0xe5, 0x10),
+    Color::rgb8(0x24, 0x72, 0xc8),
+    Color::rgb8(0xbc, 0x3f, 0xbc),
+    Color::rgb8(0x11, 0xa8, 0xcd),
+    Color::rgb8(0xe5, 0xe5, 0xe5),
+    Color::rgb8(0x66, 0x66, 0x66),
+    Color::rgb8(0xf1, 0x4c, 0x4c),
+    Color::rgb8(0x23, 0xd1, 0x8b),
+    Color::rgb8(0xf5, 0xf5, 0x43),
+    Color::rgb8(0x3b, 0x8e, 0xea),
+    Color::rgb8(0xd6, 0x70, 0xd6),
+    Color::rgb8(0x29, 0xb8, 0xdb),
+    Color::rgb8(0xff, 0xff, 0xff),
+];
+
+/// A terminal emulator, running a program such as a shell in a
+/// pseudoterminal, for the consoles of IDE-like apps.
+///
+/// It understands the VT100 and xterm escape sequences that most programs
+/// use, including colors and the alternate screen of full-screen programs,
+/// and it keeps the lines that scroll off the top, which the mouse wheel
+/// scrolls back to. Dragging selects text, which [`commands::COPY`] copies
+/// while the terminal has focus; every key, including Ctrl+C, goes to the
+/// program.
+///
+/// The text is in [`theme::MONOSPACE_FONT_NAME`] unless the terminal is
+/// given a [`font`]. As with a [`LiveImage`], the widget has to be given
+/// the id that was passed to [`new`], with [`WidgetExt::with_id`], so that
+/// the output of the program can find it.
+///
+/// This is only available with the `terminal` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use druid::widget::{Terminal, WidgetExt};
+/// use druid::{ExtEventSink, Widget, WidgetId};
+///
+/// fn console(sink: ExtEventSink) -> impl Widget<()> {
+///     let id = WidgetId::next();
+///     Terminal::shell(sink, id).unwrap().font("Fira Mono", 13.0).with_id(id)
+/// }
+/// ```
+///
+/// [`commands::COPY`]: ../commands/constant.COPY.html
+/// [`theme::MONOSPACE_FONT_NAME`]: ../theme/constant.MONOSPACE_FONT_NAME.html
+/// [`font`]: #method.font
+/// [`LiveImage`]: struct.LiveImage.html
+/// [`new`]: #method.new
+/// [`WidgetExt::with_id`]: trait.WidgetExt.html#method.with_id
+pub struct Terminal {
+    screen: Screen,
+    parser: Parser,
+    master: Box<dyn MasterPty + Send>,
+    /// Input for the program, written to the pty by another thread, so
+    /// that a program that isn't reading can't block the UI.
+    writer: Sender<Vec<u8>>,
+    /// `None` once the widget is being dropped.
+    child: Option<Box<dyn Child + Send + Sync>>,
+    font: Option<(String, f64)>,
+    /// The size of a cell, from the last layout.
+    cell: Size,
+    /// How many lines the view is scrolled back.
+    scroll: usize,
+    /// Where the selection started and ended, as line and column of the
+    /// scrollback and the screen together.
+    selection: Option<((usize, usize), (usize, usize))>,
+}
+
+/// An error from starting a [`Terminal`].
+///
+/// [`Terminal`]: struct.Terminal.html
+#[derive(Debug, Clone)]
+pub struct TerminalError {
+    message: String,
+}
+
+impl fmt::Display for TerminalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "starting the terminal failed: {}", self.message)
+    }
+}
+
+impl std::error::Error for TerminalError {}
+
+impl TerminalError {
+    fn new(message: impl fmt::Display) -> Self {
+        TerminalError {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Terminal {
+    /// Create a terminal running `program` with `args`.
+    ///
+    /// `sink` comes from [`AppLauncher::get_external_handle`], and `id` is
+    /// the id the widget will be given.
+    ///
+    /// [`AppLauncher::get_external_handle`]: ../struct.AppLauncher.html#method.get_external_handle
+    pub fn new(
+        sink: ExtEventSink,
+        id: WidgetId,
+        program: impl AsRef<OsStr>,
+        args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+    ) -> Result<Self, TerminalError> {
+        let mut command = CommandBuilder::new(program);
+        command.args(args);
+        Terminal::spawn(sink, id, command)
+    }
+
+    /// Create a terminal running the user's shell.
+    pub fn shell(sink: ExtEventSink, id: WidgetId) -> Result<Self, TerminalError> {
+        Terminal::spawn(sink, id, CommandBuilder::new_default_prog())
+    }
+
+    fn spawn(
+        sink: ExtEventSink,
+        id: WidgetId,
+        mut command: CommandBuilder,
+    ) -> Result<Self, TerminalError> {
+        command.env("TERM", "xterm-256color");
+        let size = PtySize {
+            rows: DEFAULT_ROWS as u16,
+            cols: DEFAULT_COLS as u16,
+            pixel_width: 0,
+            pixel_height: 0,
+        };
+        let pair = native_pty_system()
+            .openpty(size)
+            .map_err(TerminalError::new)?;
+        let child = pair
+            .slave
+            .spawn_command(command)
+            .map_err(TerminalError::new)?;
+        let mut reader = pair.master.try_clone_reader().map_err(TerminalError::new)?;
+        let mut pty_writer = pair.master.try_clone_writer().map_err(TerminalError::new)?;
+        let (writer, input) = mpsc::channel::<Vec<u8>>();
+
+        thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            // until the program exits, or the widget goes away
+            while let Ok(count) = reader.read(&mut buffer) {
+                let output = buffer[..count].to_vec();
+                if count == 0 || sink.submit_command(OUTPUT, output, id).is_err() {
+                    break;
+                }
+            }
+        });
+        // until the widget goes away
+        thread::spawn(move || {
+            for bytes in input {
+                if let Err(e) = pty_writer
+                    .write_all(&bytes)
+                    .and_then(|_| pty_writer.flush())
+                {
+                    error!("writing to the terminal failed: {}", e);
+                    break;
+                }
+            }
+        });
+
+        Ok(Terminal {
+            screen: Screen::new(DEFAULT_COLS, DEFAULT_ROWS, DEFAULT_SCROLLBACK),
+            parser: Parser::new(),
+            master: pair.master,
+            writer,
+            child: Some(child),
+            font: None,
+            cell: Size::ZERO,
+            scroll: 0,
+            selection: None,
+        })
+    }
+
+    /// Builder-style method for setting the font, which should be
+    /// monospaced, and its size.
+    pub fn font(mut self, name: impl Into<String>, size: f64) -> Self {
+        self.font = Some((name.into(), size));
+        self
+    }
+
+    /// Builder-style method for setting how many lines are kept after they
+    /// scroll off the top.
+    pub fn scrollback(mut self, lines: usize) -> Self {
+        self.screen.max_scrollback = lines;
+        self
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        // the writing thread only stops after an error, which it logged
+        let _ = self.writer.send(bytes.to_vec());
+    }
+
+    /// The line and column of the cell at `pos`.
+    fn cell_at(&self, pos: Point) -> (usize, usize) {
+        let top = self.screen.scrollback.len() - self.scroll;
+        let row = (pos.y / self.cell.height).max(0.0) as usize;
+        let col = (pos.x / self.cell.width).round().max(0.0) as usize;
+        (
+            top + row.min(self.screen.rows - 1),
+            col.min(self.screen.cols),
+        )
+    }
+
+    fn font_and_size(&self, env: &Env) -> (String, f64) {
+        self.font.clone().unwrap_or_else(|| {
+            let name = env.get(theme::MONOSPACE_FONT_NAME);
+            (name.to_string(), env.get(theme::TEXT_SIZE_NORMAL))
+        })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            // waiting could take a moment, so the UI doesn't
+            thread::spawn(move || {
+                let _ = child.kill();
+                let _ = child.wait();
+            });
+        }
+    }
+}
+
+/// What a key sends to the program, if anything.
+fn key_bytes(key_code: KeyCode, mods: KeyModifiers, text: Option<&str>) -> Option<Vec<u8>> {
+    let sequence: &[u8] = match key_code {
+        KeyCode::Return | KeyCode::NumpadEnter => b"\r",
+        KeyCode::Backspace => b"\x7f",
+        KeyCode::Tab if mods.shift => b"\x1b[Z",
+        KeyCode::Tab => b"\t",
+        KeyCode::Escape => b"\x1b",
+        KeyCode::ArrowUp => b"\x1b[A",
+        KeyCode::ArrowDown => b"\x1b[B",
+        KeyCode::ArrowRight => b"\x1b[C",
+        KeyCode::ArrowLeft => b"\x1b[D",
+        KeyCode::Home => b"\x1b[H",
+        KeyCode::End => b"\x1b[F",
+        KeyCode::Insert => b"\x1b[2~",
+        KeyCode::Delete => b"\x1b[3~",
+        KeyCode::PageUp => b"\x1b[5~",
+        KeyCode::PageDown => b"\x1b[6~",
+        _ => {
+            let text = text?;
+            let mut bytes = Vec::new();
+            if mods.alt {
+                bytes.push(0x1b);
+            }
+            match text.chars().next() {
+                // Ctrl+A is 1, and so on
+                Some(c) if mods.ctrl && c.is_ascii_alphabetic() => {
+                    bytes.push(c.to_ascii_lowercase() as u8 - b'a' + 1);
+                }
+                Some(c) if mods.ctrl && "@[\\]^_".contains(c) => bytes.push(c as u8 & 0x1f),
+                Some(' ') if mods.ctrl => bytes.push(0),
+                _ => bytes.extend_from_slice(text.as_bytes()),
+            }
+            return Some(bytes);
+        }
+    };
+    Some(sequence.to_vec())
+}
+
+/// The color of a cell's text or background.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Shade {
+    Default,
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CellStyle {
+    fg: Shade,
+    bg: Shade,
+    bold: bool,
+    underline: bool,
+    inverse: bool,
+}
+
+impl CellStyle {
+    const DEFAULT: CellStyle = CellStyle {
+        fg: Shade::Default,
+        bg: Shade::Default,
+        bold: false,
+        underline: false,
+        inverse: false,
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    c: char,
+    style: CellStyle,
+}
+
+impl Cell {
+    const BLANK: Cell = Cell {
+        c: ' ',
+        style: CellStyle::DEFAULT,
+    };
+}
+
+/// The lines of the terminal, and the state that escape sequences change.
+struct Screen {
+    cols: usize,
+    rows: usize,
+    lines: Vec<Vec<Cell>>,
+    scrollback: VecDeque<Vec<Cell>>,
+    max_scrollback: usize,
+    row: usize,
+    col: usize,
+    /// A character printed in the last column wraps the line only once
+    /// another one follows it.
+    wrap_pending: bool,
+    saved_cursor: (usize, usize),
+    style: CellStyle,
+    /// The rows that scroll, from the top to the bottom one.
+    scroll_region: (usize, usize),
+    cursor_visible: bool,
+    /// The main screen, while full-screen programs use the alternate one.
+    main_screen: Option<Vec<Vec<Cell>>>,
+    /// Replies to the program, such as where the cursor is.
+    replies: Vec<u8>,
+}
+
+impl Screen {
+    fn new(cols: usize, rows: usize, max_scrollback: usize) -> Screen {
+        Screen {
+            cols,
+            rows,
+            lines: vec![vec![Cell::BLANK; cols]; rows],
+            scrollback: VecDeque::new(),
+            max_scrollback,
+            row: 0,
+            col: 0,
+            wrap_pending: false,
+            saved_cursor: (0, 0),
+            style: CellStyle::DEFAULT,
+            scroll_region: (0, rows - 1),
+            cursor_visible: true,
+            main_screen: None,
+            replies: Vec::new(),
+        }
+    }
+
+    /// A line of the scrollback followed by the screen.
+    fn line(&self, index: usize) -> &[Cell] {
+        match index.checked_sub(self.scrollback.len()) {
+            Some(row) => &self.lines[row],
+            None => &self.scrollback[index],
+        }
+    }
+
+    fn resize(&mut self, cols: usize, rows: usize) {
+        if (cols, rows) == (self.cols, self.rows) {
+            return;
+        }
+        // the lines above the cursor go to the scrollback, to keep it on
+        // the screen
+        while self.lines.len() > rows && self.row > 0 {
+            let line = self.lines.remove(0);
+            self.push_scrollback(line);
+            self.row -= 1;
+        }
+        for lines in self.main_screen.iter_mut().chain(Some(&mut self.lines)) {
+            lines.truncate(rows);
+            lines.resize(rows, vec![Cell::BLANK; cols]);
+            for line in lines.iter_mut() {
+                line.resize(cols, Cell::BLANK);
+            }
+        }
+        self.cols = cols;
+        self.rows = rows;
+        self.row = self.row.min(rows - 1);
+        self.col = self.col.min(cols - 1);
+        self.wrap_pending = false;
+        self.scroll_region = (0, rows - 1);
+    }
+
+    fn push_scrollback(&mut self, line: Vec<Cell>) {
+        if self.main_screen.is_some() || self.max_scrollback == 0 {
+            return;
+        }
+        if self.scrollback.len() == self.max_scrollback {
+            self.scrollback.pop_front();
+        }
+        self.scrollback.push_back(line);
+    }
+
+    fn print(&mut self, c: char) {
+        if self.wrap_pending {
+            self.col = 0;
+            self.linefeed();
+        }
+        self.lines[self.row][self.col] = Cell {
+            c,
+            style: self.style,
+        };
+        if self.col + 1 == self.cols {
+            self.wrap_pending = true;
+        } else {
+            self.col += 1;
+        }
+    }
+
+    fn linefeed(&mut self) {
+        self.wrap_pending = false;
+        if self.row == self.scroll_region.1 {
+            self.scroll_up(1);
+        } else if self.row + 1 < self.rows {
+            self.row += 1;
+        }
+    }
+
+    fn reverse_linefeed(&mut self) {
+        self.wrap_pending = false;
+        if self.row == self.scroll_region.0 {
+            self.scroll_down(1);
+        } else {
+            self.row = self.row.saturating_sub(1);
+        }
+    }
+
+    /// Move the lines of the scrolling region up, adding blank lines at
+    /// the bottom.
+    fn scroll_up(&mut self, count: usize) {
+        let (top, bottom) = self.scroll_region;
+        for _ in 0..count.min(bottom + 1 - top) {
+            let line = self.lines.remove(top);
+            self.lines.insert(bottom, vec![Cell::BLANK; self.cols]);
+            if top == 0 {
+                self.push_scrollback(line);
+            }
+        }
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        let (top, bottom) = self.scroll_region;
+        for _ in 0..count.min(bottom + 1 - top) {
+            self.lines.remove(bottom);
+            self.lines.insert(top, vec![Cell::BLANK; self.cols]);
+        }
+    }
+
+    fn erase(&mut self, row: usize, cols: Range<usize>) {
+        let end = cols.end.min(self.cols);
+        for cell in &mut self.lines[row][cols.start.min(end)..end] {
+            *cell = Cell::BLANK;
+        }
+    }
+
+    fn move_to(&mut self, row: usize, col: usize) {
+        self.row = row.min(self.rows - 1);
+        self.col = col.min(self.cols - 1);
+        self.wrap_pending = false;
+    }
+
+    fn set_alternate_screen(&mut self, on: bool) {
+        if on && self.main_screen.is_none() {
+            let blank = vec![vec![Cell::BLANK; self.cols]; self.rows];
+            self.main_screen = Some(std::mem::replace(&mut self.lines, blank));
+            self.saved_cursor = (self.row, self.col);
+        } else if !on {
+            if let Some(lines) = self.main_screen.take() {
+                self.lines = lines;
+                let (row, col) = self.saved_cursor;
+                self.move_to(row, col);
+            }
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, params: &[i64]) {
+        if params.is_empty() {
+            self.style = CellStyle::DEFAULT;
+            return;
+        }
+        let mut params = params.iter().copied();
+        while let Some(param) = params.next() {
+            match param {
+                0 => self.style = CellStyle::DEFAULT,
+                1 => self.style.bold = true,
+                4 => self.style.underline = true,
+                7 => self.style.inverse = true,
+                22 => self.style.bold = false,
+                24 => self.style.underline = false,
+                27 => self.style.inverse = false,
+                30..=37 => self.style.fg = Shade::Indexed(param as u8 - 30),
+                39 => self.style.fg = Shade::Default,
+                40..=47 => self.style.bg = Shade::Indexed(param as u8 - 40),
+                49 => self.style.bg = Shade::Default,
+                90..=97 => self.style.fg = Shade::Indexed(param as u8 - 90 + 8),
+                100..=107 => self.style.bg = Shade::Indexed(param as u8 - 100 + 8),
+                38 | 48 => {
+                    let shade = match params.next() {
+                        Some(5) => params.next().map(|i| Shade::Indexed(i as u8)),
+                        Some(2) => {
+                            let mut next = || params.next().unwrap_or(0) as u8;
+                            Some(Shade::Rgb(next(), next(), next()))
+                        }
+                        _ => None,
+                    };
+                    if let Some(shade) = shade {
+                        if param == 38 {
+                            self.style.fg = shade;
+                        } else {
+                            self.style.bg = shade;
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// The text from one line and column to another, with the spaces at the
+    /// ends of lines left out.
+    fn text(&self, from: (usize, usize), to: (usize, usize)) -> String {
+        let (start, end) = if from <= to { (from, to) } else { (to, from) };
+        let mut text = String::new();
+        for index in start.0..=end.0 {
+            let line = self.line(index);
+            let first = if index == start.0 { start.1 } else { 0 };
+            let last = if index == end.0 { end.1 } else { line.len() };
+            let chars: String = line[first.min(line.len())..last.min(line.len())]
+                .iter()
+                .map(|cell| cell.c)
+                .collect();
+            text.push_str(chars.trim_end());
+            if index != end.0 {
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+/// The `n`th parameter, or `default` if it is missing or zero.
+fn param(params: &[i64], n: usize, default: usize) -> usize {
+    match params.get(n) {
+        Some(&value) if value > 0 => value as usize,
+        _ => default,
+    }
+}
+
+impl Perform for Screen {
+    fn print(&mut self, c: char) {
+        Screen::print(self, c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' | 0x0b | 0x0c => self.linefeed(),
+            b'\r' => self.move_to(self.row, 0),
+            0x08 => self.move_to(self.row, self.col.saturating_sub(1)),
+            b'\t' => self.move_to(self.row, (self.col / 8 + 1) * 8),
+            _ => (),
+        }
+    }
+
+    fn hook(&mut self, _: &[i64], _: &[u8], _: bool, _: char) {}
+
+    fn put(&mut self, _: u8) {}
+
+    fn unhook(&mut self) {}
+
+    fn osc_dispatch(&mut self, _: &[&[u8]], _: bool) {}
+
+    fn csi_dispatch(&mut self, params: &[i64], intermediates: &[u8], _: bool, action: char) {
+        let (row, col) = (self.row, self.col);
+        let n = param(params, 0, 1);
+        match (intermediates, action) {
+            ([], 'A') => self.move_to(row.saturating_sub(n), col),
+            ([], 'B') => self.move_to(row + n, col),
+            ([], 'C') => self.move_to(row, col + n),
+            ([], 'D') => self.move_to(row, col.saturating_sub(n)),
+            ([], 'E') => self.move_to(row + n, 0),
+            ([], 'F') => self.move_to(row.saturating_sub(n), 0),
+            ([], 'G') | ([], '`') => self.move_to(row, n - 1),
+            ([], 'd') => self.move_to(n - 1, col),
+            ([], 'H') | ([], 'f') => self.move_to(n - 1, param(params, 1, 1) - 1),
+            ([], 'J') => {
+                let (below, above) = (row + 1..self.rows, 0..row);
+                match params.first().copied().unwrap_or(0) {
+                    0 => {
+                        self.erase(row, col..self.cols);
+                        below.for_each(|row| self.erase(row, 0..self.cols));
+                    }
+                    1 => {
+                        self.erase(row, 0..col + 1);
+                        above.for_each(|row| self.erase(row, 0..self.cols));
+                    }
+                    mode => {
+                        (0..self.rows).for_each(|row| self.erase(row, 0..self.cols));
+                        if mode == 3 {
+                            self.scrollback.clear();
+                        }
+                    }
+                }
+            }
+            ([], 'K') => match params.first().copied().unwrap_or(0) {
+                0 => self.erase(row, col..self.cols),
+                1 => self.erase(row, 0..col + 1),
+                _ => self.erase(row, 0..self.cols),
+            },
+            ([], 'L') | ([], 'M') if row >= self.scroll_region.0 && row <= self.scroll_region.1 => {
+                let region = self.scroll_region;
+                self.scroll_region = (row, region.1);
+                if action == 'L' {
+                    self.scroll_down(n);
+                } else {
+                    self.scroll_up(n);
+                }
+                self.scroll_region = region;
+            }
+            ([], '@') => {
+                let line = &mut self.lines[row];
+                for _ in 0..n.min(self.cols - col) {
+                    line.pop();
+                    line.insert(col, Cell::BLANK);
+                }
+            }
+            ([], 'P') => {
+                let line = &mut self.lines[row];
+                for _ in 0..n.min(self.cols - col) {
+                    line.remove(col);
+                    line.push(Cell::BLANK);
+                }
+            }
+            ([], 'X') => self.erase(row, col..col + n),
+            ([], 'S') => self.scroll_up(n),
+            ([], 'T') => self.scroll_down(n),
+            ([], 'm') => self.select_graphic_rendition(params),
+            ([], 'r') => {
+                let top = param(params, 0, 1) - 1;
+                let bottom = param(params, 1, self.rows).min(self.rows) - 1;
+                if top < bottom {
+                    self.scroll_region = (top, bottom);
+                    self.move_to(0, 0);
+                }
+            }
+            ([], 'n') if params.first() == Some(&6) => {
+                let reply = format!("\x1b[{};{}R", row + 1, col + 1);
+                self.replies.extend_from_slice(reply.as_bytes());
+            }
+            ([], 's') => self.saved_cursor = (row, col),
+            ([], 'u') => {
+                let (row, col) = self.saved_cursor;
+                self.move_to(row, col);
+            }
+            ([b'?'], 'h') | ([b'?'], 'l') => {
+                let on = action == 'h';
+                for mode in params {
+                    match mode {
+                        25 => self.cursor_visible = on,
+                        47 | 1047 | 1049 => self.set_alternate_screen(on),
+                        _ => (),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], _: bool, byte: u8) {
+        match (intermediates, byte) {
+            ([], b'7') => self.saved_cursor = (self.row, self.col),
+            ([], b'8') => {
+                let (row, col) = self.saved_cursor;
+                self.move_to(row, col);
+            }
+            ([], b'D') => self.linefeed(),
+            ([], b'E') => {
+                self.linefeed();
+                self.move_to(self.row, 0);
+            }
+            ([], b'M') => self.reverse_linefeed(),
+            ([], b'c') => {
+                let max_scrollback = self.max_scrollback;
+                *self = Screen::new(self.cols, self.rows, max_scrollback);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The color of `shade`, or of `default` for the default shade.
+fn color(shade: Shade, bold: bool, default: &Color) -> Color {
+    match shade {
+        Shade::Default => default.clone(),
+        // bold text is bright
+        Shade::Indexed(index) if bold && index < 8 => ANSI_COLORS[index as usize + 8].clone(),
+        Shade::Indexed(index) if index < 16 => ANSI_COLORS[index as usize].clone(),
+        // a 6×6×6 cube of colors, then 24 greys
+        Shade::Indexed(index) if index < 232 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + 40 * value };
+            let index = index - 16;
+            Color::rgb8(level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        Shade::Indexed(index) => Color::grey8(8 + 10 * (index - 232)),
+        Shade::Rgb(r, g, b) => Color::rgb8(r, g, b),
+    }
+}
+
+impl<T: Data> Widget<T> for Terminal {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut T, _env: &Env) {
+        match event {
+            Event::Command(cmd) if cmd.selector == OUTPUT => {
+                if let Ok(output) = cmd.get_object::<Vec<u8>>() {
+                    let scrollback = self.screen.scrollback.len();
+                    for byte in output {
+                        self.parser.advance(&mut self.screen, *byte);
+                    }
+                    let replies = std::mem::take(&mut self.screen.replies);
+                    if !replies.is_empty() {
+                        self.write(&replies);
+                    }
+                    // the view stays where it was scrolled back to
+                    if self.scroll > 0 {
+                        let added = self.screen.scrollback.len().saturating_sub(scrollback);
+                        self.scroll = (self.scroll + added).min(self.screen.scrollback.len());
+                    }
+                    ctx.request_paint();
+                }
+                ctx.set_handled();
+            }
+            Event::Command(cmd) if cmd.selector == commands::COPY && ctx.has_focus() => {
+                if let Some((from, to)) = self.selection {
+                    Application::clipboard().put_string(self.screen.text(from, to));
+                }
+                ctx.set_handled();
+            }
+            Event::Paste(clipboard) => {
+                if let Some(text) = clipboard.get_string() {
+                    self.write(text.as_bytes());
+                    self.scroll = 0;
+                }
+            }
+            Event::KeyDown(key) => {
+                if let Some(bytes) = key_bytes(key.key_code, key.mods, key.text()) {
+                    self.write(&bytes);
+                    self.scroll = 0;
+                    self.selection = None;
+                    ctx.request_paint();
+                    ctx.set_handled();
+                }
+            }
+            Event::MouseDown(mouse) => {
+                ctx.request_focus();
+                ctx.set_active(true);
+                let cell = self.cell_at(mouse.pos);
+                self.selection = Some((cell, cell));
+                ctx.request_paint();
+            }
+            Event::MouseMoved(mouse) if ctx.is_active() => {
+                let cell = self.cell_at(mouse.pos);
+                if let Some((_, end)) = &mut self.selection {
+                    *end = cell;
+                }
+                ctx.request_paint();
+            }
+            Event::MouseUp(_) if ctx.is_active() => {
+                ctx.set_active(false);
+                if let Some((start, end)) = self.selection {
+                    if start == end {
+                        self.selection = None;
+                    }
+                }
+                ctx.request_paint();
+            }
+            Event::Wheel(wheel) if self.cell.height > 0.0 => {
+                let lines = (wheel.delta.y / self.cell.height).round() as isize;
+                let scroll = (self.scroll as isize - lines).max(0) as usize;
+                self.scroll = scroll.min(self.screen.scrollback.len());
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &T, _env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => ctx.register_for_focus(),
+            LifeCycle::FocusChanged(_) => ctx.request_paint(),
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, _ctx: &mut UpdateCtx, _old_data: &T, _data: &T, _env: &Env) {}
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Terminal");
+        let (font_name, font_size) = self.font_and_size(env);
+        let line = TextLine::new(layout_ctx.text(), &font_name, font_size, "M", env);
+        self.cell = Size::new(line.width(), (font_size * 1.2).round());
+
+        let default = Size::new(
+            self.cell.width * DEFAULT_COLS as f64,
+            self.cell.height * DEFAULT_ROWS as f64,
+        );
+        let size = Size::new(
+            if bc.is_width_bounded() {
+                bc.max().width
+            } else {
+                default.width
+            },
+            if bc.is_height_bounded() {
+                bc.max().height
+            } else {
+                default.height
+            },
+        );
+        let size = bc.constrain(size);
+        let cols = ((size.width / self.cell.width) as usize).max(1);
+        let rows = ((size.height / self.cell.height) as usize).max(1);
+        if (cols, rows) != (self.screen.cols, self.screen.rows) {
+            self.screen.resize(cols, rows);
+            self.scroll = self.scroll.min(self.screen.scrollback.len());
+            let size = PtySize {
+                rows: rows as u16,
+                cols: cols as u16,
+                pixel_width: size.width as u16,
+                pixel_height: size.height as u16,
+            };
+            if let Err(e) = self.master.resize(size) {
+                error!("resizing the terminal failed: {}", e);
+            }
+        }
+        size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _data: &T, env: &Env) {
+        let (font_name, font_size) = self.font_and_size(env);
+        let foreground = env.get(theme::LABEL_COLOR);
+        let background = env.get(theme::BACKGROUND_DARK);
+        let selection_color = env.get(theme::SELECTION_COLOR);
+        let size = paint_ctx.size();
+        paint_ctx.fill(Rect::from_origin_size(Point::ORIGIN, size), &background);
+
+        let cell = self.cell;
+        // the same baseline as a label's
+        let baseline = (cell.height + font_size * 0.6) / 2.0;
+        let top = self.screen.scrollback.len() - self.scroll;
+        let selection = self
+            .selection
+            .map(|(from, to)| if from <= to { (from, to) } else { (to, from) });
+        for row in 0..self.screen.rows {
+            let index = top + row;
+            let line = self.screen.line(index);
+            let y = row as f64 * cell.height;
+            let mut col = 0;
+            // runs of cells with the same style are drawn together
+            while col < line.len() {
+                let style = line[col].style;
+                let run = line[col..]
+                    .iter()
+                    .take_while(|cell| cell.style == style)
+                    .count();
+                let (fg, bg) = if style.inverse {
+                    (
+                        color(style.bg, false, &background),
+                        color(style.fg, style.bold, &foreground),
+                    )
+                } else {
+                    (
+                        color(style.fg, style.bold, &foreground),
+                        color(style.bg, false, &background),
+                    )
+                };
+                let rect = Rect::new(
+                    col as f64 * cell.width,
+                    y,
+                    (col + run) as f64 * cell.width,
+                    y + cell.height,
+                );
+                if style.inverse || style.bg != Shade::Default {
+                    paint_ctx.fill(rect, &bg);
+                }
+                let text: String = line[col..col + run].iter().map(|cell| cell.c).collect();
+                if !text.trim().is_empty() {
+                    let layout = TextLine::new(paint_ctx.text(), &font_name, font_size, &text, env);
+                    let origin = Point::new(rect.x0, y + baseline);
+                    paint_ctx.draw_text(layout.layout(), origin, &fg);
+                    if style.bold {
+                        paint_ctx.draw_text(layout.layout(), origin + Vec2::new(0.6, 0.0), &fg);
+                    }
+                }
+                if style.underline {
+                    let y = y + baseline + 2.5;
+                    paint_ctx.stroke(Line::new((rect.x0, y), (rect.x1, y)), &fg, 1.0);
+                }
+                col += run;
+            }
+
+            if let Some((start, end)) = selection {
+                if index >= start.0 && index <= end.0 {
+                    let first = if index == start.0 { start.1 } else { 0 };
+                    let last = if index == end.0 {
+                        end.1
+                    } else {
+                        self.screen.cols
+                    };
+                    let rect = Rect::new(
+                        first as f64 * cell.width,
+                        y,
+                        last as f64 * cell.width,
+                        y + cell.height,
+                    );
+                    paint_ctx.fill(rect, &selection_color.clone().with_alpha(0.4));
+                }
+            }
+        }
+
+        let cursor_row = self.screen.row + self.scroll;
+        if self.screen.cursor_visible && cursor_row < self.screen.rows {
+            let rect = Rect::from_origin_size(
+                (
+                    self.screen.col as f64 * cell.width,
+                    cursor_row as f64 * cell.height,
+                ),
+                cell,
+            );
+            let cursor = env.get(theme::CURSOR_COLOR);
+            if paint_ctx.has_focus() {
+                paint_ctx.fill(rect, &cursor.with_alpha(0.6));
+            } else {
+                paint_ctx.stroke(rect.inset(-0.5), &cursor, 1.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen_after(cols: usize, rows: usize, output: &str) -> Screen {
+        let mut screen = Screen::new(cols, rows, 10);
+        let mut parser = Parser::new();
+        for byte in output.bytes() {
+            parser.advance(&mut screen, byte);
+        }
+        screen
+    }
+
+    fn text(screen: &Screen) -> Vec<String> {
+        let first = screen.scrollback.len();
+        (0..screen.rows)
+            .map(|row| screen.text((first + row, 0), (first + row, screen.cols)))
+            .collect()
+    }
+
+    #[test]
+    fn output_fills_the_screen_and_scrollback() {
+        let screen = screen_after(4, 2, "abcdef\r\nxy");
+        assert_eq!(text(&screen), vec!["ef", "xy"]);
+        assert_eq!(screen.scrollback.len(), 1);
+        assert_eq!(screen.text((0, 0), (2, 2)), "abcd\nef\nxy");
+        assert_eq!((screen.row, screen.col), (1, 2));
+
+        // moving the cursor, erasing, and colors
+        let screen = screen_after(5, 3, "hello\r\nworld\x1b[1;2H\x1b[K\x1b[31;1mX\x1b[0m");
+        assert_eq!(text(&screen), vec!["hX", "world", ""]);
+        let x = screen.lines[0][1];
+        assert_eq!(x.style.fg, Shade::Indexed(1));
+        assert!(x.style.bold);
+        assert_eq!(screen.lines[0][2].style, CellStyle::DEFAULT);
+
+        // full-screen programs leave the scrollback alone
+        let screen = screen_after(
+            3,
+            2,
+            "one\x1b[?1049h\x1b[2J\x1b[Hvim\r\n\r\n\r\n\x1b[?1049l",
+        );
+        assert_eq!(text(&screen), vec!["one", ""]);
+        assert!(screen.scrollback.is_empty());
+
+        let screen = screen_after(3, 2, "a\x1b[6n");
+        assert_eq!(screen.replies, b"\x1b[1;2R".to_vec());
+    }
+
+    #[test]
+    fn keys_are_sent_as_the_program_expects() {
+        let none = KeyModifiers::default();
+        let ctrl = KeyModifiers { ctrl: true, ..none };
+        let alt = KeyModifiers { alt: true, ..none };
+        let bytes = |code, mods, text| key_bytes(code, mods, text);
+        assert_eq!(bytes(KeyCode::KeyA, none, Some("a")), Some(b"a".to_vec()));
+        assert_eq!(bytes(KeyCode::KeyC, ctrl, Some("c")), Some(vec![3]));
+        assert_eq!(
+            bytes(KeyCode::KeyB, alt, Some("b")),
+            Some(b"\x1bb".to_vec())
+        );
+        assert_eq!(
+            bytes(KeyCode::ArrowUp, none, None),
+            Some(b"\x1b[A".to_vec())
+        );
+        assert_eq!(
+            bytes(KeyCode::Return, none, Some("\r")),
+            Some(b"\r".to_vec())
+        );
+        assert_eq!(bytes(KeyCode::LeftShift, none, None), None);
+
+        assert_eq!(
+            color(Shade::Indexed(1), true, &Color::WHITE).as_rgba_u32(),
+            ANSI_COLORS[9].as_rgba_u32()
+        );
+        assert_eq!(
+            color(Shade::Indexed(196), false, &Color::WHITE).as_rgba_u32(),
+            Color::rgb8(255, 0, 0).as_rgba_u32()
+        );
+    }
+}
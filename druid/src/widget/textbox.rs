@@ -14,22 +14,26 @@
 
 //! A textbox widget.
 
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
 use std::time::{Duration, Instant};
 
 use crate::{
-    Application, BoxConstraints, Cursor, Env, Event, EventCtx, HotKey, KeyCode, LayoutCtx,
-    LifeCycle, LifeCycleCtx, PaintCtx, RawMods, Selector, SysMods, TimerToken, UpdateCtx, Widget,
+    commands, Application, BoxConstraints, Command, ContextMenu, Cursor, EditAction, Env, Event,
+    EventCtx, KeyBindings, LayoutCtx, LifeCycle, LifeCycleCtx, LocalizedString, MenuDesc, MenuItem,
+    MouseButton, PaintCtx, Selector, Spellchecker, TimerToken, UpdateCtx, Widget, WidgetId,
 };
 
-use crate::kurbo::{Affine, Line, Point, RoundedRect, Size, Vec2};
-use crate::piet::{
-    FontBuilder, PietText, PietTextLayout, RenderContext, Text, TextLayout, TextLayoutBuilder,
-    UnitPoint,
-};
+use crate::kurbo::{Affine, BezPath, Line, Point, Rect, RoundedRect, Size, Vec2};
+use crate::piet::{PietText, RenderContext, UnitPoint};
 use crate::theme;
 use crate::widget::Align;
 
-use crate::text::{movement, offset_for_delete_backwards, EditableText, Movement, Selection};
+use crate::text::{
+    movement, offset_for_delete_backwards, EditHistory, EditKind, EditableText, Granularity, Mask,
+    Movement, Selection, Spelling, TextLine,
+};
 
 const BORDER_WIDTH: f64 = 1.;
 const PADDING_TOP: f64 = 5.;
@@ -38,6 +42,40 @@ const PADDING_LEFT: f64 = 4.;
 // we send ourselves this when we want to reset blink, which must be done in event.
 const RESET_BLINK: Selector = Selector::new("druid-builtin.reset-textbox-blink");
 
+// sent by the items of the corrections menu, with a `Correction`.
+const CORRECT_SPELLING: Selector = Selector::new("druid-builtin.textbox-correct-spelling");
+
+/// How often the text scrolls while a selection is dragged past an edge.
+const AUTOSCROLL_INTERVAL: Duration = Duration::from_millis(30);
+
+/// The most corrections offered for a misspelled word.
+const MAX_CORRECTIONS: usize = 6;
+
+/// A replacement for a misspelled word, picked from the context menu.
+///
+/// Menu commands go to the whole window, so this says which text box it is for.
+#[derive(Debug, Clone)]
+struct Correction {
+    widget: WidgetId,
+    range: Range<usize>,
+    word: String,
+    replacement: String,
+}
+
+/// A hook that pasted text goes through; see [`TextBox::paste_filter`].
+///
+/// [`TextBox::paste_filter`]: struct.TextBox.html#method.paste_filter
+type PasteFilter = Rc<dyn Fn(&str, &str) -> Option<String>>;
+
+#[derive(Clone, Default)]
+struct PasteFilters(Vec<PasteFilter>);
+
+impl fmt::Debug for PasteFilters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PasteFilters({})", self.0.len())
+    }
+}
+
 /// A widget that allows user text input.
 #[derive(Debug, Clone)]
 pub struct TextBox {
@@ -47,6 +85,17 @@ pub struct TextBox {
     selection: Selection,
     cursor_timer: TimerToken,
     cursor_on: bool,
+    /// While the mouse is selecting, the units it selects by and the one
+    /// it started at.
+    drag: Option<(Granularity, Range<usize>)>,
+    /// Where the mouse last was while selecting.
+    drag_pos: Point,
+    scroll_timer: TimerToken,
+    mask: Option<Mask>,
+    spelling: Option<Spelling>,
+    history: EditHistory,
+    paste_filters: PasteFilters,
+    key_bindings: KeyBindings,
 }
 
 impl TextBox {
@@ -62,6 +111,34 @@ impl TextBox {
         Align::vertical(UnitPoint::CENTER, textbox)
     }
 
+    /// Create a new TextBox widget whose text is kept to `pattern`, such as
+    /// `(###) ###-####` for a phone number or `__/__/____` for a date.
+    ///
+    /// In the pattern, `#` and `_` are digits, `A` is a letter, `*` is a
+    /// letter or digit, and anything else is shown as it is; a `\` makes the
+    /// character after it literal. What the user types goes in the slots in
+    /// order, characters that don't fit are left out, and the literals are
+    /// put in as they are reached, with the caret stepping over them.
+    ///
+    /// The pattern, with `_` for the slots, is the placeholder.
+    pub fn with_mask(pattern: &str) -> impl Widget<String> {
+        let mut textbox = Self::raw();
+        let mask = Mask::new(pattern);
+        textbox.placeholder = mask.placeholder();
+        textbox.mask = Some(mask);
+        Align::vertical(UnitPoint::CENTER, textbox)
+    }
+
+    /// Create a new TextBox widget that checks the spelling of its text.
+    ///
+    /// Words that `checker` doesn't know are underlined, and right-clicking
+    /// one shows a menu of the corrections it suggests.
+    pub fn with_spellcheck(checker: impl Spellchecker + 'static) -> impl Widget<String> {
+        let mut textbox = Self::raw();
+        textbox.spelling = Some(Spelling::new(checker));
+        Align::vertical(UnitPoint::CENTER, textbox)
+    }
+
     /// Create a new TextBox widget with no Align wrapper
     pub fn raw() -> TextBox {
         Self {
@@ -70,24 +147,75 @@ impl TextBox {
             selection: Selection::caret(0),
             cursor_timer: TimerToken::INVALID,
             cursor_on: false,
+            drag: None,
+            drag_pos: Point::ORIGIN,
+            scroll_timer: TimerToken::INVALID,
             placeholder: String::new(),
+            mask: None,
+            spelling: None,
+            history: EditHistory::default(),
+            paste_filters: PasteFilters::default(),
+            key_bindings: KeyBindings::default(),
+        }
+    }
+
+    /// Builder-style method to transform or reject text before it is pasted.
+    ///
+    /// `filter` gets the pasted text, as plain text, and the rest of the
+    /// text box's text: what will be left around it once the selection it
+    /// replaces is gone. It returns the text to insert, or `None` to paste
+    /// nothing. Filters are applied in the order they are added.
+    ///
+    /// ```
+    /// use druid::widget::TextBox;
+    ///
+    /// const MAX_LEN: usize = 20;
+    /// let textbox = TextBox::raw()
+    ///     // one line, without the whitespace around it
+    ///     .paste_filter(|pasted, _| Some(pasted.replace(&['\r', '\n'][..], " ")))
+    ///     .paste_filter(|pasted, _| Some(pasted.trim().to_string()))
+    ///     // and only if it fits
+    ///     .paste_filter(|pasted, rest| {
+    ///         if rest.len() + pasted.len() <= MAX_LEN {
+    ///             Some(pasted.to_string())
+    ///         } else {
+    ///             None
+    ///         }
+    ///     });
+    /// ```
+    pub fn paste_filter(mut self, filter: impl Fn(&str, &str) -> Option<String> + 'static) -> Self {
+        self.paste_filters.0.push(Rc::new(filter));
+        self
+    }
+
+    /// Builder-style method to set the keys that edit the text; see
+    /// [`KeyBindings`].
+    ///
+    /// [`KeyBindings`]: ../struct.KeyBindings.html
+    pub fn key_bindings(mut self, bindings: KeyBindings) -> Self {
+        self.key_bindings = bindings;
+        self
+    }
+
+    /// The text to insert for `pasted`, after the paste filters.
+    fn filter_paste(&self, text: &str, pasted: String) -> Option<String> {
+        if self.paste_filters.0.is_empty() {
+            return Some(pasted);
         }
+        let mut rest = text.to_owned();
+        rest.replace_range(self.selection.range(), "");
+        self.paste_filters
+            .0
+            .iter()
+            .try_fold(pasted, |pasted, filter| filter(&pasted, &rest))
     }
 
-    /// Calculate the PietTextLayout from the given text, font, and font size
-    fn get_layout(&self, piet_text: &mut PietText, text: &str, env: &Env) -> PietTextLayout {
+    /// Lay out and measure the given text, with the theme's font and font size
+    fn get_layout(&self, piet_text: &mut PietText, text: &str, env: &Env) -> TextLine {
         let font_name = env.get(theme::FONT_NAME);
         let font_size = env.get(theme::TEXT_SIZE_NORMAL);
         // TODO: caching of both the format and the layout
-        let font = piet_text
-            .new_font_by_name(font_name, font_size)
-            .build()
-            .unwrap();
-
-        piet_text
-            .new_text_layout(&font, &text.to_string())
-            .build()
-            .unwrap()
+        TextLine::new(piet_text, font_name, font_size, text, env)
     }
 
     /// Insert text at the cursor position.
@@ -100,6 +228,15 @@ impl TextBox {
         // TODO: perhaps this belongs in update?
         let selection = self.selection.constrain_to(src);
 
+        if let Some(mask) = &self.mask {
+            let start = mask.count_before(src, selection.min());
+            let end = mask.count_before(src, selection.max());
+            let (text, caret) = mask.edit(src, start..end, new);
+            *src = text;
+            self.selection = Selection::caret(caret);
+            return;
+        }
+
         src.edit(selection.range(), new);
         self.selection = Selection::caret(selection.min() + new.len());
     }
@@ -121,14 +258,51 @@ impl TextBox {
 
     /// Edit a selection using a `Movement`.
     fn move_selection(&mut self, mvmnt: Movement, text: &mut String, modify: bool) {
+        if let Some(mask) = &self.mask {
+            // step over the literals, one entered character at a time
+            let forward = match mvmnt {
+                Movement::Left => Some(false),
+                Movement::Right => Some(true),
+                _ => None,
+            };
+            if let Some(forward) = forward {
+                let end = mask.step(text, self.selection.end, forward);
+                self.selection = if modify {
+                    Selection::new(self.selection.start, end)
+                } else {
+                    Selection::caret(end)
+                };
+                return;
+            }
+        }
         // This movement function should ensure all movements are legit.
         // If they aren't, that's a problem with the movement function.
         self.selection = movement(mvmnt, self.selection, text, modify);
     }
 
+    /// The closest offset to `offset` that the caret can be at.
+    fn snap(&self, text: &str, offset: usize) -> usize {
+        match &self.mask {
+            Some(mask) => mask.snap(text, offset),
+            None => offset,
+        }
+    }
+
     /// Delete to previous grapheme if in caret mode.
     /// Otherwise just delete everything inside the selection.
     fn delete_backward(&mut self, text: &mut String) {
+        if let Some(mask) = &self.mask {
+            let end = mask.count_before(text, self.selection.max());
+            let start = if self.selection.is_caret() {
+                end.saturating_sub(1)
+            } else {
+                mask.count_before(text, self.selection.min())
+            };
+            let (new_text, caret) = mask.edit(text, start..end, "");
+            *text = new_text;
+            self.selection = Selection::caret(caret);
+            return;
+        }
         if self.selection.is_caret() {
             let cursor = self.cursor();
             let new_cursor = offset_for_delete_backwards(&self.selection, text);
@@ -142,27 +316,30 @@ impl TextBox {
 
     /// For a given point, returns the corresponding offset (in bytes) of
     /// the grapheme cluster closest to that point.
-    fn offset_for_point(&self, point: Point, layout: &PietTextLayout) -> usize {
+    fn offset_for_point(&self, point: Point, layout: &TextLine) -> usize {
         // Translating from screenspace to Piet's text layout representation.
         // We need to account for hscroll_offset state and TextBox's padding.
-        let translated_point = Point::new(point.x + self.hscroll_offset - PADDING_LEFT, point.y);
-        let hit_test = layout.hit_test_point(translated_point);
-        hit_test.metrics.text_position
+        layout.offset_for_x(point.x + self.hscroll_offset - PADDING_LEFT)
+    }
+
+    /// Extend the selection being dragged to the mouse.
+    fn drag_to(&mut self, text: &str, layout: &TextLine) {
+        if let Some((granularity, anchor)) = self.drag.clone() {
+            let offset = self.offset_for_point(self.drag_pos, layout);
+            let offset = self.snap(text, offset);
+            self.selection = granularity.extend(text, anchor, offset);
+        }
     }
 
     /// Given an offset (in bytes) of a valid grapheme cluster, return
     /// the corresponding x coordinate of that grapheme on the screen.
-    fn x_for_offset(&self, layout: &PietTextLayout, offset: usize) -> f64 {
-        if let Some(position) = layout.hit_test_text_position(offset) {
-            position.point.x
-        } else {
-            //TODO: what is the correct fallback here?
-            0.0
-        }
+    fn x_for_offset(&self, layout: &TextLine, offset: usize) -> f64 {
+        //TODO: what is the correct fallback here?
+        layout.x_for_offset(offset).unwrap_or(0.0)
     }
 
     /// Calculate a stateful scroll offset
-    fn update_hscroll(&mut self, layout: &PietTextLayout) {
+    fn update_hscroll(&mut self, layout: &TextLine) {
         let cursor_x = self.x_for_offset(layout, self.cursor());
         let overall_text_width = layout.width();
 
@@ -188,6 +365,167 @@ impl TextBox {
         }
     }
 
+    /// Show the corrections for the misspelled word at `offset`, if there
+    /// is one, returning whether there was.
+    fn show_corrections(
+        &mut self,
+        ctx: &mut EventCtx,
+        text: &str,
+        offset: usize,
+        window_pos: Point,
+    ) -> bool {
+        let spelling = match &mut self.spelling {
+            Some(spelling) => spelling,
+            None => return false,
+        };
+        let range = match spelling.misspelling_at(text, offset) {
+            Some(range) => range,
+            None => return false,
+        };
+        let word = &text[range.clone()];
+        let mut menu = MenuDesc::empty();
+        for replacement in spelling.suggestions(word).into_iter().take(MAX_CORRECTIONS) {
+            let title = LocalizedString::new("").with_placeholder(replacement.clone());
+            let correction = Correction {
+                widget: ctx.widget_id(),
+                range: range.clone(),
+                word: word.to_owned(),
+                replacement,
+            };
+            menu = menu.append(MenuItem::new(
+                title,
+                Command::new(CORRECT_SPELLING, correction),
+            ));
+        }
+        if menu.is_empty() {
+            let title = LocalizedString::new("textbox-no-spelling-suggestions");
+            menu = menu.append(MenuItem::new(title, Selector::NOOP).disabled());
+        }
+        let menu: ContextMenu<()> = ContextMenu::new(menu, window_pos);
+        ctx.submit_command(Command::new(commands::SHOW_CONTEXT_MENU, menu), None);
+        true
+    }
+
+    /// Replace the misspelled word, if the text still has it.
+    fn correct(&mut self, text: &mut String, correction: &Correction) -> bool {
+        if text.get(correction.range.clone()) != Some(&correction.word) {
+            return false;
+        }
+        text.edit(correction.range.clone(), &correction.replacement);
+        self.caret_to(text, correction.range.start + correction.replacement.len());
+        true
+    }
+
+    /// Underline the misspelled words of `text`, except the one being typed.
+    fn paint_misspellings(
+        &mut self,
+        ctx: &mut PaintCtx,
+        text: &str,
+        layout: &TextLine,
+        baseline: Point,
+        env: &Env,
+    ) {
+        let caret = if ctx.has_focus() && self.selection.is_caret() {
+            Some(self.cursor())
+        } else {
+            None
+        };
+        let spelling = match &mut self.spelling {
+            Some(spelling) => spelling,
+            None => return,
+        };
+        let color = env.get(theme::MISSPELLING_COLOR);
+        for range in spelling.misspelled(text) {
+            if caret == Some(range.end) {
+                continue;
+            }
+            let start = layout.x_for_offset(range.start).unwrap_or(0.0);
+            let end = layout.x_for_offset(range.end).unwrap_or(0.0);
+            // a zigzag, two points high, just under the baseline
+            let y = baseline.y + 2.;
+            let mut line = BezPath::new();
+            line.move_to((baseline.x + start, y));
+            let mut x = start;
+            let mut up = true;
+            while x < end {
+                x = (x + 2.).min(end);
+                let dy = if up { -1. } else { 1. };
+                line.line_to((baseline.x + x, y + dy));
+                up = !up;
+            }
+            ctx.stroke(line, &color, 1.);
+        }
+    }
+
+    /// Do what a key is bound to, returning the kind of edit it made.
+    fn perform(
+        &mut self,
+        ctx: &mut EventCtx,
+        action: EditAction,
+        text: &mut String,
+        env: &Env,
+    ) -> Option<EditKind> {
+        let (movement, select) = match action {
+            EditAction::MoveLeft => (Movement::Left, false),
+            EditAction::MoveRight => (Movement::Right, false),
+            EditAction::MoveToLineStart => (Movement::LeftOfLine, false),
+            EditAction::MoveToLineEnd => (Movement::RightOfLine, false),
+            EditAction::SelectLeft => (Movement::Left, true),
+            EditAction::SelectRight => (Movement::Right, true),
+            EditAction::SelectToLineStart => (Movement::LeftOfLine, true),
+            EditAction::SelectToLineEnd => (Movement::RightOfLine, true),
+            EditAction::SelectAll => {
+                self.selection.all(text);
+                return None;
+            }
+            EditAction::DeleteBackward | EditAction::DeleteForward => {
+                if action == EditAction::DeleteForward && self.selection.is_caret() {
+                    // Never touch the characters before the cursor.
+                    if text.next_grapheme_offset(self.cursor()).is_some() {
+                        self.move_selection(Movement::Right, text, false);
+                        self.delete_backward(text);
+                    }
+                } else {
+                    self.delete_backward(text);
+                }
+                submit_undoable(ctx, "textbox-undo-typing", env);
+                self.reset_cursor_blink(ctx);
+                return Some(EditKind::Deleting);
+            }
+            // if there's nothing to undo here the app's undo gets the key
+            EditAction::Undo | EditAction::Redo => {
+                self.step_history(ctx, text, action == EditAction::Redo);
+                return None;
+            }
+            EditAction::FocusNext => {
+                ctx.focus_next();
+                return None;
+            }
+            EditAction::FocusPrevious => {
+                ctx.focus_prev();
+                return None;
+            }
+        };
+        self.move_selection(movement, text, select);
+        if !select {
+            self.reset_cursor_blink(ctx);
+        }
+        None
+    }
+
+    /// Undo, or redo, an edit of the text, if there is one.
+    fn step_history(&mut self, ctx: &mut EventCtx, text: &mut String, redo: bool) {
+        let done = if redo {
+            self.history.redo(text, &mut self.selection)
+        } else {
+            self.history.undo(text, &mut self.selection)
+        };
+        if done {
+            ctx.set_handled();
+            ctx.request_paint();
+        }
+    }
+
     fn reset_cursor_blink(&mut self, ctx: &mut EventCtx) {
         self.cursor_on = true;
         let deadline = Instant::now() + Duration::from_millis(500);
@@ -202,27 +540,54 @@ impl Widget<String> for TextBox {
         self.selection = self.selection.constrain_to(data);
 
         let mut text_layout = self.get_layout(&mut ctx.text(), &data, env);
+        // what the text was before this event, if it can be edited by it
+        let before = match event {
+            Event::KeyDown(_) | Event::Paste(_) | Event::Command(_) => {
+                Some((data.clone(), self.selection))
+            }
+            _ => None,
+        };
+        let mut edit = None;
         match event {
             Event::MouseDown(mouse) => {
                 ctx.request_focus();
-                ctx.set_active(true);
                 let cursor_off = self.offset_for_point(mouse.pos, &text_layout);
-                if mouse.mods.shift {
-                    self.selection.end = cursor_off;
-                } else {
-                    self.caret_to(data, cursor_off);
+                if mouse.button == MouseButton::Right
+                    && self.show_corrections(ctx, data, cursor_off, mouse.window_pos)
+                {
+                    return;
                 }
+                ctx.set_active(true);
+                let cursor_off = self.snap(data, cursor_off);
+                // double and triple clicks select, and drag, by word and line
+                let granularity = Granularity::from_click_count(mouse.count);
+                let anchor = if mouse.mods.shift {
+                    self.selection.start..self.selection.start
+                } else {
+                    granularity.unit_at(data, cursor_off)
+                };
+                self.selection = granularity.extend(data, anchor.clone(), cursor_off);
+                self.drag = Some((granularity, anchor));
+                self.drag_pos = mouse.pos;
                 ctx.request_paint();
                 self.reset_cursor_blink(ctx);
             }
             Event::MouseMoved(mouse) => {
                 ctx.set_cursor(&Cursor::IBeam);
                 if ctx.is_active() {
-                    self.selection.end = self.offset_for_point(mouse.pos, &text_layout);
+                    self.drag_pos = mouse.pos;
+                    self.drag_to(data, &text_layout);
+                    // past the edges, the text scrolls until the mouse comes back
+                    let outside = mouse.pos.x < 0. || mouse.pos.x > self.width;
+                    if outside && self.scroll_timer == TimerToken::INVALID {
+                        let deadline = Instant::now() + AUTOSCROLL_INTERVAL;
+                        self.scroll_timer = ctx.request_timer(deadline);
+                    }
                     ctx.request_paint();
                 }
             }
             Event::MouseUp(_) => {
+                self.drag = None;
                 if ctx.is_active() {
                     ctx.set_active(false);
                     ctx.request_paint();
@@ -234,6 +599,16 @@ impl Widget<String> for TextBox {
                     ctx.request_paint();
                     let deadline = Instant::now() + Duration::from_millis(500);
                     self.cursor_timer = ctx.request_timer(deadline);
+                } else if *id == self.scroll_timer {
+                    self.scroll_timer = TimerToken::INVALID;
+                    let outside = self.drag_pos.x < 0. || self.drag_pos.x > self.width;
+                    if ctx.is_active() && outside {
+                        self.drag_to(data, &text_layout);
+                        self.update_hscroll(&text_layout);
+                        ctx.request_paint();
+                        let deadline = Instant::now() + AUTOSCROLL_INTERVAL;
+                        self.scroll_timer = ctx.request_timer(deadline);
+                    }
                 }
             }
             Event::Command(ref cmd)
@@ -246,85 +621,48 @@ impl Widget<String> for TextBox {
                 }
                 if !self.selection.is_caret() && cmd.selector == crate::commands::CUT {
                     self.delete_backward(data);
+                    submit_undoable(ctx, "textbox-undo-cut", env);
+                    edit = Some(EditKind::Other);
                 }
                 ctx.set_handled();
             }
+            Event::Command(cmd)
+                if ctx.has_focus()
+                    && (cmd.selector == commands::UNDO || cmd.selector == commands::REDO) =>
+            {
+                self.step_history(ctx, data, cmd.selector == commands::REDO)
+            }
             Event::Command(cmd) if cmd.selector == RESET_BLINK => self.reset_cursor_blink(ctx),
+            Event::Command(cmd) if cmd.selector == CORRECT_SPELLING => {
+                if let Ok(correction) = cmd.get_object::<Correction>() {
+                    if correction.widget == ctx.widget_id() && self.correct(data, correction) {
+                        submit_undoable(ctx, "textbox-undo-correct-spelling", env);
+                        edit = Some(EditKind::Other);
+                        ctx.request_paint();
+                    }
+                }
+            }
             Event::Paste(ref item) => {
-                if let Some(string) = item.get_string() {
+                let pasted = item.get_string();
+                if let Some(string) = pasted.and_then(|s| self.filter_paste(data, s)) {
                     self.insert(data, &string);
+                    submit_undoable(ctx, "textbox-undo-paste", env);
+                    edit = Some(EditKind::Other);
                     self.reset_cursor_blink(ctx);
                 }
             }
-            //TODO: move this to a 'handle_key' function, remove the #allow above
             Event::KeyDown(key_event) => {
-                match key_event {
-                    // Select all (Ctrl+A || Cmd+A)
-                    k_e if (HotKey::new(SysMods::Cmd, "a")).matches(k_e) => {
-                        self.selection.all(data);
-                    }
-                    // Jump left (Ctrl+ArrowLeft || Cmd+ArrowLeft)
-                    k_e if (HotKey::new(SysMods::Cmd, KeyCode::ArrowLeft)).matches(k_e)
-                        || HotKey::new(None, KeyCode::Home).matches(k_e) =>
-                    {
-                        self.move_selection(Movement::LeftOfLine, data, false);
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Jump right (Ctrl+ArrowRight || Cmd+ArrowRight)
-                    k_e if (HotKey::new(SysMods::Cmd, KeyCode::ArrowRight)).matches(k_e)
-                        || HotKey::new(None, KeyCode::End).matches(k_e) =>
-                    {
-                        self.move_selection(Movement::RightOfLine, data, false);
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Select left (Shift+ArrowLeft)
-                    k_e if (HotKey::new(RawMods::Shift, KeyCode::ArrowLeft)).matches(k_e) => {
-                        self.move_selection(Movement::Left, data, true);
-                    }
-                    // Select right (Shift+ArrowRight)
-                    k_e if (HotKey::new(RawMods::Shift, KeyCode::ArrowRight)).matches(k_e) => {
-                        self.move_selection(Movement::Right, data, true);
-                    }
-                    // Move left (ArrowLeft)
-                    k_e if (HotKey::new(None, KeyCode::ArrowLeft)).matches(k_e) => {
-                        self.move_selection(Movement::Left, data, false);
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Move right (ArrowRight)
-                    k_e if (HotKey::new(None, KeyCode::ArrowRight)).matches(k_e) => {
-                        self.move_selection(Movement::Right, data, false);
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Backspace
-                    k_e if (HotKey::new(None, KeyCode::Backspace)).matches(k_e) => {
-                        self.delete_backward(data);
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Delete
-                    k_e if (HotKey::new(None, KeyCode::Delete)).matches(k_e) => {
-                        if self.selection.is_caret() {
-                            // Never touch the characters before the cursor.
-                            if data.next_grapheme_offset(self.cursor()).is_some() {
-                                self.move_selection(Movement::Right, data, false);
-                                self.delete_backward(data);
-                            }
-                        } else {
-                            self.delete_backward(data);
-                        }
-                        self.reset_cursor_blink(ctx);
-                    }
-                    // Tab and shift+tab
-                    k_e if HotKey::new(None, KeyCode::Tab).matches(k_e) => ctx.focus_next(),
-                    k_e if HotKey::new(RawMods::Shift, KeyCode::Tab).matches(k_e) => {
-                        ctx.focus_prev()
-                    }
+                match self.key_bindings.action_for(key_event) {
+                    Some(action) => edit = self.perform(ctx, action, data, env),
                     // Actual typing
-                    k_e if k_e.key_code.is_printable() => {
-                        let incoming_text = k_e.text().unwrap_or("");
+                    None if key_event.key_code.is_printable() => {
+                        let incoming_text = key_event.text().unwrap_or("");
                         self.insert(data, incoming_text);
+                        submit_undoable(ctx, "textbox-undo-typing", env);
+                        edit = Some(EditKind::Typing);
                         self.reset_cursor_blink(ctx);
                     }
-                    _ => {}
+                    None => {}
                 }
                 text_layout = self.get_layout(&mut ctx.text(), &data, env);
                 self.update_hscroll(&text_layout);
@@ -332,6 +670,12 @@ impl Widget<String> for TextBox {
             }
             _ => (),
         }
+        if let (Some(kind), Some((text, selection))) = (edit, before) {
+            let caret = self.cursor();
+            let now = Instant::now();
+            self.history
+                .record(&text, selection, data, kind, caret, now);
+        }
     }
 
     fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &String, _env: &Env) {
@@ -343,13 +687,15 @@ impl Widget<String> for TextBox {
         }
     }
 
-    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &String, _data: &String, _env: &Env) {
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &String, data: &String, _env: &Env) {
+        // the edit history is only for the text this box made
+        self.history.sync(data);
         ctx.request_paint();
     }
 
     fn layout(
         &mut self,
-        _layout_ctx: &mut LayoutCtx,
+        layout_ctx: &mut LayoutCtx,
         bc: &BoxConstraints,
         _data: &String,
         env: &Env,
@@ -362,7 +708,10 @@ impl Widget<String> for TextBox {
             self.width = default_width;
         }
 
-        bc.constrain((self.width, env.get(theme::BORDERED_WIDGET_HEIGHT)))
+        let size = bc.constrain((self.width, env.get(theme::BORDERED_WIDGET_HEIGHT)));
+        let baseline = env.get(theme::TEXT_SIZE_NORMAL) * 0.8 + PADDING_TOP;
+        layout_ctx.set_baseline_offset(size.height - baseline);
+        size
     }
 
     fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &String, env: &Env) {
@@ -378,18 +727,13 @@ impl Widget<String> for TextBox {
         let font_size = env.get(theme::TEXT_SIZE_NORMAL);
         let height = env.get(theme::BORDERED_WIDGET_HEIGHT);
         let background_color = env.get(theme::BACKGROUND_LIGHT);
-        let selection_color = env.get(theme::SELECTION_COLOR);
         let text_color = env.get(theme::LABEL_COLOR);
         let placeholder_color = env.get(theme::PLACEHOLDER_COLOR);
         let cursor_color = env.get(theme::CURSOR_COLOR);
 
         let has_focus = paint_ctx.has_focus();
 
-        let border_color = if has_focus {
-            env.get(theme::PRIMARY_LIGHT)
-        } else {
-            env.get(theme::BORDER_DARK)
-        };
+        let border_color = env.get(theme::BORDER_DARK);
 
         // Paint the background
         let clip_rect = RoundedRect::from_origin_size(
@@ -401,64 +745,75 @@ impl Widget<String> for TextBox {
         paint_ctx.fill(clip_rect, &background_color);
 
         // Render text, selection, and cursor inside a clip
-        paint_ctx
-            .with_save(|rc| {
-                rc.clip(clip_rect);
+        if let Err(e) = paint_ctx.save() {
+            log::error!("saving render context failed: {:?}", e);
+            return;
+        }
+        paint_ctx.clip(clip_rect);
 
-                // Calculate layout
-                let text_layout = self.get_layout(rc.text(), &content, env);
+        // Calculate layout
+        let text_layout = self.get_layout(paint_ctx.text(), content, env);
 
-                // Shift everything inside the clip by the hscroll_offset
-                rc.transform(Affine::translate((-self.hscroll_offset, 0.)));
+        // Shift everything inside the clip by the hscroll_offset
+        paint_ctx.transform(Affine::translate((-self.hscroll_offset, 0.)));
 
-                // Draw selection rect
-                if !self.selection.is_caret() {
-                    let (left, right) = (self.selection.min(), self.selection.max());
-                    let left_offset = self.x_for_offset(&text_layout, left);
-                    let right_offset = self.x_for_offset(&text_layout, right);
+        // Draw selection rect
+        if !self.selection.is_caret() {
+            let (left, right) = (self.selection.min(), self.selection.max());
+            let left_offset = self.x_for_offset(&text_layout, left);
+            let right_offset = self.x_for_offset(&text_layout, right);
 
-                    let selection_width = right_offset - left_offset;
+            let selection_width = right_offset - left_offset;
 
-                    let selection_pos =
-                        Point::new(left_offset + PADDING_LEFT - 1., PADDING_TOP - 2.);
+            let selection_pos = Point::new(left_offset + PADDING_LEFT - 1., PADDING_TOP - 2.);
 
-                    let selection_rect = RoundedRect::from_origin_size(
-                        selection_pos,
-                        Size::new(selection_width + 2., font_size + 4.).to_vec2(),
-                        1.,
-                    );
-                    rc.fill(selection_rect, &selection_color);
-                }
+            let selection_rect = Rect::from_origin_size(
+                selection_pos,
+                Size::new(selection_width + 2., font_size + 4.),
+            );
+            paint_ctx.paint_selection(selection_rect, env);
+        }
 
-                // Layout, measure, and draw text
-                let text_height = font_size * 0.8;
-                let text_pos = Point::new(0.0 + PADDING_LEFT, text_height + PADDING_TOP);
-                let color = if data.is_empty() {
-                    &placeholder_color
-                } else {
-                    &text_color
-                };
+        // Layout, measure, and draw text
+        let text_height = font_size * 0.8;
+        let text_pos = Point::new(0.0 + PADDING_LEFT, text_height + PADDING_TOP);
+        let color = if data.is_empty() {
+            &placeholder_color
+        } else {
+            &text_color
+        };
 
-                rc.draw_text(&text_layout, text_pos, color);
+        paint_ctx.draw_text(text_layout.layout(), text_pos, color);
+        if !data.is_empty() {
+            self.paint_misspellings(paint_ctx, data, &text_layout, text_pos, env);
+        }
 
-                // Paint the cursor if focused and there's no selection
-                if has_focus && self.cursor_on && self.selection.is_caret() {
-                    let cursor_x = self.x_for_offset(&text_layout, self.cursor());
-                    let xy = text_pos + Vec2::new(cursor_x, 2. - font_size);
-                    let x2y2 = xy + Vec2::new(0., font_size + 2.);
-                    let line = Line::new(xy, x2y2);
+        // Paint the cursor if focused and there's no selection
+        if has_focus && self.cursor_on && self.selection.is_caret() {
+            let cursor_x = self.x_for_offset(&text_layout, self.cursor());
+            let xy = text_pos + Vec2::new(cursor_x, 2. - font_size);
+            let x2y2 = xy + Vec2::new(0., font_size + 2.);
+            let line = Line::new(xy, x2y2);
 
-                    rc.stroke(line, &cursor_color, 1.);
-                }
-                Ok(())
-            })
-            .unwrap();
+            paint_ctx.stroke(line, &cursor_color, 1.);
+        }
+        if let Err(e) = paint_ctx.restore() {
+            log::error!("restoring render context failed: {:?}", e);
+        }
 
         // Paint the border
         paint_ctx.stroke(clip_rect, &border_color, BORDER_WIDTH);
+        paint_ctx.stroke_focus_ring(clip_rect, env);
     }
 }
 
+/// Make the edit undoable, named by the string for `key` in the current locale.
+fn submit_undoable(ctx: &mut EventCtx, key: &'static str, env: &Env) {
+    let mut label = LocalizedString::<()>::new(key);
+    label.resolve(&(), env);
+    ctx.submit_undoable(label.localized_str());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -520,4 +875,67 @@ mod tests {
         widget.delete_backward(&mut data);
         assert_eq!(data, String::from(""));
     }
+
+    #[test]
+    fn masked_input_steps_over_literals() {
+        let mut widget = TextBox::raw();
+        widget.mask = Some(Mask::new("(###) ###-####"));
+        let mut data = String::new();
+
+        for c in "555x12".split("") {
+            widget.insert(&mut data, c);
+        }
+        assert_eq!(data, "(555) 12");
+
+        // left goes back over the literals to the third digit
+        widget.move_selection(Movement::Left, &mut data, false);
+        widget.move_selection(Movement::Left, &mut data, false);
+        assert_eq!(widget.cursor(), 4);
+        widget.delete_backward(&mut data);
+        assert_eq!(data, "(551) 2");
+        assert_eq!(widget.cursor(), 3);
+        widget.move_selection(Movement::Right, &mut data, false);
+        assert_eq!(widget.cursor(), 4);
+        widget.move_selection(Movement::Right, &mut data, false);
+        assert_eq!(widget.cursor(), 7);
+    }
+
+    /// A correction only replaces the word it was offered for.
+    #[test]
+    fn corrections_replace_the_misspelled_word() {
+        let mut widget = TextBox::raw();
+        let mut data = "teh cat".to_string();
+        let correction = Correction {
+            widget: WidgetId::next(),
+            range: 0..3,
+            word: "teh".into(),
+            replacement: "the".into(),
+        };
+        assert!(widget.correct(&mut data, &correction));
+        assert_eq!(data, "the cat");
+        assert_eq!(widget.cursor(), 3);
+
+        // the word has already been replaced
+        assert!(!widget.correct(&mut data, &correction));
+        assert_eq!(data, "the cat");
+    }
+
+    #[test]
+    fn paste_filters_transform_and_reject() {
+        let widget = TextBox::raw()
+            .paste_filter(|pasted, _| Some(pasted.trim().replace('\n', " ")))
+            .paste_filter(|pasted, rest| {
+                if rest.len() + pasted.len() <= 10 {
+                    Some(pasted.to_string())
+                } else {
+                    None
+                }
+            });
+        let pasted = "  two\nwords \n".to_string();
+        assert_eq!(
+            widget.filter_paste("", pasted.clone()),
+            Some("two words".into())
+        );
+        assert_eq!(widget.filter_paste("abc", pasted), None);
+    }
 }
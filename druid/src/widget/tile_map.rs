@@ -0,0 +1,622 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget for panning and zooming over tiled imagery.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::kurbo::{Point, Rect, Size, Vec2};
+use crate::piet::InterpolationMode;
+use crate::widget::ImageData;
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    RenderContext, Selector, TaskHandle, UpdateCtx, Widget,
+};
+
+/// Brings a tile that has been loaded to its `TileMap`.
+const TILE_LOADED: Selector = Selector::new("druid-builtin.tile-loaded");
+
+/// How many levels past the most detailed one the map can be zoomed in,
+/// stretching its tiles.
+const OVERZOOM: f64 = 2.0;
+
+/// How many zoom levels the wheel moves for each pixel it scrolls.
+const WHEEL_ZOOM: f64 = 1.0 / 240.0;
+
+/// How quickly the zoom catches up with the wheel; it goes most of the way
+/// in a quarter of a second.
+const ZOOM_SPEED: f64 = 12.0;
+
+/// How many tiles are kept, by default, before those out of view are
+/// dropped.
+const DEFAULT_MAX_TILES: usize = 256;
+
+/// How many tiles are loaded at once; the rest wait their turn.
+const MAX_LOADING: usize = 6;
+
+/// A tile of a [`TileMap`].
+///
+/// At level 0 there is one tile, covering the whole map, and each level
+/// after that has twice as many tiles across and down as the one before,
+/// numbered from the top left; this is how most map servers number them.
+///
+/// [`TileMap`]: struct.TileMap.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileKey {
+    /// The zoom level.
+    pub level: u32,
+    /// The column, from the left.
+    pub x: u32,
+    /// The row, from the top.
+    pub y: u32,
+}
+
+impl TileKey {
+    /// The tile one level up that covers this one.
+    fn parent(self) -> Option<TileKey> {
+        if self.level == 0 {
+            return None;
+        }
+        Some(TileKey {
+            level: self.level - 1,
+            x: self.x / 2,
+            y: self.y / 2,
+        })
+    }
+}
+
+type FetchTile = dyn Fn(TileKey) -> Option<ImageData> + Send + Sync;
+
+/// A widget that pans and zooms over a map, or any image too big to load at
+/// once, made of tiles at several zoom levels.
+///
+/// Tiles come from a function, which is called in the background with
+/// [`EventCtx::spawn`], so it can read files or fetch them from a server.
+/// A few tiles are loaded at a time, those in the middle of the view first,
+/// and tiles that go out of view before they arrive are given up on. Until
+/// a tile arrives, the widget stretches a less detailed one in its place. Dragging pans the map, and the mouse wheel, pinching the trackpad
+/// and double-clicking zoom it around the pointer.
+///
+/// # Examples
+///
+/// ```no_run
+/// use druid::widget::{ImageCache, TileMap};
+///
+/// let cache = ImageCache::new();
+/// let map = TileMap::new(256.0, 6, move |tile| {
+///     let path = format!("tiles/{}/{}/{}.png", tile.level, tile.x, tile.y);
+///     cache.load(path).ok()
+/// });
+/// ```
+///
+/// [`EventCtx::spawn`]: ../struct.EventCtx.html#method.spawn
+pub struct TileMap {
+    fetch: Arc<FetchTile>,
+    tile_size: f64,
+    max_level: u32,
+    max_tiles: usize,
+    tiles: HashMap<TileKey, ImageData>,
+    /// The tiles that are loading, with the tasks loading them.
+    loading: HashMap<TileKey, TaskHandle>,
+    /// The tiles that `fetch` didn't have, which aren't asked for again.
+    missing: HashSet<TileKey>,
+    /// The point of the map in the middle of the widget, where the map is
+    /// `tile_size` across.
+    center: Point,
+    zoom: f64,
+    /// The zoom the wheel is heading for.
+    target_zoom: f64,
+    /// The point, in the widget, that stays put while zooming.
+    anchor: Point,
+    /// Where the mouse was last, for panning and zooming around it.
+    mouse: Option<Point>,
+    size: Size,
+}
+
+impl TileMap {
+    /// Create a map of square tiles that are `tile_size` across, at levels
+    /// up to `max_level`, with `fetch` returning each tile, or `None` if it
+    /// has none.
+    pub fn new(
+        tile_size: f64,
+        max_level: u32,
+        fetch: impl Fn(TileKey) -> Option<ImageData> + Send + Sync + 'static,
+    ) -> Self {
+        TileMap {
+            fetch: Arc::new(fetch),
+            tile_size,
+            max_level,
+            max_tiles: DEFAULT_MAX_TILES,
+            tiles: HashMap::new(),
+            loading: HashMap::new(),
+            missing: HashSet::new(),
+            center: Point::new(tile_size / 2.0, tile_size / 2.0),
+            zoom: 0.0,
+            target_zoom: 0.0,
+            anchor: Point::ORIGIN,
+            mouse: None,
+            size: Size::ZERO,
+        }
+    }
+
+    /// Create a map of the tiles in `dir`, in the usual layout of
+    /// `level/x/y.png`.
+    ///
+    /// This is only available with the `image` feature.
+    #[cfg(feature = "image")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+    pub fn from_directory(
+        dir: impl Into<std::path::PathBuf>,
+        tile_size: f64,
+        max_level: u32,
+    ) -> Self {
+        let dir = dir.into();
+        TileMap::new(tile_size, max_level, move |tile| {
+            let path = dir
+                .join(tile.level.to_string())
+                .join(tile.x.to_string())
+                .join(format!("{}.png", tile.y));
+            match ImageData::from_file(&path) {
+                Ok(image) => Some(image),
+                // the edges of a map often have no tiles
+                Err(crate::widget::ImageError::Io(ref e))
+                    if e.kind() == std::io::ErrorKind::NotFound =>
+                {
+                    None
+                }
+                Err(e) => {
+                    log::error!("loading tile {} failed: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+    }
+
+    /// Builder-style method for setting the point of the map, where the map
+    /// is one tile across, in the middle of the widget, and the zoom level,
+    /// which can be between levels.
+    pub fn view(mut self, center: impl Into<Point>, zoom: f64) -> Self {
+        self.center = center.into();
+        self.zoom = zoom.max(0.0).min(self.max_zoom());
+        self.target_zoom = self.zoom;
+        self
+    }
+
+    /// Builder-style method for setting how many tiles are kept in memory;
+    /// those out of view are dropped once there are more.
+    pub fn max_tiles(mut self, tiles: usize) -> Self {
+        self.max_tiles = tiles;
+        self
+    }
+
+    fn max_zoom(&self) -> f64 {
+        self.max_level as f64 + OVERZOOM
+    }
+
+    fn scale(&self) -> f64 {
+        2f64.powf(self.zoom)
+    }
+
+    /// The point of the map at `pos` in the widget.
+    fn to_map(&self, pos: Point) -> Point {
+        self.center + (pos - self.middle()) / self.scale()
+    }
+
+    /// The middle of the widget.
+    fn middle(&self) -> Point {
+        (self.size.to_vec2() / 2.0).to_point()
+    }
+
+    /// The level whose tiles are the closest to their natural size.
+    fn level(&self) -> u32 {
+        (self.zoom.round().max(0.0) as u32).min(self.max_level)
+    }
+
+    /// Where `tile` is in the widget.
+    fn tile_rect(&self, tile: TileKey) -> Rect {
+        let span = self.tile_size / 2f64.powi(tile.level as i32);
+        let origin = Point::new(tile.x as f64 * span, tile.y as f64 * span);
+        let scale = self.scale();
+        let offset = self.middle().to_vec2() - self.center.to_vec2() * scale;
+        Rect::from_origin_size(
+            (origin.to_vec2() * scale).to_point(),
+            (span * scale, span * scale),
+        ) + offset
+    }
+
+    /// The tiles of `level` that are in view, those in the middle first.
+    fn visible_tiles(&self, level: u32) -> Vec<TileKey> {
+        let count = 1u32 << level;
+        let span = self.tile_size / count as f64;
+        let top_left = self.to_map(Point::ORIGIN);
+        let bottom_right = self.to_map(self.size.to_vec2().to_point());
+        let range = |from: f64, to: f64| {
+            let first = (from / span).floor().max(0.0) as u32;
+            let last = ((to / span).ceil().max(0.0) as u32).min(count);
+            first..last
+        };
+        let mut tiles: Vec<_> = range(top_left.y, bottom_right.y)
+            .flat_map(|y| range(top_left.x, bottom_right.x).map(move |x| (x, y)))
+            .map(|(x, y)| TileKey { level, x, y })
+            .collect();
+        let center = self.middle();
+        tiles.sort_by(|a, b| {
+            let a = (self.tile_rect(*a).center() - center).hypot2();
+            let b = (self.tile_rect(*b).center() - center).hypot2();
+            a.partial_cmp(&b).unwrap()
+        });
+        tiles
+    }
+
+    /// Zoom to `zoom`, keeping the point of the map at `anchor` where it is.
+    fn zoom_about(&mut self, zoom: f64, anchor: Point) {
+        let fixed = self.to_map(anchor);
+        self.zoom = zoom.max(0.0).min(self.max_zoom());
+        self.center = fixed - (anchor - self.middle()) / self.scale();
+        self.clamp_center();
+    }
+
+    fn pan(&mut self, delta: Vec2) {
+        self.center -= delta / self.scale();
+        self.clamp_center();
+    }
+
+    fn clamp_center(&mut self) {
+        let size = self.tile_size;
+        self.center.x = self.center.x.max(0.0).min(size);
+        self.center.y = self.center.y.max(0.0).min(size);
+    }
+
+    /// Cancel the tiles that are loading but have gone out of view, and
+    /// return the next tiles to load, as many as there is room for.
+    fn tiles_to_load(&mut self) -> Vec<TileKey> {
+        if self.size.width <= 0.0 || self.size.height <= 0.0 {
+            return Vec::new();
+        }
+        let mut tiles = self.visible_tiles(self.level());
+        // the whole map, for somewhere to start
+        tiles.insert(
+            0,
+            TileKey {
+                level: 0,
+                x: 0,
+                y: 0,
+            },
+        );
+        self.loading.retain(|tile, task| {
+            let wanted = tiles.contains(tile);
+            if !wanted {
+                task.cancel();
+            }
+            wanted
+        });
+        let (loaded, loading, missing) = (&self.tiles, &self.loading, &self.missing);
+        tiles.retain(|tile| {
+            !loaded.contains_key(tile) && !loading.contains_key(tile) && !missing.contains(tile)
+        });
+        tiles.truncate(MAX_LOADING.saturating_sub(self.loading.len()));
+        tiles
+    }
+
+    fn load(&self, tile: TileKey) -> impl Future<Output = (TileKey, Option<ImageData>)> {
+        let fetch = self.fetch.clone();
+        async move { (tile, fetch(tile)) }
+    }
+
+    /// Keep `image` as `tile`, dropping tiles that are out of view if there
+    /// are too many.
+    fn insert_tile(&mut self, tile: TileKey, image: ImageData) {
+        self.tiles.insert(tile, image);
+        if self.tiles.len() > self.max_tiles {
+            let view = Rect::from_origin_size(Point::ORIGIN, self.size);
+            let tile_rects: Vec<_> = self
+                .tiles
+                .keys()
+                .map(|&tile| (tile, self.tile_rect(tile)))
+                .collect();
+            for (tile, rect) in tile_rects {
+                if tile.level != 0 && rect.intersect(view).area() <= 0.0 {
+                    self.tiles.remove(&tile);
+                }
+            }
+        }
+    }
+
+    /// The most detailed tile that has arrived and covers `tile`.
+    fn loaded_cover(&self, tile: TileKey) -> Option<(TileKey, &ImageData)> {
+        let mut cover = Some(tile);
+        while let Some(tile) = cover {
+            if let Some(image) = self.tiles.get(&tile) {
+                return Some((tile, image));
+            }
+            cover = tile.parent();
+        }
+        None
+    }
+}
+
+impl<T: Data> Widget<T> for TileMap {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut T, _env: &Env) {
+        match event {
+            Event::Command(cmd) if cmd.selector == TILE_LOADED => {
+                match cmd.get_object::<(TileKey, Option<ImageData>)>() {
+                    Ok((tile, Some(image))) => {
+                        self.loading.remove(tile);
+                        self.insert_tile(*tile, image.clone());
+                        ctx.request_paint();
+                    }
+                    Ok((tile, None)) => {
+                        self.loading.remove(tile);
+                        self.missing.insert(*tile);
+                    }
+                    Err(_) => (),
+                }
+                ctx.set_handled();
+            }
+            Event::MouseDown(mouse) => {
+                if mouse.count == 2 {
+                    self.anchor = mouse.pos;
+                    self.target_zoom = (self.target_zoom.round() + 1.0).min(self.max_zoom());
+                    ctx.request_anim_frame();
+                } else {
+                    ctx.set_active(true);
+                }
+                self.mouse = Some(mouse.pos);
+            }
+            Event::MouseMoved(mouse) => {
+                match self.mouse {
+                    Some(last) if ctx.is_active() => {
+                        self.pan(mouse.pos - last);
+                        ctx.request_paint();
+                    }
+                    _ => (),
+                }
+                self.mouse = Some(mouse.pos);
+            }
+            Event::MouseUp(_) if ctx.is_active() => ctx.set_active(false),
+            Event::Wheel(wheel) => {
+                let zoom = self.target_zoom - wheel.delta.y * WHEEL_ZOOM;
+                self.target_zoom = zoom.max(0.0).min(self.max_zoom());
+                self.anchor = self.mouse.unwrap_or_else(|| self.middle());
+                ctx.request_anim_frame();
+                ctx.set_handled();
+            }
+            Event::Zoom(delta) => {
+                // pinching follows the fingers, so it isn't smoothed
+                let anchor = self.mouse.unwrap_or_else(|| self.middle());
+                self.zoom_about(self.zoom + (1.0 + delta).max(0.1).log2(), anchor);
+                self.target_zoom = self.zoom;
+                ctx.request_paint();
+                ctx.set_handled();
+            }
+            _ => (),
+        }
+        // the view may have moved, and it may have been resized since the
+        // last event, and a tile may have arrived to make room for another
+        for tile in self.tiles_to_load() {
+            let task = ctx.spawn(TILE_LOADED, self.load(tile));
+            self.loading.insert(tile, task);
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &T, _env: &Env) {
+        match event {
+            // tiles are asked for once the widget has a size
+            LifeCycle::WidgetAdded => ctx.request_anim_frame(),
+            LifeCycle::AnimFrame(interval) => {
+                if self.size.width <= 0.0 || self.size.height <= 0.0 {
+                    ctx.request_anim_frame();
+                }
+                if self.zoom != self.target_zoom {
+                    let step = 1.0 - (-(*interval as f64) * 1e-9 * ZOOM_SPEED).exp();
+                    let mut zoom = self.zoom + (self.target_zoom - self.zoom) * step;
+                    if (self.target_zoom - zoom).abs() < 0.005 {
+                        zoom = self.target_zoom;
+                    } else {
+                        ctx.request_anim_frame();
+                    }
+                    self.zoom_about(zoom, self.anchor);
+                    ctx.request_paint();
+                }
+                for tile in self.tiles_to_load() {
+                    let task = ctx.spawn(TILE_LOADED, self.load(tile));
+                    self.loading.insert(tile, task);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, _ctx: &mut UpdateCtx, _old_data: &T, _data: &T, _env: &Env) {}
+
+    fn layout(
+        &mut self,
+        _layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        _env: &Env,
+    ) -> Size {
+        bc.debug_check("TileMap");
+        let natural = Size::new(self.tile_size, self.tile_size);
+        let size = Size::new(
+            if bc.is_width_bounded() {
+                bc.max().width
+            } else {
+                natural.width
+            },
+            if bc.is_height_bounded() {
+                bc.max().height
+            } else {
+                natural.height
+            },
+        );
+        self.size = bc.constrain(size);
+        self.size
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, _data: &T, _env: &Env) {
+        let view = Rect::from_origin_size(Point::ORIGIN, paint_ctx.size());
+        paint_ctx.clip(view);
+        let interpolation = if self.zoom.fract() == 0.0 && self.zoom <= self.max_level as f64 {
+            InterpolationMode::NearestNeighbor
+        } else {
+            InterpolationMode::Bilinear
+        };
+        for tile in self.visible_tiles(self.level()) {
+            let (cover, image) = match self.loaded_cover(tile) {
+                Some(cover) => cover,
+                None => continue,
+            };
+            let image = match paint_ctx.piet_image(image) {
+                Some(image) => image,
+                None => continue,
+            };
+            // slightly larger, so that no seams show between the tiles
+            let rect = self.tile_rect(cover).inflate(0.25, 0.25);
+            if cover == tile {
+                paint_ctx.draw_image(&*image, rect, interpolation);
+            } else {
+                let clip = self.tile_rect(tile);
+                paint_ctx
+                    .with_save(|rc| {
+                        rc.clip(clip);
+                        rc.draw_image(&*image, rect, InterpolationMode::Bilinear);
+                        Ok(())
+                    })
+                    .unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Task;
+    use crate::Target;
+
+    fn map(size: Size) -> TileMap {
+        let mut map = TileMap::new(256.0, 4, |_| None);
+        map.size = size;
+        map
+    }
+
+    #[test]
+    fn visible_tiles_follow_the_view() {
+        let mut map = map(Size::new(256.0, 256.0));
+        assert_eq!(
+            map.visible_tiles(0),
+            vec![TileKey {
+                level: 0,
+                x: 0,
+                y: 0
+            }]
+        );
+        let rect = map.tile_rect(TileKey {
+            level: 1,
+            x: 1,
+            y: 0,
+        });
+        assert_eq!(
+            (rect.x0, rect.y0, rect.x1, rect.y1),
+            (128.0, 0.0, 256.0, 128.0)
+        );
+
+        // zoomed in on the middle, the four tiles around it are in view
+        map.zoom_about(2.0, Point::new(128.0, 128.0));
+        assert_eq!(map.level(), 2);
+        let tiles = map.visible_tiles(2);
+        assert_eq!(tiles.len(), 4);
+        assert!(tiles
+            .iter()
+            .all(|tile| (1..3).contains(&tile.x) && (1..3).contains(&tile.y)));
+
+        map.pan(Vec2::new(-160.0, 0.0));
+        let columns: HashSet<_> = map.visible_tiles(2).iter().map(|tile| tile.x).collect();
+        assert_eq!(columns, (2..4).collect());
+        assert_eq!(
+            TileKey {
+                level: 2,
+                x: 3,
+                y: 1
+            }
+            .parent(),
+            Some(TileKey {
+                level: 1,
+                x: 1,
+                y: 0
+            })
+        );
+    }
+
+    #[test]
+    fn zooming_keeps_the_anchor_still() {
+        let mut map = map(Size::new(400.0, 300.0));
+        let anchor = Point::new(300.0, 100.0);
+        map.zoom_about(1.5, Point::new(200.0, 150.0));
+        let before = map.to_map(anchor);
+        map.zoom_about(3.25, anchor);
+        let after = map.to_map(anchor);
+        assert!((before - after).hypot() < 1e-9);
+
+        // the map can't be zoomed or panned off
+        map.zoom_about(20.0, anchor);
+        assert_eq!(map.zoom, 6.0);
+        map.pan(Vec2::new(1e6, 0.0));
+        assert_eq!(map.center.x, 0.0);
+
+        let mut map = map.view((10.0, 10.0), 2.0).max_tiles(1);
+        map.insert_tile(
+            TileKey {
+                level: 0,
+                x: 0,
+                y: 0,
+            },
+            ImageData::empty(),
+        );
+        let far = TileKey {
+            level: 2,
+            x: 3,
+            y: 3,
+        };
+        map.insert_tile(far, ImageData::empty());
+        assert!(!map.tiles.contains_key(&far));
+        assert_eq!(map.tiles.len(), 1);
+    }
+
+    #[test]
+    fn a_few_tiles_load_at_a_time() {
+        let mut map = map(Size::new(1024.0, 1024.0)).view((128.0, 128.0), 4.0);
+        let tiles = map.tiles_to_load();
+        assert_eq!(tiles.len(), MAX_LOADING);
+        assert_eq!(tiles[0].level, 0);
+        let tasks: Vec<_> = tiles
+            .iter()
+            .map(|&tile| {
+                let task = Task::new(async {}, TILE_LOADED, Target::Global).handle();
+                map.loading.insert(tile, task.clone());
+                task
+            })
+            .collect();
+        assert!(map.tiles_to_load().is_empty());
+
+        // those that go out of view are cancelled, making room for others
+        map.pan(Vec2::new(-2000.0, -2000.0));
+        assert_eq!(map.tiles_to_load().len(), MAX_LOADING - 1);
+        assert!(!tasks[0].is_cancelled());
+        assert!(tasks[1..].iter().all(TaskHandle::is_cancelled));
+    }
+}
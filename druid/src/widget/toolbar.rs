@@ -0,0 +1,503 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A row of buttons for common actions.
+
+use std::rc::Rc;
+
+use crate::kurbo::{Affine, BezPath, Line, Point, Rect, RoundedRect, Size, Vec2};
+use crate::lens::Lens;
+use crate::menu::{DataUpdate, UPDATE_DATA};
+use crate::text::TextLine;
+use crate::theme;
+use crate::widget::icon;
+use crate::{
+    commands, BoxConstraints, Command, ContextMenu, Data, Env, Event, EventCtx, LayoutCtx,
+    LifeCycle, LifeCycleCtx, LocalizedString, MenuDesc, MenuItem, PaintCtx, RenderContext,
+    UpdateCtx, Widget,
+};
+
+/// The space between the items.
+const ITEM_GAP: f64 = 2.0;
+
+/// The space between the edges of a button and its text.
+const TEXT_PADDING: f64 = 6.0;
+
+/// The width of a separator, including the space on either side of it.
+const SEPARATOR_WIDTH: f64 = 9.0;
+
+/// The icon of the button that opens the overflow menu.
+const OVERFLOW_ICON: &str = "more";
+
+/// A row of buttons for the actions of a window, such as the buttons for
+/// saving and undoing above a document.
+///
+/// The buttons are [`ToolbarItem`]s, with or without separators between
+/// them. The items that don't fit in the width of the toolbar move into a
+/// menu, which opens from a button at the end.
+///
+/// The overflow menu is a context menu, so like every [`ContextMenu`] it
+/// only works when the toolbar's data is the application's data.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Toolbar, ToolbarItem};
+/// use druid::{commands, Data, Lens, LocalizedString};
+///
+/// #[derive(Clone, Data, Lens)]
+/// struct Editor {
+///     bold: bool,
+/// }
+///
+/// let toolbar = Toolbar::new()
+///     .with_item(
+///         ToolbarItem::action(LocalizedString::new("common-menu-file-save"), commands::SAVE_FILE)
+///             .icon("check"),
+///     )
+///     .with_separator()
+///     .with_item(ToolbarItem::toggle(
+///         LocalizedString::new("bold").with_placeholder("Bold"),
+///         Editor::bold,
+///     ));
+/// ```
+///
+/// [`ToolbarItem`]: struct.ToolbarItem.html
+/// [`ContextMenu`]: ../struct.ContextMenu.html
+pub struct Toolbar<T> {
+    entries: Vec<Entry<T>>,
+    /// How many of the entries are shown; the rest are in the overflow menu.
+    shown: usize,
+    /// Where the overflow button is, when there is one.
+    overflow: Option<Rect>,
+    overflow_icon: Option<Rc<BezPath>>,
+    /// The part that the mouse is over, and the one it was pressed on.
+    hot: Option<Part>,
+    pressed: Option<Part>,
+}
+
+/// A button in a [`Toolbar`].
+///
+/// A button shows its icon if it has one, and its title otherwise; the
+/// title is shown in the overflow menu either way.
+///
+/// [`Toolbar`]: struct.Toolbar.html
+pub struct ToolbarItem<T> {
+    title: LocalizedString<T>,
+    icon: Option<String>,
+    kind: Kind<T>,
+}
+
+enum Kind<T> {
+    /// Submits its command to the window.
+    Action(Command),
+    /// Is pressed in while the value is true, and toggles it.
+    Toggle {
+        get: Box<dyn Fn(&T) -> bool>,
+        toggle: Rc<dyn Fn(&mut T)>,
+    },
+}
+
+/// A button or a separator.
+struct Entry<T> {
+    /// The button, or `None` for a separator.
+    item: Option<ToolbarItem<T>>,
+    /// Where the entry is, and how it looks, from the last layout.
+    rect: Rect,
+    text: Option<TextLine>,
+    icon: Option<Rc<BezPath>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Part {
+    Entry(usize),
+    Overflow,
+}
+
+impl<T: Data> ToolbarItem<T> {
+    /// A button that submits `command` to the window when it is clicked.
+    pub fn action(title: LocalizedString<T>, command: impl Into<Command>) -> Self {
+        ToolbarItem {
+            title,
+            icon: None,
+            kind: Kind::Action(command.into()),
+        }
+    }
+
+    /// A button that toggles the `bool` at `lens`, and is shown pressed in
+    /// while it is true.
+    pub fn toggle(title: LocalizedString<T>, lens: impl Lens<T, bool> + 'static) -> Self {
+        let lens = Rc::new(lens);
+        let get = {
+            let lens = lens.clone();
+            Box::new(move |data: &T| lens.with(data, |value| *value))
+        };
+        let toggle = Rc::new(move |data: &mut T| lens.with_mut(data, |value| *value = !*value));
+        ToolbarItem {
+            title,
+            icon: None,
+            kind: Kind::Toggle { get, toggle },
+        }
+    }
+
+    /// Builder-style method for showing the [`Icon`] called `name` instead
+    /// of the title.
+    ///
+    /// [`Icon`]: struct.Icon.html
+    pub fn icon(mut self, name: impl Into<String>) -> Self {
+        self.icon = Some(name.into());
+        self
+    }
+
+    fn is_on(&self, data: &T) -> bool {
+        match &self.kind {
+            Kind::Action(_) => false,
+            Kind::Toggle { get, .. } => get(data),
+        }
+    }
+
+    /// The item as an entry in the overflow menu.
+    fn menu_item(&self, data: &T) -> MenuItem<T> {
+        match &self.kind {
+            Kind::Action(command) => MenuItem::new(self.title.clone(), command.clone()),
+            Kind::Toggle { toggle, .. } => {
+                let update = DataUpdate(toggle.clone());
+                let on = self.is_on(data);
+                MenuItem::new(self.title.clone(), Command::new(UPDATE_DATA, update))
+                    .selected_if(|| on)
+            }
+        }
+    }
+}
+
+impl<T> Entry<T> {
+    fn new(item: Option<ToolbarItem<T>>) -> Self {
+        Entry {
+            item,
+            rect: Rect::ZERO,
+            text: None,
+            icon: None,
+        }
+    }
+}
+
+impl<T: Data> Toolbar<T> {
+    /// Create an empty toolbar.
+    pub fn new() -> Self {
+        Toolbar {
+            entries: Vec::new(),
+            shown: 0,
+            overflow: None,
+            overflow_icon: None,
+            hot: None,
+            pressed: None,
+        }
+    }
+
+    /// Builder-style method for adding a button.
+    pub fn with_item(mut self, item: ToolbarItem<T>) -> Self {
+        self.entries.push(Entry::new(Some(item)));
+        self
+    }
+
+    /// Builder-style method for adding a line between the buttons before
+    /// and after it.
+    pub fn with_separator(mut self) -> Self {
+        self.entries.push(Entry::new(None));
+        self
+    }
+
+    fn hit_test(&self, pos: Point) -> Option<Part> {
+        if self.overflow.is_some_and(|rect| rect.contains(pos)) {
+            return Some(Part::Overflow);
+        }
+        self.entries[..self.shown]
+            .iter()
+            .position(|entry| entry.item.is_some() && entry.rect.contains(pos))
+            .map(Part::Entry)
+    }
+
+    /// Do what clicking `part` does.
+    fn click(&mut self, ctx: &mut EventCtx, part: Part, data: &mut T, window_pos: Point) {
+        match part {
+            Part::Entry(index) => {
+                if let Some(item) = &self.entries[index].item {
+                    match &item.kind {
+                        Kind::Action(command) => ctx.submit_command(command.clone(), None),
+                        Kind::Toggle { toggle, .. } => toggle(data),
+                    }
+                }
+            }
+            Part::Overflow => {
+                let mut menu = MenuDesc::empty();
+                let mut separator = false;
+                for entry in &self.entries[self.shown..] {
+                    match &entry.item {
+                        Some(item) => {
+                            if separator && !menu.is_empty() {
+                                menu = menu.append_separator();
+                            }
+                            separator = false;
+                            menu = menu.append(item.menu_item(data));
+                        }
+                        None => separator = true,
+                    }
+                }
+                let menu = ContextMenu::new(menu, window_pos);
+                ctx.submit_command(Command::new(commands::SHOW_CONTEXT_MENU, menu), None);
+            }
+        }
+    }
+}
+
+impl<T: Data> Default for Toolbar<T> {
+    fn default() -> Self {
+        Toolbar::new()
+    }
+}
+
+/// How many of the entries `widths` wide fit in `max_width`, leaving room
+/// for an overflow button `overflow_width` wide if they don't all fit, and
+/// not ending with a separator.
+fn fit(widths: &[f64], separators: &[bool], overflow_width: f64, max_width: f64) -> usize {
+    let total = widths.iter().sum::<f64>() + ITEM_GAP * widths.len().saturating_sub(1) as f64;
+    if total <= max_width {
+        return widths.len();
+    }
+    let mut x = 0.0;
+    let mut shown = 0;
+    for width in widths {
+        if x + width + ITEM_GAP + overflow_width > max_width {
+            break;
+        }
+        x += width + ITEM_GAP;
+        shown += 1;
+    }
+    while shown > 0 && separators[shown - 1] {
+        shown -= 1;
+    }
+    shown
+}
+
+impl<T: Data> Widget<T> for Toolbar<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, _env: &Env) {
+        match event {
+            Event::MouseDown(mouse) => {
+                self.pressed = self.hit_test(mouse.pos);
+                if self.pressed.is_some() {
+                    ctx.set_active(true);
+                    ctx.request_paint();
+                }
+            }
+            Event::MouseUp(mouse) if ctx.is_active() => {
+                ctx.set_active(false);
+                if let Some(pressed) = self.pressed.take() {
+                    if self.hit_test(mouse.pos) == Some(pressed) {
+                        // the overflow menu opens below its button
+                        let below = self
+                            .overflow
+                            .map_or(mouse.pos, |rect| Point::new(rect.x0, rect.y1));
+                        let window_pos = mouse.window_pos + (below - mouse.pos);
+                        self.click(ctx, pressed, data, window_pos);
+                    }
+                }
+                ctx.request_paint();
+            }
+            Event::MouseMoved(mouse) => {
+                let hot = self.hit_test(mouse.pos).filter(|_| ctx.is_hot());
+                if hot != self.hot {
+                    self.hot = hot;
+                    ctx.request_paint();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        match event {
+            LifeCycle::WidgetAdded => {
+                self.overflow_icon = icon::find(OVERFLOW_ICON);
+                for entry in &mut self.entries {
+                    if let Some(item) = &mut entry.item {
+                        item.title.resolve(data, env);
+                        if let Some(name) = &item.icon {
+                            entry.icon = icon::find(name);
+                            if entry.icon.is_none() {
+                                log::warn!("there is no icon called '{}'", name);
+                            }
+                        }
+                    }
+                }
+            }
+            LifeCycle::HotChanged(false) => {
+                self.hot = None;
+                ctx.request_paint();
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        let mut changed = false;
+        for entry in &mut self.entries {
+            if let Some(item) = &mut entry.item {
+                changed |= item.title.resolve(data, env);
+            }
+        }
+        if changed {
+            ctx.request_layout();
+        } else if !old_data.same(data) {
+            ctx.request_paint();
+        }
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Toolbar");
+        let height = env.get(theme::BORDERED_WIDGET_HEIGHT);
+        let font_name = env.get(theme::FONT_NAME);
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+
+        let mut widths = Vec::with_capacity(self.entries.len());
+        let mut separators = Vec::with_capacity(self.entries.len());
+        for entry in &mut self.entries {
+            entry.text = None;
+            let width = match &entry.item {
+                Some(_) if entry.icon.is_some() => height,
+                Some(item) => {
+                    let title = item.title.localized_str();
+                    let line = TextLine::new(layout_ctx.text(), font_name, font_size, title, env);
+                    let width = line.width() + 2.0 * TEXT_PADDING;
+                    entry.text = Some(line);
+                    width
+                }
+                None => SEPARATOR_WIDTH,
+            };
+            widths.push(width);
+            separators.push(entry.item.is_none());
+        }
+
+        self.shown = fit(&widths, &separators, height, bc.max().width);
+        let mut x = 0.0;
+        for (entry, width) in self.entries.iter_mut().zip(&widths).take(self.shown) {
+            entry.rect = Rect::new(x, 0.0, x + width, height);
+            x += width + ITEM_GAP;
+        }
+        let width = if self.shown < self.entries.len() {
+            let rect = Rect::new(bc.max().width - height, 0.0, bc.max().width, height);
+            self.overflow = Some(rect);
+            rect.x1
+        } else {
+            self.overflow = None;
+            (x - ITEM_GAP).max(0.0)
+        };
+        bc.constrain(Size::new(width, height))
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        let height = paint_ctx.size().height;
+        let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+        let icon_size = env.get(theme::ICON_SIZE).min(height);
+        let label_color = env.get(theme::LABEL_COLOR);
+        let icon_color = env.get(theme::ICON_COLOR);
+        let radius = env.get(theme::BUTTON_BORDER_RADIUS);
+
+        let hot = self.hot;
+        let pressed = self.pressed.filter(|_| paint_ctx.is_active());
+        let paint_background = |paint_ctx: &mut PaintCtx, part: Part, rect: Rect, on: bool| {
+            let color = if on || pressed == Some(part) {
+                env.get(theme::PRIMARY_DARK)
+            } else if hot == Some(part) {
+                env.get(theme::BACKGROUND_LIGHT)
+            } else {
+                return;
+            };
+            paint_ctx.fill(RoundedRect::from_rect(rect, radius), &color);
+        };
+        let paint_icon = |paint_ctx: &mut PaintCtx, path: &BezPath, rect: Rect| {
+            let offset = rect.center() - Vec2::new(icon_size, icon_size) / 2.0;
+            let transform =
+                Affine::translate(offset.to_vec2()) * Affine::scale(icon_size / icon::GRID_SIZE);
+            paint_ctx.fill(transform * path, &icon_color);
+        };
+
+        for (index, entry) in self.entries[..self.shown].iter().enumerate() {
+            let rect = entry.rect;
+            match &entry.item {
+                Some(item) => {
+                    let on = item.is_on(data);
+                    paint_background(paint_ctx, Part::Entry(index), rect, on);
+                    if let Some(path) = &entry.icon {
+                        paint_icon(paint_ctx, path, rect);
+                    } else if let Some(line) = &entry.text {
+                        // the same baseline as a label's
+                        let baseline = (height + font_size * 0.6) / 2.0;
+                        let origin = Point::new(rect.x0 + TEXT_PADDING, baseline);
+                        paint_ctx.draw_text(line.layout(), origin, &label_color);
+                    }
+                }
+                None => {
+                    let x = rect.center().x;
+                    let line = Line::new((x, 4.0), (x, height - 4.0));
+                    paint_ctx.stroke(line, &env.get(theme::BORDER_LIGHT), 1.0);
+                }
+            }
+        }
+
+        if let Some(rect) = self.overflow {
+            paint_background(paint_ctx, Part::Overflow, rect, false);
+            if let Some(path) = &self.overflow_icon {
+                paint_icon(paint_ctx, path, rect);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items_that_dont_fit_overflow() {
+        let widths = [24.0, 24.0, SEPARATOR_WIDTH, 40.0, 30.0];
+        let separators = [false, false, true, false, false];
+        // 127 for the entries, and 8 for the gaps between them
+        assert_eq!(fit(&widths, &separators, 24.0, 135.0), 5);
+
+        // room for the overflow button after the fourth
+        assert_eq!(fit(&widths, &separators, 24.0, 134.0), 4);
+        assert_eq!(fit(&widths, &separators, 24.0, 129.0), 4);
+        // and the separator is not left at the end
+        assert_eq!(fit(&widths, &separators, 24.0, 128.0), 2);
+        assert_eq!(fit(&widths, &separators, 24.0, 20.0), 0);
+    }
+
+    #[test]
+    fn toggles_follow_the_data() {
+        let item = ToolbarItem::toggle(LocalizedString::new("bold"), crate::lens::Id);
+        assert!(item.is_on(&true));
+        assert!(!item.is_on(&false));
+        if let Kind::Toggle { toggle, .. } = &item.kind {
+            let mut data = false;
+            toggle(&mut data);
+            assert!(data);
+        }
+    }
+}
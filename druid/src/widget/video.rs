@@ -0,0 +1,445 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Playing video.
+
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use log::error;
+
+use crate::widget::{FillStrat, ImageData, LiveImage, PixelFormat};
+use crate::{
+    commands, BoxConstraints, Data, Env, Event, EventCtx, ExtEventSink, LayoutCtx, LifeCycle,
+    LifeCycleCtx, PaintCtx, Size, UpdateCtx, Widget, WidgetId,
+};
+
+/// A widget that plays a video.
+///
+/// The video is decoded on a thread of its own, and its frames are shown
+/// with a [`LiveImage`], at the times they are due. It starts out paused on
+/// its first frame, and is controlled with the [`commands::PLAY_VIDEO`],
+/// [`commands::PAUSE_VIDEO`] and [`commands::SEEK_VIDEO`] commands. Playing
+/// a video that has ended starts it again from the beginning.
+///
+/// Videos are decoded with GStreamer, unless the widget is given some other
+/// [`VideoDecoder`]. As with a `LiveImage`, the widget has to be given the
+/// id that was passed to [`new`], with [`WidgetExt::with_id`].
+///
+/// This is only available with the `video` feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// use druid::widget::{Video, WidgetExt};
+/// use druid::{commands, ExtEventSink, Widget, WidgetId};
+///
+/// fn player(sink: ExtEventSink) -> impl Widget<()> {
+///     let id = WidgetId::next();
+///     let video = Video::open(sink.clone(), id, "file:///home/me/holiday.mp4").unwrap();
+///     sink.submit_command::<()>(commands::PLAY_VIDEO, None, id).unwrap();
+///     video.with_id(id)
+/// }
+/// ```
+///
+/// [`LiveImage`]: struct.LiveImage.html
+/// [`commands::PLAY_VIDEO`]: ../commands/constant.PLAY_VIDEO.html
+/// [`commands::PAUSE_VIDEO`]: ../commands/constant.PAUSE_VIDEO.html
+/// [`commands::SEEK_VIDEO`]: ../commands/constant.SEEK_VIDEO.html
+/// [`VideoDecoder`]: trait.VideoDecoder.html
+/// [`new`]: #method.new
+/// [`WidgetExt::with_id`]: trait.WidgetExt.html#method.with_id
+pub struct Video<T> {
+    image: LiveImage<T>,
+    controls: Sender<Control>,
+}
+
+/// A frame of a video.
+#[derive(Clone)]
+pub struct VideoFrame {
+    /// The picture.
+    pub image: ImageData,
+    /// When the frame is shown, from the start of the video.
+    pub timestamp: Duration,
+}
+
+/// Something that decodes video, such as a binding to a media framework.
+///
+/// The decoder is moved to the thread that the video plays on.
+pub trait VideoDecoder {
+    /// Decode the next frame, or return `None` at the end of the video.
+    fn next_frame(&mut self) -> Result<Option<VideoFrame>, VideoError>;
+
+    /// Go to `position`, so that the next frame is the one shown then.
+    fn seek(&mut self, position: Duration) -> Result<(), VideoError>;
+}
+
+/// An error from decoding video.
+#[derive(Debug, Clone)]
+pub struct VideoError {
+    message: String,
+}
+
+impl VideoError {
+    /// Create an error with a message saying what went wrong.
+    pub fn new(message: impl fmt::Display) -> Self {
+        VideoError {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for VideoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "decoding video failed: {}", self.message)
+    }
+}
+
+impl std::error::Error for VideoError {}
+
+/// What the widget tells the thread playing the video.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Control {
+    Play,
+    Pause,
+    Seek(Duration),
+}
+
+impl<T: Data> Video<T> {
+    /// Create a widget that plays the video that `decoder` decodes.
+    ///
+    /// `sink` comes from [`AppLauncher::get_external_handle`], and `id` is
+    /// the id the widget will be given.
+    ///
+    /// [`AppLauncher::get_external_handle`]: ../struct.AppLauncher.html#method.get_external_handle
+    pub fn new(
+        sink: ExtEventSink,
+        id: WidgetId,
+        decoder: impl VideoDecoder + Send + 'static,
+    ) -> Self {
+        let (image, frames) = LiveImage::new(sink, id);
+        let (controls, receiver) = channel();
+        thread::spawn(move || play(decoder, receiver, |frame| frames.send(frame).is_ok()));
+        Video { image, controls }
+    }
+
+    /// Create a widget that plays the video at `uri`, such as a `file://`
+    /// or `https://` URI, by decoding it with GStreamer.
+    pub fn open(sink: ExtEventSink, id: WidgetId, uri: &str) -> Result<Self, VideoError> {
+        Ok(Video::new(sink, id, GstreamerDecoder::open(uri)?))
+    }
+
+    /// Builder-style method for setting how the frames fill the widget.
+    pub fn fill_mode(mut self, fill: FillStrat) -> Self {
+        self.image = self.image.fill_mode(fill);
+        self
+    }
+}
+
+/// The next frame from `decoder`, logging any error.
+fn decode(decoder: &mut impl VideoDecoder) -> Option<VideoFrame> {
+    match decoder.next_frame() {
+        Ok(frame) => frame,
+        Err(e) => {
+            error!("{}", e);
+            None
+        }
+    }
+}
+
+/// Play the video from `decoder`, as `controls` say, passing its frames to
+/// `present` when they are due until it returns `false` or `controls` is
+/// disconnected.
+fn play(
+    mut decoder: impl VideoDecoder,
+    controls: Receiver<Control>,
+    mut present: impl FnMut(ImageData) -> bool,
+) {
+    // where the video is, and when it would have started, if it is playing
+    let mut position = Duration::from_secs(0);
+    let mut started: Option<Instant> = None;
+    let mut ended = false;
+
+    // the first frame is shown straight away
+    let mut next = decode(&mut decoder);
+    if let Some(frame) = next.take() {
+        position = frame.timestamp;
+        if !present(frame.image) {
+            return;
+        }
+    }
+
+    loop {
+        let control = match started {
+            None => match controls.recv() {
+                Ok(control) => Some(control),
+                Err(_) => return,
+            },
+            Some(start) => {
+                if next.is_none() {
+                    next = decode(&mut decoder);
+                }
+                let due = match &next {
+                    Some(frame) => start + frame.timestamp,
+                    None => {
+                        started = None;
+                        ended = true;
+                        continue;
+                    }
+                };
+                match controls.recv_timeout(due.saturating_duration_since(Instant::now())) {
+                    Ok(control) => Some(control),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        };
+
+        let seek_to = match control {
+            // the next frame is due
+            None => {
+                if let Some(frame) = next.take() {
+                    position = frame.timestamp;
+                    if !present(frame.image) {
+                        return;
+                    }
+                }
+                continue;
+            }
+            Some(Control::Play) if ended => Some(Duration::from_secs(0)),
+            Some(Control::Seek(to)) => Some(to),
+            Some(Control::Play) => None,
+            Some(Control::Pause) => {
+                started = None;
+                continue;
+            }
+        };
+        if let Some(to) = seek_to {
+            if let Err(e) = decoder.seek(to) {
+                error!("{}", e);
+            }
+            ended = false;
+            position = to;
+            next = decode(&mut decoder);
+            // show where the video is now, even while it is paused
+            if let Some(frame) = next.take() {
+                position = frame.timestamp;
+                if !present(frame.image) {
+                    return;
+                }
+            }
+        }
+        if started.is_some() || control == Some(Control::Play) {
+            let now = Instant::now();
+            started = Some(now.checked_sub(position).unwrap_or(now));
+        }
+    }
+}
+
+impl<T: Data> Widget<T> for Video<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if let Event::Command(cmd) = event {
+            let control = match cmd.selector {
+                commands::PLAY_VIDEO => Some(Control::Play),
+                commands::PAUSE_VIDEO => Some(Control::Pause),
+                commands::SEEK_VIDEO => cmd
+                    .get_object::<Duration>()
+                    .ok()
+                    .map(|to| Control::Seek(*to)),
+                _ => None,
+            };
+            if let Some(control) = control {
+                // only fails if the thread has stopped, with an error logged
+                let _ = self.controls.send(control);
+                return;
+            }
+        }
+        self.image.event(ctx, event, data, env);
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        self.image.lifecycle(ctx, event, data, env);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+        self.image.update(ctx, old_data, data, env);
+    }
+
+    fn layout(
+        &mut self,
+        layout_ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &T,
+        env: &Env,
+    ) -> Size {
+        bc.debug_check("Video");
+        self.image.layout(layout_ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, paint_ctx: &mut PaintCtx, data: &T, env: &Env) {
+        self.image.paint(paint_ctx, data, env);
+    }
+}
+
+/// Decodes video with a GStreamer pipeline, into RGBA frames.
+struct GstreamerDecoder {
+    pipeline: gst::Pipeline,
+    sink: gst_app::AppSink,
+}
+
+impl GstreamerDecoder {
+    fn open(uri: &str) -> Result<Self, VideoError> {
+        gst::init().map_err(VideoError::new)?;
+        // the frames are taken as they are needed, rather than in time with
+        // the pipeline's clock
+        let description = format!(
+            "uridecodebin uri=\"{}\" ! videoconvert ! appsink name=sink \
+             caps=video/x-raw,format=RGBA sync=false max-buffers=2",
+            uri
+        );
+        let pipeline = gst::parse_launch(&description)
+            .map_err(VideoError::new)?
+            .dynamic_cast::<gst::Pipeline>()
+            .map_err(|_| VideoError::new("the pipeline could not be made"))?;
+        let sink = pipeline
+            .get_by_name("sink")
+            .and_then(|sink| sink.dynamic_cast::<gst_app::AppSink>().ok())
+            .ok_or_else(|| VideoError::new("the pipeline has no sink"))?;
+        pipeline
+            .set_state(gst::State::Playing)
+            .map_err(VideoError::new)?;
+        Ok(GstreamerDecoder { pipeline, sink })
+    }
+}
+
+impl VideoDecoder for GstreamerDecoder {
+    fn next_frame(&mut self) -> Result<Option<VideoFrame>, VideoError> {
+        let sample = match self.sink.pull_sample() {
+            Ok(sample) => sample,
+            // at the end of the stream
+            Err(_) => return Ok(None),
+        };
+        let caps = sample
+            .get_caps()
+            .ok_or_else(|| VideoError::new("a frame has no format"))?;
+        let info = gst_video::VideoInfo::from_caps(caps).map_err(VideoError::new)?;
+        let buffer = sample
+            .get_buffer()
+            .ok_or_else(|| VideoError::new("a frame has no pixels"))?;
+        let timestamp = buffer
+            .get_pts()
+            .nseconds()
+            .map_or(Duration::from_secs(0), Duration::from_nanos);
+        let map = buffer.map_readable().map_err(VideoError::new)?;
+
+        // the rows may be padded, and the image can't have padding
+        let width = info.width() as usize;
+        let height = info.height() as usize;
+        let stride = info.stride()[0] as usize;
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for row in map.as_slice().chunks(stride).take(height) {
+            pixels.extend_from_slice(&row[..width * 4]);
+        }
+        let image = ImageData::from_raw(pixels, width, height, PixelFormat::Rgba);
+        Ok(Some(VideoFrame { image, timestamp }))
+    }
+
+    fn seek(&mut self, position: Duration) -> Result<(), VideoError> {
+        let position = gst::ClockTime::from_nseconds(position.as_nanos() as u64);
+        self.pipeline
+            .seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE, position)
+            .map_err(VideoError::new)
+    }
+}
+
+impl Drop for GstreamerDecoder {
+    fn drop(&mut self) {
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frames a millisecond apart, one pixel each, with the pixel's
+    /// brightness the number of the frame.
+    struct Frames {
+        count: u8,
+        next: u8,
+    }
+
+    impl VideoDecoder for Frames {
+        fn next_frame(&mut self) -> Result<Option<VideoFrame>, VideoError> {
+            if self.next == self.count {
+                return Ok(None);
+            }
+            let image = ImageData::from_raw(vec![self.next; 3], 1, 1, PixelFormat::Rgb);
+            let timestamp = Duration::from_millis(self.next.into());
+            self.next += 1;
+            Ok(Some(VideoFrame { image, timestamp }))
+        }
+
+        fn seek(&mut self, position: Duration) -> Result<(), VideoError> {
+            self.next = (position.as_millis() as u8).min(self.count);
+            Ok(())
+        }
+    }
+
+    /// The frames that are shown, stopping after `limit` of them.
+    fn shown(count: u8, controls: &[Control], limit: usize) -> Vec<u8> {
+        let (sender, receiver) = channel();
+        for control in controls {
+            sender.send(*control).unwrap();
+        }
+        let mut shown = Vec::new();
+        play(Frames { count, next: 0 }, receiver, |frame| {
+            shown.push(frame.pixels()[0]);
+            shown.len() < limit
+        });
+        shown
+    }
+
+    #[test]
+    fn frames_are_shown_as_they_are_played() {
+        // paused on the first frame until it plays
+        assert_eq!(shown(4, &[Control::Play], 4), vec![0, 1, 2, 3]);
+        // seeking shows the frame there, even while paused
+        assert_eq!(
+            shown(4, &[Control::Seek(Duration::from_millis(2))], 2),
+            vec![0, 2]
+        );
+    }
+
+    #[test]
+    fn playing_after_the_end_starts_again() {
+        let (sender, receiver) = channel();
+        sender.send(Control::Play).unwrap();
+        let mut shown = Vec::new();
+        play(Frames { count: 2, next: 0 }, receiver, |frame| {
+            shown.push(frame.pixels()[0]);
+            if shown.len() == 2 {
+                sender.send(Control::Play).unwrap();
+            }
+            shown.len() < 4
+        });
+        assert_eq!(shown, vec![0, 1, 0, 1]);
+    }
+}
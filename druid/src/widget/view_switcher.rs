@@ -0,0 +1,136 @@
+// Copyright 2020 The xi-editor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A widget that builds its child from the data, and rebuilds it when
+//! the data calls for a different one.
+
+use crate::kurbo::{Point, Rect, Size};
+use crate::{
+    BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
+    UpdateCtx, Widget, WidgetPod,
+};
+
+type ChildPicker<T, U> = dyn Fn(&T, &Env) -> U;
+type ChildBuilder<T, U> = dyn Fn(&U, &T, &Env) -> Box<dyn Widget<T>>;
+
+/// A widget that shows one of many children, chosen by the data.
+///
+/// The `child_picker` closure derives a value from the data, such as an enum
+/// of the app's screens, and the `child_builder` closure builds the child for
+/// that value. The child is kept until the picked value changes, and only
+/// then rebuilt.
+///
+/// # Examples
+///
+/// ```
+/// use druid::widget::{Button, Label, ViewSwitcher};
+/// use druid::{Data, Widget, WidgetExt};
+///
+/// #[derive(Clone, Copy, PartialEq, Data)]
+/// enum Screen {
+///     Welcome,
+///     Settings,
+/// }
+///
+/// fn build_ui() -> impl Widget<Screen> {
+///     ViewSwitcher::new(
+///         |screen: &Screen, _env| *screen,
+///         |screen, _data, _env| match screen {
+///             Screen::Welcome => Button::new("Settings", |_, data: &mut Screen, _| {
+///                 *data = Screen::Settings
+///             })
+///             .boxed(),
+///             Screen::Settings => Label::new("Settings").boxed(),
+///         },
+///     )
+/// }
+/// ```
+pub struct ViewSwitcher<T, U> {
+    child_picker: Box<ChildPicker<T, U>>,
+    child_builder: Box<ChildBuilder<T, U>>,
+    active_child: Option<WidgetPod<T, Box<dyn Widget<T>>>>,
+    active_child_id: Option<U>,
+}
+
+impl<T: Data, U: PartialEq> ViewSwitcher<T, U> {
+    /// Create a new view switcher.
+    ///
+    /// `child_picker` is called whenever the data changes; when what it
+    /// returns differs from the last time, `child_builder` is called to make
+    /// the new child.
+    pub fn new(
+        child_picker: impl Fn(&T, &Env) -> U + 'static,
+        child_builder: impl Fn(&U, &T, &Env) -> Box<dyn Widget<T>> + 'static,
+    ) -> Self {
+        ViewSwitcher {
+            child_picker: Box::new(child_picker),
+            child_builder: Box::new(child_builder),
+            active_child: None,
+            active_child_id: None,
+        }
+    }
+}
+
+impl<T: Data, U: PartialEq> Widget<T> for ViewSwitcher<T, U> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        if let Some(child) = self.active_child.as_mut() {
+            child.event(ctx, event, data, env);
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        if let LifeCycle::WidgetAdded = event {
+            if self.active_child.is_none() {
+                let child_id = (self.child_picker)(data, env);
+                let child = (self.child_builder)(&child_id, data, env);
+                self.active_child = Some(WidgetPod::new(child));
+                self.active_child_id = Some(child_id);
+            }
+        }
+        if let Some(child) = self.active_child.as_mut() {
+            child.lifecycle(ctx, event, data, env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+        let child_id = (self.child_picker)(data, env);
+        if self.active_child_id.as_ref() != Some(&child_id) {
+            let child = (self.child_builder)(&child_id, data, env);
+            self.active_child = Some(WidgetPod::new(child));
+            self.active_child_id = Some(child_id);
+            ctx.children_changed();
+            ctx.request_layout();
+        } else if let Some(child) = self.active_child.as_mut() {
+            child.update(ctx, data, env);
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+        match self.active_child.as_mut() {
+            Some(child) => {
+                let size = child.layout(ctx, bc, data, env);
+                child.set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+                ctx.set_paint_insets(child.paint_insets());
+                size
+            }
+            None => bc.min(),
+        }
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+        if let Some(child) = self.active_child.as_mut() {
+            child.paint_with_offset(ctx, data, env);
+        }
+    }
+}
@@ -17,7 +17,10 @@
 use crate::kurbo::Insets;
 use crate::piet::{PaintBrush, UnitPoint};
 
-use super::{Align, Container, EnvScope, IdentityWrapper, Padding, Parse, SizedBox, WidgetId};
+use super::{
+    Accessible, Align, Container, Controller, ControllerHost, EnvScope, IdentityWrapper, LabelText,
+    Padding, Parse, Role, SizedBox, WidgetId,
+};
 use crate::{Data, Env, Lens, LensWrap, Widget};
 
 /// A trait that provides extra methods for combining `Widget`s.
@@ -123,6 +126,14 @@ pub trait WidgetExt<T: Data>: Widget<T> + Sized + 'static {
         EnvScope::new(f, self)
     }
 
+    /// Wrap this widget in a [`ControllerHost`], so that `controller` sees
+    /// its events first.
+    ///
+    /// [`ControllerHost`]: struct.ControllerHost.html
+    fn controller<C: Controller<T, Self>>(self, controller: C) -> ControllerHost<Self, C> {
+        ControllerHost::new(self, controller)
+    }
+
     /// Wrap this widget in a [`LensWrap`] widget for the provided [`Lens`].
     ///
     ///
@@ -153,6 +164,29 @@ pub trait WidgetExt<T: Data>: Widget<T> + Sized + 'static {
         IdentityWrapper::wrap(self, id)
     }
 
+    /// Give this widget a label for assistive technology, such as a
+    /// screen reader.
+    ///
+    /// The label can be anything a [`Label`] accepts, including a
+    /// [`LocalizedString`] or a closure over the data.
+    ///
+    /// [`Label`]: struct.Label.html
+    /// [`LocalizedString`]: ../struct.LocalizedString.html
+    fn accessibility_label(self, label: impl Into<LabelText<T>>) -> Accessible<T, Self> {
+        Accessible::new(self).accessibility_label(label)
+    }
+
+    /// Tell assistive technology what kind of control this widget is.
+    fn accessibility_role(self, role: Role) -> Accessible<T, Self> {
+        Accessible::new(self).accessibility_role(role)
+    }
+
+    /// Describe to assistive technology what interacting with this widget
+    /// does.
+    fn accessibility_hint(self, hint: impl Into<LabelText<T>>) -> Accessible<T, Self> {
+        Accessible::new(self).accessibility_hint(hint)
+    }
+
     /// Wrap this widget in a `Box`.
     fn boxed(self) -> Box<dyn Widget<T>> {
         Box::new(self)
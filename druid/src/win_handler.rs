@@ -17,24 +17,35 @@
 use std::any::Any;
 use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Instant;
 
 use log::{info, warn};
 
-use crate::kurbo::{Size, Vec2};
+use crate::kurbo::{Point, Size, Vec2};
 use crate::piet::Piet;
 use crate::shell::{
-    Application, FileDialogOptions, IdleToken, MouseEvent, WinHandler, WindowHandle,
+    Application, DragData, DropAction, FileDialogOptions, GlobalHotKeyToken, IdleToken, MouseEvent,
+    TouchBarEvent, WinHandler, WindowHandle,
 };
 
-use crate::app_delegate::{AppDelegate, DelegateCtx};
+use crate::app_delegate::{self, AppDelegate, DelegateCtx};
 use crate::core::CommandQueue;
 use crate::ext_event::ExtEventHost;
-use crate::menu::ContextMenu;
+use crate::menu::{self, ContextMenu, DataUpdate, MenuItemUpdate};
+#[cfg(feature = "persistence")]
+use crate::persist::Persistence;
+use crate::recording::{Recorder, Replay, Replayed};
+use crate::task::{self, Executor, Task};
+#[cfg(feature = "time-travel")]
+use crate::time_travel::History;
+use crate::undo::{self, UndoManager, UndoRequest};
+use crate::widget::ImageData;
 use crate::window::{PendingWindow, Window};
 use crate::{
-    Command, Data, Env, Event, KeyEvent, KeyModifiers, MenuDesc, Target, TimerToken, WheelEvent,
-    WindowDesc, WindowId,
+    theme, Command, Data, DragEvent, DropEvent, Env, Event, GlobalHotKey, KeyEvent, KeyModifiers,
+    MenuDesc, Selector, Target, TimerToken, TouchBarDesc, WheelEvent, WindowDesc, WindowId,
 };
 
 use crate::command::sys as sys_cmd;
@@ -65,6 +76,14 @@ pub(crate) struct AppState<T> {
     windows: Windows<T>,
     pub(crate) env: Env,
     pub(crate) data: T,
+    pub(crate) recorder: Option<Recorder>,
+    pub(crate) replay: Option<Replay>,
+    #[cfg(feature = "time-travel")]
+    history: History<T>,
+    pub(crate) undo: UndoManager<T>,
+    pub(crate) executor: Executor,
+    #[cfg(feature = "persistence")]
+    pub(crate) persistence: Option<Persistence<T>>,
 }
 
 /// All active windows.
@@ -111,12 +130,32 @@ impl<T: Data> AppState<T> {
             delegate,
             command_queue: VecDeque::new(),
             ext_event_host,
+            recorder: None,
+            replay: None,
+            #[cfg(feature = "time-travel")]
+            history: History::new(&data),
+            undo: UndoManager::new(),
+            executor: Executor::default(),
+            #[cfg(feature = "persistence")]
+            persistence: None,
             data,
             env,
             windows: Windows::default(),
         }))
     }
 
+    fn get_touch_bar_cmd(
+        &self,
+        window_id: WindowId,
+        id: u32,
+        event: TouchBarEvent,
+    ) -> Option<Command> {
+        self.windows
+            .windows
+            .get(&window_id)
+            .and_then(|w| w.get_touch_bar_cmd(id, event))
+    }
+
     fn get_menu_cmd(&self, window_id: WindowId, cmd_id: u32) -> Option<Command> {
         self.windows
             .windows
@@ -161,6 +200,12 @@ impl<T: Data> AppState<T> {
     fn connect(&mut self, id: WindowId, handle: WindowHandle) {
         self.windows.connect(id, handle);
 
+        if let Some(win) = self.windows.get_mut(id) {
+            if let Some(touch_bar) = win.touch_bar.take() {
+                win.set_touch_bar(touch_bar, &self.data, &self.env);
+            }
+        }
+
         // If the external event host has no handle, it cannot wake us
         // when an event arrives.
         if self.ext_event_host.handle_window_id.is_none() {
@@ -183,6 +228,8 @@ impl<T: Data> AppState<T> {
         self.with_delegate(window_id, |del, data, env, ctx| {
             del.window_removed(window_id, data, env, ctx)
         });
+        // this may be the last window, or the one with the save timer.
+        self.save_data();
         self.windows.remove(window_id);
 
         // if we are closing the window that is currently responsible for
@@ -240,6 +287,9 @@ impl<T: Data> AppState<T> {
     }
 
     fn do_event(&mut self, source_id: WindowId, event: Event) -> bool {
+        #[cfg(feature = "time-travel")]
+        self.history.event(&event);
+
         // if the event was swallowed by the delegate we consider it handled?
         let event = match self.delegate_event(source_id, event) {
             Some(event) => event,
@@ -256,6 +306,14 @@ impl<T: Data> AppState<T> {
                     self.show_context_menu(source_id, cmd);
                     return true;
                 }
+                sys_cmd::UPDATE_MENU_ITEM => {
+                    self.update_menu_item(source_id, cmd);
+                    return true;
+                }
+                sys_cmd::SET_TOUCH_BAR => {
+                    self.set_touch_bar(source_id, cmd);
+                    return true;
+                }
                 _ => (),
             }
         }
@@ -269,7 +327,8 @@ impl<T: Data> AppState<T> {
         } = self;
 
         match event {
-            Event::TargetedCommand(Target::Widget(_), _) => {
+            Event::TargetedCommand(Target::Widget(_), _)
+            | Event::TargetedCommand(Target::Global, _) => {
                 let mut any_handled = false;
 
                 for window in windows.iter_mut() {
@@ -291,31 +350,299 @@ impl<T: Data> AppState<T> {
     fn set_menu(&mut self, window_id: WindowId, cmd: &Command) {
         if let Some(win) = self.windows.get_mut(window_id) {
             match cmd.get_object::<MenuDesc<T>>() {
-                Ok(menu) => win.set_menu(menu.to_owned(), &self.data, &self.env),
+                Ok(menu) => {
+                    // an explicit menu replaces one built from the data
+                    win.menu_fn = None;
+                    win.set_menu(menu.to_owned(), &self.data, &self.env)
+                }
                 Err(e) => log::warn!("set-menu object error: '{}'", e),
             }
         }
     }
 
+    fn update_menu_item(&mut self, window_id: WindowId, cmd: &Command) {
+        if let Some(win) = self.windows.get_mut(window_id) {
+            match cmd.get_object::<MenuItemUpdate<T>>() {
+                Ok(update) => win.update_menu_item(update, &self.data, &self.env),
+                Err(e) => log::warn!("update-menu-item object error: '{}'", e),
+            }
+        }
+    }
+
+    fn set_touch_bar(&mut self, window_id: WindowId, cmd: &Command) {
+        if let Some(win) = self.windows.get_mut(window_id) {
+            match cmd.get_object::<TouchBarDesc<T>>() {
+                Ok(touch_bar) => win.set_touch_bar(touch_bar.to_owned(), &self.data, &self.env),
+                Err(e) => log::warn!("set-touch-bar object error: '{}'", e),
+            }
+        }
+    }
+
     fn show_context_menu(&mut self, window_id: WindowId, cmd: &Command) {
         if let Some(win) = self.windows.get_mut(window_id) {
             match cmd.get_object::<ContextMenu<T>>() {
                 Ok(ContextMenu { menu, location }) => {
                     win.show_context_menu(menu.to_owned(), *location, &self.data, &self.env)
                 }
-                Err(e) => log::warn!("show-context-menu object error: '{}'", e),
+                Err(e) => match cmd.get_object::<ContextMenu<()>>() {
+                    Ok(ContextMenu { menu, location }) => {
+                        win.show_plain_context_menu(menu.to_owned(), *location, &self.env)
+                    }
+                    Err(_) => log::warn!("show-context-menu object error: '{}'", e),
+                },
             }
         }
     }
 
+    fn set_locale(&mut self, cmd: &Command) {
+        let locale = match cmd.get_object::<String>() {
+            Ok(locale) => locale,
+            Err(e) => {
+                log::warn!("set-locale object error: '{}'", e);
+                return;
+            }
+        };
+        match locale.parse() {
+            Ok(locale) => self.env.set_locale(locale),
+            Err(e) => {
+                log::warn!("invalid locale '{}': {}", locale, e);
+                return;
+            }
+        }
+        for win in self.windows.iter_mut() {
+            win.update_menu(&self.data, &self.env);
+        }
+        self.do_update();
+    }
+
+    /// Handle `ADD_RECENT_DOCUMENT` or `CLEAR_RECENT_DOCUMENTS`, rebuilding
+    /// the menus for their 'Open Recent' lists.
+    fn change_recent_documents(&mut self, cmd: &Command) {
+        if cmd.selector == sys_cmd::CLEAR_RECENT_DOCUMENTS {
+            Application::clear_recent_documents();
+        } else {
+            match cmd.get_object::<PathBuf>() {
+                Ok(path) => Application::add_recent_document(path),
+                Err(e) => {
+                    log::warn!("add-recent-document object error: '{}'", e);
+                    return;
+                }
+            }
+        }
+        for win in self.windows.iter_mut() {
+            win.update_menu(&self.data, &self.env);
+        }
+    }
+
+    /// Note the data before an input event is handled, in case the event
+    /// makes an undoable change.
+    fn begin_edit(&mut self, event: &Event) {
+        self.undo.begin(&self.data, event);
+    }
+
+    /// Record the undo step for the event that was just handled, if needed.
+    fn end_edit(&mut self) {
+        self.undo.commit(&self.data, Instant::now());
+    }
+
+    fn request_undo(&mut self, cmd: &Command) {
+        match cmd.get_object::<UndoRequest>() {
+            Ok(request) => self.undo.request(request.clone()),
+            Err(e) => log::warn!("record-undo object error: '{}'", e),
+        }
+    }
+
+    /// Step back, or forward, through the undo steps.
+    fn undo(&mut self, forward: bool) {
+        let changed = if forward {
+            self.undo.redo(&mut self.data)
+        } else {
+            self.undo.undo(&mut self.data)
+        };
+        if changed {
+            self.do_update();
+        }
+    }
+
+    /// Apply a change to the data requested by a menu item.
+    fn update_data(&mut self, cmd: &Command) {
+        match cmd.get_object::<DataUpdate<T>>() {
+            Ok(DataUpdate(update)) => {
+                update(&mut self.data);
+                self.do_update();
+            }
+            Err(e) => log::warn!("menu-update-data object error: '{}'", e),
+        }
+    }
+
+    fn set_progress(&mut self, window_id: WindowId, cmd: &Command) {
+        match cmd.get_object::<Option<f64>>() {
+            Ok(progress) => {
+                if let Some(win) = self.windows.get_mut(window_id) {
+                    win.handle.set_progress(*progress);
+                }
+            }
+            Err(e) => log::warn!("set-progress object error: '{}'", e),
+        }
+    }
+
+    fn set_badge(&mut self, window_id: WindowId, cmd: &Command) {
+        match cmd.get_object::<Option<String>>() {
+            Ok(badge) => {
+                if let Some(win) = self.windows.get_mut(window_id) {
+                    win.handle.set_badge(badge.as_deref());
+                }
+            }
+            Err(e) => log::warn!("set-badge object error: '{}'", e),
+        }
+    }
+
+    fn set_icon(&mut self, window_id: WindowId, cmd: &Command) {
+        match cmd.get_object::<Option<ImageData>>() {
+            Ok(image) => {
+                let icon = image.as_ref().map(ImageData::to_icon);
+                if let Some(win) = self.windows.get_mut(window_id) {
+                    win.handle.set_icon(icon.as_ref());
+                }
+            }
+            Err(e) => log::warn!("set-icon object error: '{}'", e),
+        }
+    }
+
+    /// Handle `SET_ALWAYS_ON_TOP`, `SET_SKIP_TASKBAR` or `SET_UTILITY`.
+    fn set_window_style(&mut self, window_id: WindowId, cmd: &Command) {
+        let on = match cmd.get_object::<bool>() {
+            Ok(on) => *on,
+            Err(e) => {
+                log::warn!("{} object error: '{}'", cmd.selector, e);
+                return;
+            }
+        };
+        if let Some(win) = self.windows.get_mut(window_id) {
+            match cmd.selector {
+                sys_cmd::SET_ALWAYS_ON_TOP => win.handle.set_always_on_top(on),
+                sys_cmd::SET_SKIP_TASKBAR => win.handle.set_skip_taskbar(on),
+                _ => win.handle.set_utility(on),
+            }
+        }
+    }
+
+    fn register_global_hotkey(&mut self, window_id: WindowId, cmd: &Command) {
+        let GlobalHotKey { hotkey, command } = match cmd.get_object::<GlobalHotKey>() {
+            Ok(hotkey) => hotkey,
+            Err(e) => {
+                log::warn!("register-global-hotkey object error: '{}'", e);
+                return;
+            }
+        };
+        if let Some(win) = self.windows.get_mut(window_id) {
+            if let Some(token) = win.handle.register_global_hotkey(hotkey) {
+                win.global_hotkeys.push((token, command.clone()));
+            }
+        }
+    }
+
+    fn unregister_global_hotkey(&mut self, window_id: WindowId, cmd: &Command) {
+        let selector = match cmd.get_object::<Selector>() {
+            Ok(selector) => selector,
+            Err(e) => {
+                log::warn!("unregister-global-hotkey object error: '{}'", e);
+                return;
+            }
+        };
+        if let Some(win) = self.windows.get_mut(window_id) {
+            let handle = &win.handle;
+            win.global_hotkeys.retain(|(token, command)| {
+                let remove = &command.selector == selector;
+                if remove {
+                    handle.unregister_global_hotkey(*token);
+                }
+                !remove
+            });
+        }
+    }
+
+    fn get_global_hotkey_cmd(
+        &self,
+        window_id: WindowId,
+        token: GlobalHotKeyToken,
+    ) -> Option<Command> {
+        self.windows.windows.get(&window_id).and_then(|w| {
+            w.global_hotkeys
+                .iter()
+                .find(|(t, _)| *t == token)
+                .map(|(_, command)| command.clone())
+        })
+    }
+
+    fn set_text_scale(&mut self, scale: f64) {
+        if self.env.get(theme::TEXT_SCALE).same(&scale) {
+            return;
+        }
+        theme::apply_text_scale(&mut self.env, scale);
+        self.do_update();
+    }
+
+    fn set_reduce_motion(&mut self, reduce: bool) {
+        if self.env.get(theme::REDUCE_MOTION) == reduce {
+            return;
+        }
+        self.env.set(theme::REDUCE_MOTION, reduce);
+        self.do_update();
+    }
+
     fn do_update(&mut self) {
+        #[cfg(feature = "time-travel")]
+        self.history.record(&self.data);
+
         // we send `update` to all windows, not just the active one:
         for window in self.windows.iter_mut() {
             window.update(&self.data, &self.env);
         }
+        #[cfg(feature = "persistence")]
+        self.schedule_save();
         self.invalidate_and_finalize();
     }
 
+    /// Start the timer for saving the data, if it has changed.
+    #[cfg(feature = "persistence")]
+    fn schedule_save(&mut self) {
+        let persistence = match self.persistence.as_mut() {
+            Some(persistence) => persistence,
+            None => return,
+        };
+        if let Some(deadline) = persistence.updated(&self.data, Instant::now()) {
+            // any window will do; if it closes, the data is saved then.
+            if let Some(window) = self.windows.iter_mut().next() {
+                persistence.timer = Some(window.handle.request_timer(deadline));
+            }
+        }
+    }
+
+    /// The timer for saving the data went off.
+    #[cfg(feature = "persistence")]
+    fn save_timer_fired(&mut self, window_id: WindowId) {
+        let persistence = match self.persistence.as_mut() {
+            Some(persistence) => persistence,
+            None => return,
+        };
+        if let Some(deadline) = persistence.timer_fired(&self.data, Instant::now()) {
+            if let Some(window) = self.windows.get_mut(window_id) {
+                persistence.timer = Some(window.handle.request_timer(deadline));
+            }
+        }
+    }
+
+    /// Save the data now, if it has changed.
+    fn save_data(&mut self) {
+        #[cfg(feature = "persistence")]
+        {
+            if let Some(persistence) = self.persistence.as_mut() {
+                persistence.save(&self.data);
+            }
+        }
+    }
+
     /// invalidate any window handles that need it.
     ///
     /// This should always be called at the end of an event update cycle,
@@ -326,14 +653,69 @@ impl<T: Data> AppState<T> {
         }
     }
 
-    #[cfg(target_os = "macos")]
+    /// Show an earlier or later state of the data.
+    #[cfg(feature = "time-travel")]
+    fn time_travel(&mut self, forward: bool) {
+        let data = if forward {
+            self.history.forward()
+        } else {
+            self.history.back()
+        };
+        if let Some(data) = data {
+            self.data = data;
+            self.do_update();
+        }
+    }
+
     fn window_got_focus(&mut self, window_id: WindowId) {
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(win) = self.windows.get_mut(window_id) {
+                win.macos_update_app_menu(&self.data, &self.env)
+            }
+        }
+        self.with_delegate(window_id, |del, data, env, ctx| {
+            del.window_got_focus(window_id, data, env, ctx)
+        });
+    }
+
+    /// A window moved to a screen with a different scale; paint it again,
+    /// and it will be laid out for the new scale.
+    fn window_scale_changed(&mut self, window_id: WindowId) {
         if let Some(win) = self.windows.get_mut(window_id) {
-            win.macos_update_app_menu(&self.data, &self.env)
+            win.handle.invalidate();
+        }
+    }
+
+    fn window_lost_focus(&mut self, window_id: WindowId) {
+        self.with_delegate(window_id, |del, data, env, ctx| {
+            del.window_lost_focus(window_id, data, env, ctx)
+        });
+    }
+
+    /// Ask the delegate whether we may quit.
+    fn quit_requested(&mut self, window_id: WindowId) -> bool {
+        self.with_delegate(window_id, |del, data, env, ctx| {
+            del.quit_requested(data, env, ctx)
+        })
+        .unwrap_or(true)
+    }
+
+    /// Pass files the platform asked us to open to the delegate, or, if
+    /// there isn't one, send the `OPEN_FILE` commands ourselves.
+    fn open_files(&mut self, window_id: WindowId, paths: Vec<PathBuf>) {
+        if self.delegate.is_some() {
+            self.with_delegate(window_id, |del, data, env, ctx| {
+                del.open_files(paths, data, env, ctx)
+            });
+        } else {
+            let mut ctx = DelegateCtx {
+                source_id: window_id,
+                command_queue: &mut self.command_queue,
+            };
+            app_delegate::submit_open_file_commands(paths, &mut ctx);
         }
     }
-    #[cfg(not(target_os = "macos"))]
-    fn window_got_focus(&mut self, _: WindowId) {}
 }
 
 impl<T: Data> DruidHandler<T> {
@@ -356,12 +738,100 @@ impl<T: Data> DruidHandler<T> {
     /// This is principally because in certain cases (such as keydown on Windows)
     /// the OS needs to know if an event was handled.
     fn do_event(&mut self, event: Event) -> bool {
+        if let Some(recorder) = self.app_state.borrow_mut().recorder.as_mut() {
+            recorder.record_event(&event);
+        }
+        self.app_state.borrow_mut().begin_edit(&event);
         let result = self.app_state.borrow_mut().do_event(self.window_id, event);
         self.process_commands();
-        self.app_state.borrow_mut().do_update();
+        let mut app_state = self.app_state.borrow_mut();
+        app_state.end_edit();
+        app_state.do_update();
         result
     }
 
+    /// Handle a request from the platform to open files.
+    ///
+    /// This isn't tied to a window, so commands the delegate sends without
+    /// a target go to an arbitrary one.
+    pub(crate) fn open_files(app_state: &Rc<RefCell<AppState<T>>>, paths: Vec<PathBuf>) {
+        let window_id = app_state
+            .borrow()
+            .windows
+            .windows
+            .keys()
+            .next()
+            .copied()
+            .unwrap_or_else(WindowId::next);
+        let mut handler = DruidHandler::new_shared(app_state.clone(), window_id);
+        app_state.borrow_mut().open_files(window_id, paths);
+        handler.process_commands();
+        app_state.borrow_mut().do_update();
+    }
+
+    /// Send a command that came from outside the widget tree, such as from
+    /// a menu, to this window.
+    fn submit_external_command(&mut self, cmd: Command) {
+        let mut app_state = self.app_state.borrow_mut();
+        if let Some(recorder) = app_state.recorder.as_mut() {
+            recorder.record_command(&cmd);
+        }
+        app_state
+            .command_queue
+            .push_back((self.window_id.into(), cmd));
+        drop(app_state);
+        self.process_commands();
+    }
+
+    /// Start replaying events into this window, if there is a replay that
+    /// hasn't started yet.
+    fn start_replay(&mut self) {
+        let mut app_state = self.app_state.borrow_mut();
+        let replay = match app_state.replay.as_mut() {
+            Some(replay) if replay.timer.is_none() => replay,
+            _ => return,
+        };
+        replay.start();
+        drop(app_state);
+        self.schedule_replay();
+    }
+
+    fn schedule_replay(&mut self) {
+        let mut app_state = self.app_state.borrow_mut();
+        let deadline = app_state.replay.as_ref().and_then(Replay::next_deadline);
+        let token = match (deadline, app_state.windows.get_mut(self.window_id)) {
+            (Some(deadline), Some(win)) => win.handle.request_timer(deadline),
+            _ => {
+                app_state.replay = None;
+                log::info!("finished replaying events");
+                return;
+            }
+        };
+        if let Some(replay) = app_state.replay.as_mut() {
+            replay.timer = Some(token);
+        }
+    }
+
+    /// Send the replayed events that are due.
+    fn replay_events(&mut self) {
+        loop {
+            let next = self
+                .app_state
+                .borrow_mut()
+                .replay
+                .as_mut()
+                .and_then(Replay::next_due);
+            match next {
+                Some(Replayed::Event(event)) => {
+                    self.do_event(event);
+                }
+                Some(Replayed::Command(cmd)) => self.submit_external_command(cmd),
+                None => break,
+            }
+        }
+        self.schedule_replay();
+    }
+
     fn process_commands(&mut self) {
         loop {
             let next_cmd = self.app_state.borrow_mut().command_queue.pop_front();
@@ -372,62 +842,134 @@ impl<T: Data> DruidHandler<T> {
         }
     }
 
+    /// Handle the commands that have arrived from other threads.
+    ///
+    /// They are handled as one batch with a single update at the end, so that
+    /// a burst of them doesn't cause an update and relayout for each. Commands
+    /// that are already waiting, which came from user input, go first; and the
+    /// commands each one causes are handled before moving on to the next.
     fn process_ext_events(&mut self) {
-        loop {
-            let ext_cmd = self.app_state.borrow_mut().ext_event_host.recv();
-            match ext_cmd {
-                Some((targ, cmd)) => {
-                    let targ = targ.unwrap_or_else(|| self.window_id.into());
-                    self.handle_cmd(targ, cmd);
-                }
-                None => break,
+        self.process_commands();
+        let batch = self.app_state.borrow_mut().ext_event_host.take_batch();
+        for (target, cmd) in batch {
+            match target {
+                Some(target) => self.handle_cmd(target, cmd),
+                None => self.submit_external_command(cmd),
             }
+            self.process_commands();
         }
-        self.app_state.borrow_mut().invalidate_and_finalize();
+        self.app_state.borrow_mut().do_update();
     }
 
     fn handle_system_cmd(&mut self, cmd_id: u32) {
         let cmd = self.app_state.borrow().get_menu_cmd(self.window_id, cmd_id);
         match cmd {
-            Some(cmd) => self
-                .app_state
-                .borrow_mut()
-                .command_queue
-                .push_back((self.window_id.into(), cmd)),
+            Some(cmd) => self.submit_external_command(cmd),
             None => warn!("No command for menu id {}", cmd_id),
         }
-        self.process_commands()
     }
 
     /// Handle a command. Top level commands (e.g. for creating and destroying windows)
     /// have their logic here; other commands are passed to the window.
     fn handle_cmd(&mut self, target: Target, cmd: Command) {
-        if let Target::Window(window_id) = target {
-            match &cmd.selector {
-                &sys_cmd::SHOW_OPEN_PANEL => self.show_open_panel(cmd, window_id),
-                &sys_cmd::SHOW_SAVE_PANEL => self.show_save_panel(cmd, window_id),
-                &sys_cmd::NEW_WINDOW => {
-                    if let Err(e) = self.new_window(cmd) {
-                        log::error!("failed to create window: '{}'", e);
-                    }
+        match target {
+            Target::Window(window_id) => {
+                if let Some(cmd) = self.handle_app_cmd(cmd) {
+                    self.handle_window_cmd(window_id, cmd);
                 }
-                &sys_cmd::CLOSE_WINDOW => self.request_close_window(cmd, window_id),
-                &sys_cmd::SHOW_WINDOW => self.show_window(cmd),
-                &sys_cmd::QUIT_APP => self.quit(),
-                &sys_cmd::HIDE_APPLICATION => self.hide_app(),
-                &sys_cmd::HIDE_OTHERS => self.hide_others(),
-                &sys_cmd::PASTE => self.do_paste(window_id),
-                sel => {
-                    info!("handle_cmd {}", sel);
+            }
+            Target::Global => {
+                if let Some(cmd) = self.handle_app_cmd(cmd) {
+                    info!("handle_cmd {} -> global", cmd.selector);
                     let event = Event::TargetedCommand(target, cmd);
-                    self.app_state.borrow_mut().do_event(window_id, event);
+                    self.app_state.borrow_mut().do_event(self.window_id, event);
                 }
             }
-        } else {
-            info!("handle_cmd {} -> widget", cmd.selector);
-            let event = Event::TargetedCommand(target, cmd);
-            // TODO: self.window_id the correct source identifier here?
-            self.app_state.borrow_mut().do_event(self.window_id, event);
+            _ => {
+                info!("handle_cmd {} -> widget", cmd.selector);
+                let event = Event::TargetedCommand(target, cmd);
+                // TODO: self.window_id the correct source identifier here?
+                self.app_state.borrow_mut().do_event(self.window_id, event);
+            }
+        }
+    }
+
+    /// Handle the commands that are for the app as a whole, whether they are
+    /// sent to a window or globally. Any other command is given back.
+    fn handle_app_cmd(&mut self, cmd: Command) -> Option<Command> {
+        match &cmd.selector {
+            &sys_cmd::NEW_WINDOW => {
+                if let Err(e) = self.new_window(cmd) {
+                    log::error!("failed to create window: '{}'", e);
+                }
+            }
+            &sys_cmd::SHOW_WINDOW => self.show_window(cmd),
+            &sys_cmd::QUIT_APP => self.quit(),
+            &sys_cmd::HIDE_APPLICATION => self.hide_app(),
+            &sys_cmd::HIDE_OTHERS => self.hide_others(),
+            &task::SPAWN_TASK => self.spawn_task(&cmd),
+            &undo::RECORD_UNDO => self.app_state.borrow_mut().request_undo(&cmd),
+            &sys_cmd::SET_LOCALE => self.app_state.borrow_mut().set_locale(&cmd),
+            &menu::UPDATE_DATA => self.app_state.borrow_mut().update_data(&cmd),
+            &sys_cmd::OPEN_URL => self.open_url(&cmd),
+            &sys_cmd::OPEN_PATH => self.open_path(&cmd, false),
+            &sys_cmd::REVEAL_PATH => self.open_path(&cmd, true),
+            &sys_cmd::ADD_RECENT_DOCUMENT | &sys_cmd::CLEAR_RECENT_DOCUMENTS => {
+                self.app_state.borrow_mut().change_recent_documents(&cmd)
+            }
+            #[cfg(feature = "time-travel")]
+            &sys_cmd::HISTORY_BACK => self.app_state.borrow_mut().time_travel(false),
+            #[cfg(feature = "time-travel")]
+            &sys_cmd::HISTORY_FORWARD => self.app_state.borrow_mut().time_travel(true),
+            _ => return Some(cmd),
+        }
+        None
+    }
+
+    /// Handle the commands that act on the window they are sent to.
+    fn handle_window_cmd(&mut self, window_id: WindowId, cmd: Command) {
+        match &cmd.selector {
+            &sys_cmd::SHOW_OPEN_PANEL => self.show_open_panel(cmd, window_id),
+            &sys_cmd::SHOW_SAVE_PANEL => self.show_save_panel(cmd, window_id),
+            &sys_cmd::CLOSE_WINDOW => self.request_close_window(cmd, window_id),
+            &sys_cmd::PASTE => self.do_paste(window_id),
+            // the focused widget, such as a text box with its own edit
+            // history, gets the first chance to undo
+            &sys_cmd::UNDO | &sys_cmd::REDO => {
+                let redo = cmd.selector == sys_cmd::REDO;
+                let event = Event::TargetedCommand(window_id.into(), cmd);
+                let handled = self.app_state.borrow_mut().do_event(window_id, event);
+                let mut app_state = self.app_state.borrow_mut();
+                let can_step = if redo {
+                    app_state.undo.can_redo()
+                } else {
+                    app_state.undo.can_undo()
+                };
+                if !handled && can_step {
+                    app_state.undo(redo);
+                }
+            }
+            &sys_cmd::SET_PROGRESS => self.app_state.borrow_mut().set_progress(window_id, &cmd),
+            &sys_cmd::SET_BADGE => self.app_state.borrow_mut().set_badge(window_id, &cmd),
+            &sys_cmd::SET_ICON => self.app_state.borrow_mut().set_icon(window_id, &cmd),
+            &sys_cmd::SET_ALWAYS_ON_TOP | &sys_cmd::SET_SKIP_TASKBAR | &sys_cmd::SET_UTILITY => {
+                self.app_state
+                    .borrow_mut()
+                    .set_window_style(window_id, &cmd)
+            }
+            &sys_cmd::REGISTER_GLOBAL_HOTKEY => self
+                .app_state
+                .borrow_mut()
+                .register_global_hotkey(window_id, &cmd),
+            &sys_cmd::UNREGISTER_GLOBAL_HOTKEY => self
+                .app_state
+                .borrow_mut()
+                .unregister_global_hotkey(window_id, &cmd),
+            sel => {
+                info!("handle_cmd {}", sel);
+                let event = Event::TargetedCommand(window_id.into(), cmd);
+                self.app_state.borrow_mut().do_event(window_id, event);
+            }
         }
     }
 
@@ -492,13 +1034,27 @@ impl<T: Data> DruidHandler<T> {
         self.app_state.borrow_mut().show_window(id);
     }
 
+    fn spawn_task(&mut self, cmd: &Command) {
+        match cmd.take_object::<Task>() {
+            Ok(task) => {
+                let mut state = self.app_state.borrow_mut();
+                let task = task.into_background(state.ext_event_host.make_sink());
+                state.executor.spawn(task);
+            }
+            Err(e) => log::warn!("spawn-task object error: '{}'", e),
+        }
+    }
+
     fn do_paste(&mut self, window_id: WindowId) {
         let event = Event::Paste(Application::clipboard());
         self.app_state.borrow_mut().do_event(window_id, event);
     }
 
-    fn quit(&self) {
-        Application::quit()
+    fn quit(&mut self) {
+        if self.app_state.borrow_mut().quit_requested(self.window_id) {
+            self.app_state.borrow_mut().save_data();
+            Application::quit()
+        }
     }
 
     fn hide_app(&self) {
@@ -510,6 +1066,35 @@ impl<T: Data> DruidHandler<T> {
         #[cfg(all(target_os = "macos", not(feature = "use_gtk")))]
         Application::hide_others()
     }
+
+    fn open_url(&mut self, cmd: &Command) {
+        match cmd.get_object::<String>() {
+            Ok(url) => {
+                if let Err(e) = Application::open_url(url) {
+                    log::warn!("failed to open '{}': {}", url, e);
+                }
+            }
+            Err(e) => log::warn!("open-url object error: '{}'", e),
+        }
+    }
+
+    fn open_path(&mut self, cmd: &Command, reveal: bool) {
+        let path = match cmd.get_object::<PathBuf>() {
+            Ok(path) => path,
+            Err(e) => {
+                log::warn!("{} object error: '{}'", cmd.selector, e);
+                return;
+            }
+        };
+        let result = if reveal {
+            Application::reveal_path(path)
+        } else {
+            Application::open_path(path)
+        };
+        if let Err(e) = result {
+            log::warn!("failed to open '{}': {}", path.display(), e);
+        }
+    }
 }
 
 impl<T: Data> WinHandler for DruidHandler<T> {
@@ -524,6 +1109,7 @@ impl<T: Data> WinHandler for DruidHandler<T> {
     fn connected(&mut self) {
         let event = Event::WindowConnected;
         self.do_event(event);
+        self.start_replay();
     }
 
     fn paint(&mut self, piet: &mut Piet) -> bool {
@@ -539,6 +1125,26 @@ impl<T: Data> WinHandler for DruidHandler<T> {
         self.handle_system_cmd(id);
     }
 
+    fn global_hotkey(&mut self, token: GlobalHotKeyToken) {
+        let cmd = self
+            .app_state
+            .borrow()
+            .get_global_hotkey_cmd(self.window_id, token);
+        if let Some(cmd) = cmd {
+            self.submit_external_command(cmd);
+        }
+    }
+
+    fn touch_bar(&mut self, id: u32, event: TouchBarEvent) {
+        let cmd = self
+            .app_state
+            .borrow()
+            .get_touch_bar_cmd(self.window_id, id, event);
+        if let Some(cmd) = cmd {
+            self.submit_external_command(cmd);
+        }
+    }
+
     fn mouse_down(&mut self, event: &MouseEvent) {
         // TODO: double-click detection (or is this done in druid-shell?)
         let event = Event::MouseDown(event.clone().into());
@@ -559,6 +1165,22 @@ impl<T: Data> WinHandler for DruidHandler<T> {
         self.do_event(Event::KeyDown(event))
     }
 
+    fn drag_moved(&mut self, pos: Point) {
+        self.do_event(Event::DragMoved(DragEvent { pos }));
+    }
+
+    fn drag_left(&mut self) {
+        self.do_event(Event::DragLeave);
+    }
+
+    fn dropped(&mut self, pos: Point, data: DragData) -> bool {
+        self.do_event(Event::Drop(DropEvent { pos, data }))
+    }
+
+    fn drag_ended(&mut self, action: Option<DropAction>) {
+        self.do_event(Event::DragEnded(action));
+    }
+
     fn key_up(&mut self, event: KeyEvent) {
         self.do_event(Event::KeyUp(event));
     }
@@ -575,9 +1197,38 @@ impl<T: Data> WinHandler for DruidHandler<T> {
 
     fn got_focus(&mut self) {
         self.app_state.borrow_mut().window_got_focus(self.window_id);
+        self.process_commands();
+        self.app_state.borrow_mut().do_update();
+    }
+
+    fn lost_focus(&mut self) {
+        self.app_state
+            .borrow_mut()
+            .window_lost_focus(self.window_id);
+        self.process_commands();
+        self.app_state.borrow_mut().do_update();
     }
 
     fn timer(&mut self, token: TimerToken) {
+        let replay_timer = self
+            .app_state
+            .borrow()
+            .replay
+            .as_ref()
+            .and_then(|r| r.timer);
+        if replay_timer == Some(token) {
+            self.replay_events();
+            return;
+        }
+        #[cfg(feature = "persistence")]
+        {
+            let mut app_state = self.app_state.borrow_mut();
+            let save_timer = app_state.persistence.as_ref().and_then(|p| p.timer);
+            if save_timer == Some(token) {
+                app_state.save_timer_fired(self.window_id);
+                return;
+            }
+        }
         self.do_event(Event::Timer(token));
     }
 
@@ -585,13 +1236,33 @@ impl<T: Data> WinHandler for DruidHandler<T> {
         match token {
             RUN_COMMANDS_TOKEN => {
                 self.process_commands();
-                self.app_state.borrow_mut().invalidate_and_finalize();
+                // the commands can change the data, like those after an
+                // event, so the widgets need an update and not only a repaint
+                self.app_state.borrow_mut().do_update();
             }
             EXT_EVENT_IDLE_TOKEN => self.process_ext_events(),
             other => log::warn!("unexpected idle token {:?}", other),
         }
     }
 
+    fn text_scale_changed(&mut self, scale: f64) {
+        self.app_state.borrow_mut().set_text_scale(scale);
+    }
+
+    fn reduce_motion_changed(&mut self, reduce: bool) {
+        self.app_state.borrow_mut().set_reduce_motion(reduce);
+    }
+
+    fn monitors_changed(&mut self) {
+        self.do_event(Event::Command(sys_cmd::MONITORS_CHANGED.into()));
+    }
+
+    fn scale_changed(&mut self, _scale: f64) {
+        self.app_state
+            .borrow_mut()
+            .window_scale_changed(self.window_id);
+    }
+
     fn as_any(&mut self) -> &mut dyn Any {
         self
     }
@@ -15,17 +15,30 @@
 //! Management of multiple windows.
 
 use std::mem;
+use std::path::PathBuf;
+use std::rc::Rc;
 use std::time::Instant;
 
 use crate::kurbo::{Insets, Point, Rect, Size};
 use crate::piet::{Piet, RenderContext};
-use crate::shell::{Counter, Cursor, WindowHandle};
+use crate::shell::{
+    Counter, Cursor, GlobalHotKeyToken, HotKey, KeyCode, RawMods, SysMods, TouchBarEvent,
+    WindowHandle,
+};
 
+use crate::command::sys as sys_cmd;
 use crate::core::{BaseState, CommandQueue, FocusChange};
+use crate::inspector::{Inspector, TreeCollector, WidgetTree};
+use crate::menu::{MenuFn, MenuItemUpdate};
+use crate::perf::{Pass, PerfHud};
+use crate::testing::HarnessHost;
+use crate::trace::{self, TracePass};
+use crate::widget::image_cache::WindowImages;
 use crate::win_handler::RUN_COMMANDS_TOKEN;
 use crate::{
     BoxConstraints, Command, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx,
-    LocalizedString, MenuDesc, PaintCtx, UpdateCtx, Widget, WidgetId, WidgetPod,
+    LocalizedString, MenuDesc, PaintCtx, Target, TouchBarDesc, UpdateCtx, Widget, WidgetId,
+    WidgetPod,
 };
 
 /// A unique identifier for a window.
@@ -37,6 +50,10 @@ pub(crate) struct PendingWindow<T> {
     root: WidgetPod<T, Box<dyn Widget<T>>>,
     title: LocalizedString<T>,
     menu: Option<MenuDesc<T>>,
+    pub(crate) menu_fn: Option<Box<MenuFn<T>>>,
+    /// The data `menu` was built from by `menu_fn`.
+    pub(crate) menu_data: Option<T>,
+    pub(crate) touch_bar: Option<TouchBarDesc<T>>,
 }
 
 /// Per-window state not owned by user code.
@@ -45,11 +62,31 @@ pub struct Window<T> {
     pub(crate) root: WidgetPod<T, Box<dyn Widget<T>>>,
     pub(crate) title: LocalizedString<T>,
     size: Size,
+    /// The window's scale when it was last laid out.
+    scale: f64,
+    images: WindowImages,
     pub(crate) menu: Option<MenuDesc<T>>,
+    /// If set, `menu` is rebuilt from this whenever the data changes.
+    pub(crate) menu_fn: Option<Box<MenuFn<T>>>,
+    /// The data `menu` was last built from by `menu_fn`.
+    menu_data: Option<T>,
     pub(crate) context_menu: Option<MenuDesc<T>>,
+    /// A context menu that doesn't depend on the data, shown by widgets that
+    /// don't know its type.
+    plain_context_menu: Option<MenuDesc<()>>,
+    pub(crate) touch_bar: Option<TouchBarDesc<T>>,
+    /// Present while the inspector is shown.
+    inspector: Option<Inspector>,
+    /// Present while the performance overlay is shown.
+    perf_hud: Option<PerfHud>,
     pub(crate) last_anim: Option<Instant>,
     pub(crate) focus: Option<WidgetId>,
     pub(crate) handle: WindowHandle,
+    /// The commands of the global hotkeys registered for this window.
+    pub(crate) global_hotkeys: Vec<(GlobalHotKeyToken, Command)>,
+    /// Present when the window is run by a test harness instead of the
+    /// platform.
+    pub(crate) harness: Option<Rc<HarnessHost>>,
     // delegate?
 }
 
@@ -63,21 +100,41 @@ impl<T> PendingWindow<T> {
             root: WidgetPod::new(Box::new(root)),
             title,
             menu,
+            menu_fn: None,
+            menu_data: None,
+            touch_bar: None,
         }
     }
 
     pub(crate) fn into_window(self, id: WindowId, handle: WindowHandle) -> Window<T> {
-        let PendingWindow { root, title, menu } = self;
+        let PendingWindow {
+            root,
+            title,
+            menu,
+            menu_fn,
+            menu_data,
+            touch_bar,
+        } = self;
         Window {
             id,
             root,
             size: Size::ZERO,
+            scale: 1.0,
+            images: WindowImages::default(),
             title,
             menu,
+            menu_fn,
+            menu_data,
             context_menu: None,
+            plain_context_menu: None,
+            touch_bar,
+            inspector: None,
+            perf_hud: None,
             last_anim: None,
             focus: None,
             handle,
+            global_hotkeys: Vec::new(),
+            harness: None,
         }
     }
 }
@@ -98,6 +155,48 @@ impl<T: Data> Window<T> {
         self.menu = Some(menu);
     }
 
+    pub(crate) fn set_touch_bar(&mut self, mut touch_bar: TouchBarDesc<T>, data: &T, env: &Env) {
+        self.handle.set_touch_bar(touch_bar.build_native(data, env));
+        self.touch_bar = Some(touch_bar);
+    }
+
+    /// Change some menu items in place.
+    pub(crate) fn update_menu_item(&mut self, update: &MenuItemUpdate<T>, data: &T, env: &Env) {
+        if let Some(menu) = self.menu.as_mut() {
+            menu.apply_update(update, data, env, &self.handle);
+        }
+    }
+
+    /// Rebuild the platform menu, for instance after the locale has changed.
+    pub(crate) fn update_menu(&mut self, data: &T, env: &Env) {
+        if let Some(menu) = self.menu.as_mut() {
+            let platform_menu = menu.build_window_menu(data, env);
+            self.handle.set_menu(platform_menu);
+        }
+    }
+
+    /// Rebuild the menu if it depends on the data, and the data has changed
+    /// since it was last built.
+    fn update_menu_from_data(&mut self, data: &T, env: &Env) {
+        if self.menu_data.as_ref().map(|old| old.same(data)) == Some(true) {
+            return;
+        }
+        if let Some(menu_fn) = self.menu_fn.as_ref() {
+            let menu = menu_fn(data, env);
+            self.menu_data = Some(data.clone());
+            self.set_menu(menu, data, env);
+        } else if self.menu.as_ref().map(MenuDesc::depends_on_data) == Some(true) {
+            self.menu_data = Some(data.clone());
+            self.update_menu(data, env);
+        }
+    }
+
+    pub(crate) fn get_touch_bar_cmd(&self, id: u32, event: TouchBarEvent) -> Option<Command> {
+        self.touch_bar
+            .as_ref()
+            .and_then(|touch_bar| touch_bar.command_for_event(id, event))
+    }
+
     pub(crate) fn show_context_menu(
         &mut self,
         mut menu: MenuDesc<T>,
@@ -108,6 +207,19 @@ impl<T: Data> Window<T> {
         let platform_menu = menu.build_popup_menu(data, env);
         self.handle.show_context_menu(platform_menu, point);
         self.context_menu = Some(menu);
+        self.plain_context_menu = None;
+    }
+
+    pub(crate) fn show_plain_context_menu(
+        &mut self,
+        mut menu: MenuDesc<()>,
+        point: Point,
+        env: &Env,
+    ) {
+        let platform_menu = menu.build_popup_menu(&(), env);
+        self.handle.show_context_menu(platform_menu, point);
+        self.plain_context_menu = Some(menu);
+        self.context_menu = None;
     }
 
     /// On macos we need to update the global application menu to be the menu
@@ -145,6 +257,28 @@ impl<T: Data> Window<T> {
             self.lifecycle(queue, &LifeCycle::WidgetAdded, data, env);
         }
 
+        if let Event::TargetedCommand(Target::Window(_), cmd) = &event {
+            if cmd.selector == sys_cmd::DUMP_WIDGET_TREE {
+                self.dump_widget_tree(cmd, queue, data, env);
+                return true;
+            }
+            if cmd.selector == sys_cmd::TOGGLE_PERF_HUD {
+                self.perf_hud = match self.perf_hud {
+                    Some(_) => None,
+                    None => Some(PerfHud::new()),
+                };
+                self.handle.invalidate();
+                return true;
+            }
+        }
+
+        if self.inspector_event(&event) {
+            return true;
+        }
+
+        let _span = trace::window(TracePass::Event, self.id);
+        let start = Instant::now();
+
         let mut base_state = BaseState::new(self.root.id());
         let is_handled = {
             let mut ctx = EventCtx {
@@ -157,12 +291,44 @@ impl<T: Data> Window<T> {
                 window: &self.handle,
                 window_id: self.id,
                 focus_widget: self.focus,
+                harness: self.harness.as_deref(),
             };
 
             self.root.event(&mut ctx, &event, data, env);
             ctx.is_handled
         };
 
+        // Tab and shift+tab move focus, unless a widget has already dealt with them.
+        if let Event::KeyDown(key) = &event {
+            if !is_handled && base_state.request_focus.is_none() {
+                if HotKey::new(None, KeyCode::Tab).matches(key) {
+                    base_state.request_focus = Some(FocusChange::Next);
+                } else if HotKey::new(RawMods::Shift, KeyCode::Tab).matches(key) {
+                    base_state.request_focus = Some(FocusChange::Previous);
+                }
+            }
+            if !is_handled && key.key_code == KeyCode::Escape {
+                queue.push_back((self.id.into(), sys_cmd::DISMISS_POPUPS.into()));
+            }
+            // the menu, if it has undo and redo items, sees these shortcuts
+            // first; this is for windows without them.
+            if !is_handled {
+                let redo = if cfg!(target_os = "windows") {
+                    HotKey::new(RawMods::Ctrl, "y")
+                } else {
+                    HotKey::new(SysMods::CmdShift, "z")
+                };
+                if HotKey::new(SysMods::Cmd, "z").matches(key) {
+                    queue.push_back((self.id.into(), sys_cmd::UNDO.into()));
+                } else if redo.matches(key) {
+                    queue.push_back((self.id.into(), sys_cmd::REDO.into()));
+                } else if cfg!(debug_assertions) && HotKey::new(SysMods::CmdShift, "i").matches(key)
+                {
+                    queue.push_back((self.id.into(), sys_cmd::TOGGLE_INSPECTOR.into()));
+                }
+            }
+        }
+
         if let Some(focus_req) = base_state.request_focus.take() {
             let old = self.focus;
             let new = self.widget_for_focus_request(focus_req);
@@ -181,9 +347,81 @@ impl<T: Data> Window<T> {
             self.lifecycle(queue, &LifeCycle::WidgetAdded, data, env);
         }
 
+        if let Some(perf_hud) = self.perf_hud.as_mut() {
+            perf_hud.add_time(Pass::Event, start.elapsed());
+        }
+
         is_handled
     }
 
+    /// Let the inspector see the event first. Returns `true` if it handled
+    /// the event, which should then not be passed on to the widgets.
+    fn inspector_event(&mut self, event: &Event) -> bool {
+        let toggle = match event {
+            Event::TargetedCommand(Target::Window(_), cmd) => {
+                cmd.selector == sys_cmd::TOGGLE_INSPECTOR
+            }
+            Event::KeyDown(key) => self.inspector.is_some() && key.key_code == KeyCode::Escape,
+            _ => false,
+        };
+        if toggle {
+            self.inspector = match self.inspector {
+                Some(_) => None,
+                None => Some(Inspector::default()),
+            };
+            self.handle.invalidate();
+            return true;
+        }
+
+        let inspector = match self.inspector.as_mut() {
+            Some(inspector) => inspector,
+            None => return false,
+        };
+        match event {
+            // mouse events still reach the widgets, so hot state is shown
+            Event::MouseMoved(mouse) => {
+                if inspector.mouse_moved(mouse.pos) {
+                    self.handle.invalidate();
+                }
+                false
+            }
+            Event::KeyDown(key) => {
+                let handled = inspector.key_down(key);
+                if handled {
+                    self.handle.invalidate();
+                }
+                handled
+            }
+            _ => false,
+        }
+    }
+
+    /// Describe the widgets in the window.
+    pub(crate) fn widget_tree(
+        &mut self,
+        queue: &mut CommandQueue,
+        data: &T,
+        env: &Env,
+    ) -> WidgetTree {
+        let collector = TreeCollector::default();
+        let event = LifeCycle::DebugCollectTree(collector.clone());
+        self.lifecycle(queue, &event, data, env);
+        collector.tree(self.focus)
+    }
+
+    /// Log the widget tree, or write it as JSON to the path in the command.
+    fn dump_widget_tree(&mut self, cmd: &Command, queue: &mut CommandQueue, data: &T, env: &Env) {
+        let tree = self.widget_tree(queue, data, env);
+        match cmd.get_object::<PathBuf>() {
+            Ok(path) => {
+                if let Err(e) = std::fs::write(path, tree.to_json()) {
+                    log::error!("failed to write widget tree to '{}': {}", path.display(), e);
+                }
+            }
+            Err(_) => log::info!("widget tree of window {:?}:\n{}", self.id, tree),
+        }
+    }
+
     pub(crate) fn lifecycle(
         &mut self,
         queue: &mut CommandQueue,
@@ -191,6 +429,7 @@ impl<T: Data> Window<T> {
         data: &T,
         env: &Env,
     ) {
+        let _span = trace::window(TracePass::LifeCycle, self.id);
         let mut base_state = BaseState::new(self.root.id());
         let mut ctx = LifeCycleCtx {
             command_queue: queue,
@@ -223,7 +462,10 @@ impl<T: Data> Window<T> {
     }
 
     pub(crate) fn update(&mut self, data: &T, env: &Env) {
+        let _span = trace::window(TracePass::Update, self.id);
+        let start = Instant::now();
         self.update_title(data, env);
+        self.update_menu_from_data(data, env);
 
         let mut base_state = BaseState::new(self.root.id());
         let mut update_ctx = UpdateCtx {
@@ -233,6 +475,10 @@ impl<T: Data> Window<T> {
         };
 
         self.root.update(&mut update_ctx, data, env);
+
+        if let Some(perf_hud) = self.perf_hud.as_mut() {
+            perf_hud.add_time(Pass::Update, start.elapsed());
+        }
     }
 
     pub(crate) fn invalidate_and_finalize(
@@ -261,12 +507,29 @@ impl<T: Data> Window<T> {
         // FIXME: only do AnimFrame if root has requested_anim?
         self.lifecycle(queue, &LifeCycle::AnimFrame(0), data, env);
 
-        if self.root.state().needs_layout {
+        if self.root.state().needs_layout || !self.handle.get_scale().same(&self.scale) {
             self.layout(piet, data, env);
         }
 
+        let start = Instant::now();
         piet.clear(env.get(crate::theme::WINDOW_BACKGROUND_COLOR));
-        self.paint(piet, data, env);
+        let widgets_painted = self.paint(piet, data, env);
+        if let Some(perf_hud) = self.perf_hud.as_mut() {
+            perf_hud.add_time(Pass::Paint, start.elapsed());
+            perf_hud.end_frame(widgets_painted);
+        }
+
+        if self.inspector.is_some() {
+            let tree = self.widget_tree(queue, data, env);
+            if let Some(inspector) = self.inspector.as_mut() {
+                inspector.set_tree(tree);
+                inspector.paint(piet, self.size, env);
+            }
+        }
+
+        if let Some(perf_hud) = self.perf_hud.as_ref() {
+            perf_hud.paint(piet, self.size, env);
+        }
 
         // If commands were submitted during anim frame, ask the handler
         // to call us back on idle so we can process them in a new event/update pass.
@@ -280,25 +543,37 @@ impl<T: Data> Window<T> {
     }
 
     fn layout(&mut self, piet: &mut Piet, data: &T, env: &Env) {
+        let _span = trace::window(TracePass::Layout, self.id);
+        let start = Instant::now();
+        self.scale = self.handle.get_scale();
         let mut layout_ctx = LayoutCtx {
             text_factory: piet.text(),
             window_id: self.id,
             paint_insets: Insets::ZERO,
+            baseline_offset: 0.0,
+            scale: self.scale,
         };
         let bc = BoxConstraints::tight(self.size);
         let size = self.root.layout(&mut layout_ctx, &bc, data, env);
         self.root
             .set_layout_rect(Rect::from_origin_size(Point::ORIGIN, size));
+
+        if let Some(perf_hud) = self.perf_hud.as_mut() {
+            perf_hud.add_time(Pass::Layout, start.elapsed());
+        }
     }
 
     /// only expose `layout` for testing; normally it is called as part of `do_paint`
-    #[cfg(test)]
     pub(crate) fn just_layout(&mut self, piet: &mut Piet, data: &T, env: &Env) {
         self.layout(piet, data, env)
     }
 
-    fn paint(&mut self, piet: &mut Piet, data: &T, env: &Env) {
+    /// Paint the widgets, returning how many were painted.
+    fn paint(&mut self, piet: &mut Piet, data: &T, env: &Env) -> usize {
+        let _span = trace::window(TracePass::Paint, self.id);
         let base_state = BaseState::new(self.root.id());
+        let mut images = mem::take(&mut self.images);
+        let harness = self.harness.clone();
         let mut paint_ctx = PaintCtx {
             render_ctx: piet,
             base_state: &base_state,
@@ -306,6 +581,10 @@ impl<T: Data> Window<T> {
             z_ops: Vec::new(),
             focus_widget: self.focus,
             region: Rect::ZERO.into(),
+            widgets_painted: 0,
+            scale: self.scale,
+            images: &mut images,
+            harness: harness.as_deref(),
         };
         let visible = Rect::from_origin_size(Point::ZERO, self.size);
         paint_ctx.with_child_ctx(visible, |ctx| self.root.paint(ctx, data, env));
@@ -328,6 +607,9 @@ impl<T: Data> Window<T> {
                 }
             });
         }
+        let widgets_painted = paint_ctx.widgets_painted;
+        self.images = images;
+        widgets_painted
     }
 
     pub(crate) fn update_title(&mut self, data: &T, env: &Env) {
@@ -340,28 +622,32 @@ impl<T: Data> Window<T> {
         self.context_menu
             .as_ref()
             .and_then(|m| m.command_for_id(cmd_id))
+            .or_else(|| {
+                self.plain_context_menu
+                    .as_ref()
+                    .and_then(|m| m.command_for_id(cmd_id))
+            })
             .or_else(|| self.menu.as_ref().and_then(|m| m.command_for_id(cmd_id)))
     }
 
     fn widget_for_focus_request(&self, focus: FocusChange) -> Option<WidgetId> {
+        let chain = self.focus_chain();
+        // if nothing is focused, `Next` starts at the front of the chain and
+        // `Previous` at the back.
+        let current = self
+            .focus
+            .and_then(|id| chain.iter().position(|i| i == &id));
         match focus {
             FocusChange::Resign => None,
             FocusChange::Focus(id) => Some(id),
-            FocusChange::Next => self
-                .focus
-                .and_then(|id| self.focus_chain().iter().position(|i| i == &id))
-                .map(|idx| {
-                    let next_idx = (idx + 1) % self.focus_chain().len();
-                    self.focus_chain()[next_idx]
-                }),
-            FocusChange::Previous => self
-                .focus
-                .and_then(|id| self.focus_chain().iter().position(|i| i == &id))
-                .map(|idx| {
-                    let len = self.focus_chain().len();
-                    let prev_idx = (idx + len - 1) % len;
-                    self.focus_chain()[prev_idx]
-                }),
+            FocusChange::Next => match current {
+                Some(idx) => Some(chain[(idx + 1) % chain.len()]),
+                None => chain.first().copied(),
+            },
+            FocusChange::Previous => match current {
+                Some(idx) => Some(chain[(idx + chain.len() - 1) % chain.len()]),
+                None => chain.last().copied(),
+            },
         }
     }
 }